@@ -3,7 +3,7 @@ use winit::{
     event::{DeviceEvent, ElementState, Event, KeyEvent, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
     keyboard::{Key, ModifiersState},
-    window::WindowBuilder,
+    window::{CursorGrabMode, WindowBuilder},
 };
 
 fn main() {
@@ -37,7 +37,14 @@ fn main() {
                     match key {
                         Key::Escape => *control_flow = ControlFlow::Exit,
                         Key::Character(ch) => match ch.to_lowercase().as_str() {
-                            "g" => window.set_cursor_grab(!modifiers.shift_key()).unwrap(),
+                            "g" => {
+                                let mode = match modifiers.shift_key() {
+                                    true => CursorGrabMode::None,
+                                    false => CursorGrabMode::Confined,
+                                };
+                                window.set_cursor_grab(mode).unwrap();
+                            }
+                            "l" => window.set_cursor_grab(CursorGrabMode::Locked).unwrap(),
                             "h" => window.set_cursor_visible(modifiers.shift_key()),
                             _ => (),
                         },
@@ -8,7 +8,7 @@ fn main() {
         event::{ElementState, Event, KeyEvent, WindowEvent},
         event_loop::{ControlFlow, EventLoop},
         keyboard::{Key, ModifiersState},
-        window::{CursorIcon, Fullscreen, WindowBuilder},
+        window::{CursorGrabMode, CursorIcon, Fullscreen, WindowBuilder},
     };
 
     const WINDOW_COUNT: usize = 3;
@@ -83,7 +83,12 @@ fn main() {
                                     )),
                                     (false, _) => None,
                                 }),
-                                "g" => window.set_cursor_grab(state).unwrap(),
+                                "g" => window
+                                    .set_cursor_grab(match state {
+                                        true => CursorGrabMode::Confined,
+                                        false => CursorGrabMode::None,
+                                    })
+                                    .unwrap(),
                                 "h" => window.set_cursor_visible(!state),
                                 "i" => {
                                     println!("Info:");
@@ -12,6 +12,7 @@ fn main() {
 
     let window = WindowBuilder::new().build(&event_loop).unwrap();
     window.set_title("A fantastic window!");
+    window.set_ime_allowed(true);
 
     println!("Ime position will system default");
     println!("Click to set ime position to cursor's");
@@ -41,6 +42,12 @@ fn main() {
                 );
                 window.set_ime_position(cursor_position);
             }
+            Event::WindowEvent {
+                event: WindowEvent::Ime(event),
+                ..
+            } => {
+                println!("{:?}", event);
+            }
             Event::WindowEvent {
                 event: WindowEvent::CloseRequested,
                 ..
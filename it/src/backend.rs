@@ -11,11 +11,12 @@ use winit::event::DeviceId;
 use winit::event_loop::EventLoop as WEventLoop;
 use winit::monitor::MonitorHandle;
 use winit::window::{
-    CursorIcon, Fullscreen, Icon, UserAttentionType, Window as WWindow, WindowBuilder, WindowId,
+    CursorGrabMode, CursorIcon, CustomCursor, DamageRect, Fullscreen, Icon, UserAttentionType,
+    Window as WWindow, WindowBuilder, WindowId,
 };
 
 bitflags::bitflags! {
-    pub struct BackendFlags: u32 {
+    pub struct BackendFlags: u64 {
         const MT_SAFE = 1 << 0;
         const WINIT_SET_ALWAYS_ON_TOP = 1 << 1;
         const WINIT_SET_DECORATIONS = 1 << 2;
@@ -41,6 +42,18 @@ bitflags::bitflags! {
         const SINGLE_THREADED = 1 << 22;
         const WINIT_SET_CURSOR_POSITION = 1 << 23;
         const MANUAL_VERIFICATION = 1 << 24;
+        const CLIPBOARD = 1 << 25;
+        const IME = 1 << 26;
+        const DND_DATA = 1 << 27;
+        const DND_SOURCE = 1 << 28;
+        const CUSTOM_CURSOR = 1 << 29;
+        const GESTURES = 1 << 30;
+        const PEN = 1 << 31;
+        const DND = 1 << 32;
+        const WINIT_RESET_DEAD_KEYS = 1 << 33;
+        const SCALED_FULLSCREEN = 1 << 34;
+        const PRESENT = 1 << 35;
+        const HOVER_MODIFIERS = 1 << 36;
     }
 }
 
@@ -60,8 +73,33 @@ pub trait Instance {
     fn create_event_loop(&self) -> Box<dyn EventLoop>;
     fn take_screenshot(&self);
     fn before_poll(&self);
-    fn create_dnd_path(&self, file: &str) -> PathBuf;
-    fn start_dnd_process(&self, path: &Path) -> Box<dyn DndProcess>;
+    /// Only used by tests that require `BackendFlags::DND`.
+    fn create_dnd_path(&self, file: &str) -> PathBuf {
+        let _ = file;
+        unimplemented!();
+    }
+    /// Only used by tests that require `BackendFlags::DND`.
+    fn start_dnd_process(&self, path: &Path) -> Box<dyn DndProcess> {
+        let _ = path;
+        unimplemented!();
+    }
+    /// Starts a drag that offers `data` under the given MIME types.
+    fn start_dnd_data_process(&self, offers: &[(&str, &[u8])]) -> Box<dyn DndProcess> {
+        let _ = offers;
+        unimplemented!();
+    }
+    /// Creates a window at `x`x`y` that accepts drops of data offered as `mime_type`.
+    fn start_dnd_target_process(
+        &self,
+        mime_type: &str,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    ) -> Box<dyn DndTargetProcess> {
+        let _ = (mime_type, x, y, width, height);
+        unimplemented!();
+    }
     fn redraw_requested_scenarios(&self) -> usize;
     fn cursor_grabbed<'a>(&'a self, grab: bool) -> Pin<Box<dyn Future<Output = ()> + 'a>> {
         let _ = grab;
@@ -74,6 +112,12 @@ pub trait Instance {
         let _ = enabled;
         unimplemented!();
     }
+    /// Starts an input method that commits `text` when `key` is pressed and passes all other
+    /// keys through.
+    fn start_ime_process(&self, key: Key, text: &str) -> Box<dyn ImeProcess> {
+        let _ = (key, text);
+        unimplemented!();
+    }
 }
 
 pub trait DndProcess {
@@ -81,6 +125,16 @@ pub trait DndProcess {
     fn do_drop(&self);
 }
 
+pub trait DndTargetProcess {
+    /// Waits until data has been dropped on the window and returns it.
+    fn dropped<'a>(&'a self) -> Pin<Box<dyn Future<Output = Vec<u8>> + 'a>>;
+}
+
+pub trait ImeProcess {
+    /// Waits until the spot location of the input context is `x`x`y`.
+    fn spot<'a>(&'a self, x: i32, y: i32) -> Pin<Box<dyn Future<Output = ()> + 'a>>;
+}
+
 pub trait EventLoop {
     fn events(&self) -> Box<dyn EventStream>;
     fn changed<'a>(&'a self) -> Pin<Box<dyn Future<Output = ()> + 'a>>;
@@ -149,6 +203,14 @@ pub trait WindowProperties {
     fn supports_transparency(&self) -> bool;
     fn dragging(&self) -> bool;
     fn fullscreen(&self) -> bool;
+    /// The size to which the contents of the window are scaled.
+    fn viewport(&self) -> Option<(u32, u32)> {
+        unimplemented!();
+    }
+    /// The number of frames that the display server has committed to the window.
+    fn frames(&self) -> u64 {
+        unimplemented!();
+    }
     fn class(&self) -> Option<String> {
         unimplemented!();
     }
@@ -165,6 +227,8 @@ pub trait Window {
     fn properties_changed<'a>(&'a self) -> Pin<Box<dyn Future<Output = ()> + 'a>>;
     fn properties(&self) -> &dyn WindowProperties;
     fn set_background_color(&self, r: u8, g: u8, b: u8);
+    /// The RGB color of a pixel of the window contents.
+    fn pixel(&self, x: u32, y: u32) -> (u8, u8, u8);
     fn any(&self) -> &dyn Any;
     fn delete(&self);
     /// left, right, top, bottom
@@ -198,9 +262,9 @@ impl dyn Window {
         self.winit().set_fullscreen(fs);
     }
 
-    pub fn winit_set_cursor_grab(&self, grab: bool) {
-        log::info!("Setting cursor grab of window {} to {}", self.id(), grab);
-        self.winit().set_cursor_grab(grab).unwrap();
+    pub fn winit_set_cursor_grab(&self, mode: CursorGrabMode) {
+        log::info!("Setting cursor grab of window {} to {:?}", self.id(), mode);
+        self.winit().set_cursor_grab(mode).unwrap();
     }
 
     pub fn winit_set_cursor_icon(&self, icon: CursorIcon) {
@@ -208,6 +272,24 @@ impl dyn Window {
         self.winit().set_cursor_icon(icon);
     }
 
+    pub fn winit_set_custom_cursor(&self, cursor: &CustomCursor) {
+        log::info!("Setting custom cursor of window {}", self.id());
+        self.winit().set_custom_cursor(cursor).unwrap();
+    }
+
+    pub fn winit_present_rgba(&self, rgba: &[u8], width: u32, height: u32, damage: &[DamageRect]) {
+        log::info!(
+            "Presenting a {}x{} frame in window {} with damage {:?}",
+            width,
+            height,
+            self.id(),
+            damage
+        );
+        self.winit()
+            .present_rgba(rgba, width, height, damage)
+            .unwrap();
+    }
+
     pub fn winit_set_cursor_visible(&self, visible: bool) {
         log::info!(
             "Setting cursor visible of window {} to {:?}",
@@ -445,6 +527,24 @@ impl dyn Window {
         self.await_property(|p| p.max_size() == size).await
     }
 
+    pub async fn viewport(&self, size: Option<(u32, u32)>) {
+        log::info!(
+            "Waiting for window {} to become viewport {:?}",
+            self.id(),
+            size
+        );
+        self.await_property(|p| p.viewport() == size).await
+    }
+
+    pub async fn frames(&self, frames: u64) {
+        log::info!(
+            "Waiting for window {} to have presented {} frames",
+            self.id(),
+            frames
+        );
+        self.await_property(|p| p.frames() >= frames).await
+    }
+
     pub async fn attention(&self, attention: bool) {
         log::info!(
             "Waiting for window {} to become attention {:?}",
@@ -543,6 +643,15 @@ pub trait Seat {
     fn add_keyboard(&self) -> Box<dyn Keyboard>;
     fn add_mouse(&self) -> Box<dyn Mouse>;
     fn add_touchscreen(&self) -> Box<dyn Touchscreen>;
+    /// Only used by tests that require `BackendFlags::GESTURES`.
+    fn add_touchpad(&self) -> Box<dyn Touchpad> {
+        unimplemented!();
+    }
+    /// Only used by tests that require `BackendFlags::PEN`.
+    fn add_tablet(&self, tool: TabletTool) -> Box<dyn Tablet> {
+        let _ = tool;
+        unimplemented!();
+    }
     fn focus(&self, window: &dyn Window);
     fn un_focus(&self);
     fn set_layout(&self, layout: Layout);
@@ -589,3 +698,44 @@ pub trait Touchscreen: Device {
 pub trait Finger {
     fn move_(&self, x: i32, y: i32);
 }
+
+pub trait Touchpad: Device {
+    /// Scrolls with two fingers by `dx`x`dy` pixels.
+    fn scroll(&self, dx: i32, dy: i32);
+    fn pinch(&self, fingers: u32) -> Box<dyn PinchGesture>;
+    fn swipe(&self, fingers: u32) -> Box<dyn SwipeGesture>;
+}
+
+/// A pinch gesture that ends when it is dropped.
+pub trait PinchGesture {
+    /// `scale` is relative to the start of the gesture and `rotation` is in degrees clockwise.
+    fn update(&self, dx: f64, dy: f64, scale: f64, rotation: f64);
+    /// Makes the gesture end as cancelled when it is dropped.
+    fn cancel(&self);
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TabletTool {
+    Pen,
+    Eraser,
+}
+
+/// A drawing tablet with a pressure axis and two tilt axes.
+pub trait Tablet: Device {
+    /// Brings the tool into proximity at `x`x`y` until the returned object is dropped.
+    fn approach(&self, x: i32, y: i32) -> Box<dyn Pen>;
+}
+
+pub trait Pen {
+    /// `pressure` ranges from 0 to 1023 and the tilts are in degrees.
+    fn move_(&self, x: i32, y: i32, pressure: i32, tilt_x: i32, tilt_y: i32);
+    /// Presses a button of the tool. Button 1 is the tip.
+    fn press(&self, button: u32) -> Box<dyn PressedButton>;
+}
+
+/// A swipe gesture that ends when it is dropped.
+pub trait SwipeGesture {
+    fn update(&self, dx: f64, dy: f64);
+    /// Makes the gesture end as cancelled when it is dropped.
+    fn cancel(&self);
+}
@@ -1,7 +1,23 @@
 use crate::backend::Backend;
+use std::os::unix::io::AsRawFd;
+use std::time::Duration;
+use tokio::io::unix::AsyncFd;
 
+mod wayland;
 mod x11;
 
 pub fn backends() -> Vec<Box<dyn Backend>> {
-    vec![x11::backend()]
+    vec![x11::backend(), wayland::backend()]
+}
+
+/// Waits until the connection of an event loop becomes readable.
+///
+/// Event loops also wait for timers and file descriptors that are not visible here, such as the
+/// pipes of clipboard transfers. Therefore this also returns after a short time so that they
+/// can make progress.
+async fn wait_for_event_loop<T: AsRawFd>(afd: &AsyncFd<T>) {
+    let readable = tokio::time::timeout(Duration::from_millis(10), afd.readable()).await;
+    if let Ok(guard) = readable {
+        guard.unwrap().clear_ready();
+    }
 }
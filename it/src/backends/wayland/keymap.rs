@@ -0,0 +1,185 @@
+#![allow(non_upper_case_globals)]
+
+use crate::backends::x11::keysyms::*;
+use crate::backends::x11::layout::{
+    classify_keysyms, keymap, ALPHABETIC, FIRST_KEY, FOUR_LEVEL, FOUR_LEVEL_SEMIALPHABETIC, KEYPAD,
+    KEY_OFFSET, LAST_KEY, ONE_LEVEL, TWO_LEVEL,
+};
+use crate::keyboard::Layout;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+pub const MOD_SHIFT: u32 = 1 << 0;
+pub const MOD_LOCK: u32 = 1 << 1;
+pub const MOD_CONTROL: u32 = 1 << 2;
+pub const MOD_MOD1: u32 = 1 << 3;
+pub const MOD_MOD2: u32 = 1 << 4;
+pub const MOD_MOD3: u32 = 1 << 5;
+
+/// The same layouts as the X11 backend in the XKB text format.
+pub struct Keymaps {
+    pub keymaps: [Keymap; 2],
+}
+
+pub struct Keymap {
+    pub text: String,
+    /// The keysyms of each evdev key in each group.
+    pub groups: Vec<HashMap<u32, Vec<u32>>>,
+}
+
+pub enum Action {
+    None,
+    SetMods(u32),
+    LockMods(u32),
+}
+
+pub fn keymaps() -> Keymaps {
+    Keymaps {
+        keymaps: [
+            create_keymap(vec![keymap(Layout::Qwerty), keymap(Layout::Azerty)]),
+            create_keymap(vec![keymap(Layout::QwertySwapped)]),
+        ],
+    }
+}
+
+/// The keymap and group of a layout.
+pub fn position(layout: Layout) -> (usize, u32) {
+    match layout {
+        Layout::Qwerty => (0, 0),
+        Layout::Azerty => (0, 1),
+        Layout::QwertySwapped => (1, 0),
+    }
+}
+
+/// The action of a key whose first level has the given keysym.
+///
+/// These are the actions that the X11 backend assigns to its keys.
+pub fn action(keysym: u32) -> Action {
+    match keysym {
+        XK_Num_Lock => Action::LockMods(MOD_MOD3),
+        XK_Caps_Lock => Action::LockMods(MOD_LOCK),
+        XK_ISO_Level3_Shift => Action::SetMods(MOD_MOD2),
+        XK_Alt_L | XK_Alt_R => Action::SetMods(MOD_MOD1),
+        XK_Shift_L | XK_Shift_R => Action::SetMods(MOD_SHIFT),
+        XK_Control_L | XK_Control_R => Action::SetMods(MOD_CONTROL),
+        _ => Action::None,
+    }
+}
+
+fn type_name(ty: u8) -> &'static str {
+    match ty {
+        ONE_LEVEL => "ONE_LEVEL",
+        TWO_LEVEL => "TWO_LEVEL",
+        ALPHABETIC => "ALPHABETIC",
+        KEYPAD => "KEYPAD",
+        FOUR_LEVEL => "FOUR_LEVEL",
+        FOUR_LEVEL_SEMIALPHABETIC => "FOUR_LEVEL_SEMIALPHABETIC",
+        _ => unreachable!(),
+    }
+}
+
+fn create_keymap(groups: Vec<HashMap<u32, Vec<u32>>>) -> Keymap {
+    let mut text = String::new();
+    text.push_str("xkb_keymap {\n");
+    text.push_str("    xkb_keycodes {\n");
+    text.push_str("        minimum = 8;\n");
+    text.push_str("        maximum = 255;\n");
+    for key in FIRST_KEY..=LAST_KEY {
+        let code = key + KEY_OFFSET;
+        writeln!(text, "        <I{}> = {};", code, code).unwrap();
+    }
+    text.push_str("    };\n");
+    text.push_str(TYPES);
+    text.push_str("    xkb_compatibility {\n");
+    text.push_str("    };\n");
+    text.push_str("    xkb_symbols {\n");
+    for key in FIRST_KEY..=LAST_KEY {
+        let syms: Vec<_> = groups
+            .iter()
+            .map(|g| g.get(&key).map(|s| s.as_slice()).unwrap_or(&[]))
+            .collect();
+        if syms.iter().all(|s| s.is_empty()) {
+            continue;
+        }
+        let mut entries = vec![];
+        for (i, syms) in syms.iter().enumerate() {
+            let names: Vec<_> = match syms.is_empty() {
+                true => vec!["NoSymbol".to_string()],
+                false => syms
+                    .iter()
+                    .map(|&sym| match sym {
+                        0 => "NoSymbol".to_string(),
+                        _ => format!("0x{:x}", sym),
+                    })
+                    .collect(),
+            };
+            entries.push(format!(
+                "type[Group{0}] = \"{1}\", symbols[Group{0}] = [ {2} ]",
+                i + 1,
+                type_name(classify_keysyms(syms)),
+                names.join(", "),
+            ));
+        }
+        writeln!(
+            text,
+            "        key <I{}> {{ {} }};",
+            key + KEY_OFFSET,
+            entries.join(", ")
+        )
+        .unwrap();
+    }
+    text.push_str("    };\n");
+    text.push_str("};\n");
+    Keymap { text, groups }
+}
+
+const TYPES: &str = r#"    xkb_types {
+        type "ONE_LEVEL" {
+            modifiers = none;
+            level_name[Level1] = "Any";
+        };
+        type "TWO_LEVEL" {
+            modifiers = Shift;
+            map[Shift] = Level2;
+            level_name[Level1] = "Base";
+            level_name[Level2] = "Shift";
+        };
+        type "ALPHABETIC" {
+            modifiers = Shift + Lock;
+            map[Shift] = Level2;
+            map[Lock] = Level2;
+            level_name[Level1] = "Base";
+            level_name[Level2] = "Caps";
+        };
+        type "KEYPAD" {
+            modifiers = Shift + Mod3;
+            map[Shift] = Level2;
+            map[Mod3] = Level2;
+            level_name[Level1] = "Base";
+            level_name[Level2] = "Number";
+        };
+        type "FOUR_LEVEL" {
+            modifiers = Shift + Mod2;
+            map[Shift] = Level2;
+            map[Mod2] = Level3;
+            map[Shift + Mod2] = Level4;
+            level_name[Level1] = "Base";
+            level_name[Level2] = "Shift";
+            level_name[Level3] = "Alt Base";
+            level_name[Level4] = "Shift Alt";
+        };
+        type "FOUR_LEVEL_SEMIALPHABETIC" {
+            modifiers = Shift + Lock + Mod2;
+            map[Shift] = Level2;
+            map[Lock] = Level2;
+            map[Mod2] = Level3;
+            map[Shift + Mod2] = Level4;
+            map[Lock + Mod2] = Level3;
+            map[Shift + Lock + Mod2] = Level4;
+            level_name[Level1] = "Base";
+            level_name[Level2] = "Shift";
+            level_name[Level3] = "Alt Base";
+            level_name[Level4] = "Shift Alt";
+        };
+    };
+"#;
@@ -0,0 +1,736 @@
+use crate::backend::{
+    Backend, BackendDeviceId, BackendFlags, BackendIcon, Button, Device, EventLoop, Finger,
+    Instance, Keyboard, Mouse, PressedButton, PressedKey, Seat, Touchscreen, Window,
+    WindowProperties,
+};
+use crate::backends::wayland::keymap::Keymaps;
+use crate::backends::wayland::server::{Server, WindowProps, OUTPUT_HEIGHT, OUTPUT_WIDTH};
+use crate::backends::x11::evdev;
+use crate::env::set_env;
+use crate::event::{map_event, Event, UserEvent};
+use crate::eventstream::EventStream;
+use crate::keyboard::{Key, Layout};
+use parking_lot::Mutex;
+use std::any::Any;
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Display;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Weak};
+use std::task::{Context, Poll, Waker};
+use tokio::io::unix::AsyncFd;
+use tokio::io::Interest;
+use tokio::task::JoinHandle;
+use uapi::c::{AF_UNIX, SOCK_CLOEXEC, SOCK_STREAM};
+use uapi::socketpair;
+use winit::event::{DeviceId, Event as WEvent, StartCause};
+use winit::event_loop::{ControlFlow, EventLoop as WEventLoop};
+use winit::platform::run_return::EventLoopExtRunReturn;
+use winit::platform::unix::EventLoopExtUnix;
+use winit::window::{Window as WWindow, WindowBuilder};
+
+mod keymap;
+mod server;
+
+pub fn backend() -> Box<dyn Backend> {
+    Box::new(Arc::new(WlBackend {
+        keymaps: Arc::new(keymap::keymaps()),
+    }))
+}
+
+struct WlBackend {
+    keymaps: Arc<Keymaps>,
+}
+
+impl Backend for Arc<WlBackend> {
+    fn instantiate(&self) -> Box<dyn Instance> {
+        log::info!("Starting the compositor");
+        Box::new(Arc::new(WlInstance {
+            backend: self.clone(),
+            server: Server::new(self.keymaps.clone()),
+            event_loops: Default::default(),
+            next_device_id: Cell::new(1),
+        }))
+    }
+
+    fn flags(&self) -> BackendFlags {
+        BackendFlags::MT_SAFE
+            | BackendFlags::WINIT_SET_DECORATIONS
+            | BackendFlags::WINIT_SET_INNER_SIZE
+            | BackendFlags::WINIT_SET_TITLE
+            | BackendFlags::WINIT_SET_MAXIMIZED
+            | BackendFlags::WINIT_SET_RESIZABLE
+            | BackendFlags::SET_INNER_SIZE
+            | BackendFlags::CLIPBOARD
+            | BackendFlags::SCALED_FULLSCREEN
+            | BackendFlags::PRESENT
+            | BackendFlags::WINIT_TRANSPARENCY
+    }
+
+    fn name(&self) -> &str {
+        "wayland"
+    }
+}
+
+struct WlInstance {
+    backend: Arc<WlBackend>,
+    server: Server,
+    event_loops: Mutex<Vec<Weak<WlEventLoopData>>>,
+    next_device_id: Cell<u32>,
+}
+
+impl WlInstance {
+    fn next_device_id(&self) -> u32 {
+        let id = self.next_device_id.get();
+        self.next_device_id.set(id + 1);
+        id
+    }
+}
+
+impl Instance for Arc<WlInstance> {
+    fn backend(&self) -> &dyn Backend {
+        &self.backend
+    }
+
+    fn default_seat(&self) -> Box<dyn Seat> {
+        Box::new(Arc::new(WlSeat {
+            instance: self.clone(),
+        }))
+    }
+
+    fn create_event_loop(&self) -> Box<dyn EventLoop> {
+        let (server_fd, client_fd) = socketpair(AF_UNIX, SOCK_STREAM | SOCK_CLOEXEC, 0).unwrap();
+        let client = self.server.add_client(server_fd);
+        // The connection takes ownership of the file descriptor.
+        let fd = client_fd.unwrap();
+        let el = {
+            let _var = set_env("WAYLAND_SOCKET", &fd.to_string());
+            WEventLoop::new_wayland_any_thread()
+        };
+        let el = Arc::new(WlEventLoopData {
+            instance: self.clone(),
+            client,
+            el: Mutex::new(el),
+            waiters: Default::default(),
+            events: Default::default(),
+            version: Cell::new(1),
+            cached_num_monitors: Cell::new(usize::MAX),
+        });
+        let el2 = el.clone();
+        let jh = tokio::task::spawn_local(async move {
+            let afd = AsyncFd::with_interest(fd, Interest::READABLE).unwrap();
+            loop {
+                el2.run();
+                super::wait_for_event_loop(&afd).await;
+            }
+        });
+        self.event_loops.lock().push(Arc::downgrade(&el));
+        Box::new(Arc::new(WlEventLoop {
+            data: el,
+            jh: Some(jh),
+        }))
+    }
+
+    fn take_screenshot(&self) {
+        let image = self.server.call(|c| c.screenshot());
+        crate::screenshot::log_image(&image, OUTPUT_WIDTH as _, OUTPUT_HEIGHT as _);
+    }
+
+    fn before_poll(&self) {
+        let els = self.event_loops.lock();
+        for el in &*els {
+            if let Some(el2) = el.upgrade() {
+                el2.run();
+            }
+        }
+    }
+
+    fn redraw_requested_scenarios(&self) -> usize {
+        1
+    }
+}
+
+struct WlEventLoopData {
+    instance: Arc<WlInstance>,
+    client: u32,
+    el: Mutex<WEventLoop<UserEvent>>,
+    waiters: Mutex<Vec<Waker>>,
+    events: Mutex<VecDeque<Event>>,
+    version: Cell<u32>,
+    cached_num_monitors: Cell<usize>,
+}
+
+impl WlEventLoopData {
+    fn run(&self) {
+        let mut el = self.el.lock();
+        let mut events = self.events.lock();
+        let mut wake = false;
+        el.run_return(|ev, _, cf| {
+            // The first iteration does not read from the socket. Exit after the second.
+            match ev {
+                WEvent::NewEvents(StartCause::Init) => *cf = ControlFlow::Poll,
+                WEvent::NewEvents(_) => *cf = ControlFlow::Exit,
+                _ => {}
+            }
+            if let Some(ev) = map_event(ev) {
+                log::debug!("winit event: {:?}", ev);
+                events.push_back(ev);
+                wake = true;
+            }
+        });
+        if !wake {
+            let num_monitors = el.available_monitors().count();
+            if num_monitors != self.cached_num_monitors.get() {
+                self.cached_num_monitors.set(num_monitors);
+                wake = true;
+            }
+        }
+        if wake {
+            self.version.set(self.version.get() + 1);
+            let mut waiters = self.waiters.lock();
+            for waiter in waiters.drain(..) {
+                waiter.wake();
+            }
+        }
+    }
+}
+
+struct WlEventLoop {
+    data: Arc<WlEventLoopData>,
+    jh: Option<JoinHandle<()>>,
+}
+
+impl Drop for WlEventLoop {
+    fn drop(&mut self) {
+        self.jh.take().unwrap().abort();
+    }
+}
+
+impl WlEventLoop {
+    fn event2<'a>(&'a self) -> Pin<Box<dyn Future<Output = Event> + 'a>> {
+        struct Changed<'b>(&'b WlEventLoopData);
+        impl<'b> Future for Changed<'b> {
+            type Output = Event;
+            fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+                if let Some(e) = self.0.events.lock().pop_front() {
+                    Poll::Ready(e)
+                } else {
+                    self.0.waiters.lock().push(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        }
+        Box::pin(Changed(&self.data))
+    }
+}
+
+impl EventStream for Arc<WlEventLoop> {
+    fn event<'a>(&'a mut self) -> Pin<Box<dyn Future<Output = Event> + 'a>> {
+        self.event2()
+    }
+}
+
+impl EventLoop for Arc<WlEventLoop> {
+    fn events(&self) -> Box<dyn EventStream> {
+        Box::new(self.clone())
+    }
+
+    fn changed<'a>(&'a self) -> Pin<Box<dyn Future<Output = ()> + 'a>> {
+        struct Changed<'b>(&'b WlEventLoopData, u32);
+        impl<'b> Future for Changed<'b> {
+            type Output = ();
+            fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+                if self.1 != self.0.version.get() {
+                    Poll::Ready(())
+                } else {
+                    self.0.waiters.lock().push(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        }
+        Box::pin(Changed(&self.data, self.data.version.get()))
+    }
+
+    fn create_window(&self, builder: WindowBuilder) -> Box<dyn Window> {
+        let winit = builder.build(&*self.data.el.lock()).unwrap();
+        // Send the requests that created the toplevel.
+        self.data.run();
+        let client = self.data.client;
+        let (id, props) = self
+            .data
+            .instance
+            .server
+            .call(move |c| c.newest_window(client))
+            .unwrap();
+        log::info!("Created window {}", id);
+        Box::new(Arc::new(WlWindow {
+            el: self.clone(),
+            id,
+            props,
+            winit,
+        }))
+    }
+
+    fn with_winit<'a>(&self, f: Box<dyn FnOnce(&mut WEventLoop<UserEvent>) + 'a>) {
+        f(&mut *self.data.el.lock());
+    }
+
+    fn barrier<'a>(&'a self) -> Pin<Box<dyn Future<Output = ()> + 'a>> {
+        struct Pong<'b>(&'b Server, u32);
+        impl<'b> Future for Pong<'b> {
+            type Output = ();
+            fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+                let mut shared = self.0.shared.lock();
+                if shared.pongs.remove(&self.1) {
+                    Poll::Ready(())
+                } else {
+                    shared.wakers.push(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        }
+        log::info!("Creating event barrier");
+        Box::pin(async move {
+            let server = &self.data.instance.server;
+            let client = self.data.client;
+            let serial = server.call(move |c| c.ping(client));
+            Pong(server, serial).await;
+            // The pong is sent after the events that preceded the ping have been dispatched.
+            self.data.events.lock().clear();
+        })
+    }
+}
+
+struct WlWindow {
+    el: Arc<WlEventLoop>,
+    id: u32,
+    props: Arc<Mutex<WindowProps>>,
+    winit: WWindow,
+}
+
+impl WlWindow {
+    fn server(&self) -> &Server {
+        &self.el.data.instance.server
+    }
+}
+
+impl Window for Arc<WlWindow> {
+    fn id(&self) -> &dyn Display {
+        &self.id
+    }
+
+    fn backend(&self) -> &dyn Backend {
+        &self.el.data.instance.backend
+    }
+
+    fn event_loop(&self) -> &dyn EventLoop {
+        &self.el
+    }
+
+    fn winit(&self) -> &WWindow {
+        &self.winit
+    }
+
+    fn properties_changed<'a>(&'a self) -> Pin<Box<dyn Future<Output = ()> + 'a>> {
+        struct Changed<'b>(&'b Server, u64);
+        impl<'b> Future for Changed<'b> {
+            type Output = ();
+            fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+                let mut shared = self.0.shared.lock();
+                if self.1 != shared.generation {
+                    Poll::Ready(())
+                } else {
+                    shared.wakers.push(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        }
+        let generation = self.server().shared.lock().generation;
+        Box::pin(Changed(self.server(), generation))
+    }
+
+    fn properties(&self) -> &dyn WindowProperties {
+        self
+    }
+
+    fn set_inner_size(&self, width: u32, height: u32) {
+        let id = self.id;
+        self.server()
+            .call(move |c| c.set_inner_size(id, width, height));
+    }
+
+    fn set_background_color(&self, r: u8, g: u8, b: u8) {
+        self.props.lock().background = (r, g, b);
+    }
+
+    fn pixel(&self, x: u32, y: u32) -> (u8, u8, u8) {
+        server::pixel(&self.props.lock(), x, y)
+    }
+
+    fn any(&self) -> &dyn Any {
+        self
+    }
+
+    fn delete(&self) {
+        log::info!("Deleting window {}", self.id);
+        let id = self.id;
+        self.server().call(move |c| c.close(id));
+    }
+
+    fn frame_extents(&self) -> (u32, u32, u32, u32) {
+        (0, 0, 0, 0)
+    }
+
+    fn set_outer_position(&self, x: i32, y: i32) {
+        log::info!("Setting outer position of {} to {}x{}", self.id, x, y);
+        let id = self.id;
+        self.server().call(move |c| c.set_outer_position(id, x, y));
+    }
+
+    fn request_redraw(&self, _scenario: usize) {
+        let id = self.id;
+        self.server().call(move |c| c.request_redraw(id));
+    }
+}
+
+impl WindowProperties for Arc<WlWindow> {
+    fn mapped(&self) -> bool {
+        self.props.lock().mapped
+    }
+
+    fn always_on_top(&self) -> bool {
+        false
+    }
+
+    fn decorations(&self) -> bool {
+        self.props.lock().decorations
+    }
+
+    fn x(&self) -> i32 {
+        self.props.lock().x
+    }
+
+    fn y(&self) -> i32 {
+        self.props.lock().y
+    }
+
+    fn width(&self) -> u32 {
+        self.props.lock().width
+    }
+
+    fn height(&self) -> u32 {
+        self.props.lock().height
+    }
+
+    fn min_size(&self) -> Option<(u32, u32)> {
+        self.props.lock().min_size
+    }
+
+    fn max_size(&self) -> Option<(u32, u32)> {
+        self.props.lock().max_size
+    }
+
+    fn title(&self) -> Option<String> {
+        self.props.lock().title.clone()
+    }
+
+    fn maximized(&self) -> Option<bool> {
+        Some(self.props.lock().maximized)
+    }
+
+    fn minimized(&self) -> Option<bool> {
+        Some(self.props.lock().minimized)
+    }
+
+    fn resizable(&self) -> Option<bool> {
+        Some(
+            self.max_size() != Some((self.width(), self.height()))
+                || self.max_size() != self.min_size(),
+        )
+    }
+
+    fn icon(&self) -> Option<BackendIcon> {
+        None
+    }
+
+    fn attention(&self) -> bool {
+        false
+    }
+
+    fn supports_transparency(&self) -> bool {
+        true
+    }
+
+    fn dragging(&self) -> bool {
+        self.props.lock().dragging
+    }
+
+    fn fullscreen(&self) -> bool {
+        self.props.lock().fullscreen
+    }
+
+    fn viewport(&self) -> Option<(u32, u32)> {
+        self.props.lock().viewport
+    }
+
+    fn frames(&self) -> u64 {
+        self.props.lock().frames
+    }
+}
+
+/// The only seat of the compositor.
+struct WlSeat {
+    instance: Arc<WlInstance>,
+}
+
+impl Seat for Arc<WlSeat> {
+    fn add_keyboard(&self) -> Box<dyn Keyboard> {
+        let id = self.instance.next_device_id();
+        log::info!("Created keyboard {} on seat 0", id);
+        Box::new(Arc::new(WlKeyboard {
+            pressed_keys: Default::default(),
+            dev: WlDevice {
+                seat: self.clone(),
+                id,
+            },
+        }))
+    }
+
+    fn add_mouse(&self) -> Box<dyn Mouse> {
+        let id = self.instance.next_device_id();
+        log::info!("Created mouse {} on seat 0", id);
+        Box::new(Arc::new(WlMouse {
+            pressed_buttons: Default::default(),
+            dev: WlDevice {
+                seat: self.clone(),
+                id,
+            },
+        }))
+    }
+
+    fn add_touchscreen(&self) -> Box<dyn Touchscreen> {
+        let id = self.instance.next_device_id();
+        log::info!("Created touchscreen {} on seat 0", id);
+        Box::new(Arc::new(WlTouch {
+            dev: WlDevice {
+                seat: self.clone(),
+                id,
+            },
+        }))
+    }
+
+    fn focus(&self, window: &dyn Window) {
+        let window: &Arc<WlWindow> = window.any().downcast_ref().unwrap();
+        log::info!("Focusing seat 0 on window {}", window.id);
+        let id = window.id;
+        self.instance.server.call(move |c| c.focus(Some(id)));
+    }
+
+    fn un_focus(&self) {
+        log::info!("Unfocusing seat 0");
+        self.instance.server.call(|c| c.focus(None));
+    }
+
+    fn set_layout(&self, layout: Layout) {
+        log::info!("Setting layout of seat 0 to {:?}", layout);
+        self.instance.server.call(move |c| c.set_layout(layout));
+    }
+
+    fn set_cursor_position(&self, x: i32, y: i32) {
+        log::info!("Moving cursor of seat 0 to {}x{}", x, y);
+        self.instance
+            .server
+            .call(move |c| c.set_cursor_position(x, y));
+    }
+
+    fn cursor_position(&self) -> (i32, i32) {
+        self.instance.server.call(|c| c.cursor_position())
+    }
+
+    fn is(&self, device_id: DeviceId) -> bool {
+        WlDeviceId.is(device_id)
+    }
+}
+
+struct WlDevice {
+    seat: Arc<WlSeat>,
+    id: u32,
+}
+
+impl WlDevice {
+    fn server(&self) -> &Server {
+        &self.seat.instance.server
+    }
+}
+
+struct WlDeviceId;
+
+impl BackendDeviceId for WlDeviceId {
+    fn is(&self, device: DeviceId) -> bool {
+        // Winit does not distinguish the devices of Wayland seats.
+        device == unsafe { DeviceId::dummy() }
+    }
+}
+
+struct WlMouse {
+    pressed_buttons: Mutex<HashMap<Button, Weak<WlPressedButton>>>,
+    dev: WlDevice,
+}
+
+impl Device for Arc<WlMouse> {
+    fn id(&self) -> Box<dyn BackendDeviceId> {
+        Box::new(WlDeviceId)
+    }
+}
+
+impl Mouse for Arc<WlMouse> {
+    fn press(&self, button: Button) -> Box<dyn PressedButton> {
+        log::info!(
+            "Pressing button {:?} of mouse {} of seat 0",
+            button,
+            self.dev.id
+        );
+        let mut buttons = self.pressed_buttons.lock();
+        if let Some(p) = buttons.get(&button) {
+            if let Some(p) = p.upgrade() {
+                return Box::new(p);
+            }
+        }
+        let code = map_button(button);
+        self.dev.server().call(move |c| c.button(code, true));
+        let p = Arc::new(WlPressedButton {
+            mouse: self.clone(),
+            button,
+        });
+        buttons.insert(button, Arc::downgrade(&p));
+        Box::new(p)
+    }
+
+    fn move_(&self, dx: i32, dy: i32) {
+        log::info!("Moving mouse {} of seat 0 by {}x{}", self.dev.id, dx, dy);
+        self.dev.server().call(move |c| c.move_pointer(dx, dy));
+    }
+
+    fn scroll(&self, dx: i32, dy: i32) {
+        log::info!("Scrolling mouse {} of seat 0 by {}x{}", self.dev.id, dx, dy);
+        self.dev.server().call(move |c| c.scroll(dx, dy));
+    }
+}
+
+struct WlKeyboard {
+    pressed_keys: Mutex<HashMap<Key, Weak<WlPressedKey>>>,
+    dev: WlDevice,
+}
+
+impl Device for Arc<WlKeyboard> {
+    fn id(&self) -> Box<dyn BackendDeviceId> {
+        Box::new(WlDeviceId)
+    }
+}
+
+impl Keyboard for Arc<WlKeyboard> {
+    fn press(&self, key: Key) -> Box<dyn PressedKey> {
+        log::info!(
+            "Pressing key {:?} of keyboard {} of seat 0",
+            key,
+            self.dev.id
+        );
+        let mut keys = self.pressed_keys.lock();
+        if let Some(p) = keys.get(&key) {
+            if let Some(p) = p.upgrade() {
+                log::info!("Key already pressed");
+                return Box::new(p);
+            }
+        }
+        let code = evdev::map_key(key);
+        self.dev.server().call(move |c| c.key(code, true));
+        let p = Arc::new(WlPressedKey {
+            kb: self.clone(),
+            key,
+        });
+        keys.insert(key, Arc::downgrade(&p));
+        Box::new(p)
+    }
+}
+
+struct WlPressedButton {
+    mouse: Arc<WlMouse>,
+    button: Button,
+}
+
+impl PressedButton for Arc<WlPressedButton> {}
+
+impl Drop for WlPressedButton {
+    fn drop(&mut self) {
+        let code = map_button(self.button);
+        self.mouse.dev.server().call(move |c| c.button(code, false));
+    }
+}
+
+struct WlPressedKey {
+    kb: Arc<WlKeyboard>,
+    key: Key,
+}
+
+impl PressedKey for Arc<WlPressedKey> {}
+
+impl Drop for WlPressedKey {
+    fn drop(&mut self) {
+        log::info!("Releasing key {:?}", self.key);
+        let code = evdev::map_key(self.key);
+        self.kb.dev.server().call(move |c| c.key(code, false));
+    }
+}
+
+struct WlTouch {
+    dev: WlDevice,
+}
+
+impl Device for Arc<WlTouch> {
+    fn id(&self) -> Box<dyn BackendDeviceId> {
+        Box::new(WlDeviceId)
+    }
+}
+
+impl Touchscreen for Arc<WlTouch> {
+    fn down(&self, x: i32, y: i32) -> Box<dyn Finger> {
+        let touch_id = self.dev.server().call(move |c| c.touch_down(x, y));
+        Box::new(WlFinger {
+            touch: self.clone(),
+            touch_id,
+        })
+    }
+}
+
+struct WlFinger {
+    touch: Arc<WlTouch>,
+    touch_id: i32,
+}
+
+impl Finger for WlFinger {
+    fn move_(&self, x: i32, y: i32) {
+        let id = self.touch_id;
+        self.touch
+            .dev
+            .server()
+            .call(move |c| c.touch_move(id, x, y));
+    }
+}
+
+impl Drop for WlFinger {
+    fn drop(&mut self) {
+        let id = self.touch_id;
+        self.touch.dev.server().call(move |c| c.touch_up(id));
+    }
+}
+
+fn map_button(button: Button) -> u32 {
+    match button {
+        Button::Left => 0x110,
+        Button::Right => 0x111,
+        Button::Middle => 0x112,
+        Button::Forward => 0x115,
+        Button::Back => 0x116,
+    }
+}
@@ -0,0 +1,1603 @@
+use super::keymap::{self, Action, Keymaps};
+use parking_lot::Mutex;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::rc::Rc;
+use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::task::Waker;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use uapi::c::{MFD_CLOEXEC, O_CLOEXEC, POLLIN};
+use uapi::{memfd_create, pipe2, OwnedFd};
+use wayland_protocols::unstable::primary_selection::v1::server::{
+    zwp_primary_selection_device_manager_v1, zwp_primary_selection_device_v1,
+    zwp_primary_selection_offer_v1, zwp_primary_selection_source_v1,
+};
+use wayland_protocols::unstable::xdg_decoration::v1::server::{
+    zxdg_decoration_manager_v1, zxdg_toplevel_decoration_v1,
+};
+use wayland_protocols::viewporter::server::{wp_viewport, wp_viewporter};
+use wayland_protocols::xdg_shell::server::{xdg_surface, xdg_toplevel, xdg_wm_base};
+use wayland_server::protocol::{
+    wl_buffer, wl_callback, wl_compositor, wl_data_device, wl_data_device_manager, wl_data_offer,
+    wl_data_source, wl_keyboard, wl_output, wl_pointer, wl_seat, wl_shm, wl_shm_pool,
+    wl_subcompositor, wl_surface, wl_touch,
+};
+use wayland_server::{Display, Filter, Interface, Main, Resource};
+
+pub const OUTPUT_WIDTH: i32 = 1024;
+pub const OUTPUT_HEIGHT: i32 = 768;
+
+/// A compositor that runs on its own thread.
+///
+/// Wayland objects cannot leave the thread of their display. The tests therefore only see the
+/// compositor through `call` and through the `Shared` state.
+pub struct Server {
+    commands: Mutex<Sender<Command>>,
+    wake: OwnedFd,
+    thread: Option<JoinHandle<()>>,
+    pub shared: Arc<Mutex<Shared>>,
+}
+
+type Command = Box<dyn FnOnce(&mut Display, &mut Compositor) + Send>;
+
+/// The state of the compositor that can be observed from other threads.
+#[derive(Default)]
+pub struct Shared {
+    pub generation: u64,
+    pub wakers: Vec<Waker>,
+    pub pongs: HashSet<u32>,
+}
+
+impl Shared {
+    fn changed(&mut self) {
+        self.generation += 1;
+        for waker in self.wakers.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+/// The properties of a toplevel as the window manager sees them.
+#[derive(Default)]
+pub struct WindowProps {
+    pub mapped: bool,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub min_size: Option<(u32, u32)>,
+    pub max_size: Option<(u32, u32)>,
+    pub title: Option<String>,
+    pub maximized: bool,
+    pub minimized: bool,
+    pub fullscreen: bool,
+    pub viewport: Option<(u32, u32)>,
+    pub dragging: bool,
+    pub decorations: bool,
+    pub background: (u8, u8, u8),
+    pub frame: Option<Frame>,
+    /// The number of buffers that the window has committed.
+    pub frames: u64,
+}
+
+/// The contents of a window in BGRA order.
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl Server {
+    pub fn new(keymaps: Arc<Keymaps>) -> Self {
+        let (commands, rx) = std::sync::mpsc::channel();
+        let (wake_read, wake) = pipe2(O_CLOEXEC).unwrap();
+        let shared = Arc::new(Mutex::new(Shared::default()));
+        let shared2 = shared.clone();
+        let thread = std::thread::spawn(move || {
+            let mut display = Display::new();
+            let mut compositor = Compositor::new(&mut display, shared2, keymaps);
+            run(display, &mut compositor, rx, wake_read);
+        });
+        Self {
+            commands: Mutex::new(commands),
+            wake,
+            thread: Some(thread),
+            shared,
+        }
+    }
+
+    fn send(&self, command: Command) {
+        self.commands.lock().send(command).unwrap();
+        uapi::write(self.wake.raw(), &[0u8]).unwrap();
+    }
+
+    /// Runs `f` on the compositor thread after all pending requests have been handled.
+    pub fn call<T, F>(&self, f: F) -> T
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Compositor) -> T + Send + 'static,
+    {
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.send(Box::new(move |_, c| {
+            let _ = tx.send(f(c));
+        }));
+        rx.recv().unwrap()
+    }
+
+    /// Adds the client at the other end of `fd` and returns its id.
+    pub fn add_client(&self, fd: OwnedFd) -> u32 {
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.send(Box::new(move |display, c| {
+            let _ = tx.send(c.add_client(display, fd));
+        }));
+        rx.recv().unwrap()
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        *self.commands.lock() = std::sync::mpsc::channel().0;
+        uapi::write(self.wake.raw(), &[0u8]).unwrap();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn run(
+    mut display: Display,
+    compositor: &mut Compositor,
+    commands: Receiver<Command>,
+    wake: OwnedFd,
+) {
+    loop {
+        let mut fds = [
+            uapi::c::pollfd {
+                fd: display.get_poll_fd(),
+                events: POLLIN,
+                revents: 0,
+            },
+            uapi::c::pollfd {
+                fd: wake.raw(),
+                events: POLLIN,
+                revents: 0,
+            },
+        ];
+        uapi::poll(&mut fds, -1).unwrap();
+        if fds[1].revents != 0 {
+            let mut buf = [0u8; 64];
+            let _ = uapi::read(wake.raw(), &mut buf[..]);
+        }
+        display.dispatch(Duration::ZERO, compositor).unwrap();
+        loop {
+            match commands.try_recv() {
+                Ok(command) => {
+                    display.dispatch(Duration::ZERO, compositor).unwrap();
+                    command(&mut display, compositor);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return,
+            }
+        }
+        display.flush_clients(compositor);
+        if mem::take(&mut compositor.dirty) {
+            compositor.shared.lock().changed();
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+struct ClientId(u32);
+
+fn client_id<I>(resource: &Resource<I>) -> Option<u32>
+where
+    I: Interface + AsRef<Resource<I>> + From<Resource<I>>,
+{
+    resource
+        .client()
+        .and_then(|c| c.data_map().get::<ClientId>().map(|id| id.0))
+}
+
+struct Toplevel {
+    id: u32,
+    client: u32,
+    surface: wl_surface::WlSurface,
+    xdg_surface: xdg_surface::XdgSurface,
+    toplevel: xdg_toplevel::XdgToplevel,
+    props: Arc<Mutex<WindowProps>>,
+    configured: Cell<bool>,
+    saved_position: Cell<Option<(i32, i32)>>,
+}
+
+#[derive(Default)]
+struct SurfaceData {
+    buffer: Option<Option<wl_buffer::WlBuffer>>,
+    damage: Vec<(i32, i32, i32, i32)>,
+    frame_callbacks: Vec<Main<wl_callback::WlCallback>>,
+    /// The destination of the viewport, which takes effect with the next commit.
+    viewport: Option<Option<(u32, u32)>>,
+    toplevel: Option<u32>,
+}
+
+fn surface_data(surface: &wl_surface::WlSurface) -> &RefCell<SurfaceData> {
+    surface.as_ref().user_data().get().unwrap()
+}
+
+/// The memory of a `wl_shm_pool`.
+struct Pool {
+    fd: OwnedFd,
+    map: Cell<(*mut u8, usize)>,
+}
+
+impl Pool {
+    fn new(fd: OwnedFd, size: usize) -> Self {
+        let pool = Self {
+            fd,
+            map: Cell::new((std::ptr::null_mut(), 0)),
+        };
+        pool.resize(size);
+        pool
+    }
+
+    fn resize(&self, size: usize) {
+        self.unmap();
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                size,
+                libc::PROT_READ,
+                libc::MAP_SHARED,
+                self.fd.raw(),
+                0,
+            )
+        };
+        assert_ne!(ptr, libc::MAP_FAILED, "Could not map the pool");
+        self.map.set((ptr as *mut u8, size));
+    }
+
+    fn unmap(&self) {
+        let (ptr, size) = self.map.replace((std::ptr::null_mut(), 0));
+        if !ptr.is_null() {
+            unsafe {
+                libc::munmap(ptr as _, size);
+            }
+        }
+    }
+
+    fn data(&self) -> &[u8] {
+        let (ptr, size) = self.map.get();
+        unsafe { std::slice::from_raw_parts(ptr, size) }
+    }
+}
+
+impl Drop for Pool {
+    fn drop(&mut self) {
+        self.unmap();
+    }
+}
+
+struct ShmBuffer {
+    pool: Rc<Pool>,
+    offset: usize,
+    width: u32,
+    height: u32,
+    stride: usize,
+}
+
+struct Drag {
+    window: Rc<Toplevel>,
+    cursor: (i32, i32),
+    position: (i32, i32),
+}
+
+#[derive(Default)]
+struct Seat {
+    keyboards: Vec<wl_keyboard::WlKeyboard>,
+    pointers: Vec<wl_pointer::WlPointer>,
+    touches: Vec<wl_touch::WlTouch>,
+    /// The number of keyboards that press each key.
+    keys: HashMap<u32, usize>,
+    /// The number of mice that press each button.
+    buttons: HashMap<u32, usize>,
+    /// The modifiers that are unlocked when a key is released.
+    unlock_on_release: HashMap<u32, u32>,
+    keymap: usize,
+    group: u32,
+    depressed: u32,
+    locked: u32,
+    keyboard_focus: Option<Rc<Toplevel>>,
+    pointer_focus: Option<Rc<Toplevel>>,
+    pointer_position: (i32, i32),
+    cursor: (i32, i32),
+    drag: Option<Drag>,
+    touch_points: HashMap<i32, Rc<Toplevel>>,
+    next_touch_id: i32,
+    data_devices: Vec<wl_data_device::WlDataDevice>,
+    primary_selection_devices: Vec<zwp_primary_selection_device_v1::ZwpPrimarySelectionDeviceV1>,
+    /// The source of the clipboard.
+    selection: Option<wl_data_source::WlDataSource>,
+    primary_selection: Option<zwp_primary_selection_source_v1::ZwpPrimarySelectionSourceV1>,
+}
+
+pub struct Compositor {
+    shared: Arc<Mutex<Shared>>,
+    keymaps: Arc<Keymaps>,
+    start: Instant,
+    dirty: bool,
+    next_serial: u32,
+    next_client_id: u32,
+    next_window_id: u32,
+    wm_bases: Vec<xdg_wm_base::XdgWmBase>,
+    outputs: Vec<wl_output::WlOutput>,
+    /// The toplevels from bottom to top.
+    windows: Vec<Rc<Toplevel>>,
+    seat: Seat,
+}
+
+macro_rules! compositor {
+    ($ddata:expr) => {
+        match $ddata.get::<Compositor>() {
+            Some(c) => c,
+            None => return,
+        }
+    };
+}
+
+impl Compositor {
+    fn new(display: &mut Display, shared: Arc<Mutex<Shared>>, keymaps: Arc<Keymaps>) -> Self {
+        display.create_global::<wl_compositor::WlCompositor, _>(
+            4,
+            Filter::new(
+                |(compositor, _): (Main<wl_compositor::WlCompositor>, u32), _, _| {
+                    compositor.quick_assign(|_, req, mut ddata| match req {
+                        wl_compositor::Request::CreateSurface { id } => {
+                            compositor!(ddata).create_surface(id)
+                        }
+                        wl_compositor::Request::CreateRegion { id } => {
+                            id.quick_assign(|_, _, _| ())
+                        }
+                        _ => {}
+                    });
+                },
+            ),
+        );
+        display.create_global::<wl_subcompositor::WlSubcompositor, _>(
+            1,
+            Filter::new(
+                |(sub, _): (Main<wl_subcompositor::WlSubcompositor>, u32), _, _| {
+                    sub.quick_assign(|_, req, _| {
+                        if let wl_subcompositor::Request::GetSubsurface { id, .. } = req {
+                            id.quick_assign(|_, _, _| ());
+                        }
+                    });
+                },
+            ),
+        );
+        display.create_global::<wl_shm::WlShm, _>(
+            1,
+            Filter::new(|(shm, _): (Main<wl_shm::WlShm>, u32), _, _| {
+                shm.quick_assign(|_, req, _| {
+                    if let wl_shm::Request::CreatePool { id, fd, size } = req {
+                        create_pool(id, OwnedFd::new(fd), size as usize);
+                    }
+                });
+                shm.format(wl_shm::Format::Argb8888);
+                shm.format(wl_shm::Format::Xrgb8888);
+            }),
+        );
+        display.create_global::<xdg_wm_base::XdgWmBase, _>(
+            1,
+            Filter::new(
+                |(wm_base, _): (Main<xdg_wm_base::XdgWmBase>, u32), _, mut ddata| {
+                    wm_base.quick_assign(|_, req, mut ddata| {
+                        let c = compositor!(ddata);
+                        match req {
+                            xdg_wm_base::Request::GetXdgSurface { id, surface } => {
+                                c.create_xdg_surface(id, surface)
+                            }
+                            xdg_wm_base::Request::Pong { serial } => {
+                                c.shared.lock().pongs.insert(serial);
+                                c.dirty = true;
+                            }
+                            _ => {}
+                        }
+                    });
+                    compositor!(ddata).wm_bases.push((*wm_base).clone());
+                },
+            ),
+        );
+        display.create_global::<zxdg_decoration_manager_v1::ZxdgDecorationManagerV1, _>(
+            1,
+            Filter::new(
+                |(manager, _): (
+                    Main<zxdg_decoration_manager_v1::ZxdgDecorationManagerV1>,
+                    u32,
+                ),
+                 _,
+                 _| {
+                    manager.quick_assign(|_, req, mut ddata| {
+                        if let zxdg_decoration_manager_v1::Request::GetToplevelDecoration {
+                            id,
+                            toplevel,
+                        } = req
+                        {
+                            compositor!(ddata).create_decoration(id, toplevel);
+                        }
+                    });
+                },
+            ),
+        );
+        display.create_global::<wp_viewporter::WpViewporter, _>(
+            1,
+            Filter::new(
+                |(viewporter, _): (Main<wp_viewporter::WpViewporter>, u32), _, _| {
+                    viewporter.quick_assign(|_, req, _| {
+                        if let wp_viewporter::Request::GetViewport { id, surface } = req {
+                            create_viewport(id, surface);
+                        }
+                    });
+                },
+            ),
+        );
+        display.create_global::<wl_seat::WlSeat, _>(
+            5,
+            Filter::new(|(seat, _): (Main<wl_seat::WlSeat>, u32), _, _| {
+                seat.quick_assign(|_, req, mut ddata| {
+                    let c = compositor!(ddata);
+                    match req {
+                        wl_seat::Request::GetKeyboard { id } => c.create_keyboard(id),
+                        wl_seat::Request::GetPointer { id } => {
+                            id.quick_assign(|_, _, _| ());
+                            c.seat.pointers.push((*id).clone());
+                        }
+                        wl_seat::Request::GetTouch { id } => {
+                            id.quick_assign(|_, _, _| ());
+                            c.seat.touches.push((*id).clone());
+                        }
+                        _ => {}
+                    }
+                });
+                seat.capabilities(
+                    wl_seat::Capability::Keyboard
+                        | wl_seat::Capability::Pointer
+                        | wl_seat::Capability::Touch,
+                );
+                if seat.as_ref().version() >= 2 {
+                    seat.name("seat0".to_string());
+                }
+            }),
+        );
+        display.create_global::<wl_data_device_manager::WlDataDeviceManager, _>(
+            3,
+            Filter::new(
+                |(manager, _): (Main<wl_data_device_manager::WlDataDeviceManager>, u32), _, _| {
+                    manager.quick_assign(|_, req, mut ddata| match req {
+                        wl_data_device_manager::Request::CreateDataSource { id } => {
+                            create_data_source(id)
+                        }
+                        wl_data_device_manager::Request::GetDataDevice { id, .. } => {
+                            compositor!(ddata).create_data_device(id)
+                        }
+                        _ => {}
+                    });
+                },
+            ),
+        );
+        display.create_global::<
+            zwp_primary_selection_device_manager_v1::ZwpPrimarySelectionDeviceManagerV1,
+            _,
+        >(
+            1,
+            Filter::new(
+                |(manager, _): (
+                    Main<zwp_primary_selection_device_manager_v1::ZwpPrimarySelectionDeviceManagerV1>,
+                    u32,
+                ),
+                 _,
+                 _| {
+                    manager.quick_assign(|_, req, mut ddata| match req {
+                        zwp_primary_selection_device_manager_v1::Request::CreateSource { id } => {
+                            create_primary_selection_source(id)
+                        }
+                        zwp_primary_selection_device_manager_v1::Request::GetDevice {
+                            id, ..
+                        } => compositor!(ddata).create_primary_selection_device(id),
+                        _ => {}
+                    });
+                },
+            ),
+        );
+        display.create_global::<wl_output::WlOutput, _>(
+            3,
+            Filter::new(
+                |(output, _): (Main<wl_output::WlOutput>, u32), _, mut ddata| {
+                    output.quick_assign(|_, _, _| ());
+                    output.geometry(
+                        0,
+                        0,
+                        0,
+                        0,
+                        wl_output::Subpixel::Unknown,
+                        "winit".to_string(),
+                        "winit".to_string(),
+                        wl_output::Transform::Normal,
+                    );
+                    output.mode(
+                        wl_output::Mode::Current | wl_output::Mode::Preferred,
+                        OUTPUT_WIDTH,
+                        OUTPUT_HEIGHT,
+                        60000,
+                    );
+                    // Clients scale their contents from modes that are not current.
+                    output.mode(
+                        wl_output::Mode::empty(),
+                        OUTPUT_WIDTH / 2,
+                        OUTPUT_HEIGHT / 2,
+                        60000,
+                    );
+                    if output.as_ref().version() >= 2 {
+                        output.scale(1);
+                        output.done();
+                    }
+                    compositor!(ddata).outputs.push((*output).clone());
+                },
+            ),
+        );
+        Self {
+            shared,
+            keymaps,
+            start: Instant::now(),
+            dirty: false,
+            next_serial: 1,
+            next_client_id: 1,
+            next_window_id: 1,
+            wm_bases: vec![],
+            outputs: vec![],
+            windows: vec![],
+            seat: Default::default(),
+        }
+    }
+
+    fn add_client(&mut self, display: &mut Display, fd: OwnedFd) -> u32 {
+        let id = self.next_client_id;
+        self.next_client_id += 1;
+        let client = unsafe { display.create_client(fd.unwrap(), self) };
+        client.data_map().insert_if_missing(|| ClientId(id));
+        id
+    }
+
+    fn serial(&mut self) -> u32 {
+        self.next_serial += 1;
+        self.next_serial
+    }
+
+    fn time(&self) -> u32 {
+        self.start.elapsed().as_millis() as u32
+    }
+
+    fn window(&self, id: u32) -> Option<Rc<Toplevel>> {
+        self.windows.iter().find(|w| w.id == id).cloned()
+    }
+
+    /// The newest toplevel of a client.
+    pub fn newest_window(&self, client: u32) -> Option<(u32, Arc<Mutex<WindowProps>>)> {
+        self.windows
+            .iter()
+            .filter(|w| w.client == client)
+            .max_by_key(|w| w.id)
+            .map(|w| (w.id, w.props.clone()))
+    }
+
+    /// Asks the `xdg_wm_base` objects of a client to respond with a pong.
+    pub fn ping(&mut self, client: u32) -> u32 {
+        let serial = self.serial();
+        self.wm_bases.retain(|b| b.as_ref().is_alive());
+        let mut sent = false;
+        for wm_base in &self.wm_bases {
+            if client_id(wm_base.as_ref()) == Some(client) {
+                wm_base.ping(serial);
+                sent = true;
+            }
+        }
+        if !sent {
+            self.shared.lock().pongs.insert(serial);
+        }
+        serial
+    }
+
+    fn create_surface(&mut self, surface: Main<wl_surface::WlSurface>) {
+        surface
+            .as_ref()
+            .user_data()
+            .set(|| RefCell::new(SurfaceData::default()));
+        surface.quick_assign(|surface, req, mut ddata| {
+            let mut data = surface_data(&surface).borrow_mut();
+            match req {
+                wl_surface::Request::Attach { buffer, .. } => data.buffer = Some(buffer),
+                wl_surface::Request::Damage {
+                    x,
+                    y,
+                    width,
+                    height,
+                }
+                | wl_surface::Request::DamageBuffer {
+                    x,
+                    y,
+                    width,
+                    height,
+                } => data.damage.push((x, y, width, height)),
+                wl_surface::Request::Frame { callback } => data.frame_callbacks.push(callback),
+                wl_surface::Request::Commit => {
+                    drop(data);
+                    compositor!(ddata).commit(&surface);
+                }
+                _ => {}
+            }
+        });
+    }
+
+    fn commit(&mut self, surface: &wl_surface::WlSurface) {
+        let mut data = surface_data(surface).borrow_mut();
+        let buffer = data.buffer.take();
+        let damage = mem::take(&mut data.damage);
+        let callbacks = mem::take(&mut data.frame_callbacks);
+        let viewport = data.viewport.take();
+        let toplevel = data.toplevel.and_then(|id| self.window(id));
+        drop(data);
+        if let (Some(viewport), Some(toplevel)) = (viewport, &toplevel) {
+            toplevel.props.lock().viewport = viewport;
+            self.dirty = true;
+        }
+        if let Some(buffer) = buffer {
+            if let Some(toplevel) = &toplevel {
+                let mut props = toplevel.props.lock();
+                match &buffer {
+                    Some(buffer) => copy_buffer(&mut props, buffer, &damage),
+                    None => props.frame = None,
+                }
+                props.frames += 1;
+                self.dirty = true;
+            }
+            if let Some(buffer) = buffer {
+                buffer.release();
+            }
+        }
+        if let Some(toplevel) = toplevel {
+            if !toplevel.configured.replace(true) {
+                // The client knows its output once it has acknowledged the first configure.
+                self.outputs.retain(|o| o.as_ref().is_alive());
+                for output in &self.outputs {
+                    if output.as_ref().same_client_as(surface.as_ref()) {
+                        surface.enter(output);
+                    }
+                }
+                self.configure(&toplevel, None);
+            }
+        }
+        let time = self.time();
+        for callback in callbacks {
+            callback.done(time);
+        }
+    }
+
+    fn create_xdg_surface(
+        &mut self,
+        xdg_surface: Main<xdg_surface::XdgSurface>,
+        surface: wl_surface::WlSurface,
+    ) {
+        xdg_surface.quick_assign(move |xdg_surface, req, mut ddata| {
+            let c = compositor!(ddata);
+            match req {
+                xdg_surface::Request::GetToplevel { id } => {
+                    c.create_toplevel(id, &xdg_surface, &surface)
+                }
+                xdg_surface::Request::SetWindowGeometry { width, height, .. } => {
+                    c.set_geometry(&surface, width, height)
+                }
+                xdg_surface::Request::AckConfigure { .. } => c.ack_configure(&surface),
+                _ => {}
+            }
+        });
+    }
+
+    fn create_toplevel(
+        &mut self,
+        toplevel: Main<xdg_toplevel::XdgToplevel>,
+        xdg_surface: &xdg_surface::XdgSurface,
+        surface: &wl_surface::WlSurface,
+    ) {
+        let id = self.next_window_id;
+        self.next_window_id += 1;
+        toplevel.as_ref().user_data().set(|| id);
+        toplevel.quick_assign(move |_, req, mut ddata| {
+            let c = compositor!(ddata);
+            if let Some(window) = c.window(id) {
+                c.handle_toplevel_request(&window, req);
+            }
+        });
+        toplevel.assign_destructor(Filter::new(
+            move |_: Resource<xdg_toplevel::XdgToplevel>, _, mut ddata| {
+                compositor!(ddata).remove_toplevel(id);
+            },
+        ));
+        surface_data(surface).borrow_mut().toplevel = Some(id);
+        self.windows.push(Rc::new(Toplevel {
+            id,
+            client: client_id(toplevel.as_ref()).unwrap_or(0),
+            surface: surface.clone(),
+            xdg_surface: xdg_surface.clone(),
+            toplevel: (*toplevel).clone(),
+            props: Default::default(),
+            configured: Cell::new(false),
+            saved_position: Cell::new(None),
+        }));
+    }
+
+    fn handle_toplevel_request(&mut self, window: &Rc<Toplevel>, req: xdg_toplevel::Request) {
+        let mut props = window.props.lock();
+        let size = |width: i32, height: i32| match (width, height) {
+            (0, 0) => None,
+            _ => Some((width as u32, height as u32)),
+        };
+        match req {
+            xdg_toplevel::Request::SetTitle { title } => props.title = Some(title),
+            xdg_toplevel::Request::SetMinSize { width, height } => {
+                props.min_size = size(width, height)
+            }
+            xdg_toplevel::Request::SetMaxSize { width, height } => {
+                props.max_size = size(width, height)
+            }
+            xdg_toplevel::Request::SetMinimized => props.minimized = true,
+            xdg_toplevel::Request::SetMaximized => {
+                drop(props);
+                self.set_state(window, |p| p.maximized = true);
+                return;
+            }
+            xdg_toplevel::Request::UnsetMaximized => {
+                drop(props);
+                self.set_state(window, |p| p.maximized = false);
+                return;
+            }
+            xdg_toplevel::Request::SetFullscreen { .. } => {
+                drop(props);
+                self.set_state(window, |p| p.fullscreen = true);
+                return;
+            }
+            xdg_toplevel::Request::UnsetFullscreen => {
+                drop(props);
+                self.set_state(window, |p| p.fullscreen = false);
+                return;
+            }
+            xdg_toplevel::Request::Move { .. } => {
+                if self.seat.buttons.is_empty() {
+                    return;
+                }
+                props.dragging = true;
+                self.seat.drag = Some(Drag {
+                    window: window.clone(),
+                    cursor: self.seat.cursor,
+                    position: (props.x, props.y),
+                });
+            }
+            _ => return,
+        }
+        self.dirty = true;
+    }
+
+    /// Changes the maximized or fullscreen state of a window.
+    ///
+    /// Such windows cover the output. Their old position is restored when they return to the
+    /// normal state.
+    fn set_state<F: FnOnce(&mut WindowProps)>(&mut self, window: &Toplevel, f: F) {
+        let mut props = window.props.lock();
+        f(&mut props);
+        if props.maximized || props.fullscreen {
+            if window.saved_position.get().is_none() {
+                window.saved_position.set(Some((props.x, props.y)));
+            }
+            props.x = 0;
+            props.y = 0;
+        } else if let Some((x, y)) = window.saved_position.take() {
+            props.x = x;
+            props.y = y;
+        }
+        drop(props);
+        self.dirty = true;
+        if window.configured.get() {
+            self.configure(window, None);
+        }
+        self.update_pointer();
+    }
+
+    fn configure(&mut self, window: &Toplevel, size: Option<(i32, i32)>) {
+        let props = window.props.lock();
+        let (width, height) = if props.maximized || props.fullscreen {
+            (OUTPUT_WIDTH, OUTPUT_HEIGHT)
+        } else {
+            size.unwrap_or((0, 0))
+        };
+        let mut states = vec![];
+        if props.maximized {
+            states.push(xdg_toplevel::State::Maximized as u32);
+        }
+        if props.fullscreen {
+            states.push(xdg_toplevel::State::Fullscreen as u32);
+        }
+        drop(props);
+        let states = states.iter().flat_map(|s| s.to_ne_bytes()).collect();
+        window.toplevel.configure(width, height, states);
+        let serial = self.serial();
+        window.xdg_surface.configure(serial);
+    }
+
+    fn set_geometry(&mut self, surface: &wl_surface::WlSurface, width: i32, height: i32) {
+        let window = match surface_data(surface).borrow().toplevel {
+            Some(id) => self.window(id),
+            None => None,
+        };
+        if let Some(window) = window {
+            let mut props = window.props.lock();
+            props.width = width as u32;
+            props.height = height as u32;
+            drop(props);
+            self.dirty = true;
+            self.update_pointer();
+        }
+    }
+
+    fn ack_configure(&mut self, surface: &wl_surface::WlSurface) {
+        let window = match surface_data(surface).borrow().toplevel {
+            Some(id) => self.window(id),
+            None => None,
+        };
+        if let Some(window) = window {
+            let mut props = window.props.lock();
+            if props.mapped {
+                return;
+            }
+            props.mapped = true;
+            drop(props);
+            self.dirty = true;
+            self.update_pointer();
+        }
+    }
+
+    fn remove_toplevel(&mut self, id: u32) {
+        let window = match self.windows.iter().position(|w| w.id == id) {
+            Some(pos) => self.windows.remove(pos),
+            None => return,
+        };
+        window.props.lock().mapped = false;
+        let is = |w: &Option<Rc<Toplevel>>| matches!(w, Some(w) if w.id == id);
+        if is(&self.seat.keyboard_focus) {
+            self.seat.keyboard_focus = None;
+        }
+        if is(&self.seat.pointer_focus) {
+            self.seat.pointer_focus = None;
+        }
+        if matches!(&self.seat.drag, Some(d) if d.window.id == id) {
+            self.seat.drag = None;
+        }
+        self.seat.touch_points.retain(|_, w| w.id != id);
+        self.dirty = true;
+        self.update_pointer();
+    }
+
+    fn create_decoration(
+        &mut self,
+        decoration: Main<zxdg_toplevel_decoration_v1::ZxdgToplevelDecorationV1>,
+        toplevel: xdg_toplevel::XdgToplevel,
+    ) {
+        use zxdg_toplevel_decoration_v1::{Mode, Request};
+        let window = match toplevel.as_ref().user_data().get::<u32>() {
+            Some(&id) => id,
+            None => return,
+        };
+        decoration.quick_assign(move |decoration, req, mut ddata| {
+            let c = compositor!(ddata);
+            let mode = match req {
+                Request::SetMode { mode } => mode,
+                Request::UnsetMode => Mode::ServerSide,
+                _ => return,
+            };
+            decoration.configure(mode);
+            if let Some(window) = c.window(window) {
+                window.props.lock().decorations = mode == Mode::ServerSide;
+                c.dirty = true;
+                if window.configured.get() {
+                    c.configure(&window, None);
+                }
+            }
+        });
+        decoration.configure(Mode::ServerSide);
+        if let Some(window) = self.window(window) {
+            window.props.lock().decorations = true;
+            self.dirty = true;
+        }
+    }
+
+    fn create_keyboard(&mut self, keyboard: Main<wl_keyboard::WlKeyboard>) {
+        keyboard.quick_assign(|_, _, _| ());
+        self.send_keymap(&keyboard);
+        if keyboard.as_ref().version() >= 4 {
+            // Repeated keys would make the events of the tests unpredictable.
+            keyboard.repeat_info(0, 0);
+        }
+        self.seat.keyboards.push((*keyboard).clone());
+    }
+
+    fn create_data_device(&mut self, device: Main<wl_data_device::WlDataDevice>) {
+        device.quick_assign(|device, req, mut ddata| {
+            let c = compositor!(ddata);
+            match req {
+                wl_data_device::Request::SetSelection { source, .. } => c.set_selection(source),
+                wl_data_device::Request::Release => {
+                    c.seat.data_devices.retain(|d| *d != *device);
+                }
+                _ => {}
+            }
+        });
+        self.offer_selection(&device);
+        self.seat.data_devices.push((*device).clone());
+    }
+
+    /// Offers the clipboard to a data device. Unlike real compositors, the clipboard is offered
+    /// to all clients and not just to the one with keyboard focus.
+    fn offer_selection(&self, device: &wl_data_device::WlDataDevice) {
+        let source = match &self.seat.selection {
+            Some(source) => source.clone(),
+            None => return device.selection(None),
+        };
+        let offer = match device.as_ref().client().and_then(|client| {
+            client.create_resource::<wl_data_offer::WlDataOffer>(device.as_ref().version())
+        }) {
+            Some(offer) => offer,
+            None => return,
+        };
+        let mime_types = mime_types(source.as_ref()).borrow().clone();
+        offer.quick_assign(move |_, req, _| {
+            if let wl_data_offer::Request::Receive { mime_type, fd } = req {
+                send_selection(source.as_ref(), fd, |fd| source.send(mime_type, fd));
+            }
+        });
+        device.data_offer(&offer);
+        for mime_type in mime_types {
+            offer.offer(mime_type);
+        }
+        device.selection(Some(&offer));
+    }
+
+    fn set_selection(&mut self, source: Option<wl_data_source::WlDataSource>) {
+        let old = mem::replace(&mut self.seat.selection, source);
+        for device in &self.seat.data_devices {
+            self.offer_selection(device);
+        }
+        // The previous owner learns about the new selection before it is cancelled.
+        if let Some(old) = old {
+            if self.seat.selection.as_ref() != Some(&old) {
+                old.cancelled();
+            }
+        }
+    }
+
+    fn create_primary_selection_device(
+        &mut self,
+        device: Main<zwp_primary_selection_device_v1::ZwpPrimarySelectionDeviceV1>,
+    ) {
+        device.quick_assign(|device, req, mut ddata| {
+            let c = compositor!(ddata);
+            match req {
+                zwp_primary_selection_device_v1::Request::SetSelection { source, .. } => {
+                    c.set_primary_selection(source)
+                }
+                zwp_primary_selection_device_v1::Request::Destroy => {
+                    c.seat.primary_selection_devices.retain(|d| *d != *device);
+                }
+                _ => {}
+            }
+        });
+        self.offer_primary_selection(&device);
+        self.seat.primary_selection_devices.push((*device).clone());
+    }
+
+    /// Offers the primary selection to a device. Like the clipboard, it is offered to all
+    /// clients.
+    fn offer_primary_selection(
+        &self,
+        device: &zwp_primary_selection_device_v1::ZwpPrimarySelectionDeviceV1,
+    ) {
+        let source = match &self.seat.primary_selection {
+            Some(source) => source.clone(),
+            None => return device.selection(None),
+        };
+        let offer = match device.as_ref().client().and_then(|client| {
+            client.create_resource::<zwp_primary_selection_offer_v1::ZwpPrimarySelectionOfferV1>(
+                device.as_ref().version(),
+            )
+        }) {
+            Some(offer) => offer,
+            None => return,
+        };
+        let mime_types = mime_types(source.as_ref()).borrow().clone();
+        offer.quick_assign(move |_, req, _| {
+            if let zwp_primary_selection_offer_v1::Request::Receive { mime_type, fd } = req {
+                send_selection(source.as_ref(), fd, |fd| source.send(mime_type, fd));
+            }
+        });
+        device.data_offer(&offer);
+        for mime_type in mime_types {
+            offer.offer(mime_type);
+        }
+        device.selection(Some(&offer));
+    }
+
+    fn set_primary_selection(
+        &mut self,
+        source: Option<zwp_primary_selection_source_v1::ZwpPrimarySelectionSourceV1>,
+    ) {
+        let old = mem::replace(&mut self.seat.primary_selection, source);
+        for device in &self.seat.primary_selection_devices {
+            self.offer_primary_selection(device);
+        }
+        if let Some(old) = old {
+            if self.seat.primary_selection.as_ref() != Some(&old) {
+                old.cancelled();
+            }
+        }
+    }
+
+    fn send_keymap(&self, keyboard: &wl_keyboard::WlKeyboard) {
+        let text = &self.keymaps.keymaps[self.seat.keymap].text;
+        let fd = memfd_create("keymap", MFD_CLOEXEC).unwrap();
+        uapi::write(fd.raw(), text.as_bytes()).unwrap();
+        uapi::write(fd.raw(), &[0u8]).unwrap();
+        // The fd is closed after it has been sent.
+        keyboard.keymap(
+            wl_keyboard::KeymapFormat::XkbV1,
+            fd.unwrap(),
+            text.len() as u32 + 1,
+        );
+    }
+
+    /// The objects of the seat that belong to the client of `window`.
+    fn objects<'a, I>(objects: &'a [I], window: &'a Toplevel) -> impl Iterator<Item = &'a I>
+    where
+        I: Interface + AsRef<Resource<I>> + From<Resource<I>>,
+    {
+        objects.iter().filter(move |o| {
+            let o = o.as_ref();
+            o.is_alive() && o.same_client_as(window.surface.as_ref())
+        })
+    }
+
+    fn window_at(&self, x: i32, y: i32) -> Option<Rc<Toplevel>> {
+        self.windows
+            .iter()
+            .rev()
+            .find(|w| {
+                let props = w.props.lock();
+                props.mapped
+                    && x >= props.x
+                    && y >= props.y
+                    && x < props.x + props.width as i32
+                    && y < props.y + props.height as i32
+            })
+            .cloned()
+    }
+
+    fn local_position(window: &Toplevel, (x, y): (i32, i32)) -> (i32, i32) {
+        let props = window.props.lock();
+        (x - props.x, y - props.y)
+    }
+
+    /// Updates the pointer focus after the cursor or a window has changed.
+    fn update_pointer(&mut self) {
+        let cursor = self.seat.cursor;
+        if let Some(drag) = &self.seat.drag {
+            // The window follows the cursor while it is being dragged.
+            let mut props = drag.window.props.lock();
+            props.x = drag.position.0 + cursor.0 - drag.cursor.0;
+            props.y = drag.position.1 + cursor.1 - drag.cursor.1;
+            self.dirty = true;
+            return;
+        }
+        let grabbed = !self.seat.buttons.is_empty() && self.seat.pointer_focus.is_some();
+        let focus = match grabbed {
+            true => self.seat.pointer_focus.clone(),
+            false => self.window_at(cursor.0, cursor.1),
+        };
+        let same = match (&focus, &self.seat.pointer_focus) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        if !same {
+            if let Some(old) = self.seat.pointer_focus.take() {
+                let serial = self.serial();
+                for pointer in Self::objects(&self.seat.pointers, &old) {
+                    pointer.leave(serial, &old.surface);
+                    frame(pointer);
+                }
+            }
+            if let Some(new) = &focus {
+                let serial = self.serial();
+                let (x, y) = Self::local_position(new, cursor);
+                for pointer in Self::objects(&self.seat.pointers, new) {
+                    pointer.enter(serial, &new.surface, x as f64, y as f64);
+                    frame(pointer);
+                }
+                self.seat.pointer_position = (x, y);
+            }
+            self.seat.pointer_focus = focus;
+        } else if let Some(focus) = &focus {
+            let position = Self::local_position(focus, cursor);
+            if position != self.seat.pointer_position {
+                self.seat.pointer_position = position;
+                let time = self.time();
+                for pointer in Self::objects(&self.seat.pointers, focus) {
+                    pointer.motion(time, position.0 as f64, position.1 as f64);
+                    frame(pointer);
+                }
+            }
+        }
+    }
+
+    pub fn set_cursor_position(&mut self, x: i32, y: i32) {
+        self.seat.cursor = (x.clamp(0, OUTPUT_WIDTH - 1), y.clamp(0, OUTPUT_HEIGHT - 1));
+        self.update_pointer();
+    }
+
+    pub fn cursor_position(&self) -> (i32, i32) {
+        self.seat.cursor
+    }
+
+    pub fn move_pointer(&mut self, dx: i32, dy: i32) {
+        let (x, y) = self.seat.cursor;
+        self.set_cursor_position(x + dx, y + dy);
+    }
+
+    pub fn button(&mut self, button: u32, pressed: bool) {
+        if !update_count(&mut self.seat.buttons, button, pressed) {
+            return;
+        }
+        if let Some(focus) = self.seat.pointer_focus.clone() {
+            let serial = self.serial();
+            let time = self.time();
+            let state = match pressed {
+                true => wl_pointer::ButtonState::Pressed,
+                false => wl_pointer::ButtonState::Released,
+            };
+            for pointer in Self::objects(&self.seat.pointers, &focus) {
+                pointer.button(serial, time, button, state);
+                frame(pointer);
+            }
+        }
+        if self.seat.buttons.is_empty() {
+            if let Some(drag) = self.seat.drag.take() {
+                drag.window.props.lock().dragging = false;
+                self.dirty = true;
+            }
+            self.update_pointer();
+        }
+    }
+
+    pub fn scroll(&mut self, dx: i32, dy: i32) {
+        let focus = match self.seat.pointer_focus.clone() {
+            Some(focus) => focus,
+            None => return,
+        };
+        let time = self.time();
+        // Scrolling down is positive on Wayland and negative in the tests.
+        let axes = [
+            (wl_pointer::Axis::HorizontalScroll, dx),
+            (wl_pointer::Axis::VerticalScroll, -dy),
+        ];
+        for pointer in Self::objects(&self.seat.pointers, &focus) {
+            if pointer.as_ref().version() >= 5 {
+                pointer.axis_source(wl_pointer::AxisSource::Wheel);
+            }
+            for &(axis, discrete) in &axes {
+                if discrete == 0 {
+                    continue;
+                }
+                if pointer.as_ref().version() >= 5 {
+                    pointer.axis_discrete(axis, discrete);
+                }
+                pointer.axis(time, axis, discrete as f64 * 15.0);
+            }
+            frame(pointer);
+        }
+    }
+
+    pub fn focus(&mut self, window: Option<u32>) {
+        let window = window.and_then(|id| self.window(id));
+        let same = match (&window, &self.seat.keyboard_focus) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        if same {
+            return;
+        }
+        if let Some(old) = self.seat.keyboard_focus.take() {
+            let serial = self.serial();
+            for keyboard in Self::objects(&self.seat.keyboards, &old) {
+                keyboard.leave(serial, &old.surface);
+            }
+        }
+        if let Some(new) = &window {
+            let serial = self.serial();
+            let keys: Vec<u8> = self
+                .seat
+                .keys
+                .keys()
+                .flat_map(|k| k.to_ne_bytes())
+                .collect();
+            for keyboard in Self::objects(&self.seat.keyboards, new) {
+                keyboard.enter(serial, &new.surface, keys.clone());
+            }
+            self.seat.keyboard_focus = window;
+            self.send_modifiers();
+        }
+    }
+
+    fn send_modifiers(&mut self) {
+        if let Some(focus) = self.seat.keyboard_focus.clone() {
+            let serial = self.serial();
+            for keyboard in Self::objects(&self.seat.keyboards, &focus) {
+                keyboard.modifiers(
+                    serial,
+                    self.seat.depressed,
+                    0,
+                    self.seat.locked,
+                    self.seat.group,
+                );
+            }
+        }
+    }
+
+    /// The keysym of a key in the first level of the current group.
+    fn keysym(&self, key: u32) -> u32 {
+        let groups = &self.keymaps.keymaps[self.seat.keymap].groups;
+        let group = groups.get(self.seat.group as usize).unwrap_or(&groups[0]);
+        group
+            .get(&key)
+            .and_then(|s| s.first())
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn key(&mut self, key: u32, pressed: bool) {
+        if !update_count(&mut self.seat.keys, key, pressed) {
+            return;
+        }
+        if let Some(focus) = self.seat.keyboard_focus.clone() {
+            let serial = self.serial();
+            let time = self.time();
+            let state = match pressed {
+                true => wl_keyboard::KeyState::Pressed,
+                false => wl_keyboard::KeyState::Released,
+            };
+            for keyboard in Self::objects(&self.seat.keyboards, &focus) {
+                keyboard.key(serial, time, key, state);
+            }
+        }
+        let old = (self.seat.depressed, self.seat.locked);
+        if let Action::LockMods(mods) = keymap::action(self.keysym(key)) {
+            if pressed {
+                if self.seat.locked & mods == mods {
+                    self.seat.unlock_on_release.insert(key, mods);
+                }
+                self.seat.locked |= mods;
+            } else if let Some(mods) = self.seat.unlock_on_release.remove(&key) {
+                self.seat.locked &= !mods;
+            }
+        }
+        let mut depressed = 0;
+        for &key in self.seat.keys.keys() {
+            match keymap::action(self.keysym(key)) {
+                Action::SetMods(mods) | Action::LockMods(mods) => depressed |= mods,
+                Action::None => {}
+            }
+        }
+        self.seat.depressed = depressed;
+        if (self.seat.depressed, self.seat.locked) != old {
+            self.send_modifiers();
+        }
+    }
+
+    pub fn set_layout(&mut self, layout: crate::keyboard::Layout) {
+        let (keymap, group) = keymap::position(layout);
+        if keymap != self.seat.keymap {
+            self.seat.keymap = keymap;
+            self.seat.keyboards.retain(|k| k.as_ref().is_alive());
+            for keyboard in &self.seat.keyboards {
+                self.send_keymap(keyboard);
+            }
+        }
+        self.seat.group = group;
+        self.send_modifiers();
+    }
+
+    pub fn touch_down(&mut self, x: i32, y: i32) -> i32 {
+        let id = self.seat.next_touch_id;
+        self.seat.next_touch_id += 1;
+        if let Some(window) = self.window_at(x, y) {
+            let serial = self.serial();
+            let time = self.time();
+            let (x, y) = Self::local_position(&window, (x, y));
+            for touch in Self::objects(&self.seat.touches, &window) {
+                touch.down(serial, time, &window.surface, id, x as f64, y as f64);
+                touch.frame();
+            }
+            self.seat.touch_points.insert(id, window);
+        }
+        id
+    }
+
+    pub fn touch_move(&mut self, id: i32, x: i32, y: i32) {
+        if let Some(window) = self.seat.touch_points.get(&id).cloned() {
+            let time = self.time();
+            let (x, y) = Self::local_position(&window, (x, y));
+            for touch in Self::objects(&self.seat.touches, &window) {
+                touch.motion(time, id, x as f64, y as f64);
+                touch.frame();
+            }
+        }
+    }
+
+    pub fn touch_up(&mut self, id: i32) {
+        if let Some(window) = self.seat.touch_points.remove(&id) {
+            let serial = self.serial();
+            let time = self.time();
+            for touch in Self::objects(&self.seat.touches, &window) {
+                touch.up(serial, time, id);
+                touch.frame();
+            }
+        }
+    }
+
+    pub fn close(&mut self, window: u32) {
+        if let Some(window) = self.window(window) {
+            window.toplevel.close();
+        }
+    }
+
+    pub fn request_redraw(&mut self, window: u32) {
+        if let Some(window) = self.window(window) {
+            self.configure(&window, None);
+        }
+    }
+
+    pub fn set_inner_size(&mut self, window: u32, width: u32, height: u32) {
+        if let Some(window) = self.window(window) {
+            self.configure(&window, Some((width as i32, height as i32)));
+        }
+    }
+
+    pub fn set_outer_position(&mut self, window: u32, x: i32, y: i32) {
+        if let Some(window) = self.window(window) {
+            let mut props = window.props.lock();
+            props.x = x;
+            props.y = y;
+            drop(props);
+            self.dirty = true;
+            self.update_pointer();
+        }
+    }
+
+    /// Draws the windows into a BGRA image of the output.
+    pub fn screenshot(&self) -> Vec<u8> {
+        let stride = OUTPUT_WIDTH as usize * 4;
+        let mut image = vec![0; stride * OUTPUT_HEIGHT as usize];
+        for window in &self.windows {
+            let props = window.props.lock();
+            if !props.mapped {
+                continue;
+            }
+            for y in 0..props.height as i32 {
+                for x in 0..props.width as i32 {
+                    let (ox, oy) = (props.x + x, props.y + y);
+                    if ox < 0 || oy < 0 || ox >= OUTPUT_WIDTH || oy >= OUTPUT_HEIGHT {
+                        continue;
+                    }
+                    let (r, g, b) = pixel(&props, x as u32, y as u32);
+                    let pos = oy as usize * stride + ox as usize * 4;
+                    image[pos..pos + 4].copy_from_slice(&[b, g, r, 255]);
+                }
+            }
+        }
+        image
+    }
+}
+
+/// The RGB color of a pixel of a window.
+pub fn pixel(props: &WindowProps, x: u32, y: u32) -> (u8, u8, u8) {
+    if let Some(frame) = &props.frame {
+        if x < frame.width && y < frame.height {
+            let pos = (y * frame.width + x) as usize * 4;
+            let data = &frame.data[pos..pos + 4];
+            return (data[2], data[1], data[0]);
+        }
+    }
+    props.background
+}
+
+fn frame(pointer: &wl_pointer::WlPointer) {
+    if pointer.as_ref().version() >= 5 {
+        pointer.frame();
+    }
+}
+
+/// Counts a press or release and returns whether the key changed its state.
+fn update_count(counts: &mut HashMap<u32, usize>, key: u32, pressed: bool) -> bool {
+    if pressed {
+        let count = counts.entry(key).or_default();
+        *count += 1;
+        *count == 1
+    } else {
+        match counts.get_mut(&key) {
+            Some(count) if *count > 1 => {
+                *count -= 1;
+                false
+            }
+            Some(_) => {
+                counts.remove(&key);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// The MIME types offered by a selection source.
+fn mime_types<I>(source: &Resource<I>) -> &RefCell<Vec<String>>
+where
+    I: Interface + AsRef<Resource<I>> + From<Resource<I>>,
+{
+    source.user_data().get().unwrap()
+}
+
+/// Forwards a request for the contents of a selection to its source.
+fn send_selection<I, F>(source: &Resource<I>, fd: i32, send: F)
+where
+    I: Interface + AsRef<Resource<I>> + From<Resource<I>>,
+    F: FnOnce(i32),
+{
+    // The fd is duplicated when it is sent. Closing our copy makes sure that the receiver sees
+    // the end of the data once the source has closed its copy.
+    let fd = OwnedFd::new(fd);
+    if source.is_alive() {
+        send(fd.raw());
+    }
+}
+
+fn create_data_source(source: Main<wl_data_source::WlDataSource>) {
+    source
+        .as_ref()
+        .user_data()
+        .set(|| RefCell::new(Vec::<String>::new()));
+    source.quick_assign(|source, req, mut ddata| match req {
+        wl_data_source::Request::Offer { mime_type } => {
+            mime_types(source.as_ref()).borrow_mut().push(mime_type)
+        }
+        wl_data_source::Request::Destroy => {
+            let c = compositor!(ddata);
+            if c.seat.selection.as_ref() == Some(&*source) {
+                c.seat.selection = None;
+                c.set_selection(None);
+            }
+        }
+        _ => {}
+    });
+}
+
+fn create_primary_selection_source(
+    source: Main<zwp_primary_selection_source_v1::ZwpPrimarySelectionSourceV1>,
+) {
+    source
+        .as_ref()
+        .user_data()
+        .set(|| RefCell::new(Vec::<String>::new()));
+    source.quick_assign(|source, req, mut ddata| match req {
+        zwp_primary_selection_source_v1::Request::Offer { mime_type } => {
+            mime_types(source.as_ref()).borrow_mut().push(mime_type)
+        }
+        zwp_primary_selection_source_v1::Request::Destroy => {
+            let c = compositor!(ddata);
+            if c.seat.primary_selection.as_ref() == Some(&*source) {
+                c.seat.primary_selection = None;
+                c.set_primary_selection(None);
+            }
+        }
+        _ => {}
+    });
+}
+
+fn create_pool(pool: Main<wl_shm_pool::WlShmPool>, fd: OwnedFd, size: usize) {
+    let memory = Rc::new(Pool::new(fd, size));
+    pool.quick_assign(move |_, req, _| match req {
+        wl_shm_pool::Request::CreateBuffer {
+            id,
+            offset,
+            width,
+            height,
+            stride,
+            ..
+        } => {
+            id.quick_assign(|_, _, _| ());
+            let memory = memory.clone();
+            id.as_ref().user_data().set(move || ShmBuffer {
+                pool: memory,
+                offset: offset as usize,
+                width: width as u32,
+                height: height as u32,
+                stride: stride as usize,
+            });
+        }
+        wl_shm_pool::Request::Resize { size } => memory.resize(size as usize),
+        _ => {}
+    });
+}
+
+/// Copies the damaged parts of a buffer into the frame of a window.
+fn create_viewport(viewport: Main<wp_viewport::WpViewport>, surface: wl_surface::WlSurface) {
+    viewport.quick_assign(move |_, req, _| {
+        let destination = match req {
+            wp_viewport::Request::SetDestination { width, height } if width > 0 => {
+                Some((width as u32, height as u32))
+            }
+            wp_viewport::Request::SetDestination { .. } | wp_viewport::Request::Destroy => None,
+            _ => return,
+        };
+        surface_data(&surface).borrow_mut().viewport = Some(destination);
+    });
+}
+
+fn copy_buffer(
+    props: &mut WindowProps,
+    buffer: &wl_buffer::WlBuffer,
+    damage: &[(i32, i32, i32, i32)],
+) {
+    let buffer = match buffer.as_ref().user_data().get::<ShmBuffer>() {
+        Some(buffer) => buffer,
+        None => return,
+    };
+    let (width, height) = (buffer.width, buffer.height);
+    let resized = match &props.frame {
+        Some(frame) => frame.width != width || frame.height != height,
+        None => true,
+    };
+    let full = [(0, 0, width as i32, height as i32)];
+    let damage = match resized {
+        true => &full[..],
+        false => damage,
+    };
+    if resized {
+        props.frame = Some(Frame {
+            width,
+            height,
+            data: vec![0; (width * height * 4) as usize],
+        });
+    }
+    let frame = props.frame.as_mut().unwrap();
+    let data = buffer.pool.data();
+    for &(x, y, w, h) in damage {
+        let x1 = x.max(0) as u32;
+        let y1 = y.max(0) as u32;
+        let x2 = (x.saturating_add(w).max(0) as u32).min(width);
+        let y2 = (y.saturating_add(h).max(0) as u32).min(height);
+        if x1 >= x2 {
+            continue;
+        }
+        for row in y1..y2 {
+            let src = buffer.offset + row as usize * buffer.stride + x1 as usize * 4;
+            let dst = (row * width + x1) as usize * 4;
+            let len = (x2 - x1) as usize * 4;
+            frame.data[dst..dst + len].copy_from_slice(&data[src..src + len]);
+        }
+    }
+}
@@ -1,8 +1,9 @@
 use super::XInstanceData;
 use crate::backends::x11::XConnection;
+use std::cell::RefCell;
 use std::future::Future;
-use std::path::{Path, PathBuf};
 use std::ptr;
+use std::rc::Rc;
 use std::sync::Arc;
 use tokio::io::unix::AsyncFd;
 use tokio::io::Interest;
@@ -21,7 +22,7 @@ pub(super) enum DndMsg {
 pub(super) fn run(
     instance: Arc<XInstanceData>,
     rx: UnboundedReceiver<DndMsg>,
-    path: &Path,
+    offers: Vec<(ffi::xcb_atom_t, Vec<u8>)>,
 ) -> impl Future<Output = ()> {
     unsafe {
         let c = XConnection::new(&instance.backend, instance.display);
@@ -50,6 +51,7 @@ pub(super) fn run(
         if let Err(e) = c.errors.check_cookie(xcb, cookie) {
             panic!("Could not take ownership of XdndSelection: {}", e);
         }
+        let types: Vec<_> = offers.iter().map(|o| o.0).collect();
         let cookie = xcb.xcb_change_property_checked(
             c.c,
             ffi::XCB_PROP_MODE_REPLACE as _,
@@ -57,8 +59,8 @@ pub(super) fn run(
             instance.atoms.x_dnd_type_list,
             ffi::XCB_ATOM_ATOM,
             32,
-            1,
-            &instance.atoms.uri_list as *const _ as _,
+            types.len() as _,
+            types.as_ptr() as _,
         );
         if let Err(e) = c.errors.check_cookie(xcb, cookie) {
             panic!("Could set XdndTypeList on {}: {}", window_id, e);
@@ -73,7 +75,7 @@ pub(super) fn run(
             drop: false,
             dropped: false,
             accept: None,
-            path: path.to_owned(),
+            offers,
             stop: false,
         };
 
@@ -90,7 +92,7 @@ struct Dnd {
     drop: bool,
     dropped: bool,
     accept: Option<bool>,
-    path: PathBuf,
+    offers: Vec<(ffi::xcb_atom_t, Vec<u8>)>,
     stop: bool,
 }
 
@@ -336,10 +338,14 @@ impl Dnd {
     fn handle_selection_request(&mut self, event: &ffi::xcb_generic_event_t) {
         let event = unsafe { &*(event as *const _ as *const ffi::xcb_selection_request_event_t) };
         log::info!("Got selection request: {:?}", event);
-        if event.owner != self.window_id
-            || event.selection != self.instance.atoms.x_dnd_selection
-            || event.target != self.instance.atoms.uri_list
-        {
+        let data = match self.offers.iter().find(|o| o.0 == event.target) {
+            Some(o) => &o.1,
+            _ => {
+                log::warn!("Received selection request for unknown target: {:?}", event);
+                return;
+            }
+        };
+        if event.owner != self.window_id || event.selection != self.instance.atoms.x_dnd_selection {
             log::warn!("Received unexpected selection request: {:?}", event);
             return;
         }
@@ -353,17 +359,16 @@ impl Dnd {
             event.property
         };
         unsafe {
-            let path = format!("file://{}", self.path.display());
             let xcb = &self.instance.backend.xcb;
             let cookie = xcb.xcb_change_property_checked(
                 self.c.c,
                 ffi::XCB_PROP_MODE_REPLACE as _,
                 event.requestor,
                 property,
-                self.instance.atoms.uri_list,
+                event.target,
                 8,
-                path.len() as _,
-                path.as_ptr() as _,
+                data.len() as _,
+                data.as_ptr() as _,
             );
             if let Err(e) = self.c.errors.check_cookie(xcb, cookie) {
                 log::warn!("Could not set property on {}: {}", event.requestor, e);
@@ -392,6 +397,10 @@ impl Dnd {
 
     fn handle_client_message(&mut self, event: &ffi::xcb_generic_event_t) {
         let event = unsafe { &*(event as *const _ as *const ffi::xcb_client_message_event_t) };
+        if event.type_ == self.instance.atoms.x_dnd_finished && self.dropped {
+            log::info!("Drop finished: {:?}", event);
+            return;
+        }
         if event.type_ != self.instance.atoms.x_dnd_status {
             log::warn!("Received unexpected client message: {:?}", event);
             return;
@@ -410,7 +419,8 @@ impl Dnd {
         }
         let accept_was_none = self.accept.is_none();
         self.accept = Some(data[1] & 1 == 1);
-        if data[4] != self.instance.atoms.x_dnd_action_copy
+        if self.accept == Some(true)
+            && data[4] != self.instance.atoms.x_dnd_action_copy
             && data[4] != self.instance.atoms.x_dnd_action_private
         {
             log::warn!("Unexpected dnd action: {}", data[4]);
@@ -420,3 +430,241 @@ impl Dnd {
         }
     }
 }
+
+#[derive(Debug, PartialEq)]
+pub(super) enum DndTargetMsg {
+    Stop,
+}
+
+/// Creates an XDND-aware window that accepts drops of data offered as `mime_type` and stores
+/// the dropped data in `data`.
+pub(super) fn run_target(
+    instance: Arc<XInstanceData>,
+    rx: UnboundedReceiver<DndTargetMsg>,
+    mime_type: ffi::xcb_atom_t,
+    (x, y, width, height): (i16, i16, u16, u16),
+    data: Rc<RefCell<Option<Vec<u8>>>>,
+) -> impl Future<Output = ()> {
+    unsafe {
+        let c = XConnection::new(&instance.backend, instance.display);
+        let xcb = &instance.backend.xcb;
+        let window_id = xcb.xcb_generate_id(c.c);
+        let values = ffi::xcb_create_window_value_list_t {
+            override_redirect: 1,
+            ..Default::default()
+        };
+        let cookie = xcb.xcb_create_window_aux_checked(
+            c.c,
+            0,
+            window_id,
+            c.screen.root,
+            x,
+            y,
+            width,
+            height,
+            0,
+            ffi::XCB_WINDOW_CLASS_INPUT_OUTPUT as _,
+            0,
+            ffi::XCB_CW_OVERRIDE_REDIRECT,
+            &values,
+        );
+        if let Err(e) = c.errors.check_cookie(xcb, cookie) {
+            panic!("Could not create dnd target window: {}", e);
+        }
+        let version = 5u32;
+        let cookie = xcb.xcb_change_property_checked(
+            c.c,
+            ffi::XCB_PROP_MODE_REPLACE as _,
+            window_id,
+            instance.atoms.x_dnd_aware,
+            ffi::XCB_ATOM_ATOM,
+            32,
+            1,
+            &version as *const _ as _,
+        );
+        if let Err(e) = c.errors.check_cookie(xcb, cookie) {
+            panic!("Could not set XdndAware on {}: {}", window_id, e);
+        }
+        let cookie = xcb.xcb_map_window_checked(c.c, window_id);
+        if let Err(e) = c.errors.check_cookie(xcb, cookie) {
+            panic!("Could not map dnd target window: {}", e);
+        }
+
+        let target = DndTarget {
+            c,
+            rx,
+            instance,
+            window_id,
+            mime_type,
+            source: None,
+            offered: false,
+            data,
+            stop: false,
+        };
+
+        target.run()
+    }
+}
+
+struct DndTarget {
+    c: XConnection,
+    rx: UnboundedReceiver<DndTargetMsg>,
+    instance: Arc<XInstanceData>,
+    window_id: ffi::xcb_window_t,
+    mime_type: ffi::xcb_atom_t,
+    source: Option<ffi::xcb_window_t>,
+    offered: bool,
+    data: Rc<RefCell<Option<Vec<u8>>>>,
+    stop: bool,
+}
+
+impl DndTarget {
+    async fn run(mut self) {
+        let fd = AsyncFd::with_interest(self.c.fd, Interest::READABLE).unwrap();
+        while !self.stop {
+            self.handle_events();
+            tokio::select! {
+                guard = fd.readable() => {
+                    guard.unwrap().clear_ready();
+                }
+                msg = self.rx.recv() => {
+                    match msg {
+                        Some(DndTargetMsg::Stop) | None => self.stop = true,
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_events(&mut self) {
+        unsafe {
+            loop {
+                let event = self.instance.backend.xcb.xcb_poll_for_event(self.c.c);
+                let event = match self.c.errors.check_val(&self.instance.backend.xcb, event) {
+                    Ok(e) => e,
+                    Err(e) => {
+                        if matches!(e.ty, XcbErrorType::MissingReply) {
+                            break;
+                        }
+                        panic!("The connection is in error: {}", e);
+                    }
+                };
+                self.handle_event(&event);
+            }
+            self.instance.backend.xcb.xcb_flush(self.c.c);
+        }
+    }
+
+    fn handle_event(&mut self, event: &ffi::xcb_generic_event_t) {
+        match event.response_type & 0x7f {
+            ffi::XCB_CLIENT_MESSAGE => self.handle_client_message(event),
+            ffi::XCB_SELECTION_NOTIFY => self.handle_selection_notify(event),
+            _ => {
+                log::warn!("Received unexpected event: {:?}", event);
+            }
+        }
+    }
+
+    fn send_msg(&self, type_: ffi::xcb_atom_t, data32: [u32; 5]) {
+        let window = match self.source {
+            Some(w) => w,
+            _ => return,
+        };
+        let xcb = &self.instance.backend.xcb;
+        let msg = ffi::xcb_client_message_event_t {
+            response_type: ffi::XCB_CLIENT_MESSAGE,
+            format: 32,
+            window,
+            type_,
+            data: ffi::xcb_client_message_data_t { data32 },
+            ..Default::default()
+        };
+        unsafe {
+            let cookie = xcb.xcb_send_event_checked(self.c.c, 0, window, 0, &msg as *const _ as _);
+            if let Err(e) = self.c.errors.check_cookie(xcb, cookie) {
+                log::warn!("Could not send client message to {}: {}", window, e);
+            }
+        }
+    }
+
+    fn handle_client_message(&mut self, event: &ffi::xcb_generic_event_t) {
+        let event = unsafe { &*(event as *const _ as *const ffi::xcb_client_message_event_t) };
+        let data = unsafe { event.data.data32 };
+        let atoms = &self.instance.atoms;
+        if event.type_ == atoms.x_dnd_enter {
+            log::info!("Got XdndEnter: {:?}", data);
+            assert_eq!(data[1] >> 24, 5);
+            self.source = Some(data[0]);
+            self.offered = if data[1] & 1 == 1 {
+                let types = unsafe {
+                    xcb_dl_util::property::get_property::<u32>(
+                        &self.instance.backend.xcb,
+                        &self.c.errors,
+                        data[0],
+                        atoms.x_dnd_type_list,
+                        ffi::XCB_ATOM_ATOM,
+                        false,
+                        10000,
+                    )
+                };
+                types.unwrap().contains(&self.mime_type)
+            } else {
+                data[2..].contains(&self.mime_type)
+            };
+        } else if event.type_ == atoms.x_dnd_position {
+            log::info!("Got XdndPosition: {:?}", data);
+            let (accept, action) = match self.offered {
+                true => (1, atoms.x_dnd_action_copy),
+                false => (0, 0),
+            };
+            self.send_msg(atoms.x_dnd_status, [self.window_id, accept, 0, 0, action]);
+        } else if event.type_ == atoms.x_dnd_drop {
+            log::info!("Got XdndDrop: {:?}", data);
+            let xcb = &self.instance.backend.xcb;
+            unsafe {
+                let cookie = xcb.xcb_convert_selection_checked(
+                    self.c.c,
+                    self.window_id,
+                    atoms.x_dnd_selection,
+                    self.mime_type,
+                    atoms.x_dnd_selection,
+                    data[2],
+                );
+                if let Err(e) = self.c.errors.check_cookie(xcb, cookie) {
+                    panic!("Could not convert the XdndSelection: {}", e);
+                }
+            }
+        } else if event.type_ == atoms.x_dnd_leave {
+            log::info!("Got XdndLeave");
+            self.source = None;
+        } else {
+            log::warn!("Received unexpected client message: {:?}", event);
+        }
+    }
+
+    fn handle_selection_notify(&mut self, event: &ffi::xcb_generic_event_t) {
+        let event = unsafe { &*(event as *const _ as *const ffi::xcb_selection_notify_event_t) };
+        log::info!("Got selection notify: {:?}", event);
+        if event.property == 0 {
+            panic!("The XdndSelection could not be converted");
+        }
+        let data = unsafe {
+            xcb_dl_util::property::get_property::<u8>(
+                &self.instance.backend.xcb,
+                &self.c.errors,
+                self.window_id,
+                event.property,
+                self.mime_type,
+                true,
+                1000000,
+            )
+        };
+        *self.data.borrow_mut() = Some(data.unwrap());
+        let atoms = &self.instance.atoms;
+        self.send_msg(
+            atoms.x_dnd_finished,
+            [self.window_id, 1, atoms.x_dnd_action_copy, 0, 0],
+        );
+        self.source = None;
+    }
+}
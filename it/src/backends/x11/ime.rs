@@ -0,0 +1,499 @@
+use super::XInstanceData;
+use crate::backends::x11::XConnection;
+use std::cell::Cell;
+use std::future::Future;
+use std::ptr;
+use std::rc::Rc;
+use std::sync::Arc;
+use tokio::io::unix::AsyncFd;
+use tokio::io::Interest;
+use tokio::sync::mpsc::UnboundedReceiver;
+use xcb_dl::ffi;
+use xcb_dl_util::error::XcbErrorType;
+
+const XIM_CONNECT: u8 = 1;
+const XIM_CONNECT_REPLY: u8 = 2;
+const XIM_DISCONNECT: u8 = 3;
+const XIM_DISCONNECT_REPLY: u8 = 4;
+const XIM_OPEN: u8 = 30;
+const XIM_OPEN_REPLY: u8 = 31;
+const XIM_SET_EVENT_MASK: u8 = 37;
+const XIM_ENCODING_NEGOTIATION: u8 = 38;
+const XIM_ENCODING_NEGOTIATION_REPLY: u8 = 39;
+const XIM_GET_IM_VALUES: u8 = 44;
+const XIM_GET_IM_VALUES_REPLY: u8 = 45;
+const XIM_CREATE_IC: u8 = 50;
+const XIM_CREATE_IC_REPLY: u8 = 51;
+const XIM_DESTROY_IC: u8 = 52;
+const XIM_DESTROY_IC_REPLY: u8 = 53;
+const XIM_SET_IC_VALUES: u8 = 54;
+const XIM_SET_IC_VALUES_REPLY: u8 = 55;
+const XIM_FORWARD_EVENT: u8 = 60;
+const XIM_SYNC_REPLY: u8 = 62;
+const XIM_COMMIT: u8 = 63;
+const XIM_PREEDIT_START: u8 = 73;
+const XIM_PREEDIT_DRAW: u8 = 75;
+const XIM_PREEDIT_DONE: u8 = 78;
+
+const COMMIT_CHARS: u16 = 0x0002;
+
+const XIM_UNDERLINE: u32 = 0x0002;
+
+const IMID: u16 = 1;
+
+const QUERY_INPUT_STYLE: u16 = 0;
+
+const INPUT_STYLE: u16 = 0;
+const CLIENT_WINDOW: u16 = 1;
+const FOCUS_WINDOW: u16 = 2;
+const PREEDIT_ATTRIBUTES: u16 = 3;
+const SPOT_LOCATION: u16 = 4;
+
+/// XIMPreeditCallbacks | XIMStatusNothing and XIMPreeditPosition | XIMStatusNothing
+const STYLES: [u32; 2] = [0x0402, 0x0404];
+
+#[derive(Debug)]
+pub(super) enum ImeMsg {
+    Stop,
+}
+
+pub(super) fn run(
+    instance: Arc<XInstanceData>,
+    rx: UnboundedReceiver<ImeMsg>,
+    keycode: u8,
+    text: &str,
+    spot: Rc<Cell<Option<(i16, i16)>>>,
+) -> impl Future<Output = ()> {
+    unsafe {
+        let c = XConnection::new(&instance.backend, instance.display);
+        let xcb = &instance.backend.xcb;
+        let window_id = xcb.xcb_generate_id(c.c);
+        let cookie = xcb.xcb_create_window_checked(
+            c.c,
+            0,
+            window_id,
+            c.screen.root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            ffi::XCB_WINDOW_CLASS_INPUT_OUTPUT as _,
+            0,
+            0,
+            ptr::null(),
+        );
+        if let Err(e) = c.errors.check_cookie(xcb, cookie) {
+            panic!("Could not create dummy window: {}", e);
+        }
+        let server = c.atom("@server=winit_it");
+        let cookie = xcb.xcb_set_selection_owner_checked(c.c, window_id, server, 0);
+        if let Err(e) = c.errors.check_cookie(xcb, cookie) {
+            panic!("Could not take ownership of the server selection: {}", e);
+        }
+        let cookie = xcb.xcb_change_property_checked(
+            c.c,
+            ffi::XCB_PROP_MODE_APPEND as _,
+            c.screen.root,
+            c.atom("XIM_SERVERS"),
+            ffi::XCB_ATOM_ATOM,
+            32,
+            1,
+            &server as *const _ as _,
+        );
+        if let Err(e) = c.errors.check_cookie(xcb, cookie) {
+            panic!("Could not set XIM_SERVERS: {}", e);
+        }
+
+        let ime = Ime {
+            xim_xconnect: c.atom("_XIM_XCONNECT"),
+            xim_protocol: c.atom("_XIM_PROTOCOL"),
+            transfer: c.atom("_WINIT_IT_XIM_DATA"),
+            c,
+            rx,
+            instance,
+            window_id,
+            client: None,
+            keycode,
+            text: text.to_string(),
+            spot,
+            next_icid: 1,
+        };
+
+        ime.run()
+    }
+}
+
+struct Ime {
+    c: XConnection,
+    rx: UnboundedReceiver<ImeMsg>,
+    instance: Arc<XInstanceData>,
+    window_id: ffi::xcb_window_t,
+    xim_xconnect: ffi::xcb_atom_t,
+    xim_protocol: ffi::xcb_atom_t,
+    transfer: ffi::xcb_atom_t,
+    client: Option<ffi::xcb_window_t>,
+    keycode: u8,
+    text: String,
+    spot: Rc<Cell<Option<(i16, i16)>>>,
+    next_icid: u16,
+}
+
+impl Ime {
+    async fn run(mut self) {
+        let fd = AsyncFd::with_interest(self.c.fd, Interest::READABLE).unwrap();
+        loop {
+            self.handle_events();
+            tokio::select! {
+                guard = fd.readable() => {
+                    guard.unwrap().clear_ready();
+                }
+                msg = self.rx.recv() => {
+                    match msg {
+                        Some(ImeMsg::Stop) | None => return,
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_events(&mut self) {
+        unsafe {
+            loop {
+                let event = self.instance.backend.xcb.xcb_poll_for_event(self.c.c);
+                let event = match self.c.errors.check_val(&self.instance.backend.xcb, event) {
+                    Ok(e) => e,
+                    Err(e) => {
+                        if matches!(e.ty, XcbErrorType::MissingReply) {
+                            break;
+                        }
+                        panic!("The connection is in error: {}", e);
+                    }
+                };
+                self.handle_event(&event);
+            }
+            self.instance.backend.xcb.xcb_flush(self.c.c);
+        }
+    }
+
+    fn handle_event(&mut self, event: &ffi::xcb_generic_event_t) {
+        if event.response_type & 0x7f != ffi::XCB_CLIENT_MESSAGE {
+            log::warn!("Received unexpected event: {:?}", event);
+            return;
+        }
+        let event = unsafe { &*(event as *const _ as *const ffi::xcb_client_message_event_t) };
+        let data32 = unsafe { event.data.data32 };
+        if event.type_ == self.xim_xconnect {
+            log::info!("Client {} connected", data32[0]);
+            self.client = Some(data32[0]);
+            self.send_client_msg(self.xim_xconnect, [self.window_id, 0, 0, 20, 0]);
+        } else if event.type_ == self.xim_protocol {
+            let data = if event.format == 32 {
+                let data = unsafe {
+                    xcb_dl_util::property::get_property::<u8>(
+                        &self.instance.backend.xcb,
+                        &self.c.errors,
+                        self.window_id,
+                        data32[1],
+                        ffi::XCB_ATOM_STRING,
+                        true,
+                        10000,
+                    )
+                };
+                match data {
+                    Ok(data) => data,
+                    Err(e) => panic!("Could not read a message of the client: {}", e),
+                }
+            } else {
+                unsafe { event.data.data8.to_vec() }
+            };
+            let mut data = &data[..];
+            while data.len() >= 4 && data[0] != 0 {
+                let len = 4 + 4 * u16::from_ne_bytes([data[2], data[3]]) as usize;
+                self.handle_message(&data[..len]);
+                data = &data[len..];
+            }
+        } else {
+            log::warn!("Received unexpected client message: {:?}", event);
+        }
+    }
+
+    fn handle_message(&mut self, msg: &[u8]) {
+        log::info!("Received message {} from the client", msg[0]);
+        let u16_at = |pos: usize| u16::from_ne_bytes([msg[pos], msg[pos + 1]]);
+        match msg[0] {
+            XIM_CONNECT => {
+                self.send(Writer::new(XIM_CONNECT_REPLY).u16(1).u16(0));
+            }
+            XIM_OPEN => {
+                let mut im_attributes = Writer::default();
+                im_attributes.attribute_name(QUERY_INPUT_STYLE, 10, "queryInputStyle");
+                let mut ic_attributes = Writer::default();
+                ic_attributes
+                    .attribute_name(INPUT_STYLE, 3, "inputStyle")
+                    .attribute_name(CLIENT_WINDOW, 5, "clientWindow")
+                    .attribute_name(FOCUS_WINDOW, 5, "focusWindow")
+                    .attribute_name(PREEDIT_ATTRIBUTES, 0x7fff, "preeditAttributes")
+                    .attribute_name(SPOT_LOCATION, 18, "spotLocation");
+                self.send(
+                    Writer::new(XIM_OPEN_REPLY)
+                        .u16(IMID)
+                        .u16(im_attributes.buf.len() as u16)
+                        .bytes(&im_attributes.buf)
+                        .u16(ic_attributes.buf.len() as u16)
+                        .u16(0)
+                        .bytes(&ic_attributes.buf),
+                );
+            }
+            XIM_ENCODING_NEGOTIATION => {
+                // Choose the first encoding which is UTF-8.
+                self.send(
+                    Writer::new(XIM_ENCODING_NEGOTIATION_REPLY)
+                        .u16(IMID)
+                        .u16(0)
+                        .u16(0)
+                        .u16(0),
+                );
+            }
+            XIM_GET_IM_VALUES => {
+                let mut value = Writer::default();
+                value.u16(STYLES.len() as u16).u16(0);
+                for style in STYLES {
+                    value.u32(style);
+                }
+                let mut attributes = Writer::default();
+                attributes.attribute(QUERY_INPUT_STYLE, &value.buf);
+                self.send(
+                    Writer::new(XIM_GET_IM_VALUES_REPLY)
+                        .u16(IMID)
+                        .u16(attributes.buf.len() as u16)
+                        .bytes(&attributes.buf),
+                );
+            }
+            XIM_CREATE_IC => {
+                let len = u16_at(6) as usize;
+                let spot = parse_spot(&msg[8..8 + len]);
+                let icid = self.next_icid;
+                self.next_icid += 1;
+                self.send(Writer::new(XIM_CREATE_IC_REPLY).u16(IMID).u16(icid));
+                // Forward key releases as well.
+                self.send(
+                    Writer::new(XIM_SET_EVENT_MASK)
+                        .u16(IMID)
+                        .u16(icid)
+                        .u32(3)
+                        .u32(3),
+                );
+                // Only publish the spot once the client has been informed about the input
+                // context so that the test can start sending key events.
+                self.set_spot(spot);
+            }
+            XIM_SET_IC_VALUES => {
+                let icid = u16_at(6);
+                let len = u16_at(8) as usize;
+                let spot = parse_spot(&msg[12..12 + len]);
+                self.send(Writer::new(XIM_SET_IC_VALUES_REPLY).u16(IMID).u16(icid));
+                self.set_spot(spot);
+            }
+            XIM_DESTROY_IC => {
+                let icid = u16_at(6);
+                self.send(Writer::new(XIM_DESTROY_IC_REPLY).u16(IMID).u16(icid));
+            }
+            XIM_FORWARD_EVENT => {
+                let icid = u16_at(6);
+                let event = &msg[12..44];
+                let press = event[0] & 0x7f == ffi::XCB_KEY_PRESS;
+                if event[1] == self.keycode {
+                    if press {
+                        log::info!("Composing {}", self.text);
+                        let text = self.text.clone();
+                        let chars = text.chars().count();
+                        self.send(Writer::new(XIM_PREEDIT_START).u16(IMID).u16(icid));
+                        let mut draw = Writer::new(XIM_PREEDIT_DRAW);
+                        draw.u16(IMID)
+                            .u16(icid)
+                            .u32(chars as u32)
+                            .u32(0)
+                            .u32(0)
+                            .u32(0)
+                            .u16(text.len() as u16)
+                            .bytes(text.as_bytes())
+                            .u16(4 * chars as u16)
+                            .u16(0);
+                        for _ in 0..chars {
+                            draw.u32(XIM_UNDERLINE);
+                        }
+                        self.send(&mut draw);
+                        log::info!("Committing {}", self.text);
+                        self.send(
+                            Writer::new(XIM_COMMIT)
+                                .u16(IMID)
+                                .u16(icid)
+                                .u16(COMMIT_CHARS)
+                                .u16(text.len() as u16)
+                                .bytes(text.as_bytes()),
+                        );
+                        self.send(Writer::new(XIM_PREEDIT_DONE).u16(IMID).u16(icid));
+                    }
+                } else {
+                    self.send(
+                        Writer::new(XIM_FORWARD_EVENT)
+                            .u16(IMID)
+                            .u16(icid)
+                            .u16(0)
+                            .u16(0)
+                            .bytes(event),
+                    );
+                }
+                self.send(Writer::new(XIM_SYNC_REPLY).u16(IMID).u16(icid));
+            }
+            XIM_DISCONNECT => {
+                self.send(&mut Writer::new(XIM_DISCONNECT_REPLY));
+            }
+            _ => {}
+        }
+    }
+
+    fn set_spot(&self, spot: Option<(i16, i16)>) {
+        if let Some((x, y)) = spot {
+            log::info!("Spot location is {}x{}", x, y);
+            self.spot.set(Some((x, y)));
+        }
+    }
+
+    fn send(&mut self, msg: &mut Writer) {
+        let msg = msg.finish();
+        if msg.len() <= 20 {
+            let mut data8 = [0; 20];
+            data8[..msg.len()].copy_from_slice(&msg);
+            self.send_client_msg_data(
+                self.xim_protocol,
+                8,
+                ffi::xcb_client_message_data_t { data8 },
+            );
+        } else {
+            let client = self.client.unwrap();
+            unsafe {
+                let xcb = &self.instance.backend.xcb;
+                let cookie = xcb.xcb_change_property_checked(
+                    self.c.c,
+                    ffi::XCB_PROP_MODE_APPEND as _,
+                    client,
+                    self.transfer,
+                    ffi::XCB_ATOM_STRING,
+                    8,
+                    msg.len() as _,
+                    msg.as_ptr() as _,
+                );
+                if let Err(e) = self.c.errors.check_cookie(xcb, cookie) {
+                    panic!("Could not set property on {}: {}", client, e);
+                }
+            }
+            self.send_client_msg(
+                self.xim_protocol,
+                [msg.len() as u32, self.transfer, 0, 0, 0],
+            );
+        }
+    }
+
+    fn send_client_msg(&self, type_: ffi::xcb_atom_t, data32: [u32; 5]) {
+        self.send_client_msg_data(type_, 32, ffi::xcb_client_message_data_t { data32 });
+    }
+
+    fn send_client_msg_data(
+        &self,
+        type_: ffi::xcb_atom_t,
+        format: u8,
+        data: ffi::xcb_client_message_data_t,
+    ) {
+        let client = self.client.unwrap();
+        let xcb = &self.instance.backend.xcb;
+        let msg = ffi::xcb_client_message_event_t {
+            response_type: ffi::XCB_CLIENT_MESSAGE,
+            format,
+            window: client,
+            type_,
+            data,
+            ..Default::default()
+        };
+        unsafe {
+            let cookie = xcb.xcb_send_event_checked(self.c.c, 0, client, 0, &msg as *const _ as _);
+            if let Err(e) = self.c.errors.check_cookie(xcb, cookie) {
+                panic!("Could not send a client message to {}: {}", client, e);
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    fn new(major_opcode: u8) -> Self {
+        Writer {
+            buf: vec![major_opcode, 0, 0, 0],
+        }
+    }
+
+    fn u16(&mut self, v: u16) -> &mut Self {
+        self.buf.extend_from_slice(&v.to_ne_bytes());
+        self
+    }
+
+    fn u32(&mut self, v: u32) -> &mut Self {
+        self.buf.extend_from_slice(&v.to_ne_bytes());
+        self
+    }
+
+    fn bytes(&mut self, v: &[u8]) -> &mut Self {
+        self.buf.extend_from_slice(v);
+        self.align()
+    }
+
+    fn align(&mut self) -> &mut Self {
+        while self.buf.len() % 4 != 0 {
+            self.buf.push(0);
+        }
+        self
+    }
+
+    fn attribute_name(&mut self, id: u16, ty: u16, name: &str) -> &mut Self {
+        self.u16(id).u16(ty).u16(name.len() as u16);
+        self.buf.extend_from_slice(name.as_bytes());
+        self.align()
+    }
+
+    fn attribute(&mut self, id: u16, value: &[u8]) -> &mut Self {
+        self.u16(id).u16(value.len() as u16).bytes(value)
+    }
+
+    fn finish(&mut self) -> Vec<u8> {
+        self.align();
+        let len = ((self.buf.len() - 4) / 4) as u16;
+        self.buf[2..4].copy_from_slice(&len.to_ne_bytes());
+        std::mem::take(&mut self.buf)
+    }
+}
+
+/// Returns the spot location contained in a list of IC attributes.
+fn parse_spot(mut buf: &[u8]) -> Option<(i16, i16)> {
+    let mut spot = None;
+    while buf.len() >= 4 {
+        let id = u16::from_ne_bytes([buf[0], buf[1]]);
+        let len = u16::from_ne_bytes([buf[2], buf[3]]) as usize;
+        let value = &buf[4..4 + len];
+        match id {
+            PREEDIT_ATTRIBUTES => spot = parse_spot(value).or(spot),
+            SPOT_LOCATION => {
+                let x = i16::from_ne_bytes([value[0], value[1]]);
+                let y = i16::from_ne_bytes([value[2], value[3]]);
+                spot = Some((x, y));
+            }
+            _ => {}
+        }
+        buf = &buf[((4 + len + 3) & !3).min(buf.len())..];
+    }
+    spot
+}
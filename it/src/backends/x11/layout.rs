@@ -26,17 +26,17 @@ pub fn layouts() -> Layouts {
     }
 }
 
-const KEY_OFFSET: u32 = 8;
-const FIRST_KEY: u32 = KEY_ESC;
-const LAST_KEY: u32 = KEY_MENU;
+pub const KEY_OFFSET: u32 = 8;
+pub const FIRST_KEY: u32 = KEY_ESC;
+pub const LAST_KEY: u32 = KEY_MENU;
 const NUM_KEYS: u32 = LAST_KEY - FIRST_KEY + 1;
 
-const ONE_LEVEL: u8 = 0;
-const TWO_LEVEL: u8 = 1;
-const ALPHABETIC: u8 = 2;
-const KEYPAD: u8 = 3;
-const FOUR_LEVEL: u8 = 4;
-const FOUR_LEVEL_SEMIALPHABETIC: u8 = 5;
+pub const ONE_LEVEL: u8 = 0;
+pub const TWO_LEVEL: u8 = 1;
+pub const ALPHABETIC: u8 = 2;
+pub const KEYPAD: u8 = 3;
+pub const FOUR_LEVEL: u8 = 4;
+pub const FOUR_LEVEL_SEMIALPHABETIC: u8 = 5;
 
 // The number of types we actually use
 const NUM_TYPES: u8 = FOUR_LEVEL_SEMIALPHABETIC - ONE_LEVEL + 1;
@@ -84,7 +84,7 @@ pub(super) fn set_names(
     }
 }
 
-fn classify_keysyms(keysyms: &[u32]) -> u8 {
+pub fn classify_keysyms(keysyms: &[u32]) -> u8 {
     if keysyms.len() < 2 {
         return ONE_LEVEL;
     }
@@ -401,7 +401,7 @@ fn create_msg(layouts: &[HashMap<u32, Vec<u32>>]) -> Msg {
     Msg { header, body }
 }
 
-fn keymap(layout: Layout) -> HashMap<u32, Vec<u32>> {
+pub fn keymap(layout: Layout) -> HashMap<u32, Vec<u32>> {
     let mut res = HashMap::new();
     match layout {
         Qwerty | Azerty => {
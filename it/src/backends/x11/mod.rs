@@ -1,16 +1,20 @@
 use crate::backend::{
-    Backend, BackendDeviceId, BackendFlags, BackendIcon, Button, Device, DndProcess, EventLoop,
-    Finger, Instance, Keyboard, Mouse, PressedButton, PressedKey, Seat, Touchscreen, Window,
-    WindowProperties,
+    Backend, BackendDeviceId, BackendFlags, BackendIcon, Button, Device, DndProcess,
+    DndTargetProcess, EventLoop, Finger, ImeProcess, Instance, Keyboard, Mouse, Pen, PinchGesture,
+    PressedButton, PressedKey, Seat, SwipeGesture, Tablet, TabletTool, Touchpad, Touchscreen,
+    Window, WindowProperties,
 };
-use crate::backends::x11::dnd::DndMsg;
+use crate::backends::x11::dnd::{DndMsg, DndTargetMsg};
+use crate::backends::x11::ime::ImeMsg;
 use crate::backends::x11::layout::{layouts, set_names, Layouts};
 use crate::backends::x11::wm::TITLE_HEIGHT;
 use crate::backends::x11::MessageType::{
-    MT_BUTTON_PRESS, MT_BUTTON_RELEASE, MT_CREATE_MOUSE, MT_CREATE_MOUSE_REPLY, MT_CREATE_TOUCH,
+    MT_BUTTON_PRESS, MT_BUTTON_RELEASE, MT_CREATE_MOUSE, MT_CREATE_MOUSE_REPLY, MT_CREATE_TABLET,
+    MT_CREATE_TABLET_REPLY, MT_CREATE_TOUCH, MT_CREATE_TOUCHPAD, MT_CREATE_TOUCHPAD_REPLY,
     MT_CREATE_TOUCH_REPLY, MT_ENABLE_SECOND_MONITOR, MT_ENABLE_SECOND_MONITOR_REPLY,
-    MT_GET_VIDEO_INFO, MT_GET_VIDEO_INFO_REPLY, MT_MOUSE_MOVE, MT_MOUSE_SCROLL, MT_REMOVE_DEVICE,
-    MT_TOUCH_DOWN, MT_TOUCH_DOWN_REPLY, MT_TOUCH_MOVE, MT_TOUCH_UP,
+    MT_GESTURE_PINCH, MT_GESTURE_SWIPE, MT_GET_VIDEO_INFO, MT_GET_VIDEO_INFO_REPLY, MT_MOUSE_MOVE,
+    MT_MOUSE_SCROLL, MT_REMOVE_DEVICE, MT_TABLET_BUTTON, MT_TABLET_MOVE, MT_TABLET_PROXIMITY,
+    MT_TOUCHPAD_SCROLL, MT_TOUCH_DOWN, MT_TOUCH_DOWN_REPLY, MT_TOUCH_MOVE, MT_TOUCH_UP,
 };
 use crate::env::set_env;
 use crate::event::{map_event, DeviceEvent, DeviceEventExt, Event, UserEvent};
@@ -28,6 +32,7 @@ use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::process::Command;
+use std::rc::Rc;
 use std::sync::{Arc, Weak};
 use std::task::{Context, Poll, Waker};
 use std::time::Duration;
@@ -51,9 +56,10 @@ use xcb_dl_util::error::XcbErrorParser;
 use MessageType::{MT_CREATE_KEYBOARD, MT_CREATE_KEYBOARD_REPLY, MT_KEY_PRESS, MT_KEY_RELEASE};
 
 mod dnd;
-mod evdev;
-mod keysyms;
-mod layout;
+pub(super) mod evdev;
+mod ime;
+pub(super) mod keysyms;
+pub(super) mod layout;
 mod wm;
 
 const DEFAULT_X_PATH: &str = "/usr/lib/Xorg";
@@ -250,6 +256,7 @@ impl Backend for Arc<XBackend> {
         instance.atoms.x_dnd_status = c.atom("XdndStatus");
         instance.atoms.x_dnd_leave = c.atom("XdndLeave");
         instance.atoms.x_dnd_drop = c.atom("XdndDrop");
+        instance.atoms.x_dnd_finished = c.atom("XdndFinished");
         instance.atoms.uri_list = c.atom("text/uri-list");
 
         let instance = Arc::new(instance);
@@ -343,6 +350,16 @@ impl Backend for Arc<XBackend> {
             | BackendFlags::SECOND_MONITOR
             | BackendFlags::MONITOR_NAMES
             | BackendFlags::WINIT_SET_CURSOR_POSITION
+            | BackendFlags::CLIPBOARD
+            | BackendFlags::IME
+            | BackendFlags::DND_DATA
+            | BackendFlags::DND_SOURCE
+            | BackendFlags::CUSTOM_CURSOR
+            | BackendFlags::GESTURES
+            | BackendFlags::PEN
+            | BackendFlags::DND
+            | BackendFlags::WINIT_RESET_DEAD_KEYS
+            | BackendFlags::HOVER_MODIFIERS
     }
 }
 
@@ -483,6 +500,25 @@ impl XInstance {
         self.add_dev(MT_CREATE_TOUCH, MT_CREATE_TOUCH_REPLY)
     }
 
+    fn add_touchpad(&self) -> ffi::xcb_input_device_id_t {
+        self.add_dev(MT_CREATE_TOUCHPAD, MT_CREATE_TOUCHPAD_REPLY)
+    }
+
+    fn add_tablet(&self, tool: TabletTool) -> ffi::xcb_input_device_id_t {
+        let mut msg = Message {
+            create_tablet: CreateTablet {
+                ty: MT_CREATE_TABLET as _,
+                eraser: (tool == TabletTool::Eraser) as _,
+            },
+        };
+        uapi::write(self.data.sock.raw(), &msg).unwrap();
+        uapi::read(self.data.sock.raw(), &mut msg).unwrap();
+        unsafe {
+            assert_eq!(msg.ty, MT_CREATE_TABLET_REPLY as _);
+            msg.create_keyboard_reply.id as _
+        }
+    }
+
     fn assign_slave(&self, slave: ffi::xcb_input_device_id_t, master: ffi::xcb_input_device_id_t) {
         unsafe {
             let xcb = &self.data.backend.xcb;
@@ -683,6 +719,19 @@ fn create_seat(instance: &Arc<XInstance>) -> Arc<XSeat> {
     })
 }
 
+fn start_dnd(
+    instance: &Arc<XInstance>,
+    offers: Vec<(ffi::xcb_atom_t, Vec<u8>)>,
+) -> Box<dyn DndProcess> {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    tokio::task::spawn_local(dnd::run(instance.data.clone(), rx, offers));
+    Box::new(XDndProcess {
+        tx,
+        dropped: Cell::new(false),
+        _instance: instance.clone(),
+    })
+}
+
 impl Instance for Arc<XInstance> {
     fn backend(&self) -> &dyn Backend {
         &self.data.backend
@@ -725,7 +774,7 @@ impl Instance for Arc<XInstance> {
             let afd = AsyncFd::with_interest(el_fd, Interest::READABLE).unwrap();
             loop {
                 el2.run();
-                afd.readable().await.unwrap().clear_ready();
+                super::wait_for_event_loop(&afd).await;
             }
         });
         self.event_loops.lock().push(Arc::downgrade(&el));
@@ -874,11 +923,50 @@ impl Instance for Arc<XInstance> {
     }
 
     fn start_dnd_process(&self, path: &Path) -> Box<dyn DndProcess> {
+        let uri = format!("file://{}", path.display());
+        start_dnd(self, vec![(self.data.atoms.uri_list, uri.into_bytes())])
+    }
+
+    fn start_dnd_data_process(&self, offers: &[(&str, &[u8])]) -> Box<dyn DndProcess> {
+        let offers = offers
+            .iter()
+            .map(|(mime_type, data)| (self.c.atom(mime_type), data.to_vec()))
+            .collect();
+        start_dnd(self, offers)
+    }
+
+    fn start_dnd_target_process(
+        &self,
+        mime_type: &str,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    ) -> Box<dyn DndTargetProcess> {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let data = Rc::new(RefCell::new(None));
+        tokio::task::spawn_local(dnd::run_target(
+            self.data.clone(),
+            rx,
+            self.c.atom(mime_type),
+            (x as i16, y as i16, width as u16, height as u16),
+            data.clone(),
+        ));
+        Box::new(XDndTargetProcess {
+            tx,
+            data,
+            _instance: self.clone(),
+        })
+    }
+
+    fn start_ime_process(&self, key: Key, text: &str) -> Box<dyn ImeProcess> {
         let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
-        tokio::task::spawn_local(dnd::run(self.data.clone(), rx, path));
-        Box::new(XDndProcess {
+        let spot = Rc::new(Cell::new(None));
+        let keycode = (evdev::map_key(key) + 8) as u8;
+        tokio::task::spawn_local(ime::run(self.data.clone(), rx, keycode, text, spot.clone()));
+        Box::new(XImeProcess {
             tx,
-            dropped: Cell::new(false),
+            spot,
             _instance: self.clone(),
         })
     }
@@ -939,6 +1027,58 @@ impl Drop for XDndProcess {
     }
 }
 
+struct XDndTargetProcess {
+    tx: UnboundedSender<DndTargetMsg>,
+    data: Rc<RefCell<Option<Vec<u8>>>>,
+    _instance: Arc<XInstance>,
+}
+
+impl DndTargetProcess for XDndTargetProcess {
+    fn dropped<'a>(&'a self) -> Pin<Box<dyn Future<Output = Vec<u8>> + 'a>> {
+        Box::pin(async move {
+            log::info!("Waiting for dropped data");
+            loop {
+                if let Some(data) = self.data.borrow_mut().take() {
+                    return data;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+    }
+}
+
+impl Drop for XDndTargetProcess {
+    fn drop(&mut self) {
+        self.tx.send(DndTargetMsg::Stop).unwrap();
+    }
+}
+
+struct XImeProcess {
+    tx: UnboundedSender<ImeMsg>,
+    spot: Rc<Cell<Option<(i16, i16)>>>,
+    _instance: Arc<XInstance>,
+}
+
+impl ImeProcess for XImeProcess {
+    fn spot<'a>(&'a self, x: i32, y: i32) -> Pin<Box<dyn Future<Output = ()> + 'a>> {
+        Box::pin(async move {
+            log::info!("Waiting for spot location {}x{}", x, y);
+            loop {
+                if self.spot.get() == Some((x as i16, y as i16)) {
+                    return;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+    }
+}
+
+impl Drop for XImeProcess {
+    fn drop(&mut self) {
+        self.tx.send(ImeMsg::Stop).unwrap();
+    }
+}
+
 struct WmData {
     wakers: Vec<Waker>,
     windows: HashMap<ffi::xcb_window_t, Weak<XWindow>>,
@@ -1199,7 +1339,10 @@ impl EventLoop for Arc<XEventLoop> {
             self.data.barrier_kb.press(Key::KeyEsc);
             loop {
                 let ev = self.event2().await;
-                if let Event::DeviceEvent(DeviceEventExt { device_id, event }) = ev {
+                if let Event::DeviceEvent(DeviceEventExt {
+                    device_id, event, ..
+                }) = ev
+                {
                     if device_id.xinput_id() == Some(self.data.barrier_kb.dev.id as u32) {
                         if let DeviceEvent::Key(RawKeyEvent {
                             physical_key: KeyCode::Escape,
@@ -1358,6 +1501,31 @@ impl Window for Arc<XWindow> {
         }
     }
 
+    fn pixel(&self, x: u32, y: u32) -> (u8, u8, u8) {
+        let instance = &self.el.data.instance;
+        let backend = &instance.data.backend;
+        unsafe {
+            let mut err = ptr::null_mut();
+            let reply = backend.xcb.xcb_get_image_reply(
+                instance.c.c,
+                backend.xcb.xcb_get_image(
+                    instance.c.c,
+                    ffi::XCB_IMAGE_FORMAT_Z_PIXMAP as u8,
+                    self.id,
+                    x as i16,
+                    y as i16,
+                    1,
+                    1,
+                    !0,
+                ),
+                &mut err,
+            );
+            let mut image = instance.c.errors.check(&backend.xcb, reply, err).unwrap();
+            let data = std::slice::from_raw_parts(backend.xcb.xcb_get_image_data(&mut *image), 4);
+            (data[2], data[1], data[0])
+        }
+    }
+
     fn any(&self) -> &dyn Any {
         self
     }
@@ -1660,6 +1828,30 @@ impl Seat for Arc<XSeat> {
         }))
     }
 
+    fn add_touchpad(&self) -> Box<dyn Touchpad> {
+        let id = self.instance.add_touchpad();
+        log::info!("Created touchpad {} on seat {}", id, self.keyboard);
+        self.instance.assign_slave(id, self.pointer);
+        Box::new(Arc::new(XTouchpad {
+            dev: XDevice {
+                seat: self.clone(),
+                id,
+            },
+        }))
+    }
+
+    fn add_tablet(&self, tool: TabletTool) -> Box<dyn Tablet> {
+        let id = self.instance.add_tablet(tool);
+        log::info!("Created {:?} tablet {} on seat {}", tool, id, self.keyboard);
+        self.instance.assign_slave(id, self.pointer);
+        Box::new(Arc::new(XTablet {
+            dev: XDevice {
+                seat: self.clone(),
+                id,
+            },
+        }))
+    }
+
     fn focus(&self, window: &dyn Window) {
         let window: &Arc<XWindow> = window.any().downcast_ref().unwrap();
         log::info!("Focusing seat {} on window {}", self.keyboard, window.id);
@@ -2019,6 +2211,303 @@ impl Drop for XFinger {
     }
 }
 
+// xcb-dl predates the gesture events of XInput 2.4.
+const XCB_INPUT_GESTURE_PINCH_BEGIN: u16 = 27;
+const XCB_INPUT_GESTURE_PINCH_UPDATE: u16 = 28;
+const XCB_INPUT_GESTURE_PINCH_END: u16 = 29;
+const XCB_INPUT_GESTURE_SWIPE_BEGIN: u16 = 30;
+const XCB_INPUT_GESTURE_SWIPE_UPDATE: u16 = 31;
+const XCB_INPUT_GESTURE_SWIPE_END: u16 = 32;
+const XCB_INPUT_GESTURE_PINCH_EVENT_FLAGS_GESTURE_PINCH_CANCELLED: u32 = 1;
+const XCB_INPUT_GESTURE_SWIPE_EVENT_FLAGS_GESTURE_SWIPE_CANCELLED: u32 = 1;
+
+struct XTouchpad {
+    dev: XDevice,
+}
+
+impl XTouchpad {
+    fn send_gesture(&self, ty: MessageType, gesture_type: u16, fingers: u32, gesture: Gesture) {
+        let msg = Message {
+            gesture: GestureMsg {
+                ty: ty as _,
+                id: self.dev.id as _,
+                gesture_type: gesture_type as _,
+                fingers,
+                flags: gesture.flags,
+                dx: gesture.dx,
+                dy: gesture.dy,
+                scale: gesture.scale,
+                angle: gesture.angle,
+            },
+        };
+        uapi::write(self.dev.seat.instance.data.sock.raw(), &msg).unwrap();
+    }
+}
+
+#[derive(Copy, Clone, Default)]
+struct Gesture {
+    flags: u32,
+    dx: f64,
+    dy: f64,
+    scale: f64,
+    angle: f64,
+}
+
+impl Device for Arc<XTouchpad> {
+    fn id(&self) -> Box<dyn BackendDeviceId> {
+        Box::new(XDeviceId { id: self.dev.id })
+    }
+}
+
+impl Touchpad for Arc<XTouchpad> {
+    fn scroll(&self, dx: i32, dy: i32) {
+        log::info!(
+            "Scrolling touchpad {} of seat {} by {}x{}",
+            self.dev.id,
+            self.dev.seat.keyboard,
+            dx,
+            dy
+        );
+        let msg = Message {
+            mouse_move: MouseMove {
+                ty: MT_TOUCHPAD_SCROLL as _,
+                id: self.dev.id as _,
+                dx,
+                dy: -dy,
+            },
+        };
+        uapi::write(self.dev.seat.instance.data.sock.raw(), &msg).unwrap();
+    }
+
+    fn pinch(&self, fingers: u32) -> Box<dyn PinchGesture> {
+        let gesture = Gesture {
+            scale: 1.0,
+            ..Default::default()
+        };
+        self.send_gesture(
+            MT_GESTURE_PINCH,
+            XCB_INPUT_GESTURE_PINCH_BEGIN,
+            fingers,
+            gesture,
+        );
+        Box::new(XPinchGesture {
+            touchpad: self.clone(),
+            fingers,
+            scale: Cell::new(1.0),
+            cancelled: Cell::new(false),
+        })
+    }
+
+    fn swipe(&self, fingers: u32) -> Box<dyn SwipeGesture> {
+        self.send_gesture(
+            MT_GESTURE_SWIPE,
+            XCB_INPUT_GESTURE_SWIPE_BEGIN,
+            fingers,
+            Default::default(),
+        );
+        Box::new(XSwipeGesture {
+            touchpad: self.clone(),
+            fingers,
+            cancelled: Cell::new(false),
+        })
+    }
+}
+
+struct XPinchGesture {
+    touchpad: Arc<XTouchpad>,
+    fingers: u32,
+    scale: Cell<f64>,
+    cancelled: Cell<bool>,
+}
+
+impl PinchGesture for XPinchGesture {
+    fn update(&self, dx: f64, dy: f64, scale: f64, rotation: f64) {
+        self.scale.set(scale);
+        let gesture = Gesture {
+            flags: 0,
+            dx,
+            dy,
+            scale,
+            angle: rotation,
+        };
+        self.touchpad.send_gesture(
+            MT_GESTURE_PINCH,
+            XCB_INPUT_GESTURE_PINCH_UPDATE,
+            self.fingers,
+            gesture,
+        );
+    }
+
+    fn cancel(&self) {
+        self.cancelled.set(true);
+    }
+}
+
+impl Drop for XPinchGesture {
+    fn drop(&mut self) {
+        let mut flags = 0;
+        if self.cancelled.get() {
+            flags |= XCB_INPUT_GESTURE_PINCH_EVENT_FLAGS_GESTURE_PINCH_CANCELLED;
+        }
+        let gesture = Gesture {
+            flags,
+            scale: self.scale.get(),
+            ..Default::default()
+        };
+        self.touchpad.send_gesture(
+            MT_GESTURE_PINCH,
+            XCB_INPUT_GESTURE_PINCH_END,
+            self.fingers,
+            gesture,
+        );
+    }
+}
+
+struct XSwipeGesture {
+    touchpad: Arc<XTouchpad>,
+    fingers: u32,
+    cancelled: Cell<bool>,
+}
+
+impl SwipeGesture for XSwipeGesture {
+    fn update(&self, dx: f64, dy: f64) {
+        let gesture = Gesture {
+            dx,
+            dy,
+            ..Default::default()
+        };
+        self.touchpad.send_gesture(
+            MT_GESTURE_SWIPE,
+            XCB_INPUT_GESTURE_SWIPE_UPDATE,
+            self.fingers,
+            gesture,
+        );
+    }
+
+    fn cancel(&self) {
+        self.cancelled.set(true);
+    }
+}
+
+impl Drop for XSwipeGesture {
+    fn drop(&mut self) {
+        let mut flags = 0;
+        if self.cancelled.get() {
+            flags |= XCB_INPUT_GESTURE_SWIPE_EVENT_FLAGS_GESTURE_SWIPE_CANCELLED;
+        }
+        let gesture = Gesture {
+            flags,
+            ..Default::default()
+        };
+        self.touchpad.send_gesture(
+            MT_GESTURE_SWIPE,
+            XCB_INPUT_GESTURE_SWIPE_END,
+            self.fingers,
+            gesture,
+        );
+    }
+}
+
+struct XTablet {
+    dev: XDevice,
+}
+
+impl XTablet {
+    fn send_tool(&self, ty: MessageType, in_: bool, axes: [i32; 5]) {
+        let [x, y, pressure, tilt_x, tilt_y] = axes;
+        let msg = Message {
+            tablet_tool: TabletToolMsg {
+                ty: ty as _,
+                id: self.dev.id as _,
+                in_: in_ as _,
+                x,
+                y,
+                pressure,
+                tilt_x,
+                tilt_y,
+            },
+        };
+        uapi::write(self.dev.seat.instance.data.sock.raw(), &msg).unwrap();
+    }
+
+    fn send_button(&self, button: u32, pressed: bool) {
+        let msg = Message {
+            tablet_button: TabletButton {
+                ty: MT_TABLET_BUTTON as _,
+                id: self.dev.id as _,
+                button,
+                pressed: pressed as _,
+            },
+        };
+        uapi::write(self.dev.seat.instance.data.sock.raw(), &msg).unwrap();
+    }
+}
+
+impl Device for Arc<XTablet> {
+    fn id(&self) -> Box<dyn BackendDeviceId> {
+        Box::new(XDeviceId { id: self.dev.id })
+    }
+}
+
+impl Tablet for Arc<XTablet> {
+    fn approach(&self, x: i32, y: i32) -> Box<dyn Pen> {
+        log::info!(
+            "Bringing the tool of tablet {} of seat {} into proximity at {}x{}",
+            self.dev.id,
+            self.dev.seat.keyboard,
+            x,
+            y
+        );
+        let axes = [x, y, 0, 0, 0];
+        self.send_tool(MT_TABLET_PROXIMITY, true, axes);
+        Box::new(Arc::new(XPen {
+            tablet: self.clone(),
+            axes: Cell::new(axes),
+        }))
+    }
+}
+
+struct XPen {
+    tablet: Arc<XTablet>,
+    axes: Cell<[i32; 5]>,
+}
+
+impl Pen for Arc<XPen> {
+    fn move_(&self, x: i32, y: i32, pressure: i32, tilt_x: i32, tilt_y: i32) {
+        let axes = [x, y, pressure, tilt_x, tilt_y];
+        self.axes.set(axes);
+        self.tablet.send_tool(MT_TABLET_MOVE, true, axes);
+    }
+
+    fn press(&self, button: u32) -> Box<dyn PressedButton> {
+        self.tablet.send_button(button, true);
+        Box::new(Arc::new(XPressedPenButton {
+            pen: self.clone(),
+            button,
+        }))
+    }
+}
+
+impl Drop for XPen {
+    fn drop(&mut self) {
+        self.tablet
+            .send_tool(MT_TABLET_PROXIMITY, false, self.axes.get());
+    }
+}
+
+struct XPressedPenButton {
+    pen: Arc<XPen>,
+    button: u32,
+}
+
+impl PressedButton for Arc<XPressedPenButton> {}
+
+impl Drop for XPressedPenButton {
+    fn drop(&mut self) {
+        self.pen.tablet.send_button(self.button, false);
+    }
+}
+
 fn map_button(button: Button) -> u32 {
     match button {
         Button::Left => 1,
@@ -2071,6 +2560,16 @@ enum MessageType {
     MT_TOUCH_DOWN_REPLY,
     MT_TOUCH_UP,
     MT_TOUCH_MOVE,
+    MT_CREATE_TOUCHPAD,
+    MT_CREATE_TOUCHPAD_REPLY,
+    MT_GESTURE_PINCH,
+    MT_GESTURE_SWIPE,
+    MT_CREATE_TABLET,
+    MT_CREATE_TABLET_REPLY,
+    MT_TABLET_PROXIMITY,
+    MT_TABLET_MOVE,
+    MT_TABLET_BUTTON,
+    MT_TOUCHPAD_SCROLL,
 }
 
 #[repr(C)]
@@ -2087,6 +2586,10 @@ union Message {
     touch_down: TouchDown,
     touch_down_reply: TouchDownReply,
     touch_up: TouchUp,
+    gesture: GestureMsg,
+    create_tablet: CreateTablet,
+    tablet_tool: TabletToolMsg,
+    tablet_button: TabletButton,
 }
 
 unsafe impl Pod for Message {}
@@ -2132,6 +2635,49 @@ struct TouchMove {
     y: i32,
 }
 
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct GestureMsg {
+    ty: u32,
+    id: u32,
+    gesture_type: u32,
+    fingers: u32,
+    flags: u32,
+    dx: f64,
+    dy: f64,
+    scale: f64,
+    angle: f64,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct CreateTablet {
+    ty: u32,
+    eraser: u32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct TabletToolMsg {
+    ty: u32,
+    id: u32,
+    in_: u32,
+    x: i32,
+    y: i32,
+    pressure: i32,
+    tilt_x: i32,
+    tilt_y: i32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct TabletButton {
+    ty: u32,
+    id: u32,
+    button: u32,
+    pressed: u32,
+}
+
 #[repr(C)]
 #[derive(Copy, Clone)]
 struct EnableSecondMonitor {
@@ -2211,5 +2757,6 @@ struct Atoms {
     x_dnd_status: ffi::xcb_atom_t,
     x_dnd_leave: ffi::xcb_atom_t,
     x_dnd_drop: ffi::xcb_atom_t,
+    x_dnd_finished: ffi::xcb_atom_t,
     uri_list: ffi::xcb_atom_t,
 }
@@ -1,12 +1,15 @@
 use std::path::PathBuf;
 use winit::dpi::{PhysicalPosition, PhysicalSize};
 use winit::event::{
-    AxisId, ButtonId, DeviceEvent as WDeviceEvent, DeviceId, ElementState, KeyEvent as WKeyEvent,
-    MouseButton, MouseScrollDelta, RawKeyEvent, Touch, TouchPhase,
+    AxisId, ButtonId, ClipboardEvent, DeviceEvent as WDeviceEvent, DeviceId, DndAction, DndEvent,
+    ElementState, Ime, KeyEvent as WKeyEvent, MouseButton, MouseScrollDelta, Pen, RawKeyEvent,
+    Readiness, ScrollSource, SourceToken, Timestamp, Touch, TouchPhase,
 };
 use winit::event::{Event as WEvent, WindowEvent as WWindowEvent};
+use winit::event_loop::TimerId;
 use winit::keyboard;
 use winit::keyboard::ModifiersState;
+use winit::monitor::MonitorHandle;
 #[cfg(have_mod_supplement)]
 use winit::platform::modifier_supplement::KeyEventExtModifierSupplement;
 use winit::window::{Theme, WindowId};
@@ -37,12 +40,14 @@ pub struct WindowKeyboardInput {
     pub device_id: DeviceId,
     pub event: KeyEvent,
     pub is_synthetic: bool,
+    pub time: Option<Timestamp>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct WindowCursorMoved {
     pub device_id: DeviceId,
     pub position: PhysicalPosition<f64>,
+    pub time: Option<Timestamp>,
     pub modifiers: ModifiersState,
 }
 
@@ -61,6 +66,8 @@ pub struct WindowMouseWheel {
     pub device_id: DeviceId,
     pub delta: MouseScrollDelta,
     pub phase: TouchPhase,
+    pub source: ScrollSource,
+    pub time: Option<Timestamp>,
     pub modifiers: ModifiersState,
 }
 
@@ -69,6 +76,7 @@ pub struct WindowMouseInput {
     pub device_id: DeviceId,
     pub state: ElementState,
     pub button: MouseButton,
+    pub time: Option<Timestamp>,
     pub modifiers: ModifiersState,
 }
 
@@ -86,6 +94,28 @@ pub struct WindowAxisMotion {
     pub value: f64,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowPinchGesture {
+    pub device_id: DeviceId,
+    pub delta: f64,
+    pub phase: TouchPhase,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowRotationGesture {
+    pub device_id: DeviceId,
+    pub delta: f32,
+    pub phase: TouchPhase,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowSwipeGesture {
+    pub device_id: DeviceId,
+    pub delta: PhysicalPosition<f64>,
+    pub fingers: u32,
+    pub phase: TouchPhase,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct WindowScaleFactorChanged {
     pub scale_factor: f64,
@@ -101,6 +131,8 @@ pub enum WindowEvent {
     DroppedFile(PathBuf),
     HoveredFile(PathBuf),
     HoveredFileCancelled,
+    DragAndDrop(DndEvent),
+    DragFinished(Option<DndAction>),
     Focused(bool),
     KeyboardInput(WindowKeyboardInput),
     ModifiersChanged(ModifiersState),
@@ -112,9 +144,13 @@ pub enum WindowEvent {
     TouchpadPressure(WindowTouchpadPressure),
     AxisMotion(WindowAxisMotion),
     Touch(Touch),
+    Pen(Pen),
+    PinchGesture(WindowPinchGesture),
+    RotationGesture(WindowRotationGesture),
+    SwipeGesture(WindowSwipeGesture),
     ScaleFactorChanged(WindowScaleFactorChanged),
     ThemeChanged(Theme),
-    ReceivedImeText(String),
+    Ime(Ime),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -127,6 +163,7 @@ pub struct WindowEventExt {
 pub struct DeviceEventExt {
     pub device_id: DeviceId,
     pub event: DeviceEvent,
+    pub time: Option<Timestamp>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -135,6 +172,12 @@ pub enum Event {
     DeviceEvent(DeviceEventExt),
     UserEvent(UserEvent),
     RedrawRequested(WindowId),
+    Clipboard(ClipboardEvent),
+    FdReady(SourceToken, Readiness),
+    Timer(TimerId),
+    MonitorAdded(MonitorHandle),
+    MonitorRemoved(MonitorHandle),
+    MonitorChanged(MonitorHandle),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -220,24 +263,30 @@ pub fn map_window_event(e: WWindowEvent<'_>) -> WindowEvent {
         WWindowEvent::DroppedFile(v) => WindowEvent::DroppedFile(v),
         WWindowEvent::HoveredFile(v) => WindowEvent::HoveredFile(v),
         WWindowEvent::HoveredFileCancelled => WindowEvent::HoveredFileCancelled,
+        WWindowEvent::DragAndDrop(v) => WindowEvent::DragAndDrop(v),
+        WWindowEvent::DragFinished { action } => WindowEvent::DragFinished(action),
         WWindowEvent::Focused(v) => WindowEvent::Focused(v),
         WWindowEvent::KeyboardInput {
             device_id,
             event,
             is_synthetic,
+            time,
         } => WindowEvent::KeyboardInput(WindowKeyboardInput {
             device_id,
             event: map_key_event(event),
             is_synthetic,
+            time,
         }),
         WWindowEvent::ModifiersChanged(v) => WindowEvent::ModifiersChanged(v),
         WWindowEvent::CursorMoved {
             device_id,
             position,
+            time,
             modifiers,
         } => WindowEvent::CursorMoved(WindowCursorMoved {
             device_id,
             position,
+            time,
             modifiers,
         }),
         WWindowEvent::CursorEntered { device_id } => {
@@ -250,22 +299,28 @@ pub fn map_window_event(e: WWindowEvent<'_>) -> WindowEvent {
             device_id,
             delta,
             phase,
+            source,
+            time,
             modifiers,
         } => WindowEvent::MouseWheel(WindowMouseWheel {
             device_id,
             delta,
             phase,
+            source,
+            time,
             modifiers,
         }),
         WWindowEvent::MouseInput {
             device_id,
             state,
             button,
+            time,
             modifiers,
         } => WindowEvent::MouseInput(WindowMouseInput {
             device_id,
             state,
             button,
+            time,
             modifiers,
         }),
         WWindowEvent::TouchpadPressure {
@@ -287,6 +342,36 @@ pub fn map_window_event(e: WWindowEvent<'_>) -> WindowEvent {
             value,
         }),
         WWindowEvent::Touch(v) => WindowEvent::Touch(v),
+        WWindowEvent::Pen(v) => WindowEvent::Pen(v),
+        WWindowEvent::PinchGesture {
+            device_id,
+            delta,
+            phase,
+        } => WindowEvent::PinchGesture(WindowPinchGesture {
+            device_id,
+            delta,
+            phase,
+        }),
+        WWindowEvent::RotationGesture {
+            device_id,
+            delta,
+            phase,
+        } => WindowEvent::RotationGesture(WindowRotationGesture {
+            device_id,
+            delta,
+            phase,
+        }),
+        WWindowEvent::SwipeGesture {
+            device_id,
+            delta,
+            fingers,
+            phase,
+        } => WindowEvent::SwipeGesture(WindowSwipeGesture {
+            device_id,
+            delta,
+            fingers,
+            phase,
+        }),
         WWindowEvent::ScaleFactorChanged {
             scale_factor,
             new_inner_size,
@@ -295,7 +380,7 @@ pub fn map_window_event(e: WWindowEvent<'_>) -> WindowEvent {
             new_inner_size: *new_inner_size,
         }),
         WWindowEvent::ThemeChanged(v) => WindowEvent::ThemeChanged(v),
-        WWindowEvent::ReceivedImeText(v) => WindowEvent::ReceivedImeText(v),
+        WWindowEvent::Ime(v) => WindowEvent::Ime(v),
     }
 }
 
@@ -306,9 +391,14 @@ pub fn map_event(e: WEvent<'_, UserEvent>) -> Option<Event> {
             window_id,
             event: map_window_event(event),
         })),
-        WEvent::DeviceEvent { device_id, event } => Some(Event::DeviceEvent(DeviceEventExt {
+        WEvent::DeviceEvent {
+            device_id,
+            event,
+            time,
+        } => Some(Event::DeviceEvent(DeviceEventExt {
             device_id,
             event: map_device_event(event),
+            time,
         })),
         WEvent::UserEvent(v) => Some(Event::UserEvent(v)),
         WEvent::Suspended => None,
@@ -317,6 +407,12 @@ pub fn map_event(e: WEvent<'_, UserEvent>) -> Option<Event> {
         WEvent::RedrawRequested(v) => Some(Event::RedrawRequested(v)),
         WEvent::RedrawEventsCleared => None,
         WEvent::LoopDestroyed => None,
+        WEvent::Clipboard(v) => Some(Event::Clipboard(v)),
+        WEvent::FdReady { token, readiness } => Some(Event::FdReady(token, readiness)),
+        WEvent::Timer(id) => Some(Event::Timer(id)),
+        WEvent::MonitorAdded(v) => Some(Event::MonitorAdded(v)),
+        WEvent::MonitorRemoved(v) => Some(Event::MonitorRemoved(v)),
+        WEvent::MonitorChanged(v) => Some(Event::MonitorChanged(v)),
     }
 }
 
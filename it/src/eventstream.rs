@@ -1,14 +1,17 @@
 use crate::event::{
     DeviceButton, DeviceEvent, DeviceEventExt, DeviceMouseMotion, DeviceMouseWheel, Event,
     UserEvent, WindowCursorEntered, WindowCursorLeft, WindowCursorMoved, WindowEvent,
-    WindowEventExt, WindowKeyboardInput, WindowMouseInput, WindowMouseWheel,
-    WindowScaleFactorChanged,
+    WindowEventExt, WindowKeyboardInput, WindowMouseInput, WindowMouseWheel, WindowPinchGesture,
+    WindowRotationGesture, WindowScaleFactorChanged, WindowSwipeGesture,
 };
 use std::future::Future;
 use std::path::PathBuf;
 use std::pin::Pin;
 use winit::dpi::{PhysicalPosition, PhysicalSize};
-use winit::event::{RawKeyEvent, Touch};
+use winit::event::{
+    ClipboardEvent, DndAction, DndEvent, Ime, Pen, RawKeyEvent, Readiness, SourceToken, Touch,
+};
+use winit::event_loop::TimerId;
 use winit::keyboard::ModifiersState;
 use winit::window::WindowId;
 
@@ -28,6 +31,34 @@ impl<'a> dyn EventStream + 'a {
         }
     }
 
+    pub async fn clipboard_event(&mut self) -> ClipboardEvent {
+        log::debug!("Awaiting clipboard event");
+        loop {
+            if let Event::Clipboard(ce) = self.event().await {
+                log::debug!("Got clipboard event {:?}", ce);
+                return ce;
+            }
+        }
+    }
+
+    pub async fn fd_ready(&mut self) -> (SourceToken, Readiness) {
+        log::debug!("Awaiting fd readiness");
+        loop {
+            if let Event::FdReady(token, readiness) = self.event().await {
+                return (token, readiness);
+            }
+        }
+    }
+
+    pub async fn timer(&mut self) -> TimerId {
+        log::debug!("Awaiting timer");
+        loop {
+            if let Event::Timer(id) = self.event().await {
+                return id;
+            }
+        }
+    }
+
     pub async fn window_event(&mut self) -> WindowEventExt {
         loop {
             if let Event::WindowEvent(we) = self.event().await {
@@ -262,6 +293,50 @@ impl<'a> dyn EventStream + 'a {
         }
     }
 
+    pub async fn window_pen_event(&mut self) -> (WindowEventExt, Pen) {
+        log::debug!("Awaiting window pen event");
+        loop {
+            let we = self.window_event().await;
+            if let WindowEvent::Pen(pen) = &we.event {
+                log::debug!("Got window pen event: {:?}", pen);
+                return (we.clone(), pen.clone());
+            };
+        }
+    }
+
+    pub async fn window_pinch_gesture(&mut self) -> (WindowEventExt, WindowPinchGesture) {
+        log::debug!("Awaiting window pinch gesture");
+        loop {
+            let we = self.window_event().await;
+            if let WindowEvent::PinchGesture(pg) = &we.event {
+                log::debug!("Got window pinch gesture: {:?}", pg);
+                return (we.clone(), pg.clone());
+            };
+        }
+    }
+
+    pub async fn window_rotation_gesture(&mut self) -> (WindowEventExt, WindowRotationGesture) {
+        log::debug!("Awaiting window rotation gesture");
+        loop {
+            let we = self.window_event().await;
+            if let WindowEvent::RotationGesture(rg) = &we.event {
+                log::debug!("Got window rotation gesture: {:?}", rg);
+                return (we.clone(), rg.clone());
+            };
+        }
+    }
+
+    pub async fn window_swipe_gesture(&mut self) -> (WindowEventExt, WindowSwipeGesture) {
+        log::debug!("Awaiting window swipe gesture");
+        loop {
+            let we = self.window_event().await;
+            if let WindowEvent::SwipeGesture(sg) = &we.event {
+                log::debug!("Got window swipe gesture: {:?}", sg);
+                return (we.clone(), sg.clone());
+            };
+        }
+    }
+
     pub async fn window_resize_event(&mut self) -> (WindowEventExt, PhysicalSize<u32>) {
         log::debug!("Awaiting window resize");
         loop {
@@ -307,4 +382,39 @@ impl<'a> dyn EventStream + 'a {
             }
         }
     }
+
+    pub async fn window_ime(&mut self) -> (WindowEventExt, Ime) {
+        log::debug!("Awaiting ime event");
+        loop {
+            let we = self.window_event().await;
+            if let WindowEvent::Ime(ime) = &we.event {
+                log::debug!("Got ime event {:?}", ime);
+                let ime = ime.clone();
+                return (we, ime);
+            }
+        }
+    }
+
+    pub async fn window_dnd(&mut self) -> (WindowEventExt, DndEvent) {
+        log::debug!("Awaiting dnd event");
+        loop {
+            let we = self.window_event().await;
+            if let WindowEvent::DragAndDrop(dnd) = &we.event {
+                log::debug!("Got dnd event {:?}", dnd);
+                let dnd = dnd.clone();
+                return (we, dnd);
+            }
+        }
+    }
+
+    pub async fn window_drag_finished(&mut self) -> (WindowEventExt, Option<DndAction>) {
+        log::debug!("Awaiting drag finished");
+        loop {
+            let we = self.window_event().await;
+            if let WindowEvent::DragFinished(action) = we.event {
+                log::debug!("Got drag finished {:?}", action);
+                return (we, action);
+            }
+        }
+    }
 }
@@ -28,11 +28,16 @@ mod always_on_top;
 mod available_monitors;
 #[cfg(target_os = "linux")]
 mod class;
+#[cfg(target_os = "linux")]
+mod clipboard;
 mod current_monitor;
 mod cursor_grab;
 mod cursor_icon;
 mod cursor_position;
+#[cfg(target_os = "linux")]
+mod cursor_theme;
 mod cursor_visible;
+mod custom_cursor;
 mod decorations;
 mod delete_window;
 mod destroyed;
@@ -40,35 +45,58 @@ mod device_added;
 mod device_key;
 mod device_mouse;
 mod dnd;
+#[cfg(target_os = "linux")]
+mod dnd_data;
+#[cfg(target_os = "linux")]
+mod dnd_source;
 mod drag_window;
+#[cfg(target_os = "linux")]
+mod fd_ready;
 mod focused;
 mod focused_multi_seat;
 mod fullscreen;
 mod fullscreen2;
+mod fullscreen_scaled;
+mod gestures;
 mod icon;
+mod ime;
 mod logical_cursor_position;
 mod logical_inner_size;
 mod logical_size_bounds;
 mod maximize;
 mod minimize;
+mod monitor_hotplug;
+#[cfg(target_os = "linux")]
+mod monitor_metadata;
+mod pen;
 mod physical_inner_size;
 mod physical_outer_position;
 mod physical_size_bounds;
 #[cfg(target_os = "linux")]
 mod ping;
+mod present_rgba;
 mod primary_monitor;
+#[cfg(target_os = "linux")]
+mod pump_events;
 mod redraw_requested;
 mod reset_dead_keys;
 mod resizable;
 mod set_position;
 mod set_size;
+#[cfg(target_os = "linux")]
+mod timers;
+mod timestamps;
 mod title;
 mod touch;
+mod touchpad_scroll;
 mod transparency;
 mod urgency;
 mod user_event;
+#[cfg(target_os = "linux")]
+mod video_mode_details;
 mod visible;
 mod window_keyboard;
+mod window_modifiers_hover;
 mod window_mouse;
 
 use crate::backend::{BackendFlags, Instance};
@@ -119,11 +147,15 @@ pub fn tests() -> Vec<Box<dyn Test>> {
         Box::new(primary_monitor::Test),
         Box::new(device_mouse::Test),
         Box::new(window_mouse::Test),
+        Box::new(window_modifiers_hover::Test),
         Box::new(drag_window::Test),
         Box::new(dnd::Test),
         Box::new(cursor_grab::Test),
         Box::new(cursor_position::Test),
         Box::new(cursor_icon::Test),
+        #[cfg(target_os = "linux")]
+        Box::new(cursor_theme::Test),
+        Box::new(custom_cursor::Test),
         Box::new(cursor_visible::Test),
         Box::new(logical_inner_size::Test),
         Box::new(logical_cursor_position::Test),
@@ -131,7 +163,31 @@ pub fn tests() -> Vec<Box<dyn Test>> {
         Box::new(current_monitor::Test),
         Box::new(fullscreen::Test),
         Box::new(fullscreen2::Test),
+        Box::new(fullscreen_scaled::Test),
         Box::new(touch::Test),
+        Box::new(gestures::Test),
+        Box::new(touchpad_scroll::Test),
+        Box::new(pen::Test),
         Box::new(redraw_requested::Test),
+        #[cfg(target_os = "linux")]
+        Box::new(clipboard::Test),
+        Box::new(ime::Test),
+        #[cfg(target_os = "linux")]
+        Box::new(dnd_data::Test),
+        #[cfg(target_os = "linux")]
+        Box::new(dnd_source::Test),
+        #[cfg(target_os = "linux")]
+        Box::new(fd_ready::Test),
+        #[cfg(target_os = "linux")]
+        Box::new(timers::Test),
+        #[cfg(target_os = "linux")]
+        Box::new(pump_events::Test),
+        Box::new(monitor_hotplug::Test),
+        #[cfg(target_os = "linux")]
+        Box::new(monitor_metadata::Test),
+        #[cfg(target_os = "linux")]
+        Box::new(video_mode_details::Test),
+        Box::new(present_rgba::Test),
+        Box::new(timestamps::Test),
     ]
 }
@@ -0,0 +1,97 @@
+use crate::backend::{BackendFlags, Instance};
+use winit::event::{ClipboardEvent, ClipboardKind};
+use winit::platform::unix::EventLoopWindowTargetExtUnix;
+
+test!(run, BackendFlags::CLIPBOARD);
+
+const TEXT: &str = "text/plain;charset=utf-8";
+const CUSTOM: &str = "application/x-winit-it";
+
+async fn run(instance: &dyn Instance) {
+    let el1 = instance.create_event_loop();
+    let mut events1 = el1.events();
+    let el2 = instance.create_event_loop();
+    let mut events2 = el2.events();
+    // Wayland clients only receive the selection once they have a window.
+    let window1 = el1.create_window(Default::default());
+    let window2 = el2.create_window(Default::default());
+    window1.mapped(true).await;
+    window2.mapped(true).await;
+
+    // Large enough to require an incremental transfer on X11.
+    let large: Vec<u8> = (0..4 * 1024 * 1024).map(|i| i as u8).collect();
+
+    for &kind in &[ClipboardKind::Clipboard, ClipboardKind::Primary] {
+        let data = vec![
+            (TEXT.to_string(), b"hello world".to_vec()),
+            (CUSTOM.to_string(), large.clone()),
+        ];
+        el1.with_winit(Box::new(|el| el.set_clipboard_data(kind, data).unwrap()));
+        // On Wayland, the new selection reaches the other client asynchronously.
+        el1.barrier().await;
+        el2.barrier().await;
+
+        el2.with_winit(Box::new(|el| el.request_clipboard_mime_types(kind)));
+        match events2.clipboard_event().await {
+            ClipboardEvent::MimeTypes {
+                kind: k,
+                mime_types,
+            } => {
+                assert_eq!(k, kind);
+                assert!(mime_types.iter().any(|m| m == TEXT));
+                assert!(mime_types.iter().any(|m| m == CUSTOM));
+            }
+            e => panic!("unexpected clipboard event {:?}", e),
+        }
+
+        // The results are delivered in the order of the requests even though the large data
+        // takes longer to transfer.
+        el2.with_winit(Box::new(|el| {
+            el.request_clipboard_data(kind, CUSTOM);
+            el.request_clipboard_data(kind, TEXT);
+            el.request_clipboard_mime_types(kind);
+        }));
+        for expected in &[&large[..], &b"hello world"[..]] {
+            match events2.clipboard_event().await {
+                ClipboardEvent::Data { kind: k, data, .. } => {
+                    assert_eq!(k, kind);
+                    assert_eq!(data.as_deref(), Some(*expected));
+                }
+                e => panic!("unexpected clipboard event {:?}", e),
+            }
+        }
+        match events2.clipboard_event().await {
+            ClipboardEvent::MimeTypes { kind: k, .. } => assert_eq!(k, kind),
+            e => panic!("unexpected clipboard event {:?}", e),
+        }
+
+        el2.with_winit(Box::new(|el| {
+            el.set_clipboard_text(kind, "hello back").unwrap()
+        }));
+        match events1.clipboard_event().await {
+            ClipboardEvent::OwnershipLost { kind: k } => assert_eq!(k, kind),
+            e => panic!("unexpected clipboard event {:?}", e),
+        }
+
+        el1.with_winit(Box::new(|el| el.request_clipboard_data(kind, TEXT)));
+        match events1.clipboard_event().await {
+            ClipboardEvent::Data { kind: k, data, .. } => {
+                assert_eq!(k, kind);
+                assert_eq!(data.as_deref(), Some(&b"hello back"[..]));
+            }
+            e => panic!("unexpected clipboard event {:?}", e),
+        }
+
+        el2.with_winit(Box::new(|el| el.clear_clipboard(kind).unwrap()));
+        el2.barrier().await;
+        el1.barrier().await;
+        el1.with_winit(Box::new(|el| el.request_clipboard_data(kind, TEXT)));
+        match events1.clipboard_event().await {
+            ClipboardEvent::Data { kind: k, data, .. } => {
+                assert_eq!(k, kind);
+                assert_eq!(data, None);
+            }
+            e => panic!("unexpected clipboard event {:?}", e),
+        }
+    }
+}
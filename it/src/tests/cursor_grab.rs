@@ -1,6 +1,6 @@
 use crate::backend::{BackendFlags, Instance};
 use winit::dpi::PhysicalSize;
-use winit::window::WindowBuilder;
+use winit::window::{CursorGrabMode, WindowBuilder};
 
 test!(run, BackendFlags::X11);
 
@@ -20,12 +20,33 @@ async fn run(instance: &dyn Instance) {
 
     events.window_cursor_entered().await;
 
-    window.winit_set_cursor_grab(true);
+    window.winit_set_cursor_grab(CursorGrabMode::Confined);
     instance.cursor_grabbed(true).await;
 
     pointer.move_(1, 1);
     events.window_cursor_moved().await;
 
-    window.winit_set_cursor_grab(false);
+    window.winit_set_cursor_grab(CursorGrabMode::None);
+    instance.cursor_grabbed(false).await;
+
+    window.winit_set_cursor_grab(CursorGrabMode::Locked);
+    instance.cursor_grabbed(true).await;
+
+    // A locked cursor does not move but the motion is reported as a device event.
+    let position = seat.cursor_position();
+    let pointer2 = seat.add_mouse();
+    pointer2.move_(5, 5);
+    let mut delta = (0.0, 0.0);
+    while delta != (5.0, 5.0) {
+        let (de, me) = events.device_mouse_motion_event().await;
+        if pointer2.id().is(de.device_id) {
+            delta.0 += me.delta.0;
+            delta.1 += me.delta.1;
+        }
+    }
+    el.barrier().await;
+    assert_eq!(seat.cursor_position(), position);
+
+    window.winit_set_cursor_grab(CursorGrabMode::None);
     instance.cursor_grabbed(false).await;
 }
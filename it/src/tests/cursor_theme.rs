@@ -0,0 +1,36 @@
+use crate::backend::{BackendFlags, Instance};
+use winit::dpi::PhysicalSize;
+use winit::platform::unix::EventLoopWindowTargetExtUnix;
+use winit::window::{CursorIcon, WindowBuilder};
+
+test!(run, BackendFlags::MANUAL_VERIFICATION);
+
+async fn run(instance: &dyn Instance) {
+    let seat = instance.default_seat();
+
+    let el = instance.create_event_loop();
+    let mut events = el.events();
+
+    let window = el.create_window(WindowBuilder::new().with_inner_size(PhysicalSize {
+        width: 100,
+        height: 100,
+    }));
+    window.mapped(true).await;
+    window.set_background_color(100, 100, 150);
+    window.set_outer_position(100, 100);
+    window.outer_position(100, 100).await;
+    el.barrier();
+    seat.set_cursor_position(window.inner_offset().0 + 150, window.inner_offset().1 + 150);
+    events.window_cursor_moved().await;
+
+    window.winit_set_cursor_icon(CursorIcon::Hand);
+    el.with_winit(Box::new(|el| el.set_cursor_theme(None, Some(64))));
+    el.barrier().await;
+    log::info!("Verify that the screenshot displays a large 'Hand' cursor.");
+    instance.take_screenshot();
+
+    el.with_winit(Box::new(|el| el.set_cursor_theme(None, Some(16))));
+    el.barrier().await;
+    log::info!("Verify that the screenshot displays a small 'Hand' cursor.");
+    instance.take_screenshot();
+}
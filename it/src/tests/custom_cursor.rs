@@ -0,0 +1,45 @@
+use crate::backend::{BackendFlags, Instance};
+use std::time::Duration;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::window::{CustomCursor, WindowBuilder};
+
+test!(
+    run,
+    BackendFlags::MANUAL_VERIFICATION | BackendFlags::CUSTOM_CURSOR
+);
+
+fn square(r: u8, g: u8, b: u8) -> CustomCursor {
+    let rgba = [r, g, b, 255].repeat(16 * 16);
+    CustomCursor::from_rgba(rgba, 16, 16, PhysicalPosition { x: 8, y: 8 }).unwrap()
+}
+
+async fn run(instance: &dyn Instance) {
+    let seat = instance.default_seat();
+
+    let el = instance.create_event_loop();
+    let mut events = el.events();
+
+    let window = el.create_window(WindowBuilder::new().with_inner_size(PhysicalSize {
+        width: 100,
+        height: 100,
+    }));
+    window.mapped(true).await;
+    window.set_background_color(100, 100, 150);
+    window.set_outer_position(100, 100);
+    window.outer_position(100, 100).await;
+    el.barrier();
+    seat.set_cursor_position(window.inner_offset().0 + 150, window.inner_offset().1 + 150);
+    events.window_cursor_moved().await;
+
+    window.winit_set_custom_cursor(&square(255, 0, 0));
+    log::info!("Verify that the screenshot displays a red square centered on the cursor position.");
+    instance.take_screenshot();
+
+    let frames = vec![
+        (square(0, 255, 0), Duration::from_millis(500)),
+        (square(0, 0, 255), Duration::from_millis(500)),
+    ];
+    window.winit_set_custom_cursor(&CustomCursor::from_animation(frames).unwrap());
+    log::info!("Verify that the screenshot displays a green or a blue square.");
+    instance.take_screenshot();
+}
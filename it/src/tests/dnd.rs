@@ -1,8 +1,8 @@
-use crate::backend::Instance;
+use crate::backend::{BackendFlags, Instance};
 use winit::dpi::PhysicalSize;
 use winit::window::WindowBuilder;
 
-test!(run);
+test!(run, BackendFlags::DND);
 
 async fn run(instance: &dyn Instance) {
     let el = instance.create_event_loop();
@@ -0,0 +1,96 @@
+use crate::backend::{BackendFlags, Instance};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{DndAction, DndEvent};
+use winit::platform::unix::EventLoopWindowTargetExtUnix;
+use winit::window::WindowBuilder;
+
+test!(run, BackendFlags::DND_DATA);
+
+const TEXT: &str = "text/plain";
+const CUSTOM: &str = "application/x-winit-it";
+
+async fn run(instance: &dyn Instance) {
+    let el = instance.create_event_loop();
+    let mut events = el.events();
+
+    let window = el.create_window(WindowBuilder::new().with_inner_size(PhysicalSize {
+        width: 100,
+        height: 100,
+    }));
+    window.mapped(true).await;
+    window.set_outer_position(-window.inner_offset().0, -window.inner_offset().1);
+    window
+        .outer_position(-window.inner_offset().0, -window.inner_offset().1)
+        .await;
+
+    let offers = [(TEXT, &b"hello world"[..]), (CUSTOM, &[0, 1, 2, 3][..])];
+
+    {
+        let process = instance.start_dnd_data_process(&offers);
+        process.drag_to(50, 50);
+
+        let (we, dnd) = events.window_dnd().await;
+        assert_eq!(we.window_id, window.winit_id());
+        match dnd {
+            DndEvent::Enter { mime_types } => {
+                assert!(mime_types.iter().any(|m| m == TEXT));
+                assert!(mime_types.iter().any(|m| m == CUSTOM));
+            }
+            e => panic!("unexpected dnd event {:?}", e),
+        }
+
+        let (_, dnd) = events.window_dnd().await;
+        assert_eq!(
+            dnd,
+            DndEvent::Motion {
+                position: PhysicalPosition { x: 50.0, y: 50.0 },
+                action: DndAction::Copy,
+            }
+        );
+
+        el.with_winit(Box::new(|el| el.request_dnd_data(CUSTOM)));
+        let (_, dnd) = events.window_dnd().await;
+        assert_eq!(
+            dnd,
+            DndEvent::Data {
+                mime_type: CUSTOM.to_string(),
+                data: Some(vec![0, 1, 2, 3]),
+            }
+        );
+
+        el.with_winit(Box::new(|el| el.set_dnd_action(Some(DndAction::Copy))));
+        process.drag_to(60, 60);
+
+        let (_, dnd) = events.window_dnd().await;
+        assert_eq!(
+            dnd,
+            DndEvent::Motion {
+                position: PhysicalPosition { x: 60.0, y: 60.0 },
+                action: DndAction::Copy,
+            }
+        );
+
+        process.do_drop();
+
+        let (_, dnd) = events.window_dnd().await;
+        assert_eq!(dnd, DndEvent::Drop);
+    }
+
+    {
+        el.barrier().await;
+
+        let process = instance.start_dnd_data_process(&offers);
+        process.drag_to(50, 50);
+
+        let (_, dnd) = events.window_dnd().await;
+        assert!(matches!(dnd, DndEvent::Enter { .. }));
+        let (_, dnd) = events.window_dnd().await;
+        assert!(matches!(dnd, DndEvent::Motion { .. }));
+
+        // The drop is rejected because the application never accepted it.
+        process.do_drop();
+
+        let (_, dnd) = events.window_dnd().await;
+        assert_eq!(dnd, DndEvent::Leave);
+    }
+}
@@ -0,0 +1,73 @@
+use crate::backend::{BackendFlags, Button, Instance};
+use winit::dpi::PhysicalSize;
+use winit::event::DndAction;
+use winit::window::{DragData, WindowBuilder};
+
+test!(run, BackendFlags::DND_SOURCE);
+
+const CUSTOM: &str = "application/x-winit-it";
+
+async fn run(instance: &dyn Instance) {
+    let seat = instance.default_seat();
+    let mouse = seat.add_mouse();
+
+    let el = instance.create_event_loop();
+    let mut events = el.events();
+
+    let window = el.create_window(WindowBuilder::new().with_inner_size(PhysicalSize {
+        width: 100,
+        height: 100,
+    }));
+    window.mapped(true).await;
+    window.set_outer_position(-window.inner_offset().0, -window.inner_offset().1);
+    window
+        .outer_position(-window.inner_offset().0, -window.inner_offset().1)
+        .await;
+
+    let process = instance.start_dnd_target_process(CUSTOM, 200, 0, 100, 100);
+
+    let data = DragData::new()
+        .with_data("text/plain", "hello world")
+        .with_data(CUSTOM, vec![0, 1, 2, 3]);
+
+    {
+        seat.set_cursor_position(50, 50);
+        let left = mouse.press(Button::Left);
+        events.window_mouse_input_event().await;
+
+        window
+            .winit()
+            .start_drag(data.clone(), &[DndAction::Copy], None)
+            .unwrap();
+        el.barrier().await;
+
+        mouse.move_(200, 0);
+        el.barrier().await;
+        drop(left);
+
+        let (we, action) = events.window_drag_finished().await;
+        assert_eq!(we.window_id, window.winit_id());
+        assert_eq!(action, Some(DndAction::Copy));
+        assert_eq!(process.dropped().await, vec![0, 1, 2, 3]);
+    }
+
+    {
+        seat.set_cursor_position(50, 50);
+        let left = mouse.press(Button::Left);
+        events.window_mouse_input_event().await;
+
+        window
+            .winit()
+            .start_drag(data, &[DndAction::Copy], None)
+            .unwrap();
+        el.barrier().await;
+
+        // There is no drop target at this position.
+        mouse.move_(0, 400);
+        el.barrier().await;
+        drop(left);
+
+        let (_, action) = events.window_drag_finished().await;
+        assert_eq!(action, None);
+    }
+}
@@ -0,0 +1,52 @@
+use crate::backend::Instance;
+use crate::event::{Event, UserEvent};
+use crate::sleep::sleep_ms;
+use uapi::c::O_CLOEXEC;
+use winit::event::Readiness;
+use winit::platform::unix::{EventLoopWindowTargetExtUnix, Interest};
+
+test!(run);
+
+async fn run(instance: &dyn Instance) {
+    let el = instance.create_event_loop();
+    let mut events = el.events();
+
+    let (read, write) = uapi::pipe2(O_CLOEXEC).unwrap();
+
+    let mut token = None;
+    el.with_winit(Box::new(|el| {
+        token = Some(el.register_fd(read.raw(), Interest::READABLE).unwrap())
+    }));
+    let token = token.unwrap();
+
+    log::info!("Checking that a readable pipe is reported");
+
+    uapi::write(write.raw(), &[1u8]).unwrap();
+
+    let (t, readiness) = events.fd_ready().await;
+    assert_eq!(t, token);
+    assert!(readiness.contains(Readiness::READABLE));
+
+    let mut buf = [0u8; 1];
+    uapi::read(read.raw(), &mut buf[..]).unwrap();
+    el.with_winit(Box::new(|el| el.unregister_fd(token)));
+
+    // Skip the events that were emitted before the pipe was unregistered.
+    el.send_event(UserEvent(1));
+    while events.user_event().await != UserEvent(1) {}
+
+    log::info!("Checking that unregistered file descriptors are not reported");
+
+    uapi::write(write.raw(), &[1u8]).unwrap();
+    // Give the event loop time to report the pipe.
+    sleep_ms(20).await;
+    el.send_event(UserEvent(2));
+
+    loop {
+        match events.event().await {
+            Event::FdReady(t, _) => assert_ne!(t, token),
+            Event::UserEvent(UserEvent(2)) => break,
+            _ => {}
+        }
+    }
+}
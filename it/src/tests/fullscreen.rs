@@ -1,4 +1,4 @@
-use crate::backend::Instance;
+use crate::backend::{BackendFlags, Instance};
 use winit::dpi::PhysicalSize;
 use winit::window::{Fullscreen, WindowBuilder};
 
@@ -27,6 +27,11 @@ async fn run(instance: &dyn Instance) {
     window.outer_position(100, 100).await;
     window.inner_size(500, 400).await;
 
+    // Only X11 changes the video mode of the monitor. See fullscreen_scaled for Wayland.
+    if !instance.backend().flags().contains(BackendFlags::X11) {
+        return;
+    }
+
     let original_monitor_size = mon.size();
 
     log::info!("Testing exculsive fullscreen changes");
@@ -0,0 +1,73 @@
+use crate::backend::{BackendFlags, Instance, Window};
+use winit::dpi::PhysicalSize;
+use winit::window::{Fullscreen, WindowBuilder};
+
+test!(run, BackendFlags::SCALED_FULLSCREEN);
+
+/// Presents a frame with the size of the window. The viewport is part of the state that is
+/// committed together with the frame.
+fn present(window: &(dyn Window + 'static)) {
+    let size = window.winit().inner_size();
+    let rgba = vec![0xff; 4 * size.width as usize * size.height as usize];
+    window.winit_present_rgba(&rgba, size.width, size.height, &[]);
+}
+
+async fn run(instance: &dyn Instance) {
+    let el = instance.create_event_loop();
+
+    let window = el.create_window(WindowBuilder::new().with_inner_size(PhysicalSize {
+        width: 500,
+        height: 400,
+    }));
+    window.mapped(true).await;
+    window.viewport(None).await;
+
+    let mon = window.winit().current_monitor().unwrap();
+    let mode = mon
+        .video_modes()
+        .find(|mode| mode.size() != mon.size())
+        .expect("The monitor has no video mode that differs from its size");
+
+    log::info!("Checking that exclusive fullscreen scales the window to the monitor");
+
+    window.winit_set_fullscreen(Some(Fullscreen::Exclusive(mode.clone())));
+    window.inner_size(mon.size().width, mon.size().height).await;
+    window
+        .await_winit(|w| {
+            w.inner_size() == mode.size()
+                && matches!(w.fullscreen(), Some(Fullscreen::Exclusive(ref m)) if *m == mode)
+        })
+        .await;
+    present(&*window);
+    window
+        .viewport(Some((mon.size().width, mon.size().height)))
+        .await;
+
+    log::info!("Checking that borderless fullscreen stops the scaling");
+
+    window.winit_set_fullscreen(Some(Fullscreen::Borderless(None)));
+    window
+        .await_winit(|w| {
+            w.inner_size() == mon.size()
+                && matches!(w.fullscreen(), Some(Fullscreen::Borderless(_)))
+        })
+        .await;
+    present(&*window);
+    window.viewport(None).await;
+
+    log::info!("Checking that leaving fullscreen stops the scaling");
+
+    window.winit_set_fullscreen(Some(Fullscreen::Exclusive(mode.clone())));
+    window.await_winit(|w| w.inner_size() == mode.size()).await;
+    present(&*window);
+    window
+        .viewport(Some((mon.size().width, mon.size().height)))
+        .await;
+    window.winit_set_fullscreen(None);
+    window.inner_size(500, 400).await;
+    window
+        .await_winit(|w| w.inner_size() == PhysicalSize::new(500, 400) && w.fullscreen().is_none())
+        .await;
+    present(&*window);
+    window.viewport(None).await;
+}
@@ -0,0 +1,100 @@
+use crate::backend::{BackendFlags, Instance};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::TouchPhase;
+use winit::window::WindowBuilder;
+
+test!(run, BackendFlags::GESTURES);
+
+async fn run(instance: &dyn Instance) {
+    let seat = instance.default_seat();
+    let touchpad = seat.add_touchpad();
+
+    let el = instance.create_event_loop();
+    let mut events = el.events();
+
+    let window = el.create_window(WindowBuilder::new().with_inner_size(PhysicalSize {
+        width: 100,
+        height: 100,
+    }));
+    window.mapped(true).await;
+    window.set_outer_position(-window.inner_offset().0, -window.inner_offset().1);
+    window
+        .outer_position(-window.inner_offset().0, -window.inner_offset().1)
+        .await;
+    seat.set_cursor_position(50, 50);
+
+    {
+        log::info!("Checking pinch gestures");
+
+        let pinch = touchpad.pinch(2);
+
+        let (we, pg) = events.window_pinch_gesture().await;
+        assert_eq!(we.window_id, window.winit_id());
+        assert!(seat.is(pg.device_id));
+        assert_eq!(pg.phase, TouchPhase::Started);
+        assert_eq!(pg.delta, 0.0);
+
+        pinch.update(0.0, 0.0, 1.5, 10.0);
+
+        let (_, pg) = events.window_pinch_gesture().await;
+        assert_eq!(pg.phase, TouchPhase::Moved);
+        assert_eq!(pg.delta, 0.5);
+
+        let (we, rg) = events.window_rotation_gesture().await;
+        assert_eq!(we.window_id, window.winit_id());
+        assert!(seat.is(rg.device_id));
+        assert_eq!(rg.phase, TouchPhase::Moved);
+        assert_eq!(rg.delta, -10.0);
+
+        pinch.update(0.0, 0.0, 1.25, -5.0);
+
+        let (_, pg) = events.window_pinch_gesture().await;
+        assert_eq!(pg.phase, TouchPhase::Moved);
+        assert_eq!(pg.delta, -0.25);
+
+        let (_, rg) = events.window_rotation_gesture().await;
+        assert_eq!(rg.phase, TouchPhase::Moved);
+        assert_eq!(rg.delta, 5.0);
+
+        drop(pinch);
+
+        let (_, pg) = events.window_pinch_gesture().await;
+        assert_eq!(pg.phase, TouchPhase::Ended);
+        assert_eq!(pg.delta, 0.0);
+
+        let pinch = touchpad.pinch(2);
+        pinch.cancel();
+        drop(pinch);
+
+        let (_, pg) = events.window_pinch_gesture().await;
+        assert_eq!(pg.phase, TouchPhase::Started);
+        let (_, pg) = events.window_pinch_gesture().await;
+        assert_eq!(pg.phase, TouchPhase::Cancelled);
+    }
+
+    {
+        log::info!("Checking swipe gestures");
+
+        let swipe = touchpad.swipe(3);
+
+        let (we, sg) = events.window_swipe_gesture().await;
+        assert_eq!(we.window_id, window.winit_id());
+        assert!(seat.is(sg.device_id));
+        assert_eq!(sg.phase, TouchPhase::Started);
+        assert_eq!(sg.fingers, 3);
+        assert_eq!(sg.delta, PhysicalPosition { x: 0.0, y: 0.0 });
+
+        swipe.update(5.0, -2.0);
+
+        let (_, sg) = events.window_swipe_gesture().await;
+        assert_eq!(sg.phase, TouchPhase::Moved);
+        assert_eq!(sg.fingers, 3);
+        assert_eq!(sg.delta, PhysicalPosition { x: 5.0, y: -2.0 });
+
+        swipe.cancel();
+        drop(swipe);
+
+        let (_, sg) = events.window_swipe_gesture().await;
+        assert_eq!(sg.phase, TouchPhase::Cancelled);
+    }
+}
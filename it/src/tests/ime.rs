@@ -0,0 +1,81 @@
+use crate::backend::{BackendFlags, Instance};
+use crate::event::{Event, WindowEvent};
+use crate::keyboard::Key::{KeyA, KeyL};
+use winit::dpi::PhysicalPosition;
+use winit::event::{ElementState, Ime, PreeditSpan, PreeditStyle};
+use winit::keyboard::KeyCode;
+
+test!(run, BackendFlags::IME);
+
+async fn run(instance: &dyn Instance) {
+    let process = instance.start_ime_process(KeyA, "ä");
+
+    let el = instance.create_event_loop();
+    let mut events = el.events();
+    let window = el.create_window(Default::default());
+    window.mapped(true).await;
+    let seat = instance.default_seat();
+    seat.focus(&*window);
+    let kb = seat.add_keyboard();
+
+    window.winit().set_ime_allowed(true);
+    let (_, ime) = events.window_ime().await;
+    assert_eq!(ime, Ime::Enabled);
+
+    window
+        .winit()
+        .set_ime_position(PhysicalPosition { x: 10, y: 20 });
+    process.spot(10, 20).await;
+
+    window
+        .winit()
+        .set_ime_position(PhysicalPosition { x: 30, y: 40 });
+    process.spot(30, 40).await;
+
+    kb.press(KeyA);
+    kb.press(KeyL);
+
+    let expected = [
+        Ime::Preedit {
+            text: "ä".to_string(),
+            cursor: Some((2, 2)),
+            spans: vec![PreeditSpan {
+                start: 0,
+                end: 2,
+                style: PreeditStyle::UNDERLINE,
+            }],
+        },
+        Ime::Preedit {
+            text: String::new(),
+            cursor: None,
+            spans: vec![],
+        },
+        Ime::Commit("ä".to_string()),
+    ];
+    for expected in expected {
+        loop {
+            if let Event::WindowEvent(we) = events.event().await {
+                match we.event {
+                    WindowEvent::Ime(ime) => {
+                        assert_eq!(ime, expected);
+                        break;
+                    }
+                    WindowEvent::KeyboardInput(ki) => {
+                        panic!("Key event was not consumed by the input method: {:?}", ki)
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    for state in [ElementState::Pressed, ElementState::Released] {
+        let (_, ki) = events.window_keyboard_input().await;
+        assert_eq!(ki.event.physical_key, KeyCode::KeyL);
+        assert_eq!(ki.event.state, state);
+    }
+
+    window.winit().set_ime_allowed(false);
+    let (_, ime) = events.window_ime().await;
+    assert_eq!(ime, Ime::Disabled);
+}
@@ -0,0 +1,47 @@
+use crate::backend::{BackendFlags, Instance};
+use crate::event::Event;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+
+test!(run, BackendFlags::SECOND_MONITOR);
+
+async fn run(instance: &dyn Instance) {
+    let el = instance.create_event_loop();
+    let mut events = el.events();
+    el.num_available_monitors(1).await;
+
+    log::info!("Checking that connecting a monitor is reported");
+
+    instance.enable_second_monitor(true);
+
+    let added = loop {
+        match events.event().await {
+            Event::MonitorAdded(m) => break m,
+            Event::MonitorRemoved(m) => panic!("Unexpected removal of {:?}", m),
+            _ => {}
+        }
+    };
+    assert_eq!(added.position(), PhysicalPosition { x: 1024, y: 0 });
+    assert_eq!(
+        added.size(),
+        PhysicalSize {
+            width: 800,
+            height: 600
+        }
+    );
+    assert!(el.available_monitors().contains(&added));
+
+    log::info!("Checking that disconnecting a monitor is reported");
+
+    instance.enable_second_monitor(false);
+
+    let removed = loop {
+        match events.event().await {
+            Event::MonitorRemoved(m) => break m,
+            Event::MonitorAdded(m) => panic!("Unexpected addition of {:?}", m),
+            _ => {}
+        }
+    };
+    assert_eq!(removed, added);
+    assert_eq!(removed.position(), PhysicalPosition { x: 1024, y: 0 });
+    assert!(!el.available_monitors().contains(&removed));
+}
@@ -0,0 +1,33 @@
+use crate::backend::{BackendFlags, Instance};
+use winit::platform::unix::{MonitorHandleExtUnix, MonitorTransform};
+
+test!(run, BackendFlags::X11 | BackendFlags::SECOND_MONITOR);
+
+async fn run(instance: &dyn Instance) {
+    let el = instance.create_event_loop();
+    instance.enable_second_monitor(true);
+    el.num_available_monitors(2).await;
+
+    let monitors = el.available_monitors();
+    assert_eq!(monitors.iter().filter(|m| m.is_primary()).count(), 1);
+    for monitor in &monitors {
+        log::info!(
+            "{:?}: {:?} mm, {:?}, {:?}, {:?} {:?} {:?}",
+            monitor.name(),
+            monitor.size_mm(),
+            monitor.transform(),
+            monitor.subpixel(),
+            monitor.manufacturer(),
+            monitor.model(),
+            monitor.serial_number(),
+        );
+        assert_eq!(monitor.transform(), MonitorTransform::Normal);
+        if let Some((width, height)) = monitor.size_mm() {
+            assert!(width > 0 && height > 0);
+        }
+        if let Some(manufacturer) = monitor.manufacturer() {
+            assert_eq!(manufacturer.len(), 3);
+            assert!(monitor.edid().is_some());
+        }
+    }
+}
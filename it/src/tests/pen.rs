@@ -0,0 +1,107 @@
+use crate::backend::{BackendFlags, Instance, TabletTool};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{ElementState, PenButton, PenPhase, PenTool};
+use winit::window::WindowBuilder;
+
+test!(run, BackendFlags::PEN);
+
+async fn run(instance: &dyn Instance) {
+    let seat = instance.default_seat();
+    let tablet = seat.add_tablet(TabletTool::Pen);
+    let eraser = seat.add_tablet(TabletTool::Eraser);
+
+    let el = instance.create_event_loop();
+    let mut events = el.events();
+
+    let window = el.create_window(WindowBuilder::new().with_inner_size(PhysicalSize {
+        width: 100,
+        height: 100,
+    }));
+    window.mapped(true).await;
+    window.inner_size(100, 100).await;
+    window.set_outer_position(100 - window.inner_offset().0, 100 - window.inner_offset().1);
+    window
+        .outer_position(100 - window.inner_offset().0, 100 - window.inner_offset().1)
+        .await;
+
+    {
+        log::info!("Checking proximity and motion");
+
+        let pen = tablet.approach(110, 120);
+
+        let (we, pe) = events.window_pen_event().await;
+        assert_eq!(we.window_id, window.winit_id());
+        assert!(seat.is(pe.device_id));
+        assert_eq!(pe.phase, PenPhase::ProximityIn);
+        assert_eq!(pe.tool, PenTool::Pen);
+        assert_eq!(pe.location, PhysicalPosition { x: 10.0, y: 20.0 });
+        assert_eq!(pe.state.pressure, Some(0.0));
+        assert_eq!(pe.state.rotation, None);
+
+        let (_, pe) = events.window_pen_event().await;
+        assert_eq!(pe.phase, PenPhase::Moved);
+
+        pen.move_(115, 130, 512, 10, -20);
+
+        let (_, pe) = events.window_pen_event().await;
+        assert_eq!(pe.phase, PenPhase::Moved);
+        assert_eq!(pe.location, PhysicalPosition { x: 15.0, y: 30.0 });
+        assert_eq!(pe.state.pressure, Some(512.0 / 1023.0));
+        assert_eq!(pe.state.tilt_x, Some(10.0));
+        assert_eq!(pe.state.tilt_y, Some(-20.0));
+
+        log::info!("Checking the tip");
+
+        let tip = pen.press(1);
+
+        let (_, pe) = events.window_pen_event().await;
+        assert_eq!(pe.phase, PenPhase::Down);
+        assert_eq!(pe.location, PhysicalPosition { x: 15.0, y: 30.0 });
+
+        drop(tip);
+
+        let (_, pe) = events.window_pen_event().await;
+        assert_eq!(pe.phase, PenPhase::Up);
+
+        log::info!("Checking barrel buttons");
+
+        let button = pen.press(2);
+
+        let (_, pe) = events.window_pen_event().await;
+        assert_eq!(
+            pe.phase,
+            PenPhase::Button {
+                button: PenButton::Primary,
+                state: ElementState::Pressed,
+            }
+        );
+
+        drop(button);
+
+        let (_, pe) = events.window_pen_event().await;
+        assert_eq!(
+            pe.phase,
+            PenPhase::Button {
+                button: PenButton::Primary,
+                state: ElementState::Released,
+            }
+        );
+
+        drop(pen);
+
+        let (we, pe) = events.window_pen_event().await;
+        assert_eq!(we.window_id, window.winit_id());
+        assert_eq!(pe.phase, PenPhase::ProximityOut);
+    }
+
+    {
+        log::info!("Checking the eraser");
+
+        let _eraser = eraser.approach(150, 150);
+
+        let (_, pe) = events.window_pen_event().await;
+        assert_eq!(pe.phase, PenPhase::ProximityIn);
+        assert_eq!(pe.tool, PenTool::Eraser);
+        assert_eq!(pe.location, PhysicalPosition { x: 50.0, y: 50.0 });
+    }
+}
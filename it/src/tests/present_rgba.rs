@@ -0,0 +1,44 @@
+use crate::backend::{BackendFlags, Instance};
+use winit::dpi::PhysicalSize;
+use winit::window::{DamageRect, WindowBuilder};
+
+test!(run, BackendFlags::PRESENT);
+
+async fn run(instance: &dyn Instance) {
+    let el = instance.create_event_loop();
+
+    let window = el.create_window(WindowBuilder::new().with_inner_size(PhysicalSize {
+        width: 100,
+        height: 100,
+    }));
+    window.mapped(true).await;
+    window.set_background_color(0, 0, 0);
+
+    // Pixels are read once the frame has been committed.
+    let frames = window.properties().frames();
+    let mut frame = [255, 0, 0, 255].repeat(100 * 100);
+    window.winit_present_rgba(&frame, 100, 100, &[]);
+    window.frames(frames + 1).await;
+    instance.take_screenshot();
+    assert_eq!(window.pixel(0, 0), (255, 0, 0));
+    assert_eq!(window.pixel(99, 99), (255, 0, 0));
+
+    // Only the damaged rectangle is updated.
+    for pixel in frame.chunks_exact_mut(4) {
+        pixel.copy_from_slice(&[0, 0, 255, 255]);
+    }
+    let damage = DamageRect {
+        x: 10,
+        y: 20,
+        width: 30,
+        height: 40,
+    };
+    window.winit_present_rgba(&frame, 100, 100, &[damage]);
+    window.frames(frames + 2).await;
+    instance.take_screenshot();
+    assert_eq!(window.pixel(10, 20), (0, 0, 255));
+    assert_eq!(window.pixel(39, 59), (0, 0, 255));
+    assert_eq!(window.pixel(9, 20), (255, 0, 0));
+    assert_eq!(window.pixel(40, 59), (255, 0, 0));
+    assert_eq!(window.pixel(39, 60), (255, 0, 0));
+}
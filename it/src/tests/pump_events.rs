@@ -0,0 +1,64 @@
+use crate::backend::Instance;
+use crate::event::UserEvent;
+use std::time::Duration;
+use winit::event::{Event, StartCause};
+use winit::event_loop::{ControlFlow, EventLoop};
+use winit::platform::pump_events::{EventLoopExtPumpEvents, PumpStatus};
+
+test!(run);
+
+fn pump(
+    el: &mut EventLoop<UserEvent>,
+    control_flow: ControlFlow,
+) -> (PumpStatus, Vec<Event<'static, UserEvent>>) {
+    let mut events = vec![];
+    let status = el.pump_events(Some(Duration::from_millis(0)), |ev, _, cf| {
+        *cf = control_flow;
+        events.extend(ev.to_static());
+    });
+    (status, events)
+}
+
+async fn run(instance: &dyn Instance) {
+    let el = instance.create_event_loop();
+
+    // Everything happens in one closure so that the event loop of the test harness doesn't
+    // dispatch the user event in between.
+    el.with_winit(Box::new(|el| {
+        log::info!("Checking that the first pump starts the event loop");
+
+        let (status, events) = pump(el, ControlFlow::Wait);
+        assert_eq!(status, PumpStatus::Continue);
+        assert!(matches!(events[0], Event::NewEvents(StartCause::Init)));
+        assert!(events.iter().any(|e| matches!(e, Event::MainEventsCleared)));
+        assert!(matches!(events.last(), Some(Event::RedrawEventsCleared)));
+
+        log::info!("Checking that user events are dispatched by the next pump");
+
+        el.create_proxy().send_event(UserEvent(1)).unwrap();
+        let (status, events) = pump(el, ControlFlow::Wait);
+        assert_eq!(status, PumpStatus::Continue);
+        assert!(matches!(
+            events[0],
+            Event::NewEvents(StartCause::WaitCancelled { .. })
+        ));
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, Event::UserEvent(UserEvent(1)))));
+        assert!(matches!(events.last(), Some(Event::RedrawEventsCleared)));
+
+        log::info!("Checking that exiting destroys the event loop");
+
+        let (status, events) = pump(el, ControlFlow::Exit);
+        assert_eq!(status, PumpStatus::Exit);
+        assert!(matches!(events.last(), Some(Event::LoopDestroyed)));
+
+        log::info!("Checking that the event loop stays exited");
+
+        for _ in 0..2 {
+            let (status, events) = pump(el, ControlFlow::Wait);
+            assert_eq!(status, PumpStatus::Exit);
+            assert!(events.is_empty());
+        }
+    }));
+}
@@ -1,10 +1,10 @@
-use crate::backend::Instance;
+use crate::backend::{BackendFlags, Instance};
 use crate::keyboard::Key::{KeyLeftbrace, KeyQ};
 use crate::keyboard::Layout;
 use winit::event::ElementState;
 use winit::keyboard::{Key as WKey, KeyCode, KeyLocation};
 
-test!(run);
+test!(run, BackendFlags::WINIT_RESET_DEAD_KEYS);
 
 async fn run(instance: &dyn Instance) {
     let el = instance.create_event_loop();
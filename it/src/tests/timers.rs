@@ -0,0 +1,70 @@
+use crate::backend::Instance;
+use crate::event::{Event, UserEvent};
+use crate::sleep::sleep_ms;
+use std::time::{Duration, Instant};
+
+test!(run);
+
+async fn run(instance: &dyn Instance) {
+    let el = instance.create_event_loop();
+    let mut events = el.events();
+
+    {
+        log::info!("Checking that timers fire in the order of their deadlines");
+
+        let now = Instant::now();
+        let deadlines = [
+            now + Duration::from_millis(20),
+            now + Duration::from_millis(10),
+        ];
+        let mut ids = vec![];
+        el.with_winit(Box::new(|el| {
+            for &deadline in &deadlines {
+                ids.push(el.add_timer(deadline).unwrap());
+            }
+        }));
+
+        for &i in &[1, 0] {
+            assert_eq!(events.timer().await, ids[i]);
+            assert!(Instant::now() >= deadlines[i]);
+        }
+    }
+
+    {
+        log::info!("Checking that interval timers fire repeatedly until they are cancelled");
+
+        let start = Instant::now();
+        let interval = Duration::from_millis(10);
+        let mut id = None;
+        el.with_winit(Box::new(|el| id = Some(el.add_timer(interval).unwrap())));
+        let id = id.unwrap();
+
+        for n in 1..=2 {
+            assert_eq!(events.timer().await, id);
+            assert!(Instant::now() >= start + n * interval);
+        }
+
+        el.with_winit(Box::new(|el| el.cancel_timer(id)));
+
+        // Skip the events that were emitted before the timer was cancelled.
+        el.send_event(UserEvent(1));
+        while events.user_event().await != UserEvent(1) {}
+
+        // Timers fire in the order of their deadlines, so the interval timer would fire before
+        // a timer that expires after its next deadline.
+        sleep_ms(20).await;
+        let mut marker = None;
+        el.with_winit(Box::new(|el| {
+            marker = Some(el.add_timer(Instant::now()).unwrap())
+        }));
+        let marker = marker.unwrap();
+
+        loop {
+            match events.event().await {
+                Event::Timer(t) if t == marker => break,
+                Event::Timer(t) => assert_ne!(t, id),
+                _ => {}
+            }
+        }
+    }
+}
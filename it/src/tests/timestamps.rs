@@ -0,0 +1,118 @@
+use crate::backend::{BackendFlags, Button, Instance};
+use crate::keyboard::Key;
+use crate::sleep::sleep_ms;
+use std::time::Duration;
+use winit::dpi::PhysicalSize;
+use winit::event::{ElementState, Timestamp, TouchPhase};
+use winit::window::WindowBuilder;
+
+test!(run);
+
+/// The time that passes between two inputs.
+const DELAY_MS: u64 = 100;
+
+/// Checks that the timestamps reflect the time between two inputs even though the events are
+/// only read afterwards.
+fn check_delay(earlier: Option<Timestamp>, later: Option<Timestamp>) {
+    let delay = later.unwrap().duration_since(earlier.unwrap());
+    log::info!("Delay between the events: {:?}", delay);
+    assert!(delay >= Duration::from_millis(DELAY_MS / 2));
+    assert!(delay < Duration::from_millis(DELAY_MS * 100));
+}
+
+async fn run(instance: &dyn Instance) {
+    let seat = instance.default_seat();
+    let mouse = seat.add_mouse();
+    let kb = seat.add_keyboard();
+    let touch = seat.add_touchscreen();
+
+    let el = instance.create_event_loop();
+    let mut events = el.events();
+    let window = el.create_window(WindowBuilder::new().with_inner_size(PhysicalSize {
+        width: 100,
+        height: 100,
+    }));
+    window.mapped(true).await;
+    window.set_outer_position(-window.inner_offset().0, -window.inner_offset().1);
+    window
+        .outer_position(-window.inner_offset().0, -window.inner_offset().1)
+        .await;
+    seat.set_cursor_position(50, 50);
+    seat.focus(&*window);
+
+    {
+        log::info!("Checking key events");
+        el.barrier().await;
+
+        let key = kb.press(Key::KeyL);
+        sleep_ms(DELAY_MS).await;
+        drop(key);
+
+        let (_, press) = events.window_keyboard_input().await;
+        assert_eq!(press.event.state, ElementState::Pressed);
+        let (_, release) = events.window_keyboard_input().await;
+        assert_eq!(release.event.state, ElementState::Released);
+        check_delay(press.time, release.time);
+    }
+
+    {
+        log::info!("Checking pointer events");
+        el.barrier().await;
+
+        mouse.move_(1, 1);
+        sleep_ms(DELAY_MS).await;
+        let button = mouse.press(Button::Left);
+        sleep_ms(DELAY_MS).await;
+        drop(button);
+        sleep_ms(DELAY_MS).await;
+        mouse.scroll(0, 1);
+
+        let (_, moved) = events.window_cursor_moved().await;
+        let (_, press) = events.window_mouse_input_event().await;
+        assert_eq!(press.state, ElementState::Pressed);
+        let (_, release) = events.window_mouse_input_event().await;
+        assert_eq!(release.state, ElementState::Released);
+        let (_, wheel) = events.window_mouse_wheel().await;
+        check_delay(moved.time, press.time);
+        check_delay(press.time, release.time);
+        check_delay(release.time, wheel.time);
+    }
+
+    {
+        log::info!("Checking touch events");
+        el.barrier().await;
+
+        let finger = touch.down(10, 10);
+        sleep_ms(DELAY_MS).await;
+        finger.move_(20, 20);
+        sleep_ms(DELAY_MS).await;
+        drop(finger);
+
+        let (_, down) = events.window_touch_event().await;
+        assert_eq!(down.phase, TouchPhase::Started);
+        let (_, moved) = events.window_touch_event().await;
+        assert_eq!(moved.phase, TouchPhase::Moved);
+        let (_, up) = events.window_touch_event().await;
+        assert_eq!(up.phase, TouchPhase::Ended);
+        check_delay(down.time, moved.time);
+        check_delay(moved.time, up.time);
+    }
+
+    // Only backends that report device events.
+    if instance
+        .backend()
+        .flags()
+        .contains(BackendFlags::DEVICE_ADDED)
+    {
+        log::info!("Checking device events");
+        el.barrier().await;
+
+        let key = kb.press(Key::KeyL);
+        sleep_ms(DELAY_MS).await;
+        drop(key);
+
+        let (press, _) = events.device_key_event().await;
+        let (release, _) = events.device_key_event().await;
+        check_delay(press.time, release.time);
+    }
+}
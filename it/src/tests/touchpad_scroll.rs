@@ -0,0 +1,61 @@
+use crate::backend::{BackendFlags, Instance};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{MouseScrollDelta, ScrollSource};
+use winit::window::WindowBuilder;
+
+test!(run, BackendFlags::GESTURES);
+
+async fn run(instance: &dyn Instance) {
+    let seat = instance.default_seat();
+    let touchpad = seat.add_touchpad();
+    let mouse = seat.add_mouse();
+
+    let el = instance.create_event_loop();
+    let mut events = el.events();
+
+    let window = el.create_window(WindowBuilder::new().with_inner_size(PhysicalSize {
+        width: 100,
+        height: 100,
+    }));
+    window.mapped(true).await;
+    window.set_outer_position(-window.inner_offset().0, -window.inner_offset().1);
+    window
+        .outer_position(-window.inner_offset().0, -window.inner_offset().1)
+        .await;
+    seat.set_cursor_position(50, 50);
+
+    {
+        log::info!("Checking that touchpads scroll by pixels");
+
+        touchpad.scroll(0, 30);
+
+        let (we, mw) = events.window_mouse_wheel().await;
+        assert_eq!(we.window_id, window.winit_id());
+        assert!(seat.is(mw.device_id));
+        assert_eq!(mw.source, ScrollSource::Finger);
+        assert_eq!(
+            mw.delta,
+            MouseScrollDelta::PixelDelta(PhysicalPosition { x: 0.0, y: 30.0 })
+        );
+
+        touchpad.scroll(-15, 0);
+
+        let (_, mw) = events.window_mouse_wheel().await;
+        assert_eq!(mw.source, ScrollSource::Finger);
+        assert_eq!(
+            mw.delta,
+            MouseScrollDelta::PixelDelta(PhysicalPosition { x: -15.0, y: 0.0 })
+        );
+    }
+
+    {
+        log::info!("Checking that mouse wheels scroll by lines");
+
+        mouse.scroll(0, 1);
+
+        let (we, mw) = events.window_mouse_wheel().await;
+        assert_eq!(we.window_id, window.winit_id());
+        assert_eq!(mw.source, ScrollSource::Wheel);
+        assert_eq!(mw.delta, MouseScrollDelta::LineDelta(0.0, 1.0));
+    }
+}
@@ -0,0 +1,28 @@
+use crate::backend::{BackendFlags, Instance};
+use std::collections::HashSet;
+use winit::platform::unix::VideoModeExtUnix;
+
+test!(run, BackendFlags::X11 | BackendFlags::SECOND_MONITOR);
+
+async fn run(instance: &dyn Instance) {
+    let el = instance.create_event_loop();
+    instance.enable_second_monitor(true);
+    el.num_available_monitors(2).await;
+
+    for monitor in el.available_monitors() {
+        let mut ids = HashSet::new();
+        for mode in monitor.video_modes() {
+            log::info!(
+                "{:?}: {} mHz, {:?}",
+                mode,
+                mode.refresh_rate_millihertz(),
+                mode.native_id()
+            );
+            let millihertz = mode.refresh_rate_millihertz() as i64;
+            assert!((millihertz - mode.refresh_rate() as i64 * 1000).abs() <= 500);
+            assert!(!mode.is_interlaced());
+            assert!(!mode.is_double_scan());
+            assert!(ids.insert(mode.native_id().unwrap()));
+        }
+    }
+}
@@ -1,10 +1,9 @@
 use crate::backend::{Button, Instance};
 use crate::eventstash::EventStash;
 use crate::keyboard::Key;
-use std::collections::HashSet;
 use winit::dpi::{PhysicalPosition, PhysicalSize};
-use winit::event::{ElementState, MouseButton, MouseScrollDelta, TouchPhase};
-use winit::keyboard::{KeyCode, ModifiersState};
+use winit::event::{ElementState, MouseButton, MouseScrollDelta, ScrollSource, TouchPhase};
+use winit::keyboard::KeyCode;
 use winit::window::WindowBuilder;
 
 test!(run);
@@ -85,6 +84,7 @@ async fn run(instance: &dyn Instance) {
         assert_eq!(we.window_id, window.winit_id());
         assert!(seat.is(cl.device_id));
         assert_eq!(cl.phase, TouchPhase::Moved);
+        assert_eq!(cl.source, ScrollSource::Wheel);
         let mut delta = match cl.delta {
             MouseScrollDelta::LineDelta(dx, dy) => (dx, dy),
             _ => unreachable!(),
@@ -149,78 +149,4 @@ async fn run(instance: &dyn Instance) {
         assert_eq!(ke.event.physical_key, KeyCode::KeyA);
         seat.un_focus();
     }
-
-    {
-        log::info!("Checking modifiers changed on cursor-enter");
-        // move cursor out of window
-        mouse1.move_(450, 450);
-        el.barrier().await;
-        kb1.press(Key::KeyLeftctrl);
-        let _shift = kb1.press(Key::KeyLeftshift);
-        mouse1.move_(-450, -450);
-        let (_, mc) = events.window_modifiers().await;
-        assert_eq!(mc, ModifiersState::SHIFT);
-    }
-
-    {
-        log::info!("Testing multi-window modifiers events");
-        let w2 = el.create_window(WindowBuilder::new().with_inner_size(PhysicalSize {
-            width: 100,
-            height: 100,
-        }));
-        w2.mapped(true).await;
-        w2.set_outer_position(300, 300);
-        w2.outer_position(300, 300).await;
-        seat.focus(&*window);
-        {
-            seat.set_cursor_position(310 + w2.inner_offset().0, 310 + w2.inner_offset().1);
-            loop {
-                let (we, cp) = events.window_cursor_moved().await;
-                if we.window_id == w2.winit_id() && cp.position.x == 10.0 && cp.position.y == 10.0 {
-                    break;
-                }
-            }
-            let _shift = kb1.press(Key::KeyLeftshift);
-            let mut targets = HashSet::new();
-            targets.insert(window.winit_id());
-            targets.insert(w2.winit_id());
-            while !targets.is_empty() {
-                let (we, mo) = events.window_modifiers().await;
-                assert_eq!(mo, ModifiersState::SHIFT);
-                targets.remove(&we.window_id);
-            }
-        }
-        el.barrier().await;
-        {
-            seat.set_cursor_position(500, 500);
-            events.window_cursor_left().await;
-            let _shift = kb1.press(Key::KeyLeftshift);
-            let (we, mo) = events.window_modifiers().await;
-            assert_eq!(mo, ModifiersState::SHIFT);
-            assert_eq!(we.window_id, window.winit_id());
-            seat.set_cursor_position(310 + w2.inner_offset().0, 310 + w2.inner_offset().1);
-            let (we, mo) = events.window_modifiers().await;
-            assert_eq!(mo, ModifiersState::SHIFT);
-            assert_eq!(we.window_id, w2.winit_id());
-        }
-        el.barrier().await;
-        {
-            seat.set_cursor_position(500, 500);
-            events.window_cursor_left().await;
-            {
-                let _shift = kb1.press(Key::KeyLeftshift);
-                let (we, mo) = events.window_modifiers().await;
-                assert_eq!(mo, ModifiersState::SHIFT);
-                assert_eq!(we.window_id, window.winit_id());
-            }
-            let (we, mo) = events.window_modifiers().await;
-            assert_eq!(mo, ModifiersState::empty());
-            assert_eq!(we.window_id, window.winit_id());
-            el.barrier().await;
-            seat.set_cursor_position(310 + w2.inner_offset().0, 310 + w2.inner_offset().1);
-            let (we, mo) = events.window_modifiers().await;
-            assert_eq!(mo, ModifiersState::empty());
-            assert_eq!(we.window_id, w2.winit_id());
-        }
-    }
 }
@@ -13,6 +13,8 @@ typedef enum {
   TyKeyboard = 1,
   TyMouse,
   TyTouch,
+  TyTouchpad,
+  TyTablet,
 } Type;
 
 typedef struct Device {
@@ -69,6 +71,37 @@ static void init_mouse(DeviceIntPtr dev, Device *device) {
   assert(InitPointerAccelerationScheme(dev, PtrAccelNoOp));
 }
 
+static void init_touchpad(DeviceIntPtr dev, Device *device) {
+  init_mouse(dev, device);
+  assert(InitGestureClassDeviceStruct(dev, 4));
+}
+
+static void init_tablet(DeviceIntPtr dev, Device *device) {
+  Atom button_labels[] = {
+      XIGetKnownProperty(BTN_LABEL_PROP_BTN_LEFT),
+      XIGetKnownProperty(BTN_LABEL_PROP_BTN_MIDDLE),
+      XIGetKnownProperty(BTN_LABEL_PROP_BTN_RIGHT),
+  };
+  Atom valuator_labels[] = {
+      XIGetKnownProperty(AXIS_LABEL_PROP_ABS_X),
+      XIGetKnownProperty(AXIS_LABEL_PROP_ABS_Y),
+      XIGetKnownProperty(AXIS_LABEL_PROP_ABS_PRESSURE),
+      XIGetKnownProperty(AXIS_LABEL_PROP_ABS_TILT_X),
+      XIGetKnownProperty(AXIS_LABEL_PROP_ABS_TILT_Y),
+  };
+  uint8_t button_map[] = { 0, 1, 2, 3 };
+  assert(InitPointerDeviceStruct(&dev->public, button_map, 3, button_labels, ptr_control, GetMotionHistorySize(), 5, valuator_labels));
+  xf86InitValuatorAxisStruct(dev, 0, valuator_labels[0], 0, 1023, 0, 0, 0, Absolute);
+  xf86InitValuatorAxisStruct(dev, 1, valuator_labels[1], 0, 767, 0, 0, 0, Absolute);
+  xf86InitValuatorAxisStruct(dev, 2, valuator_labels[2], 0, 1023, 0, 0, 0, Absolute);
+  xf86InitValuatorAxisStruct(dev, 3, valuator_labels[3], -64, 63, 0, 0, 0, Absolute);
+  xf86InitValuatorAxisStruct(dev, 4, valuator_labels[4], -64, 63, 0, 0, 0, Absolute);
+  assert(InitProximityClassDeviceStruct(dev));
+  assert(InitPointerAccelerationScheme(dev, PtrAccelNoOp));
+  device->mask = valuator_mask_new(5);
+  assert(device->mask);
+}
+
 static int device_control(DeviceIntPtr dev, int what) {
   InputInfoPtr pInfo = dev->public.devicePrivate;
   Device *device = pInfo->private;
@@ -85,6 +118,12 @@ static int device_control(DeviceIntPtr dev, int what) {
     case TyTouch:
       init_touch(dev, device);
       break;
+    case TyTouchpad:
+      init_touchpad(dev, device);
+      break;
+    case TyTablet:
+      init_tablet(dev, device);
+      break;
     }
   case DEVICE_ON:
   case DEVICE_OFF:
@@ -110,6 +149,12 @@ static int pre_init(InputDriverPtr drv, InputInfoPtr pInfo, int flags) {
   case TyTouch:
     pInfo->type_name = XI_TOUCHSCREEN;
     break;
+  case TyTouchpad:
+    pInfo->type_name = XI_TOUCHPAD;
+    break;
+  case TyTablet:
+    pInfo->type_name = XI_TABLET;
+    break;
   default:
     assert(0 && "Invalid type");
   }
@@ -178,6 +223,17 @@ uint32_t input_new_touch() {
   return input_new("touchscreen");
 }
 
+uint32_t input_new_touchpad() {
+  current_type = TyTouchpad;
+  return input_new("touchpad");
+}
+
+// The name of an eraser identifies it as such.
+uint32_t input_new_tablet(uint32_t eraser) {
+  current_type = TyTablet;
+  return input_new(eraser ? "eraser" : "tablet");
+}
+
 #define MIN_KEYCODE 8
 
 static Device *get_device(uint32_t id) {
@@ -210,6 +266,18 @@ static Device *get_touch(uint32_t touch) {
   return device;
 }
 
+static Device *get_touchpad(uint32_t touchpad) {
+  Device *device = get_device(touchpad);
+  assert(device->type == TyTouchpad);
+  return device;
+}
+
+static Device *get_tablet(uint32_t tablet) {
+  Device *device = get_device(tablet);
+  assert(device->type == TyTablet);
+  return device;
+}
+
 void input_key_press(uint32_t keyboard, uint8_t key) {
   Device *device = get_keyboard(keyboard);
   xf86PostKeyboardEvent(device->device->dev, key + MIN_KEYCODE, 1);
@@ -275,6 +343,58 @@ void input_touch_move(uint32_t touch, uint32_t touch_id, int32_t x, int32_t y) {
   xf86PostTouchEvent(device->device->dev, touch_id, XI_TouchUpdate, 0, device->mask);
 }
 
+// Unlike mouse wheels, touchpads scroll by raw valuator units.
+void input_touchpad_scroll(uint32_t touchpad, int32_t dx, int32_t dy) {
+  Device *device = get_touchpad(touchpad);
+  valuator_mask_zero(device->mask);
+  if (dx) {
+    valuator_mask_set(device->mask, 2, dx);
+  }
+  if (dy) {
+    valuator_mask_set(device->mask, 3, dy);
+  }
+  xf86PostMotionEventM(device->device->dev, Relative, device->mask);
+}
+
+void input_gesture_pinch(uint32_t touchpad, uint16_t type, uint16_t fingers, uint32_t flags, double dx, double dy, double scale, double angle) {
+  Device *device = get_touchpad(touchpad);
+  xf86PostGesturePinchEvent(device->device->dev, type, fingers, flags, dx, dy, dx, dy, scale, angle);
+}
+
+void input_gesture_swipe(uint32_t touchpad, uint16_t type, uint16_t fingers, uint32_t flags, double dx, double dy) {
+  Device *device = get_touchpad(touchpad);
+  xf86PostGestureSwipeEvent(device->device->dev, type, fingers, flags, dx, dy, dx, dy);
+}
+
+static void set_tablet_axes(Device *device, int32_t x, int32_t y, int32_t pressure, int32_t tilt_x, int32_t tilt_y) {
+  valuator_mask_zero(device->mask);
+  valuator_mask_set(device->mask, 0, x);
+  valuator_mask_set(device->mask, 1, y);
+  valuator_mask_set(device->mask, 2, pressure);
+  valuator_mask_set(device->mask, 3, tilt_x);
+  valuator_mask_set(device->mask, 4, tilt_y);
+}
+
+void input_tablet_proximity(uint32_t tablet, uint32_t in, int32_t x, int32_t y, int32_t pressure, int32_t tilt_x, int32_t tilt_y) {
+  Device *device = get_tablet(tablet);
+  set_tablet_axes(device, x, y, pressure, tilt_x, tilt_y);
+  xf86PostProximityEventM(device->device->dev, in, device->mask);
+  if (in) {
+    xf86PostMotionEventM(device->device->dev, Absolute, device->mask);
+  }
+}
+
+void input_tablet_move(uint32_t tablet, int32_t x, int32_t y, int32_t pressure, int32_t tilt_x, int32_t tilt_y) {
+  Device *device = get_tablet(tablet);
+  set_tablet_axes(device, x, y, pressure, tilt_x, tilt_y);
+  xf86PostMotionEventM(device->device->dev, Absolute, device->mask);
+}
+
+void input_tablet_button(uint32_t tablet, uint32_t button, uint32_t pressed) {
+  Device *device = get_tablet(tablet);
+  xf86PostButtonEvent(device->device->dev, Absolute, button, pressed, 0, 0);
+}
+
 void input_remove_device(uint32_t id) {
   Device *device = get_device(id);
   DeleteInputDeviceRequest(device->device->dev);
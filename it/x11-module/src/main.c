@@ -35,6 +35,16 @@ enum MessageType {
   MT_TOUCH_DOWN_REPLY,
   MT_TOUCH_UP,
   MT_TOUCH_MOVE,
+  MT_CREATE_TOUCHPAD,
+  MT_CREATE_TOUCHPAD_REPLY,
+  MT_GESTURE_PINCH,
+  MT_GESTURE_SWIPE,
+  MT_CREATE_TABLET,
+  MT_CREATE_TABLET_REPLY,
+  MT_TABLET_PROXIMITY,
+  MT_TABLET_MOVE,
+  MT_TABLET_BUTTON,
+  MT_TOUCHPAD_SCROLL,
 };
 
 typedef struct {
@@ -87,6 +97,37 @@ typedef union {
     int32_t dx;
     int32_t dy;
   } mouse_move;
+  struct {
+    uint32_t type;
+    uint32_t id;
+    uint32_t gesture_type;
+    uint32_t fingers;
+    uint32_t flags;
+    double dx;
+    double dy;
+    double scale;
+    double angle;
+  } gesture;
+  struct {
+    uint32_t type;
+    uint32_t eraser;
+  } create_tablet;
+  struct {
+    uint32_t type;
+    uint32_t id;
+    uint32_t in;
+    int32_t x;
+    int32_t y;
+    int32_t pressure;
+    int32_t tilt_x;
+    int32_t tilt_y;
+  } tablet_tool;
+  struct {
+    uint32_t type;
+    uint32_t id;
+    uint32_t button;
+    uint32_t pressed;
+  } tablet_button;
   struct {
     uint32_t type;
     uint32_t id;
@@ -182,6 +223,47 @@ static void handle_message(int fd, void *closure) {
     input_touch_up(message.touch_move.id, message.touch_move.touch_id);
     break;
   }
+  case MT_CREATE_TOUCHPAD: {
+    uint32_t id = input_new_touchpad();
+    CreateKeyboardReply reply = {
+        .type = MT_CREATE_TOUCHPAD_REPLY,
+        .id = id,
+    };
+    assert(write(fd, &reply, sizeof(reply)) > 0);
+    break;
+  }
+  case MT_GESTURE_PINCH: {
+    input_gesture_pinch(message.gesture.id, message.gesture.gesture_type, message.gesture.fingers, message.gesture.flags, message.gesture.dx, message.gesture.dy, message.gesture.scale, message.gesture.angle);
+    break;
+  }
+  case MT_GESTURE_SWIPE: {
+    input_gesture_swipe(message.gesture.id, message.gesture.gesture_type, message.gesture.fingers, message.gesture.flags, message.gesture.dx, message.gesture.dy);
+    break;
+  }
+  case MT_CREATE_TABLET: {
+    uint32_t id = input_new_tablet(message.create_tablet.eraser);
+    CreateKeyboardReply reply = {
+        .type = MT_CREATE_TABLET_REPLY,
+        .id = id,
+    };
+    assert(write(fd, &reply, sizeof(reply)) > 0);
+    break;
+  }
+  case MT_TABLET_PROXIMITY: {
+    input_tablet_proximity(message.tablet_tool.id, message.tablet_tool.in, message.tablet_tool.x, message.tablet_tool.y, message.tablet_tool.pressure, message.tablet_tool.tilt_x, message.tablet_tool.tilt_y);
+    break;
+  }
+  case MT_TABLET_MOVE: {
+    input_tablet_move(message.tablet_tool.id, message.tablet_tool.x, message.tablet_tool.y, message.tablet_tool.pressure, message.tablet_tool.tilt_x, message.tablet_tool.tilt_y);
+    break;
+  }
+  case MT_TABLET_BUTTON: {
+    input_tablet_button(message.tablet_button.id, message.tablet_button.button, message.tablet_button.pressed);
+    break;
+  }
+  case MT_TOUCHPAD_SCROLL:
+    input_touchpad_scroll(message.mouse_move.id, message.mouse_move.dx, message.mouse_move.dy);
+    break;
   default:
     LogMessage(X_ERROR, "Invalid message type %u\n", message.type);
     assert(0 && "Invalid message type");
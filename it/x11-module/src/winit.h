@@ -24,4 +24,14 @@ uint32_t input_touch_down(uint32_t touch, int32_t x, int32_t y);
 void input_touch_up(uint32_t touch, uint32_t touch_id);
 void input_touch_move(uint32_t touch, uint32_t touch_id, int32_t x, int32_t y);
 
+uint32_t input_new_touchpad();
+void input_touchpad_scroll(uint32_t touchpad, int32_t dx, int32_t dy);
+void input_gesture_pinch(uint32_t touchpad, uint16_t type, uint16_t fingers, uint32_t flags, double dx, double dy, double scale, double angle);
+void input_gesture_swipe(uint32_t touchpad, uint16_t type, uint16_t fingers, uint32_t flags, double dx, double dy);
+
+uint32_t input_new_tablet(uint32_t eraser);
+void input_tablet_proximity(uint32_t tablet, uint32_t in, int32_t x, int32_t y, int32_t pressure, int32_t tilt_x, int32_t tilt_y);
+void input_tablet_move(uint32_t tablet, int32_t x, int32_t y, int32_t pressure, int32_t tilt_x, int32_t tilt_y);
+void input_tablet_button(uint32_t tablet, uint32_t button, uint32_t pressed);
+
 void input_remove_device(uint32_t id);
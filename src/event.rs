@@ -34,11 +34,13 @@
 //!
 //! [event_loop_run]: crate::event_loop::EventLoop::run
 use instant::Instant;
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
 use crate::{
     dpi::{PhysicalPosition, PhysicalSize},
+    event_loop::TimerId,
     keyboard::{self, ModifiersState},
+    monitor::MonitorHandle,
     platform_impl,
     window::{Theme, WindowId},
 };
@@ -66,6 +68,9 @@ pub enum Event<'a, T: 'static> {
     DeviceEvent {
         device_id: DeviceId,
         event: DeviceEvent,
+        /// The time at which the event was generated. `None` if the platform does not report
+        /// it.
+        time: Option<Timestamp>,
     },
 
     /// Emitted when an event is sent from [`EventLoopProxy::send_event`](crate::event_loop::EventLoopProxy::send_event)
@@ -112,6 +117,61 @@ pub enum Event<'a, T: 'static> {
     /// tasks have been completed.
     RedrawEventsCleared,
 
+    /// Emitted when the state of a clipboard changes or when data requested from a clipboard
+    /// becomes available.
+    ///
+    /// See [`ClipboardEvent`] for details.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only emitted on **X11** and **Wayland**.
+    Clipboard(ClipboardEvent),
+
+    /// Emitted when a file descriptor that was registered with
+    /// [`register_fd`](crate::platform::unix::EventLoopWindowTargetExtUnix::register_fd) is ready.
+    ///
+    /// The event is emitted in every iteration of the event loop as long as the file descriptor
+    /// stays ready.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only emitted on **X11** and **Wayland**.
+    FdReady {
+        token: SourceToken,
+        readiness: Readiness,
+    },
+
+    /// Emitted when a timer that was added with
+    /// [`add_timer`](crate::event_loop::EventLoopWindowTarget::add_timer) fires.
+    ///
+    /// Timers are emitted after [`NewEvents`](Event::NewEvents) in the order of their deadlines.
+    Timer(TimerId),
+
+    /// Emitted when a monitor has been connected.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only emitted on **X11** and **Wayland**.
+    MonitorAdded(MonitorHandle),
+
+    /// Emitted when a monitor has been disconnected.
+    ///
+    /// The handle reports the properties that the monitor had before it was disconnected.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only emitted on **X11** and **Wayland**.
+    MonitorRemoved(MonitorHandle),
+
+    /// Emitted when the position, size, scale factor, or video modes of a monitor have changed.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only emitted on **X11** and **Wayland**.
+    /// - **Wayland:** Also emitted when the compositor resends the properties of a monitor without
+    ///   changing them.
+    MonitorChanged(MonitorHandle),
+
     /// Emitted when the event loop is being shut down.
     ///
     /// This is irreversable - if this event is emitted, it is guaranteed to be the last event that
@@ -128,9 +188,14 @@ impl<T: Clone> Clone for Event<'static, T> {
                 event: event.clone(),
             },
             UserEvent(event) => UserEvent(event.clone()),
-            DeviceEvent { device_id, event } => DeviceEvent {
+            DeviceEvent {
+                device_id,
+                event,
+                time,
+            } => DeviceEvent {
                 device_id: *device_id,
                 event: event.clone(),
+                time: *time,
             },
             NewEvents(cause) => NewEvents(cause.clone()),
             MainEventsCleared => MainEventsCleared,
@@ -139,6 +204,15 @@ impl<T: Clone> Clone for Event<'static, T> {
             LoopDestroyed => LoopDestroyed,
             Suspended => Suspended,
             Resumed => Resumed,
+            Clipboard(event) => Clipboard(event.clone()),
+            FdReady { token, readiness } => FdReady {
+                token: *token,
+                readiness: *readiness,
+            },
+            Timer(id) => Timer(*id),
+            MonitorAdded(monitor) => MonitorAdded(monitor.clone()),
+            MonitorRemoved(monitor) => MonitorRemoved(monitor.clone()),
+            MonitorChanged(monitor) => MonitorChanged(monitor.clone()),
         }
     }
 }
@@ -149,7 +223,15 @@ impl<'a, T> Event<'a, T> {
         match self {
             UserEvent(_) => Err(self),
             WindowEvent { window_id, event } => Ok(WindowEvent { window_id, event }),
-            DeviceEvent { device_id, event } => Ok(DeviceEvent { device_id, event }),
+            DeviceEvent {
+                device_id,
+                event,
+                time,
+            } => Ok(DeviceEvent {
+                device_id,
+                event,
+                time,
+            }),
             NewEvents(cause) => Ok(NewEvents(cause)),
             MainEventsCleared => Ok(MainEventsCleared),
             RedrawRequested(wid) => Ok(RedrawRequested(wid)),
@@ -157,6 +239,12 @@ impl<'a, T> Event<'a, T> {
             LoopDestroyed => Ok(LoopDestroyed),
             Suspended => Ok(Suspended),
             Resumed => Ok(Resumed),
+            Clipboard(event) => Ok(Clipboard(event)),
+            FdReady { token, readiness } => Ok(FdReady { token, readiness }),
+            Timer(id) => Ok(Timer(id)),
+            MonitorAdded(monitor) => Ok(MonitorAdded(monitor)),
+            MonitorRemoved(monitor) => Ok(MonitorRemoved(monitor)),
+            MonitorChanged(monitor) => Ok(MonitorChanged(monitor)),
         }
     }
 
@@ -169,7 +257,15 @@ impl<'a, T> Event<'a, T> {
                 .to_static()
                 .map(|event| WindowEvent { window_id, event }),
             UserEvent(event) => Some(UserEvent(event)),
-            DeviceEvent { device_id, event } => Some(DeviceEvent { device_id, event }),
+            DeviceEvent {
+                device_id,
+                event,
+                time,
+            } => Some(DeviceEvent {
+                device_id,
+                event,
+                time,
+            }),
             NewEvents(cause) => Some(NewEvents(cause)),
             MainEventsCleared => Some(MainEventsCleared),
             RedrawRequested(wid) => Some(RedrawRequested(wid)),
@@ -177,10 +273,207 @@ impl<'a, T> Event<'a, T> {
             LoopDestroyed => Some(LoopDestroyed),
             Suspended => Some(Suspended),
             Resumed => Some(Resumed),
+            Clipboard(event) => Some(Clipboard(event)),
+            FdReady { token, readiness } => Some(FdReady { token, readiness }),
+            Timer(id) => Some(Timer(id)),
+            MonitorAdded(monitor) => Some(MonitorAdded(monitor)),
+            MonitorRemoved(monitor) => Some(MonitorRemoved(monitor)),
+            MonitorChanged(monitor) => Some(MonitorChanged(monitor)),
         }
     }
 }
 
+/// Identifies a file descriptor that was registered with
+/// [`register_fd`](crate::platform::unix::EventLoopWindowTargetExtUnix::register_fd).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SourceToken(pub(crate) u64);
+
+bitflags! {
+    /// The readiness of a registered file descriptor.
+    #[derive(Default)]
+    pub struct Readiness: u32 {
+        /// The file descriptor can be read from without blocking.
+        const READABLE = 1 << 0;
+        /// The file descriptor can be written to without blocking.
+        const WRITABLE = 1 << 1;
+        /// An error occurred on the file descriptor.
+        const ERROR = 1 << 2;
+    }
+}
+
+/// Identifies one of the clipboards provided by the windowing system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ClipboardKind {
+    /// The regular clipboard that is usually used with copy and paste keyboard shortcuts.
+    ///
+    /// On X11 this is the `CLIPBOARD` selection. On Wayland this is the selection of the
+    /// `wl_data_device`.
+    Clipboard,
+
+    /// The primary selection that usually contains the currently selected text and is pasted
+    /// with the middle mouse button.
+    ///
+    /// On X11 this is the `PRIMARY` selection. On Wayland this requires the
+    /// `zwp_primary_selection_device_manager_v1` protocol.
+    Primary,
+}
+
+/// Describes a change of a clipboard or the result of a clipboard request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ClipboardEvent {
+    /// Data that was requested with
+    /// [`request_clipboard_data`](crate::platform::unix::EventLoopWindowTargetExtUnix::request_clipboard_data)
+    /// has been received.
+    ///
+    /// `data` is `None` if the clipboard is empty or if its owner could not provide the data in
+    /// the requested MIME type.
+    Data {
+        kind: ClipboardKind,
+        mime_type: String,
+        data: Option<Vec<u8>>,
+    },
+
+    /// The MIME types that were requested with
+    /// [`request_clipboard_mime_types`](crate::platform::unix::EventLoopWindowTargetExtUnix::request_clipboard_mime_types)
+    /// have been received.
+    ///
+    /// The list is empty if the clipboard is empty.
+    MimeTypes {
+        kind: ClipboardKind,
+        mime_types: Vec<String>,
+    },
+
+    /// Another application has taken ownership of the clipboard.
+    ///
+    /// The data previously set by this application is no longer offered to other applications.
+    OwnershipLost { kind: ClipboardKind },
+}
+
+/// Describes an event from an input method (IME).
+///
+/// Input methods are only used for a window after
+/// [`Window::set_ime_allowed`](crate::window::Window::set_ime_allowed) has been called.
+/// While an input method is enabled, key presses can be consumed by it. The text that the user
+/// composes is then reported via `Preedit` and `Commit` instead of `KeyboardInput` events.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Ime {
+    /// The input method has been enabled for the window.
+    ///
+    /// `Preedit` and `Commit` events can only be received between this event and `Disabled`.
+    Enabled,
+
+    /// The text that is currently being composed has changed.
+    ///
+    /// This replaces the previous preedit text. An empty `text` means that the preedit text
+    /// should be hidden.
+    Preedit {
+        /// The text that is being composed.
+        text: String,
+        /// The byte range of the cursor in `text`.
+        ///
+        /// The range is empty if the input method only reports a caret position. `None` if the
+        /// cursor should be hidden.
+        cursor: Option<(usize, usize)>,
+        /// The parts of `text` that should be rendered differently.
+        ///
+        /// The spans are ordered and do not overlap.
+        spans: Vec<PreeditSpan>,
+    },
+
+    /// The input method has committed text that should be inserted at the cursor.
+    ///
+    /// The preedit text should be cleared before this event is received.
+    Commit(String),
+
+    /// The input method has been disabled for the window.
+    ///
+    /// Any preedit text should be hidden.
+    Disabled,
+}
+
+/// A part of the preedit text with a specific style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PreeditSpan {
+    /// The byte offset of the start of the span.
+    pub start: usize,
+    /// The byte offset of the end of the span.
+    pub end: usize,
+    pub style: PreeditStyle,
+}
+
+bitflags! {
+    /// The style of a part of the preedit text.
+    #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct PreeditStyle: u32 {
+        /// The text should be underlined.
+        const UNDERLINE = 1 << 0;
+        /// The text should be highlighted, e.g., because it is currently selected for
+        /// conversion.
+        const HIGHLIGHT = 1 << 1;
+        /// The foreground and background colors of the text should be swapped.
+        const REVERSE = 1 << 2;
+    }
+}
+
+/// Describes an event of a drag-and-drop operation over a window.
+///
+/// The application decides whether it accepts the drop at the current position with
+/// [`set_dnd_action`](crate::platform::unix::EventLoopWindowTargetExtUnix::set_dnd_action) and
+/// retrieves the dragged data with
+/// [`request_dnd_data`](crate::platform::unix::EventLoopWindowTargetExtUnix::request_dnd_data).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DndEvent {
+    /// A drag has entered the window.
+    ///
+    /// `mime_types` contains the MIME types that the data is offered as.
+    Enter { mime_types: Vec<String> },
+
+    /// The drag has moved within the window.
+    ///
+    /// `action` is the action that the source of the drag proposes.
+    Motion {
+        position: PhysicalPosition<f64>,
+        action: DndAction,
+    },
+
+    /// The drag has left the window without being dropped.
+    Leave,
+
+    /// The data has been dropped at the last position reported via `Motion`.
+    ///
+    /// This event is only emitted if the drop was accepted at that position. Data that is
+    /// requested while handling this event is still available.
+    Drop,
+
+    /// Data that was requested with
+    /// [`request_dnd_data`](crate::platform::unix::EventLoopWindowTargetExtUnix::request_dnd_data)
+    /// has been received.
+    ///
+    /// `data` is `None` if the source could not provide the data in the requested MIME type.
+    Data {
+        mime_type: String,
+        data: Option<Vec<u8>>,
+    },
+}
+
+/// The action that is performed with the data of a drag-and-drop operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DndAction {
+    /// The data is copied.
+    Copy,
+    /// The data is moved, i.e., the source deletes it after the drop.
+    Move,
+    /// A link to the data is created.
+    Link,
+}
+
 /// Describes the reason the event loop is resuming.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StartCause {
@@ -240,12 +533,23 @@ pub enum WindowEvent<'a> {
     /// hovered.
     HoveredFileCancelled,
 
-    /// The user commited an IME string for this window.
+    /// An event of a drag-and-drop operation over the window.
     ///
-    /// This is a temporary API until [#1497] gets completed.
+    /// Unlike `HoveredFile` and `DroppedFile`, these events are emitted for all kinds of
+    /// dragged data. See [`DndEvent`] for details.
+    DragAndDrop(DndEvent),
+
+    /// A drag-and-drop operation started with
+    /// [`Window::start_drag`](crate::window::Window::start_drag) has ended.
     ///
-    /// [#1497]: https://github.com/rust-windowing/winit/issues/1497
-    ReceivedImeText(String),
+    /// `action` is the action that the target of the drop performed with the data, or `None`
+    /// if the drag was cancelled or the drop was rejected.
+    DragFinished { action: Option<DndAction> },
+
+    /// An event from an input method.
+    ///
+    /// See [`Ime`] for details.
+    Ime(Ime),
 
     /// The window gained or lost focus.
     ///
@@ -272,6 +576,10 @@ pub enum WindowEvent<'a> {
         ///
         /// Otherwise, this value is always `false`.
         is_synthetic: bool,
+
+        /// The time at which the key was pressed or released. `None` if the platform does not
+        /// report it or if the event is synthetic.
+        time: Option<Timestamp>,
     },
 
     /// The keyboard modifiers have changed.
@@ -289,6 +597,8 @@ pub enum WindowEvent<'a> {
         /// limited by the display area and it may have been transformed by the OS to implement effects such as cursor
         /// acceleration, it should not be used to implement non-cursor-like interactions such as 3D camera control.
         position: PhysicalPosition<f64>,
+        /// The time at which the cursor moved. `None` if the platform does not report it.
+        time: Option<Timestamp>,
         #[deprecated = "Deprecated in favor of WindowEvent::ModifiersChanged"]
         modifiers: ModifiersState,
     },
@@ -300,10 +610,22 @@ pub enum WindowEvent<'a> {
     CursorLeft { device_id: DeviceId },
 
     /// A mouse wheel movement or touchpad scroll occurred.
+    ///
+    /// Scrolling with fingers starts with a `Started` phase and ends with an `Ended` phase once
+    /// the fingers are lifted. Scrolling with a wheel only produces `Moved` phases.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The phase is always `Moved` since X11 does not report when scrolling starts or
+    ///   stops. Touchpads report a `PixelDelta` in the units of their scroll valuators.
+    /// - **Windows / Web:** The source is always `Wheel`.
     MouseWheel {
         device_id: DeviceId,
         delta: MouseScrollDelta,
         phase: TouchPhase,
+        source: ScrollSource,
+        /// The time at which the scroll happened. `None` if the platform does not report it.
+        time: Option<Timestamp>,
         #[deprecated = "Deprecated in favor of WindowEvent::ModifiersChanged"]
         modifiers: ModifiersState,
     },
@@ -313,6 +635,9 @@ pub enum WindowEvent<'a> {
         device_id: DeviceId,
         state: ElementState,
         button: MouseButton,
+        /// The time at which the button was pressed or released. `None` if the platform does not
+        /// report it.
+        time: Option<Timestamp>,
         #[deprecated = "Deprecated in favor of WindowEvent::ModifiersChanged"]
         modifiers: ModifiersState,
     },
@@ -338,6 +663,62 @@ pub enum WindowEvent<'a> {
     /// Touch event has been received
     Touch(Touch),
 
+    /// Input from a pen or another tool of a drawing tablet.
+    ///
+    /// Tools also move the cursor and press the left mouse button while they touch the tablet,
+    /// so applications that do not handle this event still receive the usual mouse events.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only supported on X11 and Wayland (`zwp_tablet_v2`).
+    Pen(Pen),
+
+    /// Two-finger pinch gesture on a touchpad, usually used for zooming.
+    ///
+    /// `delta` is the change of the scale since the previous event. Positive values indicate
+    /// magnification (zooming in) and negative values indicate shrinking (zooming out). The
+    /// event with `TouchPhase::Started` has a `delta` of `0.0`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only supported on X11 (XInput 2.4 or newer) and Wayland (`zwp_pointer_gestures_v1`).
+    PinchGesture {
+        device_id: DeviceId,
+        delta: f64,
+        phase: TouchPhase,
+    },
+
+    /// Two-finger rotation gesture on a touchpad.
+    ///
+    /// `delta` is the rotation in degrees since the previous event. Positive values indicate
+    /// counterclockwise rotation. Rotation is reported as part of pinch gestures, so this event
+    /// is always preceded by a `PinchGesture` event with the same phase.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only supported on X11 (XInput 2.4 or newer) and Wayland (`zwp_pointer_gestures_v1`).
+    RotationGesture {
+        device_id: DeviceId,
+        delta: f32,
+        phase: TouchPhase,
+    },
+
+    /// Multi-finger swipe gesture on a touchpad.
+    ///
+    /// `delta` is the movement of the center of the fingers since the previous event and
+    /// `fingers` is the number of fingers taking part in the gesture. The event with
+    /// `TouchPhase::Started` has a `delta` of `(0.0, 0.0)`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only supported on X11 (XInput 2.4 or newer) and Wayland (`zwp_pointer_gestures_v1`).
+    SwipeGesture {
+        device_id: DeviceId,
+        delta: PhysicalPosition<f64>,
+        fingers: u32,
+        phase: TouchPhase,
+    },
+
     /// The window's scale factor has changed.
     ///
     /// The following user actions can cause DPI changes:
@@ -376,26 +757,32 @@ impl Clone for WindowEvent<'static> {
             DroppedFile(file) => DroppedFile(file.clone()),
             HoveredFile(file) => HoveredFile(file.clone()),
             HoveredFileCancelled => HoveredFileCancelled,
-            ReceivedImeText(s) => ReceivedImeText(s.clone()),
+            DragAndDrop(event) => DragAndDrop(event.clone()),
+            DragFinished { action } => DragFinished { action: *action },
+            Ime(ime) => Ime(ime.clone()),
             Focused(f) => Focused(*f),
             KeyboardInput {
                 device_id,
                 event,
                 is_synthetic,
+                time,
             } => KeyboardInput {
                 device_id: *device_id,
                 event: event.clone(),
                 is_synthetic: *is_synthetic,
+                time: *time,
             },
             ModifiersChanged(modifiers) => ModifiersChanged(modifiers.clone()),
             #[allow(deprecated)]
             CursorMoved {
                 device_id,
                 position,
+                time,
                 modifiers,
             } => CursorMoved {
                 device_id: *device_id,
                 position: *position,
+                time: *time,
                 modifiers: *modifiers,
             },
             CursorEntered { device_id } => CursorEntered {
@@ -409,11 +796,15 @@ impl Clone for WindowEvent<'static> {
                 device_id,
                 delta,
                 phase,
+                source,
+                time,
                 modifiers,
             } => MouseWheel {
                 device_id: *device_id,
                 delta: *delta,
                 phase: *phase,
+                source: *source,
+                time: *time,
                 modifiers: *modifiers,
             },
             #[allow(deprecated)]
@@ -421,11 +812,13 @@ impl Clone for WindowEvent<'static> {
                 device_id,
                 state,
                 button,
+                time,
                 modifiers,
             } => MouseInput {
                 device_id: *device_id,
                 state: *state,
                 button: *button,
+                time: *time,
                 modifiers: *modifiers,
             },
             TouchpadPressure {
@@ -447,6 +840,36 @@ impl Clone for WindowEvent<'static> {
                 value: *value,
             },
             Touch(touch) => Touch(*touch),
+            Pen(pen) => Pen(*pen),
+            PinchGesture {
+                device_id,
+                delta,
+                phase,
+            } => PinchGesture {
+                device_id: *device_id,
+                delta: *delta,
+                phase: *phase,
+            },
+            RotationGesture {
+                device_id,
+                delta,
+                phase,
+            } => RotationGesture {
+                device_id: *device_id,
+                delta: *delta,
+                phase: *phase,
+            },
+            SwipeGesture {
+                device_id,
+                delta,
+                fingers,
+                phase,
+            } => SwipeGesture {
+                device_id: *device_id,
+                delta: *delta,
+                fingers: *fingers,
+                phase: *phase,
+            },
             ThemeChanged(theme) => ThemeChanged(theme.clone()),
             ScaleFactorChanged { .. } => {
                 unreachable!("Static event can't be about scale factor changing")
@@ -466,26 +889,32 @@ impl<'a> WindowEvent<'a> {
             DroppedFile(file) => Some(DroppedFile(file)),
             HoveredFile(file) => Some(HoveredFile(file)),
             HoveredFileCancelled => Some(HoveredFileCancelled),
-            ReceivedImeText(s) => Some(ReceivedImeText(s)),
+            DragAndDrop(event) => Some(DragAndDrop(event)),
+            DragFinished { action } => Some(DragFinished { action }),
+            Ime(ime) => Some(Ime(ime)),
             Focused(focused) => Some(Focused(focused)),
             KeyboardInput {
                 device_id,
                 event,
                 is_synthetic,
+                time,
             } => Some(KeyboardInput {
                 device_id,
                 event,
                 is_synthetic,
+                time,
             }),
             ModifiersChanged(modifiers) => Some(ModifiersChanged(modifiers)),
             #[allow(deprecated)]
             CursorMoved {
                 device_id,
                 position,
+                time,
                 modifiers,
             } => Some(CursorMoved {
                 device_id,
                 position,
+                time,
                 modifiers,
             }),
             CursorEntered { device_id } => Some(CursorEntered { device_id }),
@@ -495,11 +924,15 @@ impl<'a> WindowEvent<'a> {
                 device_id,
                 delta,
                 phase,
+                source,
+                time,
                 modifiers,
             } => Some(MouseWheel {
                 device_id,
                 delta,
                 phase,
+                source,
+                time,
                 modifiers,
             }),
             #[allow(deprecated)]
@@ -507,11 +940,13 @@ impl<'a> WindowEvent<'a> {
                 device_id,
                 state,
                 button,
+                time,
                 modifiers,
             } => Some(MouseInput {
                 device_id,
                 state,
                 button,
+                time,
                 modifiers,
             }),
             TouchpadPressure {
@@ -533,6 +968,36 @@ impl<'a> WindowEvent<'a> {
                 value,
             }),
             Touch(touch) => Some(Touch(touch)),
+            Pen(pen) => Some(Pen(pen)),
+            PinchGesture {
+                device_id,
+                delta,
+                phase,
+            } => Some(PinchGesture {
+                device_id,
+                delta,
+                phase,
+            }),
+            RotationGesture {
+                device_id,
+                delta,
+                phase,
+            } => Some(RotationGesture {
+                device_id,
+                delta,
+                phase,
+            }),
+            SwipeGesture {
+                device_id,
+                delta,
+                fingers,
+                phase,
+            } => Some(SwipeGesture {
+                device_id,
+                delta,
+                fingers,
+                phase,
+            }),
             ThemeChanged(theme) => Some(ThemeChanged(theme)),
             ScaleFactorChanged { .. } => None,
         }
@@ -558,6 +1023,44 @@ impl DeviceId {
     }
 }
 
+/// The time at which an input event was generated.
+///
+/// Timestamps are taken from the clock of the display server and have a resolution of one
+/// millisecond. The clock starts at an unspecified point in time and wraps around after roughly
+/// 49.7 days. Timestamps are therefore only meaningful relative to other timestamps of the same
+/// event loop.
+///
+/// Unlike the time at which winit dispatches an event, the timestamp is not affected by events
+/// that are received and processed in batches.
+///
+/// ## Platform-specific
+///
+/// - Only reported on **X11** and **Wayland**. The headless backend uses a clock that starts when
+///   the event loop is created.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Timestamp(u32);
+
+impl Timestamp {
+    /// Creates a timestamp from a number of milliseconds.
+    pub fn from_millis(millis: u32) -> Self {
+        Timestamp(millis)
+    }
+
+    /// Returns the timestamp in milliseconds.
+    pub fn as_millis(self) -> u32 {
+        self.0
+    }
+
+    /// Returns the time that passed between `earlier` and `self`.
+    ///
+    /// Takes into account that the clock wraps around. If `earlier` is actually later than
+    /// `self`, the result is meaningless.
+    pub fn duration_since(self, earlier: Timestamp) -> Duration {
+        Duration::from_millis(self.0.wrapping_sub(earlier.0) as u64)
+    }
+}
+
 /// Represents raw hardware events that are not associated with any particular window.
 ///
 /// Useful for interactions that diverge significantly from a conventional 2D GUI, such as 3D camera or first-person
@@ -567,6 +1070,7 @@ impl DeviceId {
 ///
 /// Note that these events are delivered regardless of input focus.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum DeviceEvent {
     Added,
     Removed,
@@ -717,10 +1221,102 @@ pub struct Touch {
     pub force: Option<Force>,
     /// Unique identifier of a finger.
     pub id: u64,
+    /// The time at which the touch event was generated. `None` if the platform does not report
+    /// it.
+    pub time: Option<Timestamp>,
+}
+
+/// Represents an event of a drawing tablet tool.
+///
+/// A tool that comes close enough to the tablet to be tracked while it is over a window
+/// generates a `ProximityIn` event. It is followed by `Moved`, `Down`, `Up`, and `Button`
+/// events, and finally by a `ProximityOut` event when the tool leaves the window or the
+/// tablet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pen {
+    pub device_id: DeviceId,
+    pub phase: PenPhase,
+    pub tool: PenTool,
+    /// The position of the tool relative to the top-left corner of the window.
+    pub location: PhysicalPosition<f64>,
+    /// The current values of the axes of the tool.
+    pub state: PenState,
+}
+
+/// Describes what happened to a drawing tablet tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PenPhase {
+    /// The tool came into proximity of the tablet over the window.
+    ProximityIn,
+    /// The tool moved or the value of one of its axes changed.
+    Moved,
+    /// The tool touched the surface of the tablet.
+    Down,
+    /// The tool stopped touching the surface of the tablet.
+    Up,
+    /// A button of the tool was pressed or released.
+    Button {
+        button: PenButton,
+        state: ElementState,
+    },
+    /// The tool left the proximity of the tablet or the window.
+    ProximityOut,
+}
+
+/// The physical type of a drawing tablet tool.
+///
+/// ## Platform-specific
+///
+/// - **X11:** Only `Pen` and `Eraser` are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PenTool {
+    Pen,
+    Eraser,
+    Brush,
+    Pencil,
+    Airbrush,
+    Mouse,
+    Lens,
+}
+
+/// A button of a drawing tablet tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PenButton {
+    /// The first button on the barrel of a pen.
+    Primary,
+    /// The second button on the barrel of a pen.
+    Secondary,
+    /// Any other button, identified by its platform-specific code.
+    Other(u32),
+}
+
+/// The values of the axes of a drawing tablet tool.
+///
+/// Axes that the tool does not have are `None`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PenState {
+    /// The pressure of the tool on the surface, from `0.0` to `1.0`.
+    pub pressure: Option<f64>,
+    /// The tilt of the tool towards the positive x axis in degrees, from `-90.0` to `90.0`.
+    pub tilt_x: Option<f64>,
+    /// The tilt of the tool towards the positive y axis in degrees, from `-90.0` to `90.0`.
+    pub tilt_y: Option<f64>,
+    /// The clockwise rotation of the tool around its own axis in degrees, from `0.0` to
+    /// `360.0`.
+    pub rotation: Option<f64>,
+    /// The distance of the tool from the surface, from `0.0` to `1.0`.
+    ///
+    /// The range is device-specific and does not correspond to a physical distance.
+    pub distance: Option<f64>,
 }
 
 /// Describes the force of a touch event
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Force {
     /// On iOS, the force is calibrated so that the same number corresponds to
     /// roughly the same amount of pressure on the screen regardless of the
@@ -799,6 +1395,20 @@ pub enum MouseButton {
     Other(u16),
 }
 
+/// Describes the physical source of a scroll event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ScrollSource {
+    /// A mouse wheel that scrolls in discrete steps.
+    Wheel,
+    /// Fingers on a touchpad.
+    Finger,
+    /// A continuous source without discrete steps or a physical stop, such as a trackpoint.
+    Continuous,
+    /// The sideways tilt of a mouse wheel.
+    WheelTilt,
+}
+
 /// Describes a difference in the mouse scroll wheel state.
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
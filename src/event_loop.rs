@@ -11,9 +11,14 @@
 //! [send_event]: crate::event_loop::EventLoopProxy::send_event
 use instant::Instant;
 use std::ops::Deref;
+#[cfg(feature = "stream")]
+use std::pin::Pin;
+#[cfg(feature = "stream")]
+use std::task::{Context, Poll};
+use std::time::Duration;
 use std::{error, fmt};
 
-use crate::{event::Event, monitor::MonitorHandle, platform_impl};
+use crate::{error::NotSupportedError, event::Event, monitor::MonitorHandle, platform_impl};
 
 /// Provides a way to retrieve events from the system and from the windows that were registered to
 /// the events loop.
@@ -190,8 +195,56 @@ impl<T> EventLoopWindowTarget<T> {
     pub fn primary_monitor(&self) -> Option<MonitorHandle> {
         self.p.primary_monitor()
     }
+
+    /// Adds a timer that emits [`Event::Timer`] with the returned id when it fires.
+    ///
+    /// An [`Instant`] creates a timer that fires once at that time and a [`Duration`] creates a
+    /// timer that fires repeatedly at that interval. The event loop wakes up for the earliest
+    /// timer regardless of the [`ControlFlow`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11 / Wayland:** Supported.
+    /// - **Other platforms:** Returns a [`NotSupportedError`].
+    #[inline]
+    pub fn add_timer<D: Into<Timer>>(&self, timer: D) -> Result<TimerId, NotSupportedError> {
+        self.p.add_timer(timer.into())
+    }
+
+    /// Cancels a timer that was added with [`add_timer`](Self::add_timer).
+    ///
+    /// Timers that have already been cancelled or that have fired once are ignored.
+    #[inline]
+    pub fn cancel_timer(&self, id: TimerId) {
+        self.p.cancel_timer(id)
+    }
+}
+
+/// Describes when a timer added with [`EventLoopWindowTarget::add_timer`] fires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Timer {
+    /// The timer fires once at the given time.
+    Once(Instant),
+    /// The timer fires repeatedly, the first time after the interval has elapsed.
+    Interval(Duration),
+}
+
+impl From<Instant> for Timer {
+    fn from(deadline: Instant) -> Self {
+        Timer::Once(deadline)
+    }
+}
+
+impl From<Duration> for Timer {
+    fn from(interval: Duration) -> Self {
+        Timer::Interval(interval)
+    }
 }
 
+/// Identifies a timer that was added with [`EventLoopWindowTarget::add_timer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimerId(pub(crate) u64);
+
 /// Used to send custom events to `EventLoop`.
 pub struct EventLoopProxy<T: 'static> {
     event_loop_proxy: platform_impl::EventLoopProxy<T>,
@@ -216,6 +269,28 @@ impl<T: 'static> EventLoopProxy<T> {
     }
 }
 
+/// Sending never blocks, so the sink is always ready and needs no flushing.
+#[cfg(feature = "stream")]
+impl<T: 'static> futures_sink::Sink<T> for EventLoopProxy<T> {
+    type Error = EventLoopClosed<T>;
+
+    fn poll_ready(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, event: T) -> Result<(), Self::Error> {
+        self.send_event(event)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
 impl<T: 'static> fmt::Debug for EventLoopProxy<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.pad("EventLoopProxy { .. }")
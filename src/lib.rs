@@ -158,6 +158,7 @@ mod icon;
 pub mod keyboard;
 pub mod monitor;
 mod platform_impl;
+pub mod record;
 pub mod window;
 
 pub mod platform;
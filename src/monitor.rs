@@ -46,8 +46,8 @@ impl Ord for VideoMode {
         self.monitor().cmp(&other.monitor()).then(
             size.cmp(&other_size)
                 .then(
-                    self.refresh_rate()
-                        .cmp(&other.refresh_rate())
+                    self.refresh_rate_millihertz()
+                        .cmp(&other.refresh_rate_millihertz())
                         .then(self.bit_depth().cmp(&other.bit_depth())),
                 )
                 .reverse(),
@@ -83,6 +83,19 @@ impl VideoMode {
         self.video_mode.refresh_rate()
     }
 
+    /// Returns the refresh rate of this video mode in millihertz.
+    ///
+    /// This distinguishes modes such as 59.94 Hz and 60 Hz that have the same
+    /// [`refresh_rate`](VideoMode::refresh_rate).
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web:** The integer refresh rate multiplied by 1000.
+    #[inline]
+    pub fn refresh_rate_millihertz(&self) -> u32 {
+        self.video_mode.refresh_rate_millihertz()
+    }
+
     /// Returns the monitor that this video mode is valid for. Each monitor has
     /// a separate set of valid video modes.
     #[inline]
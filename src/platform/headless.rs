@@ -0,0 +1,602 @@
+#![cfg(all(
+    feature = "headless",
+    any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )
+))]
+
+//! A backend without a display server for testing applications.
+//!
+//! This module is only available with the `headless` feature. Windows of a headless event loop
+//! live in memory. Tests inject input through the handles in this module and inspect what the
+//! application requested through [`HeadlessWindow`]:
+//!
+//! ```no_run
+//! use winit::{
+//!     event_loop::EventLoop,
+//!     keyboard::KeyCode,
+//!     platform::headless::{EventLoopExtHeadless, EventLoopWindowTargetExtHeadless},
+//!     window::Window,
+//! };
+//!
+//! let event_loop = EventLoop::<()>::new_headless();
+//! let window = Window::new(&event_loop).unwrap();
+//! let seat = event_loop.headless_seat().unwrap();
+//! let keyboard = seat.add_keyboard();
+//! seat.focus(&window);
+//! drop(keyboard.press(KeyCode::KeyA));
+//! ```
+//!
+//! Injected events are queued immediately and dispatched by the next iteration of the event
+//! loop. The headless backend has a single monitor, a scale factor of 1, and a US keyboard
+//! layout. Nothing acts on the requests of the application: the window manager of the
+//! headless backend does not resize fullscreen windows or confine grabbed cursors.
+
+use std::{
+    cell::Cell,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{DeviceId, DndAction, ElementState, MouseButton, TouchPhase, WindowEvent},
+    event_loop::{EventLoop, EventLoopWindowTarget},
+    icon::Icon,
+    keyboard::KeyCode,
+    platform_impl::{
+        headless, DeviceId as LinuxDeviceId, EventLoop as LinuxEventLoop,
+        EventLoopWindowTarget as LinuxEventLoopWindowTarget, Window as LinuxWindow,
+    },
+    window::{CursorGrabMode, CursorIcon, CustomCursor, Fullscreen, UserAttentionType, Window},
+};
+
+/// Additional methods on `EventLoop` to create a headless event loop.
+pub trait EventLoopExtHeadless {
+    /// Builds a new `EventLoop` that uses the headless backend.
+    ///
+    /// Unlike the other backends, the headless backend can be created on any thread and any
+    /// number of headless event loops can exist at the same time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pipe that wakes up the event loop cannot be created.
+    fn new_headless() -> Self
+    where
+        Self: Sized;
+}
+
+impl<T> EventLoopExtHeadless for EventLoop<T> {
+    #[inline]
+    fn new_headless() -> Self {
+        EventLoop {
+            event_loop: LinuxEventLoop::new_headless()
+                // TODO: propagate
+                .expect("failed to create the headless event loop"),
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+/// Additional methods on `EventLoopWindowTarget` that are specific to the headless backend.
+pub trait EventLoopWindowTargetExtHeadless {
+    /// True if the `EventLoopWindowTarget` uses the headless backend.
+    fn is_headless(&self) -> bool;
+
+    /// Returns the seat that exists from the start.
+    ///
+    /// The position of the cursor that is set with
+    /// [`Window::set_cursor_position`](crate::window::Window::set_cursor_position) belongs to
+    /// this seat.
+    ///
+    /// Returns `None` if the event loop doesn't use the headless backend.
+    fn headless_seat(&self) -> Option<HeadlessSeat>;
+
+    /// Creates an additional seat with its own cursor, focus, and modifiers.
+    ///
+    /// Returns `None` if the event loop doesn't use the headless backend.
+    fn create_headless_seat(&self) -> Option<HeadlessSeat>;
+}
+
+impl<T> EventLoopWindowTargetExtHeadless for EventLoopWindowTarget<T> {
+    #[inline]
+    fn is_headless(&self) -> bool {
+        matches!(self.p, LinuxEventLoopWindowTarget::Headless(_))
+    }
+
+    fn headless_seat(&self) -> Option<HeadlessSeat> {
+        let shared = shared(self)?;
+        let seat = shared.with_state(|s| s.default_seat());
+        Some(HeadlessSeat { shared, seat })
+    }
+
+    fn create_headless_seat(&self) -> Option<HeadlessSeat> {
+        let shared = shared(self)?;
+        let seat = shared.with_state(|s| s.add_seat());
+        Some(HeadlessSeat { shared, seat })
+    }
+}
+
+fn shared<T>(target: &EventLoopWindowTarget<T>) -> Option<Arc<headless::Shared>> {
+    #[allow(irrefutable_let_patterns)]
+    if let LinuxEventLoopWindowTarget::Headless(p) = &target.p {
+        return Some(p.shared.clone());
+    }
+    None
+}
+
+fn window_id(window: &Window) -> Option<headless::WindowId> {
+    #[allow(irrefutable_let_patterns)]
+    if let LinuxWindow::Headless(w) = &window.window {
+        return Some(w.id());
+    }
+    None
+}
+
+/// Additional methods on `Window` that are specific to the headless backend.
+pub trait WindowExtHeadless {
+    /// Returns a handle to inspect the state of the window and to inject window events.
+    ///
+    /// Returns `None` if the window doesn't use the headless backend.
+    fn headless_window(&self) -> Option<HeadlessWindow>;
+}
+
+impl WindowExtHeadless for Window {
+    #[inline]
+    fn headless_window(&self) -> Option<HeadlessWindow> {
+        #[allow(irrefutable_let_patterns)]
+        if let LinuxWindow::Headless(w) = &self.window {
+            return Some(HeadlessWindow {
+                shared: w.shared.clone(),
+                state: w.state.clone(),
+            });
+        }
+        None
+    }
+}
+
+/// A seat of the headless backend.
+///
+/// A seat groups input devices. All keyboards of a seat share the keyboard focus and the
+/// modifiers, and all mice share the cursor.
+#[derive(Clone)]
+pub struct HeadlessSeat {
+    shared: Arc<headless::Shared>,
+    seat: u64,
+}
+
+impl HeadlessSeat {
+    /// Adds a keyboard to the seat. The keyboard is removed when it is dropped.
+    pub fn add_keyboard(&self) -> HeadlessKeyboard {
+        HeadlessKeyboard {
+            device: HeadlessDevice::new(self),
+        }
+    }
+
+    /// Adds a mouse to the seat. The mouse is removed when it is dropped.
+    pub fn add_mouse(&self) -> HeadlessMouse {
+        HeadlessMouse {
+            device: HeadlessDevice::new(self),
+        }
+    }
+
+    /// Adds a touchscreen to the seat. The touchscreen is removed when it is dropped.
+    pub fn add_touchscreen(&self) -> HeadlessTouchscreen {
+        HeadlessTouchscreen {
+            device: HeadlessDevice::new(self),
+        }
+    }
+
+    /// Moves the keyboard focus of the seat to `window`.
+    ///
+    /// Windows that don't use the headless backend are ignored.
+    pub fn focus(&self, window: &Window) {
+        if let Some(window) = window_id(window) {
+            self.shared.with_state(|s| s.focus(self.seat, Some(window)));
+        }
+    }
+
+    /// Removes the keyboard focus of the seat from all windows.
+    pub fn un_focus(&self) {
+        self.shared.with_state(|s| s.focus(self.seat, None));
+    }
+
+    /// Moves the cursor of the seat to a position on the monitor.
+    pub fn set_cursor_position(&self, x: i32, y: i32) {
+        self.shared
+            .with_state(|s| s.warp_pointer(self.seat, PhysicalPosition::new(x as f64, y as f64)));
+    }
+
+    /// Returns the position of the cursor of the seat on the monitor.
+    pub fn cursor_position(&self) -> (i32, i32) {
+        let position = self.shared.with_state(|s| s.pointer_position(self.seat));
+        (position.x as i32, position.y as i32)
+    }
+
+    /// True if `device_id` is the id that window events of this seat carry.
+    pub fn is(&self, device_id: DeviceId) -> bool {
+        is_device(device_id, self.seat)
+    }
+
+    /// Starts a drag from another application that offers `offers` as pairs of a MIME type and
+    /// the data.
+    ///
+    /// `action` is the action that the source proposes. A drag that is already in progress is
+    /// cancelled.
+    pub fn start_drag(&self, offers: &[(&str, &[u8])], action: DndAction) -> HeadlessDrag {
+        let offers = offers
+            .iter()
+            .map(|(mime, data)| (mime.to_string(), data.to_vec()))
+            .collect();
+        let id = self.shared.with_state(|s| s.start_drag(offers, action));
+        HeadlessDrag {
+            shared: self.shared.clone(),
+            id,
+        }
+    }
+}
+
+fn is_device(device_id: DeviceId, id: u64) -> bool {
+    #[allow(irrefutable_let_patterns)]
+    if let LinuxDeviceId::Headless(device_id) = device_id.0 {
+        return device_id.0 == id;
+    }
+    false
+}
+
+/// An input device of a seat that is removed when it is dropped.
+struct HeadlessDevice {
+    shared: Arc<headless::Shared>,
+    seat: u64,
+    id: u64,
+}
+
+impl HeadlessDevice {
+    fn new(seat: &HeadlessSeat) -> Self {
+        let id = seat.shared.with_state(|s| s.add_device());
+        HeadlessDevice {
+            shared: seat.shared.clone(),
+            seat: seat.seat,
+            id,
+        }
+    }
+}
+
+impl Drop for HeadlessDevice {
+    fn drop(&mut self) {
+        self.shared.with_state(|s| s.remove_device(self.id));
+    }
+}
+
+/// A keyboard of a [`HeadlessSeat`].
+pub struct HeadlessKeyboard {
+    device: HeadlessDevice,
+}
+
+impl HeadlessKeyboard {
+    /// True if `device_id` is the id that device events of this keyboard carry.
+    pub fn is(&self, device_id: DeviceId) -> bool {
+        is_device(device_id, self.device.id)
+    }
+
+    /// Presses a key until the returned object is dropped.
+    ///
+    /// Pressing a key that is already pressed emits a repeated key event.
+    pub fn press(&self, key: KeyCode) -> HeadlessPressedKey {
+        let d = &self.device;
+        d.shared
+            .with_state(|s| s.key(d.seat, d.id, key, ElementState::Pressed));
+        HeadlessPressedKey {
+            shared: d.shared.clone(),
+            seat: d.seat,
+            device: d.id,
+            key,
+        }
+    }
+}
+
+/// A pressed key that is released when it is dropped.
+pub struct HeadlessPressedKey {
+    shared: Arc<headless::Shared>,
+    seat: u64,
+    device: u64,
+    key: KeyCode,
+}
+
+impl Drop for HeadlessPressedKey {
+    fn drop(&mut self) {
+        self.shared
+            .with_state(|s| s.key(self.seat, self.device, self.key, ElementState::Released));
+    }
+}
+
+/// A mouse of a [`HeadlessSeat`].
+pub struct HeadlessMouse {
+    device: HeadlessDevice,
+}
+
+impl HeadlessMouse {
+    /// True if `device_id` is the id that device events of this mouse carry.
+    pub fn is(&self, device_id: DeviceId) -> bool {
+        is_device(device_id, self.device.id)
+    }
+
+    /// Presses a button until the returned object is dropped.
+    pub fn press(&self, button: MouseButton) -> HeadlessPressedButton {
+        let d = &self.device;
+        d.shared
+            .with_state(|s| s.button(d.seat, d.id, button, ElementState::Pressed));
+        HeadlessPressedButton {
+            shared: d.shared.clone(),
+            seat: d.seat,
+            device: d.id,
+            button,
+        }
+    }
+
+    /// Moves the cursor of the seat by `dx`x`dy` pixels.
+    pub fn move_(&self, dx: i32, dy: i32) {
+        let d = &self.device;
+        d.shared
+            .with_state(|s| s.move_pointer(d.seat, d.id, dx as f64, dy as f64));
+    }
+
+    /// Scrolls the wheel by `dx`x`dy` lines.
+    pub fn scroll(&self, dx: i32, dy: i32) {
+        let d = &self.device;
+        d.shared
+            .with_state(|s| s.scroll(d.seat, d.id, dx as f32, dy as f32));
+    }
+}
+
+/// A pressed mouse button that is released when it is dropped.
+pub struct HeadlessPressedButton {
+    shared: Arc<headless::Shared>,
+    seat: u64,
+    device: u64,
+    button: MouseButton,
+}
+
+impl Drop for HeadlessPressedButton {
+    fn drop(&mut self) {
+        self.shared
+            .with_state(|s| s.button(self.seat, self.device, self.button, ElementState::Released));
+    }
+}
+
+/// A touchscreen of a [`HeadlessSeat`] that covers the monitor.
+pub struct HeadlessTouchscreen {
+    device: HeadlessDevice,
+}
+
+impl HeadlessTouchscreen {
+    /// True if `device_id` is the id that device events of this touchscreen carry.
+    pub fn is(&self, device_id: DeviceId) -> bool {
+        is_device(device_id, self.device.id)
+    }
+
+    /// Touches the screen at `x`x`y` until the returned object is dropped.
+    pub fn down(&self, x: i32, y: i32) -> HeadlessFinger {
+        let d = &self.device;
+        let finger = HeadlessFinger {
+            shared: d.shared.clone(),
+            seat: d.seat,
+            id: d.shared.with_state(|s| s.new_id()),
+            position: Cell::new(PhysicalPosition::new(x as f64, y as f64)),
+        };
+        finger.touch(TouchPhase::Started);
+        finger
+    }
+}
+
+/// A finger on a [`HeadlessTouchscreen`] that is lifted when it is dropped.
+pub struct HeadlessFinger {
+    shared: Arc<headless::Shared>,
+    seat: u64,
+    id: u64,
+    position: Cell<PhysicalPosition<f64>>,
+}
+
+impl HeadlessFinger {
+    /// Moves the finger to `x`x`y`.
+    pub fn move_(&self, x: i32, y: i32) {
+        self.position.set(PhysicalPosition::new(x as f64, y as f64));
+        self.touch(TouchPhase::Moved);
+    }
+
+    fn touch(&self, phase: TouchPhase) {
+        self.shared
+            .with_state(|s| s.touch(self.seat, self.id, phase, self.position.get()));
+    }
+}
+
+impl Drop for HeadlessFinger {
+    fn drop(&mut self) {
+        self.touch(TouchPhase::Ended);
+    }
+}
+
+/// A drag from another application. The drag is cancelled if it is dropped without calling
+/// [`do_drop`](Self::do_drop).
+pub struct HeadlessDrag {
+    shared: Arc<headless::Shared>,
+    id: u64,
+}
+
+impl HeadlessDrag {
+    /// Moves the drag to a position on the monitor.
+    pub fn drag_to(&self, x: i32, y: i32) {
+        self.shared
+            .with_state(|s| s.drag_to(self.id, PhysicalPosition::new(x as f64, y as f64)));
+    }
+
+    /// Drops the data at the current position.
+    ///
+    /// Returns the action that the window under the drag accepted with
+    /// [`set_dnd_action`](crate::platform::unix::EventLoopWindowTargetExtUnix::set_dnd_action)
+    /// or `None` if the drop was rejected.
+    pub fn do_drop(self) -> Option<DndAction> {
+        self.shared.with_state(|s| s.drop_drag(self.id))
+    }
+}
+
+impl Drop for HeadlessDrag {
+    fn drop(&mut self) {
+        self.shared.with_state(|s| s.cancel_drag(self.id));
+    }
+}
+
+/// A handle to a window of the headless backend.
+///
+/// The getters return what the application requested most recently. The handle stays usable
+/// after the window has been destroyed.
+#[derive(Clone)]
+pub struct HeadlessWindow {
+    shared: Arc<headless::Shared>,
+    state: Arc<Mutex<headless::WindowState>>,
+}
+
+impl HeadlessWindow {
+    fn get<R, F: FnOnce(&headless::WindowState) -> R>(&self, f: F) -> R {
+        f(&self.state.lock().unwrap())
+    }
+
+    pub fn title(&self) -> String {
+        self.get(|s| s.title.clone())
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.get(|s| s.visible)
+    }
+
+    /// Returns the position of the window on the monitor.
+    pub fn outer_position(&self) -> PhysicalPosition<i32> {
+        self.get(|s| s.position)
+    }
+
+    pub fn inner_size(&self) -> PhysicalSize<u32> {
+        self.get(|s| s.size)
+    }
+
+    pub fn min_inner_size(&self) -> Option<PhysicalSize<u32>> {
+        self.get(|s| s.min_size)
+    }
+
+    pub fn max_inner_size(&self) -> Option<PhysicalSize<u32>> {
+        self.get(|s| s.max_size)
+    }
+
+    pub fn is_resizable(&self) -> bool {
+        self.get(|s| s.resizable)
+    }
+
+    pub fn cursor_icon(&self) -> CursorIcon {
+        self.get(|s| s.cursor_icon)
+    }
+
+    /// Returns the custom cursor unless a cursor icon has been set after it.
+    pub fn custom_cursor(&self) -> Option<CustomCursor> {
+        self.get(|s| s.custom_cursor.clone())
+    }
+
+    pub fn cursor_grab(&self) -> CursorGrabMode {
+        self.get(|s| s.cursor_grab)
+    }
+
+    pub fn is_cursor_visible(&self) -> bool {
+        self.get(|s| s.cursor_visible)
+    }
+
+    pub fn is_maximized(&self) -> bool {
+        self.get(|s| s.maximized)
+    }
+
+    pub fn is_minimized(&self) -> bool {
+        self.get(|s| s.minimized)
+    }
+
+    pub fn fullscreen(&self) -> Option<Fullscreen> {
+        self.get(|s| s.fullscreen.clone())
+    }
+
+    pub fn has_decorations(&self) -> bool {
+        self.get(|s| s.decorations)
+    }
+
+    pub fn is_always_on_top(&self) -> bool {
+        self.get(|s| s.always_on_top)
+    }
+
+    pub fn window_icon(&self) -> Option<Icon> {
+        self.get(|s| s.window_icon.clone())
+    }
+
+    pub fn ime_position(&self) -> PhysicalPosition<i32> {
+        self.get(|s| s.ime_position)
+    }
+
+    pub fn is_ime_allowed(&self) -> bool {
+        self.get(|s| s.ime_allowed)
+    }
+
+    /// Returns the most recent request for attention. Requests are not cleared when the
+    /// window is focused.
+    pub fn user_attention(&self) -> Option<UserAttentionType> {
+        self.get(|s| s.attention)
+    }
+
+    /// Returns the RGB color of a pixel of the frame that was presented most recently with
+    /// [`Window::present_rgba`](crate::window::Window::present_rgba).
+    ///
+    /// Returns `None` if no frame has been presented or if the pixel is outside of the frame.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<(u8, u8, u8)> {
+        self.get(|s| {
+            let frame = s.frame.as_ref()?;
+            if x >= frame.width || y >= frame.height {
+                return None;
+            }
+            let offset = (y as usize * frame.width as usize + x as usize) * 4;
+            let rgba = &frame.rgba[offset..offset + 4];
+            Some((rgba[0], rgba[1], rgba[2]))
+        })
+    }
+
+    /// Resizes the window as if the user had resized it, ignoring the size constraints of the
+    /// application.
+    pub fn resize(&self, width: u32, height: u32) {
+        self.update(|state| {
+            let size = PhysicalSize::new(width, height);
+            state.size = size;
+            WindowEvent::Resized(size)
+        });
+    }
+
+    /// Moves the window to a position on the monitor as if the user had moved it.
+    pub fn move_to(&self, x: i32, y: i32) {
+        self.update(|state| {
+            let position = PhysicalPosition::new(x, y);
+            state.position = position;
+            WindowEvent::Moved(position)
+        });
+    }
+
+    /// Asks the application to close the window as if the user had clicked the close button.
+    pub fn request_close(&self) {
+        self.update(|_| WindowEvent::CloseRequested);
+    }
+
+    fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut headless::WindowState) -> WindowEvent<'static>,
+    {
+        // The shared state is always locked before the state of a window.
+        self.shared.with_state(|s| {
+            let mut state = self.state.lock().unwrap();
+            let event = f(&mut state);
+            s.push_window_event(state.id, event);
+        });
+    }
+}
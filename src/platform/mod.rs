@@ -12,6 +12,9 @@
 //! And the following platform-specific module:
 //!
 //! - `run_return` (available on `windows`, `unix`, `macos`, and `android`)
+//! - `pump_events` (available on `unix`)
+//! - `stream` (available on `unix` with the `stream` feature)
+//! - `headless` (available on `unix` with the `headless` feature)
 //!
 //! However only the module corresponding to the platform you're compiling to will be available.
 
@@ -21,7 +24,10 @@ pub mod macos;
 pub mod unix;
 pub mod windows;
 
+pub mod headless;
 pub mod modifier_supplement;
+pub mod pump_events;
 pub mod run_return;
 pub mod scancode;
+pub mod stream;
 pub mod web;
@@ -0,0 +1,72 @@
+#![cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+
+use std::time::Duration;
+
+use crate::{
+    event::Event,
+    event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
+};
+
+/// The result of [`EventLoopExtPumpEvents::pump_events`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PumpStatus {
+    /// The event loop keeps running and `pump_events` should be called again.
+    Continue,
+    /// `control_flow` was set to `ControlFlow::Exit` and `LoopDestroyed` has been dispatched.
+    /// The event loop has exited for good.
+    Exit,
+}
+
+/// Additional methods on `EventLoop` to integrate it into a loop that is owned by the
+/// application.
+pub trait EventLoopExtPumpEvents {
+    /// A type provided by the user that can be passed through `Event::UserEvent`.
+    type UserEvent;
+
+    /// Waits for at most `timeout` for new events, dispatches them and returns.
+    ///
+    /// Every call dispatches one iteration of the event loop: `NewEvents`, the events that have
+    /// arrived, `MainEventsCleared`, `RedrawRequested` and `RedrawEventsCleared`. If events have
+    /// already been read from the connection while the iteration was running, further
+    /// iterations follow before the call returns.
+    ///
+    /// The first call dispatches `NewEvents(StartCause::Init)` without waiting. Later calls
+    /// wait as requested by `control_flow` and the timers of the event loop but never longer
+    /// than `timeout`. A `timeout` of `None` waits as long as `control_flow` requires and a zero
+    /// `timeout` never blocks. The `StartCause` is the same that `run_return` would report, so
+    /// `ControlFlow::Wait` yields `WaitCancelled` even if the call returns because `timeout`
+    /// has elapsed.
+    ///
+    /// Unlike with `run_return`, `control_flow` is preserved between calls. Once it is set to
+    /// `ControlFlow::Exit`, `LoopDestroyed` is dispatched and `PumpStatus::Exit` is returned.
+    /// The event loop does not start over: later calls return `PumpStatus::Exit` right away
+    /// without dispatching any events.
+    fn pump_events<F>(&mut self, timeout: Option<Duration>, event_handler: F) -> PumpStatus
+    where
+        F: FnMut(
+            Event<'_, Self::UserEvent>,
+            &EventLoopWindowTarget<Self::UserEvent>,
+            &mut ControlFlow,
+        );
+}
+
+impl<T> EventLoopExtPumpEvents for EventLoop<T> {
+    type UserEvent = T;
+
+    fn pump_events<F>(&mut self, timeout: Option<Duration>, event_handler: F) -> PumpStatus
+    where
+        F: FnMut(
+            Event<'_, Self::UserEvent>,
+            &EventLoopWindowTarget<Self::UserEvent>,
+            &mut ControlFlow,
+        ),
+    {
+        self.event_loop.pump_events(timeout, event_handler)
+    }
+}
@@ -0,0 +1,298 @@
+#![cfg(all(
+    feature = "stream",
+    any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )
+))]
+
+//! Polling the event loop from an asynchronous executor.
+//!
+//! This module is only available with the `stream` feature. The same feature implements
+//! `futures_sink::Sink` for [`EventLoopProxy`](crate::event_loop::EventLoopProxy) so that user
+//! events can be sent from async code on all platforms.
+
+use std::collections::VecDeque;
+use std::io;
+use std::ops::Deref;
+use std::os::unix::io::RawFd;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use futures_core::Stream;
+
+use crate::{
+    event::Event,
+    event_loop::{ControlFlow, EventLoop},
+    platform::{
+        pump_events::{EventLoopExtPumpEvents, PumpStatus},
+        unix::Interest,
+    },
+};
+
+/// Additional methods on `EventLoop` to poll it as a [`Stream`].
+pub trait EventLoopExtStream {
+    /// A type provided by the user that can be passed through `Event::UserEvent`.
+    type UserEvent: 'static;
+
+    /// Turns the event loop into a stream of events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the background thread that waits for the event loop cannot be created.
+    fn into_stream(self) -> EventStream<Self::UserEvent>;
+}
+
+impl<T> EventLoopExtStream for EventLoop<T> {
+    type UserEvent = T;
+
+    fn into_stream(self) -> EventStream<T> {
+        EventStream::new(self)
+    }
+}
+
+/// An event loop that yields its events as a [`Stream`].
+///
+/// Polling the stream never blocks. While no events are available, a background thread waits
+/// for the event loop and wakes up the task that polled the stream. Each wake-up yields one
+/// iteration of the event loop, from `NewEvents` to `RedrawEventsCleared`.
+///
+/// The stream behaves as if `ControlFlow::Wait` was set. Use
+/// [`add_timer`](crate::event_loop::EventLoopWindowTarget::add_timer) to wake up at a specific
+/// time. The stream derefs to the `EventLoop`, so windows are created by passing it to
+/// `WindowBuilder::build`.
+///
+/// `WindowEvent::ScaleFactorChanged` borrows the new size of the window and is therefore not
+/// yielded; the window uses the size suggested by the platform. The stream ends after
+/// `LoopDestroyed` once [`exit`](EventStream::exit) has been called or if the connection to the
+/// display server fails.
+pub struct EventStream<T: 'static> {
+    event_loop: EventLoop<T>,
+    events: VecDeque<Event<'static, T>>,
+    woken: Arc<AtomicBool>,
+    waiter: Waiter,
+    exit_requested: bool,
+    exited: bool,
+}
+
+// The event loop is never pinned.
+impl<T> Unpin for EventStream<T> {}
+
+impl<T> EventStream<T> {
+    fn new(event_loop: EventLoop<T>) -> Self {
+        let woken = Arc::new(AtomicBool::new(true));
+        let waiter = Waiter::new(woken.clone()).expect("Failed to create the event stream");
+        EventStream {
+            event_loop,
+            events: VecDeque::new(),
+            woken,
+            waiter,
+            exit_requested: false,
+            exited: false,
+        }
+    }
+
+    /// Makes the next poll run a final iteration of the event loop with `ControlFlow::Exit`.
+    ///
+    /// The stream yields the remaining events, including `LoopDestroyed`, and then ends.
+    pub fn exit(&mut self) {
+        self.exit_requested = true;
+    }
+}
+
+impl<T> Deref for EventStream<T> {
+    type Target = EventLoop<T>;
+
+    fn deref(&self) -> &EventLoop<T> {
+        &self.event_loop
+    }
+}
+
+impl<T> Stream for EventStream<T> {
+    type Item = Event<'static, T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(event) = this.events.pop_front() {
+                return Poll::Ready(Some(event));
+            }
+            if this.exited {
+                return Poll::Ready(None);
+            }
+            // Requests of the application might have read events from the socket.
+            let woken = this.woken.swap(false, Ordering::SeqCst);
+            if !woken && !this.exit_requested && !this.event_loop.event_loop.has_pending_events() {
+                break;
+            }
+            let events = &mut this.events;
+            let exit_requested = this.exit_requested;
+            let status = this.event_loop.pump_events(
+                Some(Duration::from_millis(0)),
+                |event, _, control_flow| {
+                    *control_flow = if exit_requested {
+                        ControlFlow::Exit
+                    } else {
+                        ControlFlow::Wait
+                    };
+                    if let Some(event) = event.to_static() {
+                        events.push_back(event);
+                    }
+                },
+            );
+            this.exited = status == PumpStatus::Exit;
+        }
+
+        let event_loop = &this.event_loop.event_loop;
+        this.waiter.wait(
+            event_loop.wakeup_fds(),
+            event_loop.next_deadline(),
+            cx.waker().clone(),
+        );
+        Poll::Pending
+    }
+}
+
+struct Request {
+    fds: Vec<libc::pollfd>,
+    deadline: Option<Instant>,
+    waker: Waker,
+}
+
+/// Waits for the file descriptors of the event loop on a background thread.
+///
+/// Each request replaces the previous one. Dropping the `Waiter` stops the thread and waits for
+/// it to exit.
+struct Waiter {
+    requests: Option<Sender<Request>>,
+    // The write end of a pipe that interrupts the thread.
+    notify: RawFd,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Waiter {
+    fn new(woken: Arc<AtomicBool>) -> io::Result<Self> {
+        let mut fds = [0; 2];
+        if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC | libc::O_NONBLOCK) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        let (sender, receiver) = mpsc::channel();
+        let notify = fds[0];
+        let spawned = thread::Builder::new()
+            .name("winit event stream".to_string())
+            .spawn(move || wait_thread(receiver, notify, woken));
+        let thread = match spawned {
+            Ok(thread) => thread,
+            Err(e) => {
+                unsafe {
+                    libc::close(fds[0]);
+                    libc::close(fds[1]);
+                }
+                return Err(e);
+            }
+        };
+        Ok(Waiter {
+            requests: Some(sender),
+            notify: fds[1],
+            thread: Some(thread),
+        })
+    }
+
+    fn wait(&mut self, fds: Vec<(RawFd, Interest)>, deadline: Option<Instant>, waker: Waker) {
+        let fds = fds
+            .into_iter()
+            .map(|(fd, interest)| {
+                let mut events = 0;
+                if interest.contains(Interest::READABLE) {
+                    events |= libc::POLLIN;
+                }
+                if interest.contains(Interest::WRITABLE) {
+                    events |= libc::POLLOUT;
+                }
+                libc::pollfd {
+                    fd,
+                    events,
+                    revents: 0,
+                }
+            })
+            .collect();
+        let request = Request {
+            fds,
+            deadline,
+            waker,
+        };
+        if let Some(requests) = &self.requests {
+            let _ = requests.send(request);
+            unsafe {
+                libc::write(self.notify, [0u8].as_ptr() as *const _, 1);
+            }
+        }
+    }
+}
+
+impl Drop for Waiter {
+    fn drop(&mut self) {
+        // The thread sees the hang-up of the pipe and then the closed channel.
+        self.requests = None;
+        unsafe {
+            libc::close(self.notify);
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn wait_thread(requests: Receiver<Request>, notify: RawFd, woken: Arc<AtomicBool>) {
+    let mut request: Option<Request> = None;
+    loop {
+        let mut fds = request.as_ref().map_or(Vec::new(), |r| r.fds.clone());
+        fds.push(libc::pollfd {
+            fd: notify,
+            events: libc::POLLIN,
+            revents: 0,
+        });
+        let timeout = match request.as_ref().and_then(|r| r.deadline) {
+            Some(deadline) => {
+                // Round up so that the timer has expired when we wake up.
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                ((timeout.as_micros() + 999) / 1000).min(i32::MAX as u128) as i32
+            }
+            None => -1,
+        };
+        let res = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as _, timeout) };
+        if res == -1 && io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+            continue;
+        }
+        if res > 0 && fds.last().unwrap().revents != 0 {
+            let mut buf = [0u8; 32];
+            while unsafe { libc::read(notify, buf.as_mut_ptr() as *mut _, buf.len()) } > 0 {}
+            loop {
+                match requests.try_recv() {
+                    Ok(r) => request = Some(r),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        unsafe {
+                            libc::close(notify);
+                        }
+                        return;
+                    }
+                }
+            }
+            continue;
+        }
+        // Any other result, including errors, lets the stream check the event loop.
+        if let Some(request) = request.take() {
+            woken.store(true, Ordering::SeqCst);
+            request.waker.wake();
+        }
+    }
+}
@@ -6,13 +6,16 @@
     target_os = "openbsd"
 ))]
 
+use std::io;
 use std::os::raw;
+use std::os::unix::io::RawFd;
 
 use crate::{
-    event::KeyEvent,
+    error::ExternalError,
+    event::{ClipboardKind, DndAction, KeyEvent, SourceToken},
     event_loop::{EventLoop, EventLoopWindowTarget},
     keyboard::{Key, KeyCode},
-    monitor::MonitorHandle,
+    monitor::{MonitorHandle, VideoMode},
     platform::{modifier_supplement::KeyEventExtModifierSupplement, scancode::KeyCodeExtScancode},
     platform_impl::common::keymap,
     window::{Window, WindowBuilder},
@@ -64,6 +67,112 @@ pub trait EventLoopWindowTargetExtUnix {
     /// The pointer will become invalid when the winit `EventLoop` is destroyed.
     #[cfg(feature = "wayland")]
     fn wayland_display(&self) -> Option<*mut raw::c_void>;
+
+    /// Takes ownership of the clipboard and offers the text under the MIME type
+    /// `text/plain;charset=utf-8`.
+    ///
+    /// This is a shorthand for `set_clipboard_data` with a single entry.
+    fn set_clipboard_text(&self, kind: ClipboardKind, text: &str) -> Result<(), ExternalError>;
+
+    /// Takes ownership of the clipboard and offers the data under the given MIME types.
+    ///
+    /// The data stays available to other clients until `clear_clipboard` is called, another
+    /// client takes ownership of the clipboard, or the event loop is destroyed. In the second
+    /// case, an [`Event::Clipboard`](crate::event::Event::Clipboard) event with a
+    /// `ClipboardEvent::OwnershipLost` payload is emitted.
+    ///
+    /// The MIME type `text/plain;charset=utf-8` is additionally offered as `UTF8_STRING` and
+    /// `TEXT`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Taking ownership of the clipboard requires a prior keyboard or pointer
+    ///   button event on one of the seats.
+    fn set_clipboard_data(
+        &self,
+        kind: ClipboardKind,
+        data: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), ExternalError>;
+
+    /// Gives up ownership of the clipboard if the data was previously set by this event loop.
+    fn clear_clipboard(&self, kind: ClipboardKind) -> Result<(), ExternalError>;
+
+    /// Requests the contents of the clipboard converted to the given MIME type.
+    ///
+    /// The contents are delivered asynchronously in an
+    /// [`Event::Clipboard`](crate::event::Event::Clipboard) event with a `ClipboardEvent::Data`
+    /// payload. The requests of a clipboard, including those of `request_clipboard_mime_types`,
+    /// are answered in the order in which they were made.
+    fn request_clipboard_data(&self, kind: ClipboardKind, mime_type: &str);
+
+    /// Requests the list of MIME types that the clipboard contents are offered as.
+    ///
+    /// The list is delivered asynchronously in an
+    /// [`Event::Clipboard`](crate::event::Event::Clipboard) event with a
+    /// `ClipboardEvent::MimeTypes` payload. It is delivered after the results of earlier requests
+    /// of the clipboard.
+    fn request_clipboard_mime_types(&self, kind: ClipboardKind);
+
+    /// Sets whether a drop at the current position of a drag-and-drop operation is accepted and
+    /// which action is performed.
+    ///
+    /// This should be called while handling a
+    /// [`DndEvent::Motion`](crate::event::DndEvent::Motion) event. The response is sent to the
+    /// source of the drag once the event has been handled and stays in effect for later
+    /// positions until it is changed. `None` rejects the drop.
+    ///
+    /// When a drag enters a window, the response is reset to `Some(DndAction::Copy)` if the data
+    /// is offered as `text/uri-list` so that `HoveredFile` and `DroppedFile` events are emitted,
+    /// and to `None` otherwise.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Unsupported.
+    fn set_dnd_action(&self, action: Option<DndAction>);
+
+    /// Requests the data of the current drag-and-drop operation converted to the given MIME type.
+    ///
+    /// The data is delivered asynchronously in a
+    /// [`DndEvent::Data`](crate::event::DndEvent::Data) event. The data can be requested while a
+    /// drag is over one of the windows and while handling
+    /// [`DndEvent::Drop`](crate::event::DndEvent::Drop). In the latter case, the drop is only
+    /// completed once all requested data has been received. The request is ignored if no drag is
+    /// in progress.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Unsupported.
+    fn request_dnd_data(&self, mime_type: &str);
+
+    /// Sets the theme and the size of the cursor icons.
+    ///
+    /// The size is given in logical pixels and scaled with the scale factor of each window. If
+    /// `theme` or `size` is `None`, the value of the `XCURSOR_THEME` or `XCURSOR_SIZE` environment
+    /// variable is used. Without these, the theme is taken from the `Xcursor.theme` X resource and
+    /// the size from the `Xcursor.size` or `Xft.dpi` X resources, and the size is not scaled. The
+    /// cursors of existing windows are updated.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Unsupported.
+    fn set_cursor_theme(&self, theme: Option<&str>, size: Option<u32>);
+
+    /// Registers a file descriptor with the event loop.
+    ///
+    /// [`Event::FdReady`](crate::event::Event::FdReady) is emitted with the returned token
+    /// whenever the file descriptor is ready for one of the operations in `interest`. This allows
+    /// sockets, pipes, timerfds, and eventfds to be handled on the thread of the event loop.
+    ///
+    /// The file descriptor must stay open until it is unregistered with
+    /// [`unregister_fd`](Self::unregister_fd) or the event loop is dropped.
+    fn register_fd(&self, fd: RawFd, interest: Interest) -> io::Result<SourceToken>;
+
+    /// Unregisters a file descriptor that was registered with
+    /// [`register_fd`](Self::register_fd).
+    ///
+    /// No `FdReady` events are emitted for the token afterwards, even if the file descriptor
+    /// became ready before it was unregistered.
+    fn unregister_fd(&self, token: SourceToken);
 }
 
 impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
@@ -76,7 +185,7 @@ impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
     #[inline]
     #[cfg(feature = "x11")]
     fn is_x11(&self) -> bool {
-        !self.p.is_wayland()
+        matches!(self.p, LinuxEventLoopWindowTarget::X(_))
     }
 
     #[inline]
@@ -107,10 +216,79 @@ impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
             LinuxEventLoopWindowTarget::Wayland(ref p) => {
                 Some(p.display().get_display_ptr() as *mut _)
             }
-            #[cfg(feature = "x11")]
+            #[cfg(any(feature = "x11", feature = "headless"))]
             _ => None,
         }
     }
+
+    #[inline]
+    fn set_clipboard_text(&self, kind: ClipboardKind, text: &str) -> Result<(), ExternalError> {
+        self.set_clipboard_data(
+            kind,
+            vec![(
+                "text/plain;charset=utf-8".to_string(),
+                text.as_bytes().to_vec(),
+            )],
+        )
+    }
+
+    #[inline]
+    fn set_clipboard_data(
+        &self,
+        kind: ClipboardKind,
+        data: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), ExternalError> {
+        self.p.set_clipboard_data(kind, data)
+    }
+
+    #[inline]
+    fn clear_clipboard(&self, kind: ClipboardKind) -> Result<(), ExternalError> {
+        self.p.clear_clipboard(kind)
+    }
+
+    #[inline]
+    fn request_clipboard_data(&self, kind: ClipboardKind, mime_type: &str) {
+        self.p.request_clipboard_data(kind, mime_type)
+    }
+
+    #[inline]
+    fn request_clipboard_mime_types(&self, kind: ClipboardKind) {
+        self.p.request_clipboard_mime_types(kind)
+    }
+
+    #[inline]
+    fn set_dnd_action(&self, action: Option<DndAction>) {
+        self.p.set_dnd_action(action)
+    }
+
+    #[inline]
+    fn request_dnd_data(&self, mime_type: &str) {
+        self.p.request_dnd_data(mime_type)
+    }
+
+    #[inline]
+    fn set_cursor_theme(&self, theme: Option<&str>, size: Option<u32>) {
+        self.p.set_cursor_theme(theme, size)
+    }
+
+    #[inline]
+    fn register_fd(&self, fd: RawFd, interest: Interest) -> io::Result<SourceToken> {
+        self.p.register_fd(fd, interest)
+    }
+
+    #[inline]
+    fn unregister_fd(&self, token: SourceToken) {
+        self.p.unregister_fd(token)
+    }
+}
+
+bitflags! {
+    /// The readiness that a file descriptor registered with
+    /// [`register_fd`](EventLoopWindowTargetExtUnix::register_fd) is watched for.
+    pub struct Interest: u32 {
+        const READABLE = 1 << 0;
+        const WRITABLE = 1 << 1;
+    }
 }
 
 /// Additional methods on `EventLoop` that are specific to Unix.
@@ -162,6 +340,7 @@ pub trait EventLoopExtUnix {
     fn new_wayland_any_thread() -> Self
     where
         Self: Sized;
+
 }
 
 fn wrap_ev<T>(event_loop: LinuxEventLoop<T>) -> EventLoop<T> {
@@ -287,7 +466,7 @@ impl WindowExtUnix for Window {
     fn x11_screen_id(&self) -> Option<u32> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.screen.screen_id as _),
-            #[cfg(feature = "wayland")]
+            #[cfg(any(feature = "wayland", feature = "headless"))]
             _ => None,
         }
     }
@@ -308,7 +487,7 @@ impl WindowExtUnix for Window {
     fn xcb_connection(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xconn.c as _),
-            #[cfg(feature = "wayland")]
+            #[cfg(any(feature = "wayland", feature = "headless"))]
             _ => None,
         }
     }
@@ -318,7 +497,7 @@ impl WindowExtUnix for Window {
     fn wayland_surface(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::Wayland(ref w) => Some(w.surface().as_ref().c_ptr() as *mut _),
-            #[cfg(feature = "x11")]
+            #[cfg(any(feature = "x11", feature = "headless"))]
             _ => None,
         }
     }
@@ -328,7 +507,7 @@ impl WindowExtUnix for Window {
     fn wayland_display(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::Wayland(ref w) => Some(w.display().get_display_ptr() as *mut _),
-            #[cfg(feature = "x11")]
+            #[cfg(any(feature = "x11", feature = "headless"))]
             _ => None,
         }
     }
@@ -338,7 +517,7 @@ impl WindowExtUnix for Window {
     fn set_wayland_theme<T: Theme>(&self, theme: T) {
         match self.window {
             LinuxWindow::Wayland(ref w) => w.set_theme(theme),
-            #[cfg(feature = "x11")]
+            #[cfg(any(feature = "x11", feature = "headless"))]
             _ => {}
         }
     }
@@ -480,6 +659,56 @@ impl DeviceIdExtUnix for DeviceId {
 pub trait MonitorHandleExtUnix {
     /// Returns the inner identifier of the monitor.
     fn native_id(&self) -> u32;
+
+    /// Returns the physical width and height of the monitor in millimetres.
+    ///
+    /// The size is not adjusted for the transform of the monitor. Returns `None` if the size
+    /// is unknown.
+    fn size_mm(&self) -> Option<(u32, u32)>;
+
+    /// Returns the rotation and reflection that is applied to the contents of the monitor.
+    fn transform(&self) -> MonitorTransform;
+
+    /// Returns the layout of the subpixels of the monitor.
+    fn subpixel(&self) -> Subpixel;
+
+    /// Returns the manufacturer of the monitor.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The three-letter PNP ID from the EDID of the monitor.
+    /// - **Wayland:** The make that was reported by the compositor.
+    fn manufacturer(&self) -> Option<String>;
+
+    /// Returns the model of the monitor.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The name from the EDID of the monitor or, if it has no name, the product
+    ///   code in hexadecimal.
+    /// - **Wayland:** The model that was reported by the compositor.
+    fn model(&self) -> Option<String>;
+
+    /// Returns the serial number of the monitor.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Always returns `None`.
+    fn serial_number(&self) -> Option<String>;
+
+    /// Returns the raw EDID of the monitor.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Always returns `None`.
+    fn edid(&self) -> Option<Vec<u8>>;
+
+    /// Returns whether the monitor is the primary monitor.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Always returns `false`.
+    fn is_primary(&self) -> bool;
 }
 
 impl MonitorHandleExtUnix for MonitorHandle {
@@ -487,6 +716,128 @@ impl MonitorHandleExtUnix for MonitorHandle {
     fn native_id(&self) -> u32 {
         self.inner.native_identifier()
     }
+
+    #[inline]
+    fn size_mm(&self) -> Option<(u32, u32)> {
+        self.inner.size_mm()
+    }
+
+    #[inline]
+    fn transform(&self) -> MonitorTransform {
+        self.inner.transform()
+    }
+
+    #[inline]
+    fn subpixel(&self) -> Subpixel {
+        self.inner.subpixel()
+    }
+
+    #[inline]
+    fn manufacturer(&self) -> Option<String> {
+        self.inner.manufacturer()
+    }
+
+    #[inline]
+    fn model(&self) -> Option<String> {
+        self.inner.model()
+    }
+
+    #[inline]
+    fn serial_number(&self) -> Option<String> {
+        self.inner.serial_number()
+    }
+
+    #[inline]
+    fn edid(&self) -> Option<Vec<u8>> {
+        self.inner.edid()
+    }
+
+    #[inline]
+    fn is_primary(&self) -> bool {
+        self.inner.is_primary()
+    }
+}
+
+/// Additional methods on `VideoMode` that are specific to Linux.
+pub trait VideoModeExtUnix {
+    /// Returns whether the video mode is interlaced.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Always returns `false`.
+    fn is_interlaced(&self) -> bool;
+
+    /// Returns whether every line of the video mode is scanned twice.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Always returns `false`.
+    fn is_double_scan(&self) -> bool;
+
+    /// Returns the RandR identifier of the video mode.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Always returns `None`.
+    fn native_id(&self) -> Option<u32>;
+}
+
+impl VideoModeExtUnix for VideoMode {
+    #[inline]
+    fn is_interlaced(&self) -> bool {
+        self.video_mode.is_interlaced()
+    }
+
+    #[inline]
+    fn is_double_scan(&self) -> bool {
+        self.video_mode.is_double_scan()
+    }
+
+    #[inline]
+    fn native_id(&self) -> Option<u32> {
+        self.video_mode.native_id()
+    }
+}
+
+/// The rotation and reflection of the contents of a monitor.
+///
+/// Rotations are counter-clockwise. The flipped variants reflect the contents around the
+/// vertical axis before they are rotated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MonitorTransform {
+    Normal,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    Flipped,
+    Flipped90,
+    Flipped180,
+    Flipped270,
+}
+
+impl Default for MonitorTransform {
+    fn default() -> Self {
+        MonitorTransform::Normal
+    }
+}
+
+/// The layout of the subpixels of a monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Subpixel {
+    /// The layout is not known.
+    Unknown,
+    /// The monitor has no subpixels or their layout cannot be used for rendering.
+    None,
+    HorizontalRgb,
+    HorizontalBgr,
+    VerticalRgb,
+    VerticalBgr,
+}
+
+impl Default for Subpixel {
+    fn default() -> Self {
+        Subpixel::Unknown
+    }
 }
 
 /// A theme for a Wayland's client side decorations.
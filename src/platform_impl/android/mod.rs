@@ -227,6 +227,7 @@ impl<T: 'static> EventLoop<T> {
                                                             location,
                                                             id: pointer.pointer_id() as u64,
                                                             force: None,
+                                                            time: None,
                                                         },
                                                     ),
                                                 };
@@ -257,6 +258,7 @@ impl<T: 'static> EventLoop<T> {
                                                     modifiers: event::ModifiersState::default(),
                                                 },
                                                 is_synthetic: false,
+                                                time: None,
                                             },
                                         };
                                         call_event_handler!(
@@ -411,6 +413,15 @@ impl<T: 'static> EventLoopWindowTarget<T> {
         })
     }
 
+    pub fn add_timer(
+        &self,
+        _timer: crate::event_loop::Timer,
+    ) -> Result<crate::event_loop::TimerId, crate::error::NotSupportedError> {
+        Err(crate::error::NotSupportedError::new())
+    }
+
+    pub fn cancel_timer(&self, _id: crate::event_loop::TimerId) {}
+
     pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
         let mut v = VecDeque::with_capacity(1);
         v.push_back(MonitorHandle);
@@ -481,6 +492,18 @@ impl Window {
         // TODO
     }
 
+    pub fn present_rgba(
+        &self,
+        _rgba: &[u8],
+        _width: u32,
+        _height: u32,
+        _damage: &[window::DamageRect],
+    ) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
     pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, error::NotSupportedError> {
         Err(error::NotSupportedError::new())
     }
@@ -539,6 +562,8 @@ impl Window {
 
     pub fn set_ime_position(&self, _position: Position) {}
 
+    pub fn set_ime_allowed(&self, _allowed: bool) {}
+
     pub fn request_user_attention(&self, _request_type: Option<window::UserAttentionType>) {}
 
     pub fn set_cursor_icon(&self, _: window::CursorIcon) {}
@@ -549,7 +574,13 @@ impl Window {
         ))
     }
 
-    pub fn set_cursor_grab(&self, _: bool) -> Result<(), error::ExternalError> {
+    pub fn set_custom_cursor(&self, _: &window::CustomCursor) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn set_cursor_grab(&self, _: window::CursorGrabMode) -> Result<(), error::ExternalError> {
         Err(error::ExternalError::NotSupported(
             error::NotSupportedError::new(),
         ))
@@ -563,6 +594,17 @@ impl Window {
         ))
     }
 
+    pub fn start_drag(
+        &self,
+        _data: window::DragData,
+        _allowed_actions: &[event::DndAction],
+        _icon: Option<window::CursorIcon>,
+    ) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
     pub fn raw_window_handle(&self) -> raw_window_handle::RawWindowHandle {
         let a_native_window = if let Some(native_window) = ndk_glue::native_window().as_ref() {
             unsafe { native_window.ptr().as_mut() as *mut _ as *mut _ }
@@ -663,6 +705,10 @@ impl VideoMode {
         self.refresh_rate
     }
 
+    pub fn refresh_rate_millihertz(&self) -> u32 {
+        self.refresh_rate as u32 * 1000
+    }
+
     pub fn monitor(&self) -> monitor::MonitorHandle {
         monitor::MonitorHandle {
             inner: self.monitor.clone(),
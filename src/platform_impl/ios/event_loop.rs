@@ -63,6 +63,15 @@ impl<T: 'static> EventLoopWindowTarget<T> {
 
         Some(RootMonitorHandle { inner: monitor })
     }
+
+    pub fn add_timer(
+        &self,
+        _timer: crate::event_loop::Timer,
+    ) -> Result<crate::event_loop::TimerId, crate::error::NotSupportedError> {
+        Err(crate::error::NotSupportedError::new())
+    }
+
+    pub fn cancel_timer(&self, _id: crate::event_loop::TimerId) {}
 }
 
 pub struct EventLoop<T: 'static> {
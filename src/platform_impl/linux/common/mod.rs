@@ -1,2 +1,4 @@
 pub mod keymap;
+pub mod timer;
+#[cfg(any(feature = "x11", feature = "wayland"))]
 pub mod xkb_state;
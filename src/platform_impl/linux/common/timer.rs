@@ -0,0 +1,68 @@
+//! Timers that are shared by the X11 and Wayland event loops.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::event_loop::{Timer, TimerId};
+
+#[derive(Default)]
+pub struct Timers {
+    next_id: Cell<u64>,
+    // The next deadline and the interval of repeating timers.
+    timers: RefCell<HashMap<TimerId, (Instant, Option<Duration>)>>,
+}
+
+impl Timers {
+    pub fn add(&self, timer: Timer) -> TimerId {
+        let id = TimerId(self.next_id.get());
+        self.next_id.set(id.0 + 1);
+        let timer = match timer {
+            Timer::Once(deadline) => (deadline, None),
+            Timer::Interval(interval) => (Instant::now() + interval, Some(interval)),
+        };
+        self.timers.borrow_mut().insert(id, timer);
+        id
+    }
+
+    pub fn cancel(&self, id: TimerId) {
+        self.timers.borrow_mut().remove(&id);
+    }
+
+    /// Returns the earliest deadline of all timers.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.timers
+            .borrow()
+            .values()
+            .map(|&(deadline, _)| deadline)
+            .min()
+    }
+
+    /// Returns the timers that have fired in the order of their deadlines.
+    ///
+    /// One-shot timers are removed and repeating timers are rescheduled.
+    pub fn take_expired(&self, now: Instant) -> Vec<TimerId> {
+        let mut timers = self.timers.borrow_mut();
+        let mut expired: Vec<_> = timers
+            .iter()
+            .filter(|(_, &(deadline, _))| deadline <= now)
+            .map(|(&id, &(deadline, _))| (deadline, id))
+            .collect();
+        expired.sort();
+        for &(_, id) in &expired {
+            match timers.get_mut(&id) {
+                Some((deadline, Some(interval))) => {
+                    *deadline += *interval;
+                    // Don't fire repeatedly to catch up if the event loop was blocked.
+                    if *deadline <= now {
+                        *deadline = now + *interval;
+                    }
+                }
+                _ => {
+                    timers.remove(&id);
+                }
+            }
+        }
+        expired.into_iter().map(|(_, id)| id).collect()
+    }
+}
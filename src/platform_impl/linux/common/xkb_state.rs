@@ -583,7 +583,7 @@ fn keysym_to_key(keysym: u32) -> Key<'static> {
     }
 }
 
-fn keysym_to_utf8_raw(keysym: u32) -> Option<&'static str> {
+pub(crate) fn keysym_to_utf8_raw(keysym: u32) -> Option<&'static str> {
     let utf32 = unsafe { (XKBH.xkb_keysym_to_utf32)(keysym) };
     char_to_str(utf32)
 }
@@ -0,0 +1,491 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet, VecDeque},
+    io,
+    os::unix::io::RawFd,
+    rc::Rc,
+    sync::{
+        mpsc::{self, Receiver, SendError, Sender},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use super::{mkdid, mkwid, state::QueuedEvent, MonitorHandle, Shared};
+use crate::{
+    error::ExternalError,
+    event::{ClipboardEvent, ClipboardKind, DndAction, Event, Readiness, SourceToken, StartCause},
+    event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW},
+    monitor::MonitorHandle as RootMonitorHandle,
+    platform::{pump_events::PumpStatus, unix::Interest},
+    platform_impl::{
+        platform::{common::timer::Timers, sticky_exit_callback},
+        MonitorHandle as PlatformMonitorHandle,
+    },
+};
+
+pub struct EventLoopWindowTarget<T> {
+    pub(crate) shared: Arc<Shared>,
+    fd_sources: RefCell<HashMap<SourceToken, (RawFd, Interest)>>,
+    next_fd_token: Cell<u64>,
+    clipboards: RefCell<HashMap<ClipboardKind, Vec<(String, Vec<u8>)>>>,
+    // Clipboard events are answered in the next iteration like on other backends.
+    clipboard_events: RefCell<Vec<ClipboardEvent>>,
+    /// Timers added by the user.
+    pub timers: Timers,
+    _marker: ::std::marker::PhantomData<T>,
+}
+
+pub struct EventLoop<T: 'static> {
+    user_channel: Receiver<T>,
+    user_sender: Sender<T>,
+    target: Rc<RootELW<T>>,
+    // The control flow between calls to `pump_events` or `None` before the first call. It stays
+    // `ControlFlow::Exit` once the loop has exited.
+    pump_control_flow: Option<ControlFlow>,
+}
+
+pub struct EventLoopProxy<T: 'static> {
+    user_sender: Sender<T>,
+    shared: Arc<Shared>,
+}
+
+impl<T: 'static> Clone for EventLoopProxy<T> {
+    fn clone(&self) -> Self {
+        EventLoopProxy {
+            user_sender: self.user_sender.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T: 'static> EventLoop<T> {
+    pub fn new() -> io::Result<EventLoop<T>> {
+        let (user_sender, user_channel) = mpsc::channel();
+        let target = Rc::new(RootELW {
+            p: super::super::EventLoopWindowTarget::Headless(EventLoopWindowTarget {
+                shared: Arc::new(Shared::new()?),
+                fd_sources: Default::default(),
+                next_fd_token: Cell::new(0),
+                clipboards: Default::default(),
+                clipboard_events: Default::default(),
+                timers: Default::default(),
+                _marker: ::std::marker::PhantomData,
+            }),
+            _marker: ::std::marker::PhantomData,
+        });
+        Ok(EventLoop {
+            user_channel,
+            user_sender,
+            target,
+            pump_control_flow: None,
+        })
+    }
+
+    pub fn create_proxy(&self) -> EventLoopProxy<T> {
+        EventLoopProxy {
+            user_sender: self.user_sender.clone(),
+            shared: get_headless_target(&self.target).shared.clone(),
+        }
+    }
+
+    pub(crate) fn window_target(&self) -> &RootELW<T> {
+        &self.target
+    }
+
+    pub fn run_return<F>(&mut self, mut callback: F)
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        let mut control_flow = ControlFlow::default();
+        let mut cause = StartCause::Init;
+
+        loop {
+            self.single_iteration(&mut callback, &mut control_flow, cause);
+
+            if control_flow == ControlFlow::Exit {
+                break;
+            }
+
+            cause = self.wait(control_flow, None);
+        }
+
+        callback(Event::LoopDestroyed, &self.target, &mut control_flow);
+    }
+
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, mut callback: F) -> PumpStatus
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        if self.pump_control_flow == Some(ControlFlow::Exit) {
+            return PumpStatus::Exit;
+        }
+
+        let (mut control_flow, cause) = match self.pump_control_flow {
+            Some(control_flow) => (control_flow, self.wait(control_flow, timeout)),
+            None => (ControlFlow::default(), StartCause::Init),
+        };
+
+        // Events that are queued during the iteration wake up the next call, so a single
+        // iteration is enough.
+        self.single_iteration(&mut callback, &mut control_flow, cause);
+
+        if control_flow == ControlFlow::Exit {
+            self.pump_control_flow = Some(ControlFlow::Exit);
+            callback(Event::LoopDestroyed, &self.target, &mut control_flow);
+            PumpStatus::Exit
+        } else {
+            self.pump_control_flow = Some(control_flow);
+            PumpStatus::Continue
+        }
+    }
+
+    fn single_iteration<F>(
+        &mut self,
+        callback: &mut F,
+        control_flow: &mut ControlFlow,
+        cause: StartCause,
+    ) where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        sticky_exit_callback(
+            Event::NewEvents(cause),
+            &self.target,
+            control_flow,
+            callback,
+        );
+
+        let wt = get_headless_target(&self.target);
+
+        // Emit the timers that have fired
+        for id in wt.timers.take_expired(Instant::now()) {
+            sticky_exit_callback(Event::Timer(id), &self.target, control_flow, callback);
+        }
+
+        // Process all pending events
+        let events = wt.shared.with_state(|s| std::mem::take(&mut s.events));
+        let mut redraws = HashSet::new();
+        for event in events {
+            let event = match event {
+                QueuedEvent::Window(window_id, event) => Event::WindowEvent {
+                    window_id: mkwid(window_id),
+                    event,
+                },
+                QueuedEvent::Device(device_id, event, time) => Event::DeviceEvent {
+                    device_id: mkdid(device_id),
+                    event,
+                    time,
+                },
+                QueuedEvent::Redraw(window_id) => {
+                    redraws.insert(window_id);
+                    continue;
+                }
+            };
+            sticky_exit_callback(event, &self.target, control_flow, callback);
+        }
+
+        let clipboard_events = std::mem::take(&mut *wt.clipboard_events.borrow_mut());
+        for event in clipboard_events {
+            sticky_exit_callback(
+                Event::Clipboard(event),
+                &self.target,
+                control_flow,
+                callback,
+            );
+        }
+
+        self.dispatch_fds(callback, control_flow);
+
+        // Empty the user event buffer
+        while let Ok(event) = self.user_channel.try_recv() {
+            sticky_exit_callback(
+                Event::UserEvent(event),
+                &self.target,
+                control_flow,
+                callback,
+            );
+        }
+
+        sticky_exit_callback(
+            Event::MainEventsCleared,
+            &self.target,
+            control_flow,
+            callback,
+        );
+
+        for window_id in redraws {
+            sticky_exit_callback(
+                Event::RedrawRequested(mkwid(window_id)),
+                &self.target,
+                control_flow,
+                callback,
+            );
+        }
+
+        sticky_exit_callback(
+            Event::RedrawEventsCleared,
+            &self.target,
+            control_flow,
+            callback,
+        );
+    }
+
+    /// Blocks until new events arrive or the time requested by `control_flow` or by a timer has
+    /// been reached, but not longer than `max_timeout`. Returns the cause of the wake-up.
+    fn wait(&mut self, control_flow: ControlFlow, max_timeout: Option<Duration>) -> StartCause {
+        let start = Instant::now();
+        let (mut cause, deadline, timeout);
+
+        match control_flow {
+            ControlFlow::Poll | ControlFlow::Exit => {
+                cause = StartCause::Poll;
+                deadline = None;
+                timeout = Some(Duration::from_millis(0));
+            }
+            ControlFlow::Wait => {
+                cause = StartCause::WaitCancelled {
+                    start,
+                    requested_resume: None,
+                };
+                deadline = None;
+                timeout = None;
+            }
+            ControlFlow::WaitUntil(wait_deadline) => {
+                cause = StartCause::ResumeTimeReached {
+                    start,
+                    requested_resume: wait_deadline,
+                };
+                timeout = Some(wait_deadline.saturating_duration_since(start));
+                deadline = Some(wait_deadline);
+            }
+        }
+
+        let wt = get_headless_target(&self.target);
+
+        // Wake up for the earliest timer
+        let timeout = match wt.timers.next_deadline() {
+            Some(timer) => {
+                let until_timer = timer.saturating_duration_since(start);
+                Some(timeout.map_or(until_timer, |timeout| timeout.min(until_timer)))
+            }
+            None => timeout,
+        };
+        let timeout = match max_timeout {
+            Some(max_timeout) => Some(timeout.map_or(max_timeout, |t| t.min(max_timeout))),
+            None => timeout,
+        };
+
+        let mut fds: Vec<_> = self
+            .wakeup_fds()
+            .into_iter()
+            .map(|(fd, interest)| pollfd(fd, interest))
+            .collect();
+        let timeout = match timeout {
+            // Round up so that the deadline has been reached when we wake up.
+            Some(timeout) => ((timeout.as_micros() + 999) / 1000).min(i32::MAX as u128) as i32,
+            None => -1,
+        };
+        unsafe {
+            libc::poll(fds.as_mut_ptr(), fds.len() as _, timeout);
+        }
+        wt.shared.clear_wakeups();
+
+        let wait_cancelled = deadline.map_or(false, |deadline| Instant::now() < deadline);
+
+        if wait_cancelled {
+            cause = StartCause::WaitCancelled {
+                start,
+                requested_resume: deadline,
+            };
+        }
+
+        cause
+    }
+
+    pub fn run<F>(mut self, callback: F) -> !
+    where
+        F: 'static + FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        self.run_return(callback);
+        ::std::process::exit(0);
+    }
+
+    /// Returns whether events have been queued that `pump_events` hasn't dispatched yet.
+    #[cfg(feature = "stream")]
+    pub fn has_pending_events(&mut self) -> bool {
+        let wt = get_headless_target(&self.target);
+        wt.shared.with_state(|s| !s.events.is_empty()) || !wt.clipboard_events.borrow().is_empty()
+    }
+
+    /// Returns the deadline of the earliest timer.
+    #[cfg(feature = "stream")]
+    pub fn next_deadline(&self) -> Option<Instant> {
+        get_headless_target(&self.target).timers.next_deadline()
+    }
+
+    /// Returns the file descriptors that become ready when the event loop has new events.
+    pub fn wakeup_fds(&self) -> Vec<(RawFd, Interest)> {
+        let wt = get_headless_target(&self.target);
+        let mut fds = vec![(wt.shared.wakeup_fd(), Interest::READABLE)];
+        fds.extend(wt.fd_sources.borrow().values().copied());
+        fds
+    }
+
+    /// Emits `FdReady` for all registered file descriptors that are ready.
+    fn dispatch_fds<F>(&mut self, callback: &mut F, control_flow: &mut ControlFlow)
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        let wt = get_headless_target(&self.target);
+        let (tokens, mut fds): (Vec<_>, Vec<_>) = wt
+            .fd_sources
+            .borrow()
+            .iter()
+            .map(|(&token, &(fd, interest))| (token, pollfd(fd, interest)))
+            .unzip();
+        if fds.is_empty() {
+            return;
+        }
+        if unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as _, 0) } == -1 {
+            log::error!(
+                "Could not poll the registered file descriptors: {}",
+                io::Error::last_os_error()
+            );
+            return;
+        }
+        for (token, fd) in tokens.into_iter().zip(fds) {
+            let mut readiness = Readiness::empty();
+            readiness.set(
+                Readiness::READABLE,
+                fd.revents & (libc::POLLIN | libc::POLLHUP) != 0,
+            );
+            readiness.set(Readiness::WRITABLE, fd.revents & libc::POLLOUT != 0);
+            readiness.set(
+                Readiness::ERROR,
+                fd.revents & (libc::POLLERR | libc::POLLNVAL) != 0,
+            );
+            // An earlier callback might have unregistered the file descriptor.
+            if !readiness.is_empty() && wt.fd_sources.borrow().contains_key(&token) {
+                sticky_exit_callback(
+                    Event::FdReady { token, readiness },
+                    &self.target,
+                    control_flow,
+                    callback,
+                );
+            }
+        }
+    }
+}
+
+fn pollfd(fd: RawFd, interest: Interest) -> libc::pollfd {
+    let mut events = 0;
+    if interest.contains(Interest::READABLE) {
+        events |= libc::POLLIN;
+    }
+    if interest.contains(Interest::WRITABLE) {
+        events |= libc::POLLOUT;
+    }
+    libc::pollfd {
+        fd,
+        events,
+        revents: 0,
+    }
+}
+
+pub(crate) fn get_headless_target<T>(target: &RootELW<T>) -> &EventLoopWindowTarget<T> {
+    match target.p {
+        super::super::EventLoopWindowTarget::Headless(ref target) => target,
+        #[cfg(any(feature = "x11", feature = "wayland"))]
+        _ => unreachable!(),
+    }
+}
+
+impl<T> EventLoopWindowTarget<T> {
+    pub fn register_fd(&self, fd: RawFd, interest: Interest) -> io::Result<SourceToken> {
+        if interest.is_empty() {
+            return Err(io::ErrorKind::InvalidInput.into());
+        }
+        let token = SourceToken(self.next_fd_token.get());
+        self.next_fd_token.set(token.0 + 1);
+        self.fd_sources.borrow_mut().insert(token, (fd, interest));
+        Ok(token)
+    }
+
+    pub fn unregister_fd(&self, token: SourceToken) {
+        self.fd_sources.borrow_mut().remove(&token);
+    }
+
+    #[inline]
+    pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
+        vec![MonitorHandle].into()
+    }
+
+    #[inline]
+    pub fn primary_monitor(&self) -> Option<RootMonitorHandle> {
+        Some(RootMonitorHandle {
+            inner: PlatformMonitorHandle::Headless(MonitorHandle),
+        })
+    }
+
+    pub fn set_clipboard_data(
+        &self,
+        kind: ClipboardKind,
+        data: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), ExternalError> {
+        self.clipboards.borrow_mut().insert(kind, data);
+        Ok(())
+    }
+
+    pub fn clear_clipboard(&self, kind: ClipboardKind) -> Result<(), ExternalError> {
+        self.clipboards.borrow_mut().remove(&kind);
+        Ok(())
+    }
+
+    pub fn request_clipboard_data(&self, kind: ClipboardKind, mime_type: &str) {
+        let data = self.clipboards.borrow().get(&kind).and_then(|offers| {
+            offers
+                .iter()
+                .find(|(mime, _)| mime == mime_type)
+                .map(|(_, data)| data.clone())
+        });
+        self.push_clipboard_event(ClipboardEvent::Data {
+            kind,
+            mime_type: mime_type.to_string(),
+            data,
+        });
+    }
+
+    pub fn request_clipboard_mime_types(&self, kind: ClipboardKind) {
+        let mime_types = self
+            .clipboards
+            .borrow()
+            .get(&kind)
+            .map_or(vec![], |offers| {
+                offers.iter().map(|(mime, _)| mime.clone()).collect()
+            });
+        self.push_clipboard_event(ClipboardEvent::MimeTypes { kind, mime_types });
+    }
+
+    fn push_clipboard_event(&self, event: ClipboardEvent) {
+        self.clipboard_events.borrow_mut().push(event);
+        self.shared.wake();
+    }
+
+    pub fn set_dnd_action(&self, action: Option<DndAction>) {
+        self.shared.with_state(|s| s.set_dnd_action(action));
+    }
+
+    pub fn request_dnd_data(&self, mime_type: &str) {
+        self.shared.with_state(|s| s.request_dnd_data(mime_type));
+    }
+}
+
+impl<T: 'static> EventLoopProxy<T> {
+    pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
+        self.user_sender
+            .send(event)
+            .map_err(|SendError(x)| EventLoopClosed(x))?;
+        self.shared.wake();
+        Ok(())
+    }
+}
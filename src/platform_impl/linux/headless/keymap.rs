@@ -0,0 +1,183 @@
+//! A US QWERTY layout for the keys of the headless backend.
+
+use crate::keyboard::{Key, KeyCode, KeyLocation, ModifiersState, NativeKeyCode};
+
+/// The result of translating a key with the US layout.
+pub struct Translation {
+    pub key: Key<'static>,
+    pub key_without_modifiers: Key<'static>,
+    pub text: Option<&'static str>,
+    pub location: KeyLocation,
+}
+
+pub fn translate(code: KeyCode, modifiers: ModifiersState) -> Translation {
+    if let Some((plain, shifted)) = character(code) {
+        let text = if modifiers.shift_key() {
+            shifted
+        } else {
+            plain
+        };
+        let location = match code {
+            KeyCode::Numpad0
+            | KeyCode::Numpad1
+            | KeyCode::Numpad2
+            | KeyCode::Numpad3
+            | KeyCode::Numpad4
+            | KeyCode::Numpad5
+            | KeyCode::Numpad6
+            | KeyCode::Numpad7
+            | KeyCode::Numpad8
+            | KeyCode::Numpad9
+            | KeyCode::NumpadAdd
+            | KeyCode::NumpadDecimal
+            | KeyCode::NumpadDivide
+            | KeyCode::NumpadMultiply
+            | KeyCode::NumpadSubtract => KeyLocation::Numpad,
+            _ => KeyLocation::Standard,
+        };
+        return Translation {
+            key: Key::Character(text),
+            key_without_modifiers: Key::Character(plain),
+            text: Some(text),
+            location,
+        };
+    }
+    let (key, text) = match code {
+        KeyCode::Enter => (Key::Enter, Some("\r")),
+        KeyCode::NumpadEnter => (Key::Enter, Some("\r")),
+        KeyCode::Tab => (Key::Tab, Some("\t")),
+        KeyCode::Space => (Key::Space, Some(" ")),
+        KeyCode::Backspace => (Key::Backspace, Some("\u{8}")),
+        KeyCode::Escape => (Key::Escape, Some("\u{1b}")),
+        KeyCode::Delete => (Key::Delete, Some("\u{7f}")),
+        KeyCode::Insert => (Key::Insert, None),
+        KeyCode::Home => (Key::Home, None),
+        KeyCode::End => (Key::End, None),
+        KeyCode::PageUp => (Key::PageUp, None),
+        KeyCode::PageDown => (Key::PageDown, None),
+        KeyCode::ArrowUp => (Key::ArrowUp, None),
+        KeyCode::ArrowDown => (Key::ArrowDown, None),
+        KeyCode::ArrowLeft => (Key::ArrowLeft, None),
+        KeyCode::ArrowRight => (Key::ArrowRight, None),
+        KeyCode::ShiftLeft | KeyCode::ShiftRight => (Key::Shift, None),
+        KeyCode::ControlLeft | KeyCode::ControlRight => (Key::Control, None),
+        KeyCode::AltLeft | KeyCode::AltRight => (Key::Alt, None),
+        KeyCode::SuperLeft | KeyCode::SuperRight => (Key::Super, None),
+        KeyCode::CapsLock => (Key::CapsLock, None),
+        KeyCode::NumLock => (Key::NumLock, None),
+        KeyCode::ScrollLock => (Key::ScrollLock, None),
+        KeyCode::ContextMenu => (Key::ContextMenu, None),
+        KeyCode::PrintScreen => (Key::PrintScreen, None),
+        KeyCode::Pause => (Key::Pause, None),
+        KeyCode::F1 => (Key::F1, None),
+        KeyCode::F2 => (Key::F2, None),
+        KeyCode::F3 => (Key::F3, None),
+        KeyCode::F4 => (Key::F4, None),
+        KeyCode::F5 => (Key::F5, None),
+        KeyCode::F6 => (Key::F6, None),
+        KeyCode::F7 => (Key::F7, None),
+        KeyCode::F8 => (Key::F8, None),
+        KeyCode::F9 => (Key::F9, None),
+        KeyCode::F10 => (Key::F10, None),
+        KeyCode::F11 => (Key::F11, None),
+        KeyCode::F12 => (Key::F12, None),
+        _ => (Key::Unidentified(NativeKeyCode::Unidentified), None),
+    };
+    let location = match code {
+        KeyCode::ShiftLeft | KeyCode::ControlLeft | KeyCode::AltLeft | KeyCode::SuperLeft => {
+            KeyLocation::Left
+        }
+        KeyCode::ShiftRight | KeyCode::ControlRight | KeyCode::AltRight | KeyCode::SuperRight => {
+            KeyLocation::Right
+        }
+        KeyCode::NumpadEnter => KeyLocation::Numpad,
+        _ => KeyLocation::Standard,
+    };
+    Translation {
+        key,
+        key_without_modifiers: key,
+        text,
+        location,
+    }
+}
+
+/// Returns the modifier that a key controls.
+pub fn modifier(code: KeyCode) -> ModifiersState {
+    match code {
+        KeyCode::ShiftLeft | KeyCode::ShiftRight => ModifiersState::SHIFT,
+        KeyCode::ControlLeft | KeyCode::ControlRight => ModifiersState::CONTROL,
+        KeyCode::AltLeft | KeyCode::AltRight => ModifiersState::ALT,
+        KeyCode::SuperLeft | KeyCode::SuperRight => ModifiersState::SUPER,
+        _ => ModifiersState::empty(),
+    }
+}
+
+/// Returns the characters of a key without and with shift.
+fn character(code: KeyCode) -> Option<(&'static str, &'static str)> {
+    let c = match code {
+        KeyCode::KeyA => ("a", "A"),
+        KeyCode::KeyB => ("b", "B"),
+        KeyCode::KeyC => ("c", "C"),
+        KeyCode::KeyD => ("d", "D"),
+        KeyCode::KeyE => ("e", "E"),
+        KeyCode::KeyF => ("f", "F"),
+        KeyCode::KeyG => ("g", "G"),
+        KeyCode::KeyH => ("h", "H"),
+        KeyCode::KeyI => ("i", "I"),
+        KeyCode::KeyJ => ("j", "J"),
+        KeyCode::KeyK => ("k", "K"),
+        KeyCode::KeyL => ("l", "L"),
+        KeyCode::KeyM => ("m", "M"),
+        KeyCode::KeyN => ("n", "N"),
+        KeyCode::KeyO => ("o", "O"),
+        KeyCode::KeyP => ("p", "P"),
+        KeyCode::KeyQ => ("q", "Q"),
+        KeyCode::KeyR => ("r", "R"),
+        KeyCode::KeyS => ("s", "S"),
+        KeyCode::KeyT => ("t", "T"),
+        KeyCode::KeyU => ("u", "U"),
+        KeyCode::KeyV => ("v", "V"),
+        KeyCode::KeyW => ("w", "W"),
+        KeyCode::KeyX => ("x", "X"),
+        KeyCode::KeyY => ("y", "Y"),
+        KeyCode::KeyZ => ("z", "Z"),
+        KeyCode::Digit1 => ("1", "!"),
+        KeyCode::Digit2 => ("2", "@"),
+        KeyCode::Digit3 => ("3", "#"),
+        KeyCode::Digit4 => ("4", "$"),
+        KeyCode::Digit5 => ("5", "%"),
+        KeyCode::Digit6 => ("6", "^"),
+        KeyCode::Digit7 => ("7", "&"),
+        KeyCode::Digit8 => ("8", "*"),
+        KeyCode::Digit9 => ("9", "("),
+        KeyCode::Digit0 => ("0", ")"),
+        KeyCode::Minus => ("-", "_"),
+        KeyCode::Equal => ("=", "+"),
+        KeyCode::BracketLeft => ("[", "{"),
+        KeyCode::BracketRight => ("]", "}"),
+        KeyCode::Backslash => ("\\", "|"),
+        KeyCode::Semicolon => (";", ":"),
+        KeyCode::Quote => ("'", "\""),
+        KeyCode::Backquote => ("`", "~"),
+        KeyCode::Comma => (",", "<"),
+        KeyCode::Period => (".", ">"),
+        KeyCode::Slash => ("/", "?"),
+        KeyCode::Numpad0 => ("0", "0"),
+        KeyCode::Numpad1 => ("1", "1"),
+        KeyCode::Numpad2 => ("2", "2"),
+        KeyCode::Numpad3 => ("3", "3"),
+        KeyCode::Numpad4 => ("4", "4"),
+        KeyCode::Numpad5 => ("5", "5"),
+        KeyCode::Numpad6 => ("6", "6"),
+        KeyCode::Numpad7 => ("7", "7"),
+        KeyCode::Numpad8 => ("8", "8"),
+        KeyCode::Numpad9 => ("9", "9"),
+        KeyCode::NumpadAdd => ("+", "+"),
+        KeyCode::NumpadDecimal => (".", "."),
+        KeyCode::NumpadDivide => ("/", "/"),
+        KeyCode::NumpadMultiply => ("*", "*"),
+        KeyCode::NumpadSubtract => ("-", "-"),
+        _ => return None,
+    };
+    Some(c)
+}
@@ -0,0 +1,181 @@
+//! A backend that keeps its windows in memory.
+//!
+//! Input is injected through the handles in [`crate::platform::headless`]. The handles queue
+//! the events that a display server would send and wake up the event loop, which dispatches
+//! them in its next iteration.
+
+pub use event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget};
+pub use window::Window;
+
+pub(crate) use state::Shared;
+pub(crate) use window::WindowState;
+
+use crate::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
+    platform::unix::{MonitorTransform, Subpixel},
+    platform_impl::{MonitorHandle as PlatformMonitorHandle, VideoMode as PlatformVideoMode},
+};
+
+mod event_loop;
+mod keymap;
+mod state;
+mod window;
+
+/// The size of the only monitor.
+const MONITOR_SIZE: (u32, u32) = (1920, 1080);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DeviceId(pub(crate) u64);
+
+impl DeviceId {
+    #[cfg(not(any(feature = "x11", feature = "wayland")))]
+    pub unsafe fn dummy() -> Self {
+        DeviceId(0)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WindowId(pub(crate) u64);
+
+impl WindowId {
+    #[cfg(not(any(feature = "x11", feature = "wayland")))]
+    pub unsafe fn dummy() -> Self {
+        WindowId(0)
+    }
+}
+
+fn mkwid(id: WindowId) -> crate::window::WindowId {
+    crate::window::WindowId(crate::platform_impl::WindowId::Headless(id))
+}
+
+fn mkdid(id: u64) -> crate::event::DeviceId {
+    crate::event::DeviceId(crate::platform_impl::DeviceId::Headless(DeviceId(id)))
+}
+
+/// The only monitor of the headless backend.
+///
+/// It is placed at the origin and has a single video mode with a refresh rate of 60 Hz.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MonitorHandle;
+
+impl MonitorHandle {
+    #[inline]
+    pub fn name(&self) -> Option<String> {
+        Some("HEADLESS-1".to_string())
+    }
+
+    #[inline]
+    pub fn native_identifier(&self) -> u32 {
+        0
+    }
+
+    #[inline]
+    pub fn size(&self) -> PhysicalSize<u32> {
+        MONITOR_SIZE.into()
+    }
+
+    #[inline]
+    pub fn position(&self) -> PhysicalPosition<i32> {
+        PhysicalPosition::new(0, 0)
+    }
+
+    #[inline]
+    pub fn scale_factor(&self) -> f64 {
+        1.0
+    }
+
+    #[inline]
+    pub fn video_modes(&self) -> impl Iterator<Item = RootVideoMode> {
+        std::iter::once(RootVideoMode {
+            video_mode: PlatformVideoMode::Headless(VideoMode),
+        })
+    }
+
+    #[inline]
+    pub fn size_mm(&self) -> Option<(u32, u32)> {
+        None
+    }
+
+    #[inline]
+    pub fn transform(&self) -> MonitorTransform {
+        MonitorTransform::Normal
+    }
+
+    #[inline]
+    pub fn subpixel(&self) -> Subpixel {
+        Subpixel::Unknown
+    }
+
+    #[inline]
+    pub fn manufacturer(&self) -> Option<String> {
+        None
+    }
+
+    #[inline]
+    pub fn model(&self) -> Option<String> {
+        None
+    }
+
+    #[inline]
+    pub fn serial_number(&self) -> Option<String> {
+        None
+    }
+
+    #[inline]
+    pub fn edid(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    #[inline]
+    pub fn is_primary(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VideoMode;
+
+impl VideoMode {
+    #[inline]
+    pub fn size(&self) -> PhysicalSize<u32> {
+        MONITOR_SIZE.into()
+    }
+
+    #[inline]
+    pub fn bit_depth(&self) -> u16 {
+        24
+    }
+
+    #[inline]
+    pub fn refresh_rate(&self) -> u16 {
+        60
+    }
+
+    #[inline]
+    pub fn refresh_rate_millihertz(&self) -> u32 {
+        60_000
+    }
+
+    #[inline]
+    pub fn is_interlaced(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    pub fn is_double_scan(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    pub fn native_id(&self) -> Option<u32> {
+        None
+    }
+
+    #[inline]
+    pub fn monitor(&self) -> RootMonitorHandle {
+        RootMonitorHandle {
+            inner: PlatformMonitorHandle::Headless(MonitorHandle),
+        }
+    }
+}
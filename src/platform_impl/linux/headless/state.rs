@@ -0,0 +1,607 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    os::unix::io::RawFd,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use super::{keymap, mkdid, WindowId, WindowState};
+use crate::{
+    dpi::PhysicalPosition,
+    event::{
+        DeviceEvent, DndAction, DndEvent, ElementState, KeyEvent, MouseButton, MouseScrollDelta,
+        RawKeyEvent, ScrollSource, Timestamp, Touch, TouchPhase, WindowEvent,
+    },
+    keyboard::{KeyCode, ModifiersState},
+    platform_impl::KeyEventExtra,
+};
+
+/// The state that is shared between the event loop, its windows, and the handles that inject
+/// input.
+///
+/// The event loop sleeps on the read end of a pipe. Whenever events are queued, a byte is
+/// written to the pipe to wake it up.
+pub struct Shared {
+    state: Mutex<State>,
+    wake_read: RawFd,
+    wake_write: RawFd,
+}
+
+impl Shared {
+    pub fn new() -> io::Result<Self> {
+        let mut fds = [0; 2];
+        if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC | libc::O_NONBLOCK) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Shared {
+            state: Mutex::new(State::new()),
+            wake_read: fds[0],
+            wake_write: fds[1],
+        })
+    }
+
+    /// Runs `f` with the state and wakes up the event loop if events are queued afterwards.
+    pub fn with_state<R, F: FnOnce(&mut State) -> R>(&self, f: F) -> R {
+        let mut state = self.state.lock().unwrap();
+        let res = f(&mut state);
+        if !state.events.is_empty() {
+            self.wake();
+        }
+        res
+    }
+
+    pub fn wake(&self) {
+        // The pipe only has to be readable. If it is full, the event loop wakes up anyway.
+        unsafe {
+            libc::write(self.wake_write, [0u8].as_ptr() as *const _, 1);
+        }
+    }
+
+    /// Empties the pipe after the event loop has woken up.
+    pub fn clear_wakeups(&self) {
+        let mut buf = [0u8; 64];
+        while unsafe { libc::read(self.wake_read, buf.as_mut_ptr() as *mut _, buf.len()) } > 0 {}
+    }
+
+    pub fn wakeup_fd(&self) -> RawFd {
+        self.wake_read
+    }
+}
+
+impl Drop for Shared {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.wake_read);
+            libc::close(self.wake_write);
+        }
+    }
+}
+
+/// An event that waits to be dispatched by the event loop.
+pub enum QueuedEvent {
+    Window(WindowId, WindowEvent<'static>),
+    Device(u64, DeviceEvent, Option<Timestamp>),
+    Redraw(WindowId),
+}
+
+pub struct State {
+    next_id: u64,
+    // The origin of the timestamps of input events.
+    start: Instant,
+    pub(super) events: VecDeque<QueuedEvent>,
+    // Ordered from bottom to top.
+    windows: Vec<Arc<Mutex<WindowState>>>,
+    seats: HashMap<u64, Seat>,
+    default_seat: u64,
+    drag: Option<Drag>,
+}
+
+#[derive(Default)]
+struct Seat {
+    pointer: PhysicalPosition<f64>,
+    // The window that contains the pointer.
+    hover: Option<WindowId>,
+    // While buttons are pressed, pointer events go to the window that received the first press.
+    grab: Option<WindowId>,
+    buttons: Vec<MouseButton>,
+    focus: Option<WindowId>,
+    keys: Vec<KeyCode>,
+    modifiers: ModifiersState,
+    // The window that each finger touched first.
+    touches: HashMap<u64, WindowId>,
+}
+
+struct Drag {
+    id: u64,
+    offers: Vec<(String, Vec<u8>)>,
+    action: DndAction,
+    window: Option<WindowId>,
+    accepted: Option<DndAction>,
+    dropped: bool,
+}
+
+impl State {
+    fn new() -> Self {
+        let mut state = State {
+            next_id: 1,
+            start: Instant::now(),
+            events: VecDeque::new(),
+            windows: Vec::new(),
+            seats: HashMap::new(),
+            default_seat: 0,
+            drag: None,
+        };
+        state.default_seat = state.add_seat();
+        state
+    }
+
+    /// Returns an id that is unique among windows, seats, devices, touches, and drags.
+    pub fn new_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    /// Returns the timestamp of an input event that is generated now.
+    fn time(&self) -> Option<Timestamp> {
+        Some(Timestamp::from_millis(
+            self.start.elapsed().as_millis() as u32
+        ))
+    }
+
+    pub fn default_seat(&self) -> u64 {
+        self.default_seat
+    }
+
+    pub fn add_seat(&mut self) -> u64 {
+        let id = self.new_id();
+        self.seats.insert(id, Seat::default());
+        id
+    }
+
+    pub fn add_device(&mut self) -> u64 {
+        let id = self.new_id();
+        let time = self.time();
+        self.events
+            .push_back(QueuedEvent::Device(id, DeviceEvent::Added, time));
+        id
+    }
+
+    pub fn remove_device(&mut self, device: u64) {
+        let time = self.time();
+        self.events
+            .push_back(QueuedEvent::Device(device, DeviceEvent::Removed, time));
+    }
+
+    pub fn push_window_event(&mut self, window: WindowId, event: WindowEvent<'static>) {
+        self.events.push_back(QueuedEvent::Window(window, event));
+    }
+
+    pub fn request_redraw(&mut self, window: WindowId) {
+        self.events.push_back(QueuedEvent::Redraw(window));
+    }
+
+    pub fn add_window(&mut self, window: Arc<Mutex<WindowState>>) {
+        self.windows.push(window);
+    }
+
+    pub fn remove_window(&mut self, window: WindowId) {
+        self.windows.retain(|w| w.lock().unwrap().id != window);
+        for seat in self.seats.values_mut() {
+            let forget = |w: &mut Option<WindowId>| {
+                if *w == Some(window) {
+                    *w = None;
+                }
+            };
+            forget(&mut seat.hover);
+            forget(&mut seat.grab);
+            forget(&mut seat.focus);
+            seat.touches.retain(|_, w| *w != window);
+        }
+        if let Some(drag) = &mut self.drag {
+            if drag.window == Some(window) {
+                drag.window = None;
+            }
+        }
+        self.push_window_event(window, WindowEvent::Destroyed);
+    }
+
+    /// Returns the topmost visible window that contains `position`.
+    fn window_at(&self, position: PhysicalPosition<f64>) -> Option<WindowId> {
+        self.windows.iter().rev().find_map(|window| {
+            let window = window.lock().unwrap();
+            let x = position.x - window.position.x as f64;
+            let y = position.y - window.position.y as f64;
+            let inside = x >= 0.0
+                && y >= 0.0
+                && x < window.size.width as f64
+                && y < window.size.height as f64;
+            if window.visible && !window.minimized && inside {
+                Some(window.id)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Converts a position on the screen to a position relative to the window.
+    fn to_window(
+        &self,
+        window: WindowId,
+        position: PhysicalPosition<f64>,
+    ) -> PhysicalPosition<f64> {
+        let origin = self
+            .windows
+            .iter()
+            .map(|w| w.lock().unwrap())
+            .find(|w| w.id == window)
+            .map_or(PhysicalPosition::new(0, 0), |w| w.position);
+        PhysicalPosition::new(position.x - origin.x as f64, position.y - origin.y as f64)
+    }
+
+    pub fn focus(&mut self, seat: u64, window: Option<WindowId>) {
+        let s = self.seats.get_mut(&seat).unwrap();
+        if s.focus == window {
+            return;
+        }
+        let old = std::mem::replace(&mut s.focus, window);
+        let modifiers = s.modifiers;
+        if let Some(old) = old {
+            self.push_window_event(old, WindowEvent::Focused(false));
+        }
+        if let Some(window) = window {
+            self.push_window_event(window, WindowEvent::Focused(true));
+            self.push_window_event(window, WindowEvent::ModifiersChanged(modifiers));
+        }
+    }
+
+    pub fn key(&mut self, seat: u64, device: u64, code: KeyCode, state: ElementState) {
+        let time = self.time();
+        self.events.push_back(QueuedEvent::Device(
+            device,
+            DeviceEvent::Key(RawKeyEvent {
+                physical_key: code,
+                state,
+            }),
+            time,
+        ));
+        let s = self.seats.get_mut(&seat).unwrap();
+        let repeat = match state {
+            ElementState::Pressed if s.keys.contains(&code) => true,
+            ElementState::Pressed => {
+                s.keys.push(code);
+                false
+            }
+            ElementState::Released => {
+                s.keys.retain(|&k| k != code);
+                false
+            }
+        };
+        let translation = keymap::translate(code, s.modifiers);
+        let text = match state {
+            ElementState::Pressed => translation.text,
+            ElementState::Released => None,
+        };
+        let modifiers = s
+            .keys
+            .iter()
+            .fold(ModifiersState::empty(), |m, &k| m | keymap::modifier(k));
+        let old_modifiers = std::mem::replace(&mut s.modifiers, modifiers);
+        let focus = s.focus;
+        if let Some(window) = focus {
+            let event = KeyEvent {
+                physical_key: code,
+                logical_key: translation.key,
+                text,
+                location: translation.location,
+                state,
+                repeat,
+                platform_specific: KeyEventExtra {
+                    key_without_modifiers: translation.key_without_modifiers,
+                    // Control combines letters into control characters that we don't produce.
+                    text_with_all_modifiers: text.filter(|_| !old_modifiers.control_key()),
+                },
+            };
+            self.push_window_event(
+                window,
+                WindowEvent::KeyboardInput {
+                    device_id: mkdid(seat),
+                    event,
+                    is_synthetic: false,
+                    time,
+                },
+            );
+            if modifiers != old_modifiers {
+                self.push_window_event(window, WindowEvent::ModifiersChanged(modifiers));
+            }
+        }
+    }
+
+    pub fn pointer_position(&self, seat: u64) -> PhysicalPosition<f64> {
+        self.seats[&seat].pointer
+    }
+
+    pub fn move_pointer(&mut self, seat: u64, device: u64, dx: f64, dy: f64) {
+        let time = self.time();
+        self.events.push_back(QueuedEvent::Device(
+            device,
+            DeviceEvent::MouseMotion { delta: (dx, dy) },
+            time,
+        ));
+        let pointer = self.seats[&seat].pointer;
+        self.warp_pointer(seat, PhysicalPosition::new(pointer.x + dx, pointer.y + dy));
+    }
+
+    pub fn warp_pointer(&mut self, seat: u64, position: PhysicalPosition<f64>) {
+        self.seats.get_mut(&seat).unwrap().pointer = position;
+        self.update_pointer(seat);
+    }
+
+    /// Emits the events for the current position of the pointer.
+    fn update_pointer(&mut self, seat: u64) {
+        let s = &self.seats[&seat];
+        let position = s.pointer;
+        let hover = match s.grab {
+            Some(window) => Some(window),
+            None => self.window_at(position),
+        };
+        let s = self.seats.get_mut(&seat).unwrap();
+        let old = std::mem::replace(&mut s.hover, hover);
+        let modifiers = s.modifiers;
+        let device_id = mkdid(seat);
+        if old != hover {
+            if let Some(old) = old {
+                self.push_window_event(old, WindowEvent::CursorLeft { device_id });
+            }
+            if let Some(window) = hover {
+                self.push_window_event(window, WindowEvent::CursorEntered { device_id });
+            }
+        }
+        if let Some(window) = hover {
+            let position = self.to_window(window, position);
+            self.push_window_event(
+                window,
+                WindowEvent::CursorMoved {
+                    device_id,
+                    position,
+                    time: self.time(),
+                    modifiers,
+                },
+            );
+        }
+    }
+
+    pub fn button(&mut self, seat: u64, device: u64, button: MouseButton, state: ElementState) {
+        let button_id = match button {
+            MouseButton::Left => 1,
+            MouseButton::Middle => 2,
+            MouseButton::Right => 3,
+            MouseButton::Other(id) => id as u32,
+        };
+        let time = self.time();
+        self.events.push_back(QueuedEvent::Device(
+            device,
+            DeviceEvent::Button {
+                button: button_id,
+                state,
+            },
+            time,
+        ));
+        let s = self.seats.get_mut(&seat).unwrap();
+        let window = s.grab.or(s.hover);
+        match state {
+            ElementState::Pressed => {
+                if s.buttons.is_empty() {
+                    s.grab = s.hover;
+                }
+                s.buttons.push(button);
+            }
+            ElementState::Released => {
+                if let Some(i) = s.buttons.iter().position(|&b| b == button) {
+                    s.buttons.remove(i);
+                }
+            }
+        }
+        let ungrab = s.buttons.is_empty() && s.grab.is_some();
+        let modifiers = s.modifiers;
+        if let Some(window) = window {
+            self.push_window_event(
+                window,
+                WindowEvent::MouseInput {
+                    device_id: mkdid(seat),
+                    state,
+                    button,
+                    time,
+                    modifiers,
+                },
+            );
+        }
+        if ungrab {
+            // The pointer might have left the window while the buttons were pressed.
+            self.seats.get_mut(&seat).unwrap().grab = None;
+            if self.window_at(self.seats[&seat].pointer) != window {
+                self.update_pointer(seat);
+            }
+        }
+    }
+
+    pub fn scroll(&mut self, seat: u64, device: u64, dx: f32, dy: f32) {
+        let delta = MouseScrollDelta::LineDelta(dx, dy);
+        let time = self.time();
+        self.events.push_back(QueuedEvent::Device(
+            device,
+            DeviceEvent::MouseWheel { delta },
+            time,
+        ));
+        let s = &self.seats[&seat];
+        let modifiers = s.modifiers;
+        if let Some(window) = s.grab.or(s.hover) {
+            self.push_window_event(
+                window,
+                WindowEvent::MouseWheel {
+                    device_id: mkdid(seat),
+                    delta,
+                    phase: TouchPhase::Moved,
+                    source: ScrollSource::Wheel,
+                    time,
+                    modifiers,
+                },
+            );
+        }
+    }
+
+    pub fn touch(
+        &mut self,
+        seat: u64,
+        finger: u64,
+        phase: TouchPhase,
+        position: PhysicalPosition<f64>,
+    ) {
+        let window = match phase {
+            TouchPhase::Started => self.window_at(position),
+            _ => self.seats[&seat].touches.get(&finger).copied(),
+        };
+        let window = match window {
+            Some(window) => window,
+            None => return,
+        };
+        let touches = &mut self.seats.get_mut(&seat).unwrap().touches;
+        match phase {
+            TouchPhase::Started => {
+                touches.insert(finger, window);
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                touches.remove(&finger);
+            }
+            TouchPhase::Moved => {}
+        }
+        let location = self.to_window(window, position);
+        self.push_window_event(
+            window,
+            WindowEvent::Touch(Touch {
+                device_id: mkdid(seat),
+                phase,
+                location,
+                force: None,
+                id: finger,
+                time: self.time(),
+            }),
+        );
+    }
+
+    /// Starts a drag and returns its id. A drag that is in progress is cancelled.
+    pub fn start_drag(&mut self, offers: Vec<(String, Vec<u8>)>, action: DndAction) -> u64 {
+        if let Some(drag) = &self.drag {
+            self.cancel_drag(drag.id);
+        }
+        let id = self.new_id();
+        self.drag = Some(Drag {
+            id,
+            offers,
+            action,
+            window: None,
+            accepted: None,
+            dropped: false,
+        });
+        id
+    }
+
+    /// Returns the drag with the given id unless it has been dropped.
+    fn active_drag(&mut self, id: u64) -> Option<&mut Drag> {
+        self.drag
+            .as_mut()
+            .filter(|drag| drag.id == id && !drag.dropped)
+    }
+
+    pub fn drag_to(&mut self, id: u64, position: PhysicalPosition<f64>) {
+        let target = self.window_at(position);
+        let local = target.map(|window| self.to_window(window, position));
+        let drag = match self.active_drag(id) {
+            Some(drag) => drag,
+            None => return,
+        };
+        let old = std::mem::replace(&mut drag.window, target);
+        let mime_types = drag.offers.iter().map(|(mime, _)| mime.clone()).collect();
+        let action = drag.action;
+        if old != target {
+            drag.accepted = None;
+            if let Some(old) = old {
+                self.push_window_event(old, WindowEvent::DragAndDrop(DndEvent::Leave));
+            }
+            if let Some(window) = target {
+                self.push_window_event(
+                    window,
+                    WindowEvent::DragAndDrop(DndEvent::Enter { mime_types }),
+                );
+            }
+        }
+        if let (Some(window), Some(position)) = (target, local) {
+            self.push_window_event(
+                window,
+                WindowEvent::DragAndDrop(DndEvent::Motion { position, action }),
+            );
+        }
+    }
+
+    /// Drops the data and returns the action that the window accepted.
+    pub fn drop_drag(&mut self, id: u64) -> Option<DndAction> {
+        let drag = self.active_drag(id)?;
+        drag.dropped = true;
+        let accepted = drag.accepted;
+        let window = drag.window?;
+        // The data stays available to the window that accepted the drop.
+        let event = match accepted {
+            Some(_) => DndEvent::Drop,
+            None => {
+                drag.window = None;
+                DndEvent::Leave
+            }
+        };
+        self.push_window_event(window, WindowEvent::DragAndDrop(event));
+        accepted
+    }
+
+    pub fn cancel_drag(&mut self, id: u64) {
+        let window = match self.active_drag(id) {
+            Some(drag) => drag.window,
+            None => return,
+        };
+        self.drag = None;
+        if let Some(window) = window {
+            self.push_window_event(window, WindowEvent::DragAndDrop(DndEvent::Leave));
+        }
+    }
+
+    pub fn set_dnd_action(&mut self, action: Option<DndAction>) {
+        if let Some(drag) = &mut self.drag {
+            if !drag.dropped && drag.window.is_some() {
+                drag.accepted = action;
+            }
+        }
+    }
+
+    pub fn request_dnd_data(&mut self, mime_type: &str) {
+        let (window, data) = match &self.drag {
+            Some(Drag {
+                window: Some(window),
+                offers,
+                ..
+            }) => {
+                let data = offers
+                    .iter()
+                    .find(|(mime, _)| mime == mime_type)
+                    .map(|(_, data)| data.clone());
+                (*window, data)
+            }
+            _ => return,
+        };
+        self.push_window_event(
+            window,
+            WindowEvent::DragAndDrop(DndEvent::Data {
+                mime_type: mime_type.to_string(),
+                data,
+            }),
+        );
+    }
+}
@@ -0,0 +1,383 @@
+use std::sync::{Arc, Mutex};
+
+use super::{EventLoopWindowTarget, MonitorHandle, Shared, WindowId};
+use crate::{
+    dpi::{PhysicalPosition, PhysicalSize, Position, Size},
+    error::{ExternalError, NotSupportedError, OsError as RootOsError},
+    event::{DndAction, WindowEvent},
+    icon::Icon,
+    platform_impl::PlatformSpecificWindowBuilderAttributes,
+    window::{
+        CursorGrabMode, CursorIcon, CustomCursor, DamageRect, DragData, Fullscreen,
+        UserAttentionType, WindowAttributes,
+    },
+};
+
+/// The last frame that was presented with `present_rgba`.
+pub struct Frame {
+    pub rgba: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Everything that a display server would know about a window.
+///
+/// Nothing acts on the requests of the application: fullscreen and maximized windows keep
+/// their size, and cursor grabs don't confine the pointer.
+pub struct WindowState {
+    pub id: WindowId,
+    pub title: String,
+    pub visible: bool,
+    pub position: PhysicalPosition<i32>,
+    pub size: PhysicalSize<u32>,
+    pub min_size: Option<PhysicalSize<u32>>,
+    pub max_size: Option<PhysicalSize<u32>>,
+    pub resizable: bool,
+    pub cursor_icon: CursorIcon,
+    pub custom_cursor: Option<CustomCursor>,
+    pub cursor_grab: CursorGrabMode,
+    pub cursor_visible: bool,
+    pub maximized: bool,
+    pub minimized: bool,
+    pub fullscreen: Option<Fullscreen>,
+    pub decorations: bool,
+    pub always_on_top: bool,
+    pub window_icon: Option<Icon>,
+    pub ime_position: PhysicalPosition<i32>,
+    pub ime_allowed: bool,
+    pub attention: Option<UserAttentionType>,
+    pub frame: Option<Frame>,
+}
+
+impl WindowState {
+    fn clamp_size(&self, size: PhysicalSize<u32>) -> PhysicalSize<u32> {
+        let mut size = size;
+        if let Some(min) = self.min_size {
+            size.width = size.width.max(min.width);
+            size.height = size.height.max(min.height);
+        }
+        if let Some(max) = self.max_size {
+            size.width = size.width.min(max.width);
+            size.height = size.height.min(max.height);
+        }
+        size
+    }
+}
+
+pub struct Window {
+    pub(crate) shared: Arc<Shared>,
+    pub(crate) state: Arc<Mutex<WindowState>>,
+    id: WindowId,
+}
+
+impl Window {
+    pub fn new<T>(
+        event_loop: &EventLoopWindowTarget<T>,
+        attribs: WindowAttributes,
+        _pl_attribs: PlatformSpecificWindowBuilderAttributes,
+    ) -> Result<Self, RootOsError> {
+        let shared = event_loop.shared.clone();
+        let id = WindowId(shared.with_state(|s| s.new_id()));
+        let mut state = WindowState {
+            id,
+            title: attribs.title,
+            visible: attribs.visible,
+            position: attribs
+                .position
+                .map_or(PhysicalPosition::new(0, 0), |p| p.to_physical(1.0)),
+            size: attribs
+                .inner_size
+                .map_or(PhysicalSize::new(800, 600), |s| s.to_physical(1.0)),
+            min_size: attribs.min_inner_size.map(|s| s.to_physical(1.0)),
+            max_size: attribs.max_inner_size.map(|s| s.to_physical(1.0)),
+            resizable: attribs.resizable,
+            cursor_icon: CursorIcon::default(),
+            custom_cursor: None,
+            cursor_grab: CursorGrabMode::None,
+            cursor_visible: true,
+            maximized: attribs.maximized,
+            minimized: false,
+            fullscreen: attribs.fullscreen,
+            decorations: attribs.decorations,
+            always_on_top: attribs.always_on_top,
+            window_icon: attribs.window_icon,
+            ime_position: PhysicalPosition::new(0, 0),
+            ime_allowed: false,
+            attention: None,
+            frame: None,
+        };
+        state.size = state.clamp_size(state.size);
+        let visible = state.visible;
+        let state = Arc::new(Mutex::new(state));
+        shared.with_state(|s| {
+            s.add_window(state.clone());
+            if visible {
+                s.request_redraw(id);
+            }
+        });
+        Ok(Window { shared, state, id })
+    }
+
+    #[inline]
+    pub fn id(&self) -> WindowId {
+        self.id
+    }
+
+    /// Changes the state of the window and queues the event that `f` returns.
+    fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut WindowState) -> Option<WindowEvent<'static>>,
+    {
+        // The shared state is always locked before the state of a window.
+        self.shared.with_state(|s| {
+            let event = f(&mut self.state.lock().unwrap());
+            if let Some(event) = event {
+                s.push_window_event(self.id, event);
+            }
+        });
+    }
+
+    #[inline]
+    pub fn set_title(&self, title: &str) {
+        self.state.lock().unwrap().title = title.to_string();
+    }
+
+    #[inline]
+    pub fn set_visible(&self, visible: bool) {
+        self.state.lock().unwrap().visible = visible;
+        if visible {
+            self.request_redraw();
+        }
+    }
+
+    #[inline]
+    pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+        Ok(self.state.lock().unwrap().position)
+    }
+
+    #[inline]
+    pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+        self.outer_position()
+    }
+
+    #[inline]
+    pub fn set_outer_position(&self, position: Position) {
+        self.update(|state| {
+            let position = position.to_physical(1.0);
+            if state.position == position {
+                return None;
+            }
+            state.position = position;
+            Some(WindowEvent::Moved(position))
+        });
+    }
+
+    #[inline]
+    pub fn inner_size(&self) -> PhysicalSize<u32> {
+        self.state.lock().unwrap().size
+    }
+
+    #[inline]
+    pub fn outer_size(&self) -> PhysicalSize<u32> {
+        self.inner_size()
+    }
+
+    #[inline]
+    pub fn set_inner_size(&self, size: Size) {
+        self.update(|state| {
+            let size = state.clamp_size(size.to_physical(1.0));
+            if state.size == size {
+                return None;
+            }
+            state.size = size;
+            Some(WindowEvent::Resized(size))
+        });
+    }
+
+    #[inline]
+    pub fn set_min_inner_size(&self, dimensions: Option<Size>) {
+        self.state.lock().unwrap().min_size = dimensions.map(|s| s.to_physical(1.0));
+    }
+
+    #[inline]
+    pub fn set_max_inner_size(&self, dimensions: Option<Size>) {
+        self.state.lock().unwrap().max_size = dimensions.map(|s| s.to_physical(1.0));
+    }
+
+    #[inline]
+    pub fn set_resizable(&self, resizable: bool) {
+        self.state.lock().unwrap().resizable = resizable;
+    }
+
+    #[inline]
+    pub fn set_cursor_icon(&self, cursor: CursorIcon) {
+        let mut state = self.state.lock().unwrap();
+        state.cursor_icon = cursor;
+        state.custom_cursor = None;
+    }
+
+    #[inline]
+    pub fn set_custom_cursor(&self, cursor: &CustomCursor) -> Result<(), ExternalError> {
+        self.state.lock().unwrap().custom_cursor = Some(cursor.clone());
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+        self.state.lock().unwrap().cursor_grab = mode;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_cursor_visible(&self, visible: bool) {
+        self.state.lock().unwrap().cursor_visible = visible;
+    }
+
+    #[inline]
+    pub fn drag_window(&self) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn start_drag(
+        &self,
+        _data: DragData,
+        _allowed_actions: &[DndAction],
+        _icon: Option<CursorIcon>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn scale_factor(&self) -> f64 {
+        1.0
+    }
+
+    #[inline]
+    pub fn set_cursor_position(&self, position: Position) -> Result<(), ExternalError> {
+        let position: PhysicalPosition<f64> = position.to_physical(1.0);
+        let origin = self.state.lock().unwrap().position;
+        let position =
+            PhysicalPosition::new(origin.x as f64 + position.x, origin.y as f64 + position.y);
+        self.shared.with_state(|s| {
+            let seat = s.default_seat();
+            s.warp_pointer(seat, position);
+        });
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_maximized(&self, maximized: bool) {
+        self.state.lock().unwrap().maximized = maximized;
+    }
+
+    #[inline]
+    pub fn set_minimized(&self, minimized: bool) {
+        self.state.lock().unwrap().minimized = minimized;
+    }
+
+    #[inline]
+    pub fn fullscreen(&self) -> Option<Fullscreen> {
+        self.state.lock().unwrap().fullscreen.clone()
+    }
+
+    #[inline]
+    pub fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+        self.state.lock().unwrap().fullscreen = fullscreen;
+    }
+
+    #[inline]
+    pub fn set_decorations(&self, decorations: bool) {
+        self.state.lock().unwrap().decorations = decorations;
+    }
+
+    #[inline]
+    pub fn set_always_on_top(&self, always_on_top: bool) {
+        self.state.lock().unwrap().always_on_top = always_on_top;
+    }
+
+    #[inline]
+    pub fn set_window_icon(&self, window_icon: Option<Icon>) {
+        self.state.lock().unwrap().window_icon = window_icon;
+    }
+
+    #[inline]
+    pub fn set_ime_position(&self, position: Position) {
+        self.state.lock().unwrap().ime_position = position.to_physical(1.0);
+    }
+
+    #[inline]
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        self.state.lock().unwrap().ime_allowed = allowed;
+    }
+
+    #[inline]
+    pub fn reset_dead_keys(&self) {
+        // The layout of the headless backend has no dead keys.
+    }
+
+    #[inline]
+    pub fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
+        self.state.lock().unwrap().attention = request_type;
+    }
+
+    #[inline]
+    pub fn request_redraw(&self) {
+        self.shared.with_state(|s| s.request_redraw(self.id));
+    }
+
+    /// Stores the damaged rectangles of a frame.
+    ///
+    /// `damage` must already be clipped to the frame.
+    pub fn present_rgba(
+        &self,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        damage: &[DamageRect],
+    ) -> Result<(), ExternalError> {
+        let mut state = self.state.lock().unwrap();
+        let resized = state
+            .frame
+            .as_ref()
+            .map_or(true, |frame| frame.width != width || frame.height != height);
+        if resized {
+            state.frame = Some(Frame {
+                rgba: vec![0; rgba.len()],
+                width,
+                height,
+            });
+        }
+        let frame = state.frame.as_mut().unwrap();
+        let stride = width as usize * 4;
+        for rect in damage {
+            for row in rect.y..rect.y + rect.height {
+                let start = row as usize * stride + rect.x as usize * 4;
+                let end = start + rect.width as usize * 4;
+                frame.rgba[start..end].copy_from_slice(&rgba[start..end]);
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn current_monitor(&self) -> MonitorHandle {
+        MonitorHandle
+    }
+
+    #[inline]
+    pub fn available_monitors(&self) -> Vec<MonitorHandle> {
+        vec![MonitorHandle]
+    }
+
+    #[inline]
+    pub fn primary_monitor(&self) -> MonitorHandle {
+        MonitorHandle
+    }
+}
+
+impl Drop for Window {
+    fn drop(&mut self) {
+        self.shared.with_state(|s| s.remove_window(self.id));
+    }
+}
@@ -6,17 +6,24 @@
     target_os = "openbsd"
 ))]
 
-#[cfg(all(not(feature = "x11"), not(feature = "wayland")))]
-compile_error!("Please select a feature to build for unix: `x11`, `wayland`");
+#[cfg(all(
+    not(feature = "x11"),
+    not(feature = "wayland"),
+    not(feature = "headless")
+))]
+compile_error!("Please select a feature to build for unix: `x11`, `wayland`, `headless`");
 
 #[cfg(feature = "wayland")]
 use std::error::Error;
 #[cfg(feature = "x11")]
 use std::sync::Arc;
-use std::{collections::VecDeque, env, fmt};
+#[cfg(feature = "stream")]
+use std::time::Instant;
+use std::{collections::VecDeque, env, fmt, io, os::unix::io::RawFd, time::Duration};
 
 use raw_window_handle::RawWindowHandle;
 
+use self::common::timer::Timers;
 #[cfg(feature = "x11")]
 pub use self::x11::XNotSupported;
 #[cfg(feature = "x11")]
@@ -24,12 +31,19 @@ use self::x11::{util::WindowType as XWindowType, XConnection, XError};
 use crate::{
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
     error::{ExternalError, NotSupportedError, OsError as RootOsError},
-    event::Event,
-    event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW},
+    event::{ClipboardKind, DndAction, Event, SourceToken},
+    event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW, Timer, TimerId},
     icon::Icon,
     keyboard::Key,
     monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
-    window::{CursorIcon, Fullscreen, UserAttentionType, WindowAttributes},
+    platform::{
+        pump_events::PumpStatus,
+        unix::{Interest, MonitorTransform, Subpixel},
+    },
+    window::{
+        CursorGrabMode, CursorIcon, CustomCursor, DamageRect, DragData, Fullscreen,
+        UserAttentionType, WindowAttributes,
+    },
 };
 
 pub(crate) use crate::icon::RgbaIcon as PlatformIcon;
@@ -38,8 +52,9 @@ pub(crate) use crate::icon::RgbaIcon as PlatformIcon;
 pub mod wayland;
 #[cfg(feature = "x11")]
 pub mod x11;
+#[cfg(feature = "headless")]
+pub mod headless;
 
-#[cfg(any(feature = "x11", feature = "wayland"))]
 pub mod common;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -50,9 +65,10 @@ pub struct KeyEventExtra {
 
 /// Environment variable specifying which backend should be used on unix platform.
 ///
-/// Legal values are x11 and wayland. If this variable is set only the named backend
+/// Legal values are x11, wayland, and headless. If this variable is set only the named backend
 /// will be tried by winit. If it is not set, winit will try to connect to a wayland connection,
-/// and if it fails will fallback on x11.
+/// and if it fails will fallback on x11. The headless backend is only used if it is requested
+/// or if neither x11 nor wayland was compiled in.
 ///
 /// If this variable is set with any other value, winit will panic.
 const BACKEND_PREFERENCE_ENV_VAR: &str = "WINIT_UNIX_BACKEND";
@@ -112,6 +128,8 @@ pub enum OsError {
     XMisc(&'static str),
     #[cfg(feature = "wayland")]
     WaylandMisc(&'static str),
+    #[cfg(feature = "headless")]
+    HeadlessMisc(&'static str),
 }
 
 impl fmt::Display for OsError {
@@ -123,6 +141,8 @@ impl fmt::Display for OsError {
             OsError::XMisc(ref e) => _f.pad(e),
             #[cfg(feature = "wayland")]
             OsError::WaylandMisc(ref e) => _f.pad(e),
+            #[cfg(feature = "headless")]
+            OsError::HeadlessMisc(ref e) => _f.pad(e),
         }
     }
 }
@@ -132,6 +152,8 @@ pub enum Window {
     X(x11::Window),
     #[cfg(feature = "wayland")]
     Wayland(wayland::Window),
+    #[cfg(feature = "headless")]
+    Headless(headless::Window),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -140,6 +162,8 @@ pub enum WindowId {
     X(x11::WindowId),
     #[cfg(feature = "wayland")]
     Wayland(wayland::WindowId),
+    #[cfg(feature = "headless")]
+    Headless(headless::WindowId),
 }
 
 impl WindowId {
@@ -148,6 +172,8 @@ impl WindowId {
         return WindowId::Wayland(wayland::WindowId::dummy());
         #[cfg(all(not(feature = "wayland"), feature = "x11"))]
         return WindowId::X(x11::WindowId::dummy());
+        #[cfg(not(any(feature = "x11", feature = "wayland")))]
+        return WindowId::Headless(headless::WindowId::dummy());
     }
 }
 
@@ -157,6 +183,8 @@ pub enum DeviceId {
     X(x11::DeviceId),
     #[cfg(feature = "wayland")]
     Wayland(wayland::DeviceId),
+    #[cfg(feature = "headless")]
+    Headless(headless::DeviceId),
 }
 
 impl DeviceId {
@@ -165,6 +193,8 @@ impl DeviceId {
         return DeviceId::Wayland(wayland::DeviceId::dummy());
         #[cfg(all(not(feature = "wayland"), feature = "x11"))]
         return DeviceId::X(x11::DeviceId::dummy());
+        #[cfg(not(any(feature = "x11", feature = "wayland")))]
+        return DeviceId::Headless(headless::DeviceId::dummy());
     }
 }
 
@@ -174,6 +204,8 @@ pub enum MonitorHandle {
     X(x11::MonitorHandle),
     #[cfg(feature = "wayland")]
     Wayland(wayland::MonitorHandle),
+    #[cfg(feature = "headless")]
+    Headless(headless::MonitorHandle),
 }
 
 /// `x11_or_wayland!(match expr; Enum(foo) => foo.something())`
@@ -182,6 +214,7 @@ pub enum MonitorHandle {
 /// match self {
 ///    Enum::X(foo) => foo.something(),
 ///    Enum::Wayland(foo) => foo.something(),
+///    Enum::Headless(foo) => foo.something(),
 /// }
 /// ```
 /// The result can be converted to another enum by adding `; as AnotherEnum`
@@ -192,6 +225,8 @@ macro_rules! x11_or_wayland {
             $enum::X($($c1)*) => $enum2::X($x),
             #[cfg(feature = "wayland")]
             $enum::Wayland($($c1)*) => $enum2::Wayland($x),
+            #[cfg(feature = "headless")]
+            $enum::Headless($($c1)*) => $enum2::Headless($x),
         }
     };
     (match $what:expr; $enum:ident ( $($c1:tt)* ) => $x:expr) => {
@@ -200,6 +235,8 @@ macro_rules! x11_or_wayland {
             $enum::X($($c1)*) => $x,
             #[cfg(feature = "wayland")]
             $enum::Wayland($($c1)*) => $x,
+            #[cfg(feature = "headless")]
+            $enum::Headless($($c1)*) => $x,
         }
     };
 }
@@ -234,6 +271,46 @@ impl MonitorHandle {
     pub fn video_modes(&self) -> Box<dyn Iterator<Item = RootVideoMode>> {
         x11_or_wayland!(match self; MonitorHandle(m) => Box::new(m.video_modes()))
     }
+
+    #[inline]
+    pub fn size_mm(&self) -> Option<(u32, u32)> {
+        x11_or_wayland!(match self; MonitorHandle(m) => m.size_mm())
+    }
+
+    #[inline]
+    pub fn transform(&self) -> MonitorTransform {
+        x11_or_wayland!(match self; MonitorHandle(m) => m.transform())
+    }
+
+    #[inline]
+    pub fn subpixel(&self) -> Subpixel {
+        x11_or_wayland!(match self; MonitorHandle(m) => m.subpixel())
+    }
+
+    #[inline]
+    pub fn manufacturer(&self) -> Option<String> {
+        x11_or_wayland!(match self; MonitorHandle(m) => m.manufacturer())
+    }
+
+    #[inline]
+    pub fn model(&self) -> Option<String> {
+        x11_or_wayland!(match self; MonitorHandle(m) => m.model())
+    }
+
+    #[inline]
+    pub fn serial_number(&self) -> Option<String> {
+        x11_or_wayland!(match self; MonitorHandle(m) => m.serial_number())
+    }
+
+    #[inline]
+    pub fn edid(&self) -> Option<Vec<u8>> {
+        x11_or_wayland!(match self; MonitorHandle(m) => m.edid())
+    }
+
+    #[inline]
+    pub fn is_primary(&self) -> bool {
+        x11_or_wayland!(match self; MonitorHandle(m) => m.is_primary())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -242,6 +319,8 @@ pub enum VideoMode {
     X(x11::VideoMode),
     #[cfg(feature = "wayland")]
     Wayland(wayland::VideoMode),
+    #[cfg(feature = "headless")]
+    Headless(headless::VideoMode),
 }
 
 impl VideoMode {
@@ -260,6 +339,26 @@ impl VideoMode {
         x11_or_wayland!(match self; VideoMode(m) => m.refresh_rate())
     }
 
+    #[inline]
+    pub fn refresh_rate_millihertz(&self) -> u32 {
+        x11_or_wayland!(match self; VideoMode(m) => m.refresh_rate_millihertz())
+    }
+
+    #[inline]
+    pub fn is_interlaced(&self) -> bool {
+        x11_or_wayland!(match self; VideoMode(m) => m.is_interlaced())
+    }
+
+    #[inline]
+    pub fn is_double_scan(&self) -> bool {
+        x11_or_wayland!(match self; VideoMode(m) => m.is_double_scan())
+    }
+
+    #[inline]
+    pub fn native_id(&self) -> Option<u32> {
+        x11_or_wayland!(match self; VideoMode(m) => m.native_id())
+    }
+
     #[inline]
     pub fn monitor(&self) -> RootMonitorHandle {
         x11_or_wayland!(match self; VideoMode(m) => m.monitor())
@@ -282,6 +381,10 @@ impl Window {
             EventLoopWindowTarget::X(ref window_target) => {
                 x11::Window::new(window_target, attribs, pl_attribs).map(Window::X)
             }
+            #[cfg(feature = "headless")]
+            EventLoopWindowTarget::Headless(ref window_target) => {
+                headless::Window::new(window_target, attribs, pl_attribs).map(Window::Headless)
+            }
         }
     }
 
@@ -351,8 +454,13 @@ impl Window {
     }
 
     #[inline]
-    pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
-        x11_or_wayland!(match self; Window(window) => window.set_cursor_grab(grab))
+    pub fn set_custom_cursor(&self, cursor: &CustomCursor) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.set_custom_cursor(cursor))
+    }
+
+    #[inline]
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(window) => window.set_cursor_grab(mode))
     }
 
     #[inline]
@@ -365,6 +473,16 @@ impl Window {
         x11_or_wayland!(match self; Window(window) => window.drag_window())
     }
 
+    #[inline]
+    pub fn start_drag(
+        &self,
+        data: DragData,
+        allowed_actions: &[DndAction],
+        icon: Option<CursorIcon>,
+    ) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.start_drag(data, allowed_actions, icon))
+    }
+
     #[inline]
     pub fn scale_factor(&self) -> f64 {
         x11_or_wayland!(match self; Window(w) => w.scale_factor() as f64)
@@ -411,6 +529,8 @@ impl Window {
         match self {
             #[cfg(feature = "x11")]
             &Window::X(ref w) => w.set_always_on_top(_always_on_top),
+            #[cfg(feature = "headless")]
+            &Window::Headless(ref w) => w.set_always_on_top(_always_on_top),
             #[cfg(feature = "wayland")]
             _ => (),
         }
@@ -421,6 +541,8 @@ impl Window {
         match self {
             #[cfg(feature = "x11")]
             &Window::X(ref w) => w.set_window_icon(_window_icon),
+            #[cfg(feature = "headless")]
+            &Window::Headless(ref w) => w.set_window_icon(_window_icon),
             #[cfg(feature = "wayland")]
             _ => (),
         }
@@ -431,6 +553,11 @@ impl Window {
         x11_or_wayland!(match self; Window(w) => w.set_ime_position(position))
     }
 
+    #[inline]
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        x11_or_wayland!(match self; Window(w) => w.set_ime_allowed(allowed))
+    }
+
     #[inline]
     pub fn reset_dead_keys(&self) {
         x11_or_wayland!(match self; Window(w) => w.reset_dead_keys())
@@ -441,6 +568,8 @@ impl Window {
         match self {
             #[cfg(feature = "x11")]
             &Window::X(ref w) => w.request_user_attention(_request_type),
+            #[cfg(feature = "headless")]
+            &Window::Headless(ref w) => w.request_user_attention(_request_type),
             #[cfg(feature = "wayland")]
             _ => (),
         }
@@ -451,6 +580,18 @@ impl Window {
         x11_or_wayland!(match self; Window(w) => w.request_redraw())
     }
 
+    #[inline]
+    pub fn present_rgba(
+        &self,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        damage: &[DamageRect],
+    ) -> Result<(), ExternalError> {
+        let damage = clip_damage(damage, width, height);
+        x11_or_wayland!(match self; Window(w) => w.present_rgba(rgba, width, height, &damage))
+    }
+
     #[inline]
     pub fn current_monitor(&self) -> Option<RootMonitorHandle> {
         match self {
@@ -468,6 +609,13 @@ impl Window {
                     inner: current_monitor,
                 })
             }
+            #[cfg(feature = "headless")]
+            &Window::Headless(ref window) => {
+                let current_monitor = MonitorHandle::Headless(window.current_monitor());
+                Some(RootMonitorHandle {
+                    inner: current_monitor,
+                })
+            }
         }
     }
 
@@ -486,6 +634,12 @@ impl Window {
                 .into_iter()
                 .map(MonitorHandle::Wayland)
                 .collect(),
+            #[cfg(feature = "headless")]
+            &Window::Headless(ref window) => window
+                .available_monitors()
+                .into_iter()
+                .map(MonitorHandle::Headless)
+                .collect(),
         }
     }
 
@@ -501,6 +655,13 @@ impl Window {
             }
             #[cfg(feature = "wayland")]
             &Window::Wayland(ref window) => window.primary_monitor(),
+            #[cfg(feature = "headless")]
+            &Window::Headless(ref window) => {
+                let primary_monitor = MonitorHandle::Headless(window.primary_monitor());
+                Some(RootMonitorHandle {
+                    inner: primary_monitor,
+                })
+            }
         }
     }
 
@@ -517,6 +678,11 @@ impl Window {
             },
             #[cfg(feature = "wayland")]
             &Window::Wayland(ref window) => RawWindowHandle::Wayland(window.raw_window_handle()),
+            // Headless windows cannot be rendered to by graphics APIs.
+            #[cfg(feature = "headless")]
+            &Window::Headless(_) => {
+                RawWindowHandle::Xcb(raw_window_handle::unix::XcbHandle::empty())
+            }
         }
     }
 }
@@ -526,6 +692,8 @@ pub enum EventLoop<T: 'static> {
     Wayland(wayland::EventLoop<T>),
     #[cfg(feature = "x11")]
     X(x11::EventLoop<T>),
+    #[cfg(feature = "headless")]
+    Headless(headless::EventLoop<T>),
 }
 
 pub enum EventLoopProxy<T: 'static> {
@@ -533,6 +701,8 @@ pub enum EventLoopProxy<T: 'static> {
     X(x11::EventLoopProxy<T>),
     #[cfg(feature = "wayland")]
     Wayland(wayland::EventLoopProxy<T>),
+    #[cfg(feature = "headless")]
+    Headless(headless::EventLoopProxy<T>),
 }
 
 impl<T: 'static> Clone for EventLoopProxy<T> {
@@ -566,8 +736,14 @@ impl<T: 'static> EventLoop<T> {
                     #[cfg(not(feature = "wayland"))]
                     panic!("wayland feature is not enabled");
                 }
+                "headless" => {
+                    #[cfg(feature = "headless")]
+                    return EventLoop::new_headless().expect("Failed to initialize headless backend");
+                    #[cfg(not(feature = "headless"))]
+                    panic!("headless feature is not enabled");
+                }
                 _ => panic!(
-                    "Unknown environment variable value for {}, try one of `x11`,`wayland`",
+                    "Unknown environment variable value for {}, try one of `x11`,`wayland`,`headless`",
                     BACKEND_PREFERENCE_ENV_VAR,
                 ),
             }
@@ -585,6 +761,12 @@ impl<T: 'static> EventLoop<T> {
             Err(err) => err,
         };
 
+        // Without a display server backend, the headless backend is the only choice.
+        #[cfg(all(feature = "headless", not(any(feature = "x11", feature = "wayland"))))]
+        if let Ok(event_loop) = EventLoop::new_headless() {
+            return event_loop;
+        }
+
         #[cfg(not(feature = "wayland"))]
         let wayland_err = "backend disabled";
         #[cfg(not(feature = "x11"))]
@@ -622,6 +804,11 @@ impl<T: 'static> EventLoop<T> {
         ))))
     }
 
+    #[cfg(feature = "headless")]
+    pub fn new_headless() -> io::Result<EventLoop<T>> {
+        headless::EventLoop::new().map(EventLoop::Headless)
+    }
+
     pub fn create_proxy(&self) -> EventLoopProxy<T> {
         x11_or_wayland!(match self; EventLoop(evlp) => evlp.create_proxy(); as EventLoopProxy)
     }
@@ -633,6 +820,29 @@ impl<T: 'static> EventLoop<T> {
         x11_or_wayland!(match self; EventLoop(evlp) => evlp.run_return(callback))
     }
 
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, callback: F) -> PumpStatus
+    where
+        F: FnMut(crate::event::Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        x11_or_wayland!(match self; EventLoop(evlp) => evlp.pump_events(timeout, callback))
+    }
+
+    #[cfg(feature = "stream")]
+    pub fn has_pending_events(&mut self) -> bool {
+        x11_or_wayland!(match self; EventLoop(evlp) => evlp.has_pending_events())
+    }
+
+    #[cfg(feature = "stream")]
+    pub fn wakeup_fds(&self) -> Vec<(RawFd, Interest)> {
+        x11_or_wayland!(match self; EventLoop(evlp) => evlp.wakeup_fds())
+    }
+
+    /// Returns the time at which the event loop has to wake up for a timer.
+    #[cfg(feature = "stream")]
+    pub fn next_deadline(&self) -> Option<Instant> {
+        x11_or_wayland!(match self; EventLoop(evlp) => evlp.next_deadline())
+    }
+
     pub fn run<F>(self, callback: F) -> !
     where
         F: 'static + FnMut(crate::event::Event<'_, T>, &RootELW<T>, &mut ControlFlow),
@@ -656,6 +866,8 @@ pub enum EventLoopWindowTarget<T> {
     Wayland(wayland::EventLoopWindowTarget<T>),
     #[cfg(feature = "x11")]
     X(x11::EventLoopWindowTarget<T>),
+    #[cfg(feature = "headless")]
+    Headless(headless::EventLoopWindowTarget<T>),
 }
 
 impl<T> EventLoopWindowTarget<T> {
@@ -664,7 +876,7 @@ impl<T> EventLoopWindowTarget<T> {
         match *self {
             #[cfg(feature = "wayland")]
             EventLoopWindowTarget::Wayland(_) => true,
-            #[cfg(feature = "x11")]
+            #[cfg(any(feature = "x11", feature = "headless"))]
             _ => false,
         }
     }
@@ -685,6 +897,12 @@ impl<T> EventLoopWindowTarget<T> {
                 .into_iter()
                 .map(MonitorHandle::X)
                 .collect(),
+            #[cfg(feature = "headless")]
+            EventLoopWindowTarget::Headless(ref evlp) => evlp
+                .available_monitors()
+                .into_iter()
+                .map(MonitorHandle::Headless)
+                .collect(),
         }
     }
 
@@ -700,8 +918,162 @@ impl<T> EventLoopWindowTarget<T> {
                     inner: primary_monitor,
                 })
             }
+            #[cfg(feature = "headless")]
+            EventLoopWindowTarget::Headless(ref evlp) => evlp.primary_monitor(),
+        }
+    }
+
+    #[inline]
+    pub fn set_clipboard_data(
+        &self,
+        kind: ClipboardKind,
+        data: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), ExternalError> {
+        match *self {
+            #[cfg(feature = "wayland")]
+            EventLoopWindowTarget::Wayland(ref evlp) => evlp.set_clipboard_data(kind, data),
+            #[cfg(feature = "x11")]
+            EventLoopWindowTarget::X(ref evlp) => evlp.set_clipboard_data(kind, data),
+            #[cfg(feature = "headless")]
+            EventLoopWindowTarget::Headless(ref evlp) => evlp.set_clipboard_data(kind, data),
+        }
+    }
+
+    #[inline]
+    pub fn clear_clipboard(&self, kind: ClipboardKind) -> Result<(), ExternalError> {
+        match *self {
+            #[cfg(feature = "wayland")]
+            EventLoopWindowTarget::Wayland(ref evlp) => evlp.clear_clipboard(kind),
+            #[cfg(feature = "x11")]
+            EventLoopWindowTarget::X(ref evlp) => evlp.clear_clipboard(kind),
+            #[cfg(feature = "headless")]
+            EventLoopWindowTarget::Headless(ref evlp) => evlp.clear_clipboard(kind),
+        }
+    }
+
+    #[inline]
+    pub fn request_clipboard_data(&self, kind: ClipboardKind, mime_type: &str) {
+        match *self {
+            #[cfg(feature = "wayland")]
+            EventLoopWindowTarget::Wayland(ref evlp) => {
+                evlp.request_clipboard_data(kind, mime_type)
+            }
+            #[cfg(feature = "x11")]
+            EventLoopWindowTarget::X(ref evlp) => evlp.request_clipboard_data(kind, mime_type),
+            #[cfg(feature = "headless")]
+            EventLoopWindowTarget::Headless(ref evlp) => {
+                evlp.request_clipboard_data(kind, mime_type)
+            }
         }
     }
+
+    #[inline]
+    pub fn request_clipboard_mime_types(&self, kind: ClipboardKind) {
+        match *self {
+            #[cfg(feature = "wayland")]
+            EventLoopWindowTarget::Wayland(ref evlp) => evlp.request_clipboard_mime_types(kind),
+            #[cfg(feature = "x11")]
+            EventLoopWindowTarget::X(ref evlp) => evlp.request_clipboard_mime_types(kind),
+            #[cfg(feature = "headless")]
+            EventLoopWindowTarget::Headless(ref evlp) => evlp.request_clipboard_mime_types(kind),
+        }
+    }
+
+    #[inline]
+    pub fn set_dnd_action(&self, action: Option<DndAction>) {
+        match *self {
+            // Drag and drop is not implemented on Wayland.
+            #[cfg(feature = "wayland")]
+            EventLoopWindowTarget::Wayland(_) => {
+                let _ = action;
+            }
+            #[cfg(feature = "x11")]
+            EventLoopWindowTarget::X(ref evlp) => evlp.set_dnd_action(action),
+            #[cfg(feature = "headless")]
+            EventLoopWindowTarget::Headless(ref evlp) => evlp.set_dnd_action(action),
+        }
+    }
+
+    #[inline]
+    pub fn request_dnd_data(&self, mime_type: &str) {
+        match *self {
+            #[cfg(feature = "wayland")]
+            EventLoopWindowTarget::Wayland(_) => {
+                let _ = mime_type;
+            }
+            #[cfg(feature = "x11")]
+            EventLoopWindowTarget::X(ref evlp) => evlp.request_dnd_data(mime_type),
+            #[cfg(feature = "headless")]
+            EventLoopWindowTarget::Headless(ref evlp) => evlp.request_dnd_data(mime_type),
+        }
+    }
+
+    pub fn register_fd(&self, fd: RawFd, interest: Interest) -> io::Result<SourceToken> {
+        x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => evlp.register_fd(fd, interest))
+    }
+
+    pub fn unregister_fd(&self, token: SourceToken) {
+        x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => evlp.unregister_fd(token))
+    }
+
+    fn timers(&self) -> &Timers {
+        x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => &evlp.timers)
+    }
+
+    pub fn add_timer(&self, timer: Timer) -> Result<TimerId, NotSupportedError> {
+        Ok(self.timers().add(timer))
+    }
+
+    pub fn cancel_timer(&self, id: TimerId) {
+        self.timers().cancel(id)
+    }
+
+    #[inline]
+    pub fn set_cursor_theme(&self, theme: Option<&str>, size: Option<u32>) {
+        match *self {
+            // The cursor theme of Wayland windows is chosen when the event loop is created.
+            #[cfg(feature = "wayland")]
+            EventLoopWindowTarget::Wayland(_) => {
+                let _ = (theme, size);
+            }
+            #[cfg(feature = "x11")]
+            EventLoopWindowTarget::X(ref evlp) => evlp.set_cursor_theme(theme, size),
+            // Headless windows have no cursor images.
+            #[cfg(feature = "headless")]
+            EventLoopWindowTarget::Headless(_) => {
+                let _ = (theme, size);
+            }
+        }
+    }
+}
+
+/// Clips the damage of a frame to the frame. Empty damage covers the whole frame.
+fn clip_damage(damage: &[DamageRect], width: u32, height: u32) -> Vec<DamageRect> {
+    let frame = [DamageRect {
+        x: 0,
+        y: 0,
+        width,
+        height,
+    }];
+    let damage: &[DamageRect] = if damage.is_empty() { &frame } else { damage };
+    damage
+        .iter()
+        .filter_map(|rect| {
+            let x = rect.x.min(width);
+            let y = rect.y.min(height);
+            let rect = DamageRect {
+                x,
+                y,
+                width: rect.width.min(width - x),
+                height: rect.height.min(height - y),
+            };
+            if rect.width == 0 || rect.height == 0 {
+                None
+            } else {
+                Some(rect)
+            }
+        })
+        .collect()
 }
 
 fn sticky_exit_callback<T, F>(
@@ -12,7 +12,12 @@ use sctk::reexports::client::protocol::wl_shm::WlShm;
 use sctk::reexports::protocols::xdg_shell::client::xdg_wm_base::XdgWmBase;
 use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_relative_pointer_manager_v1::ZwpRelativePointerManagerV1;
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_pointer_constraints_v1::ZwpPointerConstraintsV1;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gestures_v1::ZwpPointerGesturesV1;
 use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3;
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_manager_v2::ZwpTabletManagerV2;
+use sctk::reexports::client::protocol::wl_data_device_manager::WlDataDeviceManager;
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_device_manager_v1::ZwpPrimarySelectionDeviceManagerV1;
+use sctk::reexports::protocols::viewporter::client::wp_viewporter::WpViewporter;
 
 use sctk::environment::{Environment, SimpleGlobal};
 use sctk::output::{OutputHandler, OutputHandling, OutputInfo, OutputStatusListener};
@@ -24,18 +29,27 @@ use sctk::shm::ShmHandler;
 #[derive(Debug, Clone, Copy)]
 pub struct WindowingFeatures {
     cursor_grab: bool,
+    viewporter: bool,
 }
 
 impl WindowingFeatures {
     /// Create `WindowingFeatures` based on the presented interfaces.
     pub fn new(env: &Environment<WinitEnv>) -> Self {
         let cursor_grab = env.get_global::<ZwpPointerConstraintsV1>().is_some();
-        Self { cursor_grab }
+        let viewporter = env.get_global::<WpViewporter>().is_some();
+        Self {
+            cursor_grab,
+            viewporter,
+        }
     }
 
     pub fn cursor_grab(&self) -> bool {
         self.cursor_grab
     }
+
+    pub fn viewporter(&self) -> bool {
+        self.viewporter
+    }
 }
 
 sctk::environment!(WinitEnv,
@@ -49,7 +63,12 @@ sctk::environment!(WinitEnv,
         ZxdgDecorationManagerV1 => decoration_manager,
         ZwpRelativePointerManagerV1 => relative_pointer_manager,
         ZwpPointerConstraintsV1 => pointer_constraints,
+        ZwpPointerGesturesV1 => pointer_gestures,
         ZwpTextInputManagerV3 => text_input_manager,
+        ZwpTabletManagerV2 => tablet_manager,
+        WlDataDeviceManager => data_device_manager,
+        ZwpPrimarySelectionDeviceManagerV1 => primary_selection_manager,
+        WpViewporter => viewporter,
     ],
     multis = [
         WlSeat => seats,
@@ -75,9 +94,19 @@ pub struct WinitEnv {
 
     pointer_constraints: SimpleGlobal<ZwpPointerConstraintsV1>,
 
+    pointer_gestures: SimpleGlobal<ZwpPointerGesturesV1>,
+
     text_input_manager: SimpleGlobal<ZwpTextInputManagerV3>,
 
+    tablet_manager: SimpleGlobal<ZwpTabletManagerV2>,
+
     decoration_manager: SimpleGlobal<ZxdgDecorationManagerV1>,
+
+    data_device_manager: SimpleGlobal<WlDataDeviceManager>,
+
+    primary_selection_manager: SimpleGlobal<ZwpPrimarySelectionDeviceManagerV1>,
+
+    viewporter: SimpleGlobal<WpViewporter>,
 }
 
 impl WinitEnv {
@@ -106,9 +135,22 @@ impl WinitEnv {
         // Pointer grab functionality.
         let pointer_constraints = SimpleGlobal::new();
 
+        // Touchpad gestures.
+        let pointer_gestures = SimpleGlobal::new();
+
         // IME handling.
         let text_input_manager = SimpleGlobal::new();
 
+        // Drawing tablets.
+        let tablet_manager = SimpleGlobal::new();
+
+        // Clipboard handling.
+        let data_device_manager = SimpleGlobal::new();
+        let primary_selection_manager = SimpleGlobal::new();
+
+        // Scaling of surfaces in exclusive fullscreen.
+        let viewporter = SimpleGlobal::new();
+
         Self {
             seats,
             outputs,
@@ -119,7 +161,12 @@ impl WinitEnv {
             decoration_manager,
             relative_pointer_manager,
             pointer_constraints,
+            pointer_gestures,
             text_input_manager,
+            tablet_manager,
+            data_device_manager,
+            primary_selection_manager,
+            viewporter,
         }
     }
 }
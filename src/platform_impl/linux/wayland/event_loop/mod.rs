@@ -1,8 +1,11 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::error::Error;
+use std::io;
+use std::os::unix::io::RawFd;
 use std::process;
 use std::rc::Rc;
+use std::sync::mpsc;
 use std::time::{Duration, Instant};
 
 use sctk::reexports::client::protocol::wl_compositor::WlCompositor;
@@ -10,29 +13,35 @@ use sctk::reexports::client::protocol::wl_shm::WlShm;
 use sctk::reexports::client::Display;
 
 use sctk::reexports::calloop;
+use sctk::reexports::calloop::generic::{Fd, Generic};
 
 use sctk::environment::Environment;
 use sctk::seat::pointer::{ThemeManager, ThemeSpec};
 use sctk::WaylandSource;
 
-use crate::event::{Event, StartCause, WindowEvent};
+use crate::event::{Event, Readiness, SourceToken, StartCause, WindowEvent};
 use crate::event_loop::{ControlFlow, EventLoopWindowTarget as RootEventLoopWindowTarget};
+use crate::platform::pump_events::PumpStatus;
+use crate::platform::unix::Interest;
+use crate::platform_impl::platform::common::timer::Timers;
 use crate::platform_impl::platform::sticky_exit_callback;
 
 use super::env::{WindowingFeatures, WinitEnv};
 use super::output::OutputManager;
-use super::seat::SeatManager;
+use super::seat::{ClipboardManager, RepeatTimers, SeatManager};
 use super::window::shim::{self, WindowUpdate};
 use super::{DeviceId, WindowId};
 
+mod ping;
 mod proxy;
 mod sink;
 mod state;
 
+pub use ping::{make_ping, Ping, PingReader};
 pub use proxy::EventLoopProxy;
 pub use state::WinitState;
 
-use sink::EventSink;
+pub use sink::EventSink;
 
 pub struct EventLoopWindowTarget<T> {
     /// Wayland display.
@@ -54,7 +63,7 @@ pub struct EventLoopWindowTarget<T> {
     pub wayland_source: Rc<calloop::Source<WaylandSource>>,
 
     /// A proxy to wake up event loop.
-    pub event_loop_awakener: calloop::ping::Ping,
+    pub event_loop_awakener: Ping,
 
     /// The available windowing features.
     pub windowing_features: WindowingFeatures,
@@ -65,6 +74,18 @@ pub struct EventLoopWindowTarget<T> {
     /// multiple similar themes.
     pub theme_manager: ThemeManager,
 
+    /// Clipboard manager to access the clipboards of all seats.
+    pub clipboard_manager: ClipboardManager,
+
+    /// Timers added by the user.
+    pub timers: Timers,
+
+    /// File descriptors registered by the user.
+    fd_sources: RefCell<HashMap<SourceToken, calloop::Source<Generic<Fd>>>>,
+
+    /// The token of the next registered file descriptor.
+    next_fd_token: Cell<u64>,
+
     _marker: std::marker::PhantomData<T>,
 }
 
@@ -79,7 +100,18 @@ pub struct EventLoop<T: 'static> {
     pending_user_events: Rc<RefCell<Vec<T>>>,
 
     /// Sender of user events.
-    user_events_sender: calloop::channel::Sender<T>,
+    user_events_sender: mpsc::Sender<T>,
+
+    /// A handle to wake up the event loop.
+    event_loop_awakener: Ping,
+
+    /// The file descriptor that becomes readable when `event_loop_awakener` is pinged.
+    #[cfg(feature = "stream")]
+    wakeup_fd: RawFd,
+
+    /// The control flow between calls to `pump_events` or `None` before the first call. It stays
+    /// `ControlFlow::Exit` once the loop has exited.
+    pump_control_flow: Option<ControlFlow>,
 
     /// Wayland source of events.
     wayland_source: Rc<calloop::Source<WaylandSource>>,
@@ -87,6 +119,9 @@ pub struct EventLoop<T: 'static> {
     /// Window target.
     window_target: RootEventLoopWindowTarget<T>,
 
+    /// The timers of key repetition, which are woken up by us.
+    repeat_timers: RepeatTimers,
+
     /// Output manager.
     _seat_manager: SeatManager,
 }
@@ -112,7 +147,15 @@ impl<T: 'static> EventLoop<T> {
         let theme_manager = ThemeManager::init(ThemeSpec::System, compositor, shm);
 
         // Setup theme seat and output managers.
-        let seat_manager = SeatManager::new(&env, event_loop.handle(), theme_manager.clone());
+        let clipboard_manager = ClipboardManager::new(&env, event_loop.handle());
+        let repeat_timers = RepeatTimers::default();
+        let seat_manager = SeatManager::new(
+            &env,
+            event_loop.handle(),
+            theme_manager.clone(),
+            clipboard_manager.clone(),
+            repeat_timers.clone(),
+        );
         let output_manager = OutputManager::new(&env);
 
         // A source of events that we plug into our event loop.
@@ -122,27 +165,30 @@ impl<T: 'static> EventLoop<T> {
         // A source of user events.
         let pending_user_events = Rc::new(RefCell::new(Vec::new()));
         let pending_user_events_clone = pending_user_events.clone();
-        let (user_events_sender, user_events_channel) = calloop::channel::channel();
-
-        // User events channel.
+        let (user_events_sender, user_events_receiver) = mpsc::channel();
+
+        // An event's loop awakener to wake up for user events and window events from winit's
+        // windows.
+        let (event_loop_awakener, event_loop_awakener_reader) = ping::make_ping()?;
+        #[cfg(feature = "stream")]
+        let wakeup_fd = event_loop_awakener_reader.fd();
+
+        // Handler of user events and window requests.
+        let awakener_source = Generic::from_fd(
+            event_loop_awakener_reader.fd(),
+            calloop::Interest::Readable,
+            calloop::Mode::Level,
+        );
         event_loop
             .handle()
-            .insert_source(user_events_channel, move |event, _, _| {
-                if let calloop::channel::Event::Msg(msg) = event {
-                    pending_user_events_clone.borrow_mut().push(msg);
-                }
-            })?;
-
-        // An event's loop awakener to wake up for window events from winit's windows.
-        let (event_loop_awakener, event_loop_awakener_source) = calloop::ping::make_ping()?;
-
-        // Handler of window requests.
-        event_loop.handle().insert_source(
-            event_loop_awakener_source,
-            move |_, _, winit_state| {
+            .insert_source(awakener_source, move |_, _, winit_state| {
+                event_loop_awakener_reader.drain();
+                pending_user_events_clone
+                    .borrow_mut()
+                    .extend(user_events_receiver.try_iter());
                 shim::handle_window_requests(winit_state);
-            },
-        )?;
+                Ok(())
+            })?;
 
         let event_loop_handle = event_loop.handle();
         let window_map = HashMap::new();
@@ -160,10 +206,14 @@ impl<T: 'static> EventLoop<T> {
             }),
             event_loop_handle,
             output_manager,
-            event_loop_awakener,
+            event_loop_awakener: event_loop_awakener.clone(),
             wayland_source: wayland_source.clone(),
             windowing_features,
             theme_manager,
+            clipboard_manager,
+            timers: Default::default(),
+            fd_sources: Default::default(),
+            next_fd_token: Cell::new(0),
             _marker: std::marker::PhantomData,
         };
 
@@ -175,6 +225,11 @@ impl<T: 'static> EventLoop<T> {
             wayland_source,
             _seat_manager: seat_manager,
             user_events_sender,
+            event_loop_awakener,
+            #[cfg(feature = "stream")]
+            wakeup_fd,
+            pump_control_flow: None,
+            repeat_timers,
             window_target: RootEventLoopWindowTarget {
                 p: crate::platform_impl::EventLoopWindowTarget::Wayland(event_loop_window_target),
                 _marker: std::marker::PhantomData,
@@ -200,17 +255,7 @@ impl<T: 'static> EventLoop<T> {
         let _ = self.display.flush();
 
         let mut control_flow = ControlFlow::default();
-
-        let pending_user_events = self.pending_user_events.clone();
-
-        callback(
-            Event::NewEvents(StartCause::Init),
-            &self.window_target,
-            &mut control_flow,
-        );
-
-        let mut window_updates: Vec<(WindowId, WindowUpdate)> = Vec::new();
-        let mut event_sink_back_buffer = Vec::new();
+        let mut cause = StartCause::Init;
 
         // NOTE We break on errors from dispatches, since if we've got protocol error
         // libwayland-client/wayland-rs will inform us anyway, but crashing downstream is not
@@ -218,287 +263,390 @@ impl<T: 'static> EventLoop<T> {
         // communicate an error that something was terminated, but winit doesn't provide us
         // with an API to do that via some event.
         loop {
-            // Handle pending user events. We don't need back buffer, since we can't dispatch
-            // user events indirectly via callback to the user.
-            for user_event in pending_user_events.borrow_mut().drain(..) {
-                sticky_exit_callback(
-                    Event::UserEvent(user_event),
-                    &self.window_target,
-                    &mut control_flow,
-                    &mut callback,
-                );
+            let instant_wakeup =
+                match self.single_iteration(&mut callback, &mut control_flow, cause) {
+                    Some(instant_wakeup) => instant_wakeup,
+                    None => break,
+                };
+
+            if control_flow == ControlFlow::Exit {
+                break;
             }
 
-            // Process 'new' pending updates.
-            self.with_state(|state| {
-                window_updates.clear();
-                window_updates.extend(
-                    state
-                        .window_updates
-                        .iter_mut()
-                        .map(|(wid, window_update)| (*wid, window_update.take())),
-                );
-            });
+            cause = match self.wait(control_flow, instant_wakeup, None) {
+                Some(cause) => cause,
+                None => break,
+            };
+        }
 
-            for (window_id, window_update) in window_updates.iter_mut() {
-                if let Some(scale_factor) = window_update.scale_factor.map(|f| f as f64) {
-                    let mut physical_size = self.with_state(|state| {
-                        let window_handle = state.window_map.get(&window_id).unwrap();
-                        let mut size = window_handle.size.lock().unwrap();
+        callback(Event::LoopDestroyed, &self.window_target, &mut control_flow);
+    }
 
-                        // Update the new logical size if it was changed.
-                        let window_size = window_update.size.unwrap_or(*size);
-                        *size = window_size;
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, mut callback: F) -> PumpStatus
+    where
+        F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        if self.pump_control_flow == Some(ControlFlow::Exit) {
+            return PumpStatus::Exit;
+        }
 
-                        window_size.to_physical(scale_factor)
-                    });
+        let mut control_flow = ControlFlow::default();
+        let mut cause = match self.pump_control_flow {
+            Some(pump_control_flow) => {
+                control_flow = pump_control_flow;
+                self.wait(control_flow, false, timeout)
+            }
+            None => {
+                // Send pending events to the server.
+                let _ = self.display.flush();
+                Some(StartCause::Init)
+            }
+        };
 
-                    sticky_exit_callback(
-                        Event::WindowEvent {
-                            window_id: crate::window::WindowId(
-                                crate::platform_impl::WindowId::Wayland(*window_id),
-                            ),
-                            event: WindowEvent::ScaleFactorChanged {
-                                scale_factor,
-                                new_inner_size: &mut physical_size,
-                            },
-                        },
-                        &self.window_target,
-                        &mut control_flow,
-                        &mut callback,
-                    );
+        while let Some(current_cause) = cause {
+            let instant_wakeup =
+                match self.single_iteration(&mut callback, &mut control_flow, current_cause) {
+                    Some(instant_wakeup) => instant_wakeup,
+                    None => break,
+                };
 
-                    // We don't update size on a window handle since we'll do that later
-                    // when handling size update.
-                    let new_logical_size = physical_size.to_logical(scale_factor);
-                    window_update.size = Some(new_logical_size);
-                }
+            if control_flow == ControlFlow::Exit {
+                break;
+            }
+
+            // Events that were read by other code while the callback ran would otherwise only
+            // be dispatched after the next wake-up.
+            if !instant_wakeup {
+                self.pump_control_flow = Some(control_flow);
+                return PumpStatus::Continue;
+            }
+            cause = self.wait(control_flow, true, None);
+        }
+
+        self.pump_control_flow = Some(ControlFlow::Exit);
+        callback(Event::LoopDestroyed, &self.window_target, &mut control_flow);
+        PumpStatus::Exit
+    }
+
+    /// Runs one iteration of the event loop, from `NewEvents` to `RedrawEventsCleared`.
+    ///
+    /// Returns whether events are already queued or `None` if the connection has failed.
+    fn single_iteration<F>(
+        &mut self,
+        callback: &mut F,
+        control_flow: &mut ControlFlow,
+        cause: StartCause,
+    ) -> Option<bool>
+    where
+        F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        sticky_exit_callback(
+            Event::NewEvents(cause),
+            &self.window_target,
+            control_flow,
+            callback,
+        );
+
+        let pending_user_events = self.pending_user_events.clone();
+        let mut window_updates: Vec<(WindowId, WindowUpdate)> = Vec::new();
+        let mut event_sink_back_buffer = Vec::new();
+
+        // Handle the timers that have fired.
+        let expired = self.window_target.p.timers().take_expired(Instant::now());
+        for id in expired {
+            sticky_exit_callback(
+                Event::Timer(id),
+                &self.window_target,
+                control_flow,
+                callback,
+            );
+        }
 
-                if let Some(size) = window_update.size.take() {
-                    let physical_size = self.with_state(|state| {
-                        let window_handle = state.window_map.get_mut(&window_id).unwrap();
-                        let mut window_size = window_handle.size.lock().unwrap();
-
-                        // Always issue resize event on scale factor change.
-                        let physical_size =
-                            if window_update.scale_factor.is_none() && *window_size == size {
-                                // The size hasn't changed, don't inform downstream about that.
-                                None
-                            } else {
-                                *window_size = size;
-                                let scale_factor =
-                                    sctk::get_surface_scale_factor(&window_handle.window.surface());
-                                let physical_size = size.to_physical(scale_factor as f64);
-                                Some(physical_size)
-                            };
-
-                        // We still perform all of those resize related logic even if the size
-                        // hasn't changed, since GNOME relies on `set_geometry` calls after
-                        // configures.
-                        window_handle.window.resize(size.width, size.height);
-                        window_handle.window.refresh();
-
-                        // Mark that refresh isn't required, since we've done it right now.
-                        window_update.refresh_frame = false;
-
-                        physical_size
-                    });
-
-                    if let Some(physical_size) = physical_size {
-                        sticky_exit_callback(
-                            Event::WindowEvent {
-                                window_id: crate::window::WindowId(
-                                    crate::platform_impl::WindowId::Wayland(*window_id),
-                                ),
-                                event: WindowEvent::Resized(physical_size),
-                            },
-                            &self.window_target,
-                            &mut control_flow,
-                            &mut callback,
-                        );
+        // Handle pending user events. We don't need back buffer, since we can't dispatch
+        // user events indirectly via callback to the user.
+        for user_event in pending_user_events.borrow_mut().drain(..) {
+            sticky_exit_callback(
+                Event::UserEvent(user_event),
+                &self.window_target,
+                control_flow,
+                callback,
+            );
+        }
+
+        // Process 'new' pending updates.
+        self.with_state(|state| {
+            window_updates.clear();
+            window_updates.extend(
+                state
+                    .window_updates
+                    .iter_mut()
+                    .map(|(wid, window_update)| (*wid, window_update.take())),
+            );
+        });
+
+        for (window_id, window_update) in window_updates.iter_mut() {
+            // The size of the window is only known once it has been configured.
+            let viewport_changed = window_update.configured
+                && self.with_state(|state| {
+                    let window_handle = state.window_map.get(window_id).unwrap();
+                    let changed = window_handle.apply_viewport();
+                    if changed && window_update.size.is_none() {
+                        window_update.size = Some(*window_handle.size.lock().unwrap());
                     }
-                }
+                    changed
+                });
+
+            if let Some(scale_factor) = window_update.scale_factor.map(|f| f as f64) {
+                let mut physical_size = self.with_state(|state| {
+                    let window_handle = state.window_map.get(&window_id).unwrap();
+                    let mut size = window_handle.size.lock().unwrap();
+
+                    // Update the new logical size if it was changed.
+                    let window_size = window_update.size.unwrap_or(*size);
+                    *size = window_size;
+
+                    window_size.to_physical(scale_factor)
+                });
+
+                sticky_exit_callback(
+                    Event::WindowEvent {
+                        window_id: crate::window::WindowId(
+                            crate::platform_impl::WindowId::Wayland(*window_id),
+                        ),
+                        event: WindowEvent::ScaleFactorChanged {
+                            scale_factor,
+                            new_inner_size: &mut physical_size,
+                        },
+                    },
+                    &self.window_target,
+                    control_flow,
+                    callback,
+                );
+
+                // We don't update size on a window handle since we'll do that later
+                // when handling size update.
+                let new_logical_size = physical_size.to_logical(scale_factor);
+                window_update.size = Some(new_logical_size);
+            }
+
+            if let Some(size) = window_update.size.take() {
+                let physical_size = self.with_state(|state| {
+                    let window_handle = state.window_map.get_mut(&window_id).unwrap();
+                    let mut window_size = window_handle.size.lock().unwrap();
+
+                    // Always issue resize event on scale factor change.
+                    let physical_size = if window_update.scale_factor.is_none()
+                        && !viewport_changed
+                        && *window_size == size
+                    {
+                        // The size hasn't changed, don't inform downstream about that.
+                        None
+                    } else {
+                        *window_size = size;
+                        let scale_factor =
+                            sctk::get_surface_scale_factor(&window_handle.window.surface());
+                        let physical_size = window_handle
+                            .viewport_size()
+                            .unwrap_or_else(|| size.to_physical(scale_factor as f64));
+                        Some(physical_size)
+                    };
 
-                if window_update.close_window {
+                    // We still perform all of those resize related logic even if the size
+                    // hasn't changed, since GNOME relies on `set_geometry` calls after
+                    // configures.
+                    window_handle.window.resize(size.width, size.height);
+                    window_handle.update_viewport(size);
+                    window_handle.window.refresh();
+
+                    // Mark that refresh isn't required, since we've done it right now.
+                    window_update.refresh_frame = false;
+
+                    physical_size
+                });
+
+                if let Some(physical_size) = physical_size {
                     sticky_exit_callback(
                         Event::WindowEvent {
                             window_id: crate::window::WindowId(
                                 crate::platform_impl::WindowId::Wayland(*window_id),
                             ),
-                            event: WindowEvent::CloseRequested,
+                            event: WindowEvent::Resized(physical_size),
                         },
                         &self.window_target,
-                        &mut control_flow,
-                        &mut callback,
+                        control_flow,
+                        callback,
                     );
                 }
             }
 
-            // The purpose of the back buffer and that swap is to not hold borrow_mut when
-            // we're doing callback to the user, since we can double borrow if the user decides
-            // to create a window in one of those callbacks.
-            self.with_state(|state| {
-                std::mem::swap(
-                    &mut event_sink_back_buffer,
-                    &mut state.event_sink.window_events,
-                )
-            });
-
-            // Handle pending window events.
-            for event in event_sink_back_buffer.drain(..) {
-                let event = event.map_nonuser_event().unwrap();
-                sticky_exit_callback(event, &self.window_target, &mut control_flow, &mut callback);
+            if window_update.close_window {
+                sticky_exit_callback(
+                    Event::WindowEvent {
+                        window_id: crate::window::WindowId(
+                            crate::platform_impl::WindowId::Wayland(*window_id),
+                        ),
+                        event: WindowEvent::CloseRequested,
+                    },
+                    &self.window_target,
+                    control_flow,
+                    callback,
+                );
             }
+        }
 
-            // Send events cleared.
-            sticky_exit_callback(
-                Event::MainEventsCleared,
-                &self.window_target,
-                &mut control_flow,
-                &mut callback,
-            );
-
-            // Handle RedrawRequested events.
-            for (window_id, window_update) in window_updates.iter() {
-                // Handle refresh of the frame.
-                if window_update.refresh_frame {
-                    self.with_state(|state| {
-                        let window_handle = state.window_map.get_mut(&window_id).unwrap();
-                        window_handle.window.refresh();
-                        if !window_update.redraw_requested {
-                            window_handle.window.surface().commit();
-                        }
-                    });
-                }
-
-                // Handle redraw request.
-                if window_update.redraw_requested {
-                    sticky_exit_callback(
-                        Event::RedrawRequested(crate::window::WindowId(
-                            crate::platform_impl::WindowId::Wayland(*window_id),
-                        )),
-                        &self.window_target,
-                        &mut control_flow,
-                        &mut callback,
-                    );
+        // The purpose of the back buffer and that swap is to not hold borrow_mut when
+        // we're doing callback to the user, since we can double borrow if the user decides
+        // to create a window in one of those callbacks.
+        self.with_state(|state| {
+            std::mem::swap(
+                &mut event_sink_back_buffer,
+                &mut state.event_sink.window_events,
+            )
+        });
+
+        // Handle pending window events.
+        for event in event_sink_back_buffer.drain(..) {
+            let event = event.map_nonuser_event().unwrap();
+            // An earlier callback might have unregistered the file descriptor.
+            if let Event::FdReady { token, .. } = event {
+                if !self.is_fd_registered(token) {
+                    continue;
                 }
             }
+            sticky_exit_callback(event, &self.window_target, control_flow, callback);
+        }
 
-            // Send RedrawEventCleared.
-            sticky_exit_callback(
-                Event::RedrawEventsCleared,
-                &self.window_target,
-                &mut control_flow,
-                &mut callback,
-            );
+        // Send events cleared.
+        sticky_exit_callback(
+            Event::MainEventsCleared,
+            &self.window_target,
+            control_flow,
+            callback,
+        );
 
-            // Send pending events to the server.
-            let _ = self.display.flush();
-
-            // During the run of the user callback, some other code monitoring and reading the
-            // Wayland socket may have been run (mesa for example does this with vsync), if that
-            // is the case, some events may have been enqueued in our event queue.
-            //
-            // If some messages are there, the event loop needs to behave as if it was instantly
-            // woken up by messages arriving from the Wayland socket, to avoid delaying the
-            // dispatch of these events until we're woken up again.
-            let instant_wakeup = {
-                let handle = self.event_loop.handle();
-                let source = self.wayland_source.clone();
-                let dispatched = handle.with_source(&source, |wayland_source| {
-                    let queue = wayland_source.queue();
-                    self.with_state(|state| {
-                        queue.dispatch_pending(state, |_, _, _| unimplemented!())
-                    })
+        // Handle RedrawRequested events.
+        for (window_id, window_update) in window_updates.iter() {
+            // Handle refresh of the frame.
+            if window_update.refresh_frame {
+                self.with_state(|state| {
+                    let window_handle = state.window_map.get_mut(&window_id).unwrap();
+                    window_handle.window.refresh();
+                    if !window_update.redraw_requested {
+                        window_handle.window.surface().commit();
+                    }
                 });
+            }
 
-                if let Ok(dispatched) = dispatched {
-                    dispatched > 0
-                } else {
-                    break;
-                }
-            };
+            // Handle redraw request.
+            if window_update.redraw_requested {
+                sticky_exit_callback(
+                    Event::RedrawRequested(crate::window::WindowId(
+                        crate::platform_impl::WindowId::Wayland(*window_id),
+                    )),
+                    &self.window_target,
+                    control_flow,
+                    callback,
+                );
+            }
+        }
 
-            match control_flow {
-                ControlFlow::Exit => break,
-                ControlFlow::Poll => {
-                    // Non-blocking dispatch.
-                    let timeout = Duration::from_millis(0);
-                    if self.loop_dispatch(Some(timeout)).is_err() {
-                        break;
-                    }
+        // Send RedrawEventCleared.
+        sticky_exit_callback(
+            Event::RedrawEventsCleared,
+            &self.window_target,
+            control_flow,
+            callback,
+        );
 
-                    callback(
-                        Event::NewEvents(StartCause::Poll),
-                        &self.window_target,
-                        &mut control_flow,
-                    );
-                }
-                ControlFlow::Wait => {
-                    let timeout = if instant_wakeup {
-                        Some(Duration::from_millis(0))
-                    } else {
-                        None
-                    };
+        // Send pending events to the server.
+        let _ = self.display.flush();
 
-                    if self.loop_dispatch(timeout).is_err() {
-                        break;
-                    }
+        // During the run of the user callback, some other code monitoring and reading the
+        // Wayland socket may have been run (mesa for example does this with vsync), if that
+        // is the case, some events may have been enqueued in our event queue.
+        //
+        // If some messages are there, the event loop needs to behave as if it was instantly
+        // woken up by messages arriving from the Wayland socket, to avoid delaying the
+        // dispatch of these events until we're woken up again.
+        self.dispatch_pending()
+    }
 
-                    callback(
-                        Event::NewEvents(StartCause::WaitCancelled {
-                            start: Instant::now(),
-                            requested_resume: None,
-                        }),
-                        &self.window_target,
-                        &mut control_flow,
-                    );
-                }
-                ControlFlow::WaitUntil(deadline) => {
-                    let start = Instant::now();
+    /// Dispatches the events that have already been read from the socket.
+    ///
+    /// Returns whether there were any or `None` if the connection has failed.
+    fn dispatch_pending(&mut self) -> Option<bool> {
+        let handle = self.event_loop.handle();
+        let source = self.wayland_source.clone();
+        let dispatched = handle.with_source(&source, |wayland_source| {
+            let queue = wayland_source.queue();
+            self.with_state(|state| queue.dispatch_pending(state, |_, _, _| unimplemented!()))
+        });
+
+        dispatched.ok().map(|dispatched| dispatched > 0)
+    }
 
-                    // Compute the amount of time we'll block for.
-                    let duration = if deadline > start && !instant_wakeup {
-                        deadline - start
-                    } else {
-                        Duration::from_millis(0)
-                    };
+    /// Dispatches the event sources, blocking until new events arrive or the time requested by
+    /// `control_flow` or by a timer has been reached, but not longer than `max_timeout`.
+    ///
+    /// Returns the cause of the wake-up or `None` if the connection has failed.
+    fn wait(
+        &mut self,
+        control_flow: ControlFlow,
+        instant_wakeup: bool,
+        max_timeout: Option<Duration>,
+    ) -> Option<StartCause> {
+        let start = Instant::now();
+
+        // The earliest timer limits how long we block for.
+        let until_timer = self
+            .next_deadline()
+            .map(|deadline| deadline.saturating_duration_since(start));
+
+        let timeout = match control_flow {
+            _ if instant_wakeup => Some(Duration::from_millis(0)),
+            ControlFlow::Poll | ControlFlow::Exit => Some(Duration::from_millis(0)),
+            ControlFlow::Wait => until_timer,
+            ControlFlow::WaitUntil(deadline) => {
+                let duration = deadline.saturating_duration_since(start);
+                Some(until_timer.map_or(duration, |t| t.min(duration)))
+            }
+        };
+        let timeout = match max_timeout {
+            Some(max_timeout) => Some(timeout.map_or(max_timeout, |t| t.min(max_timeout))),
+            None => timeout,
+        };
 
-                    if self.loop_dispatch(Some(duration)).is_err() {
-                        break;
-                    }
+        self.loop_dispatch(timeout).ok()?;
 
-                    let now = Instant::now();
-
-                    if now < deadline {
-                        callback(
-                            Event::NewEvents(StartCause::WaitCancelled {
-                                start,
-                                requested_resume: Some(deadline),
-                            }),
-                            &self.window_target,
-                            &mut control_flow,
-                        )
-                    } else {
-                        callback(
-                            Event::NewEvents(StartCause::ResumeTimeReached {
-                                start,
-                                requested_resume: deadline,
-                            }),
-                            &self.window_target,
-                            &mut control_flow,
-                        )
+        let cause = match control_flow {
+            ControlFlow::Poll | ControlFlow::Exit => StartCause::Poll,
+            ControlFlow::Wait => StartCause::WaitCancelled {
+                start,
+                requested_resume: None,
+            },
+            ControlFlow::WaitUntil(deadline) => {
+                if Instant::now() < deadline {
+                    StartCause::WaitCancelled {
+                        start,
+                        requested_resume: Some(deadline),
+                    }
+                } else {
+                    StartCause::ResumeTimeReached {
+                        start,
+                        requested_resume: deadline,
                     }
                 }
             }
-        }
-
-        callback(Event::LoopDestroyed, &self.window_target, &mut control_flow);
+        };
+        Some(cause)
     }
 
     #[inline]
     pub fn create_proxy(&self) -> EventLoopProxy<T> {
-        EventLoopProxy::new(self.user_events_sender.clone())
+        EventLoopProxy::new(
+            self.user_events_sender.clone(),
+            self.event_loop_awakener.clone(),
+        )
     }
 
     #[inline]
@@ -506,12 +654,66 @@ impl<T: 'static> EventLoop<T> {
         &self.window_target
     }
 
+    /// Returns the deadline of the earliest timer, including the timers of key repetition.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let timer = self.window_target.p.timers().next_deadline();
+        match (timer, self.repeat_timers.next_deadline()) {
+            (Some(timer), Some(repeat)) => Some(timer.min(repeat)),
+            (timer, repeat) => timer.or(repeat),
+        }
+    }
+
+    /// Returns whether events have been read from the socket that `pump_events` hasn't
+    /// dispatched yet.
+    #[cfg(feature = "stream")]
+    pub fn has_pending_events(&mut self) -> bool {
+        // Let `pump_events` report the failure of the connection.
+        self.dispatch_pending().unwrap_or(true)
+    }
+
+    /// Returns the file descriptors that become ready when the event loop has new events.
+    ///
+    /// Timers, including those of key repetition, additionally require a wake-up at
+    /// `next_deadline`.
+    #[cfg(feature = "stream")]
+    pub fn wakeup_fds(&self) -> Vec<(RawFd, Interest)> {
+        let mut fds = vec![
+            (self.display.get_connection_fd(), Interest::READABLE),
+            (self.wakeup_fd, Interest::READABLE),
+        ];
+        let handle = self.event_loop.handle();
+        let wt = match &self.window_target.p {
+            crate::platform_impl::EventLoopWindowTarget::Wayland(wt) => wt,
+            #[cfg(any(feature = "x11", feature = "headless"))]
+            _ => unreachable!(),
+        };
+        for (fd, interest) in wt.clipboard_manager.transfer_fds() {
+            fds.push((fd, convert_interest(interest)));
+        }
+        for source in wt.fd_sources.borrow().values() {
+            fds.push(handle.with_source(source, |generic| {
+                (generic.file.0, convert_interest(generic.interest))
+            }));
+        }
+        fds
+    }
+
+    fn is_fd_registered(&self, token: SourceToken) -> bool {
+        match &self.window_target.p {
+            crate::platform_impl::EventLoopWindowTarget::Wayland(window_target) => {
+                window_target.fd_sources.borrow().contains_key(&token)
+            }
+            #[cfg(any(feature = "x11", feature = "headless"))]
+            _ => unreachable!(),
+        }
+    }
+
     fn with_state<U, F: FnOnce(&mut WinitState) -> U>(&mut self, f: F) -> U {
         let state = match &mut self.window_target.p {
             crate::platform_impl::EventLoopWindowTarget::Wayland(ref mut window_target) => {
                 window_target.state.get_mut()
             }
-            #[cfg(feature = "x11")]
+            #[cfg(any(feature = "x11", feature = "headless"))]
             _ => unreachable!(),
         };
 
@@ -526,10 +728,64 @@ impl<T: 'static> EventLoop<T> {
             crate::platform_impl::EventLoopWindowTarget::Wayland(ref mut window_target) => {
                 window_target.state.get_mut()
             }
-            #[cfg(feature = "x11")]
+            #[cfg(any(feature = "x11", feature = "headless"))]
             _ => unreachable!(),
         };
 
-        self.event_loop.dispatch(timeout, &mut state)
+        self.event_loop.dispatch(timeout, &mut state)?;
+
+        // Dispatch the key repetitions that are due now rather than after the next wake-up.
+        if self.repeat_timers.wake_expired(Instant::now()) {
+            self.event_loop
+                .dispatch(Some(Duration::from_millis(0)), &mut state)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> EventLoopWindowTarget<T> {
+    pub fn register_fd(&self, fd: RawFd, interest: Interest) -> io::Result<SourceToken> {
+        let interest = match (
+            interest.contains(Interest::READABLE),
+            interest.contains(Interest::WRITABLE),
+        ) {
+            (true, true) => calloop::Interest::Both,
+            (true, false) => calloop::Interest::Readable,
+            (false, true) => calloop::Interest::Writable,
+            (false, false) => return Err(io::ErrorKind::InvalidInput.into()),
+        };
+        let token = SourceToken(self.next_fd_token.get());
+        self.next_fd_token.set(token.0 + 1);
+
+        let generic = Generic::from_fd(fd, interest, calloop::Mode::Level);
+        let source = self
+            .event_loop_handle
+            .insert_source(generic, move |ready, _, winit_state| {
+                let mut readiness = Readiness::empty();
+                readiness.set(Readiness::READABLE, ready.readable);
+                readiness.set(Readiness::WRITABLE, ready.writable);
+                readiness.set(Readiness::ERROR, ready.error);
+                winit_state.event_sink.push_fd_ready(token, readiness);
+                Ok(())
+            })
+            .map_err(|e| e.error)?;
+        self.fd_sources.borrow_mut().insert(token, source);
+        Ok(token)
+    }
+
+    pub fn unregister_fd(&self, token: SourceToken) {
+        if let Some(source) = self.fd_sources.borrow_mut().remove(&token) {
+            self.event_loop_handle.remove(source);
+        }
+    }
+}
+
+#[cfg(feature = "stream")]
+fn convert_interest(interest: calloop::Interest) -> Interest {
+    match interest {
+        calloop::Interest::Readable => Interest::READABLE,
+        calloop::Interest::Writable => Interest::WRITABLE,
+        calloop::Interest::Both => Interest::READABLE | Interest::WRITABLE,
     }
 }
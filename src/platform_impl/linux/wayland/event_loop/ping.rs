@@ -0,0 +1,64 @@
+//! A pipe that wakes up the event loop.
+//!
+//! Unlike the pings of calloop, the file descriptor of the pipe is known to us so that the
+//! event loop can be waited on without dispatching it.
+
+use std::io;
+use std::os::unix::io::RawFd;
+use std::sync::Arc;
+
+struct PipeFd(RawFd);
+
+impl Drop for PipeFd {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.0);
+        }
+    }
+}
+
+/// A handle that wakes up the event loop from any thread.
+#[derive(Clone)]
+pub struct Ping {
+    write: Arc<PipeFd>,
+}
+
+impl Ping {
+    pub fn ping(&self) {
+        // If the pipe is full, the event loop is going to wake up anyway.
+        unsafe {
+            libc::write(self.write.0, [0u8].as_ptr() as *const _, 1);
+        }
+    }
+}
+
+/// The end of the pipe that is registered with the event loop.
+pub struct PingReader {
+    read: PipeFd,
+}
+
+impl PingReader {
+    pub fn fd(&self) -> RawFd {
+        self.read.0
+    }
+
+    /// Resets the pipe after a wake-up.
+    pub fn drain(&self) {
+        let mut buf = [0u8; 32];
+        while unsafe { libc::read(self.read.0, buf.as_mut_ptr() as *mut _, buf.len()) } > 0 {}
+    }
+}
+
+pub fn make_ping() -> io::Result<(Ping, PingReader)> {
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC | libc::O_NONBLOCK) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    let ping = Ping {
+        write: Arc::new(PipeFd(fds[1])),
+    };
+    let reader = PingReader {
+        read: PipeFd(fds[0]),
+    };
+    Ok((ping, reader))
+}
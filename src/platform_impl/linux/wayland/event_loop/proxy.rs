@@ -1,32 +1,39 @@
 //! An event loop proxy.
 
-use std::sync::mpsc::SendError;
-
-use sctk::reexports::calloop::channel::Sender;
+use std::sync::mpsc::{SendError, Sender};
 
 use crate::event_loop::EventLoopClosed;
 
+use super::ping::Ping;
+
 /// A handle that can be sent across the threads and used to wake up the `EventLoop`.
 pub struct EventLoopProxy<T: 'static> {
     user_events_sender: Sender<T>,
+    event_loop_awakener: Ping,
 }
 
 impl<T: 'static> Clone for EventLoopProxy<T> {
     fn clone(&self) -> Self {
         EventLoopProxy {
             user_events_sender: self.user_events_sender.clone(),
+            event_loop_awakener: self.event_loop_awakener.clone(),
         }
     }
 }
 
 impl<T: 'static> EventLoopProxy<T> {
-    pub fn new(user_events_sender: Sender<T>) -> Self {
-        Self { user_events_sender }
+    pub fn new(user_events_sender: Sender<T>, event_loop_awakener: Ping) -> Self {
+        Self {
+            user_events_sender,
+            event_loop_awakener,
+        }
     }
 
     pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
         self.user_events_sender
             .send(event)
-            .map_err(|SendError(error)| EventLoopClosed(error))
+            .map_err(|SendError(error)| EventLoopClosed(error))?;
+        self.event_loop_awakener.ping();
+        Ok(())
     }
 }
@@ -1,6 +1,9 @@
 //! An event loop's sink to deliver events from the Wayland event callbacks.
 
-use crate::event::{DeviceEvent, DeviceId as RootDeviceId, Event, WindowEvent};
+use crate::event::{
+    ClipboardEvent, DeviceEvent, DeviceId as RootDeviceId, Event, Readiness, SourceToken,
+    Timestamp, WindowEvent,
+};
 use crate::platform_impl::platform::{DeviceId as PlatformDeviceId, WindowId as PlatformWindowId};
 use crate::window::WindowId as RootWindowId;
 
@@ -19,10 +22,16 @@ impl EventSink {
     }
 
     /// Add new device event to a queue.
-    pub fn push_device_event(&mut self, event: DeviceEvent, device_id: DeviceId) {
+    pub fn push_device_event(
+        &mut self,
+        event: DeviceEvent,
+        device_id: DeviceId,
+        time: Option<Timestamp>,
+    ) {
         self.window_events.push(Event::DeviceEvent {
             event,
             device_id: RootDeviceId(PlatformDeviceId::Wayland(device_id)),
+            time,
         });
     }
 
@@ -33,4 +42,19 @@ impl EventSink {
             window_id: RootWindowId(PlatformWindowId::Wayland(window_id)),
         });
     }
+
+    /// Add new clipboard event to a queue.
+    pub fn push_clipboard_event(&mut self, event: ClipboardEvent) {
+        self.window_events.push(Event::Clipboard(event));
+    }
+
+    /// Add new `MonitorAdded`, `MonitorRemoved`, or `MonitorChanged` event to a queue.
+    pub fn push_monitor_event(&mut self, event: Event<'static, ()>) {
+        self.window_events.push(event);
+    }
+
+    /// Add new readiness of a user file descriptor to a queue.
+    pub fn push_fd_ready(&mut self, token: SourceToken, readiness: Readiness) {
+        self.window_events.push(Event::FdReady { token, readiness });
+    }
 }
@@ -1,20 +1,22 @@
 use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 
-use sctk::reexports::client::protocol::wl_output::WlOutput;
+use sctk::reexports::client::protocol::wl_output::{self, WlOutput};
 use sctk::reexports::client::Display;
 
 use sctk::environment::Environment;
 use sctk::output::OutputStatusListener;
 
 use crate::dpi::{PhysicalPosition, PhysicalSize};
+use crate::event::Event;
 use crate::monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode};
+use crate::platform::unix::{MonitorTransform, Subpixel};
 use crate::platform_impl::platform::{
     MonitorHandle as PlatformMonitorHandle, VideoMode as PlatformVideoMode,
 };
 
 use super::env::WinitEnv;
-use super::event_loop::EventLoopWindowTarget;
+use super::event_loop::{EventLoopWindowTarget, WinitState};
 
 /// Output manager.
 pub struct OutputManager {
@@ -42,11 +44,23 @@ impl OutputManager {
 
         let handle_for_listener = handle.clone();
 
-        let output_listener = env.listen_for_outputs(move |output, info, _| {
-            if info.obsolete {
-                handle_for_listener.remove_output(output)
+        let output_listener = env.listen_for_outputs(move |output, info, mut dispatch_data| {
+            let monitor = RootMonitorHandle {
+                inner: PlatformMonitorHandle::Wayland(MonitorHandle::new(output.clone())),
+            };
+            let event = if info.obsolete {
+                if !handle_for_listener.remove_output(output) {
+                    return;
+                }
+                Event::MonitorRemoved(monitor)
+            } else if handle_for_listener.add_output(output) {
+                Event::MonitorAdded(monitor)
             } else {
-                handle_for_listener.add_output(output)
+                Event::MonitorChanged(monitor)
+            };
+
+            if let Some(winit_state) = dispatch_data.get::<WinitState>() {
+                winit_state.event_sink.push_monitor_event(event);
             }
         });
 
@@ -73,22 +87,24 @@ impl OutputManagerHandle {
         Self { outputs }
     }
 
-    /// Handle addition of the output.
-    fn add_output(&self, output: WlOutput) {
+    /// Handle addition of the output. Returns `false` if the output was already known.
+    fn add_output(&self, output: WlOutput) -> bool {
         let mut outputs = self.outputs.lock().unwrap();
         let position = outputs.iter().position(|handle| handle.proxy == output);
         if position.is_none() {
             outputs.push_back(MonitorHandle::new(output));
         }
+        position.is_none()
     }
 
-    /// Handle removal of the output.
-    fn remove_output(&self, output: WlOutput) {
+    /// Handle removal of the output. Returns `false` if the output was unknown.
+    fn remove_output(&self, output: WlOutput) -> bool {
         let mut outputs = self.outputs.lock().unwrap();
         let position = outputs.iter().position(|handle| handle.proxy == output);
         if let Some(position) = position {
             outputs.remove(position);
         }
+        position.is_some()
     }
 
     /// Get all observed outputs.
@@ -182,19 +198,76 @@ impl MonitorHandle {
         modes.into_iter().map(move |mode| RootVideoMode {
             video_mode: PlatformVideoMode::Wayland(VideoMode {
                 size: (mode.dimensions.0 as u32, mode.dimensions.1 as u32).into(),
-                refresh_rate: (mode.refresh_rate as f32 / 1000.0).round() as u16,
+                refresh_rate_millihertz: mode.refresh_rate.max(0) as u32,
                 bit_depth: 32,
                 monitor: monitor.clone(),
             }),
         })
     }
+
+    #[inline]
+    pub fn size_mm(&self) -> Option<(u32, u32)> {
+        match sctk::output::with_output_info(&self.proxy, |info| info.physical_size) {
+            Some((w, h)) if w > 0 && h > 0 => Some((w as u32, h as u32)),
+            _ => None,
+        }
+    }
+
+    pub fn transform(&self) -> MonitorTransform {
+        match sctk::output::with_output_info(&self.proxy, |info| info.transform) {
+            Some(wl_output::Transform::_90) => MonitorTransform::Rotate90,
+            Some(wl_output::Transform::_180) => MonitorTransform::Rotate180,
+            Some(wl_output::Transform::_270) => MonitorTransform::Rotate270,
+            Some(wl_output::Transform::Flipped) => MonitorTransform::Flipped,
+            Some(wl_output::Transform::Flipped90) => MonitorTransform::Flipped90,
+            Some(wl_output::Transform::Flipped180) => MonitorTransform::Flipped180,
+            Some(wl_output::Transform::Flipped270) => MonitorTransform::Flipped270,
+            _ => MonitorTransform::Normal,
+        }
+    }
+
+    pub fn subpixel(&self) -> Subpixel {
+        match sctk::output::with_output_info(&self.proxy, |info| info.subpixel) {
+            Some(wl_output::Subpixel::None) => Subpixel::None,
+            Some(wl_output::Subpixel::HorizontalRgb) => Subpixel::HorizontalRgb,
+            Some(wl_output::Subpixel::HorizontalBgr) => Subpixel::HorizontalBgr,
+            Some(wl_output::Subpixel::VerticalRgb) => Subpixel::VerticalRgb,
+            Some(wl_output::Subpixel::VerticalBgr) => Subpixel::VerticalBgr,
+            _ => Subpixel::Unknown,
+        }
+    }
+
+    #[inline]
+    pub fn manufacturer(&self) -> Option<String> {
+        sctk::output::with_output_info(&self.proxy, |info| info.make.clone())
+    }
+
+    #[inline]
+    pub fn model(&self) -> Option<String> {
+        sctk::output::with_output_info(&self.proxy, |info| info.model.clone())
+    }
+
+    #[inline]
+    pub fn serial_number(&self) -> Option<String> {
+        None
+    }
+
+    #[inline]
+    pub fn edid(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    #[inline]
+    pub fn is_primary(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct VideoMode {
     pub(crate) size: PhysicalSize<u32>,
     pub(crate) bit_depth: u16,
-    pub(crate) refresh_rate: u16,
+    pub(crate) refresh_rate_millihertz: u32,
     pub(crate) monitor: MonitorHandle,
 }
 
@@ -211,7 +284,27 @@ impl VideoMode {
 
     #[inline]
     pub fn refresh_rate(&self) -> u16 {
-        self.refresh_rate
+        ((self.refresh_rate_millihertz + 500) / 1000) as u16
+    }
+
+    #[inline]
+    pub fn refresh_rate_millihertz(&self) -> u32 {
+        self.refresh_rate_millihertz
+    }
+
+    #[inline]
+    pub fn is_interlaced(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    pub fn is_double_scan(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    pub fn native_id(&self) -> Option<u32> {
+        None
     }
 
     pub fn monitor(&self) -> RootMonitorHandle {
@@ -0,0 +1,564 @@
+//! Clipboard and primary selection handling.
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::mem;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+use std::rc::{Rc, Weak};
+
+use sctk::reexports::calloop::generic::Generic;
+use sctk::reexports::calloop::{Interest, LoopHandle, Mode, Source};
+use sctk::reexports::client::protocol::wl_data_device_manager::WlDataDeviceManager;
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::Attached;
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_device_manager_v1::ZwpPrimarySelectionDeviceManagerV1;
+
+use sctk::data_device::{DataDevice, DataSource, DataSourceEvent, WritePipe};
+use sctk::environment::Environment;
+use sctk::primary_selection::{
+    PrimarySelectionDevice, PrimarySelectionDeviceManager, PrimarySelectionSource,
+    PrimarySelectionSourceEvent,
+};
+
+use crate::error::{ExternalError, NotSupportedError};
+use crate::event::{ClipboardEvent, ClipboardKind};
+use crate::platform_impl::wayland::env::WinitEnv;
+use crate::platform_impl::wayland::event_loop::{EventLoopWindowTarget, WinitState};
+use crate::platform_impl::OsError;
+
+/// The MIME type under which UTF-8 text is exchanged.
+const TEXT_MIME_TYPE: &str = "text/plain;charset=utf-8";
+
+/// Legacy names of UTF-8 text that are understood by X11 clients running under Xwayland.
+const TEXT_MIME_TYPE_ALIASES: &[&str] = &["UTF8_STRING", "TEXT"];
+
+/// The data offered while we own a clipboard.
+type Offer = Rc<Vec<(String, Rc<[u8]>)>>;
+
+/// The result of a request for the contents of a clipboard. `None` until the request has
+/// completed.
+type Request = Rc<RefCell<Option<ClipboardEvent>>>;
+
+/// The pipes of the transfers in progress and the readiness that they wait for.
+type Transfers = Rc<RefCell<HashMap<RawFd, Interest>>>;
+
+/// A handle to the clipboards of all seats.
+#[derive(Clone)]
+pub struct ClipboardManager {
+    inner: Rc<RefCell<ClipboardManagerInner>>,
+}
+
+struct ClipboardManagerInner {
+    /// Clipboard devices of the currently observed seats.
+    devices: Vec<ClipboardDevice>,
+
+    /// Data device manager.
+    data_device_manager: Option<Attached<WlDataDeviceManager>>,
+
+    /// Primary selection device manager.
+    primary_selection_manager: Option<Attached<ZwpPrimarySelectionDeviceManagerV1>>,
+
+    /// Loop handle to read from and write to pipes.
+    loop_handle: LoopHandle<WinitState>,
+
+    /// The data that we currently offer.
+    offers: HashMap<ClipboardKind, Offer>,
+
+    /// The requests of each clipboard in the order in which they were made. Their results are
+    /// reported in this order.
+    requests: HashMap<ClipboardKind, VecDeque<Request>>,
+
+    /// The pipes that we are reading from or writing to.
+    transfers: Transfers,
+}
+
+/// The clipboard devices of a single seat.
+struct ClipboardDevice {
+    seat: WlSeat,
+
+    /// Serial of the latest keyboard or pointer button event on the seat.
+    input_serial: Rc<Cell<u32>>,
+
+    data_device: Option<DataDevice>,
+
+    primary_selection_device: Option<PrimarySelectionDevice>,
+}
+
+impl ClipboardManager {
+    pub fn new(env: &Environment<WinitEnv>, loop_handle: LoopHandle<WinitState>) -> Self {
+        let inner = ClipboardManagerInner {
+            devices: Vec::new(),
+            data_device_manager: env.get_global::<WlDataDeviceManager>(),
+            primary_selection_manager: env.get_global::<ZwpPrimarySelectionDeviceManagerV1>(),
+            loop_handle,
+            offers: HashMap::new(),
+            requests: HashMap::new(),
+            transfers: Default::default(),
+        };
+
+        Self {
+            inner: Rc::new(RefCell::new(inner)),
+        }
+    }
+
+    /// Create clipboard devices for a new seat.
+    pub fn add_seat(&self, seat: &Attached<WlSeat>, input_serial: Rc<Cell<u32>>) {
+        let mut inner = self.inner.borrow_mut();
+        let detached_seat = seat.detach();
+        if inner
+            .devices
+            .iter()
+            .any(|device| device.seat == detached_seat)
+        {
+            return;
+        }
+
+        // Drag and drop isn't handled through the data device yet.
+        let data_device = inner
+            .data_device_manager
+            .as_ref()
+            .map(|manager| DataDevice::init_for_seat(manager, seat, |_, _| {}));
+        let primary_selection_device = inner.primary_selection_manager.as_ref().map(|manager| {
+            let manager = PrimarySelectionDeviceManager::Zwp(manager.clone());
+            PrimarySelectionDevice::init_for_seat(&manager, seat)
+        });
+
+        inner.devices.push(ClipboardDevice {
+            seat: detached_seat,
+            input_serial,
+            data_device,
+            primary_selection_device,
+        });
+    }
+
+    /// Destroy the clipboard devices of a removed seat.
+    pub fn remove_seat(&self, seat: &WlSeat) {
+        self.inner
+            .borrow_mut()
+            .devices
+            .retain(|device| device.seat != *seat);
+    }
+
+    /// Take ownership of the clipboard and offer `data`.
+    pub fn set_data(
+        &self,
+        kind: ClipboardKind,
+        data: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), ExternalError> {
+        let mut offer = Vec::with_capacity(data.len());
+        for (mime_type, bytes) in data {
+            let bytes: Rc<[u8]> = bytes.into();
+            if mime_type == TEXT_MIME_TYPE {
+                for alias in TEXT_MIME_TYPE_ALIASES {
+                    offer.push((alias.to_string(), bytes.clone()));
+                }
+            }
+            offer.push((mime_type, bytes));
+        }
+        let offer = Rc::new(offer);
+        let mime_types: Vec<String> = offer
+            .iter()
+            .map(|(mime_type, _)| mime_type.clone())
+            .collect();
+
+        let mut inner = self.inner.borrow_mut();
+        let (device, serial) = inner.active_device()?;
+        let weak_inner = Rc::downgrade(&self.inner);
+        let loop_handle = inner.loop_handle.clone();
+        let transfers = inner.transfers.clone();
+        match kind {
+            ClipboardKind::Clipboard => {
+                let (manager, data_device) = match (&inner.data_device_manager, &device.data_device)
+                {
+                    (Some(manager), Some(data_device)) => (manager, data_device),
+                    _ => return Err(ExternalError::NotSupported(NotSupportedError::new())),
+                };
+                let source_offer = offer.clone();
+                let source = DataSource::new(
+                    manager,
+                    mime_types,
+                    move |event, mut dispatch_data| match event {
+                        DataSourceEvent::Send { mime_type, pipe } => {
+                            send_data(&loop_handle, &transfers, &source_offer, &mime_type, pipe)
+                        }
+                        DataSourceEvent::Cancelled => {
+                            let winit_state = dispatch_data.get::<WinitState>().unwrap();
+                            ownership_lost(&weak_inner, kind, &source_offer, winit_state);
+                        }
+                        _ => (),
+                    },
+                );
+                data_device.set_selection(&Some(source), serial);
+            }
+            ClipboardKind::Primary => {
+                let (manager, primary_selection_device) = match (
+                    &inner.primary_selection_manager,
+                    &device.primary_selection_device,
+                ) {
+                    (Some(manager), Some(device)) => {
+                        (PrimarySelectionDeviceManager::Zwp(manager.clone()), device)
+                    }
+                    _ => return Err(ExternalError::NotSupported(NotSupportedError::new())),
+                };
+                let source_offer = offer.clone();
+                let source = PrimarySelectionSource::new(
+                    &manager,
+                    mime_types,
+                    move |event, mut dispatch_data| match event {
+                        PrimarySelectionSourceEvent::Send { mime_type, pipe } => {
+                            send_data(&loop_handle, &transfers, &source_offer, &mime_type, pipe)
+                        }
+                        PrimarySelectionSourceEvent::Cancelled => {
+                            let winit_state = dispatch_data.get::<WinitState>().unwrap();
+                            ownership_lost(&weak_inner, kind, &source_offer, winit_state);
+                        }
+                    },
+                );
+                primary_selection_device.set_selection(&Some(source), serial);
+            }
+        }
+
+        inner.offers.insert(kind, offer);
+
+        Ok(())
+    }
+
+    /// Give up ownership of the clipboard if we own it.
+    pub fn clear(&self, kind: ClipboardKind) -> Result<(), ExternalError> {
+        let mut inner = self.inner.borrow_mut();
+        if inner.offers.remove(&kind).is_none() {
+            return Ok(());
+        }
+
+        let (device, serial) = inner.active_device()?;
+        match kind {
+            ClipboardKind::Clipboard => {
+                if let Some(data_device) = device.data_device.as_ref() {
+                    data_device.set_selection(&None, serial);
+                }
+            }
+            ClipboardKind::Primary => {
+                if let Some(device) = device.primary_selection_device.as_ref() {
+                    device.set_selection(&None, serial);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Start reading the contents of the clipboard.
+    ///
+    /// Returns the results of the requests that have completed. Later results are pushed to the
+    /// event sink.
+    pub fn request_data(&self, kind: ClipboardKind, mime_type: &str) -> Vec<ClipboardEvent> {
+        let mut inner = self.inner.borrow_mut();
+        let request = inner.push_request(kind);
+        let failed = || {
+            *request.borrow_mut() = Some(ClipboardEvent::Data {
+                kind,
+                mime_type: mime_type.to_owned(),
+                data: None,
+            });
+        };
+
+        let device = match inner.active_device() {
+            Ok((device, _)) => device,
+            Err(_) => {
+                failed();
+                return inner.take_completed(kind);
+            }
+        };
+        let pipe = match kind {
+            ClipboardKind::Clipboard => device.data_device.as_ref().and_then(|data_device| {
+                data_device.with_selection(|offer| offer?.receive(mime_type.to_owned()).ok())
+            }),
+            ClipboardKind::Primary => device.primary_selection_device.as_ref().and_then(|device| {
+                device.with_selection(|offer| offer?.receive(mime_type.to_owned()).ok())
+            }),
+        };
+        let pipe = match pipe {
+            Some(pipe) => pipe,
+            None => {
+                failed();
+                return inner.take_completed(kind);
+            }
+        };
+
+        let file = unsafe { File::from_raw_fd(pipe.into_raw_fd()) };
+        if let Err(err) = set_nonblocking(file.as_raw_fd()) {
+            log::warn!("Failed to make clipboard pipe non-blocking: {}", err);
+            failed();
+            return inner.take_completed(kind);
+        }
+
+        let fd = file.as_raw_fd();
+        let source: Rc<RefCell<Option<Source<Generic<File>>>>> = Default::default();
+        let source_clone = source.clone();
+        let loop_handle = inner.loop_handle.clone();
+        let weak_inner = Rc::downgrade(&self.inner);
+        let request_clone = request.clone();
+        let transfers = inner.transfers.clone();
+        let mime_type_clone = mime_type.to_owned();
+        let mut data = Vec::new();
+        let inserted = inner.loop_handle.insert_source(
+            Generic::new(file, Interest::Readable, Mode::Level),
+            move |_, file, winit_state| {
+                let mut buf = [0; 4096];
+                let data = loop {
+                    match file.read(&mut buf) {
+                        Ok(0) => break Some(mem::take(&mut data)),
+                        Ok(n) => data.extend_from_slice(&buf[..n]),
+                        Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                        Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+                        Err(err) => {
+                            log::warn!("Failed to read clipboard data: {}", err);
+                            break None;
+                        }
+                    }
+                };
+
+                *request_clone.borrow_mut() = Some(ClipboardEvent::Data {
+                    kind,
+                    mime_type: mime_type_clone.clone(),
+                    data,
+                });
+                if let Some(inner) = weak_inner.upgrade() {
+                    for event in inner.borrow_mut().take_completed(kind) {
+                        winit_state.event_sink.push_clipboard_event(event);
+                    }
+                }
+
+                transfers.borrow_mut().remove(&fd);
+                if let Some(source) = source_clone.borrow_mut().take() {
+                    loop_handle.kill(source);
+                }
+
+                Ok(())
+            },
+        );
+
+        match inserted {
+            Ok(inserted) => {
+                *source.borrow_mut() = Some(inserted);
+                inner.transfers.borrow_mut().insert(fd, Interest::Readable);
+            }
+            Err(err) => {
+                log::warn!("Failed to read clipboard data: {}", err.error);
+                failed();
+            }
+        }
+        inner.take_completed(kind)
+    }
+
+    /// Get the pipes of the transfers in progress and the readiness that they wait for.
+    #[cfg(feature = "stream")]
+    pub fn transfer_fds(&self) -> Vec<(RawFd, Interest)> {
+        let inner = self.inner.borrow();
+        let transfers = inner.transfers.borrow();
+        transfers
+            .iter()
+            .map(|(&fd, &interest)| (fd, interest))
+            .collect()
+    }
+
+    /// Get the MIME types of the current clipboard contents.
+    ///
+    /// Returns the results of the requests that have completed. The MIME types are known right
+    /// away, but they are reported after the results of earlier requests.
+    pub fn mime_types(&self, kind: ClipboardKind) -> Vec<ClipboardEvent> {
+        let mut inner = self.inner.borrow_mut();
+        let mime_types = inner
+            .active_device()
+            .ok()
+            .and_then(|(device, _)| match kind {
+                ClipboardKind::Clipboard => device.data_device.as_ref().and_then(|data_device| {
+                    data_device.with_selection(|offer| {
+                        Some(offer?.with_mime_types(|mime_types| mime_types.to_vec()))
+                    })
+                }),
+                ClipboardKind::Primary => {
+                    device.primary_selection_device.as_ref().and_then(|device| {
+                        device.with_selection(|offer| {
+                            Some(offer?.with_mime_types(|mime_types| mime_types.to_vec()))
+                        })
+                    })
+                }
+            })
+            .unwrap_or_default();
+
+        let request = inner.push_request(kind);
+        *request.borrow_mut() = Some(ClipboardEvent::MimeTypes { kind, mime_types });
+        inner.take_completed(kind)
+    }
+}
+
+impl ClipboardManagerInner {
+    /// Queue a new request of the clipboard.
+    fn push_request(&mut self, kind: ClipboardKind) -> Request {
+        let request = Request::default();
+        self.requests
+            .entry(kind)
+            .or_default()
+            .push_back(request.clone());
+        request
+    }
+
+    /// Remove the completed requests at the front of the queue of the clipboard and return
+    /// their results.
+    fn take_completed(&mut self, kind: ClipboardKind) -> Vec<ClipboardEvent> {
+        let mut events = Vec::new();
+        if let Some(requests) = self.requests.get_mut(&kind) {
+            while let Some(event) = requests.front().and_then(|r| r.borrow_mut().take()) {
+                requests.pop_front();
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    /// The device of the seat with the latest user interaction, together with the serial of
+    /// that interaction.
+    fn active_device(&self) -> Result<(&ClipboardDevice, u32), ExternalError> {
+        self.devices
+            .iter()
+            .map(|device| (device, device.input_serial.get()))
+            .max_by_key(|&(_, serial)| serial)
+            .ok_or_else(|| {
+                ExternalError::Os(os_error!(OsError::WaylandMisc(
+                    "no seat is available for clipboard access"
+                )))
+            })
+    }
+}
+
+/// Report that a source was cancelled unless it has been replaced by us.
+fn ownership_lost(
+    inner: &Weak<RefCell<ClipboardManagerInner>>,
+    kind: ClipboardKind,
+    offer: &Offer,
+    winit_state: &mut WinitState,
+) {
+    let inner = match inner.upgrade() {
+        Some(inner) => inner,
+        None => return,
+    };
+    let mut inner = inner.borrow_mut();
+    match inner.offers.get(&kind) {
+        Some(current) if Rc::ptr_eq(current, offer) => {}
+        _ => return,
+    }
+    inner.offers.remove(&kind);
+    winit_state
+        .event_sink
+        .push_clipboard_event(ClipboardEvent::OwnershipLost { kind });
+}
+
+/// Write the data offered under `mime_type` into the pipe without blocking the event loop.
+fn send_data(
+    loop_handle: &LoopHandle<WinitState>,
+    transfers: &Transfers,
+    offer: &Offer,
+    mime_type: &str,
+    pipe: WritePipe,
+) {
+    let data = match offer.iter().find(|(m, _)| m == mime_type) {
+        Some((_, data)) => data.clone(),
+        // Dropping the pipe signals the end of the data to the receiver.
+        None => return,
+    };
+
+    let file = unsafe { File::from_raw_fd(pipe.into_raw_fd()) };
+    if let Err(err) = set_nonblocking(file.as_raw_fd()) {
+        log::warn!("Failed to make clipboard pipe non-blocking: {}", err);
+        return;
+    }
+
+    let fd = file.as_raw_fd();
+    let source: Rc<RefCell<Option<Source<Generic<File>>>>> = Default::default();
+    let source_clone = source.clone();
+    let loop_handle_clone = loop_handle.clone();
+    let transfers_clone = transfers.clone();
+    let mut offset = 0;
+    let inserted = loop_handle.insert_source(
+        Generic::new(file, Interest::Writable, Mode::Level),
+        move |_, file, _| {
+            while offset < data.len() {
+                match file.write(&data[offset..]) {
+                    Ok(n) => offset += n,
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+                    Err(err) => {
+                        log::warn!("Failed to write clipboard data: {}", err);
+                        break;
+                    }
+                }
+            }
+
+            // Killing the source closes the pipe.
+            transfers_clone.borrow_mut().remove(&fd);
+            if let Some(source) = source_clone.borrow_mut().take() {
+                loop_handle_clone.kill(source);
+            }
+
+            Ok(())
+        },
+    );
+
+    match inserted {
+        Ok(inserted) => {
+            *source.borrow_mut() = Some(inserted);
+            transfers.borrow_mut().insert(fd, Interest::Writable);
+        }
+        Err(err) => log::warn!("Failed to write clipboard data: {}", err.error),
+    }
+}
+
+fn set_nonblocking(fd: RawFd) -> io::Result<()> {
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFL);
+        if flags == -1 || libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) == -1 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+impl<T> EventLoopWindowTarget<T> {
+    pub fn set_clipboard_data(
+        &self,
+        kind: ClipboardKind,
+        data: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), ExternalError> {
+        self.clipboard_manager.set_data(kind, data)
+    }
+
+    pub fn clear_clipboard(&self, kind: ClipboardKind) -> Result<(), ExternalError> {
+        self.clipboard_manager.clear(kind)
+    }
+
+    pub fn request_clipboard_data(&self, kind: ClipboardKind, mime_type: &str) {
+        let events = self.clipboard_manager.request_data(kind, mime_type);
+        self.push_clipboard_events(events);
+    }
+
+    pub fn request_clipboard_mime_types(&self, kind: ClipboardKind) {
+        let events = self.clipboard_manager.mime_types(kind);
+        self.push_clipboard_events(events);
+    }
+
+    fn push_clipboard_events(&self, events: Vec<ClipboardEvent>) {
+        if events.is_empty() {
+            return;
+        }
+        let mut state = self.state.borrow_mut();
+        for event in events {
+            state.event_sink.push_clipboard_event(event);
+        }
+
+        // Make sure that the events are delivered even if the event loop is about to block.
+        self.event_loop_awakener.ping();
+    }
+}
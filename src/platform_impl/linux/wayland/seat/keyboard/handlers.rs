@@ -5,11 +5,11 @@ use sctk::reexports::client;
 
 use crate::keyboard::{Key, KeyLocation, ModifiersState};
 use crate::platform_impl::platform::common::xkb_state::{self, RMLVO};
-use crate::platform_impl::wayland::event_loop::WinitState;
+use crate::platform_impl::wayland::event_loop::{make_ping, Ping, PingReader, WinitState};
 use crate::platform_impl::wayland::{self, DeviceId};
 use crate::platform_impl::KeyEventExtra;
 use crate::{
-    event::{ElementState, KeyEvent, WindowEvent},
+    event::{ElementState, KeyEvent, Timestamp, WindowEvent},
     keyboard::KeyCode,
 };
 
@@ -23,8 +23,11 @@ pub(super) fn handle_keyboard(
 ) {
     let event_sink = &mut winit_state.event_sink;
     match event {
-        Event::Enter { surface, .. } => {
+        Event::Enter {
+            surface, serial, ..
+        } => {
             let window_id = wayland::make_wid(&surface);
+            inner.input_serial.set(serial);
 
             // Window gained focus.
             event_sink.push_window_event(WindowEvent::Focused(true), window_id);
@@ -62,8 +65,10 @@ pub(super) fn handle_keyboard(
             state,
             key_without_modifiers,
             text_with_all_modifiers,
-            ..
+            serial,
+            time,
         } => {
+            inner.input_serial.set(serial);
             let window_id = match inner.target_window_id {
                 Some(window_id) => window_id,
                 None => return,
@@ -87,6 +92,7 @@ pub(super) fn handle_keyboard(
                         },
                     },
                     is_synthetic: false,
+                    time: Some(Timestamp::from_millis(time)),
                 },
                 window_id,
             );
@@ -98,7 +104,7 @@ pub(super) fn handle_keyboard(
             location,
             key_without_modifiers,
             text_with_all_modifiers,
-            ..
+            time,
         } => {
             let window_id = match inner.target_window_id {
                 Some(window_id) => window_id,
@@ -123,6 +129,7 @@ pub(super) fn handle_keyboard(
                         },
                     },
                     is_synthetic: false,
+                    time: Some(Timestamp::from_millis(time)),
                 },
                 window_id,
             );
@@ -148,13 +155,13 @@ pub(super) fn handle_keyboard(
 // ! ====================================================================================================== !
 
 use std::num::NonZeroU32;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     convert::TryInto,
     fs::File,
     os::unix::io::{FromRawFd, RawFd},
-    rc::Rc,
+    rc::{Rc, Weak},
 };
 
 use sctk::reexports::client::{
@@ -260,6 +267,7 @@ pub enum Event<'a> {
 /// contained invalid values, or if the provided seat does not have keyboard capability.
 pub fn map_keyboard_repeat<F, Data: 'static>(
     loop_handle: calloop::LoopHandle<Data>,
+    timers: &RepeatTimers,
     seat: &Attached<wl_seat::WlSeat>,
     rmlvo: Option<RMLVO>,
     repeatkind: RepeatKind,
@@ -303,19 +311,25 @@ where
     let (mut kbd_handler, source) = {
         let current_repeat = Rc::new(RefCell::new(None));
 
+        let (ping, reader) = make_ping().map_err(Error::TimerError)?;
+        let timer = Rc::new(RepeatTimer {
+            deadline: Cell::new(None),
+            ping,
+        });
+        timers.add(&timer);
+
         let source = RepeatSource {
-            timer: calloop::timer::Timer::new().map_err(Error::TimerError)?,
+            reader,
+            timer: timer.clone(),
             state: state.clone(),
             current_repeat: current_repeat.clone(),
         };
 
-        let timer_handle = source.timer.handle();
-
         let handler = KbdHandler {
             callback: callback.clone(),
             state,
             repeat: Some(KbdRepeat {
-                timer_handle,
+                timer,
                 current_repeat,
                 details: repeat,
             }),
@@ -375,7 +389,7 @@ struct KbdHandler {
 }
 
 struct KbdRepeat {
-    timer_handle: calloop::timer::TimerHandle<()>,
+    timer: Rc<RepeatTimer>,
     current_repeat: Rc<RefCell<Option<RepeatData>>>,
     details: RepeatDetails,
 }
@@ -383,7 +397,7 @@ struct KbdRepeat {
 impl KbdRepeat {
     fn start_repeat(&self, key: u32, group: u32, keyboard: wl_keyboard::WlKeyboard, time: u32) {
         // Start a new repetition, overwriting the previous ones
-        self.timer_handle.cancel_all_timeouts();
+        self.timer.deadline.set(None);
 
         // Handle disabled repeat rate.
         let gap = match self.details.gap {
@@ -398,8 +412,9 @@ impl KbdRepeat {
             gap,
             time: (time + self.details.delay) as u64 * 1000,
         });
-        self.timer_handle
-            .add_timeout(Duration::from_micros(self.details.delay as u64 * 1000), ());
+        self.timer.deadline.set(Some(
+            Instant::now() + Duration::from_micros(self.details.delay as u64 * 1000),
+        ));
     }
 
     fn stop_repeat(&self, key: u32) {
@@ -407,13 +422,13 @@ impl KbdRepeat {
         let mut guard = self.current_repeat.borrow_mut();
         let stop = (*guard).as_ref().map(|d| d.keycode == key).unwrap_or(false);
         if stop {
-            self.timer_handle.cancel_all_timeouts();
+            self.timer.deadline.set(None);
             *guard = None;
         }
     }
 
     fn stop_all_repeat(&self) {
-        self.timer_handle.cancel_all_timeouts();
+        self.timer.deadline.set(None);
         *self.current_repeat.borrow_mut() = None;
     }
 }
@@ -644,6 +659,61 @@ struct RepeatData {
     time: u64,
 }
 
+/// The deadline of the next key repetition of a keyboard.
+///
+/// There is no timer thread: the event loop waits until the earliest deadline of all
+/// keyboards and then wakes up the `RepeatSource` through a pipe.
+pub struct RepeatTimer {
+    deadline: Cell<Option<Instant>>,
+    ping: Ping,
+}
+
+/// The repeat timers of all keyboards.
+#[derive(Clone, Default)]
+pub struct RepeatTimers {
+    timers: Rc<RefCell<Vec<Weak<RepeatTimer>>>>,
+}
+
+impl RepeatTimers {
+    fn add(&self, timer: &Rc<RepeatTimer>) {
+        let mut timers = self.timers.borrow_mut();
+        timers.retain(|timer| timer.strong_count() > 0);
+        timers.push(Rc::downgrade(timer));
+    }
+
+    fn for_each<F: FnMut(&RepeatTimer)>(&self, mut f: F) {
+        for timer in self.timers.borrow().iter().filter_map(Weak::upgrade) {
+            f(&timer);
+        }
+    }
+
+    /// Returns the earliest deadline of all keyboards.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let mut next = None;
+        self.for_each(|timer| {
+            if let Some(deadline) = timer.deadline.get() {
+                next = Some(next.map_or(deadline, |next: Instant| next.min(deadline)));
+            }
+        });
+        next
+    }
+
+    /// Wakes up the sources whose deadline has passed.
+    ///
+    /// Returns whether any source has been woken up.
+    pub fn wake_expired(&self, now: Instant) -> bool {
+        let mut woken = false;
+        self.for_each(|timer| match timer.deadline.get() {
+            Some(deadline) if deadline <= now => {
+                timer.ping.ping();
+                woken = true;
+            }
+            _ => {}
+        });
+        woken
+    }
+}
+
 /// An event source managing the key repetition of a keyboard
 ///
 /// It is given to you from [`map_keyboard`](fn.map_keyboard.html), and you need to
@@ -655,7 +725,8 @@ struct RepeatData {
 /// `EventLoopHandle::insert_source()` will be ignored. Instead it triggers the
 /// callback you provided to [`map_keyboard`](fn.map_keyboard.html).
 pub struct RepeatSource {
-    timer: calloop::timer::Timer<()>,
+    reader: PingReader,
+    timer: Rc<RepeatTimer>,
     state: Rc<RefCell<KbState>>,
     current_repeat: Rc<RefCell<Option<RepeatData>>>,
 }
@@ -667,50 +738,56 @@ impl calloop::EventSource for RepeatSource {
 
     fn process_events<F>(
         &mut self,
-        readiness: calloop::Readiness,
-        token: calloop::Token,
+        _: calloop::Readiness,
+        _: calloop::Token,
         mut callback: F,
     ) -> std::io::Result<()>
     where
         F: FnMut(Event<'static>, &mut wl_keyboard::WlKeyboard),
     {
-        let current_repeat = &self.current_repeat;
-        let state = &self.state;
-        self.timer
-            .process_events(readiness, token, |(), timer_handle| {
-                if let Some(ref mut data) = *current_repeat.borrow_mut() {
-                    // there is something to repeat
-                    let mut state = state.borrow_mut();
-                    let ker = state.process_key_event(
-                        data.keycode + 8,
-                        data.group,
-                        ElementState::Pressed,
-                    );
-
-                    let new_time = data.gap + data.time;
-                    // Notify the callback.
-                    callback(
-                        Event::Repeat {
-                            time: (new_time / 1000) as u32,
-                            physical_key: ker.keycode,
-                            logical_key: ker.key,
-                            text: ker.text,
-                            location: ker.location,
-                            key_without_modifiers: ker.key_without_modifiers,
-                            text_with_all_modifiers: ker.text_with_all_modifiers,
-                        },
-                        &mut data.keyboard,
-                    );
-                    // Update the time of last event.
-                    data.time = new_time;
-                    // Schedule the next timeout.
-                    timer_handle.add_timeout(Duration::from_micros(data.gap), ());
-                }
-            })
+        self.reader.drain();
+        match self.timer.deadline.get() {
+            Some(deadline) if deadline <= Instant::now() => {}
+            _ => return Ok(()),
+        }
+        self.timer.deadline.set(None);
+
+        if let Some(ref mut data) = *self.current_repeat.borrow_mut() {
+            // there is something to repeat
+            let mut state = self.state.borrow_mut();
+            let ker = state.process_key_event(data.keycode + 8, data.group, ElementState::Pressed);
+
+            let new_time = data.gap + data.time;
+            // Notify the callback.
+            callback(
+                Event::Repeat {
+                    time: (new_time / 1000) as u32,
+                    physical_key: ker.keycode,
+                    logical_key: ker.key,
+                    text: ker.text,
+                    location: ker.location,
+                    key_without_modifiers: ker.key_without_modifiers,
+                    text_with_all_modifiers: ker.text_with_all_modifiers,
+                },
+                &mut data.keyboard,
+            );
+            // Update the time of last event.
+            data.time = new_time;
+            // Schedule the next repetition.
+            self.timer
+                .deadline
+                .set(Some(Instant::now() + Duration::from_micros(data.gap)));
+        }
+        Ok(())
     }
 
     fn register(&mut self, poll: &mut calloop::Poll, token: calloop::Token) -> std::io::Result<()> {
-        self.timer.register(poll, token)
+        poll.register(
+            self.reader.fd(),
+            calloop::Interest::Readable,
+            calloop::Mode::Level,
+            token,
+        )
     }
 
     fn reregister(
@@ -718,10 +795,15 @@ impl calloop::EventSource for RepeatSource {
         poll: &mut calloop::Poll,
         token: calloop::Token,
     ) -> std::io::Result<()> {
-        self.timer.reregister(poll, token)
+        poll.reregister(
+            self.reader.fd(),
+            calloop::Interest::Readable,
+            calloop::Mode::Level,
+            token,
+        )
     }
 
     fn unregister(&mut self, poll: &mut calloop::Poll) -> std::io::Result<()> {
-        self.timer.unregister(poll)
+        poll.unregister(self.reader.fd())
     }
 }
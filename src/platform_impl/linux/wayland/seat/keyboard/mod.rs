@@ -1,6 +1,6 @@
 //! Wayland keyboard handling.
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use sctk::reexports::calloop::{LoopHandle, Source};
@@ -14,6 +14,8 @@ use crate::platform_impl::wayland::WindowId;
 
 mod handlers;
 
+pub use handlers::RepeatTimers;
+
 pub(crate) struct Keyboard {
     pub keyboard: WlKeyboard,
 
@@ -28,11 +30,14 @@ impl Keyboard {
     pub fn new(
         seat: &Attached<WlSeat>,
         loop_handle: LoopHandle<WinitState>,
+        repeat_timers: &RepeatTimers,
         modifiers_state: Rc<RefCell<ModifiersState>>,
+        input_serial: Rc<Cell<u32>>,
     ) -> Option<Self> {
-        let mut inner = KeyboardInner::new(modifiers_state);
+        let mut inner = KeyboardInner::new(modifiers_state, input_serial);
         let keyboard_data = handlers::map_keyboard_repeat(
             loop_handle.clone(),
+            repeat_timers,
             &seat,
             None,
             handlers::RepeatKind::System,
@@ -77,14 +82,18 @@ struct KeyboardInner {
 
     /// Current state of modifiers keys.
     modifiers_state: Rc<RefCell<ModifiersState>>,
+
+    /// The serial of the latest input event on the seat.
+    input_serial: Rc<Cell<u32>>,
 }
 
 impl KeyboardInner {
-    fn new(modifiers_state: Rc<RefCell<ModifiersState>>) -> Self {
+    fn new(modifiers_state: Rc<RefCell<ModifiersState>>, input_serial: Rc<Cell<u32>>) -> Self {
         Self {
             target_window_id: None,
             pending_modifers_state: None,
             modifiers_state,
+            input_serial,
         }
     }
 }
@@ -1,11 +1,13 @@
 //! Seat handling and managing.
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_relative_pointer_manager_v1::ZwpRelativePointerManagerV1;
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_pointer_constraints_v1::ZwpPointerConstraintsV1;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gestures_v1::ZwpPointerGesturesV1;
 use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3;
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_manager_v2::ZwpTabletManagerV2;
 
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::client::Attached;
@@ -19,13 +21,19 @@ use super::env::WinitEnv;
 use super::event_loop::WinitState;
 use crate::keyboard::ModifiersState;
 
+mod clipboard;
 mod keyboard;
 pub mod pointer;
+mod tablet;
 pub mod text_input;
 mod touch;
 
+pub use clipboard::ClipboardManager;
+pub use keyboard::RepeatTimers;
+
 use keyboard::Keyboard;
 use pointer::Pointers;
+use tablet::Tablet;
 use text_input::TextInput;
 use touch::Touch;
 
@@ -39,16 +47,24 @@ impl SeatManager {
         env: &Environment<WinitEnv>,
         loop_handle: LoopHandle<WinitState>,
         theme_manager: ThemeManager,
+        clipboard_manager: ClipboardManager,
+        repeat_timers: RepeatTimers,
     ) -> Self {
         let relative_pointer_manager = env.get_global::<ZwpRelativePointerManagerV1>();
         let pointer_constraints = env.get_global::<ZwpPointerConstraintsV1>();
+        let pointer_gestures = env.get_global::<ZwpPointerGesturesV1>();
         let text_input_manager = env.get_global::<ZwpTextInputManagerV3>();
+        let tablet_manager = env.get_global::<ZwpTabletManagerV2>();
 
         let mut inner = SeatManagerInner::new(
             theme_manager,
             relative_pointer_manager,
             pointer_constraints,
+            pointer_gestures,
             text_input_manager,
+            tablet_manager,
+            clipboard_manager,
+            repeat_timers,
             loop_handle,
         );
 
@@ -86,11 +102,23 @@ struct SeatManagerInner {
     /// Pointer constraints.
     pointer_constraints: Option<Attached<ZwpPointerConstraintsV1>>,
 
+    /// Pointer gestures.
+    pointer_gestures: Option<Attached<ZwpPointerGesturesV1>>,
+
     /// Text input manager.
     text_input_manager: Option<Attached<ZwpTextInputManagerV3>>,
 
+    /// Tablet manager.
+    tablet_manager: Option<Attached<ZwpTabletManagerV2>>,
+
     /// A theme manager.
     theme_manager: ThemeManager,
+
+    /// A clipboard manager.
+    clipboard_manager: ClipboardManager,
+
+    /// The timers of key repetition.
+    repeat_timers: RepeatTimers,
 }
 
 impl SeatManagerInner {
@@ -98,7 +126,11 @@ impl SeatManagerInner {
         theme_manager: ThemeManager,
         relative_pointer_manager: Option<Attached<ZwpRelativePointerManagerV1>>,
         pointer_constraints: Option<Attached<ZwpPointerConstraintsV1>>,
+        pointer_gestures: Option<Attached<ZwpPointerGesturesV1>>,
         text_input_manager: Option<Attached<ZwpTextInputManagerV3>>,
+        tablet_manager: Option<Attached<ZwpTabletManagerV2>>,
+        clipboard_manager: ClipboardManager,
+        repeat_timers: RepeatTimers,
         loop_handle: LoopHandle<WinitState>,
     ) -> Self {
         Self {
@@ -106,8 +138,12 @@ impl SeatManagerInner {
             loop_handle,
             relative_pointer_manager,
             pointer_constraints,
+            pointer_gestures,
             text_input_manager,
+            tablet_manager,
             theme_manager,
+            clipboard_manager,
+            repeat_timers,
         }
     }
 
@@ -131,7 +167,9 @@ impl SeatManagerInner {
                     &self.theme_manager,
                     &self.relative_pointer_manager,
                     &self.pointer_constraints,
+                    &self.pointer_gestures,
                     seat_info.modifiers_state.clone(),
+                    seat_info.input_serial.clone(),
                 ));
             }
         } else {
@@ -144,7 +182,9 @@ impl SeatManagerInner {
                 seat_info.keyboard = Keyboard::new(
                     &seat,
                     self.loop_handle.clone(),
+                    &self.repeat_timers,
                     seat_info.modifiers_state.clone(),
+                    seat_info.input_serial.clone(),
                 );
             }
         } else {
@@ -160,6 +200,19 @@ impl SeatManagerInner {
             seat_info.touch = None;
         }
 
+        // Handle drawing tablets.
+        if let Some(tablet_manager) = self.tablet_manager.as_ref() {
+            if seat_data.defunct {
+                seat_info.tablet = None;
+            } else if seat_info.tablet.is_none() {
+                seat_info.tablet = Some(Tablet::new(
+                    &seat,
+                    &tablet_manager,
+                    seat_info.modifiers_state.clone(),
+                ));
+            }
+        }
+
         // Handle text input.
         if let Some(text_input_manager) = self.text_input_manager.as_ref() {
             if seat_data.defunct {
@@ -168,6 +221,14 @@ impl SeatManagerInner {
                 seat_info.text_input = Some(TextInput::new(&seat, &text_input_manager));
             }
         }
+
+        // Handle clipboard.
+        if seat_data.defunct {
+            self.clipboard_manager.remove_seat(&seat_info.seat);
+        } else {
+            self.clipboard_manager
+                .add_seat(&seat, seat_info.input_serial.clone());
+        }
     }
 }
 
@@ -185,6 +246,9 @@ struct SeatInfo {
     /// Touch handling.
     touch: Option<Touch>,
 
+    /// Drawing tablet handling.
+    tablet: Option<Tablet>,
+
     /// Text input handling aka IME.
     text_input: Option<TextInput>,
 
@@ -192,6 +256,11 @@ struct SeatInfo {
     ///
     /// We keep modifiers state on a seat, since it's being used by pointer events as well.
     modifiers_state: Rc<RefCell<ModifiersState>>,
+
+    /// The serial of the latest keyboard or pointer button event.
+    ///
+    /// It's required to take ownership of the clipboard.
+    input_serial: Rc<Cell<u32>>,
 }
 
 impl SeatInfo {
@@ -201,8 +270,10 @@ impl SeatInfo {
             keyboard: None,
             pointer: None,
             touch: None,
+            tablet: None,
             text_input: None,
             modifiers_state: Rc::new(RefCell::new(ModifiersState::default())),
+            input_serial: Rc::new(Cell::new(0)),
         }
     }
 }
@@ -6,11 +6,12 @@ use std::rc::Rc;
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
 use sctk::reexports::client::Attached;
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_pointer_constraints_v1::{ZwpPointerConstraintsV1};
-use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_confined_pointer_v1::ZwpConfinedPointerV1;
 
-use crate::event::TouchPhase;
+use crate::event::{ScrollSource, TouchPhase};
 use crate::keyboard::ModifiersState;
 
+use super::PointerConstraint;
+
 /// A data being used by pointer handlers.
 pub(super) struct PointerData {
     /// Winit's surface the pointer is currently over.
@@ -25,25 +26,31 @@ pub(super) struct PointerData {
     /// Pointer constraints.
     pub pointer_constraints: Option<Attached<ZwpPointerConstraintsV1>>,
 
-    pub confined_pointer: Rc<RefCell<Option<ZwpConfinedPointerV1>>>,
+    /// The active confinement or lock of the pointer.
+    pub pointer_constraint: Rc<RefCell<Option<PointerConstraint>>>,
 
     /// A latest event serial.
     pub latest_serial: Rc<Cell<u32>>,
 
+    /// The serial of the latest input event on the seat.
+    pub input_serial: Rc<Cell<u32>>,
+
     /// The currently accumulated axis data on a pointer.
     pub axis_data: AxisData,
 }
 
 impl PointerData {
     pub fn new(
-        confined_pointer: Rc<RefCell<Option<ZwpConfinedPointerV1>>>,
+        pointer_constraint: Rc<RefCell<Option<PointerConstraint>>>,
         pointer_constraints: Option<Attached<ZwpPointerConstraintsV1>>,
         modifiers_state: Rc<RefCell<ModifiersState>>,
+        input_serial: Rc<Cell<u32>>,
     ) -> Self {
         Self {
             surface: None,
             latest_serial: Rc::new(Cell::new(0)),
-            confined_pointer,
+            input_serial,
+            pointer_constraint,
             modifiers_state,
             pointer_constraints,
             axis_data: AxisData::new(),
@@ -62,6 +69,15 @@ pub(super) struct AxisData {
 
     /// A buffer for `LineDelta` event.
     pub axis_discrete_buffer: Option<(f32, f32)>,
+
+    /// The source of the latest axis events.
+    pub source: ScrollSource,
+
+    /// Whether the current frame contains an `axis_stop` event.
+    pub axis_stopped: bool,
+
+    /// The time of the latest `axis` or `axis_stop` event of the current frame.
+    pub axis_time: Option<u32>,
 }
 
 impl AxisData {
@@ -70,6 +86,31 @@ impl AxisData {
             axis_state: TouchPhase::Ended,
             axis_buffer: None,
             axis_discrete_buffer: None,
+            source: ScrollSource::Wheel,
+            axis_stopped: false,
+            axis_time: None,
+        }
+    }
+}
+
+/// Data of the touchpad gesture that is currently performed.
+pub(super) struct GestureData {
+    /// Winit's surface the gesture started on.
+    pub surface: Option<WlSurface>,
+
+    /// The scale of the current pinch gesture relative to its start.
+    pub pinch_scale: f64,
+
+    /// The number of fingers of the current swipe gesture.
+    pub swipe_fingers: u32,
+}
+
+impl GestureData {
+    pub fn new() -> Self {
+        Self {
+            surface: None,
+            pinch_scale: 1.0,
+            swipe_fingers: 0,
         }
     }
 }
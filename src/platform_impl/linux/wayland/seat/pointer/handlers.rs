@@ -1,22 +1,26 @@
 //! Handlers for the pointers we're using.
 
 use std::cell::RefCell;
+use std::mem;
 use std::rc::Rc;
 
 use sctk::reexports::client::protocol::wl_pointer::{self, Event as PointerEvent};
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_relative_pointer_v1::Event as RelativePointerEvent;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gesture_pinch_v1::Event as PinchEvent;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gesture_swipe_v1::Event as SwipeEvent;
 
 use sctk::seat::pointer::ThemedPointer;
 
-use crate::dpi::LogicalPosition;
+use crate::dpi::{LogicalPosition, PhysicalPosition};
 use crate::event::{
-    DeviceEvent, ElementState, MouseButton, MouseScrollDelta, TouchPhase, WindowEvent,
+    DeviceEvent, ElementState, MouseButton, MouseScrollDelta, ScrollSource, Timestamp, TouchPhase,
+    WindowEvent,
 };
 use crate::platform_impl::wayland::event_loop::WinitState;
 use crate::platform_impl::wayland::{self, DeviceId};
 
-use super::{PointerData, WinitPointer};
+use super::{GestureData, PointerData, WinitPointer};
 
 // These values are comming from <linux/input-event-codes.h>.
 const BTN_LEFT: u32 = 0x110;
@@ -58,7 +62,7 @@ pub(super) fn handle_pointer(
             // Notify window that pointer entered the surface.
             let winit_pointer = WinitPointer {
                 pointer,
-                confined_pointer: Rc::downgrade(&pointer_data.confined_pointer),
+                pointer_constraint: Rc::downgrade(&pointer_data.pointer_constraint),
                 pointer_constraints: pointer_data.pointer_constraints.clone(),
                 latest_serial: pointer_data.latest_serial.clone(),
                 seat,
@@ -82,6 +86,7 @@ pub(super) fn handle_pointer(
                         DeviceId,
                     )),
                     position,
+                    time: None,
                     modifiers: *pointer_data.modifiers_state.borrow(),
                 },
                 window_id,
@@ -101,7 +106,7 @@ pub(super) fn handle_pointer(
             // Notify a window that pointer is no longer observing it.
             let winit_pointer = WinitPointer {
                 pointer,
-                confined_pointer: Rc::downgrade(&pointer_data.confined_pointer),
+                pointer_constraint: Rc::downgrade(&pointer_data.pointer_constraint),
                 pointer_constraints: pointer_data.pointer_constraints.clone(),
                 latest_serial: pointer_data.latest_serial.clone(),
                 seat,
@@ -118,9 +123,9 @@ pub(super) fn handle_pointer(
             );
         }
         PointerEvent::Motion {
+            time,
             surface_x,
             surface_y,
-            ..
         } => {
             let surface = match pointer_data.surface.as_ref() {
                 Some(surface) => surface,
@@ -138,18 +143,20 @@ pub(super) fn handle_pointer(
                         DeviceId,
                     )),
                     position,
+                    time: Some(Timestamp::from_millis(time)),
                     modifiers: *pointer_data.modifiers_state.borrow(),
                 },
                 window_id,
             );
         }
         PointerEvent::Button {
+            serial,
+            time,
             button,
             state,
-            serial,
-            ..
         } => {
             pointer_data.latest_serial.replace(serial);
+            pointer_data.input_serial.replace(serial);
             let window_id = match pointer_data.surface.as_ref().map(wayland::make_wid) {
                 Some(window_id) => window_id,
                 None => return,
@@ -175,12 +182,13 @@ pub(super) fn handle_pointer(
                     )),
                     state,
                     button,
+                    time: Some(Timestamp::from_millis(time)),
                     modifiers: *pointer_data.modifiers_state.borrow(),
                 },
                 window_id,
             );
         }
-        PointerEvent::Axis { axis, value, .. } => {
+        PointerEvent::Axis { time, axis, value } => {
             let surface = match pointer_data.surface.as_ref() {
                 Some(surface) => surface,
                 None => return,
@@ -209,6 +217,8 @@ pub(super) fn handle_pointer(
                         )),
                         delta: MouseScrollDelta::PixelDelta(delta),
                         phase: TouchPhase::Moved,
+                        source: pointer_data.axis_data.source,
+                        time: Some(Timestamp::from_millis(time)),
                         modifiers: *pointer_data.modifiers_state.borrow(),
                     },
                     window_id,
@@ -223,6 +233,7 @@ pub(super) fn handle_pointer(
                 }
 
                 pointer_data.axis_data.axis_buffer = Some((x, y));
+                pointer_data.axis_data.axis_time = Some(time);
 
                 pointer_data.axis_data.axis_state = match pointer_data.axis_data.axis_state {
                     TouchPhase::Started | TouchPhase::Moved => TouchPhase::Moved,
@@ -250,13 +261,24 @@ pub(super) fn handle_pointer(
                 _ => TouchPhase::Started,
             }
         }
-        PointerEvent::AxisSource { .. } => (),
-        PointerEvent::AxisStop { .. } => {
+        PointerEvent::AxisSource { axis_source } => {
+            pointer_data.axis_data.source = match axis_source {
+                wl_pointer::AxisSource::Finger => ScrollSource::Finger,
+                wl_pointer::AxisSource::Continuous => ScrollSource::Continuous,
+                wl_pointer::AxisSource::WheelTilt => ScrollSource::WheelTilt,
+                _ => ScrollSource::Wheel,
+            };
+        }
+        PointerEvent::AxisStop { time, .. } => {
             pointer_data.axis_data.axis_state = TouchPhase::Ended;
+            pointer_data.axis_data.axis_stopped = true;
+            pointer_data.axis_data.axis_time = Some(time);
         }
         PointerEvent::Frame => {
             let axis_buffer = pointer_data.axis_data.axis_buffer.take();
             let axis_discrete_buffer = pointer_data.axis_data.axis_discrete_buffer.take();
+            let axis_stopped = mem::replace(&mut pointer_data.axis_data.axis_stopped, false);
+            let time = pointer_data.axis_data.axis_time.take();
 
             let surface = match pointer_data.surface.as_ref() {
                 Some(surface) => surface,
@@ -264,32 +286,44 @@ pub(super) fn handle_pointer(
             };
             let window_id = wayland::make_wid(&surface);
 
-            let window_event = if let Some((x, y)) = axis_discrete_buffer {
-                WindowEvent::MouseWheel {
-                    device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                        DeviceId,
-                    )),
-                    delta: MouseScrollDelta::LineDelta(x, y),
-                    phase: pointer_data.axis_data.axis_state,
-                    modifiers: *pointer_data.modifiers_state.borrow(),
-                }
+            let delta = if let Some((x, y)) = axis_discrete_buffer {
+                MouseScrollDelta::LineDelta(x, y)
             } else if let Some((x, y)) = axis_buffer {
                 let scale_factor = sctk::get_surface_scale_factor(&surface) as f64;
                 let delta = LogicalPosition::new(x, y).to_physical(scale_factor);
+                MouseScrollDelta::PixelDelta(delta)
+            } else if axis_stopped {
+                MouseScrollDelta::PixelDelta(PhysicalPosition::new(0.0, 0.0))
+            } else {
+                return;
+            };
 
+            // Only fingers and continuous sources send `axis_stop`. Wheel events are not part of
+            // a scroll sequence.
+            let source = pointer_data.axis_data.source;
+            let phase = match source {
+                ScrollSource::Finger | ScrollSource::Continuous => {
+                    pointer_data.axis_data.axis_state
+                }
+                _ => {
+                    pointer_data.axis_data.axis_state = TouchPhase::Ended;
+                    TouchPhase::Moved
+                }
+            };
+
+            event_sink.push_window_event(
                 WindowEvent::MouseWheel {
                     device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
                         DeviceId,
                     )),
-                    delta: MouseScrollDelta::PixelDelta(delta),
-                    phase: pointer_data.axis_data.axis_state,
+                    delta,
+                    phase,
+                    source,
+                    time: time.map(Timestamp::from_millis),
                     modifiers: *pointer_data.modifiers_state.borrow(),
-                }
-            } else {
-                return;
-            };
-
-            event_sink.push_window_event(window_event, window_id);
+                },
+                window_id,
+            );
         }
         _ => (),
     }
@@ -297,9 +331,127 @@ pub(super) fn handle_pointer(
 
 #[inline]
 pub(super) fn handle_relative_pointer(event: RelativePointerEvent, winit_state: &mut WinitState) {
-    if let RelativePointerEvent::RelativeMotion { dx, dy, .. } = event {
-        winit_state
-            .event_sink
-            .push_device_event(DeviceEvent::MouseMotion { delta: (dx, dy) }, DeviceId)
+    if let RelativePointerEvent::RelativeMotion {
+        utime_hi,
+        utime_lo,
+        dx,
+        dy,
+        ..
+    } = event
+    {
+        // The timestamp has microsecond granularity but uses the same clock as the
+        // millisecond timestamps of the other events.
+        let utime = (utime_hi as u64) << 32 | utime_lo as u64;
+        winit_state.event_sink.push_device_event(
+            DeviceEvent::MouseMotion { delta: (dx, dy) },
+            DeviceId,
+            Some(Timestamp::from_millis((utime / 1000) as u32)),
+        )
+    }
+}
+
+#[inline]
+pub(super) fn handle_pinch_gesture(
+    event: PinchEvent,
+    gesture_data: &mut GestureData,
+    winit_state: &mut WinitState,
+) {
+    let (delta, rotation, phase) = match event {
+        PinchEvent::Begin { surface, .. } => {
+            gesture_data.surface = Some(surface);
+            gesture_data.pinch_scale = 1.0;
+            (0.0, 0.0, TouchPhase::Started)
+        }
+        PinchEvent::Update {
+            scale, rotation, ..
+        } => {
+            // The scale is relative to the start of the gesture.
+            let delta = scale - gesture_data.pinch_scale;
+            gesture_data.pinch_scale = scale;
+            (delta, rotation, TouchPhase::Moved)
+        }
+        PinchEvent::End { cancelled, .. } => {
+            let phase = match cancelled {
+                0 => TouchPhase::Ended,
+                _ => TouchPhase::Cancelled,
+            };
+            (0.0, 0.0, phase)
+        }
+        _ => return,
+    };
+
+    let window_id = match gesture_data.surface.as_ref() {
+        Some(surface) => wayland::make_wid(surface),
+        None => return,
+    };
+    if matches!(phase, TouchPhase::Ended | TouchPhase::Cancelled) {
+        gesture_data.surface = None;
     }
+
+    let device_id = crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(DeviceId));
+    let event_sink = &mut winit_state.event_sink;
+    event_sink.push_window_event(
+        WindowEvent::PinchGesture {
+            device_id,
+            delta,
+            phase,
+        },
+        window_id,
+    );
+    // Wayland reports clockwise rotation as positive.
+    event_sink.push_window_event(
+        WindowEvent::RotationGesture {
+            device_id,
+            delta: -rotation as f32,
+            phase,
+        },
+        window_id,
+    );
+}
+
+#[inline]
+pub(super) fn handle_swipe_gesture(
+    event: SwipeEvent,
+    gesture_data: &mut GestureData,
+    winit_state: &mut WinitState,
+) {
+    let (delta, phase) = match event {
+        SwipeEvent::Begin {
+            surface, fingers, ..
+        } => {
+            gesture_data.surface = Some(surface);
+            gesture_data.swipe_fingers = fingers;
+            ((0.0, 0.0), TouchPhase::Started)
+        }
+        SwipeEvent::Update { dx, dy, .. } => ((dx, dy), TouchPhase::Moved),
+        SwipeEvent::End { cancelled, .. } => {
+            let phase = match cancelled {
+                0 => TouchPhase::Ended,
+                _ => TouchPhase::Cancelled,
+            };
+            ((0.0, 0.0), phase)
+        }
+        _ => return,
+    };
+
+    let surface = match gesture_data.surface.as_ref() {
+        Some(surface) => surface,
+        None => return,
+    };
+    let window_id = wayland::make_wid(surface);
+    let scale_factor = sctk::get_surface_scale_factor(surface) as f64;
+    let delta = LogicalPosition::new(delta.0, delta.1).to_physical(scale_factor);
+    if matches!(phase, TouchPhase::Ended | TouchPhase::Cancelled) {
+        gesture_data.surface = None;
+    }
+
+    winit_state.event_sink.push_window_event(
+        WindowEvent::SwipeGesture {
+            device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(DeviceId)),
+            delta,
+            fingers: gesture_data.swipe_fingers,
+            phase,
+        },
+        window_id,
+    );
 }
@@ -11,18 +11,22 @@ use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_rela
 use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_relative_pointer_v1::ZwpRelativePointerV1;
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_pointer_constraints_v1::{ZwpPointerConstraintsV1, Lifetime};
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_confined_pointer_v1::ZwpConfinedPointerV1;
+use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_locked_pointer_v1::ZwpLockedPointerV1;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gestures_v1::ZwpPointerGesturesV1;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gesture_swipe_v1::ZwpPointerGestureSwipeV1;
 
 use sctk::seat::pointer::{ThemeManager, ThemedPointer};
 use sctk::window::{ConceptFrame, Window};
 
 use crate::keyboard::ModifiersState;
 use crate::platform_impl::wayland::event_loop::WinitState;
-use crate::window::CursorIcon;
+use crate::window::{CursorGrabMode, CursorIcon};
 
 mod data;
 mod handlers;
 
-use data::PointerData;
+use data::{GestureData, PointerData};
 
 /// A proxy to Wayland pointer, which serves requests from a `WindowHandle`.
 pub struct WinitPointer {
@@ -31,8 +35,8 @@ pub struct WinitPointer {
     /// Create confined pointers.
     pointer_constraints: Option<Attached<ZwpPointerConstraintsV1>>,
 
-    /// Cursor to handle confine requests.
-    confined_pointer: Weak<RefCell<Option<ZwpConfinedPointerV1>>>,
+    /// Cursor to handle confine and lock requests.
+    pointer_constraint: Weak<RefCell<Option<PointerConstraint>>>,
 
     /// Latest observed serial in pointer events.
     latest_serial: Rc<Cell<u32>>,
@@ -114,38 +118,50 @@ impl WinitPointer {
         }
     }
 
-    /// Confine the pointer to a surface.
-    pub fn confine(&self, surface: &WlSurface) {
+    /// Confine or lock the pointer to a surface.
+    ///
+    /// A previous constraint of the pointer is released. `CursorGrabMode::None` only releases
+    /// the previous constraint.
+    pub fn constrain(&self, surface: &WlSurface, mode: CursorGrabMode) {
+        self.unconstrain();
+
         let pointer_constraints = match &self.pointer_constraints {
             Some(pointer_constraints) => pointer_constraints,
             None => return,
         };
 
-        let confined_pointer = match self.confined_pointer.upgrade() {
-            Some(confined_pointer) => confined_pointer,
+        let pointer_constraint = match self.pointer_constraint.upgrade() {
+            Some(pointer_constraint) => pointer_constraint,
             // A pointer is gone.
             None => return,
         };
 
-        *confined_pointer.borrow_mut() = Some(init_confined_pointer(
-            &pointer_constraints,
-            &surface,
-            &*self.pointer,
-        ));
+        let pointer = &*self.pointer;
+        *pointer_constraint.borrow_mut() = match mode {
+            CursorGrabMode::None => None,
+            CursorGrabMode::Confined => {
+                let confined_pointer = init_confined_pointer(pointer_constraints, surface, pointer);
+                Some(PointerConstraint::Confined(confined_pointer))
+            }
+            CursorGrabMode::Locked => {
+                let locked_pointer = init_locked_pointer(pointer_constraints, surface, pointer);
+                Some(PointerConstraint::Locked(locked_pointer))
+            }
+        };
     }
 
-    /// Tries to unconfine the pointer if the current pointer is confined.
-    pub fn unconfine(&self) {
-        let confined_pointer = match self.confined_pointer.upgrade() {
-            Some(confined_pointer) => confined_pointer,
+    /// Tries to release the pointer if the current pointer is confined or locked.
+    pub fn unconstrain(&self) {
+        let pointer_constraint = match self.pointer_constraint.upgrade() {
+            Some(pointer_constraint) => pointer_constraint,
             // A pointer is gone.
             None => return,
         };
 
-        let mut confined_pointer = confined_pointer.borrow_mut();
+        let mut pointer_constraint = pointer_constraint.borrow_mut();
 
-        if let Some(confined_pointer) = confined_pointer.take() {
-            confined_pointer.destroy();
+        if let Some(pointer_constraint) = pointer_constraint.take() {
+            pointer_constraint.destroy();
         }
     }
 
@@ -162,8 +178,39 @@ pub(super) struct Pointers {
     /// A relative pointer handler.
     relative_pointer: Option<ZwpRelativePointerV1>,
 
-    /// Confined pointer.
-    confined_pointer: Rc<RefCell<Option<ZwpConfinedPointerV1>>>,
+    /// Confined or locked pointer.
+    pointer_constraint: Rc<RefCell<Option<PointerConstraint>>>,
+
+    /// Pinch and swipe gesture handlers.
+    pointer_gestures: Option<PointerGestures>,
+}
+
+/// A constraint of the pointer created by `zwp_pointer_constraints_v1`.
+pub(super) enum PointerConstraint {
+    Confined(ZwpConfinedPointerV1),
+    Locked(ZwpLockedPointerV1),
+}
+
+impl PointerConstraint {
+    fn destroy(self) {
+        match self {
+            PointerConstraint::Confined(confined_pointer) => confined_pointer.destroy(),
+            PointerConstraint::Locked(locked_pointer) => locked_pointer.destroy(),
+        }
+    }
+}
+
+/// The gesture objects created by `zwp_pointer_gestures_v1`.
+pub(super) struct PointerGestures {
+    pinch: ZwpPointerGesturePinchV1,
+    swipe: ZwpPointerGestureSwipeV1,
+}
+
+impl PointerGestures {
+    fn destroy(self) {
+        self.pinch.destroy();
+        self.swipe.destroy();
+    }
 }
 
 impl Pointers {
@@ -172,13 +219,16 @@ impl Pointers {
         theme_manager: &ThemeManager,
         relative_pointer_manager: &Option<Attached<ZwpRelativePointerManagerV1>>,
         pointer_constraints: &Option<Attached<ZwpPointerConstraintsV1>>,
+        pointer_gestures: &Option<Attached<ZwpPointerGesturesV1>>,
         modifiers_state: Rc<RefCell<ModifiersState>>,
+        input_serial: Rc<Cell<u32>>,
     ) -> Self {
-        let confined_pointer = Rc::new(RefCell::new(None));
+        let pointer_constraint = Rc::new(RefCell::new(None));
         let pointer_data = Rc::new(RefCell::new(PointerData::new(
-            confined_pointer.clone(),
+            pointer_constraint.clone(),
             pointer_constraints.clone(),
             modifiers_state,
+            input_serial,
         )));
         let pointer_seat = seat.detach();
         let pointer = theme_manager.theme_pointer_with_impl(
@@ -203,10 +253,16 @@ impl Pointers {
             None => None,
         };
 
+        // Setup pointer gestures if they're available.
+        let pointer_gestures = pointer_gestures
+            .as_ref()
+            .map(|pointer_gestures| init_pointer_gestures(pointer_gestures, &*pointer));
+
         Self {
             pointer,
             relative_pointer,
-            confined_pointer,
+            pointer_constraint,
+            pointer_gestures,
         }
     }
 }
@@ -218,9 +274,14 @@ impl Drop for Pointers {
             relative_pointer.destroy();
         }
 
-        // Drop confined pointer.
-        if let Some(confined_pointer) = self.confined_pointer.borrow_mut().take() {
-            confined_pointer.destroy();
+        // Drop pointer gestures.
+        if let Some(pointer_gestures) = self.pointer_gestures.take() {
+            pointer_gestures.destroy();
+        }
+
+        // Drop confined or locked pointer.
+        if let Some(pointer_constraint) = self.pointer_constraint.borrow_mut().take() {
+            pointer_constraint.destroy();
         }
 
         // Drop the pointer itself in case it's possible.
@@ -243,6 +304,32 @@ pub(super) fn init_relative_pointer(
     relative_pointer.detach()
 }
 
+pub(super) fn init_pointer_gestures(
+    pointer_gestures: &ZwpPointerGesturesV1,
+    pointer: &WlPointer,
+) -> PointerGestures {
+    // The surface and scale are shared since a pointer can only perform one gesture at a time.
+    let gesture_data = Rc::new(RefCell::new(GestureData::new()));
+
+    let pinch = pointer_gestures.get_pinch_gesture(pointer);
+    let pinch_data = gesture_data.clone();
+    pinch.quick_assign(move |_, event, mut dispatch_data| {
+        let winit_state = dispatch_data.get::<WinitState>().unwrap();
+        handlers::handle_pinch_gesture(event, &mut pinch_data.borrow_mut(), winit_state);
+    });
+
+    let swipe = pointer_gestures.get_swipe_gesture(pointer);
+    swipe.quick_assign(move |_, event, mut dispatch_data| {
+        let winit_state = dispatch_data.get::<WinitState>().unwrap();
+        handlers::handle_swipe_gesture(event, &mut gesture_data.borrow_mut(), winit_state);
+    });
+
+    PointerGestures {
+        pinch: pinch.detach(),
+        swipe: swipe.detach(),
+    }
+}
+
 pub(super) fn init_confined_pointer(
     pointer_constraints: &Attached<ZwpPointerConstraintsV1>,
     surface: &WlSurface,
@@ -255,3 +342,16 @@ pub(super) fn init_confined_pointer(
 
     confined_pointer.detach()
 }
+
+pub(super) fn init_locked_pointer(
+    pointer_constraints: &Attached<ZwpPointerConstraintsV1>,
+    surface: &WlSurface,
+    pointer: &WlPointer,
+) -> ZwpLockedPointerV1 {
+    let locked_pointer =
+        pointer_constraints.lock_pointer(surface, pointer, None, Lifetime::Persistent.to_raw());
+
+    locked_pointer.quick_assign(move |_, _, _| {});
+
+    locked_pointer.detach()
+}
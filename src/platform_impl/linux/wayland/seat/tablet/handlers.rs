@@ -0,0 +1,191 @@
+//! Handlers for tablet tool events.
+
+use std::mem;
+
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_tool_v2::{
+    ButtonState, Capability, Event as ToolEvent, Type, ZwpTabletToolV2,
+};
+
+use crate::dpi::LogicalPosition;
+use crate::event::{
+    ElementState, MouseButton, PenButton, PenPhase, PenTool, Timestamp, WindowEvent,
+};
+use crate::platform_impl::wayland::event_loop::WinitState;
+use crate::platform_impl::wayland::{self, DeviceId};
+
+use super::ToolInner;
+
+// These values are comming from <linux/input-event-codes.h>.
+const BTN_STYLUS: u32 = 0x14b;
+const BTN_STYLUS2: u32 = 0x14c;
+
+/// The maximum value of the pressure and distance axes.
+const AXIS_MAX: f64 = 65535.0;
+
+/// Handle ZwpTabletToolV2 events.
+#[inline]
+pub(super) fn handle_tool(
+    tool: &ZwpTabletToolV2,
+    event: ToolEvent,
+    inner: &mut ToolInner,
+    winit_state: &mut WinitState,
+) {
+    match event {
+        ToolEvent::Type { tool_type } => {
+            inner.tool = match tool_type {
+                Type::Eraser => PenTool::Eraser,
+                Type::Brush => PenTool::Brush,
+                Type::Pencil => PenTool::Pencil,
+                Type::Airbrush => PenTool::Airbrush,
+                Type::Mouse => PenTool::Mouse,
+                Type::Lens => PenTool::Lens,
+                _ => PenTool::Pen,
+            };
+        }
+        // Axes that the tool has start at zero and all others stay `None`.
+        ToolEvent::Capability { capability } => match capability {
+            Capability::Tilt => {
+                inner.state.tilt_x = Some(0.0);
+                inner.state.tilt_y = Some(0.0);
+            }
+            Capability::Pressure => inner.state.pressure = Some(0.0),
+            Capability::Distance => inner.state.distance = Some(0.0),
+            Capability::Rotation => inner.state.rotation = Some(0.0),
+            _ => (),
+        },
+        ToolEvent::ProximityIn { surface, .. } => {
+            let window_id = wayland::make_wid(&surface);
+            if !winit_state.window_map.contains_key(&window_id) {
+                return;
+            }
+            inner.surface = Some(surface);
+            inner.pending_phases.push(PenPhase::ProximityIn);
+        }
+        ToolEvent::ProximityOut => inner.pending_phases.push(PenPhase::ProximityOut),
+        ToolEvent::Down { .. } => inner.pending_phases.push(PenPhase::Down),
+        ToolEvent::Up => inner.pending_phases.push(PenPhase::Up),
+        ToolEvent::Button { button, state, .. } => {
+            let button = match button {
+                BTN_STYLUS => PenButton::Primary,
+                BTN_STYLUS2 => PenButton::Secondary,
+                button => PenButton::Other(button),
+            };
+            let state = match state {
+                ButtonState::Pressed => ElementState::Pressed,
+                ButtonState::Released => ElementState::Released,
+                _ => unreachable!(),
+            };
+            inner
+                .pending_phases
+                .push(PenPhase::Button { button, state });
+        }
+        ToolEvent::Motion { x, y } => {
+            inner.position = LogicalPosition::new(x, y);
+            inner.position_changed = true;
+        }
+        ToolEvent::Pressure { pressure } => {
+            inner.state.pressure = Some(pressure as f64 / AXIS_MAX);
+            inner.axes_changed = true;
+        }
+        ToolEvent::Distance { distance } => {
+            inner.state.distance = Some(distance as f64 / AXIS_MAX);
+            inner.axes_changed = true;
+        }
+        ToolEvent::Tilt { tilt_x, tilt_y } => {
+            inner.state.tilt_x = Some(tilt_x);
+            inner.state.tilt_y = Some(tilt_y);
+            inner.axes_changed = true;
+        }
+        ToolEvent::Rotation { degrees } => {
+            inner.state.rotation = Some(degrees.rem_euclid(360.0));
+            inner.axes_changed = true;
+        }
+        ToolEvent::Frame { time } => handle_frame(inner, winit_state, time),
+        ToolEvent::Removed => tool.destroy(),
+        _ => (),
+    }
+}
+
+/// Emits the events that were accumulated since the last frame.
+///
+/// Tablet tools do not move the pointer, so the mouse events that applications expect from a pen
+/// are emulated as well.
+fn handle_frame(inner: &mut ToolInner, winit_state: &mut WinitState, time: u32) {
+    let phases = mem::take(&mut inner.pending_phases);
+    let position_changed = mem::replace(&mut inner.position_changed, false);
+    let axes_changed = mem::replace(&mut inner.axes_changed, false);
+
+    let surface = match inner.surface.as_ref() {
+        Some(surface) => surface,
+        None => return,
+    };
+    let window_id = wayland::make_wid(surface);
+    let scale_factor = sctk::get_surface_scale_factor(surface) as f64;
+    let location = inner.position.to_physical(scale_factor);
+    let device_id = crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(DeviceId));
+    let modifiers = *inner.modifiers_state.borrow();
+    let time = Some(Timestamp::from_millis(time));
+
+    let event_sink = &mut winit_state.event_sink;
+    let pen = |phase| {
+        WindowEvent::Pen(crate::event::Pen {
+            device_id,
+            phase,
+            tool: inner.tool,
+            location,
+            state: inner.state,
+        })
+    };
+
+    // `proximity_in` is always the first event of its frame and `proximity_out` the last one.
+    let (entered, phases) = match phases.split_first() {
+        Some((PenPhase::ProximityIn, rest)) => (true, rest),
+        _ => (false, &phases[..]),
+    };
+    if entered {
+        event_sink.push_window_event(pen(PenPhase::ProximityIn), window_id);
+        event_sink.push_window_event(WindowEvent::CursorEntered { device_id }, window_id);
+    }
+    if entered || position_changed {
+        event_sink.push_window_event(
+            WindowEvent::CursorMoved {
+                device_id,
+                position: location,
+                time,
+                modifiers,
+            },
+            window_id,
+        );
+    }
+    if !entered && (position_changed || axes_changed) {
+        event_sink.push_window_event(pen(PenPhase::Moved), window_id);
+    }
+
+    let mut left = false;
+    for &phase in phases {
+        event_sink.push_window_event(pen(phase), window_id);
+        let state = match phase {
+            PenPhase::Down => ElementState::Pressed,
+            PenPhase::Up => ElementState::Released,
+            PenPhase::ProximityOut => {
+                event_sink.push_window_event(WindowEvent::CursorLeft { device_id }, window_id);
+                left = true;
+                continue;
+            }
+            _ => continue,
+        };
+        event_sink.push_window_event(
+            WindowEvent::MouseInput {
+                device_id,
+                state,
+                button: MouseButton::Left,
+                time,
+                modifiers,
+            },
+            window_id,
+        );
+    }
+    if left {
+        inner.surface = None;
+    }
+}
@@ -0,0 +1,115 @@
+//! Drawing tablet handling.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::Attached;
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_manager_v2::ZwpTabletManagerV2;
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_pad_v2::Event as PadEvent;
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_seat_v2::{
+    Event as TabletSeatEvent, ZwpTabletSeatV2,
+};
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_v2::Event as TabletEvent;
+
+use crate::dpi::LogicalPosition;
+use crate::event::{PenPhase, PenState, PenTool};
+use crate::keyboard::ModifiersState;
+use crate::platform_impl::wayland::event_loop::WinitState;
+
+mod handlers;
+
+/// Wrapper around the tablet seat to handle release.
+pub struct Tablet {
+    /// Proxy to the tablet seat.
+    tablet_seat: ZwpTabletSeatV2,
+}
+
+impl Tablet {
+    pub fn new(
+        seat: &Attached<WlSeat>,
+        tablet_manager: &ZwpTabletManagerV2,
+        modifiers_state: Rc<RefCell<ModifiersState>>,
+    ) -> Self {
+        let tablet_seat = tablet_manager.get_tablet_seat(seat);
+
+        tablet_seat.quick_assign(move |_, event, _| match event {
+            TabletSeatEvent::ToolAdded { id } => {
+                let mut inner = ToolInner::new(modifiers_state.clone());
+                id.quick_assign(move |tool, event, mut dispatch_data| {
+                    let winit_state = dispatch_data.get::<WinitState>().unwrap();
+                    handlers::handle_tool(&tool, event, &mut inner, winit_state);
+                });
+            }
+            // Tablets and pads are only tracked to destroy them once they are removed.
+            TabletSeatEvent::TabletAdded { id } => {
+                id.quick_assign(|tablet, event, _| {
+                    if let TabletEvent::Removed = event {
+                        tablet.destroy();
+                    }
+                });
+            }
+            TabletSeatEvent::PadAdded { id } => {
+                id.quick_assign(|pad, event, _| {
+                    if let PadEvent::Removed = event {
+                        pad.destroy();
+                    }
+                });
+            }
+            _ => (),
+        });
+
+        Self {
+            tablet_seat: tablet_seat.detach(),
+        }
+    }
+}
+
+impl Drop for Tablet {
+    fn drop(&mut self) {
+        self.tablet_seat.destroy();
+    }
+}
+
+/// The data used by tablet tool handlers.
+pub(super) struct ToolInner {
+    /// The type of the tool.
+    tool: PenTool,
+
+    /// The surface that the tool is in proximity of.
+    surface: Option<WlSurface>,
+
+    /// The location of the tool on the surface.
+    position: LogicalPosition<f64>,
+
+    /// The values of the axes. Axes that the tool does not have are `None`.
+    state: PenState,
+
+    /// The phases received since the last frame.
+    pending_phases: Vec<PenPhase>,
+
+    /// Whether the position changed since the last frame.
+    position_changed: bool,
+
+    /// Whether an axis changed since the last frame.
+    axes_changed: bool,
+
+    /// The current state of modifiers observed in the keyboard handler.
+    modifiers_state: Rc<RefCell<ModifiersState>>,
+}
+
+impl ToolInner {
+    fn new(modifiers_state: Rc<RefCell<ModifiersState>>) -> Self {
+        Self {
+            tool: PenTool::Pen,
+            surface: None,
+            position: LogicalPosition::new(0.0, 0.0),
+            state: PenState::default(),
+            pending_phases: Vec::new(),
+            position_changed: false,
+            axes_changed: false,
+            modifiers_state,
+        }
+    }
+}
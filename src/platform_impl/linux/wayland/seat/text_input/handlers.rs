@@ -5,11 +5,11 @@ use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input
     Event as TextInputEvent, ZwpTextInputV3,
 };
 
-use crate::event::WindowEvent;
+use crate::event::{Ime, WindowEvent};
 use crate::platform_impl::wayland;
 use crate::platform_impl::wayland::event_loop::WinitState;
 
-use super::{TextInputHandler, TextInputInner};
+use super::{Preedit, TextInputHandler, TextInputInner};
 
 #[inline]
 pub(super) fn handle_text_input(
@@ -29,14 +29,17 @@ pub(super) fn handle_text_input(
             };
             inner.target_window_id = Some(window_id);
 
-            // Enable text input on that surface.
-            text_input.enable();
-            text_input.commit();
-
             // Notify a window we're currently over about text input handler.
             let text_input_handler = TextInputHandler {
                 text_input: text_input.detach(),
             };
+
+            // Enable text input on that surface if the window accepts IME input.
+            if window_handle.ime_allowed() {
+                text_input_handler.set_enabled(true);
+                event_sink.push_window_event(WindowEvent::Ime(Ime::Enabled), window_id);
+            }
+
             window_handle.text_input_entered(text_input_handler);
         }
         TextInputEvent::Leave { surface } => {
@@ -52,6 +55,13 @@ pub(super) fn handle_text_input(
             };
 
             inner.target_window_id = None;
+            inner.commit_string = None;
+            inner.pending_preedit = None;
+            inner.preedit_shown = false;
+
+            if window_handle.ime_allowed() {
+                event_sink.push_window_event(WindowEvent::Ime(Ime::Disabled), window_id);
+            }
 
             // Remove text input handler from the window we're leaving.
             let text_input_handler = TextInputHandler {
@@ -59,17 +69,49 @@ pub(super) fn handle_text_input(
             };
             window_handle.text_input_left(text_input_handler);
         }
+        TextInputEvent::PreeditString {
+            text,
+            cursor_begin,
+            cursor_end,
+        } => {
+            // A negative cursor position means that the cursor should be hidden.
+            let cursor = if cursor_begin < 0 || cursor_end < 0 {
+                None
+            } else {
+                Some((cursor_begin as usize, cursor_end as usize))
+            };
+            inner.pending_preedit = Some(Preedit {
+                text: text.unwrap_or_default(),
+                cursor,
+            });
+        }
         TextInputEvent::CommitString { text } => {
             // Update currenly commited string.
             inner.commit_string = text;
         }
         TextInputEvent::Done { .. } => {
-            let (window_id, text) = match (inner.target_window_id, inner.commit_string.take()) {
-                (Some(window_id), Some(text)) => (window_id, text),
-                _ => return,
+            let window_id = match inner.target_window_id {
+                Some(window_id) => window_id,
+                None => return,
             };
 
-            event_sink.push_window_event(WindowEvent::ReceivedImeText(text), window_id);
+            // The preedit string is reset by every `done` event unless it is sent again.
+            let preedit = inner.pending_preedit.take().unwrap_or_default();
+
+            if let Some(text) = inner.commit_string.take() {
+                // Hide the preedit string before inserting the commit string.
+                if inner.preedit_shown {
+                    event_sink
+                        .push_window_event(WindowEvent::Ime(Preedit::default().into()), window_id);
+                    inner.preedit_shown = false;
+                }
+                event_sink.push_window_event(WindowEvent::Ime(Ime::Commit(text)), window_id);
+            }
+
+            if !preedit.text.is_empty() || inner.preedit_shown {
+                inner.preedit_shown = !preedit.text.is_empty();
+                event_sink.push_window_event(WindowEvent::Ime(preedit.into()), window_id);
+            }
         }
         _ => (),
     }
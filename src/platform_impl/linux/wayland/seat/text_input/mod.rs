@@ -3,6 +3,7 @@ use sctk::reexports::client::Attached;
 use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3;
 use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input_v3::ZwpTextInputV3;
 
+use crate::event::Ime;
 use crate::platform_impl::wayland::event_loop::WinitState;
 use crate::platform_impl::wayland::WindowId;
 
@@ -20,6 +21,16 @@ impl TextInputHandler {
         self.text_input.set_cursor_rectangle(x, y, 0, 0);
         self.text_input.commit();
     }
+
+    #[inline]
+    pub fn set_enabled(&self, enabled: bool) {
+        if enabled {
+            self.text_input.enable();
+        } else {
+            self.text_input.disable();
+        }
+        self.text_input.commit();
+    }
 }
 
 /// A wrapper around text input to automatically destroy the object on `Drop`.
@@ -54,6 +65,12 @@ struct TextInputInner {
 
     /// Pending string to commit.
     commit_string: Option<String>,
+
+    /// Pending preedit string.
+    pending_preedit: Option<Preedit>,
+
+    /// Whether the application is currently showing a preedit string.
+    preedit_shown: bool,
 }
 
 impl TextInputInner {
@@ -61,6 +78,26 @@ impl TextInputInner {
         Self {
             target_window_id: None,
             commit_string: None,
+            pending_preedit: None,
+            preedit_shown: false,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Preedit {
+    text: String,
+    /// The byte range of the cursor.
+    cursor: Option<(usize, usize)>,
+}
+
+impl From<Preedit> for Ime {
+    fn from(preedit: Preedit) -> Self {
+        // `zwp_text_input_v3` does not provide any styling information.
+        Ime::Preedit {
+            text: preedit.text,
+            cursor: preedit.cursor,
+            spans: vec![],
         }
     }
 }
@@ -3,7 +3,7 @@
 use sctk::reexports::client::protocol::wl_touch::Event as TouchEvent;
 
 use crate::dpi::LogicalPosition;
-use crate::event::{TouchPhase, WindowEvent};
+use crate::event::{Timestamp, TouchPhase, WindowEvent};
 
 use crate::platform_impl::wayland::event_loop::WinitState;
 use crate::platform_impl::wayland::{self, DeviceId};
@@ -21,7 +21,12 @@ pub(super) fn handle_touch(
 
     match event {
         TouchEvent::Down {
-            surface, id, x, y, ..
+            time,
+            surface,
+            id,
+            x,
+            y,
+            ..
         } => {
             let window_id = wayland::make_wid(&surface);
             if !winit_state.window_map.contains_key(&window_id) {
@@ -40,6 +45,7 @@ pub(super) fn handle_touch(
                     location: position.to_physical(scale_factor),
                     force: None, // TODO
                     id: id as u64,
+                    time: Some(Timestamp::from_millis(time)),
                 }),
                 window_id,
             );
@@ -48,7 +54,7 @@ pub(super) fn handle_touch(
                 .touch_points
                 .push(TouchPoint::new(surface, position, id));
         }
-        TouchEvent::Up { id, .. } => {
+        TouchEvent::Up { time, id, .. } => {
             let touch_point = match inner.touch_points.iter().find(|p| p.id == id) {
                 Some(touch_point) => touch_point,
                 None => return,
@@ -67,11 +73,12 @@ pub(super) fn handle_touch(
                     location,
                     force: None, // TODO
                     id: id as u64,
+                    time: Some(Timestamp::from_millis(time)),
                 }),
                 window_id,
             );
         }
-        TouchEvent::Motion { id, x, y, .. } => {
+        TouchEvent::Motion { time, id, x, y } => {
             let touch_point = match inner.touch_points.iter_mut().find(|p| p.id == id) {
                 Some(touch_point) => touch_point,
                 None => return,
@@ -92,6 +99,7 @@ pub(super) fn handle_touch(
                     location,
                     force: None, // TODO
                     id: id as u64,
+                    time: Some(Timestamp::from_millis(time)),
                 }),
                 window_id,
             );
@@ -112,6 +120,7 @@ pub(super) fn handle_touch(
                         location,
                         force: None, // TODO
                         id: touch_point.id as u64,
+                        time: None,
                     }),
                     window_id,
                 );
@@ -2,11 +2,12 @@ use std::collections::VecDeque;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
+use sctk::reexports::client::protocol::wl_output::WlOutput;
+use sctk::reexports::client::protocol::wl_shm::WlShm;
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
 use sctk::reexports::client::Display;
 
-use sctk::reexports::calloop;
-
+use sctk::reexports::protocols::viewporter::client::wp_viewporter::WpViewporter;
 use sctk::window::{
     ARGBColor, ButtonColorSpec, ColorSpec, ConceptConfig, ConceptFrame, Decorations,
 };
@@ -15,22 +16,25 @@ use raw_window_handle::unix::WaylandHandle;
 
 use crate::dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
 use crate::error::{ExternalError, NotSupportedError, OsError as RootOsError};
-use crate::monitor::MonitorHandle as RootMonitorHandle;
+use crate::event::DndAction;
+use crate::monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode};
 use crate::platform::unix::{ARGBColor as LocalARGBColor, Button, ButtonState, Element, Theme};
 use crate::platform_impl::{
     MonitorHandle as PlatformMonitorHandle, OsError,
-    PlatformSpecificWindowBuilderAttributes as PlatformAttributes,
+    PlatformSpecificWindowBuilderAttributes as PlatformAttributes, VideoMode as PlatformVideoMode,
+};
+use crate::window::{
+    CursorGrabMode, CursorIcon, CustomCursor, DamageRect, DragData, Fullscreen, WindowAttributes,
 };
-use crate::window::{CursorIcon, Fullscreen, WindowAttributes};
 
 use super::env::WindowingFeatures;
-use super::event_loop::WinitState;
+use super::event_loop::{Ping, WinitState};
 use super::output::{MonitorHandle, OutputManagerHandle};
 use super::{EventLoopWindowTarget, WindowId};
 
 pub mod shim;
 
-use shim::{WindowHandle, WindowRequest, WindowUpdate};
+use shim::{Frame, WindowHandle, WindowRequest, WindowUpdate};
 
 pub struct Window {
     /// Window id.
@@ -45,11 +49,17 @@ pub struct Window {
     /// The current window size.
     size: Arc<Mutex<LogicalSize<u32>>>,
 
+    /// The size of the buffers while they are scaled to the window size.
+    viewport_size: Arc<Mutex<Option<PhysicalSize<u32>>>>,
+
+    /// The video mode of exclusive fullscreen, which is in effect while the buffers are scaled.
+    video_mode: Arc<Mutex<Option<RootVideoMode>>>,
+
     /// A handle to output manager.
     output_manager_handle: OutputManagerHandle,
 
     /// Event loop proxy to wake it up.
-    event_loop_awakener: calloop::ping::Ping,
+    event_loop_awakener: Ping,
 
     /// Fullscreen state.
     fullscreen: Arc<AtomicBool>,
@@ -80,7 +90,14 @@ impl Window {
                 window_update.scale_factor = Some(scale);
                 window_update.redraw_requested = true;
 
-                surface.set_buffer_scale(scale);
+                // Scaled buffers always use a buffer scale of 1.
+                let has_viewport = winit_state
+                    .window_map
+                    .get(&window_id)
+                    .map_or(false, |handle| handle.viewport_size().is_some());
+                if !has_viewport {
+                    surface.set_buffer_scale(scale);
+                }
             })
             .detach();
 
@@ -118,6 +135,7 @@ impl Window {
 
                             window_update.refresh_frame = true;
                             window_update.redraw_requested = true;
+                            window_update.configured = true;
                             if let Some((w, h)) = new_size {
                                 window_update.size = Some(LogicalSize::new(w, h));
                             }
@@ -161,20 +179,16 @@ impl Window {
         window.set_resizable(attributes.resizable);
         window.set_title(attributes.title);
 
+        let windowing_features = event_loop_window_target.windowing_features;
+
         // Set fullscreen/maximized if so was requested.
+        let mut viewport_size = None;
+        let video_mode = exclusive_video_mode(&attributes.fullscreen);
         match attributes.fullscreen {
-            Some(Fullscreen::Exclusive(_)) => {
-                warn!("`Fullscreen::Exclusive` is ignored on Wayland")
-            }
-            Some(Fullscreen::Borderless(monitor)) => {
-                let monitor =
-                    monitor.and_then(|RootMonitorHandle { inner: monitor }| match monitor {
-                        PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
-                        #[cfg(feature = "x11")]
-                        PlatformMonitorHandle::X(_) => None,
-                    });
-
-                window.set_fullscreen(monitor.as_ref());
+            Some(fullscreen) => {
+                let (output, size) = fullscreen_target(fullscreen, windowing_features);
+                window.set_fullscreen(output.as_ref());
+                viewport_size = size;
             }
             None => {
                 if attributes.maximized {
@@ -194,7 +208,18 @@ impl Window {
         let window_requests = Arc::new(Mutex::new(Vec::with_capacity(64)));
 
         // Create a handle that performs all the requests on underlying sctk a window.
-        let window_handle = WindowHandle::new(window, size.clone(), window_requests.clone());
+        let viewporter = event_loop_window_target.env.get_global::<WpViewporter>();
+        let viewport = Arc::new(Mutex::new(None));
+        let shm = event_loop_window_target.env.require_global::<WlShm>();
+        let window_handle = WindowHandle::new(
+            window,
+            size.clone(),
+            window_requests.clone(),
+            viewporter,
+            viewport.clone(),
+            shm,
+        );
+        window_handle.set_viewport(viewport_size);
 
         let mut winit_state = event_loop_window_target.state.borrow_mut();
 
@@ -204,8 +229,6 @@ impl Window {
             .window_updates
             .insert(window_id, WindowUpdate::new());
 
-        let windowing_features = event_loop_window_target.windowing_features;
-
         // Send all updates to the server.
         let wayland_source = &event_loop_window_target.wayland_source;
         let event_loop_handle = &event_loop_window_target.event_loop_handle;
@@ -232,6 +255,8 @@ impl Window {
             display: event_loop_window_target.display.clone(),
             output_manager_handle,
             size,
+            viewport_size: viewport,
+            video_mode: Arc::new(Mutex::new(video_mode)),
             window_requests,
             event_loop_awakener: event_loop_window_target.event_loop_awakener.clone(),
             fullscreen,
@@ -276,6 +301,10 @@ impl Window {
     }
 
     pub fn inner_size(&self) -> PhysicalSize<u32> {
+        if let Some(size) = *self.viewport_size.lock().unwrap() {
+            return size;
+        }
+
         self.size
             .lock()
             .unwrap()
@@ -289,8 +318,38 @@ impl Window {
         self.event_loop_awakener.ping();
     }
 
+    pub fn present_rgba(
+        &self,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        damage: &[DamageRect],
+    ) -> Result<(), ExternalError> {
+        // `XRGB8888` is stored as BGRX in little-endian order.
+        let mut pixels = Vec::with_capacity(rgba.len());
+        for pixel in rgba.chunks_exact(4) {
+            pixels.extend_from_slice(&[pixel[2], pixel[1], pixel[0], 0xff]);
+        }
+        let frame = Frame {
+            pixels,
+            width,
+            height,
+            damage: damage.to_vec(),
+        };
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(WindowRequest::Present(frame));
+        self.event_loop_awakener.ping();
+        Ok(())
+    }
+
     #[inline]
     pub fn outer_size(&self) -> PhysicalSize<u32> {
+        if let Some(size) = *self.viewport_size.lock().unwrap() {
+            return size;
+        }
+
         self.size
             .lock()
             .unwrap()
@@ -378,6 +437,12 @@ impl Window {
     #[inline]
     pub fn fullscreen(&self) -> Option<Fullscreen> {
         if self.fullscreen.load(Ordering::Relaxed) {
+            if self.viewport_size.lock().unwrap().is_some() {
+                if let Some(video_mode) = self.video_mode.lock().unwrap().clone() {
+                    return Some(Fullscreen::Exclusive(video_mode));
+                }
+            }
+
             let current_monitor = self.current_monitor().map(|monitor| RootMonitorHandle {
                 inner: PlatformMonitorHandle::Wayland(monitor),
             });
@@ -390,28 +455,19 @@ impl Window {
 
     #[inline]
     pub fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
-        let fullscreen_request = match fullscreen {
-            Some(Fullscreen::Exclusive(_)) => {
-                warn!("`Fullscreen::Exclusive` is ignored on Wayland");
-                return;
+        *self.video_mode.lock().unwrap() = exclusive_video_mode(&fullscreen);
+        let (fullscreen_request, viewport_size) = match fullscreen {
+            Some(fullscreen) => {
+                let (output, size) = fullscreen_target(fullscreen, self.windowing_features);
+                (WindowRequest::Fullscreen(output), size)
             }
-            Some(Fullscreen::Borderless(monitor)) => {
-                let monitor =
-                    monitor.and_then(|RootMonitorHandle { inner: monitor }| match monitor {
-                        PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
-                        #[cfg(feature = "x11")]
-                        PlatformMonitorHandle::X(_) => None,
-                    });
-
-                WindowRequest::Fullscreen(monitor)
-            }
-            None => WindowRequest::UnsetFullscreen,
+            None => (WindowRequest::UnsetFullscreen, None),
         };
 
-        self.window_requests
-            .lock()
-            .unwrap()
-            .push(fullscreen_request);
+        let mut window_requests = self.window_requests.lock().unwrap();
+        window_requests.push(fullscreen_request);
+        window_requests.push(WindowRequest::Viewport(viewport_size));
+        drop(window_requests);
         self.event_loop_awakener.ping();
     }
 
@@ -548,6 +604,11 @@ impl Window {
         self.event_loop_awakener.ping();
     }
 
+    #[inline]
+    pub fn set_custom_cursor(&self, _cursor: &CustomCursor) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     #[inline]
     pub fn set_cursor_visible(&self, visible: bool) {
         let cursor_visible_request = WindowRequest::ShowCursor(visible);
@@ -559,12 +620,12 @@ impl Window {
     }
 
     #[inline]
-    pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
         if !self.windowing_features.cursor_grab() {
             return Err(ExternalError::NotSupported(NotSupportedError::new()));
         }
 
-        let cursor_grab_request = WindowRequest::GrabCursor(grab);
+        let cursor_grab_request = WindowRequest::GrabCursor(mode);
         self.window_requests
             .lock()
             .unwrap()
@@ -598,6 +659,16 @@ impl Window {
         Ok(())
     }
 
+    #[inline]
+    pub fn start_drag(
+        &self,
+        _data: DragData,
+        _allowed_actions: &[DndAction],
+        _icon: Option<CursorIcon>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     #[inline]
     pub fn set_ime_position(&self, position: Position) {
         let scale_factor = self.scale_factor() as f64;
@@ -610,6 +681,15 @@ impl Window {
         self.event_loop_awakener.ping();
     }
 
+    #[inline]
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(WindowRequest::AllowIME(allowed));
+        self.event_loop_awakener.ping();
+    }
+
     #[inline]
     pub fn reset_dead_keys(&self) {
         // not implemented
@@ -671,3 +751,49 @@ impl Drop for Window {
         self.event_loop_awakener.ping();
     }
 }
+
+/// Returns the video mode of exclusive fullscreen.
+fn exclusive_video_mode(fullscreen: &Option<Fullscreen>) -> Option<RootVideoMode> {
+    match fullscreen {
+        Some(Fullscreen::Exclusive(video_mode)) => Some(video_mode.clone()),
+        _ => None,
+    }
+}
+
+/// Returns the output of a fullscreen window and the size of its buffers if they are scaled.
+///
+/// The video mode of an output cannot be changed on Wayland. An exclusive fullscreen window
+/// covers the output of the video mode instead and its buffers are scaled to the size of the
+/// output if the compositor supports `wp_viewporter`. Otherwise, it falls back to borderless
+/// fullscreen on that output.
+fn fullscreen_target(
+    fullscreen: Fullscreen,
+    windowing_features: WindowingFeatures,
+) -> (Option<WlOutput>, Option<PhysicalSize<u32>>) {
+    match fullscreen {
+        Fullscreen::Exclusive(RootVideoMode { video_mode }) => match video_mode {
+            PlatformVideoMode::Wayland(video_mode) => {
+                if windowing_features.viewporter() {
+                    (Some(video_mode.monitor.proxy), Some(video_mode.size))
+                } else {
+                    warn!("`wp_viewporter` is not available, using borderless fullscreen");
+                    (Some(video_mode.monitor.proxy), None)
+                }
+            }
+            #[cfg(any(feature = "x11", feature = "headless"))]
+            _ => {
+                warn!("The video mode does not belong to a Wayland output");
+                (None, None)
+            }
+        },
+        Fullscreen::Borderless(monitor) => {
+            let monitor = monitor.and_then(|RootMonitorHandle { inner: monitor }| match monitor {
+                PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
+                #[cfg(any(feature = "x11", feature = "headless"))]
+                _ => None,
+            });
+
+            (monitor, None)
+        }
+    }
+}
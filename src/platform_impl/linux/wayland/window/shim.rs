@@ -1,18 +1,24 @@
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
+use std::mem;
 use std::sync::{Arc, Mutex};
 
 use sctk::reexports::client::protocol::wl_output::WlOutput;
+use sctk::reexports::client::protocol::wl_shm::{Format, WlShm};
+use sctk::reexports::client::Attached;
+use sctk::reexports::protocols::viewporter::client::wp_viewport::WpViewport;
+use sctk::reexports::protocols::viewporter::client::wp_viewporter::WpViewporter;
 
+use sctk::shm::DoubleMemPool;
 use sctk::window::{ConceptConfig, ConceptFrame, Decorations, Window};
 
-use crate::dpi::{LogicalPosition, LogicalSize};
+use crate::dpi::{LogicalPosition, LogicalSize, PhysicalSize};
 
-use crate::event::WindowEvent;
-use crate::platform_impl::wayland::event_loop::WinitState;
+use crate::event::{Ime, WindowEvent};
+use crate::platform_impl::wayland::event_loop::{EventSink, WinitState};
 use crate::platform_impl::wayland::seat::pointer::WinitPointer;
 use crate::platform_impl::wayland::seat::text_input::TextInputHandler;
-use crate::platform_impl::wayland::WindowId;
-use crate::window::CursorIcon;
+use crate::platform_impl::wayland::{self, WindowId};
+use crate::window::{CursorGrabMode, CursorIcon, DamageRect};
 
 /// A request to SCTK window from Winit window.
 #[derive(Debug, Clone)]
@@ -25,6 +31,11 @@ pub enum WindowRequest {
     /// Unset fullscreen.
     UnsetFullscreen,
 
+    /// Scale buffers of the given size to the window size.
+    ///
+    /// Passing `None` will stop the scaling.
+    Viewport(Option<PhysicalSize<u32>>),
+
     /// Show cursor for the certain window or not.
     ShowCursor(bool),
 
@@ -32,7 +43,7 @@ pub enum WindowRequest {
     NewCursorIcon(CursorIcon),
 
     /// Grab cursor.
-    GrabCursor(bool),
+    GrabCursor(CursorGrabMode),
 
     /// Drag window.
     DragWindow,
@@ -64,16 +75,33 @@ pub enum WindowRequest {
     /// Set IME window position.
     IMEPosition(LogicalPosition<u32>),
 
+    /// Allow or disallow IME input.
+    AllowIME(bool),
+
     /// Redraw was requested.
     Redraw,
 
     /// A new theme for a concept frame was requested.
     Theme(ConceptConfig),
 
+    /// Present a frame of pixels.
+    Present(Frame),
+
     /// Window should be closed.
     Close,
 }
 
+/// A frame of pixels in the `XRGB8888` format of `wl_shm`.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub pixels: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+
+    /// The damage of the frame, clipped to the frame.
+    pub damage: Vec<DamageRect>,
+}
+
 /// Pending update to a window from SCTK window.
 #[derive(Debug, Clone, Copy)]
 pub struct WindowUpdate {
@@ -89,6 +117,9 @@ pub struct WindowUpdate {
     /// Wether the frame should be refreshed.
     pub refresh_frame: bool,
 
+    /// Whether the window has been configured.
+    pub configured: bool,
+
     /// Close the window.
     pub close_window: bool,
 }
@@ -100,6 +131,7 @@ impl WindowUpdate {
             scale_factor: None,
             redraw_requested: false,
             refresh_frame: false,
+            configured: false,
             close_window: false,
         }
     }
@@ -114,6 +146,9 @@ impl WindowUpdate {
         let refresh_frame = self.refresh_frame;
         self.refresh_frame = false;
 
+        let configured = self.configured;
+        self.configured = false;
+
         let close_window = self.close_window;
         self.close_window = false;
 
@@ -122,6 +157,7 @@ impl WindowUpdate {
             scale_factor,
             redraw_requested,
             refresh_frame,
+            configured,
             close_window,
         }
     }
@@ -145,14 +181,38 @@ pub struct WindowHandle {
     /// Visible cursor or not.
     cursor_visible: Cell<bool>,
 
-    /// Cursor confined to the surface.
-    confined: Cell<bool>,
+    /// How the cursor is confined to or locked on the surface.
+    cursor_grab_mode: Cell<CursorGrabMode>,
 
     /// Pointers over the current surface.
     pointers: Vec<WinitPointer>,
 
     /// Text inputs on the current surface.
     text_inputs: Vec<TextInputHandler>,
+
+    /// Whether the window accepts IME input.
+    ime_allowed: Cell<bool>,
+
+    /// The global to scale surfaces if the compositor supports it.
+    viewporter: Option<Attached<WpViewporter>>,
+
+    /// The viewport that scales the buffers to the window size.
+    viewport: RefCell<Option<WpViewport>>,
+
+    /// The buffer size requested by `set_viewport`, which takes effect with the next configure.
+    pending_viewport: Cell<Option<Option<PhysicalSize<u32>>>>,
+
+    /// The size of the buffers while the viewport is active.
+    viewport_size: Arc<Mutex<Option<PhysicalSize<u32>>>>,
+
+    /// The global to create buffers of presented frames.
+    shm: Attached<WlShm>,
+
+    /// The buffers of presented frames, created when the first frame is presented.
+    pool: RefCell<Option<DoubleMemPool>>,
+
+    /// The latest frame if the compositor still used both buffers when it was presented.
+    pending_frame: RefCell<Option<Frame>>,
 }
 
 impl WindowHandle {
@@ -160,35 +220,177 @@ impl WindowHandle {
         window: Window<ConceptFrame>,
         size: Arc<Mutex<LogicalSize<u32>>>,
         pending_window_requests: Arc<Mutex<Vec<WindowRequest>>>,
+        viewporter: Option<Attached<WpViewporter>>,
+        viewport_size: Arc<Mutex<Option<PhysicalSize<u32>>>>,
+        shm: Attached<WlShm>,
     ) -> Self {
         Self {
             window,
             size,
             pending_window_requests,
             cursor_icon: Cell::new(CursorIcon::Default),
-            confined: Cell::new(false),
+            cursor_grab_mode: Cell::new(CursorGrabMode::None),
             cursor_visible: Cell::new(true),
             pointers: Vec::new(),
             text_inputs: Vec::new(),
+            ime_allowed: Cell::new(false),
+            viewporter,
+            viewport: RefCell::new(None),
+            pending_viewport: Cell::new(None),
+            viewport_size,
+            shm,
+            pool: RefCell::new(None),
+            pending_frame: RefCell::new(None),
         }
     }
 
-    pub fn set_cursor_grab(&self, grab: bool) {
-        // The new requested state matches the current confine status, return.
-        if self.confined.get() == grab {
+    /// Attaches a frame to the surface.
+    ///
+    /// If the compositor still uses both buffers, the frame is attached once one of them is
+    /// released. A frame that replaces such a pending frame inherits its damage.
+    pub fn present(&self, mut frame: Frame) {
+        // Buffers cannot be empty.
+        if frame.width == 0 || frame.height == 0 {
             return;
         }
+        if let Some(pending) = self.pending_frame.borrow_mut().take() {
+            // An empty list damages the whole frame.
+            if pending.damage.is_empty() {
+                frame.damage.clear();
+            } else if !frame.damage.is_empty() {
+                frame.damage.extend(pending.damage);
+            }
+        }
 
-        self.confined.replace(grab);
+        let mut pool = self.pool.borrow_mut();
+        if pool.is_none() {
+            let window_id = wayland::make_wid(self.window.surface());
+            let double_pool = DoubleMemPool::new(self.shm.clone(), move |mut dispatch_data| {
+                let winit_state = dispatch_data.get::<WinitState>().unwrap();
+                if let Some(window_handle) = winit_state.window_map.get(&window_id) {
+                    window_handle.present_pending();
+                }
+            });
+            match double_pool {
+                Ok(double_pool) => *pool = Some(double_pool),
+                Err(e) => {
+                    warn!("Could not create the buffers of the window: {}", e);
+                    return;
+                }
+            }
+        }
 
-        for pointer in self.pointers.iter() {
-            if self.confined.get() {
-                let surface = self.window.surface();
-                pointer.confine(&surface);
-            } else {
-                pointer.unconfine();
+        let pool = match pool.as_mut().unwrap().pool() {
+            Some(pool) => pool,
+            None => {
+                *self.pending_frame.borrow_mut() = Some(frame);
+                return;
+            }
+        };
+        if let Err(e) = pool.resize(frame.pixels.len()) {
+            warn!("Could not resize the buffers of the window: {}", e);
+            return;
+        }
+        pool.mmap()[..frame.pixels.len()].copy_from_slice(&frame.pixels);
+        let buffer = pool.buffer(
+            0,
+            frame.width as i32,
+            frame.height as i32,
+            4 * frame.width as i32,
+            Format::Xrgb8888,
+        );
+
+        let surface = self.window.surface();
+        surface.attach(Some(&buffer), 0, 0);
+        // Damage in buffer coordinates requires version 4 of `wl_surface`.
+        if surface.as_ref().version() >= 4 && !frame.damage.is_empty() {
+            for rect in &frame.damage {
+                surface.damage_buffer(
+                    rect.x as i32,
+                    rect.y as i32,
+                    rect.width as i32,
+                    rect.height as i32,
+                );
             }
+        } else {
+            surface.damage(0, 0, i32::MAX, i32::MAX);
         }
+        surface.commit();
+    }
+
+    /// Attaches the pending frame after one of the buffers has been released.
+    fn present_pending(&self) {
+        let frame = self.pending_frame.borrow_mut().take();
+        if let Some(frame) = frame {
+            self.present(frame);
+        }
+    }
+
+    /// Scales buffers of the given size to the window size or stops the scaling once the
+    /// compositor has configured the window.
+    ///
+    /// Until then, the size of the window is not known and the buffers keep their size.
+    pub fn set_viewport(&self, size: Option<PhysicalSize<u32>>) {
+        self.pending_viewport.set(Some(size));
+    }
+
+    /// Applies the viewport requested by `set_viewport` after a configure.
+    ///
+    /// Without `wp_viewporter`, the buffers always have the size of the window. Returns whether
+    /// the size of the buffers has changed.
+    pub fn apply_viewport(&self) -> bool {
+        let size = match self.pending_viewport.take() {
+            Some(size) => size,
+            None => return false,
+        };
+        let surface = self.window.surface();
+        let mut viewport = self.viewport.borrow_mut();
+        match (size, &self.viewporter) {
+            (Some(_), Some(viewporter)) => {
+                if viewport.is_none() {
+                    *viewport = Some(viewporter.get_viewport(surface).detach());
+                }
+                // The viewport determines the size of the surface.
+                surface.set_buffer_scale(1);
+            }
+            _ => {
+                if let Some(viewport) = viewport.take() {
+                    viewport.destroy();
+                }
+                surface.set_buffer_scale(sctk::get_surface_scale_factor(surface));
+            }
+        }
+        let size = size.filter(|_| viewport.is_some());
+        drop(viewport);
+
+        mem::replace(&mut *self.viewport_size.lock().unwrap(), size) != size
+    }
+
+    /// The size of the buffers while they are scaled to the window size.
+    pub fn viewport_size(&self) -> Option<PhysicalSize<u32>> {
+        *self.viewport_size.lock().unwrap()
+    }
+
+    /// Scales the buffers to the new size of the window.
+    pub fn update_viewport(&self, size: LogicalSize<u32>) {
+        if let Some(viewport) = &*self.viewport.borrow() {
+            viewport.set_destination(size.width as i32, size.height as i32);
+        }
+    }
+
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) {
+        // The new requested state matches the current grab mode, return.
+        if self.cursor_grab_mode.replace(mode) == mode {
+            return;
+        }
+
+        let surface = self.window.surface();
+        for pointer in self.pointers.iter() {
+            pointer.constrain(&surface, mode);
+        }
+
+        // Locked cursors are hidden.
+        self.set_cursor_visible(self.cursor_visible.get());
     }
 
     /// Pointer appeared over the window.
@@ -196,9 +398,10 @@ impl WindowHandle {
         let position = self.pointers.iter().position(|p| *p == pointer);
 
         if position.is_none() {
-            if self.confined.get() {
+            let mode = self.cursor_grab_mode.get();
+            if mode != CursorGrabMode::None {
                 let surface = self.window.surface();
-                pointer.confine(&surface);
+                pointer.constrain(&surface, mode);
             }
             self.pointers.push(pointer);
         }
@@ -214,9 +417,9 @@ impl WindowHandle {
         if let Some(position) = position {
             let pointer = self.pointers.remove(position);
 
-            // Drop the confined pointer.
-            if self.confined.get() {
-                pointer.unconfine();
+            // Drop the confined or locked pointer.
+            if self.cursor_grab_mode.get() != CursorGrabMode::None {
+                pointer.unconstrain();
             }
         }
     }
@@ -248,9 +451,33 @@ impl WindowHandle {
         }
     }
 
+    pub fn ime_allowed(&self) -> bool {
+        self.ime_allowed.get()
+    }
+
+    pub fn set_ime_allowed(&self, allowed: bool, window_id: WindowId, event_sink: &mut EventSink) {
+        if self.ime_allowed.replace(allowed) == allowed {
+            return;
+        }
+
+        for text_input in self.text_inputs.iter() {
+            text_input.set_enabled(allowed);
+        }
+
+        // The IME can only be used if a text input is on the surface.
+        if !self.text_inputs.is_empty() {
+            let event = match allowed {
+                true => Ime::Enabled,
+                false => Ime::Disabled,
+            };
+            event_sink.push_window_event(WindowEvent::Ime(event), window_id);
+        }
+    }
+
     pub fn set_cursor_visible(&self, visible: bool) {
         self.cursor_visible.replace(visible);
-        let cursor_icon = match visible {
+        let locked = self.cursor_grab_mode.get() == CursorGrabMode::Locked;
+        let cursor_icon = match visible && !locked {
             true => Some(self.cursor_icon.get()),
             false => None,
         };
@@ -263,7 +490,7 @@ impl WindowHandle {
     pub fn set_cursor_icon(&self, cursor_icon: CursorIcon) {
         self.cursor_icon.replace(cursor_icon);
 
-        if !self.cursor_visible.get() {
+        if !self.cursor_visible.get() || self.cursor_grab_mode.get() == CursorGrabMode::Locked {
             return;
         }
 
@@ -283,6 +510,7 @@ impl WindowHandle {
 pub fn handle_window_requests(winit_state: &mut WinitState) {
     let window_map = &mut winit_state.window_map;
     let window_updates = &mut winit_state.window_updates;
+    let event_sink = &mut winit_state.event_sink;
     let mut windows_to_close: Vec<WindowId> = Vec::new();
 
     // Process the rest of the events.
@@ -296,6 +524,12 @@ pub fn handle_window_requests(winit_state: &mut WinitState) {
                 WindowRequest::UnsetFullscreen => {
                     window_handle.window.unset_fullscreen();
                 }
+                WindowRequest::Viewport(size) => {
+                    window_handle.set_viewport(size);
+
+                    let window_update = window_updates.get_mut(&window_id).unwrap();
+                    window_update.redraw_requested = true;
+                }
                 WindowRequest::ShowCursor(show_cursor) => {
                     window_handle.set_cursor_visible(show_cursor);
                 }
@@ -305,8 +539,11 @@ pub fn handle_window_requests(winit_state: &mut WinitState) {
                 WindowRequest::IMEPosition(position) => {
                     window_handle.set_ime_position(position);
                 }
-                WindowRequest::GrabCursor(grab) => {
-                    window_handle.set_cursor_grab(grab);
+                WindowRequest::AllowIME(allowed) => {
+                    window_handle.set_ime_allowed(allowed, *window_id, event_sink);
+                }
+                WindowRequest::GrabCursor(mode) => {
+                    window_handle.set_cursor_grab(mode);
                 }
                 WindowRequest::DragWindow => {
                     window_handle.drag_window();
@@ -369,6 +606,9 @@ pub fn handle_window_requests(winit_state: &mut WinitState) {
                     let window_update = window_updates.get_mut(&window_id).unwrap();
                     window_update.refresh_frame = true;
                 }
+                WindowRequest::Present(frame) => {
+                    window_handle.present(frame);
+                }
                 WindowRequest::Redraw => {
                     let window_update = window_updates.get_mut(&window_id).unwrap();
                     window_update.redraw_requested = true;
@@ -385,7 +625,6 @@ pub fn handle_window_requests(winit_state: &mut WinitState) {
                     windows_to_close.push(*window_id);
 
                     // Send event that the window was destroyed.
-                    let event_sink = &mut winit_state.event_sink;
                     event_sink.push_window_event(WindowEvent::Destroyed, *window_id);
                 }
             };
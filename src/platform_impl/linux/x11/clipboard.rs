@@ -0,0 +1,795 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, VecDeque},
+    mem, ptr,
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use super::{ffi, util, util::EventQueue, XConnection};
+use crate::event::{ClipboardEvent, ClipboardKind};
+use xcb_dl_util::error::XcbError;
+
+/// The MIME type under which UTF-8 text is exchanged.
+pub const TEXT_MIME_TYPE: &str = "text/plain;charset=utf-8";
+
+/// How long we wait for the owner of a selection to answer a request or to send the next chunk
+/// of an INCR transfer, and for the requestor of an INCR transfer to read the next chunk.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+pub struct SelectionAtoms {
+    pub clipboard: ffi::xcb_atom_t,
+    pub primary: ffi::xcb_atom_t,
+    pub xdnd_selection: ffi::xcb_atom_t,
+    pub targets: ffi::xcb_atom_t,
+    pub multiple: ffi::xcb_atom_t,
+    pub timestamp: ffi::xcb_atom_t,
+    pub save_targets: ffi::xcb_atom_t,
+    pub incr: ffi::xcb_atom_t,
+    pub utf8_string: ffi::xcb_atom_t,
+    pub text: ffi::xcb_atom_t,
+    pub text_mime: ffi::xcb_atom_t,
+    // The property on our own window into which selection owners write converted data
+    pub transfer: ffi::xcb_atom_t,
+    // The property on our own window that we append to in order to learn the server time
+    pub server_time: ffi::xcb_atom_t,
+}
+
+impl SelectionAtoms {
+    pub fn new(xconn: &Arc<XConnection>) -> Self {
+        SelectionAtoms {
+            clipboard: xconn.get_atom("CLIPBOARD"),
+            primary: ffi::XCB_ATOM_PRIMARY,
+            xdnd_selection: xconn.get_atom("XdndSelection"),
+            targets: xconn.get_atom("TARGETS"),
+            multiple: xconn.get_atom("MULTIPLE"),
+            timestamp: xconn.get_atom("TIMESTAMP"),
+            save_targets: xconn.get_atom("SAVE_TARGETS"),
+            incr: xconn.get_atom("INCR"),
+            utf8_string: xconn.get_atom("UTF8_STRING"),
+            text: xconn.get_atom("TEXT"),
+            text_mime: xconn.get_atom(TEXT_MIME_TYPE),
+            transfer: xconn.get_atom("_WINIT_SELECTION"),
+            server_time: xconn.get_atom("_WINIT_SERVER_TIME"),
+        }
+    }
+}
+
+/// The data offered while we own a selection.
+struct Offer {
+    targets: Vec<(ffi::xcb_atom_t, Rc<[u8]>)>,
+    // The time at which we acquired the selection
+    time: ffi::xcb_timestamp_t,
+}
+
+/// An INCR transfer of data to another client.
+///
+/// After the initial `INCR` property has been written, the requestor deletes the property every
+/// time it has read a chunk. We then write the next chunk. A zero-length chunk ends the
+/// transfer.
+struct OutgoingTransfer {
+    requestor: ffi::xcb_window_t,
+    property: ffi::xcb_atom_t,
+    target: ffi::xcb_atom_t,
+    data: Rc<[u8]>,
+    offset: usize,
+    // The time at which the transfer is abandoned if the requestor has not read the last chunk
+    deadline: Instant,
+}
+
+/// The origin of a selection request.
+#[derive(Copy, Clone)]
+enum Requester {
+    Clipboard(ClipboardKind),
+    // The data of the drag-and-drop operation over this window
+    Dnd(ffi::xcb_window_t),
+}
+
+struct Request {
+    requester: Requester,
+    selection: ffi::xcb_atom_t,
+    target: ffi::xcb_atom_t,
+    // `None` if the request is for the list of offered MIME types
+    mime_type: Option<String>,
+    time: ffi::xcb_timestamp_t,
+}
+
+/// The result of a selection request.
+pub enum SelectionEvent {
+    Clipboard(ClipboardEvent),
+    Dnd {
+        window: ffi::xcb_window_t,
+        mime_type: String,
+        data: Option<Vec<u8>>,
+    },
+}
+
+/// Implements both sides of the ICCCM selection protocol for the `CLIPBOARD` and `PRIMARY`
+/// selections. Both sides are also used for the `XdndSelection` to exchange the data of
+/// drag-and-drop operations.
+///
+/// All selections are owned by and all conversions are requested for a hidden window that is
+/// created together with the event loop.
+pub struct Clipboard {
+    xconn: Arc<XConnection>,
+    events: EventQueue,
+    pub atoms: SelectionAtoms,
+    window: ffi::xcb_window_t,
+    max_chunk_size: usize,
+    offers: RefCell<HashMap<ffi::xcb_atom_t, Offer>>,
+    outgoing: RefCell<Vec<OutgoingTransfer>>,
+    requests: RefCell<VecDeque<Request>>,
+    // The time at which the request at the front of `requests` fails. `None` if no request has
+    // been sent.
+    request_deadline: Cell<Option<Instant>>,
+    // The results of requests and offers that have failed outside of an event handler
+    failed: RefCell<Vec<SelectionEvent>>,
+    // Populated while an INCR transfer from another client is in progress
+    incoming: RefCell<Option<Vec<u8>>>,
+}
+
+impl Clipboard {
+    pub fn new(xconn: Arc<XConnection>, events: EventQueue) -> Self {
+        let atoms = SelectionAtoms::new(&xconn);
+        let screen = &xconn.screens[xconn.default_screen_id];
+        let window = xconn.generate_id();
+        unsafe {
+            let value_list = ffi::xcb_create_window_value_list_t {
+                event_mask: ffi::XCB_EVENT_MASK_PROPERTY_CHANGE,
+                ..Default::default()
+            };
+            let cookie = xconn.xcb.xcb_create_window_aux_checked(
+                xconn.c,
+                ffi::XCB_COPY_FROM_PARENT as _,
+                window,
+                screen.root,
+                0,
+                0,
+                1,
+                1,
+                0,
+                ffi::XCB_WINDOW_CLASS_INPUT_ONLY as u16,
+                ffi::XCB_COPY_FROM_PARENT as _,
+                ffi::XCB_CW_EVENT_MASK,
+                &value_list,
+            );
+            if let Err(e) = xconn.check_cookie(cookie) {
+                log::error!("Could not create the clipboard window: {}", e);
+            }
+        }
+        // Leave some room for the request header of `ChangeProperty`.
+        let max_request_size =
+            unsafe { xconn.xcb.xcb_get_maximum_request_length(xconn.c) as usize * 4 };
+        let max_chunk_size = max_request_size.saturating_sub(64).min(1 << 20).max(1024);
+        Clipboard {
+            xconn,
+            events,
+            atoms,
+            window,
+            max_chunk_size,
+            offers: Default::default(),
+            outgoing: Default::default(),
+            requests: Default::default(),
+            request_deadline: Cell::new(None),
+            failed: Default::default(),
+            incoming: Default::default(),
+        }
+    }
+
+    pub fn window(&self) -> ffi::xcb_window_t {
+        self.window
+    }
+
+    fn selection(&self, kind: ClipboardKind) -> ffi::xcb_atom_t {
+        match kind {
+            ClipboardKind::Clipboard => self.atoms.clipboard,
+            ClipboardKind::Primary => self.atoms.primary,
+        }
+    }
+
+    fn kind(&self, selection: ffi::xcb_atom_t) -> Option<ClipboardKind> {
+        if selection == self.atoms.clipboard {
+            Some(ClipboardKind::Clipboard)
+        } else if selection == self.atoms.primary {
+            Some(ClipboardKind::Primary)
+        } else {
+            None
+        }
+    }
+
+    fn mime_type_to_target(&self, mime_type: &str) -> ffi::xcb_atom_t {
+        if mime_type == TEXT_MIME_TYPE {
+            self.atoms.utf8_string
+        } else {
+            self.xconn.get_atom(mime_type)
+        }
+    }
+
+    /// Takes ownership of the selection and offers the data under the given MIME types.
+    pub fn set_data(
+        &self,
+        kind: ClipboardKind,
+        data: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), XcbError> {
+        self.offer(self.selection(kind), data).map(drop)
+    }
+
+    /// Takes ownership of the `XdndSelection` and offers the data of a drag under the given MIME
+    /// types. Returns the targets that the data can be converted to. They are empty if another
+    /// client has acquired the selection in the meantime.
+    pub fn set_dnd_data(
+        &self,
+        data: Vec<(String, Vec<u8>)>,
+    ) -> Result<Vec<ffi::xcb_atom_t>, XcbError> {
+        self.offer(self.atoms.xdnd_selection, data)
+    }
+
+    fn offer(
+        &self,
+        selection: ffi::xcb_atom_t,
+        data: Vec<(String, Vec<u8>)>,
+    ) -> Result<Vec<ffi::xcb_atom_t>, XcbError> {
+        let mut targets = Vec::with_capacity(data.len());
+        for (mime_type, bytes) in data {
+            let bytes: Rc<[u8]> = bytes.into();
+            if mime_type == TEXT_MIME_TYPE {
+                // Many X11 clients only understand the legacy names for UTF-8 text.
+                targets.push((self.atoms.utf8_string, bytes.clone()));
+                targets.push((self.atoms.text, bytes.clone()));
+            }
+            targets.push((self.xconn.get_atom(&mime_type), bytes));
+        }
+        // The ICCCM forbids acquiring a selection with `CurrentTime`.
+        let time = self.server_time()?;
+        if !self.set_selection_owner(self.window, selection, time)? {
+            // Another client has acquired the selection after us.
+            self.offers.borrow_mut().remove(&selection);
+            if let Some(kind) = self.kind(selection) {
+                self.failed.borrow_mut().push(SelectionEvent::Clipboard(
+                    ClipboardEvent::OwnershipLost { kind },
+                ));
+            }
+            return Ok(Vec::new());
+        }
+        let atoms = targets.iter().map(|&(target, _)| target).collect();
+        self.offers
+            .borrow_mut()
+            .insert(selection, Offer { targets, time });
+        Ok(atoms)
+    }
+
+    /// Returns the current server time. This is the time of the `PropertyNotify` event of an
+    /// empty append to a property of our window.
+    fn server_time(&self) -> Result<ffi::xcb_timestamp_t, XcbError> {
+        let pending = self.xconn.change_property(
+            self.window,
+            self.atoms.server_time,
+            ffi::XCB_ATOM_INTEGER,
+            util::PropMode::Append,
+            &[0u32; 0],
+        );
+        self.xconn.check_pending1(pending)?;
+        let event = self.events.wait_for_event(|event| {
+            if event.response_type & 0x7f != ffi::XCB_PROPERTY_NOTIFY {
+                return false;
+            }
+            let event = unsafe { &*(event as *const _ as *const ffi::xcb_property_notify_event_t) };
+            event.window == self.window && event.atom == self.atoms.server_time
+        });
+        let event = unsafe { &*(&*event as *const _ as *const ffi::xcb_property_notify_event_t) };
+        Ok(event.time)
+    }
+
+    /// Changes the owner of the selection and returns whether the change took effect. It does
+    /// not if the selection has changed owners after `time`.
+    fn set_selection_owner(
+        &self,
+        owner: ffi::xcb_window_t,
+        selection: ffi::xcb_atom_t,
+        time: ffi::xcb_timestamp_t,
+    ) -> Result<bool, XcbError> {
+        unsafe {
+            let cookie = self.xconn.xcb.xcb_set_selection_owner_checked(
+                self.xconn.c,
+                owner,
+                selection,
+                time,
+            );
+            self.xconn.check_cookie(cookie)?;
+            let cookie = self
+                .xconn
+                .xcb
+                .xcb_get_selection_owner(self.xconn.c, selection);
+            let mut err = ptr::null_mut();
+            let reply =
+                self.xconn
+                    .xcb
+                    .xcb_get_selection_owner_reply(self.xconn.c, cookie, &mut err);
+            let reply = self.xconn.check(reply, err)?;
+            Ok(reply.owner == owner)
+        }
+    }
+
+    /// Gives up ownership of the selection if we own it.
+    pub fn clear(&self, kind: ClipboardKind) -> Result<(), XcbError> {
+        self.clear_selection(self.selection(kind))
+    }
+
+    /// Gives up ownership of the `XdndSelection` if we own it.
+    pub fn clear_dnd_data(&self) -> Result<(), XcbError> {
+        self.clear_selection(self.atoms.xdnd_selection)
+    }
+
+    fn clear_selection(&self, selection: ffi::xcb_atom_t) -> Result<(), XcbError> {
+        let offer = match self.offers.borrow_mut().remove(&selection) {
+            Some(offer) => offer,
+            None => return Ok(()),
+        };
+        // The ICCCM requires the time of the acquisition.
+        self.set_selection_owner(ffi::XCB_NONE, selection, offer.time)
+            .map(drop)
+    }
+
+    /// Requests the contents of the selection. The result is reported via a `ClipboardEvent`.
+    ///
+    /// If `mime_type` is `None`, the list of offered MIME types is requested.
+    pub fn request(&self, kind: ClipboardKind, mime_type: Option<&str>) {
+        let target = match mime_type {
+            Some(mime_type) => self.mime_type_to_target(mime_type),
+            None => self.atoms.targets,
+        };
+        self.push_request(Request {
+            requester: Requester::Clipboard(kind),
+            selection: self.selection(kind),
+            target,
+            mime_type: mime_type.map(|m| m.to_owned()),
+            time: ffi::XCB_TIME_CURRENT_TIME,
+        });
+    }
+
+    /// Requests the data of a drag-and-drop operation over `window`. The result is reported via
+    /// `SelectionEvent::Dnd`.
+    pub fn request_dnd(
+        &self,
+        window: ffi::xcb_window_t,
+        mime_type: &str,
+        time: ffi::xcb_timestamp_t,
+    ) {
+        self.push_request(Request {
+            requester: Requester::Dnd(window),
+            selection: self.atoms.xdnd_selection,
+            target: self.mime_type_to_target(mime_type),
+            mime_type: Some(mime_type.to_owned()),
+            time,
+        });
+    }
+
+    /// Returns whether there are unanswered requests for the data of drag-and-drop operations.
+    pub fn has_dnd_requests(&self) -> bool {
+        self.requests
+            .borrow()
+            .iter()
+            .any(|r| matches!(r.requester, Requester::Dnd(_)))
+    }
+
+    fn push_request(&self, request: Request) {
+        self.requests.borrow_mut().push_back(request);
+        if self.request_deadline.get().is_none() {
+            self.send_next_request();
+        }
+    }
+
+    /// Sends the request at the front of the queue. Requests that cannot be sent fail.
+    fn send_next_request(&self) {
+        loop {
+            let pending = {
+                let requests = self.requests.borrow();
+                let request = match requests.front() {
+                    Some(request) => request,
+                    None => return,
+                };
+                unsafe {
+                    self.xconn
+                        .xcb
+                        .xcb_convert_selection_checked(
+                            self.xconn.c,
+                            self.window,
+                            request.selection,
+                            request.target,
+                            self.atoms.transfer,
+                            request.time,
+                        )
+                        .into()
+                }
+            };
+            match self.xconn.check_pending1(pending) {
+                Ok(()) => {
+                    self.request_deadline
+                        .set(Some(Instant::now() + REQUEST_TIMEOUT));
+                    return;
+                }
+                Err(e) => {
+                    log::error!("Could not request the selection contents: {}", e);
+                    if let Some(event) = self.pop_request(None) {
+                        self.failed.borrow_mut().push(event);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the time at which `handle_timeouts` has to be called.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        if !self.failed.borrow().is_empty() {
+            return Some(Instant::now());
+        }
+        self.outgoing
+            .borrow()
+            .iter()
+            .map(|t| t.deadline)
+            .chain(self.request_deadline.get())
+            .min()
+    }
+
+    /// Fails the request in flight if the owner of the selection has not answered in time and
+    /// abandons the INCR transfers whose requestors have stopped reading. Returns the results of
+    /// all requests and offers that have failed outside of an event handler.
+    pub fn handle_timeouts(&self) -> Vec<SelectionEvent> {
+        let now = Instant::now();
+        {
+            let mut outgoing = self.outgoing.borrow_mut();
+            while let Some(idx) = outgoing.iter().position(|t| t.deadline <= now) {
+                log::warn!(
+                    "{} did not read the selection data in time",
+                    outgoing[idx].requestor
+                );
+                self.stop_transfer(&mut outgoing, idx);
+            }
+        }
+        if let Some(deadline) = self.request_deadline.get() {
+            if deadline <= now {
+                log::warn!("The owner of the selection did not answer in time");
+                if let Some(event) = self.pop_request(None) {
+                    self.failed.borrow_mut().push(event);
+                }
+                self.send_next_request();
+            }
+        }
+        mem::take(&mut *self.failed.borrow_mut())
+    }
+
+    fn finish_request(&self, data: Option<Vec<u8>>) -> Option<SelectionEvent> {
+        let event = self.pop_request(data);
+        self.send_next_request();
+        event
+    }
+
+    /// Removes the request at the front of the queue and returns its result.
+    fn pop_request(&self, data: Option<Vec<u8>>) -> Option<SelectionEvent> {
+        self.request_deadline.set(None);
+        *self.incoming.borrow_mut() = None;
+        let request = self.requests.borrow_mut().pop_front();
+        request.map(|request| match (request.requester, request.mime_type) {
+            (Requester::Clipboard(kind), Some(mime_type)) => {
+                SelectionEvent::Clipboard(ClipboardEvent::Data {
+                    kind,
+                    mime_type,
+                    data,
+                })
+            }
+            (Requester::Clipboard(kind), None) => {
+                SelectionEvent::Clipboard(ClipboardEvent::MimeTypes {
+                    kind,
+                    mime_types: data.map(|d| self.parse_targets(&d)).unwrap_or_default(),
+                })
+            }
+            (Requester::Dnd(window), mime_type) => SelectionEvent::Dnd {
+                window,
+                mime_type: mime_type.unwrap_or_default(),
+                data,
+            },
+        })
+    }
+
+    fn parse_targets(&self, data: &[u8]) -> Vec<String> {
+        let targets: Vec<_> = data
+            .chunks_exact(mem::size_of::<ffi::xcb_atom_t>())
+            .map(|atom| ffi::xcb_atom_t::from_ne_bytes([atom[0], atom[1], atom[2], atom[3]]))
+            .collect();
+        self.targets_to_mime_types(&targets)
+    }
+
+    /// Converts a list of selection targets to MIME types. Targets that are not data types are
+    /// skipped.
+    pub fn targets_to_mime_types(&self, targets: &[ffi::xcb_atom_t]) -> Vec<String> {
+        let mut mime_types = Vec::new();
+        for &atom in targets {
+            if [
+                self.atoms.targets,
+                self.atoms.multiple,
+                self.atoms.timestamp,
+                self.atoms.save_targets,
+            ]
+            .contains(&atom)
+            {
+                continue;
+            }
+            let mime_type = if atom == self.atoms.utf8_string {
+                TEXT_MIME_TYPE.to_owned()
+            } else {
+                match self.xconn.get_atom_name(atom) {
+                    Ok(name) => name,
+                    Err(e) => {
+                        log::error!("Could not get the name of atom {}: {}", atom, e);
+                        continue;
+                    }
+                }
+            };
+            if !mime_types.contains(&mime_type) {
+                mime_types.push(mime_type);
+            }
+        }
+        mime_types
+    }
+
+    /// Handles a `SelectionNotify` event sent to our window in response to `ConvertSelection`.
+    pub fn handle_selection_notify(
+        &self,
+        xsel: &ffi::xcb_selection_notify_event_t,
+    ) -> Option<SelectionEvent> {
+        // Answers to requests that have timed out are ignored.
+        match self.requests.borrow().front() {
+            Some(request)
+                if self.request_deadline.get().is_some()
+                    && request.selection == xsel.selection
+                    && request.target == xsel.target => {}
+            _ => return None,
+        }
+        if xsel.property == ffi::XCB_ATOM_NONE {
+            // The selection has no owner or the owner refused the conversion.
+            return self.finish_request(None);
+        }
+        match self
+            .xconn
+            .get_property_raw(self.window, self.atoms.transfer, true)
+        {
+            Ok((property_type, _)) if property_type == self.atoms.incr => {
+                // Deleting the property above signals the owner to start the transfer.
+                *self.incoming.borrow_mut() = Some(Vec::new());
+                self.request_deadline
+                    .set(Some(Instant::now() + REQUEST_TIMEOUT));
+                None
+            }
+            Ok((_, data)) => self.finish_request(Some(data)),
+            Err(e) => {
+                log::error!("Could not read the selection contents: {}", e);
+                self.finish_request(None)
+            }
+        }
+    }
+
+    /// Handles a `PropertyNotify` event. This drives INCR transfers in both directions.
+    pub fn handle_property_notify(
+        &self,
+        xev: &ffi::xcb_property_notify_event_t,
+    ) -> Option<SelectionEvent> {
+        if xev.window == self.window {
+            if xev.atom != self.atoms.transfer
+                || xev.state != ffi::XCB_PROPERTY_NEW_VALUE as u8
+                || self.incoming.borrow().is_none()
+            {
+                return None;
+            }
+            let chunk = match self
+                .xconn
+                .get_property_raw(self.window, self.atoms.transfer, true)
+            {
+                Ok((_, chunk)) => chunk,
+                Err(e) => {
+                    log::error!("Could not read the selection contents: {}", e);
+                    return self.finish_request(None);
+                }
+            };
+            if chunk.is_empty() {
+                let data = self.incoming.borrow_mut().take();
+                return self.finish_request(data);
+            }
+            if let Some(data) = &mut *self.incoming.borrow_mut() {
+                data.extend_from_slice(&chunk);
+            }
+            self.request_deadline
+                .set(Some(Instant::now() + REQUEST_TIMEOUT));
+        } else if xev.state == ffi::XCB_PROPERTY_DELETE as u8 {
+            self.continue_transfer(xev.window, xev.atom);
+        }
+        None
+    }
+
+    fn continue_transfer(&self, requestor: ffi::xcb_window_t, property: ffi::xcb_atom_t) {
+        let mut outgoing = self.outgoing.borrow_mut();
+        let idx = match outgoing
+            .iter()
+            .position(|t| t.requestor == requestor && t.property == property)
+        {
+            Some(idx) => idx,
+            None => return,
+        };
+        let transfer = &mut outgoing[idx];
+        let end = (transfer.offset + self.max_chunk_size).min(transfer.data.len());
+        let chunk = &transfer.data[transfer.offset..end];
+        let finished = chunk.is_empty();
+        transfer.offset = end;
+        transfer.deadline = Instant::now() + REQUEST_TIMEOUT;
+        let pending = self.xconn.change_property(
+            requestor,
+            property,
+            transfer.target,
+            util::PropMode::Replace,
+            chunk,
+        );
+        if let Err(e) = self.xconn.check_pending1(pending) {
+            log::error!("Could not send selection data to {}: {}", requestor, e);
+            self.stop_transfer(&mut outgoing, idx);
+        } else if finished {
+            self.stop_transfer(&mut outgoing, idx);
+        }
+    }
+
+    fn stop_transfer(&self, outgoing: &mut Vec<OutgoingTransfer>, idx: usize) {
+        let transfer = outgoing.swap_remove(idx);
+        if outgoing.iter().all(|t| t.requestor != transfer.requestor) {
+            self.select_property_events(transfer.requestor, false);
+        }
+    }
+
+    fn select_property_events(&self, window: ffi::xcb_window_t, select: bool) -> bool {
+        let mask = if select {
+            ffi::XCB_EVENT_MASK_PROPERTY_CHANGE
+        } else {
+            ffi::XCB_EVENT_MASK_NO_EVENT
+        };
+        unsafe {
+            let cookie = self.xconn.xcb.xcb_change_window_attributes_checked(
+                self.xconn.c,
+                window,
+                ffi::XCB_CW_EVENT_MASK,
+                &mask as *const _ as _,
+            );
+            match self.xconn.check_cookie(cookie) {
+                Ok(()) => true,
+                Err(e) => {
+                    log::error!("Could not select property events on {}: {}", window, e);
+                    false
+                }
+            }
+        }
+    }
+
+    /// Handles a `SelectionRequest` event sent to us as the owner of a selection.
+    pub fn handle_selection_request(&self, xev: &ffi::xcb_selection_request_event_t) {
+        // Obsolete clients use `None` as the property.
+        let property = if xev.property == ffi::XCB_ATOM_NONE {
+            xev.target
+        } else {
+            xev.property
+        };
+        let success = match self.offers.borrow().get(&xev.selection) {
+            Some(offer) => self.convert(xev.requestor, property, xev.target, offer),
+            None => false,
+        };
+        let notify = ffi::xcb_selection_notify_event_t {
+            response_type: ffi::XCB_SELECTION_NOTIFY,
+            time: xev.time,
+            requestor: xev.requestor,
+            selection: xev.selection,
+            target: xev.target,
+            property: if success {
+                property
+            } else {
+                ffi::XCB_ATOM_NONE
+            },
+            ..Default::default()
+        };
+        let pending = self.xconn.send_event(xev.requestor, None, &notify);
+        if let Err(e) = self.xconn.check_pending1(pending) {
+            log::error!("Could not send SelectionNotify to {}: {}", xev.requestor, e);
+        }
+    }
+
+    fn convert(
+        &self,
+        requestor: ffi::xcb_window_t,
+        property: ffi::xcb_atom_t,
+        target: ffi::xcb_atom_t,
+        offer: &Offer,
+    ) -> bool {
+        let pending = if target == self.atoms.targets {
+            let mut targets = vec![self.atoms.targets, self.atoms.timestamp];
+            targets.extend(offer.targets.iter().map(|&(target, _)| target));
+            self.xconn.change_property(
+                requestor,
+                property,
+                ffi::XCB_ATOM_ATOM,
+                util::PropMode::Replace,
+                &targets,
+            )
+        } else if target == self.atoms.timestamp {
+            self.xconn.change_property(
+                requestor,
+                property,
+                ffi::XCB_ATOM_INTEGER,
+                util::PropMode::Replace,
+                &[offer.time],
+            )
+        } else {
+            let data = match offer.targets.iter().find(|&&(t, _)| t == target) {
+                Some((_, data)) => data,
+                None => return false,
+            };
+            if data.len() > self.max_chunk_size {
+                if !self.select_property_events(requestor, true) {
+                    return false;
+                }
+                self.outgoing.borrow_mut().push(OutgoingTransfer {
+                    requestor,
+                    property,
+                    target,
+                    data: data.clone(),
+                    offset: 0,
+                    deadline: Instant::now() + REQUEST_TIMEOUT,
+                });
+                self.xconn.change_property(
+                    requestor,
+                    property,
+                    self.atoms.incr,
+                    util::PropMode::Replace,
+                    &[data.len() as u32],
+                )
+            } else {
+                self.xconn.change_property(
+                    requestor,
+                    property,
+                    target,
+                    util::PropMode::Replace,
+                    &data[..],
+                )
+            }
+        };
+        match self.xconn.check_pending1(pending) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("Could not convert selection for {}: {}", requestor, e);
+                false
+            }
+        }
+    }
+
+    /// Handles a `SelectionClear` event. Returns an event if we lost ownership of a clipboard.
+    pub fn handle_selection_clear(
+        &self,
+        xev: &ffi::xcb_selection_clear_event_t,
+    ) -> Option<ClipboardEvent> {
+        if xev.owner != self.window {
+            return None;
+        }
+        self.offers.borrow_mut().remove(&xev.selection)?;
+        self.kind(xev.selection)
+            .map(|kind| ClipboardEvent::OwnershipLost { kind })
+    }
+}
+
+impl Drop for Clipboard {
+    fn drop(&mut self) {
+        unsafe {
+            let cookie = self
+                .xconn
+                .xcb
+                .xcb_destroy_window_checked(self.xconn.c, self.window);
+            if let Err(e) = self.xconn.check_cookie(cookie) {
+                log::error!("Could not destroy the clipboard window: {}", e);
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use std::{
+    cell::Cell,
     io,
     os::raw::*,
     path::{Path, PathBuf},
@@ -9,6 +10,7 @@ use std::{
 use percent_encoding::percent_decode;
 
 use super::{ffi, XConnection};
+use crate::event::DndAction;
 use xcb_dl_util::error::XcbError;
 use xcb_dl_util::property::XcbGetPropertyError;
 
@@ -20,7 +22,9 @@ pub struct DndAtoms {
     pub drop: ffi::xcb_atom_t,
     pub position: ffi::xcb_atom_t,
     pub status: ffi::xcb_atom_t,
-    pub action_private: ffi::xcb_atom_t,
+    pub action_copy: ffi::xcb_atom_t,
+    pub action_move: ffi::xcb_atom_t,
+    pub action_link: ffi::xcb_atom_t,
     pub selection: ffi::xcb_atom_t,
     pub finished: ffi::xcb_atom_t,
     pub type_list: ffi::xcb_atom_t,
@@ -37,7 +41,9 @@ impl DndAtoms {
             drop: xconn.get_atom("XdndDrop"),
             position: xconn.get_atom("XdndPosition"),
             status: xconn.get_atom("XdndStatus"),
-            action_private: xconn.get_atom("XdndActionPrivate"),
+            action_copy: xconn.get_atom("XdndActionCopy"),
+            action_move: xconn.get_atom("XdndActionMove"),
+            action_link: xconn.get_atom("XdndActionLink"),
             selection: xconn.get_atom("XdndSelection"),
             finished: xconn.get_atom("XdndFinished"),
             type_list: xconn.get_atom("XdndTypeList"),
@@ -45,12 +51,37 @@ impl DndAtoms {
             none: xconn.get_atom("None"),
         }
     }
+
+    pub fn action_from_atom(&self, atom: ffi::xcb_atom_t) -> DndAction {
+        // `XdndActionAsk` and `XdndActionPrivate` are treated as copies.
+        if atom == self.action_move {
+            DndAction::Move
+        } else if atom == self.action_link {
+            DndAction::Link
+        } else {
+            DndAction::Copy
+        }
+    }
+
+    /// Returns the `accepted` flag and the action atom of `XdndStatus` and `XdndFinished`.
+    pub fn action_to_atom(&self, action: Option<DndAction>) -> (u32, ffi::xcb_atom_t) {
+        match action {
+            Some(DndAction::Copy) => (1, self.action_copy),
+            Some(DndAction::Move) => (1, self.action_move),
+            Some(DndAction::Link) => (1, self.action_link),
+            None => (0, self.none),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum DndState {
-    Accepted,
-    Rejected,
+/// The part of the drag-and-drop state that the application accesses through the
+/// `EventLoopWindowTarget`.
+#[derive(Debug, Default)]
+pub struct DndContext {
+    /// The window that the drag is over together with the time of the last XDND message.
+    pub target: Cell<Option<(ffi::xcb_window_t, ffi::xcb_timestamp_t)>>,
+    /// The action with which a drop at the current position is accepted.
+    pub action: Cell<Option<DndAction>>,
 }
 
 #[derive(Debug)]
@@ -82,8 +113,12 @@ pub struct Dnd {
     pub type_list: Option<Vec<ffi::xcb_atom_t>>,
     // Populated by XdndPosition event handler
     pub source_window: Option<ffi::xcb_window_t>,
+    // Set when the `text/uri-list` conversion has been requested
+    pub converting: bool,
     // Populated by SelectionNotify event handler (triggered by XdndPosition event handler)
     pub result: Option<Result<Vec<PathBuf>, DndDataParseError>>,
+    // Set by XdndDrop event handler while data requested by the application is outstanding
+    pub dropped: Option<Option<DndAction>>,
 }
 
 impl Dnd {
@@ -95,7 +130,9 @@ impl Dnd {
             version: None,
             type_list: None,
             source_window: None,
+            converting: false,
             result: None,
+            dropped: None,
         }
     }
 
@@ -103,19 +140,25 @@ impl Dnd {
         self.version = None;
         self.type_list = None;
         self.source_window = None;
+        self.converting = false;
         self.result = None;
+        self.dropped = None;
+    }
+
+    pub fn offers_files(&self) -> bool {
+        match &self.type_list {
+            Some(type_list) => type_list.contains(&self.atoms.uri_list),
+            None => false,
+        }
     }
 
     pub unsafe fn send_status(
         &self,
         this_window: ffi::xcb_window_t,
         target_window: ffi::xcb_window_t,
-        state: DndState,
+        action: Option<DndAction>,
     ) {
-        let (accepted, action) = match state {
-            DndState::Accepted => (1, self.atoms.action_private),
-            DndState::Rejected => (0, self.atoms.none),
-        };
+        let (accepted, action) = self.atoms.action_to_atom(action);
         let pending = self.xconn.send_client_msg(
             target_window,
             target_window,
@@ -130,12 +173,9 @@ impl Dnd {
         &self,
         this_window: ffi::xcb_window_t,
         target_window: ffi::xcb_window_t,
-        state: DndState,
+        action: Option<DndAction>,
     ) -> Result<(), XcbError> {
-        let (accepted, action) = match state {
-            DndState::Accepted => (1, self.atoms.action_private),
-            DndState::Rejected => (0, self.atoms.none),
-        };
+        let (accepted, action) = self.atoms.action_to_atom(action);
         let pending = self.xconn.send_client_msg(
             target_window,
             target_window,
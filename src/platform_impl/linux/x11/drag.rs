@@ -0,0 +1,351 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use super::{
+    dnd::DndAtoms,
+    ffi,
+    util::{self, PropMode},
+    XConnection,
+};
+use crate::{event::DndAction, window::CursorIcon};
+
+/// If the target has not sent `XdndFinished` within this time after the drop, we end the drag.
+const FINISHED_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A request from a window to start a drag-and-drop operation.
+pub struct DragRequest {
+    pub window: ffi::xcb_window_t,
+    pub data: Vec<(String, Vec<u8>)>,
+    pub actions: Vec<DndAction>,
+    pub icon: Option<CursorIcon>,
+    /// The scale factor of the window, used to size the cursor.
+    pub scale_factor: f64,
+}
+
+/// The XDND-aware window that the drag is currently over.
+struct Target {
+    window: ffi::xcb_window_t,
+    version: u32,
+    // Set while an `XdndPosition` message has not been answered
+    awaiting_status: bool,
+    // The last position that could not be sent because the target had not answered yet
+    pending_position: Option<(i16, i16, ffi::xcb_timestamp_t)>,
+    // The action of the last `XdndStatus` message if the drop is accepted
+    action: Option<DndAction>,
+}
+
+/// Implements the source side of the XDND protocol.
+///
+/// The `XdndSelection` is owned by the clipboard window which therefore identifies us in all
+/// XDND messages. The pointer is grabbed for the window that started the drag until the button
+/// is released.
+pub struct Drag {
+    xconn: Arc<XConnection>,
+    atoms: DndAtoms,
+    /// The window that started the drag.
+    pub window: ffi::xcb_window_t,
+    source: ffi::xcb_window_t,
+    root: ffi::xcb_window_t,
+    types: Vec<ffi::xcb_atom_t>,
+    actions: Vec<DndAction>,
+    icon: Option<CursorIcon>,
+    scale_factor: f64,
+    cursor: Option<CursorIcon>,
+    target: Option<Target>,
+    // The time at which the button was released
+    released: Option<ffi::xcb_timestamp_t>,
+    // Set once `XdndDrop` has been sent. Contains the time at which we stop waiting for
+    // `XdndFinished`.
+    dropped: Option<Instant>,
+}
+
+impl Drag {
+    /// Grabs the pointer for `request.window`.
+    ///
+    /// `source` must own the `XdndSelection` and offer the data as `types`.
+    pub fn start(
+        xconn: Arc<XConnection>,
+        request: DragRequest,
+        source: ffi::xcb_window_t,
+        root: ffi::xcb_window_t,
+        types: Vec<ffi::xcb_atom_t>,
+    ) -> Option<Self> {
+        let atoms = DndAtoms::new(&xconn);
+        if types.len() > 3 {
+            let pending = xconn.change_property(
+                source,
+                atoms.type_list,
+                ffi::XCB_ATOM_ATOM,
+                PropMode::Replace,
+                &types,
+            );
+            if let Err(e) = xconn.check_pending1(pending) {
+                log::error!("Could not set the XdndTypeList property: {}", e);
+                return None;
+            }
+        }
+        let mut actions = request.actions;
+        if actions.is_empty() {
+            actions.push(DndAction::Copy);
+        }
+        let mut drag = Drag {
+            xconn,
+            atoms,
+            window: request.window,
+            source,
+            root,
+            types,
+            actions,
+            icon: request.icon,
+            scale_factor: request.scale_factor,
+            cursor: None,
+            target: None,
+            released: None,
+            dropped: None,
+        };
+        if !drag.set_cursor(CursorIcon::NoDrop) {
+            return None;
+        }
+        Some(drag)
+    }
+
+    fn set_cursor(&mut self, cursor: CursorIcon) -> bool {
+        if self.cursor == Some(cursor) {
+            return true;
+        }
+        let xcursor = self
+            .xconn
+            .get_cached_cursor(Some(cursor), self.scale_factor);
+        let status =
+            self.xconn
+                .grab_pointer_device(self.window, util::VIRTUAL_CORE_POINTER, xcursor);
+        match status {
+            Ok(ffi::XCB_GRAB_STATUS_SUCCESS) => {
+                self.cursor = Some(cursor);
+                true
+            }
+            Ok(status) => {
+                log::error!("Could not grab the pointer for the drag: status {}", status);
+                false
+            }
+            Err(e) => {
+                log::error!("Could not grab the pointer for the drag: {}", e);
+                false
+            }
+        }
+    }
+
+    fn update_cursor(&mut self) {
+        let action = self.target.as_ref().and_then(|t| t.action);
+        let cursor = match (action, self.icon) {
+            (None, _) => CursorIcon::NoDrop,
+            (Some(_), Some(icon)) => icon,
+            (Some(DndAction::Copy), None) => CursorIcon::Copy,
+            (Some(DndAction::Move), None) => CursorIcon::Move,
+            (Some(DndAction::Link), None) => CursorIcon::Alias,
+        };
+        self.set_cursor(cursor);
+    }
+
+    fn send(&self, window: ffi::xcb_window_t, message_type: ffi::xcb_atom_t, data: [u32; 5]) {
+        let pending = self
+            .xconn
+            .send_client_msg(window, window, message_type, None, data);
+        if let Err(e) = self.xconn.check_pending1(pending) {
+            log::warn!("Could not send an XDND message to {}: {}", window, e);
+        }
+    }
+
+    /// Returns the XDND-aware window at the position and its protocol version.
+    fn find_target(&self, x: i16, y: i16) -> Option<(ffi::xcb_window_t, u32)> {
+        let mut window = self.root;
+        loop {
+            window = match self.xconn.child_at(self.root, window, x, y) {
+                Ok(0) => return None,
+                Ok(child) => child,
+                Err(e) => {
+                    log::warn!("Could not find the window under the pointer: {}", e);
+                    return None;
+                }
+            };
+            let version = self
+                .xconn
+                .get_property::<u32>(window, self.atoms.aware, ffi::XCB_ATOM_ATOM)
+                .ok()
+                .and_then(|v| v.first().copied());
+            if let Some(version) = version {
+                // Versions before 3 do not transmit the types in `XdndEnter`.
+                if version >= 3 {
+                    return Some((window, version.min(5)));
+                }
+                return None;
+            }
+        }
+    }
+
+    /// Handles a motion of the grabbed pointer to the root coordinates `x`x`y`.
+    pub fn handle_motion(&mut self, x: i16, y: i16, time: ffi::xcb_timestamp_t) {
+        if self.released.is_some() {
+            return;
+        }
+        let new_target = self.find_target(x, y);
+        if self.target.as_ref().map(|t| t.window) != new_target.map(|t| t.0) {
+            if let Some(target) = self.target.take() {
+                self.send(target.window, self.atoms.leave, [self.source, 0, 0, 0, 0]);
+            }
+            if let Some((window, version)) = new_target {
+                let more_types = (self.types.len() > 3) as u32;
+                let mut data = [self.source, (version << 24) | more_types, 0, 0, 0];
+                for (slot, &ty) in data[2..].iter_mut().zip(&self.types) {
+                    *slot = ty;
+                }
+                self.send(window, self.atoms.enter, data);
+                self.target = Some(Target {
+                    window,
+                    version,
+                    awaiting_status: false,
+                    pending_position: None,
+                    action: None,
+                });
+            }
+            self.update_cursor();
+        }
+        if let Some(target) = &mut self.target {
+            if target.awaiting_status {
+                target.pending_position = Some((x, y, time));
+            } else {
+                self.send_position(x, y, time);
+            }
+        }
+    }
+
+    fn send_position(&mut self, x: i16, y: i16, time: ffi::xcb_timestamp_t) {
+        let (_, action) = self.atoms.action_to_atom(Some(self.actions[0]));
+        if let Some(target) = &mut self.target {
+            target.awaiting_status = true;
+            let window = target.window;
+            let position = ((x as u16 as u32) << 16) | y as u16 as u32;
+            self.send(
+                window,
+                self.atoms.position,
+                [self.source, 0, position, time, action],
+            );
+        }
+    }
+
+    /// Handles an `XdndStatus` message.
+    ///
+    /// Returns `Some` with the result of the drag if it has ended.
+    pub fn handle_status(&mut self, data: [u32; 5]) -> Option<Option<DndAction>> {
+        let (atoms, actions) = (&self.atoms, &self.actions);
+        let target = match &mut self.target {
+            Some(target) if target.window == data[0] && target.awaiting_status => target,
+            _ => return None,
+        };
+        target.awaiting_status = false;
+        // Actions that we do not allow are treated as a rejection.
+        target.action = match data[1] & 1 {
+            0 => None,
+            _ if target.version < 2 => Some(actions[0]),
+            _ => Some(atoms.action_from_atom(data[4])),
+        }
+        .filter(|action| actions.contains(action));
+        let pending_position = target.pending_position.take();
+        self.update_cursor();
+        if let Some(time) = self.released {
+            return self.do_drop(time);
+        }
+        if let Some((x, y, time)) = pending_position {
+            self.send_position(x, y, time);
+        }
+        None
+    }
+
+    /// Handles the release of the button that started the drag.
+    ///
+    /// Returns `Some` with the result of the drag if it has ended.
+    pub fn handle_release(&mut self, time: ffi::xcb_timestamp_t) -> Option<Option<DndAction>> {
+        if self.released.is_some() {
+            return None;
+        }
+        self.released = Some(time);
+        self.ungrab();
+        match &self.target {
+            // The drop happens once the target has answered the last position.
+            Some(target) if target.awaiting_status => None,
+            _ => self.do_drop(time),
+        }
+    }
+
+    fn do_drop(&mut self, time: ffi::xcb_timestamp_t) -> Option<Option<DndAction>> {
+        let target = match &self.target {
+            Some(target) => target,
+            None => return Some(None),
+        };
+        if target.action.is_some() {
+            self.send(target.window, self.atoms.drop, [self.source, 0, time, 0, 0]);
+            self.dropped = Some(Instant::now() + FINISHED_TIMEOUT);
+            None
+        } else {
+            self.send(target.window, self.atoms.leave, [self.source, 0, 0, 0, 0]);
+            Some(None)
+        }
+    }
+
+    /// Handles an `XdndFinished` message.
+    ///
+    /// Returns `Some` with the result of the drag if it has ended.
+    pub fn handle_finished(&mut self, data: [u32; 5]) -> Option<Option<DndAction>> {
+        let target = match &self.target {
+            Some(target) if target.window == data[0] && self.dropped.is_some() => target,
+            _ => return None,
+        };
+        let action = if target.version < 5 {
+            target.action
+        } else if data[1] & 1 == 1 {
+            Some(self.atoms.action_from_atom(data[2]))
+        } else {
+            None
+        };
+        Some(action)
+    }
+
+    /// Returns the time at which `handle_timeout` has to be called.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.dropped
+    }
+
+    /// Gives up on a target that has not answered the drop in time.
+    ///
+    /// Returns `Some` with the result of the drag if it has ended.
+    pub fn handle_timeout(&mut self) -> Option<Option<DndAction>> {
+        match self.dropped {
+            Some(deadline) if deadline <= Instant::now() => {
+                log::warn!("The drop target did not finish the drop in time");
+                Some(None)
+            }
+            _ => None,
+        }
+    }
+
+    /// Aborts the drag without dropping the data.
+    pub fn cancel(&mut self) {
+        if self.released.is_none() {
+            self.ungrab();
+        }
+        if let Some(target) = self.target.take() {
+            if self.dropped.is_none() {
+                self.send(target.window, self.atoms.leave, [self.source, 0, 0, 0, 0]);
+            }
+        }
+    }
+
+    fn ungrab(&self) {
+        let pending = self.xconn.ungrab_pointer_device(util::VIRTUAL_CORE_POINTER);
+        if let Err(e) = self.xconn.check_pending1(pending) {
+            log::error!("Could not ungrab the pointer after the drag: {}", e);
+        }
+    }
+}
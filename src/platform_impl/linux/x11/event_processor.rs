@@ -1,22 +1,32 @@
 use std::sync::atomic::Ordering::Relaxed;
-use std::{collections::HashMap, rc::Rc, slice, sync::Arc};
+use std::{
+    collections::HashMap, mem, rc::Rc, slice, sync::mpsc::Receiver, sync::Arc, time::Instant,
+};
 
 use parking_lot::MutexGuard;
 use SeatFocus::{KbFocus, PtrFocus};
 
 use super::{
-    ffi, get_xtarget, mkdid, mkwid, util, Device, DeviceId, DeviceInfo, Dnd, DndState,
+    clipboard::SelectionEvent,
+    drag::{Drag, DragRequest},
+    ffi, gesture_ffi, get_xtarget,
+    ime::{self, Ime, ImeEvent, ImeRequest},
+    mkdid, mkwid, util, Device, DeviceId, DeviceInfo, Dnd, MonitorHandle, PenDevice,
     ScrollOrientation, UnownedWindow, WindowId,
 };
 
 use crate::{
     dpi::{PhysicalPosition, PhysicalSize},
-    event::{DeviceEvent, Event, KeyEvent, RawKeyEvent, TouchPhase, WindowEvent},
+    event::{
+        DeviceEvent, DndAction, DndEvent, ElementState, Event, KeyEvent, PenButton, PenPhase,
+        RawKeyEvent, Timestamp, TouchPhase, WindowEvent,
+    },
     event_loop::EventLoopWindowTarget as RootELW,
     keyboard::ModifiersState,
+    monitor::MonitorHandle as RootMonitorHandle,
     platform_impl::platform::{
         common::{keymap, xkb_state::KbState},
-        KeyEventExtra,
+        KeyEventExtra, MonitorHandle as PlatformMonitorHandle,
     },
     platform_impl::x11::EventLoopWindowTarget,
 };
@@ -36,6 +46,8 @@ pub(super) struct Seat {
     ptr_focus: Option<ffi::xcb_window_t>,
     /// The latest modifiers state
     current_modifiers: ModifiersState,
+    /// The scale of the current pinch gesture
+    pinch_scale: f64,
 
     num_errors: usize,
 }
@@ -53,6 +65,13 @@ pub(super) struct EventProcessor<T: 'static> {
     // Number of touch events currently in progress
     pub(super) num_touch: u32,
     pub(super) first_touch: Option<u64>,
+    pub(super) ime: Ime,
+    pub(super) ime_receiver: Receiver<ImeRequest>,
+    // The drag that one of our windows has started
+    pub(super) drag: Option<Drag>,
+    pub(super) drag_receiver: Receiver<DragRequest>,
+    // The monitors that were reported by the last RandR event
+    pub(super) monitors: Vec<MonitorHandle>,
 }
 
 impl<T: 'static> EventProcessor<T> {
@@ -67,7 +86,7 @@ impl<T: 'static> EventProcessor<T> {
             for info in info {
                 let info = unsafe { &*info };
                 let device_id = DeviceId(info.deviceid);
-                if info.type_ == ffi::XCB_INPUT_DEVICE_TYPE_MASTER_KEYBOARD as _ {
+                if info.type_ == ffi::XCB_INPUT_DEVICE_TYPE_MASTER_KEYBOARD as u16 {
                     if devices.contains_key(&device_id) {
                         seats.retain(|s| s.keyboard != info.deviceid);
                     }
@@ -88,12 +107,34 @@ impl<T: 'static> EventProcessor<T> {
                         kb_focus: None,
                         ptr_focus: None,
                         current_modifiers: ModifiersState::empty(),
+                        pinch_scale: 1.0,
                         num_errors: 0,
                     });
                 }
                 devices.insert(device_id, Device::new(wt, info));
             }
         }
+        Self::select_proximity_events(wt, devices);
+    }
+
+    /// Selects the proximity-out events of all tablet tools on the root windows.
+    ///
+    /// XInput2 has no proximity events and XInput 1 selections replace each other, so all tools
+    /// have to be selected at once.
+    fn select_proximity_events(wt: &EventLoopWindowTarget<T>, devices: &HashMap<DeviceId, Device>) {
+        let proximity_out =
+            (wt.xconn.xinput_first_event + ffi::XCB_INPUT_PROXIMITY_OUT as u8) as u32;
+        let classes: Vec<_> = devices
+            .iter()
+            .filter(|(_, device)| device.pen.is_some())
+            .map(|(id, _)| (id.0 as u32) << 8 | proximity_out)
+            .collect();
+        for screen in &wt.xconn.screens {
+            let pending = wt.xconn.select_xi1_events(screen.root, &classes);
+            if let Err(e) = wt.xconn.check_pending1(pending) {
+                log::error!("Cannot listen for proximity events of tablet tools: {}", e);
+            }
+        }
     }
 
     fn with_window<F, Ret>(
@@ -127,6 +168,234 @@ impl<T: 'static> EventProcessor<T> {
         Self::with_window(wt, window_id, |_| ()).is_some()
     }
 
+    /// Handles the requests that windows have sent to the input method.
+    pub(super) fn process_ime_requests<F>(&mut self, mut callback: F)
+    where
+        F: FnMut(Event<'_, T>),
+    {
+        let wt = get_xtarget(&self.target);
+        while let Ok(request) = self.ime_receiver.try_recv() {
+            self.ime.handle_request(request);
+        }
+        Self::dispatch_ime_events(wt, &mut self.ime, &mut self.seats, &mut callback);
+    }
+
+    fn dispatch_ime_events<F>(
+        wt: &EventLoopWindowTarget<T>,
+        ime: &mut Ime,
+        seats: &mut [Seat],
+        callback: &mut F,
+    ) where
+        F: FnMut(Event<'_, T>),
+    {
+        while let Some(event) = ime.next_event() {
+            match event {
+                ImeEvent::Key(key) => Self::process_key(wt, seats, &key, callback),
+                ImeEvent::Ime(window, event) => {
+                    if Self::window_exists(wt, window) {
+                        callback(Event::WindowEvent {
+                            window_id: mkwid(window),
+                            event: WindowEvent::Ime(event),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Starts the drags that windows have requested.
+    pub(super) fn process_drag_requests<F>(&mut self, mut callback: F)
+    where
+        F: FnMut(Event<'_, T>),
+    {
+        let wt = get_xtarget(&self.target);
+        while let Ok(mut request) = self.drag_receiver.try_recv() {
+            // Only one drag can be in progress at a time.
+            if let Some(mut drag) = self.drag.take() {
+                drag.cancel();
+                Self::finish_drag(wt, drag.window, None, &mut callback);
+            }
+            let window = request.window;
+            let root = Self::with_window(wt, window, |w| w.screen.root);
+            let types = match wt.clipboard.set_dnd_data(mem::take(&mut request.data)) {
+                Ok(types) => types,
+                Err(e) => {
+                    log::error!("Could not take ownership of the XdndSelection: {}", e);
+                    Vec::new()
+                }
+            };
+            self.drag = match (root, types.is_empty()) {
+                (Some(root), false) => Drag::start(
+                    wt.xconn.clone(),
+                    request,
+                    wt.clipboard.window(),
+                    root,
+                    types,
+                ),
+                _ => None,
+            };
+            if self.drag.is_none() {
+                Self::finish_drag(wt, window, None, &mut callback);
+            }
+        }
+    }
+
+    /// Returns the time at which `process_timeouts` has to be called.
+    pub(super) fn next_deadline(&self) -> Option<Instant> {
+        let wt = get_xtarget(&self.target);
+        [
+            wt.clipboard.next_deadline(),
+            self.ime.next_deadline(),
+            self.drag.as_ref().and_then(|drag| drag.next_deadline()),
+        ]
+        .iter()
+        .flatten()
+        .copied()
+        .min()
+    }
+
+    /// Fails the operations whose peers have not answered in time.
+    pub(super) fn process_timeouts<F>(&mut self, mut callback: F)
+    where
+        F: FnMut(Event<'_, T>),
+    {
+        let wt = get_xtarget(&self.target);
+        for event in wt.clipboard.handle_timeouts() {
+            Self::dispatch_selection_event(wt, &mut self.dnd, event, &mut callback);
+        }
+        self.ime.handle_timeouts();
+        Self::dispatch_ime_events(wt, &mut self.ime, &mut self.seats, &mut callback);
+        if let Some(action) = self.drag.as_mut().and_then(|drag| drag.handle_timeout()) {
+            let drag = self.drag.take().unwrap();
+            Self::finish_drag(wt, drag.window, action, &mut callback);
+        }
+    }
+
+    /// Releases the data of a drag that has ended and reports the result to the window that
+    /// started it.
+    fn finish_drag<F>(
+        wt: &EventLoopWindowTarget<T>,
+        window: ffi::xcb_window_t,
+        action: Option<DndAction>,
+        callback: &mut F,
+    ) where
+        F: FnMut(Event<'_, T>),
+    {
+        if let Err(e) = wt.clipboard.clear_dnd_data() {
+            log::error!("Could not give up ownership of the XdndSelection: {}", e);
+        }
+        if Self::window_exists(wt, window) {
+            callback(Event::WindowEvent {
+                window_id: mkwid(window),
+                event: WindowEvent::DragFinished { action },
+            });
+        }
+    }
+
+    /// Emits the result of a selection request.
+    fn dispatch_selection_event<F>(
+        wt: &EventLoopWindowTarget<T>,
+        dnd: &mut Dnd,
+        event: SelectionEvent,
+        callback: &mut F,
+    ) where
+        F: FnMut(Event<'_, T>),
+    {
+        match event {
+            SelectionEvent::Clipboard(event) => callback(Event::Clipboard(event)),
+            SelectionEvent::Dnd {
+                window,
+                mime_type,
+                data,
+            } => {
+                callback(Event::WindowEvent {
+                    window_id: mkwid(window),
+                    event: WindowEvent::DragAndDrop(DndEvent::Data { mime_type, data }),
+                });
+                if dnd.dropped.is_some() && !wt.clipboard.has_dnd_requests() {
+                    Self::finish_drop(wt, dnd);
+                }
+            }
+        }
+    }
+
+    /// Sends `XdndFinished` if a drop is in progress and forgets the current drag.
+    fn finish_drop(wt: &EventLoopWindowTarget<T>, dnd: &mut Dnd) {
+        if let (Some(action), Some(source_window), Some((window, _))) =
+            (dnd.dropped, dnd.source_window, wt.dnd.target.get())
+        {
+            unsafe {
+                dnd.send_finished(window, source_window, action)
+                    .expect("Failed to send `XdndFinished` message.");
+            }
+        }
+        dnd.reset();
+        wt.dnd.target.set(None);
+    }
+
+    /// Processes a key event that has passed through the input method.
+    fn process_key<F>(
+        wt: &EventLoopWindowTarget<T>,
+        seats: &mut [Seat],
+        key: &ime::Key,
+        callback: &mut F,
+    ) where
+        F: FnMut(Event<'_, T>),
+    {
+        let seat = match find_seat(seats, key.deviceid) {
+            Some(seat) => seat,
+            _ => return,
+        };
+        if let Some(xi) = &key.xi_state {
+            Self::update_seat_kb(
+                seat,
+                xi.base_mods,
+                xi.latched_mods,
+                xi.locked_mods,
+                xi.base_group,
+                xi.latched_group,
+                xi.locked_group,
+                callback,
+            );
+        }
+        Self::update_seat_focus(seat, KbFocus, wt, Some(key.event), callback);
+
+        if let Some(focus) = seat.kb_focus {
+            let state = if key.press {
+                ElementState::Pressed
+            } else {
+                ElementState::Released
+            };
+
+            let device_id = mkdid(seat.keyboard);
+
+            let ker = seat
+                .kb_state
+                .process_key_event(key.keycode, key.group(), state);
+
+            callback(Event::WindowEvent {
+                window_id: mkwid(focus),
+                event: WindowEvent::KeyboardInput {
+                    device_id,
+                    event: KeyEvent {
+                        physical_key: ker.keycode,
+                        logical_key: ker.key,
+                        text: ker.text,
+                        location: ker.location,
+                        state,
+                        repeat: key.repeat,
+                        platform_specific: KeyEventExtra {
+                            key_without_modifiers: ker.key_without_modifiers,
+                            text_with_all_modifiers: ker.text_with_all_modifiers,
+                        },
+                    },
+                    is_synthetic: false,
+                    time: Some(Timestamp::from_millis(key.time)),
+                },
+            });
+        }
+    }
+
     pub(super) fn process_event<F>(&mut self, xev: &mut ffi::xcb_generic_event_t, mut callback: F)
     where
         F: FnMut(Event<'_, T>),
@@ -158,7 +427,9 @@ impl<T: 'static> EventProcessor<T> {
                 let window = client_msg.window;
                 let window_id = mkwid(window);
 
-                if data32[0] == wt.wm_delete_window {
+                if self.ime.handle_client_message(client_msg) {
+                    Self::dispatch_ime_events(wt, &mut self.ime, &mut self.seats, &mut callback);
+                } else if data32[0] == wt.wm_delete_window {
                     callback(Event::WindowEvent {
                         window_id,
                         event: WindowEvent::CloseRequested,
@@ -179,11 +450,30 @@ impl<T: 'static> EventProcessor<T> {
                         );
                         wt.xconn.discard(pending);
                     });
+                } else if window == wt.clipboard.window() {
+                    // The target of our drag is answering.
+                    let result = match &mut self.drag {
+                        Some(drag) if client_msg.type_ == self.dnd.atoms.status => {
+                            drag.handle_status(data32)
+                        }
+                        Some(drag) if client_msg.type_ == self.dnd.atoms.finished => {
+                            drag.handle_finished(data32)
+                        }
+                        _ => None,
+                    };
+                    if let Some(action) = result {
+                        if let Some(drag) = self.drag.take() {
+                            Self::finish_drag(wt, drag.window, action, &mut callback);
+                        }
+                    }
                 } else if client_msg.type_ == self.dnd.atoms.enter {
+                    // Complete a drop that is still waiting for data before the next drag starts.
+                    Self::finish_drop(wt, &mut self.dnd);
                     let source_window = data32[0];
                     let flags = data32[1];
                     let version = flags >> 24;
                     self.dnd.version = Some(version);
+                    self.dnd.source_window = Some(source_window);
                     let has_more_types = flags - (flags & (u32::MAX - 1)) == 1;
                     if !has_more_types {
                         let type_list = vec![data32[2], data32[3], data32[4]];
@@ -192,64 +482,94 @@ impl<T: 'static> EventProcessor<T> {
                     {
                         self.dnd.type_list = Some(more_types);
                     }
+                    wt.dnd
+                        .target
+                        .set(Some((window, ffi::XCB_TIME_CURRENT_TIME)));
+                    // Files are accepted by default for `HoveredFile` and `DroppedFile`.
+                    wt.dnd.action.set(match self.dnd.offers_files() {
+                        true => Some(DndAction::Copy),
+                        false => None,
+                    });
+                    let type_list = self.dnd.type_list.as_deref().unwrap_or_default();
+                    let mime_types = wt.clipboard.targets_to_mime_types(type_list);
+                    callback(Event::WindowEvent {
+                        window_id,
+                        event: WindowEvent::DragAndDrop(DndEvent::Enter { mime_types }),
+                    });
                 } else if client_msg.type_ == self.dnd.atoms.position {
-                    // This event occurs every time the mouse moves while a file's being dragged
-                    // over our window. We emit HoveredFile in response; while the macOS backend
-                    // does that upon a drag entering, XDND doesn't have access to the actual drop
-                    // data until this event. For parity with other platforms, we only emit
-                    // `HoveredFile` the first time, though if winit's API is later extended to
-                    // supply position updates with `HoveredFile` or another event, implementing
-                    // that here would be trivial.
+                    // This event occurs every time the mouse moves while something is being
+                    // dragged over our window. XDND doesn't give us access to the dropped files
+                    // until this event, so we request them the first time it occurs and emit
+                    // `HoveredFile` once they have arrived.
 
                     let source_window = data32[0];
 
-                    // Equivalent to `(x << shift) | y`
-                    // where `shift = mem::size_of::<c_short>() * 8`
-                    // Note that coordinates are in "desktop space", not "window space"
-                    // (in X11 parlance, they're root window coordinates)
-                    //let packed_coordinates = client_msg.data.get_long(2);
-                    //let shift = mem::size_of::<libc::c_short>() * 8;
-                    //let x = packed_coordinates >> shift;
-                    //let y = packed_coordinates & !(x << shift);
+                    // Equivalent to `(x << 16) | y`. Note that coordinates are in "desktop
+                    // space", not "window space" (in X11 parlance, they're root window
+                    // coordinates)
+                    let root_x = (data32[2] >> 16) as i16;
+                    let root_y = (data32[2] & 0xffff) as i16;
 
                     // By our own state flow, `version` should never be `None` at this point.
                     let version = self.dnd.version.unwrap_or(5);
 
-                    // Action is specified in versions 2 and up, though we don't need it anyway.
-                    //let action = client_msg.data.get_long(4);
-
-                    let accepted = if let Some(ref type_list) = self.dnd.type_list {
-                        type_list.contains(&self.dnd.atoms.uri_list)
+                    let time = if version >= 1 {
+                        data32[3]
                     } else {
-                        false
+                        // In version 0, time isn't specified
+                        ffi::XCB_TIME_CURRENT_TIME
                     };
 
-                    if accepted {
-                        self.dnd.source_window = Some(source_window);
-                        unsafe {
-                            if self.dnd.result.is_none() {
-                                let time = if version >= 1 {
-                                    data32[3]
-                                } else {
-                                    // In version 0, time isn't specified
-                                    ffi::XCB_TIME_CURRENT_TIME
-                                };
-                                // This results in the `SelectionNotify` event below
-                                self.dnd.convert_selection(window, time);
-                            }
-                            self.dnd
-                                .send_status(window, source_window, DndState::Accepted);
-                        }
+                    // The action is specified in versions 2 and up.
+                    let action = if version >= 2 {
+                        self.dnd.atoms.action_from_atom(data32[4])
                     } else {
-                        unsafe {
-                            self.dnd
-                                .send_status(window, source_window, DndState::Rejected);
-                        }
-                        self.dnd.reset();
+                        DndAction::Copy
+                    };
+
+                    self.dnd.source_window = Some(source_window);
+                    wt.dnd.target.set(Some((window, time)));
+
+                    if self.dnd.offers_files() && !self.dnd.converting {
+                        self.dnd.converting = true;
+                        // This results in the `SelectionNotify` event below
+                        unsafe { self.dnd.convert_selection(window, time) };
+                    }
+
+                    let root = Self::with_window(wt, window, |w| w.screen.root);
+                    let origin = root.and_then(|root| wt.xconn.translate_coords(window, root).ok());
+                    if let Some(origin) = origin {
+                        let position = PhysicalPosition::new(
+                            root_x as f64 - origin.x_rel_root as f64,
+                            root_y as f64 - origin.y_rel_root as f64,
+                        );
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event: WindowEvent::DragAndDrop(DndEvent::Motion { position, action }),
+                        });
+                    }
+
+                    // The application might have changed the response while handling `Motion`.
+                    unsafe {
+                        self.dnd
+                            .send_status(window, source_window, wt.dnd.action.get());
                     }
                 } else if client_msg.type_ == self.dnd.atoms.drop {
-                    let (source_window, state) = if let Some(source_window) = self.dnd.source_window
-                    {
+                    // `source_window` won't be part of our DND state if we have not seen a
+                    // position yet.
+                    let action = match self.dnd.source_window {
+                        Some(_) => wt.dnd.action.get(),
+                        None => None,
+                    };
+                    let version = self.dnd.version.unwrap_or(5);
+                    let time = if version >= 1 {
+                        data32[2]
+                    } else {
+                        ffi::XCB_TIME_CURRENT_TIME
+                    };
+                    self.dnd.source_window = Some(data32[0]);
+                    wt.dnd.target.set(Some((window, time)));
+                    if action.is_some() {
                         if let Some(Ok(ref path_list)) = self.dnd.result {
                             for path in path_list {
                                 callback(Event::WindowEvent {
@@ -258,25 +578,33 @@ impl<T: 'static> EventProcessor<T> {
                                 });
                             }
                         }
-                        (source_window, DndState::Accepted)
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event: WindowEvent::DragAndDrop(DndEvent::Drop),
+                        });
                     } else {
-                        // `source_window` won't be part of our DND state if we already rejected the drop in our
-                        // `XdndPosition` handler.
-                        let source_window = data32[0];
-                        (source_window, DndState::Rejected)
-                    };
-                    unsafe {
-                        self.dnd
-                            .send_finished(window, source_window, state)
-                            .expect("Failed to send `XdndFinished` message.");
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event: WindowEvent::DragAndDrop(DndEvent::Leave),
+                        });
+                    }
+                    // `XdndFinished` is sent once the data requested while handling `Drop` has
+                    // been received.
+                    self.dnd.dropped = Some(action);
+                    if !wt.clipboard.has_dnd_requests() {
+                        Self::finish_drop(wt, &mut self.dnd);
                     }
-                    self.dnd.reset();
                 } else if client_msg.type_ == self.dnd.atoms.leave {
                     self.dnd.reset();
+                    wt.dnd.target.set(None);
                     callback(Event::WindowEvent {
                         window_id,
                         event: WindowEvent::HoveredFileCancelled,
                     });
+                    callback(Event::WindowEvent {
+                        window_id,
+                        event: WindowEvent::DragAndDrop(DndEvent::Leave),
+                    });
                 }
             }
 
@@ -287,7 +615,11 @@ impl<T: 'static> EventProcessor<T> {
                 let window = xsel.requestor;
                 let window_id = mkwid(window);
 
-                if xsel.property == self.dnd.atoms.selection {
+                if window == wt.clipboard.window() {
+                    if let Some(event) = wt.clipboard.handle_selection_notify(xsel) {
+                        Self::dispatch_selection_event(wt, &mut self.dnd, event, &mut callback);
+                    }
+                } else if xsel.property == self.dnd.atoms.selection {
                     let mut result = None;
 
                     // This is where we receive data from drag and drop
@@ -308,6 +640,30 @@ impl<T: 'static> EventProcessor<T> {
                 }
             }
 
+            ffi::XCB_SELECTION_REQUEST => {
+                let xev =
+                    unsafe { &*(xev as *const _ as *const ffi::xcb_selection_request_event_t) };
+                if xev.owner == wt.clipboard.window() {
+                    wt.clipboard.handle_selection_request(xev);
+                }
+            }
+
+            ffi::XCB_SELECTION_CLEAR => {
+                let xev = unsafe { &*(xev as *const _ as *const ffi::xcb_selection_clear_event_t) };
+                if let Some(event) = wt.clipboard.handle_selection_clear(xev) {
+                    callback(Event::Clipboard(event));
+                }
+            }
+
+            ffi::XCB_PROPERTY_NOTIFY => {
+                let xev = unsafe { &*(xev as *const _ as *const ffi::xcb_property_notify_event_t) };
+                if let Some(event) = wt.clipboard.handle_property_notify(xev) {
+                    Self::dispatch_selection_event(wt, &mut self.dnd, event, &mut callback);
+                }
+                self.ime.handle_property_notify(xev);
+                Self::dispatch_ime_events(wt, &mut self.ime, &mut self.seats, &mut callback);
+            }
+
             ffi::XCB_CONFIGURE_NOTIFY => {
                 let xev =
                     unsafe { &*(xev as *const _ as *const ffi::xcb_configure_notify_event_t) };
@@ -426,6 +782,7 @@ impl<T: 'static> EventProcessor<T> {
                                         new_inner_size: &mut new_inner_size,
                                     },
                                 });
+                                window.refresh_cursor(new_scale_factor);
                             });
 
                             if new_inner_size != old_inner_size {
@@ -489,6 +846,19 @@ impl<T: 'static> EventProcessor<T> {
                 let window = xev.window;
                 let window_id = mkwid(window);
 
+                if self.ime.handle_destroy_notify(window) {
+                    Self::dispatch_ime_events(wt, &mut self.ime, &mut self.seats, &mut callback);
+                    return;
+                }
+                self.ime.remove_window(window);
+
+                if self.drag.as_ref().map(|d| d.window) == Some(window) {
+                    if let Some(mut drag) = self.drag.take() {
+                        drag.cancel();
+                    }
+                    Self::finish_drag(wt, window, None, &mut callback);
+                }
+
                 // In the event that the window's been destroyed without being dropped first, we
                 // cleanup again here.
                 wt.windows.borrow_mut().remove(&WindowId(window));
@@ -530,8 +900,8 @@ impl<T: 'static> EventProcessor<T> {
                 use crate::event::{
                     ElementState::{Pressed, Released},
                     MouseButton::{Left, Middle, Other, Right},
-                    MouseScrollDelta::LineDelta,
-                    Touch,
+                    MouseScrollDelta::{LineDelta, PixelDelta},
+                    ScrollSource, Touch,
                     WindowEvent::{AxisMotion, CursorMoved, MouseInput, MouseWheel},
                 };
 
@@ -545,6 +915,19 @@ impl<T: 'static> EventProcessor<T> {
                             return;
                         }
 
+                        if let Some(drag) = &mut self.drag {
+                            if xev.event_type == ffi::XCB_INPUT_BUTTON_RELEASE
+                                && xev.detail as u32 == ffi::XCB_BUTTON_INDEX_1
+                                && xev.deviceid == util::VIRTUAL_CORE_POINTER
+                                && xev.event == drag.window
+                            {
+                                if let Some(action) = drag.handle_release(xev.time) {
+                                    self.drag = None;
+                                    Self::finish_drag(wt, xev.event, action, &mut callback);
+                                }
+                            }
+                        }
+
                         let seat = match find_seat_by_pointer(&mut self.seats, xev.deviceid) {
                             Some(seat) => seat,
                             _ => return,
@@ -554,12 +937,60 @@ impl<T: 'static> EventProcessor<T> {
 
                         let window_id = mkwid(xev.event);
                         let device_id = mkdid(seat.keyboard);
+                        let time = Some(Timestamp::from_millis(xev.time));
 
                         let state = if xev.event_type == ffi::XCB_INPUT_BUTTON_PRESS {
                             Pressed
                         } else {
                             Released
                         };
+
+                        let pen = self
+                            .devices
+                            .get_mut(&DeviceId(xev.sourceid))
+                            .and_then(|device| device.pen.as_mut())
+                            .filter(|_| Self::window_exists(wt, xev.event));
+                        if let Some(pen) = pen {
+                            let mask = unsafe {
+                                slice::from_raw_parts(
+                                    wt.xconn.xinput.xcb_input_button_press_valuator_mask(xev),
+                                    xev.valuators_len as usize,
+                                )
+                            };
+                            let axis_ids = mask_to_axis_ids(mask);
+                            let axes = unsafe {
+                                slice::from_raw_parts(
+                                    wt.xconn.xinput.xcb_input_button_press_axisvalues(xev),
+                                    axis_ids.len(),
+                                )
+                            };
+                            pen.update_axes(&axis_ids, axes);
+                            let phase = match (xev.detail, state) {
+                                (1, Pressed) => PenPhase::Down,
+                                (1, Released) => PenPhase::Up,
+                                (button, state) => PenPhase::Button {
+                                    button: match button {
+                                        2 => PenButton::Primary,
+                                        3 => PenButton::Secondary,
+                                        _ => PenButton::Other(button),
+                                    },
+                                    state,
+                                },
+                            };
+                            let location = PhysicalPosition::new(
+                                util::fp1616_to_f64(xev.event_x),
+                                util::fp1616_to_f64(xev.event_y),
+                            );
+                            Self::handle_pen(
+                                pen,
+                                device_id,
+                                xev.event,
+                                location,
+                                phase,
+                                &mut callback,
+                            );
+                        }
+
                         match xev.detail as u32 {
                             ffi::XCB_BUTTON_INDEX_1
                             | ffi::XCB_BUTTON_INDEX_2
@@ -576,6 +1007,7 @@ impl<T: 'static> EventProcessor<T> {
                                         device_id,
                                         state,
                                         button,
+                                        time,
                                         modifiers: seat.current_modifiers,
                                     },
                                 })
@@ -600,6 +1032,11 @@ impl<T: 'static> EventProcessor<T> {
                                                 _ => unreachable!(),
                                             },
                                             phase: TouchPhase::Moved,
+                                            source: match xev.detail {
+                                                4 | 5 => ScrollSource::Wheel,
+                                                _ => ScrollSource::WheelTilt,
+                                            },
+                                            time,
                                             modifiers: seat.current_modifiers,
                                         },
                                     });
@@ -612,6 +1049,7 @@ impl<T: 'static> EventProcessor<T> {
                                     device_id,
                                     state,
                                     button: Other(x as u16),
+                                    time,
                                     modifiers: seat.current_modifiers,
                                 },
                             }),
@@ -621,6 +1059,18 @@ impl<T: 'static> EventProcessor<T> {
                         let xev =
                             unsafe { &*(xev as *const _ as *const ffi::xcb_input_motion_event_t) };
 
+                        if let Some(drag) = &mut self.drag {
+                            if xev.deviceid == util::VIRTUAL_CORE_POINTER
+                                && xev.event == drag.window
+                            {
+                                drag.handle_motion(
+                                    util::fp1616_to_f64(xev.root_x) as i16,
+                                    util::fp1616_to_f64(xev.root_y) as i16,
+                                    xev.time,
+                                );
+                            }
+                        }
+
                         let seat = match find_seat_by_pointer(&mut self.seats, xev.deviceid) {
                             Some(seat) => seat,
                             _ => return,
@@ -630,12 +1080,21 @@ impl<T: 'static> EventProcessor<T> {
 
                         let device_id = mkdid(seat.keyboard);
                         let window_id = mkwid(xev.event);
+                        let time = Some(Timestamp::from_millis(xev.time));
                         let event_x = util::fp1616_to_f64(xev.event_x);
                         let event_y = util::fp1616_to_f64(xev.event_y);
                         let new_cursor_pos = (event_x, event_y);
 
                         let cursor_moved = Self::with_window(wt, xev.event, |window| {
                             let mut shared_state_lock = window.shared_state.lock();
+                            if let Some((x, y)) = shared_state_lock.cursor_lock {
+                                // A locked cursor is moved back and only produces device events.
+                                drop(shared_state_lock);
+                                if new_cursor_pos != (x as f64, y as f64) {
+                                    let _ = window.set_cursor_position_physical(x, y);
+                                }
+                                return false;
+                            }
                             util::maybe_change(&mut shared_state_lock.cursor_pos, new_cursor_pos)
                         });
                         if cursor_moved == Some(true) {
@@ -646,6 +1105,7 @@ impl<T: 'static> EventProcessor<T> {
                                 event: CursorMoved {
                                     device_id,
                                     position,
+                                    time,
                                     modifiers: seat.current_modifiers,
                                 },
                             });
@@ -675,6 +1135,10 @@ impl<T: 'static> EventProcessor<T> {
                                     None => return,
                                 };
 
+                            if let Some(pen) = &mut physical_device.pen {
+                                pen.update_axes(&axis_ids, axes);
+                            }
+
                             for (&i, &x) in axis_ids.iter().zip(axes.iter()) {
                                 let x = util::fp3232_to_f64(x);
                                 if let Some(&mut (_, ref mut info)) = physical_device
@@ -682,22 +1146,30 @@ impl<T: 'static> EventProcessor<T> {
                                     .iter_mut()
                                     .find(|&&mut (axis, _)| axis == i)
                                 {
-                                    let delta = (x - info.position) / info.increment;
+                                    let delta = x - info.position;
                                     info.position = x;
+                                    // X11 vertical scroll coordinates are opposite to winit's
+                                    let (dx, dy) = match info.orientation {
+                                        ScrollOrientation::Horizontal => (delta, 0.0),
+                                        ScrollOrientation::Vertical => (0.0, -delta),
+                                    };
+                                    let delta = match info.source {
+                                        ScrollSource::Finger => {
+                                            PixelDelta(PhysicalPosition::new(dx, dy))
+                                        }
+                                        _ => LineDelta(
+                                            (dx / info.increment) as f32,
+                                            (dy / info.increment) as f32,
+                                        ),
+                                    };
                                     events.push(Event::WindowEvent {
                                         window_id,
                                         event: MouseWheel {
                                             device_id,
-                                            delta: match info.orientation {
-                                                ScrollOrientation::Horizontal => {
-                                                    LineDelta(delta as f32, 0.0)
-                                                }
-                                                // X11 vertical scroll coordinates are opposite to winit's
-                                                ScrollOrientation::Vertical => {
-                                                    LineDelta(0.0, -delta as f32)
-                                                }
-                                            },
+                                            delta,
                                             phase: TouchPhase::Moved,
+                                            source: info.source,
+                                            time,
                                             modifiers: seat.current_modifiers,
                                         },
                                     });
@@ -716,6 +1188,22 @@ impl<T: 'static> EventProcessor<T> {
                         for event in events {
                             callback(event);
                         }
+
+                        let pen = self
+                            .devices
+                            .get_mut(&DeviceId(xev.sourceid))
+                            .and_then(|device| device.pen.as_mut());
+                        if let Some(pen) = pen {
+                            let location = PhysicalPosition::new(event_x, event_y);
+                            Self::handle_pen(
+                                pen,
+                                device_id,
+                                xev.event,
+                                location,
+                                PenPhase::Moved,
+                                &mut callback,
+                            );
+                        }
                     }
 
                     ffi::XCB_INPUT_ENTER => {
@@ -755,12 +1243,14 @@ impl<T: 'static> EventProcessor<T> {
                             let event_x = util::fp1616_to_f64(xev.event_x);
                             let event_y = util::fp1616_to_f64(xev.event_y);
                             let position = PhysicalPosition::new(event_x, event_y);
+                            let time = Some(Timestamp::from_millis(xev.time));
 
                             callback(Event::WindowEvent {
                                 window_id: mkwid(window),
                                 event: CursorMoved {
                                     device_id,
                                     position,
+                                    time,
                                     modifiers: seat.current_modifiers,
                                 },
                             });
@@ -776,6 +1266,17 @@ impl<T: 'static> EventProcessor<T> {
                         };
                         Self::update_seat_kb_xi(seat, &xev.mods, &xev.group, &mut callback);
                         Self::update_seat_focus(seat, PtrFocus, wt, None, &mut callback);
+
+                        let device_id = mkdid(seat.keyboard);
+                        let pen = self
+                            .devices
+                            .get_mut(&DeviceId(xev.sourceid))
+                            .and_then(|device| device.pen.as_mut());
+                        if let Some(pen) = pen {
+                            if pen.window == Some(xev.event) {
+                                Self::pen_proximity_out(pen, device_id, &mut callback);
+                            }
+                        }
                     }
                     ffi::XCB_INPUT_FOCUS_IN => {
                         let xev = unsafe {
@@ -788,6 +1289,10 @@ impl<T: 'static> EventProcessor<T> {
                         };
                         Self::update_seat_kb_xi(seat, &xev.mods, &xev.group, &mut callback);
                         Self::update_seat_focus(seat, KbFocus, wt, Some(xev.event), &mut callback);
+
+                        if Self::window_exists(wt, xev.event) {
+                            self.ime.focus_in(xev.event);
+                        }
                     }
                     ffi::XCB_INPUT_FOCUS_OUT => {
                         let xev = unsafe {
@@ -800,6 +1305,8 @@ impl<T: 'static> EventProcessor<T> {
                         };
                         Self::update_seat_kb_xi(seat, &xev.mods, &xev.group, &mut callback);
                         Self::update_seat_focus(seat, KbFocus, wt, None, &mut callback);
+
+                        self.ime.focus_out(xev.event);
                     }
 
                     ffi::XCB_INPUT_TOUCH_BEGIN
@@ -827,6 +1334,7 @@ impl<T: 'static> EventProcessor<T> {
                             let event_x = util::fp1616_to_f64(xev.event_x);
                             let event_y = util::fp1616_to_f64(xev.event_y);
                             let location = PhysicalPosition::new(event_x, event_y);
+                            let time = Some(Timestamp::from_millis(xev.time));
 
                             // Mouse cursor position changes when touch events are received.
                             // Only the first concurrently active touch ID moves the mouse cursor.
@@ -837,6 +1345,7 @@ impl<T: 'static> EventProcessor<T> {
                                     event: WindowEvent::CursorMoved {
                                         device_id: mkdid(seat.keyboard),
                                         position: location.cast(),
+                                        time,
                                         modifiers: seat.current_modifiers,
                                     },
                                 });
@@ -850,11 +1359,110 @@ impl<T: 'static> EventProcessor<T> {
                                     location,
                                     force: None, // TODO
                                     id,
+                                    time,
                                 }),
                             })
                         }
                     }
 
+                    gesture_ffi::XCB_INPUT_GESTURE_PINCH_BEGIN
+                    | gesture_ffi::XCB_INPUT_GESTURE_PINCH_UPDATE
+                    | gesture_ffi::XCB_INPUT_GESTURE_PINCH_END => {
+                        let xev = unsafe {
+                            &*(xev as *const _
+                                as *const gesture_ffi::xcb_input_gesture_pinch_begin_event_t)
+                        };
+                        let seat = match find_seat_by_pointer(&mut self.seats, xev.deviceid) {
+                            Some(seat) => seat,
+                            _ => return,
+                        };
+                        Self::update_seat_kb_xi(seat, &xev.mods, &xev.group, &mut callback);
+                        Self::update_seat_focus(seat, PtrFocus, wt, Some(xev.event), &mut callback);
+
+                        let cancelled = xev.flags
+                            & gesture_ffi::XCB_INPUT_GESTURE_PINCH_EVENT_FLAGS_GESTURE_PINCH_CANCELLED
+                            != 0;
+                        let phase = match xev.event_type {
+                            gesture_ffi::XCB_INPUT_GESTURE_PINCH_BEGIN => TouchPhase::Started,
+                            gesture_ffi::XCB_INPUT_GESTURE_PINCH_UPDATE => TouchPhase::Moved,
+                            _ if cancelled => TouchPhase::Cancelled,
+                            _ => TouchPhase::Ended,
+                        };
+                        // The scale is relative to the start of the gesture.
+                        let scale = util::fp1616_to_f64(xev.scale);
+                        let (delta, angle) = match phase {
+                            TouchPhase::Started => (0.0, 0.0),
+                            _ => (
+                                scale - seat.pinch_scale,
+                                util::fp1616_to_f64(xev.delta_angle),
+                            ),
+                        };
+                        seat.pinch_scale = scale;
+
+                        let window_id = mkwid(xev.event);
+                        let device_id = mkdid(seat.keyboard);
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event: WindowEvent::PinchGesture {
+                                device_id,
+                                delta,
+                                phase,
+                            },
+                        });
+                        // X11 reports clockwise rotation as positive.
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event: WindowEvent::RotationGesture {
+                                device_id,
+                                delta: -angle as f32,
+                                phase,
+                            },
+                        });
+                    }
+
+                    gesture_ffi::XCB_INPUT_GESTURE_SWIPE_BEGIN
+                    | gesture_ffi::XCB_INPUT_GESTURE_SWIPE_UPDATE
+                    | gesture_ffi::XCB_INPUT_GESTURE_SWIPE_END => {
+                        let xev = unsafe {
+                            &*(xev as *const _
+                                as *const gesture_ffi::xcb_input_gesture_swipe_begin_event_t)
+                        };
+                        let seat = match find_seat_by_pointer(&mut self.seats, xev.deviceid) {
+                            Some(seat) => seat,
+                            _ => return,
+                        };
+                        Self::update_seat_kb_xi(seat, &xev.mods, &xev.group, &mut callback);
+                        Self::update_seat_focus(seat, PtrFocus, wt, Some(xev.event), &mut callback);
+
+                        let cancelled = xev.flags
+                            & gesture_ffi::XCB_INPUT_GESTURE_SWIPE_EVENT_FLAGS_GESTURE_SWIPE_CANCELLED
+                            != 0;
+                        let phase = match xev.event_type {
+                            gesture_ffi::XCB_INPUT_GESTURE_SWIPE_BEGIN => TouchPhase::Started,
+                            gesture_ffi::XCB_INPUT_GESTURE_SWIPE_UPDATE => TouchPhase::Moved,
+                            _ if cancelled => TouchPhase::Cancelled,
+                            _ => TouchPhase::Ended,
+                        };
+                        let delta = match phase {
+                            TouchPhase::Started => PhysicalPosition::new(0.0, 0.0),
+                            _ => PhysicalPosition::new(
+                                util::fp1616_to_f64(xev.delta_x),
+                                util::fp1616_to_f64(xev.delta_y),
+                            ),
+                        };
+
+                        callback(Event::WindowEvent {
+                            window_id: mkwid(xev.event),
+                            event: WindowEvent::SwipeGesture {
+                                device_id: mkdid(seat.keyboard),
+                                delta,
+                                // The detail of gesture events is the number of touches.
+                                fingers: xev.detail,
+                                phase,
+                            },
+                        });
+                    }
+
                     ffi::XCB_INPUT_RAW_BUTTON_PRESS | ffi::XCB_INPUT_RAW_BUTTON_RELEASE => {
                         let xev = unsafe {
                             &*(xev as *const _ as *const ffi::xcb_input_raw_button_press_event_t)
@@ -870,6 +1478,7 @@ impl<T: 'static> EventProcessor<T> {
                                         _ => unreachable!(),
                                     },
                                 },
+                                time: Some(Timestamp::from_millis(xev.time)),
                             });
                         }
                     }
@@ -879,6 +1488,7 @@ impl<T: 'static> EventProcessor<T> {
                             &*(xev as *const _ as *const ffi::xcb_input_raw_motion_event_t)
                         };
                         let did = mkdid(xev.deviceid);
+                        let time = Some(Timestamp::from_millis(xev.time));
 
                         let mask = unsafe {
                             slice::from_raw_parts(
@@ -914,12 +1524,14 @@ impl<T: 'static> EventProcessor<T> {
                                     axis: i as u32,
                                     value: x,
                                 },
+                                time,
                             });
                         }
                         if mouse_delta != (0.0, 0.0) {
                             callback(Event::DeviceEvent {
                                 device_id: did,
                                 event: DeviceEvent::MouseMotion { delta: mouse_delta },
+                                time,
                             });
                         }
                         if scroll_delta != (0.0, 0.0) {
@@ -928,6 +1540,7 @@ impl<T: 'static> EventProcessor<T> {
                                 event: DeviceEvent::MouseWheel {
                                     delta: LineDelta(scroll_delta.0, scroll_delta.1),
                                 },
+                                time,
                             });
                         }
                     }
@@ -939,51 +1552,14 @@ impl<T: 'static> EventProcessor<T> {
                             &*(xev as *const _ as *const ffi::xcb_input_key_press_event_t)
                         };
 
-                        let seat = match find_seat(&mut self.seats, xkev.deviceid) {
-                            Some(seat) => seat,
-                            _ => return,
-                        };
-                        Self::update_seat_kb_xi(seat, &xkev.mods, &xkev.group, &mut callback);
-                        Self::update_seat_focus(seat, KbFocus, wt, Some(xkev.event), &mut callback);
-
-                        if let Some(focus) = seat.kb_focus {
-                            let state = if xev.event_type == ffi::XCB_INPUT_KEY_PRESS {
-                                Pressed
-                            } else {
-                                Released
-                            };
-
-                            let device_id = mkdid(seat.keyboard);
-                            let keycode = xkev.detail as u32;
-
-                            let ker = seat.kb_state.process_key_event(
-                                keycode,
-                                xkev.group.effective as u32,
-                                state,
-                            );
-                            let repeat =
-                                xkev.flags & ffi::XCB_INPUT_KEY_EVENT_FLAGS_KEY_REPEAT != 0;
-
-                            callback(Event::WindowEvent {
-                                window_id: mkwid(focus),
-                                event: WindowEvent::KeyboardInput {
-                                    device_id,
-                                    event: KeyEvent {
-                                        physical_key: ker.keycode,
-                                        logical_key: ker.key,
-                                        text: ker.text,
-                                        location: ker.location,
-                                        state,
-                                        repeat,
-                                        platform_specific: KeyEventExtra {
-                                            key_without_modifiers: ker.key_without_modifiers,
-                                            text_with_all_modifiers: ker.text_with_all_modifiers,
-                                        },
-                                    },
-                                    is_synthetic: false,
-                                },
-                            });
-                        }
+                        // Key events are processed once the input method is done with them.
+                        self.ime.push_key(ime::Key::new(xkev));
+                        Self::dispatch_ime_events(
+                            wt,
+                            &mut self.ime,
+                            &mut self.seats,
+                            &mut callback,
+                        );
                     }
 
                     ffi::XCB_INPUT_RAW_KEY_PRESS | ffi::XCB_INPUT_RAW_KEY_RELEASE => {
@@ -1010,6 +1586,7 @@ impl<T: 'static> EventProcessor<T> {
                                 physical_key,
                                 state,
                             }),
+                            time: Some(Timestamp::from_millis(xev.time)),
                         });
                     }
 
@@ -1037,6 +1614,7 @@ impl<T: 'static> EventProcessor<T> {
                                 callback(Event::DeviceEvent {
                                     device_id: mkdid(info.deviceid),
                                     event: DeviceEvent::Added,
+                                    time: Some(Timestamp::from_millis(xev.time)),
                                 });
                             } else if 0
                                 != info.flags
@@ -1046,9 +1624,10 @@ impl<T: 'static> EventProcessor<T> {
                                 callback(Event::DeviceEvent {
                                     device_id: mkdid(info.deviceid),
                                     event: DeviceEvent::Removed,
+                                    time: Some(Timestamp::from_millis(xev.time)),
                                 });
                                 self.devices.remove(&DeviceId(info.deviceid));
-                                if info.type_ == ffi::XCB_INPUT_DEVICE_TYPE_MASTER_KEYBOARD as _ {
+                                if info.type_ == ffi::XCB_INPUT_DEVICE_TYPE_MASTER_KEYBOARD as u8 {
                                     self.seats.retain(|s| s.keyboard != info.deviceid);
                                 }
                             }
@@ -1058,63 +1637,103 @@ impl<T: 'static> EventProcessor<T> {
                     _ => {}
                 }
             }
-            _ if response_type == wt.xconn.randr_first_event => {
-                // In the future, it would be quite easy to emit monitor hotplug events.
-                let prev_list = wt.xconn.invalidate_cached_monitor_list();
-                if let Some(prev_list) = prev_list {
-                    let new_list = wt.xconn.available_monitors();
-                    for new_monitor in new_list {
-                        prev_list
-                            .iter()
-                            .find(|prev_monitor| prev_monitor.name == new_monitor.name)
-                            .map(|prev_monitor| {
-                                if new_monitor.scale_factor != prev_monitor.scale_factor {
-                                    for (window_id, window) in wt.windows.borrow().iter() {
-                                        if let Some(window) = window.upgrade() {
-                                            // Check if the window is on this monitor
-                                            let monitor = window.current_monitor();
-                                            if monitor.name == new_monitor.name {
-                                                let (width, height) = window.inner_size_physical();
-                                                let (new_width, new_height) = window
-                                                    .adjust_for_dpi(
-                                                        prev_monitor.scale_factor,
-                                                        new_monitor.scale_factor,
-                                                        width,
-                                                        height,
-                                                        &*window.shared_state.lock(),
-                                                    );
-
-                                                let window_id = crate::window::WindowId(
-                                                    crate::platform_impl::platform::WindowId::X(
-                                                        *window_id,
-                                                    ),
-                                                );
-                                                let old_inner_size =
-                                                    PhysicalSize::new(width, height);
-                                                let mut new_inner_size =
-                                                    PhysicalSize::new(new_width, new_height);
-
-                                                callback(Event::WindowEvent {
-                                                    window_id,
-                                                    event: WindowEvent::ScaleFactorChanged {
-                                                        scale_factor: new_monitor.scale_factor,
-                                                        new_inner_size: &mut new_inner_size,
-                                                    },
-                                                });
-
-                                                if new_inner_size != old_inner_size {
-                                                    let (new_width, new_height) =
-                                                        new_inner_size.into();
-                                                    window.set_inner_size_physical(
-                                                        new_width, new_height,
-                                                    );
-                                                }
+            _ if response_type
+                == wt.xconn.xinput_first_event + ffi::XCB_INPUT_PROXIMITY_OUT as u8 =>
+            {
+                let xev =
+                    unsafe { &*(xev as *const _ as *const ffi::xcb_input_proximity_out_event_t) };
+                // The high bit indicates that more events follow.
+                let device = match self.devices.get_mut(&DeviceId((xev.device_id & 0x7f) as _)) {
+                    Some(device) => device,
+                    None => return,
+                };
+                let seat = match find_seat_by_pointer(&mut self.seats, device.attachment) {
+                    Some(seat) => seat,
+                    _ => return,
+                };
+                if let Some(pen) = &mut device.pen {
+                    Self::pen_proximity_out(pen, mkdid(seat.keyboard), &mut callback);
+                }
+            }
+            _ if response_type == wt.xconn.randr_first_event
+                || response_type == wt.xconn.randr_first_event + ffi::XCB_RANDR_NOTIFY as u8 =>
+            {
+                wt.xconn.invalidate_cached_monitor_list();
+                let new_list = match wt.xconn.available_monitors_inner() {
+                    Ok(m) => m,
+                    Err(e) => {
+                        log::error!("Could not retrieve monitors: {}", e);
+                        return;
+                    }
+                };
+                let prev_list = mem::replace(&mut self.monitors, new_list.clone());
+
+                let root_monitor = |monitor: &MonitorHandle| RootMonitorHandle {
+                    inner: PlatformMonitorHandle::X(monitor.clone()),
+                };
+                for prev_monitor in &prev_list {
+                    if !new_list.iter().any(|m| m.name == prev_monitor.name) {
+                        callback(Event::MonitorRemoved(root_monitor(prev_monitor)));
+                    }
+                }
+                for new_monitor in &new_list {
+                    match prev_list.iter().find(|m| m.name == new_monitor.name) {
+                        Some(prev_monitor) if !prev_monitor.is_unchanged(new_monitor) => {
+                            callback(Event::MonitorChanged(root_monitor(new_monitor)))
+                        }
+                        Some(_) => {}
+                        None => callback(Event::MonitorAdded(root_monitor(new_monitor))),
+                    }
+                }
+
+                for new_monitor in new_list {
+                    prev_list
+                        .iter()
+                        .find(|prev_monitor| prev_monitor.name == new_monitor.name)
+                        .map(|prev_monitor| {
+                            if new_monitor.scale_factor != prev_monitor.scale_factor {
+                                for (window_id, window) in wt.windows.borrow().iter() {
+                                    if let Some(window) = window.upgrade() {
+                                        // Check if the window is on this monitor
+                                        let monitor = window.current_monitor();
+                                        if monitor.name == new_monitor.name {
+                                            let (width, height) = window.inner_size_physical();
+                                            let (new_width, new_height) = window.adjust_for_dpi(
+                                                prev_monitor.scale_factor,
+                                                new_monitor.scale_factor,
+                                                width,
+                                                height,
+                                                &*window.shared_state.lock(),
+                                            );
+
+                                            let window_id = crate::window::WindowId(
+                                                crate::platform_impl::platform::WindowId::X(
+                                                    *window_id,
+                                                ),
+                                            );
+                                            let old_inner_size = PhysicalSize::new(width, height);
+                                            let mut new_inner_size =
+                                                PhysicalSize::new(new_width, new_height);
+
+                                            callback(Event::WindowEvent {
+                                                window_id,
+                                                event: WindowEvent::ScaleFactorChanged {
+                                                    scale_factor: new_monitor.scale_factor,
+                                                    new_inner_size: &mut new_inner_size,
+                                                },
+                                            });
+                                            window.refresh_cursor(new_monitor.scale_factor);
+
+                                            if new_inner_size != old_inner_size {
+                                                let (new_width, new_height) = new_inner_size.into();
+                                                window
+                                                    .set_inner_size_physical(new_width, new_height);
                                             }
                                         }
                                     }
                                 }
-                            });
-                    }
+                            }
+                        });
                 }
             }
             _ if response_type == wt.xconn.xkb_first_event => {
@@ -1159,6 +1778,62 @@ impl<T: 'static> EventProcessor<T> {
     /// as changes of the core-seat focus. In these cases the X server will not send FocusIn/Out
     /// events for additional seats. By calling this function on every keyboard/pointer input,
     /// we update the focus as necessary.
+    /// Emits an event of a pen over `window`.
+    ///
+    /// If the pen was not in proximity of `window` before, proximity events are emitted first.
+    fn handle_pen<F>(
+        pen: &mut PenDevice,
+        device_id: crate::event::DeviceId,
+        window: ffi::xcb_window_t,
+        location: PhysicalPosition<f64>,
+        phase: PenPhase,
+        callback: &mut F,
+    ) where
+        F: FnMut(Event<'_, T>),
+    {
+        if pen.window != Some(window) {
+            Self::pen_proximity_out(pen, device_id, callback);
+            pen.window = Some(window);
+            pen.location = location;
+            Self::emit_pen(pen, device_id, PenPhase::ProximityIn, callback);
+        }
+        pen.location = location;
+        Self::emit_pen(pen, device_id, phase, callback);
+    }
+
+    fn pen_proximity_out<F>(
+        pen: &mut PenDevice,
+        device_id: crate::event::DeviceId,
+        callback: &mut F,
+    ) where
+        F: FnMut(Event<'_, T>),
+    {
+        Self::emit_pen(pen, device_id, PenPhase::ProximityOut, callback);
+        pen.window = None;
+    }
+
+    fn emit_pen<F>(
+        pen: &PenDevice,
+        device_id: crate::event::DeviceId,
+        phase: PenPhase,
+        callback: &mut F,
+    ) where
+        F: FnMut(Event<'_, T>),
+    {
+        if let Some(window) = pen.window {
+            callback(Event::WindowEvent {
+                window_id: mkwid(window),
+                event: WindowEvent::Pen(crate::event::Pen {
+                    device_id,
+                    phase,
+                    tool: pen.tool,
+                    location: pen.location,
+                    state: pen.state,
+                }),
+            });
+        }
+    }
+
     fn update_seat_focus<F>(
         seat: &mut Seat,
         component: SeatFocus,
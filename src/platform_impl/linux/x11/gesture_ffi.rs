@@ -0,0 +1,90 @@
+//! The gesture events of XInput 2.4.
+//!
+//! xcb-dl predates XInput 2.4, so the event layouts and constants are defined here as they are
+//! generated from `xinput.xml` of xcb-proto 1.15.
+#![allow(non_camel_case_types)]
+
+use xcb_dl::ffi;
+
+pub const XCB_INPUT_GESTURE_PINCH_BEGIN: u16 = 27;
+pub const XCB_INPUT_GESTURE_PINCH_UPDATE: u16 = 28;
+pub const XCB_INPUT_GESTURE_PINCH_END: u16 = 29;
+pub const XCB_INPUT_GESTURE_SWIPE_BEGIN: u16 = 30;
+pub const XCB_INPUT_GESTURE_SWIPE_UPDATE: u16 = 31;
+pub const XCB_INPUT_GESTURE_SWIPE_END: u16 = 32;
+
+// The mask of `XI_GestureSwipeEnd` does not fit into 32 bits.
+pub const XCB_INPUT_XI_EVENT_MASK_GESTURE_PINCH_BEGIN: ffi::xcb_input_xi_event_mask_t = 1 << 27;
+pub const XCB_INPUT_XI_EVENT_MASK_GESTURE_PINCH_UPDATE: ffi::xcb_input_xi_event_mask_t = 1 << 28;
+pub const XCB_INPUT_XI_EVENT_MASK_GESTURE_PINCH_END: ffi::xcb_input_xi_event_mask_t = 1 << 29;
+pub const XCB_INPUT_XI_EVENT_MASK_GESTURE_SWIPE_BEGIN: ffi::xcb_input_xi_event_mask_t = 1 << 30;
+pub const XCB_INPUT_XI_EVENT_MASK_GESTURE_SWIPE_UPDATE: ffi::xcb_input_xi_event_mask_t = 1 << 31;
+
+pub const XCB_INPUT_GESTURE_PINCH_EVENT_FLAGS_GESTURE_PINCH_CANCELLED: u32 = 1;
+pub const XCB_INPUT_GESTURE_SWIPE_EVENT_FLAGS_GESTURE_SWIPE_CANCELLED: u32 = 1;
+
+/// The `Input::GesturePinchBegin`, `Input::GesturePinchUpdate`, and `Input::GesturePinchEnd`
+/// events.
+#[derive(Copy, Clone, Debug)]
+#[repr(C)]
+pub struct xcb_input_gesture_pinch_begin_event_t {
+    pub response_type: u8,
+    pub extension: u8,
+    pub sequence: u16,
+    pub length: u32,
+    pub event_type: u16,
+    pub deviceid: ffi::xcb_input_device_id_t,
+    pub time: ffi::xcb_timestamp_t,
+    pub detail: u32,
+    pub root: ffi::xcb_window_t,
+    pub event: ffi::xcb_window_t,
+    pub child: ffi::xcb_window_t,
+    pub full_sequence: u32,
+    pub root_x: ffi::xcb_input_fp1616_t,
+    pub root_y: ffi::xcb_input_fp1616_t,
+    pub event_x: ffi::xcb_input_fp1616_t,
+    pub event_y: ffi::xcb_input_fp1616_t,
+    pub delta_x: ffi::xcb_input_fp1616_t,
+    pub delta_y: ffi::xcb_input_fp1616_t,
+    pub delta_unaccel_x: ffi::xcb_input_fp1616_t,
+    pub delta_unaccel_y: ffi::xcb_input_fp1616_t,
+    pub scale: ffi::xcb_input_fp1616_t,
+    pub delta_angle: ffi::xcb_input_fp1616_t,
+    pub sourceid: ffi::xcb_input_device_id_t,
+    pub pad0: [u8; 2],
+    pub mods: ffi::xcb_input_modifier_info_t,
+    pub group: ffi::xcb_input_group_info_t,
+    pub flags: u32,
+}
+
+/// The `Input::GestureSwipeBegin`, `Input::GestureSwipeUpdate`, and `Input::GestureSwipeEnd`
+/// events.
+#[derive(Copy, Clone, Debug)]
+#[repr(C)]
+pub struct xcb_input_gesture_swipe_begin_event_t {
+    pub response_type: u8,
+    pub extension: u8,
+    pub sequence: u16,
+    pub length: u32,
+    pub event_type: u16,
+    pub deviceid: ffi::xcb_input_device_id_t,
+    pub time: ffi::xcb_timestamp_t,
+    pub detail: u32,
+    pub root: ffi::xcb_window_t,
+    pub event: ffi::xcb_window_t,
+    pub child: ffi::xcb_window_t,
+    pub full_sequence: u32,
+    pub root_x: ffi::xcb_input_fp1616_t,
+    pub root_y: ffi::xcb_input_fp1616_t,
+    pub event_x: ffi::xcb_input_fp1616_t,
+    pub event_y: ffi::xcb_input_fp1616_t,
+    pub delta_x: ffi::xcb_input_fp1616_t,
+    pub delta_y: ffi::xcb_input_fp1616_t,
+    pub delta_unaccel_x: ffi::xcb_input_fp1616_t,
+    pub delta_unaccel_y: ffi::xcb_input_fp1616_t,
+    pub sourceid: ffi::xcb_input_device_id_t,
+    pub pad0: [u8; 2],
+    pub mods: ffi::xcb_input_modifier_info_t,
+    pub group: ffi::xcb_input_group_info_t,
+    pub flags: u32,
+}
@@ -0,0 +1,244 @@
+//! A decoder for the `COMPOUND_TEXT` encoding.
+//!
+//! Compound text is based on ISO 2022: escape sequences designate the character sets that are
+//! invoked by the left (GL) and right (GR) halves of the byte range. Input methods that do not
+//! support UTF-8 use it to commit text.
+
+use encoding_rs::{
+    Encoding, BIG5, EUC_JP, EUC_KR, GBK, ISO_8859_10, ISO_8859_13, ISO_8859_14, ISO_8859_15,
+    ISO_8859_16, ISO_8859_2, ISO_8859_3, ISO_8859_4, ISO_8859_5, ISO_8859_6, ISO_8859_7,
+    ISO_8859_8, WINDOWS_1254,
+};
+
+const ESC: u8 = 0x1b;
+const CSI: u8 = 0x9b;
+const STX: u8 = 0x02;
+
+#[derive(Copy, Clone)]
+enum Charset {
+    Ascii,
+    /// The right half of ISO 8859-1.
+    Latin1,
+    /// JIS X 0201 Katakana.
+    Katakana,
+    /// A character set whose bytes can be decoded after setting the high bit.
+    ///
+    /// This covers the right halves of the ISO 8859 parts and the 94^2 character sets, which
+    /// are decoded via their EUC form.
+    Encoded(&'static Encoding),
+    Unknown,
+}
+
+struct Decoder {
+    out: String,
+    gl: Charset,
+    gr: Charset,
+    /// Bytes of the current run of `Charset::Encoded` characters.
+    run: Vec<u8>,
+    run_encoding: Option<&'static Encoding>,
+}
+
+/// Decodes compound text. Characters from unknown character sets are replaced by U+FFFD.
+pub fn decode(data: &[u8]) -> String {
+    let mut decoder = Decoder {
+        out: String::new(),
+        gl: Charset::Ascii,
+        gr: Charset::Latin1,
+        run: vec![],
+        run_encoding: None,
+    };
+    let mut pos = 0;
+    while pos < data.len() {
+        let b = data[pos];
+        pos += 1;
+        match b {
+            ESC => pos = decoder.escape(data, pos),
+            CSI => {
+                // Directionality and other control sequences carry no text.
+                while pos < data.len() && !(0x40..=0x7e).contains(&data[pos]) {
+                    pos += 1;
+                }
+                pos += 1;
+            }
+            0x21..=0x7e => decoder.byte(decoder.gl, b),
+            0xa0..=0xff => decoder.byte(decoder.gr, b),
+            b' ' | b'\t' | b'\n' => {
+                decoder.flush();
+                decoder.out.push(b as char);
+            }
+            _ => {}
+        }
+    }
+    decoder.flush();
+    decoder.out
+}
+
+impl Decoder {
+    fn byte(&mut self, charset: Charset, b: u8) {
+        match charset {
+            Charset::Ascii => {
+                self.flush();
+                self.out.push((b & 0x7f) as char);
+            }
+            Charset::Latin1 => {
+                self.flush();
+                self.out.push((b | 0x80) as char);
+            }
+            Charset::Katakana => {
+                self.flush();
+                let b = b & 0x7f;
+                let c = match b {
+                    0x21..=0x5f => char::from_u32(0xff61 + (b - 0x21) as u32),
+                    _ => None,
+                };
+                self.out.push(c.unwrap_or(char::REPLACEMENT_CHARACTER));
+            }
+            Charset::Encoded(encoding) => {
+                if self.run_encoding != Some(encoding) {
+                    self.flush();
+                    self.run_encoding = Some(encoding);
+                }
+                self.run.push(b | 0x80);
+            }
+            Charset::Unknown => {
+                self.flush();
+                self.out.push(char::REPLACEMENT_CHARACTER);
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Some(encoding) = self.run_encoding.take() {
+            let (s, _) = encoding.decode_without_bom_handling(&self.run);
+            self.out.push_str(&s);
+            self.run.clear();
+        }
+    }
+
+    /// Handles the escape sequence starting at `pos`. Returns the position after the sequence.
+    fn escape(&mut self, data: &[u8], pos: usize) -> usize {
+        let rest = &data[pos..];
+        match rest {
+            [b'(', f, ..] => {
+                self.gl = match f {
+                    b'B' | b'J' => Charset::Ascii,
+                    b'I' => Charset::Katakana,
+                    _ => Charset::Unknown,
+                };
+                pos + 2
+            }
+            [b')', f, ..] => {
+                self.gr = match f {
+                    b'I' => Charset::Katakana,
+                    _ => Charset::Unknown,
+                };
+                pos + 2
+            }
+            [b'-', f, ..] => {
+                self.gr = match f {
+                    b'A' => Charset::Latin1,
+                    _ => iso_8859(*f).map_or(Charset::Unknown, Charset::Encoded),
+                };
+                pos + 2
+            }
+            [b'$', b'(', f, ..] => {
+                self.gl = multi_byte(*f);
+                pos + 3
+            }
+            [b'$', b')', f, ..] => {
+                self.gr = multi_byte(*f);
+                pos + 3
+            }
+            [b'$', f @ b'A'..=b'C', ..] => {
+                self.gl = multi_byte(*f);
+                pos + 2
+            }
+            [b'%', b'G', ..] => {
+                // A UTF-8 segment terminated by `ESC % @`.
+                self.flush();
+                let segment = &rest[2..];
+                let len = segment
+                    .windows(3)
+                    .position(|w| w == [ESC, b'%', b'@'])
+                    .unwrap_or(segment.len());
+                self.out.push_str(&String::from_utf8_lossy(&segment[..len]));
+                (pos + 2 + len + 3).min(data.len())
+            }
+            [b'%', b'/', b'0'..=b'4', m, l, ..] => {
+                // An extended segment: the length, the name of the encoding terminated by STX,
+                // and the encoded text.
+                self.flush();
+                let len = (*m as usize & 0x7f) * 128 + (*l as usize & 0x7f);
+                let end = (pos + 5 + len).min(data.len());
+                let segment = &data[pos + 5..end];
+                let name_len = segment
+                    .iter()
+                    .position(|&b| b == STX)
+                    .unwrap_or(segment.len());
+                let name = String::from_utf8_lossy(&segment[..name_len]).to_ascii_lowercase();
+                let text = segment.get(name_len + 1..).unwrap_or(&[]);
+                match extended_segment_encoding(&name) {
+                    Some(encoding) => {
+                        let (s, _) = encoding.decode_without_bom_handling(text);
+                        self.out.push_str(&s);
+                    }
+                    None => {
+                        log::warn!("Unknown compound text segment encoding {}", name);
+                        self.out.push(char::REPLACEMENT_CHARACTER);
+                    }
+                }
+                end
+            }
+            _ => {
+                // Skip unknown sequences: any number of intermediate bytes and a final byte.
+                let len = rest
+                    .iter()
+                    .position(|b| !(0x20..=0x2f).contains(b))
+                    .map_or(rest.len(), |len| len + 1);
+                pos + len
+            }
+        }
+    }
+}
+
+fn iso_8859(f: u8) -> Option<&'static Encoding> {
+    let encoding = match f {
+        b'B' => ISO_8859_2,
+        b'C' => ISO_8859_3,
+        b'D' => ISO_8859_4,
+        b'F' => ISO_8859_7,
+        b'G' => ISO_8859_6,
+        b'H' => ISO_8859_8,
+        b'L' => ISO_8859_5,
+        // The right half of windows-1254 is identical to ISO 8859-9.
+        b'M' => WINDOWS_1254,
+        b'V' => ISO_8859_10,
+        b'Y' => ISO_8859_13,
+        b'_' => ISO_8859_14,
+        b'b' => ISO_8859_15,
+        b'f' => ISO_8859_16,
+        _ => return None,
+    };
+    Some(encoding)
+}
+
+fn multi_byte(f: u8) -> Charset {
+    match f {
+        // GB 2312
+        b'A' => Charset::Encoded(GBK),
+        // JIS X 0208
+        b'B' => Charset::Encoded(EUC_JP),
+        // KS C 5601
+        b'C' => Charset::Encoded(EUC_KR),
+        _ => Charset::Unknown,
+    }
+}
+
+fn extended_segment_encoding(name: &str) -> Option<&'static Encoding> {
+    // Names have the form of the charset registry and encoding of an XLFD, e.g., `big5-0`.
+    let name = name.strip_suffix("-0").unwrap_or(name);
+    match name {
+        "big5" => Some(BIG5),
+        _ => Encoding::for_label(name.as_bytes()),
+    }
+}
@@ -0,0 +1,1318 @@
+//! Support for input methods via the X Input Method protocol (XIM).
+//!
+//! We implement the client side of the protocol on top of the X transport described in "X
+//! Transport Specification for X Input Method". An input context (IC) is created for every
+//! window when it first receives the keyboard focus while input methods are allowed for it.
+//!
+//! If the server supports it, we use the `XIMPreeditCallbacks` style so that the preedit text is
+//! drawn by the application. The server then sends us the changes to the preedit text which we
+//! apply to a local copy and report as `Ime::Preedit` events.
+//!
+//! Key events are forwarded to the input method server synchronously: while the server
+//! processes a key event, further key events are queued so that the application observes them
+//! in their original order. Keys that the server does not consume are sent back to us and are
+//! then processed as usual. Key events that arrive while the queue is empty and that are not
+//! forwarded are processed immediately.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    env, mem,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use self::protocol::{
+    Attributes, Reader, COMMIT_CHARS, COMMIT_KEYSYM, COMMIT_SYNCHRONOUS, ERROR_ICID_VALID,
+    EVENT_SIZE, FORWARD_EVENT_SYNCHRONOUS, KEY_PRESS_MASK, KEY_RELEASE_MASK,
+    PREEDIT_DRAW_NO_FEEDBACK, PREEDIT_DRAW_NO_STRING, XIM_ABSOLUTE_POSITION, XIM_BACKWARD_CHAR,
+    XIM_FORWARD_CHAR, XIM_HIGHLIGHT, XIM_IS_INVISIBLE, XIM_LINE_END, XIM_LINE_START,
+    XIM_PREEDIT_CALLBACKS, XIM_PREEDIT_NONE, XIM_PREEDIT_NOTHING, XIM_PREEDIT_POSITION,
+    XIM_REVERSE, XIM_STATUS_NONE, XIM_STATUS_NOTHING, XIM_UNDERLINE,
+};
+use super::{ffi, util, XConnection};
+use crate::event::{Ime as ImeState, PreeditSpan, PreeditStyle};
+use crate::platform_impl::platform::common::xkb_state;
+use xcb_dl_util::error::XcbError;
+
+mod compound_text;
+mod protocol;
+
+/// The styles we can use in the order of preference.
+const STYLES: [u32; 7] = [
+    XIM_PREEDIT_CALLBACKS | XIM_STATUS_NOTHING,
+    XIM_PREEDIT_CALLBACKS | XIM_STATUS_NONE,
+    XIM_PREEDIT_POSITION | XIM_STATUS_NOTHING,
+    XIM_PREEDIT_POSITION | XIM_STATUS_NONE,
+    XIM_PREEDIT_NOTHING | XIM_STATUS_NOTHING,
+    XIM_PREEDIT_NOTHING | XIM_STATUS_NONE,
+    XIM_PREEDIT_NONE | XIM_STATUS_NONE,
+];
+
+/// If the server has not answered a forwarded key event within this time, we stop waiting for it.
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The number of atoms used to transfer large messages via properties.
+const NUM_TRANSFER_ATOMS: usize = 16;
+
+/// A request sent by a window to the event loop.
+#[derive(Debug)]
+pub enum ImeRequest {
+    /// The position of the candidate window has changed.
+    Position(ffi::xcb_window_t, i16, i16),
+    /// The application has allowed or disallowed input methods for a window.
+    Allow(ffi::xcb_window_t, bool),
+}
+
+/// An event that must be handled by the `EventProcessor`.
+#[derive(Debug)]
+pub enum ImeEvent {
+    /// A key event that should be processed as if it had just been received from the X server.
+    Key(Key),
+    /// An input method event for a window.
+    Ime(ffi::xcb_window_t, ImeState),
+}
+
+/// A key event that passes through the input method.
+#[derive(Copy, Clone, Debug)]
+pub struct Key {
+    pub press: bool,
+    pub deviceid: ffi::xcb_input_device_id_t,
+    pub keycode: u32,
+    pub time: ffi::xcb_timestamp_t,
+    pub root: ffi::xcb_window_t,
+    pub event: ffi::xcb_window_t,
+    pub child: ffi::xcb_window_t,
+    pub root_x: i16,
+    pub root_y: i16,
+    pub event_x: i16,
+    pub event_y: i16,
+    /// The state in the format of a core key event.
+    pub state: u16,
+    pub repeat: bool,
+    /// The XInput2 keyboard state. `None` if the event was synthesized by the input method.
+    pub xi_state: Option<XiState>,
+}
+
+/// The keyboard state attached to an XInput2 key event.
+#[derive(Copy, Clone, Debug)]
+pub struct XiState {
+    pub base_mods: u32,
+    pub latched_mods: u32,
+    pub locked_mods: u32,
+    pub base_group: u32,
+    pub latched_group: u32,
+    pub locked_group: u32,
+    pub effective_group: u32,
+}
+
+impl Key {
+    pub fn new(xkev: &ffi::xcb_input_key_press_event_t) -> Self {
+        let state = (xkev.mods.effective & 0xff) | (xkev.group.effective as u32 & 3) << 13;
+        Key {
+            press: xkev.event_type == ffi::XCB_INPUT_KEY_PRESS,
+            deviceid: xkev.deviceid,
+            keycode: xkev.detail,
+            time: xkev.time,
+            root: xkev.root,
+            event: xkev.event,
+            child: xkev.child,
+            root_x: util::fp1616_to_f64(xkev.root_x) as i16,
+            root_y: util::fp1616_to_f64(xkev.root_y) as i16,
+            event_x: util::fp1616_to_f64(xkev.event_x) as i16,
+            event_y: util::fp1616_to_f64(xkev.event_y) as i16,
+            state: state as u16,
+            repeat: xkev.flags & ffi::XCB_INPUT_KEY_EVENT_FLAGS_KEY_REPEAT != 0,
+            xi_state: Some(XiState {
+                base_mods: xkev.mods.base,
+                latched_mods: xkev.mods.latched,
+                locked_mods: xkev.mods.locked,
+                base_group: xkev.group.base as u32,
+                latched_group: xkev.group.latched as u32,
+                locked_group: xkev.group.locked as u32,
+                effective_group: xkev.group.effective as u32,
+            }),
+        }
+    }
+
+    /// The effective keyboard group of the event.
+    pub fn group(&self) -> u32 {
+        match &self.xi_state {
+            Some(xi_state) => xi_state.effective_group,
+            None => (self.state as u32 >> 13) & 3,
+        }
+    }
+
+    fn to_core(&self) -> [u8; EVENT_SIZE] {
+        let event = ffi::xcb_key_press_event_t {
+            response_type: if self.press {
+                ffi::XCB_KEY_PRESS
+            } else {
+                ffi::XCB_KEY_RELEASE
+            },
+            detail: self.keycode as u8,
+            sequence: 0,
+            time: self.time,
+            root: self.root,
+            event: self.event,
+            child: self.child,
+            root_x: self.root_x,
+            root_y: self.root_y,
+            event_x: self.event_x,
+            event_y: self.event_y,
+            state: self.state,
+            same_screen: 1,
+            pad0: 0,
+        };
+        unsafe { mem::transmute(event) }
+    }
+
+    fn from_core(buf: &[u8], deviceid: ffi::xcb_input_device_id_t) -> Option<Self> {
+        let mut r = Reader::new(buf);
+        let response_type = r.u8()? & 0x7f;
+        let keycode = r.u8()? as u32;
+        let _sequence = r.u16()?;
+        let time = r.u32()?;
+        let root = r.u32()?;
+        let event = r.u32()?;
+        let child = r.u32()?;
+        let root_x = r.i16()?;
+        let root_y = r.i16()?;
+        let event_x = r.i16()?;
+        let event_y = r.i16()?;
+        let state = r.u16()?;
+        let press = match response_type {
+            ffi::XCB_KEY_PRESS => true,
+            ffi::XCB_KEY_RELEASE => false,
+            _ => return None,
+        };
+        Some(Key {
+            press,
+            deviceid,
+            keycode,
+            time,
+            root,
+            event,
+            child,
+            root_x,
+            root_y,
+            event_x,
+            event_y,
+            state,
+            repeat: false,
+            xi_state: None,
+        })
+    }
+}
+
+struct Atoms {
+    xim_servers: ffi::xcb_atom_t,
+    xim_xconnect: ffi::xcb_atom_t,
+    xim_protocol: ffi::xcb_atom_t,
+    xim_moredata: ffi::xcb_atom_t,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Encoding {
+    Utf8,
+    CompoundText,
+}
+
+/// An established connection to an input method server.
+struct Connection {
+    /// The communication window of the server.
+    server: ffi::xcb_window_t,
+    /// `None` until the input method has been opened.
+    imid: Option<u16>,
+    ic_attributes: HashMap<String, u16>,
+    encoding: Encoding,
+    /// `None` until the supported input styles are known. Input contexts can only be created
+    /// afterwards.
+    style: Option<u32>,
+    forward_mask: u32,
+}
+
+enum State {
+    Disconnected,
+    /// We have sent `_XIM_XCONNECT` to the owner of the server selection and are waiting for the
+    /// reply.
+    Connecting,
+    Connected(Connection),
+}
+
+#[derive(Default)]
+struct WindowState {
+    ic: Option<u16>,
+    /// Set while `XIM_CREATE_IC` has been sent but no reply has been received.
+    creating: bool,
+    /// Set if the server refused to create an input context for this window.
+    failed: bool,
+    spot: Option<(i16, i16)>,
+    /// Set if the application has allowed input methods for this window.
+    allowed: bool,
+    /// Set while the input context has the focus, i.e., after we have sent `Ime::Enabled`.
+    enabled: bool,
+    preedit: Preedit,
+    /// Set if the last preedit event we have sent contained text.
+    preedit_shown: bool,
+}
+
+/// The preedit text as drawn by the server via `XIM_PREEDIT_DRAW`.
+#[derive(Default)]
+struct Preedit {
+    text: Vec<char>,
+    /// The `XIMFeedback` of each character in `text`.
+    feedback: Vec<u32>,
+    /// The caret position in characters.
+    caret: usize,
+    /// Set if the server has asked us to hide the caret.
+    caret_hidden: bool,
+}
+
+impl Preedit {
+    fn to_event(&self) -> ImeState {
+        let mut text = String::new();
+        let mut spans: Vec<PreeditSpan> = vec![];
+        let mut cursor = None;
+        for (i, (&c, &feedback)) in self.text.iter().zip(&self.feedback).enumerate() {
+            if i == self.caret {
+                cursor = Some(text.len());
+            }
+            let start = text.len();
+            text.push(c);
+            let style = feedback_to_style(feedback);
+            if style.is_empty() {
+                continue;
+            }
+            match spans.last_mut() {
+                Some(span) if span.end == start && span.style == style => span.end = text.len(),
+                _ => spans.push(PreeditSpan {
+                    start,
+                    end: text.len(),
+                    style,
+                }),
+            }
+        }
+        let cursor = if self.caret_hidden || text.is_empty() {
+            None
+        } else {
+            Some(cursor.unwrap_or(text.len()))
+        };
+        ImeState::Preedit {
+            text,
+            cursor: cursor.map(|c| (c, c)),
+            spans,
+        }
+    }
+}
+
+fn feedback_to_style(feedback: u32) -> PreeditStyle {
+    let mut style = PreeditStyle::empty();
+    if feedback & XIM_REVERSE != 0 {
+        style |= PreeditStyle::REVERSE;
+    }
+    if feedback & XIM_UNDERLINE != 0 {
+        style |= PreeditStyle::UNDERLINE;
+    }
+    if feedback & XIM_HIGHLIGHT != 0 {
+        style |= PreeditStyle::HIGHLIGHT;
+    }
+    style
+}
+
+/// A key event in the queue.
+struct QueuedKey {
+    key: Key,
+    /// Set if the key event has been forwarded to the server and we're waiting for
+    /// `XIM_SYNC_REPLY`. Contains the time at which we stop waiting.
+    deadline: Option<Instant>,
+}
+
+pub struct Ime {
+    xconn: Arc<XConnection>,
+    atoms: Atoms,
+    /// Our communication window.
+    window: ffi::xcb_window_t,
+    root: ffi::xcb_window_t,
+    /// `None` if the user has disabled input methods via `XMODIFIERS`.
+    server_name: Option<String>,
+    state: State,
+    /// The partially received message.
+    incoming: Vec<u8>,
+    next_transfer_atom: usize,
+    windows: HashMap<ffi::xcb_window_t, WindowState>,
+    /// The windows whose `XIM_CREATE_IC` has not yet been answered, in order.
+    pending_ics: VecDeque<ffi::xcb_window_t>,
+    focus: Option<ffi::xcb_window_t>,
+    keys: VecDeque<QueuedKey>,
+    last_device: ffi::xcb_input_device_id_t,
+    events: VecDeque<ImeEvent>,
+}
+
+impl Ime {
+    pub fn new(xconn: Arc<XConnection>) -> Self {
+        let atoms = Atoms {
+            xim_servers: xconn.get_atom("XIM_SERVERS"),
+            xim_xconnect: xconn.get_atom("_XIM_XCONNECT"),
+            xim_protocol: xconn.get_atom("_XIM_PROTOCOL"),
+            xim_moredata: xconn.get_atom("_XIM_MOREDATA"),
+        };
+        let root = xconn.screens[xconn.default_screen_id].root;
+        let window = xconn.generate_id();
+        unsafe {
+            let cookie = xconn.xcb.xcb_create_window_checked(
+                xconn.c,
+                ffi::XCB_COPY_FROM_PARENT as _,
+                window,
+                root,
+                0,
+                0,
+                1,
+                1,
+                0,
+                ffi::XCB_WINDOW_CLASS_INPUT_ONLY as u16,
+                ffi::XCB_COPY_FROM_PARENT as _,
+                0,
+                std::ptr::null(),
+            );
+            if let Err(e) = xconn.check_cookie(cookie) {
+                log::error!("Could not create the input method window: {}", e);
+            }
+        }
+        let server_name = server_name_from_modifiers(env::var("XMODIFIERS").ok().as_deref());
+        let mut ime = Ime {
+            xconn,
+            atoms,
+            window,
+            root,
+            server_name,
+            state: State::Disconnected,
+            incoming: vec![],
+            next_transfer_atom: 0,
+            windows: Default::default(),
+            pending_ics: Default::default(),
+            focus: None,
+            keys: Default::default(),
+            last_device: 0,
+            events: Default::default(),
+        };
+        if ime.server_name.is_some() {
+            // Get notified when input method servers are started.
+            let mask = ffi::XCB_EVENT_MASK_PROPERTY_CHANGE;
+            unsafe {
+                let cookie = ime.xconn.xcb.xcb_change_window_attributes_checked(
+                    ime.xconn.c,
+                    root,
+                    ffi::XCB_CW_EVENT_MASK,
+                    &mask as *const _ as _,
+                );
+                if let Err(e) = ime.xconn.check_cookie(cookie) {
+                    log::error!("Could not select property events on the root window: {}", e);
+                }
+            }
+            ime.connect();
+        }
+        ime
+    }
+
+    /// Returns the next event that must be handled.
+    pub fn next_event(&mut self) -> Option<ImeEvent> {
+        self.events.pop_front()
+    }
+
+    /// Tries to connect to the input method server.
+    fn connect(&mut self) {
+        let server_name = match &self.server_name {
+            Some(name) => name,
+            _ => return,
+        };
+        let servers = match self.xconn.get_property::<ffi::xcb_atom_t>(
+            self.root,
+            self.atoms.xim_servers,
+            ffi::XCB_ATOM_ATOM,
+        ) {
+            Ok(servers) => servers,
+            Err(e) => {
+                log::debug!("Could not read the list of input method servers: {}", e);
+                return;
+            }
+        };
+        let mut candidates = vec![];
+        for server in servers {
+            match self.xconn.get_atom_name(server) {
+                Ok(name) if name.starts_with("@server=") => {
+                    // An empty name matches any server. If the requested server is not
+                    // available, we still fall back to the other servers.
+                    if server_name.is_empty() || name["@server=".len()..] == *server_name {
+                        candidates.insert(0, server);
+                    } else {
+                        candidates.push(server);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("Could not retrieve the name of atom {}: {}", server, e),
+            }
+        }
+        for server in candidates {
+            let owner = match self.selection_owner(server) {
+                Ok(owner) if owner != ffi::XCB_NONE => owner,
+                Ok(_) => continue,
+                Err(e) => {
+                    log::warn!("Could not retrieve the input method server window: {}", e);
+                    continue;
+                }
+            };
+            let pending = self.xconn.send_client_msg(
+                owner,
+                owner,
+                self.atoms.xim_xconnect,
+                None,
+                [self.window, 0, 0, 0, 0],
+            );
+            match self.xconn.check_pending1(pending) {
+                Ok(()) => {
+                    self.state = State::Connecting;
+                    return;
+                }
+                Err(e) => log::warn!("Could not connect to the input method server: {}", e),
+            }
+        }
+    }
+
+    fn selection_owner(&self, selection: ffi::xcb_atom_t) -> Result<ffi::xcb_window_t, XcbError> {
+        unsafe {
+            let cookie = self
+                .xconn
+                .xcb
+                .xcb_get_selection_owner(self.xconn.c, selection);
+            let mut err = std::ptr::null_mut();
+            let reply =
+                self.xconn
+                    .xcb
+                    .xcb_get_selection_owner_reply(self.xconn.c, cookie, &mut err);
+            self.xconn.check(reply, err).map(|r| r.owner)
+        }
+    }
+
+    /// Drops the connection to the server. All queued key events are processed locally.
+    fn disconnect(&mut self) {
+        if let State::Connected(conn) = &self.state {
+            // Stop listening for the destruction of the server window.
+            self.select_structure_events(conn.server, false);
+        }
+        self.state = State::Disconnected;
+        self.incoming.clear();
+        self.pending_ics.clear();
+        for (&id, window) in &mut self.windows {
+            window.ic = None;
+            window.creating = false;
+            window.failed = false;
+            if mem::take(&mut window.enabled) {
+                window.preedit = Preedit::default();
+                window.preedit_shown = false;
+                self.events.push_back(ImeEvent::Ime(id, ImeState::Disabled));
+            }
+        }
+        for key in self.keys.drain(..) {
+            self.events.push_back(ImeEvent::Key(key.key));
+        }
+    }
+
+    fn select_structure_events(&self, window: ffi::xcb_window_t, select: bool) {
+        let mask = if select {
+            ffi::XCB_EVENT_MASK_STRUCTURE_NOTIFY
+        } else {
+            ffi::XCB_EVENT_MASK_NO_EVENT
+        };
+        unsafe {
+            let cookie = self.xconn.xcb.xcb_change_window_attributes_checked(
+                self.xconn.c,
+                window,
+                ffi::XCB_CW_EVENT_MASK,
+                &mask as *const _ as _,
+            );
+            // The server window might already have been destroyed.
+            if let Err(e) = self.xconn.check_cookie(cookie) {
+                log::debug!("Could not select structure events on {}: {}", window, e);
+            }
+        }
+    }
+
+    /// Sends a message to the server. Drops the connection if this fails.
+    fn send(&mut self, msg: Vec<u8>) -> bool {
+        let server = match &self.state {
+            State::Connected(conn) => conn.server,
+            _ => return false,
+        };
+        let res = if msg.len() <= 20 {
+            let mut data = [0u8; 20];
+            data[..msg.len()].copy_from_slice(&msg);
+            let pending =
+                self.xconn
+                    .send_client_msg(server, server, self.atoms.xim_protocol, None, data);
+            self.xconn.check_pending1(pending)
+        } else {
+            // Larger messages are transferred via a property on the server window. We rotate
+            // between several properties since the server might not have read the previous
+            // message yet.
+            let atom = self
+                .xconn
+                .get_atom(&format!("_WINIT_XIM_DATA_{}", self.next_transfer_atom));
+            self.next_transfer_atom = (self.next_transfer_atom + 1) % NUM_TRANSFER_ATOMS;
+            let pending = self.xconn.change_property(
+                server,
+                atom,
+                ffi::XCB_ATOM_STRING,
+                util::PropMode::Append,
+                &msg[..],
+            );
+            self.xconn.check_pending1(pending).and_then(|_| {
+                let pending = self.xconn.send_client_msg(
+                    server,
+                    server,
+                    self.atoms.xim_protocol,
+                    None,
+                    [msg.len() as u32, atom, 0, 0, 0],
+                );
+                self.xconn.check_pending1(pending)
+            })
+        };
+        if let Err(e) = res {
+            log::error!("Could not send a message to the input method server: {}", e);
+            self.disconnect();
+            return false;
+        }
+        true
+    }
+
+    /// Handles a `ClientMessage`. Returns `true` if the message was sent to us.
+    pub fn handle_client_message(&mut self, xev: &ffi::xcb_client_message_event_t) -> bool {
+        if xev.window != self.window {
+            return false;
+        }
+        let data32 = unsafe { xev.data.data32 };
+        if xev.type_ == self.atoms.xim_xconnect {
+            if let State::Connecting = self.state {
+                let server = data32[0];
+                self.state = State::Connected(Connection {
+                    server,
+                    imid: None,
+                    ic_attributes: Default::default(),
+                    encoding: Encoding::CompoundText,
+                    style: None,
+                    forward_mask: KEY_PRESS_MASK,
+                });
+                self.select_structure_events(server, true);
+                self.send(protocol::connect());
+            }
+        } else if xev.type_ == self.atoms.xim_moredata {
+            self.incoming
+                .extend_from_slice(unsafe { &xev.data.data8[..] });
+        } else if xev.type_ == self.atoms.xim_protocol {
+            let msg = if xev.format == 32 {
+                // The server might have appended several messages to the property before we
+                // read it. Since every message carries its own length, we process all of them
+                // now and ignore the length in this notification.
+                let property = data32[1];
+                match self.xconn.get_property_raw(self.window, property, true) {
+                    Ok((_, data)) => data,
+                    Err(e) => {
+                        log::error!("Could not read a message of the input method server: {}", e);
+                        return true;
+                    }
+                }
+            } else {
+                self.incoming
+                    .extend_from_slice(unsafe { &xev.data.data8[..] });
+                mem::take(&mut self.incoming)
+            };
+            self.handle_messages(&msg);
+        }
+        true
+    }
+
+    /// Handles a `PropertyNotify` event.
+    pub fn handle_property_notify(&mut self, xev: &ffi::xcb_property_notify_event_t) {
+        if xev.window == self.root && xev.atom == self.atoms.xim_servers {
+            if let State::Disconnected = self.state {
+                self.connect();
+            }
+        }
+    }
+
+    /// Handles a `DestroyNotify` event. Returns `true` if the destroyed window belonged to the
+    /// server.
+    pub fn handle_destroy_notify(&mut self, window: ffi::xcb_window_t) -> bool {
+        let is_server = match &self.state {
+            State::Connected(conn) => conn.server == window,
+            _ => false,
+        };
+        if is_server {
+            log::warn!("The input method server has disappeared");
+            self.disconnect();
+            // Maybe another server is available.
+            self.connect();
+        }
+        is_server
+    }
+
+    /// Forgets a window that has been destroyed.
+    pub fn remove_window(&mut self, window: ffi::xcb_window_t) {
+        if self.focus == Some(window) {
+            self.focus = None;
+        }
+        if let Some(ic) = self.windows.remove(&window).and_then(|w| w.ic) {
+            if let Some(imid) = self.imid() {
+                self.send(protocol::ic_request(protocol::XIM_DESTROY_IC, imid, ic));
+            }
+        }
+    }
+
+    pub fn handle_request(&mut self, request: ImeRequest) {
+        match request {
+            ImeRequest::Position(window, x, y) => {
+                let ic = {
+                    let state = self.windows.entry(window).or_default();
+                    state.spot = Some((x, y));
+                    state.ic
+                };
+                if let Some(ic) = ic {
+                    if let Some(attributes) = self.spot_attributes(x, y) {
+                        let imid = self.imid().unwrap();
+                        self.send(protocol::set_ic_values(imid, ic, &attributes));
+                    }
+                }
+            }
+            ImeRequest::Allow(window, allowed) => {
+                let state = self.windows.entry(window).or_default();
+                if state.allowed == allowed {
+                    return;
+                }
+                state.allowed = allowed;
+                if self.focus == Some(window) {
+                    if allowed {
+                        self.activate(window);
+                    } else {
+                        self.deactivate(window);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Builds the `preeditAttributes` containing the spot location if the server supports them.
+    fn spot_attributes(&self, x: i16, y: i16) -> Option<Attributes> {
+        let conn = match &self.state {
+            State::Connected(conn) => conn,
+            _ => return None,
+        };
+        if conn.style? & (XIM_PREEDIT_POSITION | XIM_PREEDIT_CALLBACKS) == 0 {
+            return None;
+        }
+        let preedit = *conn.ic_attributes.get("preeditAttributes")?;
+        let spot = *conn.ic_attributes.get("spotLocation")?;
+        let mut nested = Attributes::default();
+        nested.point(spot, x, y);
+        let mut attributes = Attributes::default();
+        attributes.nested(preedit, &nested);
+        Some(attributes)
+    }
+
+    fn imid(&self) -> Option<u16> {
+        match &self.state {
+            State::Connected(conn) => conn.imid,
+            _ => None,
+        }
+    }
+
+    /// Handles a window gaining the keyboard focus.
+    pub fn focus_in(&mut self, window: ffi::xcb_window_t) {
+        if self.focus == Some(window) {
+            return;
+        }
+        if let Some(old) = self.focus {
+            self.focus_out(old);
+        }
+        self.focus = Some(window);
+        if self.windows.entry(window).or_default().allowed {
+            self.activate(window);
+        }
+    }
+
+    /// Handles a window losing the keyboard focus.
+    pub fn focus_out(&mut self, window: ffi::xcb_window_t) {
+        if self.focus != Some(window) {
+            return;
+        }
+        self.focus = None;
+        self.deactivate(window);
+    }
+
+    /// Gives the input context of a focused window the focus, creating it if necessary.
+    fn activate(&mut self, window: ffi::xcb_window_t) {
+        let state = self.windows.entry(window).or_default();
+        if state.enabled {
+            return;
+        }
+        let ic = match state.ic {
+            Some(ic) => ic,
+            None => return self.create_ic(window),
+        };
+        state.enabled = true;
+        let imid = self.imid().unwrap();
+        self.events
+            .push_back(ImeEvent::Ime(window, ImeState::Enabled));
+        self.send(protocol::ic_request(protocol::XIM_SET_IC_FOCUS, imid, ic));
+    }
+
+    /// Removes the focus from the input context of a window.
+    fn deactivate(&mut self, window: ffi::xcb_window_t) {
+        let state = match self.windows.get_mut(&window) {
+            Some(state) if state.enabled => state,
+            _ => return,
+        };
+        state.enabled = false;
+        state.preedit = Preedit::default();
+        state.preedit_shown = false;
+        let ic = state.ic.unwrap();
+        let imid = self.imid().unwrap();
+        self.events
+            .push_back(ImeEvent::Ime(window, ImeState::Disabled));
+        self.send(protocol::ic_request(protocol::XIM_UNSET_IC_FOCUS, imid, ic));
+    }
+
+    fn create_ic(&mut self, window: ffi::xcb_window_t) {
+        let (imid, style, attrs) = match &self.state {
+            State::Connected(Connection {
+                imid: Some(imid),
+                style: Some(style),
+                ic_attributes,
+                ..
+            }) => (*imid, *style, ic_attributes),
+            _ => return,
+        };
+        let state = self.windows.entry(window).or_default();
+        if state.ic.is_some() || state.creating || state.failed {
+            return;
+        }
+        let mut attributes = Attributes::default();
+        for (name, value) in [
+            ("inputStyle", style),
+            ("clientWindow", window),
+            ("focusWindow", window),
+        ] {
+            if let Some(&id) = attrs.get(name) {
+                attributes.u32(id, value);
+            }
+        }
+        let spot = state.spot;
+        if let Some(spot) = spot.and_then(|(x, y)| self.spot_attributes(x, y)) {
+            attributes.extend(&spot);
+        }
+        self.windows.get_mut(&window).unwrap().creating = true;
+        self.pending_ics.push_back(window);
+        self.send(protocol::create_ic(imid, &attributes));
+    }
+
+    fn window_of_ic(&self, icid: u16) -> Option<ffi::xcb_window_t> {
+        self.windows
+            .iter()
+            .find(|(_, w)| w.ic == Some(icid))
+            .map(|(&window, _)| window)
+    }
+
+    /// Adds a key event to the queue.
+    pub fn push_key(&mut self, key: Key) {
+        self.last_device = key.deviceid;
+        if self.keys.is_empty() && self.forward_target(&key).is_none() {
+            // Nothing to wait for. Don't delay the event.
+            self.events.push_back(ImeEvent::Key(key));
+            return;
+        }
+        self.keys.push_back(QueuedKey {
+            key,
+            deadline: None,
+        });
+        self.process_keys();
+    }
+
+    /// Returns the time at which `handle_timeouts` has to be called.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.keys.front().and_then(|front| front.deadline)
+    }
+
+    /// Stops waiting for a forwarded key event that the server has not answered in time.
+    pub fn handle_timeouts(&mut self) {
+        match self.next_deadline() {
+            Some(deadline) if deadline <= Instant::now() => {}
+            _ => return,
+        }
+        log::warn!("The input method server did not process a key event in time");
+        let front = self.keys.pop_front().unwrap();
+        self.events.push_back(ImeEvent::Key(front.key));
+        self.process_keys();
+    }
+
+    /// Forwards the first queued key event to the server or processes queued key events locally
+    /// until the queue is empty.
+    fn process_keys(&mut self) {
+        while let Some(front) = self.keys.front() {
+            if front.deadline.is_some() {
+                return;
+            }
+            let key = front.key;
+            match self.forward_target(&key) {
+                Some((imid, icid)) => {
+                    self.keys.front_mut().unwrap().deadline =
+                        Some(Instant::now() + FORWARD_TIMEOUT);
+                    // If this fails, the queue is flushed by `disconnect`.
+                    let event = key.to_core();
+                    self.send(protocol::forward_event(
+                        imid,
+                        icid,
+                        FORWARD_EVENT_SYNCHRONOUS,
+                        &event,
+                    ));
+                    return;
+                }
+                None => {
+                    self.keys.pop_front();
+                    self.events.push_back(ImeEvent::Key(key));
+                }
+            }
+        }
+    }
+
+    /// Returns the ids of the input context to which the key should be forwarded.
+    fn forward_target(&self, key: &Key) -> Option<(u16, u16)> {
+        let conn = match &self.state {
+            State::Connected(conn) => conn,
+            _ => return None,
+        };
+        let mask = if key.press {
+            KEY_PRESS_MASK
+        } else {
+            KEY_RELEASE_MASK
+        };
+        if self.focus != Some(key.event) || conn.forward_mask & mask == 0 {
+            return None;
+        }
+        let state = self.windows.get(&key.event)?;
+        if !state.enabled {
+            return None;
+        }
+        Some((conn.imid?, state.ic?))
+    }
+
+    fn handle_messages(&mut self, mut buf: &[u8]) {
+        // Messages sent via client messages are padded with zeros.
+        while buf.len() >= 4 && buf[0] != 0 {
+            let len = 4 + 4 * u16::from_ne_bytes([buf[2], buf[3]]) as usize;
+            if len > buf.len() {
+                log::warn!("Received a truncated message from the input method server");
+                return;
+            }
+            if self.handle_message(&buf[..len]).is_none() {
+                log::warn!(
+                    "Received a malformed message from the input method server: {:?}",
+                    &buf[..len]
+                );
+            }
+            buf = &buf[len..];
+        }
+    }
+
+    fn handle_message(&mut self, msg: &[u8]) -> Option<()> {
+        let mut r = Reader::new(msg);
+        let major = r.u8()?;
+        let _minor = r.u8()?;
+        let _len = r.u16()?;
+        match major {
+            protocol::XIM_CONNECT_REPLY => {
+                self.send(protocol::open(&locale()));
+            }
+            protocol::XIM_OPEN_REPLY => {
+                let imid = r.u16()?;
+                let n = r.u16()? as usize;
+                let im_attributes = protocol::parse_attribute_names(r.bytes(n)?)?;
+                let m = r.u16()? as usize;
+                let _unused = r.u16()?;
+                let ic_attributes = protocol::parse_attribute_names(r.bytes(m)?)?;
+                let query_input_style = im_attributes
+                    .iter()
+                    .find(|(name, _)| name == "queryInputStyle")
+                    .map(|&(_, id)| id);
+                if let State::Connected(conn) = &mut self.state {
+                    conn.imid = Some(imid);
+                    conn.ic_attributes = ic_attributes.into_iter().collect();
+                }
+                let query_input_style = match query_input_style {
+                    Some(id) => id,
+                    _ => {
+                        log::error!("The input method server does not support queryInputStyle");
+                        self.disconnect();
+                        return Some(());
+                    }
+                };
+                self.send(protocol::encoding_negotiation(
+                    imid,
+                    &["UTF-8", "COMPOUND_TEXT"],
+                ));
+                self.send(protocol::get_im_values(imid, &[query_input_style]));
+            }
+            protocol::XIM_ENCODING_NEGOTIATION_REPLY => {
+                let _imid = r.u16()?;
+                let _category = r.u16()?;
+                let index = r.i16()?;
+                if let State::Connected(conn) = &mut self.state {
+                    conn.encoding = match index {
+                        0 => Encoding::Utf8,
+                        _ => Encoding::CompoundText,
+                    };
+                }
+            }
+            protocol::XIM_GET_IM_VALUES_REPLY => {
+                let _imid = r.u16()?;
+                let n = r.u16()? as usize;
+                let attributes = protocol::parse_attributes(r.bytes(n)?)?;
+                let mut styles = vec![];
+                for (_, value) in attributes {
+                    styles.extend(protocol::parse_styles(value)?);
+                }
+                let style = match STYLES.iter().find(|&&s| styles.contains(&s)) {
+                    Some(&style) => style,
+                    _ => {
+                        log::error!(
+                            "The input method server supports none of our input styles: {:x?}",
+                            styles
+                        );
+                        self.disconnect();
+                        return Some(());
+                    }
+                };
+                if let State::Connected(conn) = &mut self.state {
+                    conn.style = Some(style);
+                }
+                if let Some(focus) = self.focus {
+                    if self.windows[&focus].allowed {
+                        self.activate(focus);
+                    }
+                }
+            }
+            protocol::XIM_CREATE_IC_REPLY => {
+                let imid = r.u16()?;
+                let icid = r.u16()?;
+                let window = self.pending_ics.pop_front()?;
+                match self.windows.get_mut(&window) {
+                    Some(state) => {
+                        state.creating = false;
+                        state.ic = Some(icid);
+                    }
+                    None => {
+                        // The window has been destroyed in the meantime.
+                        self.send(protocol::ic_request(protocol::XIM_DESTROY_IC, imid, icid));
+                        return Some(());
+                    }
+                }
+                if self.focus == Some(window) && self.windows[&window].allowed {
+                    self.activate(window);
+                }
+            }
+            protocol::XIM_SET_EVENT_MASK => {
+                let _imid = r.u16()?;
+                let _icid = r.u16()?;
+                let forward_mask = r.u32()?;
+                let _synchronous_mask = r.u32()?;
+                if let State::Connected(conn) = &mut self.state {
+                    conn.forward_mask = forward_mask;
+                }
+            }
+            protocol::XIM_FORWARD_EVENT => {
+                let imid = r.u16()?;
+                let icid = r.u16()?;
+                let flag = r.u16()?;
+                let _serial = r.u16()?;
+                let key = Key::from_core(r.bytes(EVENT_SIZE)?, self.last_device)?;
+                // If this is the key event we forwarded, use the original event which contains
+                // more information.
+                let key = match self.keys.front() {
+                    Some(front)
+                        if front.deadline.is_some()
+                            && front.key.keycode == key.keycode
+                            && front.key.time == key.time
+                            && front.key.press == key.press =>
+                    {
+                        front.key
+                    }
+                    _ => key,
+                };
+                self.events.push_back(ImeEvent::Key(key));
+                if flag & FORWARD_EVENT_SYNCHRONOUS != 0 {
+                    self.send(protocol::ic_request(protocol::XIM_SYNC_REPLY, imid, icid));
+                }
+            }
+            protocol::XIM_SYNC => {
+                let imid = r.u16()?;
+                let icid = r.u16()?;
+                self.send(protocol::ic_request(protocol::XIM_SYNC_REPLY, imid, icid));
+            }
+            protocol::XIM_SYNC_REPLY => {
+                if let Some(front) = self.keys.front() {
+                    if front.deadline.is_some() {
+                        self.keys.pop_front();
+                        self.process_keys();
+                    }
+                }
+            }
+            protocol::XIM_COMMIT => {
+                let imid = r.u16()?;
+                let icid = r.u16()?;
+                let flag = r.u16()?;
+                let mut text = String::new();
+                if flag & COMMIT_KEYSYM != 0 {
+                    let _unused = r.u16()?;
+                    let keysym = r.u32()?;
+                    if flag & COMMIT_CHARS == 0 {
+                        text.push_str(xkb_state::keysym_to_utf8_raw(keysym).unwrap_or_default());
+                    }
+                }
+                if flag & COMMIT_CHARS != 0 {
+                    let n = r.u16()? as usize;
+                    text = self.decode(r.bytes(n)?);
+                }
+                if let Some(window) = self.window_of_ic(icid) {
+                    if !text.is_empty() {
+                        // The committed text replaces the preedit text. The server usually
+                        // clears the preedit text afterwards but applications expect it to be
+                        // gone before the commit.
+                        self.clear_preedit(window);
+                        self.events
+                            .push_back(ImeEvent::Ime(window, ImeState::Commit(text)));
+                    }
+                }
+                if flag & COMMIT_SYNCHRONOUS != 0 {
+                    self.send(protocol::ic_request(protocol::XIM_SYNC_REPLY, imid, icid));
+                }
+            }
+            protocol::XIM_PREEDIT_START => {
+                let imid = r.u16()?;
+                let icid = r.u16()?;
+                if let Some(state) = self.window_state_of_ic(icid) {
+                    state.preedit = Preedit::default();
+                }
+                // We do not limit the length of the preedit text.
+                self.send(protocol::preedit_start_reply(imid, icid, -1));
+            }
+            protocol::XIM_PREEDIT_DRAW => {
+                let _imid = r.u16()?;
+                let icid = r.u16()?;
+                let caret = r.i32()?;
+                let chg_first = r.i32()?;
+                let chg_length = r.i32()?;
+                let status = r.u32()?;
+                let n = r.u16()? as usize;
+                let bytes = r.bytes(n)?;
+                r.align();
+                let m = r.u16()? as usize;
+                let _unused = r.u16()?;
+                let mut feedback = Vec::with_capacity(m / 4);
+                for _ in 0..m / 4 {
+                    feedback.push(r.u32()?);
+                }
+                let text: Vec<char> = match status & PREEDIT_DRAW_NO_STRING {
+                    0 => self.decode(bytes).chars().collect(),
+                    _ => vec![],
+                };
+                let window = match self.window_of_ic(icid) {
+                    Some(window) => window,
+                    _ => return Some(()),
+                };
+                let preedit = &mut self.windows.get_mut(&window).unwrap().preedit;
+                let len = preedit.text.len();
+                let first = (chg_first.max(0) as usize).min(len);
+                let last = first.saturating_add(chg_length.max(0) as usize).min(len);
+                if text.is_empty() && !feedback.is_empty() {
+                    // Only the feedback of the existing characters has changed.
+                    for (old, new) in preedit.feedback[first..].iter_mut().zip(feedback) {
+                        *old = new;
+                    }
+                } else {
+                    if status & PREEDIT_DRAW_NO_FEEDBACK != 0 {
+                        feedback.clear();
+                    }
+                    feedback.resize(text.len(), 0);
+                    preedit.text.splice(first..last, text);
+                    preedit.feedback.splice(first..last, feedback);
+                }
+                preedit.caret = (caret.max(0) as usize).min(preedit.text.len());
+                self.push_preedit(window);
+            }
+            protocol::XIM_PREEDIT_CARET => {
+                let imid = r.u16()?;
+                let icid = r.u16()?;
+                let position = r.i32()?;
+                let direction = r.u32()?;
+                let style = r.u32()?;
+                let mut caret = 0;
+                if let Some(window) = self.window_of_ic(icid) {
+                    let preedit = &mut self.windows.get_mut(&window).unwrap().preedit;
+                    let len = preedit.text.len();
+                    preedit.caret = match direction {
+                        XIM_FORWARD_CHAR => (preedit.caret + 1).min(len),
+                        XIM_BACKWARD_CHAR => preedit.caret.saturating_sub(1),
+                        XIM_LINE_START => 0,
+                        XIM_LINE_END => len,
+                        XIM_ABSOLUTE_POSITION => (position.max(0) as usize).min(len),
+                        // Movements by words or lines are not supported.
+                        _ => preedit.caret,
+                    };
+                    preedit.caret_hidden = style == XIM_IS_INVISIBLE;
+                    caret = preedit.caret;
+                    self.push_preedit(window);
+                }
+                self.send(protocol::preedit_caret_reply(imid, icid, caret as u32));
+            }
+            protocol::XIM_PREEDIT_DONE => {
+                let _imid = r.u16()?;
+                let icid = r.u16()?;
+                if let Some(window) = self.window_of_ic(icid) {
+                    self.windows.get_mut(&window).unwrap().preedit = Preedit::default();
+                    self.clear_preedit(window);
+                }
+            }
+            protocol::XIM_ERROR => {
+                let _imid = r.u16()?;
+                let _icid = r.u16()?;
+                let flag = r.u16()?;
+                let code = r.u16()?;
+                let n = r.u16()? as usize;
+                let _ty = r.u16()?;
+                let detail = String::from_utf8_lossy(r.bytes(n)?);
+                log::error!("The input method server sent an error {}: {}", code, detail);
+                let style_known = match &self.state {
+                    State::Connected(conn) => conn.style.is_some(),
+                    _ => false,
+                };
+                if !style_known {
+                    // The connection cannot be established.
+                    self.disconnect();
+                } else if flag & ERROR_ICID_VALID == 0 {
+                    if let Some(window) = self.pending_ics.pop_front() {
+                        // Assume that this error is the answer to `XIM_CREATE_IC`.
+                        if let Some(state) = self.windows.get_mut(&window) {
+                            state.creating = false;
+                            state.failed = true;
+                        }
+                    }
+                } else if let Some(front) = self.keys.front() {
+                    // Assume that the server could not process the key event.
+                    if front.deadline.is_some() {
+                        let front = self.keys.pop_front().unwrap();
+                        self.events.push_back(ImeEvent::Key(front.key));
+                        self.process_keys();
+                    }
+                }
+            }
+            protocol::XIM_DISCONNECT_REPLY
+            | protocol::XIM_REGISTER_TRIGGERKEYS
+            | protocol::XIM_SET_IC_VALUES_REPLY
+            | protocol::XIM_DESTROY_IC_REPLY => {}
+            _ => log::debug!("Ignoring input method message {}", major),
+        }
+        Some(())
+    }
+
+    /// Emits the current preedit text of a window if its input context has the focus.
+    ///
+    /// An empty preedit text is only emitted if it replaces a non-empty one.
+    fn push_preedit(&mut self, window: ffi::xcb_window_t) {
+        let state = self.windows.get_mut(&window).unwrap();
+        let empty = state.preedit.text.is_empty();
+        if !state.enabled || (empty && !state.preedit_shown) {
+            return;
+        }
+        state.preedit_shown = !empty;
+        let event = state.preedit.to_event();
+        self.events.push_back(ImeEvent::Ime(window, event));
+    }
+
+    /// Emits an empty preedit text if the application is currently showing a preedit text.
+    fn clear_preedit(&mut self, window: ffi::xcb_window_t) {
+        let state = self.windows.get_mut(&window).unwrap();
+        if mem::take(&mut state.preedit_shown) {
+            let event = ImeState::Preedit {
+                text: String::new(),
+                cursor: None,
+                spans: vec![],
+            };
+            self.events.push_back(ImeEvent::Ime(window, event));
+        }
+    }
+
+    fn window_state_of_ic(&mut self, icid: u16) -> Option<&mut WindowState> {
+        let window = self.window_of_ic(icid)?;
+        self.windows.get_mut(&window)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> String {
+        match self.encoding() {
+            Encoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+            Encoding::CompoundText => compound_text::decode(bytes),
+        }
+    }
+
+    fn encoding(&self) -> Encoding {
+        match &self.state {
+            State::Connected(conn) => conn.encoding,
+            _ => Encoding::CompoundText,
+        }
+    }
+}
+
+impl Drop for Ime {
+    fn drop(&mut self) {
+        if let State::Connected(_) = self.state {
+            self.send(protocol::disconnect());
+        }
+        unsafe {
+            let cookie = self
+                .xconn
+                .xcb
+                .xcb_destroy_window_checked(self.xconn.c, self.window);
+            if let Err(e) = self.xconn.check_cookie(cookie) {
+                log::error!("Could not destroy the input method window: {}", e);
+            }
+        }
+    }
+}
+
+/// Returns the name of the server requested via `XMODIFIERS`, e.g., `@im=ibus`.
+///
+/// Returns `None` if input methods have been disabled via `@im=none`. Returns an empty string if
+/// no server has been requested.
+fn server_name_from_modifiers(modifiers: Option<&str>) -> Option<String> {
+    let modifiers = modifiers.unwrap_or("");
+    let name = match modifiers.find("@im=") {
+        Some(pos) => {
+            let name = &modifiers[pos + "@im=".len()..];
+            name.split('@').next().unwrap_or("")
+        }
+        _ => "",
+    };
+    if name == "none" {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+/// Returns the locale that is sent to the server in `XIM_OPEN`.
+fn locale() -> String {
+    for var in ["LC_ALL", "LC_CTYPE", "LANG"] {
+        if let Ok(locale) = env::var(var) {
+            if !locale.is_empty() {
+                return locale;
+            }
+        }
+    }
+    "C".to_string()
+}
@@ -0,0 +1,360 @@
+//! Encoding and decoding of XIM protocol messages.
+//!
+//! All messages are exchanged in the native byte order of the client, which is announced to the
+//! server in `XIM_CONNECT`. Every message starts with a 4 byte header consisting of the major
+//! opcode, the minor opcode, and the length of the body in units of 4 bytes.
+
+pub const XIM_CONNECT: u8 = 1;
+pub const XIM_CONNECT_REPLY: u8 = 2;
+pub const XIM_DISCONNECT: u8 = 3;
+pub const XIM_DISCONNECT_REPLY: u8 = 4;
+pub const XIM_ERROR: u8 = 20;
+pub const XIM_OPEN: u8 = 30;
+pub const XIM_OPEN_REPLY: u8 = 31;
+pub const XIM_REGISTER_TRIGGERKEYS: u8 = 34;
+pub const XIM_SET_EVENT_MASK: u8 = 37;
+pub const XIM_ENCODING_NEGOTIATION: u8 = 38;
+pub const XIM_ENCODING_NEGOTIATION_REPLY: u8 = 39;
+pub const XIM_GET_IM_VALUES: u8 = 44;
+pub const XIM_GET_IM_VALUES_REPLY: u8 = 45;
+pub const XIM_CREATE_IC: u8 = 50;
+pub const XIM_CREATE_IC_REPLY: u8 = 51;
+pub const XIM_DESTROY_IC: u8 = 52;
+pub const XIM_DESTROY_IC_REPLY: u8 = 53;
+pub const XIM_SET_IC_VALUES: u8 = 54;
+pub const XIM_SET_IC_VALUES_REPLY: u8 = 55;
+pub const XIM_SET_IC_FOCUS: u8 = 58;
+pub const XIM_UNSET_IC_FOCUS: u8 = 59;
+pub const XIM_FORWARD_EVENT: u8 = 60;
+pub const XIM_SYNC: u8 = 61;
+pub const XIM_SYNC_REPLY: u8 = 62;
+pub const XIM_COMMIT: u8 = 63;
+pub const XIM_PREEDIT_START: u8 = 73;
+pub const XIM_PREEDIT_START_REPLY: u8 = 74;
+pub const XIM_PREEDIT_DRAW: u8 = 75;
+pub const XIM_PREEDIT_CARET: u8 = 76;
+pub const XIM_PREEDIT_CARET_REPLY: u8 = 77;
+pub const XIM_PREEDIT_DONE: u8 = 78;
+
+/// The receiver of `XIM_FORWARD_EVENT` must answer with `XIM_SYNC_REPLY`.
+pub const FORWARD_EVENT_SYNCHRONOUS: u16 = 0x0001;
+
+pub const COMMIT_SYNCHRONOUS: u16 = 0x0001;
+pub const COMMIT_CHARS: u16 = 0x0002;
+pub const COMMIT_KEYSYM: u16 = 0x0004;
+
+pub const ERROR_ICID_VALID: u16 = 0x0002;
+
+pub const XIM_PREEDIT_CALLBACKS: u32 = 0x0002;
+pub const XIM_PREEDIT_POSITION: u32 = 0x0004;
+pub const XIM_PREEDIT_NOTHING: u32 = 0x0008;
+pub const XIM_PREEDIT_NONE: u32 = 0x0010;
+pub const XIM_STATUS_NOTHING: u32 = 0x0400;
+pub const XIM_STATUS_NONE: u32 = 0x0800;
+
+pub const PREEDIT_DRAW_NO_STRING: u32 = 0x0001;
+pub const PREEDIT_DRAW_NO_FEEDBACK: u32 = 0x0002;
+
+pub const XIM_REVERSE: u32 = 0x0001;
+pub const XIM_UNDERLINE: u32 = 0x0002;
+pub const XIM_HIGHLIGHT: u32 = 0x0004;
+
+pub const XIM_FORWARD_CHAR: u32 = 0;
+pub const XIM_BACKWARD_CHAR: u32 = 1;
+pub const XIM_LINE_START: u32 = 8;
+pub const XIM_LINE_END: u32 = 9;
+pub const XIM_ABSOLUTE_POSITION: u32 = 10;
+
+pub const XIM_IS_INVISIBLE: u32 = 0;
+
+pub const KEY_PRESS_MASK: u32 = 0x0001;
+pub const KEY_RELEASE_MASK: u32 = 0x0002;
+
+/// The size of an X event on the wire.
+pub const EVENT_SIZE: usize = 32;
+
+/// Builds a single message.
+pub struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    pub fn new(major_opcode: u8) -> Self {
+        Writer {
+            buf: vec![major_opcode, 0, 0, 0],
+        }
+    }
+
+    pub fn u8(&mut self, v: u8) -> &mut Self {
+        self.buf.push(v);
+        self
+    }
+
+    pub fn u16(&mut self, v: u16) -> &mut Self {
+        self.buf.extend_from_slice(&v.to_ne_bytes());
+        self
+    }
+
+    pub fn u32(&mut self, v: u32) -> &mut Self {
+        self.buf.extend_from_slice(&v.to_ne_bytes());
+        self
+    }
+
+    pub fn bytes(&mut self, v: &[u8]) -> &mut Self {
+        self.buf.extend_from_slice(v);
+        self
+    }
+
+    /// Writes a `STR`, i.e., a string prefixed by its length in a single byte.
+    pub fn str(&mut self, v: &str) -> &mut Self {
+        self.u8(v.len() as u8).bytes(v.as_bytes())
+    }
+
+    /// Pads the message to a multiple of 4 bytes.
+    pub fn align(&mut self) -> &mut Self {
+        while self.buf.len() % 4 != 0 {
+            self.buf.push(0);
+        }
+        self
+    }
+
+    /// Writes the length of the data written after `pos` as a `CARD16` at `pos`.
+    fn patch_len(&mut self, pos: usize) {
+        let len = (self.buf.len() - pos - 2) as u16;
+        self.buf[pos..pos + 2].copy_from_slice(&len.to_ne_bytes());
+    }
+
+    pub fn finish(&mut self) -> Vec<u8> {
+        self.align();
+        let len = ((self.buf.len() - 4) / 4) as u16;
+        self.buf[2..4].copy_from_slice(&len.to_ne_bytes());
+        std::mem::take(&mut self.buf)
+    }
+}
+
+/// A list of `XICATTRIBUTE`s.
+#[derive(Default)]
+pub struct Attributes {
+    buf: Vec<u8>,
+}
+
+impl Attributes {
+    pub fn bytes(&mut self, id: u16, value: &[u8]) -> &mut Self {
+        self.buf.extend_from_slice(&id.to_ne_bytes());
+        self.buf
+            .extend_from_slice(&(value.len() as u16).to_ne_bytes());
+        self.buf.extend_from_slice(value);
+        while self.buf.len() % 4 != 0 {
+            self.buf.push(0);
+        }
+        self
+    }
+
+    pub fn u32(&mut self, id: u16, value: u32) -> &mut Self {
+        self.bytes(id, &value.to_ne_bytes())
+    }
+
+    pub fn point(&mut self, id: u16, x: i16, y: i16) -> &mut Self {
+        let mut value = [0; 4];
+        value[..2].copy_from_slice(&x.to_ne_bytes());
+        value[2..].copy_from_slice(&y.to_ne_bytes());
+        self.bytes(id, &value)
+    }
+
+    pub fn nested(&mut self, id: u16, attributes: &Attributes) -> &mut Self {
+        self.bytes(id, &attributes.buf)
+    }
+
+    pub fn extend(&mut self, attributes: &Attributes) -> &mut Self {
+        self.buf.extend_from_slice(&attributes.buf);
+        self
+    }
+}
+
+pub fn connect() -> Vec<u8> {
+    let byte_order = if cfg!(target_endian = "little") {
+        b'l'
+    } else {
+        b'B'
+    };
+    Writer::new(XIM_CONNECT)
+        .u8(byte_order)
+        .u8(0)
+        // Protocol version 1.0
+        .u16(1)
+        .u16(0)
+        // No authentication protocols
+        .u16(0)
+        .finish()
+}
+
+pub fn disconnect() -> Vec<u8> {
+    Writer::new(XIM_DISCONNECT).finish()
+}
+
+pub fn open(locale: &str) -> Vec<u8> {
+    Writer::new(XIM_OPEN).str(locale).finish()
+}
+
+pub fn encoding_negotiation(imid: u16, encodings: &[&str]) -> Vec<u8> {
+    let mut w = Writer::new(XIM_ENCODING_NEGOTIATION);
+    w.u16(imid);
+    let pos = w.buf.len();
+    w.u16(0);
+    for encoding in encodings {
+        w.str(encoding);
+    }
+    w.patch_len(pos);
+    w.align();
+    // No encodings are specified by detailed data.
+    w.u16(0).u16(0).finish()
+}
+
+pub fn get_im_values(imid: u16, attributes: &[u16]) -> Vec<u8> {
+    let mut w = Writer::new(XIM_GET_IM_VALUES);
+    w.u16(imid).u16(attributes.len() as u16 * 2);
+    for &attribute in attributes {
+        w.u16(attribute);
+    }
+    w.finish()
+}
+
+pub fn create_ic(imid: u16, attributes: &Attributes) -> Vec<u8> {
+    Writer::new(XIM_CREATE_IC)
+        .u16(imid)
+        .u16(attributes.buf.len() as u16)
+        .bytes(&attributes.buf)
+        .finish()
+}
+
+pub fn set_ic_values(imid: u16, icid: u16, attributes: &Attributes) -> Vec<u8> {
+    Writer::new(XIM_SET_IC_VALUES)
+        .u16(imid)
+        .u16(icid)
+        .u16(attributes.buf.len() as u16)
+        .u16(0)
+        .bytes(&attributes.buf)
+        .finish()
+}
+
+/// Builds one of the messages whose body consists only of the IM and IC ids.
+pub fn ic_request(major_opcode: u8, imid: u16, icid: u16) -> Vec<u8> {
+    Writer::new(major_opcode).u16(imid).u16(icid).finish()
+}
+
+pub fn forward_event(imid: u16, icid: u16, flag: u16, event: &[u8; EVENT_SIZE]) -> Vec<u8> {
+    Writer::new(XIM_FORWARD_EVENT)
+        .u16(imid)
+        .u16(icid)
+        .u16(flag)
+        // The upper 16 bits of the serial number of the event
+        .u16(0)
+        .bytes(event)
+        .finish()
+}
+
+pub fn preedit_start_reply(imid: u16, icid: u16, return_value: i32) -> Vec<u8> {
+    Writer::new(XIM_PREEDIT_START_REPLY)
+        .u16(imid)
+        .u16(icid)
+        .u32(return_value as u32)
+        .finish()
+}
+
+pub fn preedit_caret_reply(imid: u16, icid: u16, position: u32) -> Vec<u8> {
+    Writer::new(XIM_PREEDIT_CARET_REPLY)
+        .u16(imid)
+        .u16(icid)
+        .u32(position)
+        .finish()
+}
+
+/// Reads the fields of a message.
+///
+/// All methods return `None` if the message is too short.
+pub struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Reader { buf, pos: 0 }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    pub fn bytes(&mut self, n: usize) -> Option<&'a [u8]> {
+        let res = self.buf.get(self.pos..self.pos.checked_add(n)?)?;
+        self.pos += n;
+        Some(res)
+    }
+
+    pub fn u8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|b| b[0])
+    }
+
+    pub fn u16(&mut self) -> Option<u16> {
+        self.bytes(2).map(|b| u16::from_ne_bytes([b[0], b[1]]))
+    }
+
+    pub fn i16(&mut self) -> Option<i16> {
+        self.u16().map(|v| v as i16)
+    }
+
+    pub fn u32(&mut self) -> Option<u32> {
+        self.bytes(4)
+            .map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    pub fn i32(&mut self) -> Option<i32> {
+        self.u32().map(|v| v as i32)
+    }
+
+    /// Skips the padding up to the next multiple of 4 bytes.
+    pub fn align(&mut self) {
+        self.pos = (self.pos + 3) & !3;
+        self.pos = self.pos.min(self.buf.len());
+    }
+}
+
+/// Parses a list of `XIMATTR`s or `XICATTR`s as sent in `XIM_OPEN_REPLY`.
+///
+/// Returns the names of the attributes together with their ids.
+pub fn parse_attribute_names(buf: &[u8]) -> Option<Vec<(String, u16)>> {
+    let mut r = Reader::new(buf);
+    let mut res = vec![];
+    while r.remaining() > 0 {
+        let id = r.u16()?;
+        let _ty = r.u16()?;
+        let len = r.u16()? as usize;
+        let name = r.bytes(len)?;
+        r.align();
+        res.push((String::from_utf8_lossy(name).into_owned(), id));
+    }
+    Some(res)
+}
+
+/// Parses a list of `XIMATTRIBUTE`s or `XICATTRIBUTE`s.
+pub fn parse_attributes(buf: &[u8]) -> Option<Vec<(u16, &[u8])>> {
+    let mut r = Reader::new(buf);
+    let mut res = vec![];
+    while r.remaining() > 0 {
+        let id = r.u16()?;
+        let len = r.u16()? as usize;
+        let value = r.bytes(len)?;
+        r.align();
+        res.push((id, value));
+    }
+    Some(res)
+}
+
+/// Parses the value of the `queryInputStyle` attribute.
+pub fn parse_styles(buf: &[u8]) -> Option<Vec<u32>> {
+    let mut r = Reader::new(buf);
+    let n = r.u16()?;
+    let _unused = r.u16()?;
+    (0..n).map(|_| r.u32()).collect()
+}
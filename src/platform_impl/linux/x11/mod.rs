@@ -6,8 +6,12 @@
     target_os = "openbsd"
 ))]
 
+mod clipboard;
 mod dnd;
+mod drag;
 mod event_processor;
+mod gesture_ffi;
+mod ime;
 mod monitor;
 pub mod util;
 mod window;
@@ -22,10 +26,12 @@ pub use self::{
 
 use std::sync::atomic::AtomicUsize;
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     collections::{HashMap, HashSet},
+    io,
     ops::Deref,
     os::raw::*,
+    os::unix::io::{AsRawFd, RawFd},
     ptr,
     rc::Rc,
     sync::mpsc::Receiver,
@@ -33,7 +39,7 @@ use std::{
     time::{Duration, Instant},
 };
 
-use mio::{unix::SourceFd, Events, Interest, Poll, Token, Waker};
+use mio::{unix::SourceFd, Events, Interest, Poll, Registry, Token, Waker};
 
 use mio_misc::{
     channel::{channel, SendError, Sender},
@@ -42,23 +48,35 @@ use mio_misc::{
 };
 
 use self::{
-    dnd::{Dnd, DndState},
+    clipboard::Clipboard,
+    dnd::{Dnd, DndContext},
+    drag::DragRequest,
     event_processor::EventProcessor,
+    ime::{Ime, ImeRequest},
 };
 use crate::{
-    error::OsError as RootOsError,
-    event::{Event, StartCause},
+    dpi::PhysicalPosition,
+    error::{ExternalError, OsError as RootOsError},
+    event::{
+        ClipboardKind, DndAction, Event, PenState, PenTool, Readiness, ScrollSource, SourceToken,
+        StartCause,
+    },
     event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW},
-    platform_impl::{platform::sticky_exit_callback, PlatformSpecificWindowBuilderAttributes},
+    platform::pump_events::PumpStatus,
+    platform_impl::{
+        platform::sticky_exit_callback, OsError, PlatformSpecificWindowBuilderAttributes,
+    },
     window::WindowAttributes,
 };
 
+use crate::platform_impl::platform::common::timer::Timers;
 use crate::platform_impl::x11::util::EventQueue;
 use xcb_dl::{ffi, XcbXinput};
 use xcb_dl_util::xcb_box::XcbBox;
 
 const X_TOKEN: Token = Token(0);
 const USER_REDRAW_TOKEN: Token = Token(1);
+const USER_FD_TOKEN: Token = Token(2);
 
 pub struct EventLoopWindowTarget<T> {
     xconn: Arc<XConnection>,
@@ -67,17 +85,34 @@ pub struct EventLoopWindowTarget<T> {
     windows: RefCell<HashMap<WindowId, Weak<UnownedWindow>>>,
     redraw_sender: Sender<WindowId>,
     reset_dead_keys: Arc<AtomicUsize>,
+    clipboard: Clipboard,
+    dnd: DndContext,
+    ime_sender: Sender<ImeRequest>,
+    drag_sender: Sender<DragRequest>,
+    pub(super) timers: Timers,
+    // The registry of the poll of the file descriptors registered by the user.
+    fd_registry: Registry,
+    fd_sources: RefCell<HashMap<Token, (RawFd, Interest)>>,
+    next_fd_token: Cell<usize>,
     _marker: ::std::marker::PhantomData<T>,
 }
 
 pub struct EventLoop<T: 'static> {
     poll: Poll,
+    events: Events,
+    // File descriptors registered by the user. This poll is itself registered with `poll` so
+    // that it wakes up the event loop.
+    fd_poll: Poll,
+    fd_events: Events,
     event_queue: EventQueue,
     event_processor: EventProcessor<T>,
     redraw_channel: Receiver<WindowId>,
     user_channel: Receiver<T>,
     user_sender: Sender<T>,
     target: Rc<RootELW<T>>,
+    // The control flow between calls to `pump_events` or `None` before the first call. It stays
+    // `ControlFlow::Exit` once the loop has exited.
+    pump_control_flow: Option<ControlFlow>,
 }
 
 pub struct EventLoopProxy<T: 'static> {
@@ -100,6 +135,12 @@ impl<T: 'static> EventLoop<T> {
 
         let dnd = Dnd::new(Arc::clone(&xconn));
 
+        let event_queue = EventQueue::new(&xconn);
+
+        let clipboard = Clipboard::new(Arc::clone(&xconn), event_queue.clone());
+
+        let ime = Ime::new(Arc::clone(&xconn));
+
         xconn.update_cached_wm_info();
 
         let poll = Poll::new().unwrap();
@@ -110,11 +151,24 @@ impl<T: 'static> EventLoop<T> {
             .register(&mut SourceFd(&xconn.fd), X_TOKEN, Interest::READABLE)
             .unwrap();
 
+        let fd_poll = Poll::new().unwrap();
+        poll.registry()
+            .register(
+                &mut SourceFd(&fd_poll.as_raw_fd()),
+                USER_FD_TOKEN,
+                Interest::READABLE,
+            )
+            .unwrap();
+
         let (user_sender, user_channel) = channel(queue.clone(), NotificationId::gen_next());
 
-        let (redraw_sender, redraw_channel) = channel(queue, NotificationId::gen_next());
+        let (redraw_sender, redraw_channel) = channel(queue.clone(), NotificationId::gen_next());
 
-        let event_queue = EventQueue::new(&xconn);
+        let (ime_sender, ime_receiver) = channel(queue.clone(), NotificationId::gen_next());
+
+        let (drag_sender, drag_receiver) = channel(queue, NotificationId::gen_next());
+
+        let monitors = xconn.available_monitors();
 
         let target = Rc::new(RootELW {
             p: super::EventLoopWindowTarget::X(EventLoopWindowTarget {
@@ -125,6 +179,14 @@ impl<T: 'static> EventLoop<T> {
                 net_wm_ping,
                 redraw_sender,
                 reset_dead_keys: Arc::new(AtomicUsize::new(0)),
+                clipboard,
+                dnd: Default::default(),
+                ime_sender,
+                drag_sender,
+                timers: Default::default(),
+                fd_registry: fd_poll.registry().try_clone().unwrap(),
+                fd_sources: Default::default(),
+                next_fd_token: Cell::new(0),
             }),
             _marker: ::std::marker::PhantomData,
         });
@@ -136,6 +198,11 @@ impl<T: 'static> EventLoop<T> {
             num_touch: 0,
             first_touch: None,
             seats: Default::default(),
+            ime,
+            ime_receiver,
+            drag: None,
+            drag_receiver,
+            monitors,
         };
 
         // Register for device hotplug events
@@ -144,7 +211,7 @@ impl<T: 'static> EventLoop<T> {
             let pending = wt.xconn.select_xinput_events(
                 screen.root,
                 ffi::XCB_INPUT_DEVICE_ALL as _,
-                ffi::XCB_INPUT_XI_EVENT_MASK_HIERARCHY,
+                [ffi::XCB_INPUT_XI_EVENT_MASK_HIERARCHY],
             );
             if let Err(e) = wt.xconn.check_pending1(pending) {
                 log::error!("Cannot listen for device hotplug events: {}", e);
@@ -160,12 +227,16 @@ impl<T: 'static> EventLoop<T> {
 
         let result = EventLoop {
             poll,
+            events: Events::with_capacity(8),
+            fd_poll,
+            fd_events: Events::with_capacity(8),
             event_queue,
             redraw_channel,
             user_channel,
             user_sender,
             event_processor,
             target,
+            pump_control_flow: None,
         };
 
         result
@@ -186,133 +257,221 @@ impl<T: 'static> EventLoop<T> {
         F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
     {
         let mut control_flow = ControlFlow::default();
-        let mut events = Events::with_capacity(8);
         let mut cause = StartCause::Init;
 
         loop {
-            sticky_exit_callback(
-                crate::event::Event::NewEvents(cause),
+            self.single_iteration(&mut callback, &mut control_flow, cause);
+
+            if self.event_queue.has_pending_events() {
+                // If there are pending events that have already been read from the socket
+                // but not yet dispatched, we HAVE to handle them now. Otherwise, if the
+                // application is using run_return, it has no way to get notified that it
+                // should call run_return again. The application will probably try to wait
+                // for the socket to become readable but that's no good because the socket
+                // might be empty while we already have events queued.
+                //
+                // TODO: Should we change `cause`?
+                continue;
+            }
+
+            if control_flow == ControlFlow::Exit {
+                break;
+            }
+
+            cause = self.wait(control_flow, None);
+        }
+
+        callback(
+            crate::event::Event::LoopDestroyed,
+            &self.target,
+            &mut control_flow,
+        );
+    }
+
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, mut callback: F) -> PumpStatus
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        if self.pump_control_flow == Some(ControlFlow::Exit) {
+            return PumpStatus::Exit;
+        }
+
+        let (mut control_flow, cause) = match self.pump_control_flow {
+            Some(control_flow) => {
+                // Requests of the application might have read events from the socket.
+                let timeout = if self.event_queue.has_pending_events() {
+                    Some(Duration::from_millis(0))
+                } else {
+                    timeout
+                };
+                (control_flow, self.wait(control_flow, timeout))
+            }
+            None => (ControlFlow::default(), StartCause::Init),
+        };
+
+        loop {
+            self.single_iteration(&mut callback, &mut control_flow, cause);
+
+            // See `run_return` for why events that have already been read must be handled now.
+            if control_flow == ControlFlow::Exit || !self.event_queue.has_pending_events() {
+                break;
+            }
+        }
+
+        if control_flow == ControlFlow::Exit {
+            self.pump_control_flow = Some(ControlFlow::Exit);
+            callback(
+                crate::event::Event::LoopDestroyed,
                 &self.target,
                 &mut control_flow,
-                &mut callback,
             );
+            PumpStatus::Exit
+        } else {
+            self.pump_control_flow = Some(control_flow);
+            PumpStatus::Continue
+        }
+    }
+
+    fn single_iteration<F>(
+        &mut self,
+        callback: &mut F,
+        control_flow: &mut ControlFlow,
+        cause: StartCause,
+    ) where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        sticky_exit_callback(
+            crate::event::Event::NewEvents(cause),
+            &self.target,
+            control_flow,
+            callback,
+        );
 
-            // Process all pending events
-            self.drain_events(&mut callback, &mut control_flow);
+        // Emit the timers that have fired
+        let expired = get_xtarget(&self.target)
+            .timers
+            .take_expired(Instant::now());
+        for id in expired {
+            sticky_exit_callback(Event::Timer(id), &self.target, control_flow, callback);
+        }
 
-            // Send unchecked requests
-            self.flush_requests();
+        // Process all pending events
+        self.drain_events(callback, control_flow);
 
-            // Empty the user event buffer
-            {
-                while let Ok(event) = self.user_channel.try_recv() {
-                    sticky_exit_callback(
-                        crate::event::Event::UserEvent(event),
-                        &self.target,
-                        &mut control_flow,
-                        &mut callback,
-                    );
-                }
-            }
-            // send MainEventsCleared
-            {
+        self.dispatch_fds(callback, control_flow);
+
+        // Send unchecked requests
+        self.flush_requests();
+
+        // Empty the user event buffer
+        {
+            while let Ok(event) = self.user_channel.try_recv() {
                 sticky_exit_callback(
-                    crate::event::Event::MainEventsCleared,
+                    crate::event::Event::UserEvent(event),
                     &self.target,
-                    &mut control_flow,
-                    &mut callback,
+                    control_flow,
+                    callback,
                 );
             }
-            // Empty the redraw requests
-            {
-                let mut windows = HashSet::new();
-
-                while let Ok(window_id) = self.redraw_channel.try_recv() {
-                    windows.insert(window_id);
-                }
+        }
+        // send MainEventsCleared
+        {
+            sticky_exit_callback(
+                crate::event::Event::MainEventsCleared,
+                &self.target,
+                control_flow,
+                callback,
+            );
+        }
+        // Empty the redraw requests
+        {
+            let mut windows = HashSet::new();
 
-                for window_id in windows {
-                    let window_id = crate::window::WindowId(super::WindowId::X(window_id));
-                    sticky_exit_callback(
-                        Event::RedrawRequested(window_id),
-                        &self.target,
-                        &mut control_flow,
-                        &mut callback,
-                    );
-                }
+            while let Ok(window_id) = self.redraw_channel.try_recv() {
+                windows.insert(window_id);
             }
-            // send RedrawEventsCleared
-            {
+
+            for window_id in windows {
+                let window_id = crate::window::WindowId(super::WindowId::X(window_id));
                 sticky_exit_callback(
-                    crate::event::Event::RedrawEventsCleared,
+                    Event::RedrawRequested(window_id),
                     &self.target,
-                    &mut control_flow,
-                    &mut callback,
+                    control_flow,
+                    callback,
                 );
             }
+        }
+        // send RedrawEventsCleared
+        {
+            sticky_exit_callback(
+                crate::event::Event::RedrawEventsCleared,
+                &self.target,
+                control_flow,
+                callback,
+            );
+        }
+    }
 
-            if self.event_queue.has_pending_events() {
-                // If there are pending events that have already been read from the socket
-                // but not yet dispatched, we HAVE to handle them now. Otherwise, if the
-                // application is using run_return, it has no way to get notified that it
-                // should call run_return again. The application will probably try to wait
-                // for the socket to become readable but that's no good because the socket
-                // might be empty while we already have events queued.
-                //
-                // TODO: Should we change `cause`?
-                continue;
+    /// Blocks until new events arrive or the time requested by `control_flow` or by a timer has
+    /// been reached, but not longer than `max_timeout`. Returns the cause of the wake-up.
+    fn wait(&mut self, control_flow: ControlFlow, max_timeout: Option<Duration>) -> StartCause {
+        let start = Instant::now();
+        let (mut cause, deadline, timeout);
+
+        match control_flow {
+            ControlFlow::Poll | ControlFlow::Exit => {
+                cause = StartCause::Poll;
+                deadline = None;
+                timeout = Some(Duration::from_millis(0));
             }
+            ControlFlow::Wait => {
+                cause = StartCause::WaitCancelled {
+                    start,
+                    requested_resume: None,
+                };
+                deadline = None;
+                timeout = None;
+            }
+            ControlFlow::WaitUntil(wait_deadline) => {
+                cause = StartCause::ResumeTimeReached {
+                    start,
+                    requested_resume: wait_deadline,
+                };
+                timeout = if wait_deadline > start {
+                    Some(wait_deadline - start)
+                } else {
+                    Some(Duration::from_millis(0))
+                };
+                deadline = Some(wait_deadline);
+            }
+        }
 
-            let start = Instant::now();
-            let (deadline, timeout);
-
-            match control_flow {
-                ControlFlow::Exit => break,
-                ControlFlow::Poll => {
-                    cause = StartCause::Poll;
-                    deadline = None;
-                    timeout = Some(Duration::from_millis(0));
-                }
-                ControlFlow::Wait => {
-                    cause = StartCause::WaitCancelled {
-                        start,
-                        requested_resume: None,
-                    };
-                    deadline = None;
-                    timeout = None;
-                }
-                ControlFlow::WaitUntil(wait_deadline) => {
-                    cause = StartCause::ResumeTimeReached {
-                        start,
-                        requested_resume: wait_deadline,
-                    };
-                    timeout = if wait_deadline > start {
-                        Some(wait_deadline - start)
-                    } else {
-                        Some(Duration::from_millis(0))
-                    };
-                    deadline = Some(wait_deadline);
-                }
+        // Wake up for the earliest timer
+        let timeout = match self.next_deadline() {
+            Some(timer) => {
+                let until_timer = timer.saturating_duration_since(start);
+                Some(timeout.map_or(until_timer, |timeout| timeout.min(until_timer)))
             }
+            None => timeout,
+        };
+        let timeout = match max_timeout {
+            Some(max_timeout) => Some(timeout.map_or(max_timeout, |t| t.min(max_timeout))),
+            None => timeout,
+        };
 
-            self.poll.poll(&mut events, timeout).unwrap();
-            events.clear();
+        self.poll.poll(&mut self.events, timeout).unwrap();
+        self.events.clear();
 
-            let wait_cancelled = deadline.map_or(false, |deadline| Instant::now() < deadline);
+        let wait_cancelled = deadline.map_or(false, |deadline| Instant::now() < deadline);
 
-            if wait_cancelled {
-                cause = StartCause::WaitCancelled {
-                    start,
-                    requested_resume: deadline,
-                };
-            }
+        if wait_cancelled {
+            cause = StartCause::WaitCancelled {
+                start,
+                requested_resume: deadline,
+            };
         }
 
-        callback(
-            crate::event::Event::LoopDestroyed,
-            &self.target,
-            &mut control_flow,
-        );
+        cause
     }
 
     pub fn run<F>(mut self, callback: F) -> !
@@ -323,6 +482,89 @@ impl<T: 'static> EventLoop<T> {
         ::std::process::exit(0);
     }
 
+    /// Returns the time at which the event loop has to wake up for a timer or for an internal
+    /// timeout.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let timer = get_xtarget(&self.target).timers.next_deadline();
+        let internal = self.event_processor.next_deadline();
+        match (timer, internal) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Returns whether events have been read from the socket that `pump_events` hasn't
+    /// dispatched yet.
+    #[cfg(feature = "stream")]
+    pub fn has_pending_events(&mut self) -> bool {
+        self.event_queue.has_pending_events()
+    }
+
+    /// Returns the file descriptors that become ready when the event loop has new events.
+    #[cfg(feature = "stream")]
+    pub fn wakeup_fds(&self) -> Vec<(RawFd, crate::platform::unix::Interest)> {
+        // Everything that wakes up the event loop is registered with `poll`.
+        vec![(
+            self.poll.as_raw_fd(),
+            crate::platform::unix::Interest::READABLE,
+        )]
+    }
+
+    /// Emits `FdReady` for all registered file descriptors that are ready.
+    fn dispatch_fds<F>(&mut self, callback: &mut F, control_flow: &mut ControlFlow)
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        let target = self.target.clone();
+        let wt = get_xtarget(&target);
+        if wt.fd_sources.borrow().is_empty() {
+            return;
+        }
+        if let Err(e) = self
+            .fd_poll
+            .poll(&mut self.fd_events, Some(Duration::from_millis(0)))
+        {
+            log::error!("Could not poll the registered file descriptors: {}", e);
+            return;
+        }
+        let ready: Vec<_> = self
+            .fd_events
+            .iter()
+            .map(|event| {
+                let mut readiness = Readiness::empty();
+                readiness.set(
+                    Readiness::READABLE,
+                    event.is_readable() || event.is_read_closed(),
+                );
+                readiness.set(Readiness::WRITABLE, event.is_writable());
+                readiness.set(Readiness::ERROR, event.is_error());
+                (event.token(), readiness)
+            })
+            .collect();
+        for (token, readiness) in ready {
+            // An earlier callback might have unregistered the file descriptor.
+            if !wt.fd_sources.borrow().contains_key(&token) {
+                continue;
+            }
+            sticky_exit_callback(
+                Event::FdReady {
+                    token: SourceToken(token.0 as u64),
+                    readiness,
+                },
+                &self.target,
+                control_flow,
+                callback,
+            );
+            // Registrations are edge-triggered. Re-arming the file descriptor makes it
+            // reported again if the callback did not consume all of its data.
+            if let Some(&(fd, interest)) = wt.fd_sources.borrow().get(&token) {
+                let _ = wt
+                    .fd_registry
+                    .reregister(&mut SourceFd(&fd), token, interest);
+            }
+        }
+    }
+
     fn drain_events<F>(&mut self, callback: &mut F, control_flow: &mut ControlFlow)
     where
         F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
@@ -331,25 +573,33 @@ impl<T: 'static> EventLoop<T> {
 
         let wt = get_xtarget(&self.target);
 
+        let mut callback = |event: Event<'_, T>| {
+            sticky_exit_callback(
+                event,
+                target,
+                control_flow,
+                &mut |event, window_target, control_flow| {
+                    if let Event::RedrawRequested(crate::window::WindowId(super::WindowId::X(
+                        wid,
+                    ))) = event
+                    {
+                        wt.redraw_sender.send(wid).unwrap();
+                    } else {
+                        callback(event, window_target, control_flow);
+                    }
+                },
+            );
+        };
+
+        self.event_processor.process_ime_requests(&mut callback);
+        self.event_processor.process_drag_requests(&mut callback);
+
         while let Some(mut event) = self.event_queue.poll_for_event() {
-            self.event_processor.process_event(&mut *event, |event| {
-                sticky_exit_callback(
-                    event,
-                    target,
-                    control_flow,
-                    &mut |event, window_target, control_flow| {
-                        if let Event::RedrawRequested(crate::window::WindowId(
-                            super::WindowId::X(wid),
-                        )) = event
-                        {
-                            wt.redraw_sender.send(wid).unwrap();
-                        } else {
-                            callback(event, window_target, control_flow);
-                        }
-                    },
-                );
-            });
+            self.event_processor
+                .process_event(&mut *event, &mut callback);
         }
+
+        self.event_processor.process_timeouts(&mut callback);
     }
 
     fn flush_requests(&self) {
@@ -363,7 +613,7 @@ impl<T: 'static> EventLoop<T> {
 pub(crate) fn get_xtarget<T>(target: &RootELW<T>) -> &EventLoopWindowTarget<T> {
     match target.p {
         super::EventLoopWindowTarget::X(ref target) => target,
-        #[cfg(feature = "wayland")]
+        #[cfg(any(feature = "wayland", feature = "headless"))]
         _ => unreachable!(),
     }
 }
@@ -374,6 +624,83 @@ impl<T> EventLoopWindowTarget<T> {
     pub fn x_connection(&self) -> &Arc<XConnection> {
         &self.xconn
     }
+
+    pub fn register_fd(
+        &self,
+        fd: RawFd,
+        interest: crate::platform::unix::Interest,
+    ) -> io::Result<SourceToken> {
+        use crate::platform::unix::Interest as UserInterest;
+
+        let interest = match (
+            interest.contains(UserInterest::READABLE),
+            interest.contains(UserInterest::WRITABLE),
+        ) {
+            (true, true) => Interest::READABLE | Interest::WRITABLE,
+            (true, false) => Interest::READABLE,
+            (false, true) => Interest::WRITABLE,
+            (false, false) => return Err(io::ErrorKind::InvalidInput.into()),
+        };
+        let token = Token(self.next_fd_token.get());
+        self.next_fd_token.set(token.0 + 1);
+        self.fd_registry
+            .register(&mut SourceFd(&fd), token, interest)?;
+        self.fd_sources.borrow_mut().insert(token, (fd, interest));
+        Ok(SourceToken(token.0 as u64))
+    }
+
+    pub fn unregister_fd(&self, token: SourceToken) {
+        if let Some((fd, _)) = self
+            .fd_sources
+            .borrow_mut()
+            .remove(&Token(token.0 as usize))
+        {
+            let _ = self.fd_registry.deregister(&mut SourceFd(&fd));
+        }
+    }
+
+    pub fn set_clipboard_data(
+        &self,
+        kind: ClipboardKind,
+        data: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), ExternalError> {
+        self.clipboard
+            .set_data(kind, data)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err.into()))))
+    }
+
+    pub fn clear_clipboard(&self, kind: ClipboardKind) -> Result<(), ExternalError> {
+        self.clipboard
+            .clear(kind)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err.into()))))
+    }
+
+    pub fn request_clipboard_data(&self, kind: ClipboardKind, mime_type: &str) {
+        self.clipboard.request(kind, Some(mime_type));
+    }
+
+    pub fn request_clipboard_mime_types(&self, kind: ClipboardKind) {
+        self.clipboard.request(kind, None);
+    }
+
+    pub fn set_dnd_action(&self, action: Option<DndAction>) {
+        self.dnd.action.set(action);
+    }
+
+    pub fn request_dnd_data(&self, mime_type: &str) {
+        if let Some((window, time)) = self.dnd.target.get() {
+            self.clipboard.request_dnd(window, mime_type, time);
+        }
+    }
+
+    pub fn set_cursor_theme(&self, theme: Option<&str>, size: Option<u32>) {
+        self.xconn.set_cursor_theme(theme, size);
+        for window in self.windows.borrow().values() {
+            if let Some(window) = window.upgrade() {
+                window.refresh_cursor(window.scale_factor());
+            }
+        }
+    }
 }
 
 impl<T: 'static> EventLoopProxy<T> {
@@ -516,6 +843,8 @@ fn mkdid(w: ffi::xcb_input_device_id_t) -> crate::event::DeviceId {
 struct Device {
     name: String,
     scroll_axes: Vec<(u16, ScrollAxis)>,
+    // Set if the device is a drawing tablet tool.
+    pen: Option<PenDevice>,
     // For master devices, this is the paired device (pointer <-> keyboard).
     // For slave devices, this is the master.
     attachment: ffi::xcb_input_device_id_t,
@@ -524,6 +853,8 @@ struct Device {
 #[derive(Debug, Copy, Clone)]
 struct ScrollAxis {
     increment: f64,
+    // Touchpads scroll by pixels instead of lines.
+    source: ScrollSource,
     orientation: ScrollOrientation,
     position: f64,
 }
@@ -534,6 +865,63 @@ enum ScrollOrientation {
     Horizontal,
 }
 
+#[derive(Debug)]
+struct PenDevice {
+    tool: PenTool,
+    axes: Vec<(u16, PenAxis)>,
+    state: PenState,
+    location: PhysicalPosition<f64>,
+    // The window that the pen is in proximity of
+    window: Option<ffi::xcb_window_t>,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct PenAxis {
+    kind: PenAxisKind,
+    min: f64,
+    max: f64,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum PenAxisKind {
+    Pressure,
+    TiltX,
+    TiltY,
+    Rotation,
+    Distance,
+}
+
+impl PenDevice {
+    /// Updates the state from the valuators of an event.
+    fn update_axes(&mut self, axis_ids: &[u16], values: &[ffi::xcb_input_fp3232_t]) {
+        for (&i, &value) in axis_ids.iter().zip(values.iter()) {
+            if let Some(&(_, axis)) = self.axes.iter().find(|&&(axis, _)| axis == i) {
+                axis.apply(&mut self.state, util::fp3232_to_f64(value));
+            }
+        }
+    }
+}
+
+impl PenAxis {
+    fn apply(&self, state: &mut PenState, value: f64) {
+        let normalized = if self.max > self.min {
+            ((value - self.min) / (self.max - self.min))
+                .max(0.0)
+                .min(1.0)
+        } else {
+            0.0
+        };
+        match self.kind {
+            PenAxisKind::Pressure => state.pressure = Some(normalized),
+            // Tilt axes are reported in degrees by both the wacom and the libinput driver.
+            PenAxisKind::TiltX => state.tilt_x = Some(value.max(-90.0).min(90.0)),
+            PenAxisKind::TiltY => state.tilt_y = Some(value.max(-90.0).min(90.0)),
+            PenAxisKind::Rotation => state.rotation = Some(normalized * 360.0),
+            PenAxisKind::Distance => state.distance = Some(normalized),
+        }
+    }
+}
+
 impl Device {
     fn new<T: 'static>(
         wt: &EventLoopWindowTarget<T>,
@@ -547,6 +935,8 @@ impl Device {
             String::from_utf8_lossy(name)
         };
         let mut scroll_axes = Vec::new();
+        let mut pen_axes = Vec::new();
+        let mut scroll_source = ScrollSource::Wheel;
 
         if info.type_ == ffi::XCB_INPUT_DEVICE_TYPE_MASTER_KEYBOARD as u16 {
             let pending = wt.xconn.select_xkb_events(
@@ -572,7 +962,7 @@ impl Device {
             for screen in &wt.xconn.screens {
                 let pending = wt
                     .xconn
-                    .select_xinput_events(screen.root, info.deviceid, mask);
+                    .select_xinput_events(screen.root, info.deviceid, [mask]);
                 if let Err(e) = wt.xconn.check_pending1(pending) {
                     log::error!(
                         "Cannot listen for raw input events of device {}: {}",
@@ -582,7 +972,15 @@ impl Device {
                 }
             }
 
-            // Identify scroll axes
+            match wt.xconn.input_device_type(info.deviceid) {
+                Ok(Some(ty)) if ty == wt.xconn.get_atom("TOUCHPAD") => {
+                    scroll_source = ScrollSource::Finger;
+                }
+                Ok(_) => {}
+                Err(e) => log::error!("Cannot query the type of device {}: {}", info.deviceid, e),
+            }
+
+            // Identify scroll and pen axes
             let classes = unsafe { Classes::new(&wt.xconn.xinput, info) };
             for class in classes {
                 match class.type_ as ffi::xcb_input_device_class_type_t {
@@ -594,6 +992,7 @@ impl Device {
                             info.number,
                             ScrollAxis {
                                 increment: util::fp3232_to_f64(info.increment),
+                                source: scroll_source,
                                 orientation: match info.scroll_type as ffi::xcb_input_scroll_type_t
                                 {
                                     ffi::XCB_INPUT_SCROLL_TYPE_HORIZONTAL => {
@@ -608,14 +1007,58 @@ impl Device {
                             },
                         ));
                     }
+                    ffi::XCB_INPUT_DEVICE_CLASS_TYPE_VALUATOR => {
+                        let info = unsafe {
+                            &*(class as *const _ as *const ffi::xcb_input_valuator_class_t)
+                        };
+                        let kind = match info.label {
+                            0 => continue,
+                            l if l == wt.xconn.get_atom("Abs Pressure") => PenAxisKind::Pressure,
+                            l if l == wt.xconn.get_atom("Abs Tilt X") => PenAxisKind::TiltX,
+                            l if l == wt.xconn.get_atom("Abs Tilt Y") => PenAxisKind::TiltY,
+                            l if l == wt.xconn.get_atom("Abs Rotary Z") => PenAxisKind::Rotation,
+                            l if l == wt.xconn.get_atom("Abs Distance") => PenAxisKind::Distance,
+                            _ => continue,
+                        };
+                        pen_axes.push((
+                            info.number,
+                            PenAxis {
+                                kind,
+                                min: util::fp3232_to_f64(info.min),
+                                max: util::fp3232_to_f64(info.max),
+                            },
+                        ));
+                    }
                     _ => {}
                 }
             }
         }
 
+        // Only tablet tools have a pressure axis. Their erasers are separate devices.
+        let is_pen = pen_axes
+            .iter()
+            .any(|(_, axis)| matches!(axis.kind, PenAxisKind::Pressure));
+        let pen = if is_pen {
+            let tool = if name.to_lowercase().contains("eraser") {
+                PenTool::Eraser
+            } else {
+                PenTool::Pen
+            };
+            Some(PenDevice {
+                tool,
+                axes: pen_axes,
+                state: PenState::default(),
+                location: PhysicalPosition::new(0.0, 0.0),
+                window: None,
+            })
+        } else {
+            None
+        };
+
         let mut device = Device {
             name: name.into_owned(),
             scroll_axes,
+            pen,
             attachment: info.attachment,
         };
         device.reset_scroll_position(wt, info);
@@ -642,6 +1085,9 @@ impl Device {
                         {
                             axis.position = util::fp3232_to_f64(info.value);
                         }
+                        if let Some(pen) = &mut self.pen {
+                            pen.update_axes(&[info.number], &[info.value]);
+                        }
                     }
                     _ => {}
                 }
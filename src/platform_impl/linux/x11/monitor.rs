@@ -3,6 +3,7 @@ use crate::platform_impl::x11::xdisplay::Screen;
 use crate::{
     dpi::{PhysicalPosition, PhysicalSize},
     monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
+    platform::unix::{MonitorTransform, Subpixel},
     platform_impl::{MonitorHandle as PlatformMonitorHandle, VideoMode as PlatformVideoMode},
 };
 use std::ptr;
@@ -17,7 +18,9 @@ const DISABLE_MONITOR_LIST_CACHING: bool = false;
 pub struct VideoMode {
     pub(crate) size: (u16, u16),
     pub(crate) bit_depth: u16,
-    pub(crate) refresh_rate: u16,
+    pub(crate) refresh_rate_millihertz: u32,
+    pub(crate) interlaced: bool,
+    pub(crate) double_scan: bool,
     pub(crate) native_mode: u32,
     pub(crate) monitor: Option<MonitorHandle>,
 }
@@ -35,7 +38,27 @@ impl VideoMode {
 
     #[inline]
     pub fn refresh_rate(&self) -> u16 {
-        self.refresh_rate
+        ((self.refresh_rate_millihertz + 500) / 1000) as u16
+    }
+
+    #[inline]
+    pub fn refresh_rate_millihertz(&self) -> u32 {
+        self.refresh_rate_millihertz
+    }
+
+    #[inline]
+    pub fn is_interlaced(&self) -> bool {
+        self.interlaced
+    }
+
+    #[inline]
+    pub fn is_double_scan(&self) -> bool {
+        self.double_scan
+    }
+
+    #[inline]
+    pub fn native_id(&self) -> Option<u32> {
+        Some(self.native_mode)
     }
 
     #[inline]
@@ -66,6 +89,14 @@ pub struct MonitorHandle {
     pub(crate) rect: util::AaRect,
     /// Supported video modes on this monitor
     video_modes: Vec<VideoMode>,
+    /// The physical size of the monitor in millimetres
+    size_mm: (u32, u32),
+    /// The rotation and reflection of the monitor
+    transform: MonitorTransform,
+    /// The subpixel layout of the monitor
+    subpixel: Subpixel,
+    /// The EDID of the monitor
+    edid: Option<Arc<[u8]>>,
 }
 
 impl PartialEq for MonitorHandle {
@@ -104,7 +135,7 @@ impl MonitorHandle {
         primary: bool,
     ) -> Result<Option<Self>, XcbError> {
         let output_info = unsafe { xconn.get_output_info(screen, resources, crtc)? };
-        let (name, scale_factor, video_modes) = match output_info {
+        let output_info = match output_info {
             Some(o) => o,
             _ => return Ok(None),
         };
@@ -113,14 +144,18 @@ impl MonitorHandle {
         let rect = util::AaRect::new(position, dimensions);
         Ok(Some(MonitorHandle {
             id,
-            name,
+            name: output_info.name,
             screen: Some(screen.clone()),
-            scale_factor,
+            scale_factor: output_info.scale_factor,
             dimensions,
             position,
             primary,
             rect,
-            video_modes,
+            video_modes: output_info.video_modes,
+            size_mm: output_info.size_mm,
+            transform: transform_from_rotation(crtc.rotation),
+            subpixel: output_info.subpixel,
+            edid: output_info.edid.map(Arc::from),
         }))
     }
 
@@ -135,9 +170,28 @@ impl MonitorHandle {
             primary: true,
             rect: util::AaRect::new((0, 0), (1, 1)),
             video_modes: Vec::new(),
+            size_mm: (0, 0),
+            transform: MonitorTransform::Normal,
+            subpixel: Subpixel::Unknown,
+            edid: None,
         }
     }
 
+    /// Returns whether `other` describes this monitor with the same properties.
+    pub(crate) fn is_unchanged(&self, other: &Self) -> bool {
+        self.id == other.id
+            && self.name == other.name
+            && self.dimensions == other.dimensions
+            && self.position == other.position
+            && self.primary == other.primary
+            && self.scale_factor == other.scale_factor
+            && self.video_modes == other.video_modes
+            && self.size_mm == other.size_mm
+            && self.transform == other.transform
+            && self.subpixel == other.subpixel
+            && self.edid == other.edid
+    }
+
     pub(crate) fn is_dummy(&self) -> bool {
         // Zero is an invalid XID value; no real monitor will have it
         self.id == 0
@@ -175,6 +229,77 @@ impl MonitorHandle {
             }
         })
     }
+
+    pub fn size_mm(&self) -> Option<(u32, u32)> {
+        match self.size_mm {
+            (0, _) | (_, 0) => None,
+            size => Some(size),
+        }
+    }
+
+    #[inline]
+    pub fn transform(&self) -> MonitorTransform {
+        self.transform
+    }
+
+    #[inline]
+    pub fn subpixel(&self) -> Subpixel {
+        self.subpixel
+    }
+
+    fn edid_info(&self) -> util::EdidInfo {
+        self.edid
+            .as_deref()
+            .and_then(util::parse_edid)
+            .unwrap_or_default()
+    }
+
+    pub fn manufacturer(&self) -> Option<String> {
+        self.edid_info().manufacturer
+    }
+
+    pub fn model(&self) -> Option<String> {
+        self.edid_info().model
+    }
+
+    pub fn serial_number(&self) -> Option<String> {
+        self.edid_info().serial_number
+    }
+
+    pub fn edid(&self) -> Option<Vec<u8>> {
+        self.edid.as_deref().map(|edid| edid.to_vec())
+    }
+
+    #[inline]
+    pub fn is_primary(&self) -> bool {
+        self.primary
+    }
+}
+
+fn transform_from_rotation(rotation: u16) -> MonitorTransform {
+    let rotation = rotation as u32;
+    let mut degrees = match rotation & 0xf {
+        ffi::XCB_RANDR_ROTATION_ROTATE_90 => 90,
+        ffi::XCB_RANDR_ROTATION_ROTATE_180 => 180,
+        ffi::XCB_RANDR_ROTATION_ROTATE_270 => 270,
+        _ => 0,
+    };
+    let reflect_x = rotation & ffi::XCB_RANDR_ROTATION_REFLECT_X != 0;
+    let reflect_y = rotation & ffi::XCB_RANDR_ROTATION_REFLECT_Y != 0;
+    // A reflection in y is a reflection in x followed by a rotation by 180 degrees.
+    if reflect_y {
+        degrees = (degrees + 180) % 360;
+    }
+    match (reflect_x != reflect_y, degrees) {
+        (false, 90) => MonitorTransform::Rotate90,
+        (false, 180) => MonitorTransform::Rotate180,
+        (false, 270) => MonitorTransform::Rotate270,
+        (false, _) => MonitorTransform::Normal,
+        (true, 90) => MonitorTransform::Flipped90,
+        (true, 180) => MonitorTransform::Flipped180,
+        (true, 270) => MonitorTransform::Flipped270,
+        (true, _) => MonitorTransform::Flipped,
+    }
 }
 
 impl XConnection {
@@ -262,7 +387,6 @@ impl XConnection {
             // If no monitors were detected as being primary, we just pick one ourselves!
             if !has_primary {
                 if let Some(ref mut fallback) = available.first_mut() {
-                    // Setting this here will come in handy if we ever add an `is_primary` method.
                     fallback.primary = true;
                 }
             }
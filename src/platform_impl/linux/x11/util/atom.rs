@@ -26,4 +26,18 @@ impl XConnection {
             }
         }
     }
+
+    pub fn get_atom_name(&self, atom: ffi::xcb_atom_t) -> Result<String, XcbError> {
+        unsafe {
+            let cookie = self.xcb.xcb_get_atom_name(self.c, atom);
+            let mut err = ptr::null_mut();
+            let reply = self.xcb.xcb_get_atom_name_reply(self.c, cookie, &mut err);
+            let reply = self.check(reply, err)?;
+            let name = std::slice::from_raw_parts(
+                self.xcb.xcb_get_atom_name_name(&*reply) as *const u8,
+                self.xcb.xcb_get_atom_name_name_length(&*reply) as usize,
+            );
+            Ok(String::from_utf8_lossy(name).into_owned())
+        }
+    }
 }
@@ -1,18 +1,140 @@
-use crate::window::CursorIcon;
+use crate::{
+    error::NotSupportedError,
+    window::{CursorIcon, CustomCursor},
+};
 
 use super::*;
-use std::slice;
+use std::{convert::TryFrom, env, slice, sync::Arc};
 use xcb_dl_util::cursor::{XcbCursorImage, XcbLoadCursorConfig};
 
+/// An X cursor created from a `CustomCursor`. The cursor is freed when this is dropped.
+pub struct XCustomCursor {
+    xconn: Arc<XConnection>,
+    pub cursor: ffi::xcb_cursor_t,
+}
+
+impl Drop for XCustomCursor {
+    fn drop(&mut self) {
+        if self.cursor != 0 {
+            unsafe {
+                let cookie = self
+                    .xconn
+                    .xcb
+                    .xcb_free_cursor_checked(self.xconn.c, self.cursor);
+                if let Err(e) = self.xconn.check_cookie(cookie) {
+                    log::warn!("Could not free a custom cursor: {}", e);
+                }
+            }
+        }
+    }
+}
+
+/// The Xcursor theme from which cursor icons are loaded.
+#[derive(Debug, Clone)]
+pub struct CursorTheme {
+    pub name: Option<String>,
+    /// The size in logical pixels. If this is `None`, the size is determined from the
+    /// `Xcursor.size` and `Xft.dpi` resources.
+    pub size: Option<u32>,
+}
+
 impl XConnection {
-    pub fn set_cursor_icon(&self, window: ffi::xcb_window_t, cursor: Option<CursorIcon>) {
-        let cursor = *self
+    pub fn set_cursor_icon(
+        &self,
+        window: ffi::xcb_window_t,
+        cursor: Option<CursorIcon>,
+        scale_factor: f64,
+    ) {
+        let cursor = self.get_cached_cursor(cursor, scale_factor);
+
+        self.update_cursor(window, cursor);
+    }
+
+    /// Returns the X cursor for the icon. `None` is an invisible cursor.
+    ///
+    /// If the cursor theme has a size, the size of the cursor is that size multiplied by
+    /// `scale_factor`.
+    pub fn get_cached_cursor(
+        &self,
+        cursor: Option<CursorIcon>,
+        scale_factor: f64,
+    ) -> ffi::xcb_cursor_t {
+        let theme = self.cursor_theme();
+        let size = theme
+            .size
+            .map(|size| (size as f64 * scale_factor).round() as u32);
+        *self
             .cursor_cache
             .lock()
-            .entry(cursor)
-            .or_insert_with(|| self.get_cursor(cursor));
+            .entry((cursor, size))
+            .or_insert_with(|| self.get_cursor(cursor, &theme, size))
+    }
 
-        self.update_cursor(window, cursor);
+    /// Returns the cursor theme, determining the default theme on first use.
+    fn cursor_theme(&self) -> CursorTheme {
+        self.cursor_theme
+            .lock()
+            .get_or_insert_with(|| self.default_cursor_theme())
+            .clone()
+    }
+
+    /// Replaces the cursor theme. `None` values are taken from the environment.
+    ///
+    /// Cursors that have been loaded from the previous theme are freed. Windows keep showing
+    /// them until their cursor is set again.
+    pub fn set_cursor_theme(&self, name: Option<&str>, size: Option<u32>) {
+        let default = self.default_cursor_theme();
+        let theme = CursorTheme {
+            name: name.map(|n| n.to_string()).or(default.name),
+            size: size.or(default.size),
+        };
+        *self.cursor_theme.lock() = Some(theme);
+        for (_, cursor) in self.cursor_cache.lock().drain() {
+            if cursor != 0 {
+                unsafe {
+                    self.xcb.xcb_free_cursor(self.c, cursor);
+                }
+            }
+        }
+    }
+
+    /// Determines the cursor theme from the `XCURSOR_THEME` and `XCURSOR_SIZE` environment
+    /// variables and the `Xcursor.theme` resource, in this order.
+    fn default_cursor_theme(&self) -> CursorTheme {
+        let resources = self.resource_manager();
+        let resource = |name: &str| {
+            let resources = resources.as_deref()?;
+            resources.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                match key.trim() == name {
+                    true => Some(value.trim().to_string()),
+                    false => None,
+                }
+            })
+        };
+        let name = env::var("XCURSOR_THEME")
+            .ok()
+            .or_else(|| resource("Xcursor.theme"))
+            .filter(|n| !n.is_empty());
+        let size = env::var("XCURSOR_SIZE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|&s| s > 0);
+        CursorTheme { name, size }
+    }
+
+    /// Returns the contents of the `RESOURCE_MANAGER` property.
+    fn resource_manager(&self) -> Option<String> {
+        // Like Xlib, read the resources of the first screen.
+        let root = self.screens[0].root;
+        match self.get_property_raw(root, ffi::XCB_ATOM_RESOURCE_MANAGER, false) {
+            Ok((_, data)) if !data.is_empty() => Some(String::from_utf8_lossy(&data).into_owned()),
+            Ok(_) => None,
+            Err(e) => {
+                log::warn!("Could not read the X resources: {}", e);
+                None
+            }
+        }
     }
 
     fn create_empty_cursor(&self) -> ffi::xcb_cursor_t {
@@ -39,10 +161,67 @@ impl XConnection {
         }
     }
 
-    fn load_cursor(&self, name: &str) -> Option<ffi::xcb_cursor_t> {
+    /// Creates an X cursor from the frames of the cursor.
+    ///
+    /// Returns an error if a frame does not fit into the 16-bit dimensions of X cursors. If the
+    /// cursor cannot be created, the default cursor is used instead.
+    pub fn create_custom_cursor(
+        self: &Arc<Self>,
+        cursor: &CustomCursor,
+    ) -> Result<XCustomCursor, NotSupportedError> {
+        let to_u16 = |v: u32| u16::try_from(v).map_err(|_| NotSupportedError::new());
+        let mut images = Vec::with_capacity(cursor.frames.len());
+        for frame in cursor.frames.iter() {
+            // Cursor images contain premultiplied ARGB pixels.
+            let pixels = frame
+                .image
+                .rgba
+                .chunks_exact(4)
+                .map(|p| {
+                    let a = p[3] as u32;
+                    let premultiply = |c: u8| c as u32 * a / 255;
+                    (a << 24)
+                        | (premultiply(p[0]) << 16)
+                        | (premultiply(p[1]) << 8)
+                        | premultiply(p[2])
+                })
+                .collect();
+            images.push(XcbCursorImage {
+                width: to_u16(frame.image.width)?,
+                height: to_u16(frame.image.height)?,
+                xhot: to_u16(frame.hotspot.x)?,
+                yhot: to_u16(frame.hotspot.y)?,
+                delay: frame.duration.as_millis() as u32,
+                pixels,
+                ..Default::default()
+            });
+        }
+        let cursor = unsafe {
+            match self.cursors.create_cursor(&self.xcb, &self.render, &images) {
+                Ok(c) => c,
+                Err(e) => {
+                    log::error!("Could not create custom cursor: {}", e);
+                    0
+                }
+            }
+        };
+        Ok(XCustomCursor {
+            xconn: self.clone(),
+            cursor,
+        })
+    }
+
+    fn load_cursor(
+        &self,
+        name: &str,
+        theme: &CursorTheme,
+        size: Option<u32>,
+    ) -> Option<ffi::xcb_cursor_t> {
         unsafe {
             let config = XcbLoadCursorConfig {
                 name,
+                theme: theme.name.as_deref(),
+                size,
                 ..Default::default()
             };
             match self.cursors.load_cursor(&self.xcb, &self.render, &config) {
@@ -55,24 +234,34 @@ impl XConnection {
         }
     }
 
-    fn load_first_existing_cursor(&self, names: &[&str]) -> Option<ffi::xcb_cursor_t> {
+    fn load_first_existing_cursor(
+        &self,
+        names: &[&str],
+        theme: &CursorTheme,
+        size: Option<u32>,
+    ) -> Option<ffi::xcb_cursor_t> {
         for name in names.iter() {
-            if let Some(xcursor) = self.load_cursor(name) {
+            if let Some(xcursor) = self.load_cursor(name, theme, size) {
                 return Some(xcursor);
             }
         }
         None
     }
 
-    fn get_cursor(&self, cursor: Option<CursorIcon>) -> ffi::xcb_cursor_t {
+    fn get_cursor(
+        &self,
+        cursor: Option<CursorIcon>,
+        theme: &CursorTheme,
+        size: Option<u32>,
+    ) -> ffi::xcb_cursor_t {
         let cursor = match cursor {
             Some(cursor) => cursor,
             None => return self.create_empty_cursor(),
         };
 
-        let load = |name: &str| self.load_cursor(name);
+        let load = |name: &str| self.load_cursor(name, theme, size);
 
-        let loadn = |names: &[&str]| self.load_first_existing_cursor(names);
+        let loadn = |names: &[&str]| self.load_first_existing_cursor(names, theme, size);
 
         // Try multiple names in some cases where the name
         // differs on the desktop environments or themes.
@@ -125,7 +314,7 @@ impl XConnection {
         cursor.unwrap_or(0)
     }
 
-    fn update_cursor(&self, window: ffi::xcb_window_t, cursor: ffi::xcb_cursor_t) {
+    pub fn update_cursor(&self, window: ffi::xcb_window_t, cursor: ffi::xcb_cursor_t) {
         unsafe {
             let cookie = self.xcb.xcb_change_window_attributes(
                 self.c,
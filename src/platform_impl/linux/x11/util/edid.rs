@@ -0,0 +1,62 @@
+//! Parsing of the EDID that monitors report in the `EDID` output property.
+
+const HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+
+const DESCRIPTOR_SERIAL_NUMBER: u8 = 0xff;
+const DESCRIPTOR_NAME: u8 = 0xfc;
+
+/// The identification of a monitor in its EDID.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EdidInfo {
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    pub serial_number: Option<String>,
+}
+
+pub fn parse_edid(edid: &[u8]) -> Option<EdidInfo> {
+    if edid.len() < 128 || edid[..8] != HEADER {
+        return None;
+    }
+
+    // Three letters with five bits each, where 1 is `A`.
+    let id = u16::from_be_bytes([edid[8], edid[9]]);
+    let letter = |shift: u16| match ((id >> shift) & 0x1f) as u8 {
+        c @ 1..=26 => Some((b'A' + c - 1) as char),
+        _ => None,
+    };
+    let manufacturer = match (letter(10), letter(5), letter(0)) {
+        (Some(a), Some(b), Some(c)) => Some([a, b, c].iter().collect()),
+        _ => None,
+    };
+    let product_code = u16::from_le_bytes([edid[10], edid[11]]);
+    let serial = u32::from_le_bytes([edid[12], edid[13], edid[14], edid[15]]);
+
+    let mut name = None;
+    let mut serial_number = None;
+    for descriptor in edid[54..126].chunks(18) {
+        // Detailed timing descriptors start with a non-zero pixel clock.
+        if descriptor[..3] != [0, 0, 0] {
+            continue;
+        }
+        match descriptor[3] {
+            DESCRIPTOR_NAME => name = Some(descriptor_text(&descriptor[5..])),
+            DESCRIPTOR_SERIAL_NUMBER => serial_number = Some(descriptor_text(&descriptor[5..])),
+            _ => {}
+        }
+    }
+    if serial_number.is_none() && serial != 0 {
+        serial_number = Some(serial.to_string());
+    }
+
+    Some(EdidInfo {
+        manufacturer,
+        model: name.or_else(|| Some(format!("{:04X}", product_code))),
+        serial_number,
+    })
+}
+
+fn descriptor_text(text: &[u8]) -> String {
+    // The text is terminated by a line feed and padded with spaces.
+    let end = text.iter().position(|&b| b == b'\n').unwrap_or(text.len());
+    String::from_utf8_lossy(&text[..end]).trim_end().to_string()
+}
@@ -166,6 +166,27 @@ impl XConnection {
         }
     }
 
+    /// Returns the child of `window` that contains the point `x`x`y` given in coordinates of
+    /// `root`. Returns 0 if there is no such child.
+    pub fn child_at(
+        &self,
+        root: ffi::xcb_window_t,
+        window: ffi::xcb_window_t,
+        x: i16,
+        y: i16,
+    ) -> Result<ffi::xcb_window_t, XcbError> {
+        unsafe {
+            let mut err = ptr::null_mut();
+            let reply = self.xcb.xcb_translate_coordinates_reply(
+                self.c,
+                self.xcb
+                    .xcb_translate_coordinates(self.c, root, window, x, y),
+                &mut err,
+            );
+            Ok(self.check(reply, err)?.child)
+        }
+    }
+
     // This is adequate for inner_size
     pub fn get_geometry(&self, window: ffi::xcb_window_t) -> Result<Geometry, XcbError> {
         unsafe {
@@ -5,21 +5,39 @@ use xcb_dl_util::xcb_box::XcbBox;
 pub const VIRTUAL_CORE_POINTER: ffi::xcb_input_device_id_t = 2;
 
 impl XConnection {
-    pub fn select_xinput_events(
+    /// Selects XInput2 events.
+    ///
+    /// The `n`th word of `mask` contains the bits of the event types `32 * n..32 * (n + 1)`.
+    pub fn select_xinput_events<const N: usize>(
         &self,
         window: ffi::xcb_window_t,
         device_id: ffi::xcb_input_device_id_t,
-        mask: u32,
+        mask: [u32; N],
     ) -> XcbPendingCommand {
         unsafe {
-            xcb_dl_util::input::select_events_checked(
-                &self.xinput,
-                self.c,
-                window,
-                device_id,
-                [mask],
-            )
-            .into()
+            xcb_dl_util::input::select_events_checked(&self.xinput, self.c, window, device_id, mask)
+                .into()
+        }
+    }
+
+    /// Selects XInput 1 events.
+    ///
+    /// Each class is `(device_id << 8) | event_type`. This is only needed for events that have
+    /// no XInput2 equivalent.
+    pub fn select_xi1_events(
+        &self,
+        window: ffi::xcb_window_t,
+        classes: &[ffi::xcb_input_event_class_t],
+    ) -> XcbPendingCommand {
+        unsafe {
+            self.xinput
+                .xcb_input_select_extension_event_checked(
+                    self.c,
+                    window,
+                    classes.len() as _,
+                    classes.as_ptr(),
+                )
+                .into()
         }
     }
 
@@ -66,6 +84,50 @@ impl XConnection {
         }
     }
 
+    /// Actively grabs a pointer so that its motion and button release events are reported to
+    /// `window` wherever the pointer is. Grabbing again replaces the cursor.
+    pub fn grab_pointer_device(
+        &self,
+        window: ffi::xcb_window_t,
+        device_id: ffi::xcb_input_device_id_t,
+        cursor: ffi::xcb_cursor_t,
+    ) -> Result<ffi::xcb_grab_status_t, XcbError> {
+        let mask =
+            ffi::XCB_INPUT_XI_EVENT_MASK_MOTION | ffi::XCB_INPUT_XI_EVENT_MASK_BUTTON_RELEASE;
+        unsafe {
+            let mut err = ptr::null_mut();
+            let reply = self.xinput.xcb_input_xi_grab_device_reply(
+                self.c,
+                self.xinput.xcb_input_xi_grab_device(
+                    self.c,
+                    window,
+                    ffi::XCB_TIME_CURRENT_TIME,
+                    cursor,
+                    device_id,
+                    ffi::XCB_INPUT_GRAB_MODE_22_ASYNC as u8,
+                    ffi::XCB_INPUT_GRAB_MODE_22_ASYNC as u8,
+                    ffi::XCB_INPUT_GRAB_OWNER_NO_OWNER as u8,
+                    1,
+                    &mask,
+                ),
+                &mut err,
+            );
+            let reply = self.check(reply, err)?;
+            Ok(reply.status as ffi::xcb_grab_status_t)
+        }
+    }
+
+    pub fn ungrab_pointer_device(
+        &self,
+        device_id: ffi::xcb_input_device_id_t,
+    ) -> XcbPendingCommand {
+        unsafe {
+            self.xinput
+                .xcb_input_xi_ungrab_device_checked(self.c, ffi::XCB_TIME_CURRENT_TIME, device_id)
+                .into()
+        }
+    }
+
     pub fn query_pointer(
         &self,
         window: ffi::xcb_window_t,
@@ -83,6 +145,33 @@ impl XConnection {
         }
     }
 
+    /// Returns the XInput 1 type of a device, e.g. the `TOUCHPAD` atom.
+    ///
+    /// XInput2 does not report the type of physical devices.
+    pub fn input_device_type(
+        &self,
+        device_id: ffi::xcb_input_device_id_t,
+    ) -> Result<Option<ffi::xcb_atom_t>, XcbError> {
+        unsafe {
+            let mut err = ptr::null_mut();
+            let reply = self.xinput.xcb_input_list_input_devices_reply(
+                self.c,
+                self.xinput.xcb_input_list_input_devices(self.c),
+                &mut err,
+            );
+            let reply = self.check(reply, err)?;
+            let devices = std::slice::from_raw_parts(
+                self.xinput.xcb_input_list_input_devices_devices(&*reply),
+                reply.devices_len as usize,
+            );
+            Ok(devices
+                .iter()
+                .find(|d| d.device_id as ffi::xcb_input_device_id_t == device_id)
+                .map(|d| d.device_type)
+                .filter(|&ty| ty != 0))
+        }
+    }
+
     pub fn make_auto_repeat_detectable(
         &self,
         device_id: ffi::xcb_input_device_id_t,
@@ -4,18 +4,20 @@
 mod atom;
 mod client_msg;
 mod cursor;
+mod edid;
 mod geometry;
 mod hint;
 mod icon;
 mod input;
+mod present;
 mod queue;
 mod randr;
 mod window_property;
 mod wm;
 
 pub use self::{
-    atom::*, client_msg::*, geometry::*, hint::*, icon::*, input::*, queue::*, randr::*,
-    window_property::*, wm::*,
+    atom::*, client_msg::*, cursor::*, edid::*, geometry::*, hint::*, icon::*, input::*,
+    present::*, queue::*, randr::*, window_property::*, wm::*,
 };
 
 use std::{
@@ -0,0 +1,287 @@
+use crate::error::{ExternalError, NotSupportedError};
+use crate::platform_impl::OsError;
+use crate::window::DamageRect;
+
+use super::*;
+use std::{io, slice, sync::Arc};
+
+/// The size of the header of a `PutImage` request in bytes.
+const PUT_IMAGE_HEADER: usize = 24;
+
+/// A shared memory segment that is attached to the X server.
+struct ShmSegment {
+    seg: ffi::xcb_shm_seg_t,
+    addr: *mut u8,
+    len: usize,
+}
+
+/// Copies frames of RGBA pixels into a window.
+///
+/// The graphics context and the shared memory segment are created when the first frame is
+/// presented and freed when this is dropped.
+pub struct Presenter {
+    xconn: Arc<XConnection>,
+    window: ffi::xcb_window_t,
+    gc: ffi::xcb_gcontext_t,
+    depth: u8,
+    shm: Option<ShmSegment>,
+    // Attaching a segment fails if the X server runs on another machine. We don't try again.
+    shm_failed: bool,
+    buffer: Vec<u8>,
+}
+
+// The segment is only accessed while the presenter is borrowed mutably.
+unsafe impl Send for Presenter {}
+
+impl Presenter {
+    pub fn new(xconn: &Arc<XConnection>, window: ffi::xcb_window_t) -> Self {
+        Presenter {
+            xconn: xconn.clone(),
+            window,
+            gc: 0,
+            depth: 0,
+            shm: None,
+            shm_failed: false,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Copies the damaged rectangles of a frame into the window.
+    ///
+    /// `damage` must already be clipped to the frame. Returns once the X server has copied the
+    /// frame.
+    pub fn present(
+        &mut self,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        damage: &[DamageRect],
+    ) -> Result<(), ExternalError> {
+        if width > i16::MAX as u32 || height > i16::MAX as u32 {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+        if self.gc == 0 {
+            self.init().map_err(os_error)?;
+        }
+        // Windows with a depth of 24 or 32 bits use 32-bit pixels on all common X servers.
+        if self.depth != 24 && self.depth != 32 {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+        let lsb_first = unsafe {
+            (*self.xconn.xcb.xcb_get_setup(self.xconn.c)).image_byte_order
+                == ffi::XCB_IMAGE_ORDER_LSB_FIRST as u8
+        };
+        let res = if self.shm_segment(rgba.len()) {
+            self.present_shm(rgba, width, height, damage, lsb_first)
+        } else {
+            self.present_put_image(rgba, width, damage, lsb_first)
+        };
+        res.map_err(os_error)
+    }
+
+    fn init(&mut self) -> Result<(), XcbError> {
+        self.depth = self.xconn.get_geometry(self.window)?.depth;
+        let gc = self.xconn.generate_id();
+        unsafe {
+            let cookie =
+                self.xconn
+                    .xcb
+                    .xcb_create_gc_checked(self.xconn.c, gc, self.window, 0, ptr::null());
+            self.xconn.check_cookie(cookie)?;
+        }
+        self.gc = gc;
+        Ok(())
+    }
+
+    /// Makes sure that a segment of at least `len` bytes is attached if MIT-SHM can be used.
+    fn shm_segment(&mut self, len: usize) -> bool {
+        if self.shm_failed || self.xconn.shm.is_none() {
+            return false;
+        }
+        if let Some(segment) = &self.shm {
+            if segment.len >= len {
+                return true;
+            }
+        }
+        if let Some(segment) = self.shm.take() {
+            self.destroy_segment(segment);
+        }
+        match self.create_segment(len) {
+            Ok(segment) => {
+                self.shm = Some(segment);
+                true
+            }
+            Err(e) => {
+                log::warn!("Could not use MIT-SHM, falling back to PutImage: {}", e);
+                self.shm_failed = true;
+                false
+            }
+        }
+    }
+
+    fn create_segment(&self, len: usize) -> Result<ShmSegment, String> {
+        let shm = self.xconn.shm.as_ref().unwrap();
+        unsafe {
+            let id = libc::shmget(libc::IPC_PRIVATE, len, libc::IPC_CREAT | 0o600);
+            if id == -1 {
+                return Err(io::Error::last_os_error().to_string());
+            }
+            let addr = libc::shmat(id, ptr::null(), 0);
+            if addr as isize == -1 {
+                let e = io::Error::last_os_error();
+                libc::shmctl(id, libc::IPC_RMID, ptr::null_mut());
+                return Err(e.to_string());
+            }
+            let seg = self.xconn.generate_id();
+            let cookie = shm.xcb_shm_attach_checked(self.xconn.c, seg, id as u32, 0);
+            let res = self.xconn.check_cookie(cookie);
+            // The segment is removed once both we and the X server have detached it.
+            libc::shmctl(id, libc::IPC_RMID, ptr::null_mut());
+            if let Err(e) = res {
+                libc::shmdt(addr);
+                return Err(e.to_string());
+            }
+            Ok(ShmSegment {
+                seg,
+                addr: addr as *mut u8,
+                len,
+            })
+        }
+    }
+
+    fn destroy_segment(&self, segment: ShmSegment) {
+        let shm = self.xconn.shm.as_ref().unwrap();
+        unsafe {
+            let cookie = shm.xcb_shm_detach_checked(self.xconn.c, segment.seg);
+            if let Err(e) = self.xconn.check_cookie(cookie) {
+                log::warn!("Could not detach a shared memory segment: {}", e);
+            }
+            libc::shmdt(segment.addr as *const _);
+        }
+    }
+
+    fn present_shm(
+        &mut self,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        damage: &[DamageRect],
+        lsb_first: bool,
+    ) -> Result<(), XcbError> {
+        let shm = self.xconn.shm.as_ref().unwrap();
+        let segment = self.shm.as_ref().unwrap();
+        // Requests are checked before returning, so the X server no longer reads the segment.
+        let data = unsafe { slice::from_raw_parts_mut(segment.addr, segment.len) };
+        let stride = width as usize * 4;
+        let mut commands = XcbPendingCommands::new();
+        for rect in damage {
+            for row in rect.y..rect.y + rect.height {
+                let start = row as usize * stride + rect.x as usize * 4;
+                let end = start + rect.width as usize * 4;
+                convert_pixels(&rgba[start..end], &mut data[start..end], lsb_first);
+            }
+            unsafe {
+                let cookie = shm.xcb_shm_put_image_checked(
+                    self.xconn.c,
+                    self.window,
+                    self.gc,
+                    width as u16,
+                    height as u16,
+                    rect.x as u16,
+                    rect.y as u16,
+                    rect.width as u16,
+                    rect.height as u16,
+                    rect.x as i16,
+                    rect.y as i16,
+                    self.depth,
+                    ffi::XCB_IMAGE_FORMAT_Z_PIXMAP as u8,
+                    0,
+                    segment.seg,
+                    0,
+                );
+                commands.push(cookie.into());
+            }
+        }
+        self.xconn.check_pending(commands)
+    }
+
+    fn present_put_image(
+        &mut self,
+        rgba: &[u8],
+        width: u32,
+        damage: &[DamageRect],
+        lsb_first: bool,
+    ) -> Result<(), XcbError> {
+        let max_request_len =
+            unsafe { self.xconn.xcb.xcb_get_maximum_request_length(self.xconn.c) } as usize * 4;
+        let stride = width as usize * 4;
+        let mut commands = XcbPendingCommands::new();
+        for rect in damage {
+            let rect_stride = rect.width as usize * 4;
+            self.buffer.resize(rect_stride * rect.height as usize, 0);
+            for row in 0..rect.height as usize {
+                let start = (rect.y as usize + row) * stride + rect.x as usize * 4;
+                convert_pixels(
+                    &rgba[start..start + rect_stride],
+                    &mut self.buffer[row * rect_stride..(row + 1) * rect_stride],
+                    lsb_first,
+                );
+            }
+            // Large rectangles are split into several requests.
+            let rows_per_request = ((max_request_len - PUT_IMAGE_HEADER) / rect_stride).max(1);
+            let chunk_len = rows_per_request * rect_stride;
+            for (i, rows) in self.buffer.chunks(chunk_len).enumerate() {
+                unsafe {
+                    let cookie = self.xconn.xcb.xcb_put_image_checked(
+                        self.xconn.c,
+                        ffi::XCB_IMAGE_FORMAT_Z_PIXMAP as u8,
+                        self.window,
+                        self.gc,
+                        rect.width as u16,
+                        (rows.len() / rect_stride) as u16,
+                        rect.x as i16,
+                        (rect.y as usize + i * rows_per_request) as i16,
+                        0,
+                        self.depth,
+                        rows.len() as u32,
+                        rows.as_ptr(),
+                    );
+                    commands.push(cookie.into());
+                }
+            }
+        }
+        self.xconn.check_pending(commands)
+    }
+}
+
+impl Drop for Presenter {
+    fn drop(&mut self) {
+        if let Some(segment) = self.shm.take() {
+            self.destroy_segment(segment);
+        }
+        if self.gc != 0 {
+            unsafe {
+                let cookie = self.xconn.xcb.xcb_free_gc_checked(self.xconn.c, self.gc);
+                if let Err(e) = self.xconn.check_cookie(cookie) {
+                    log::warn!("Could not free a graphics context: {}", e);
+                }
+            }
+        }
+    }
+}
+
+/// Converts RGBA pixels to opaque 32-bit pixels in the byte order of the X server.
+fn convert_pixels(src: &[u8], dst: &mut [u8], lsb_first: bool) {
+    for (src, dst) in src.chunks_exact(4).zip(dst.chunks_exact_mut(4)) {
+        let pixel = if lsb_first {
+            [src[2], src[1], src[0], 0xff]
+        } else {
+            [0xff, src[0], src[1], src[2]]
+        };
+        dst.copy_from_slice(&pixel);
+    }
+}
+
+fn os_error(e: XcbError) -> ExternalError {
+    ExternalError::Os(os_error!(OsError::XError(e.into())))
+}
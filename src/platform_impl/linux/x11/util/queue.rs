@@ -1,26 +1,29 @@
 use crate::platform_impl::x11::XConnection;
-use std::sync::Arc;
+use std::{cell::RefCell, collections::VecDeque, rc::Rc, sync::Arc};
 use xcb_dl::ffi;
 use xcb_dl_util::xcb_box::XcbBox;
 
+/// The events of the X connection. Clones share the events that have been read but not yet
+/// returned.
+#[derive(Clone)]
 pub struct EventQueue {
     xconn: Arc<XConnection>,
-    pending: Option<XcbBox<ffi::xcb_generic_event_t>>,
+    pending: Rc<RefCell<VecDeque<XcbBox<ffi::xcb_generic_event_t>>>>,
 }
 
 impl EventQueue {
     pub fn new(xconn: &Arc<XConnection>) -> Self {
         Self {
             xconn: xconn.clone(),
-            pending: None,
+            pending: Default::default(),
         }
     }
 
     pub fn has_pending_events(&mut self) -> bool {
         if let Some(event) = self.poll_for_event2(true) {
-            self.pending = Some(event);
+            self.pending.borrow_mut().push_front(event);
         }
-        self.pending.is_some()
+        !self.pending.borrow().is_empty()
     }
 
     pub fn poll_for_event(&mut self) -> Option<XcbBox<ffi::xcb_generic_event_t>> {
@@ -28,8 +31,8 @@ impl EventQueue {
     }
 
     fn poll_for_event2(&mut self, only_queued: bool) -> Option<XcbBox<ffi::xcb_generic_event_t>> {
-        if self.pending.is_some() {
-            return self.pending.take();
+        if let Some(event) = self.pending.borrow_mut().pop_front() {
+            return Some(event);
         }
         unsafe {
             let event = if only_queued {
@@ -37,14 +40,41 @@ impl EventQueue {
             } else {
                 self.xconn.xcb.xcb_poll_for_event(self.xconn.c)
             };
-            if event.is_null() {
-                if let Err(e) = self.xconn.errors.check_connection(&self.xconn.xcb) {
-                    panic!("The X connection is broken: {}", e);
+            self.wrap(event)
+        }
+    }
+
+    /// Blocks until an event arrives for which `f` returns `true` and returns it. The events
+    /// that arrive before it are returned by later calls of `poll_for_event`.
+    pub fn wait_for_event<F>(&self, mut f: F) -> XcbBox<ffi::xcb_generic_event_t>
+    where
+        F: FnMut(&ffi::xcb_generic_event_t) -> bool,
+    {
+        loop {
+            let event = unsafe {
+                let event = self.xconn.xcb.xcb_wait_for_event(self.xconn.c);
+                self.wrap(event)
+            };
+            if let Some(event) = event {
+                if f(&event) {
+                    return event;
                 }
-                None
-            } else {
-                Some(XcbBox::new(event))
+                self.pending.borrow_mut().push_back(event);
+            }
+        }
+    }
+
+    unsafe fn wrap(
+        &self,
+        event: *mut ffi::xcb_generic_event_t,
+    ) -> Option<XcbBox<ffi::xcb_generic_event_t>> {
+        if event.is_null() {
+            if let Err(e) = self.xconn.errors.check_connection(&self.xconn.xcb) {
+                panic!("The X connection is broken: {}", e);
             }
+            None
+        } else {
+            Some(XcbBox::new(event))
         }
     }
 }
@@ -2,7 +2,9 @@ use std::{env, slice, str, str::FromStr};
 
 use super::*;
 use crate::platform_impl::x11::xdisplay::Screen;
-use crate::{dpi::validate_scale_factor, platform_impl::platform::x11::VideoMode};
+use crate::{
+    dpi::validate_scale_factor, platform::unix::Subpixel, platform_impl::platform::x11::VideoMode,
+};
 
 /// Represents values of `WINIT_HIDPI_FACTOR`.
 pub enum EnvVarDPI {
@@ -11,6 +13,16 @@ pub enum EnvVarDPI {
     NotSet,
 }
 
+/// The properties of the first output of a CRTC.
+pub struct OutputInfo {
+    pub name: String,
+    pub scale_factor: f64,
+    pub video_modes: Vec<VideoMode>,
+    pub size_mm: (u32, u32),
+    pub subpixel: Subpixel,
+    pub edid: Option<Vec<u8>>,
+}
+
 pub fn calc_dpi_factor(
     (width_px, height_px): (u32, u32),
     (width_mm, height_mm): (u64, u64),
@@ -50,7 +62,7 @@ impl XConnection {
         screen: &Screen,
         resources: &ffi::xcb_randr_get_screen_resources_reply_t,
         crtc: &ffi::xcb_randr_get_crtc_info_reply_t,
-    ) -> Result<Option<(String, f64, Vec<VideoMode>)>, XcbError> {
+    ) -> Result<Option<OutputInfo>, XcbError> {
         let mut err = ptr::null_mut();
 
         let first_output = {
@@ -84,15 +96,28 @@ impl XConnection {
             // modes in the array in XRRScreenResources
             .filter(|x| output_modes.iter().any(|id| x.id == *id))
             .map(|x| {
-                let refresh_rate = if x.dot_clock > 0 && x.htotal > 0 && x.vtotal > 0 {
-                    x.dot_clock as u64 * 1000 / (x.htotal as u64 * x.vtotal as u64)
+                let interlaced = x.mode_flags & ffi::XCB_RANDR_MODE_FLAG_INTERLACE as u32 != 0;
+                let double_scan = x.mode_flags & ffi::XCB_RANDR_MODE_FLAG_DOUBLE_SCAN as u32 != 0;
+                // The same calculation as in xrandr.
+                let mut vtotal = x.vtotal as u64;
+                if double_scan {
+                    vtotal *= 2;
+                }
+                if interlaced {
+                    vtotal /= 2;
+                }
+                let refresh_rate_millihertz = if x.dot_clock > 0 && x.htotal > 0 && vtotal > 0 {
+                    let frame = x.htotal as u64 * vtotal;
+                    ((x.dot_clock as u64 * 1000 + frame / 2) / frame) as u32
                 } else {
                     0
                 };
 
                 VideoMode {
                     size: (x.width, x.height),
-                    refresh_rate: (refresh_rate as f32 / 1000.0).round() as u16,
+                    refresh_rate_millihertz,
+                    interlaced,
+                    double_scan,
                     bit_depth: screen.root_depth as u16,
                     native_mode: x.id,
                     // This is populated in `MonitorHandle::video_modes` as the
@@ -158,7 +183,56 @@ impl XConnection {
             }
         };
 
-        Ok(Some((name, scale_factor, modes)))
+        let subpixel = match output_info.subpixel_order as u32 {
+            ffi::XCB_RENDER_SUB_PIXEL_HORIZONTAL_RGB => Subpixel::HorizontalRgb,
+            ffi::XCB_RENDER_SUB_PIXEL_HORIZONTAL_BGR => Subpixel::HorizontalBgr,
+            ffi::XCB_RENDER_SUB_PIXEL_VERTICAL_RGB => Subpixel::VerticalRgb,
+            ffi::XCB_RENDER_SUB_PIXEL_VERTICAL_BGR => Subpixel::VerticalBgr,
+            ffi::XCB_RENDER_SUB_PIXEL_NONE => Subpixel::None,
+            _ => Subpixel::Unknown,
+        };
+
+        Ok(Some(OutputInfo {
+            name,
+            scale_factor,
+            video_modes: modes,
+            size_mm: (output_info.mm_width, output_info.mm_height),
+            subpixel,
+            edid: self.get_output_edid(first_output)?,
+        }))
+    }
+
+    unsafe fn get_output_edid(
+        &self,
+        output: ffi::xcb_randr_output_t,
+    ) -> Result<Option<Vec<u8>>, XcbError> {
+        let edid = self.get_atom("EDID");
+        let mut err = ptr::null_mut();
+        let reply = self.randr.xcb_randr_get_output_property_reply(
+            self.c,
+            self.randr.xcb_randr_get_output_property(
+                self.c,
+                output,
+                edid,
+                ffi::XCB_GET_PROPERTY_TYPE_ANY,
+                0,
+                // The length is measured in 4-byte units. EDIDs with extension blocks are
+                // rarely longer than 512 bytes.
+                256,
+                0,
+                0,
+            ),
+            &mut err,
+        );
+        let reply = self.check(reply, err)?;
+        if reply.format != 8 || reply.num_items == 0 {
+            return Ok(None);
+        }
+        let data = slice::from_raw_parts(
+            self.randr.xcb_randr_get_output_property_data(&*reply),
+            reply.num_items as usize,
+        );
+        Ok(Some(data.to_vec()))
     }
     pub fn set_crtc_config(
         &self,
@@ -1,4 +1,5 @@
 use super::*;
+use std::slice;
 use xcb_dl_util::format::XcbDataType;
 use xcb_dl_util::property::XcbGetPropertyError;
 
@@ -35,6 +36,47 @@ impl XConnection {
         }
     }
 
+    /// Reads a property of any type as raw bytes.
+    ///
+    /// Returns the type of the property and its contents. If `delete` is true, the property is
+    /// deleted after it has been read completely, as required by the selection protocol.
+    pub fn get_property_raw(
+        &self,
+        window: ffi::xcb_window_t,
+        property: ffi::xcb_atom_t,
+        delete: bool,
+    ) -> Result<(ffi::xcb_atom_t, Vec<u8>), XcbError> {
+        const STEP: u32 = 256 * 1024;
+        let mut property_type = ffi::XCB_ATOM_NONE;
+        let mut data = Vec::new();
+        unsafe {
+            loop {
+                let cookie = self.xcb.xcb_get_property(
+                    self.c,
+                    delete as u8,
+                    window,
+                    property,
+                    ffi::XCB_GET_PROPERTY_TYPE_ANY,
+                    (data.len() / 4) as u32,
+                    STEP,
+                );
+                let mut err = ptr::null_mut();
+                let reply = self.xcb.xcb_get_property_reply(self.c, cookie, &mut err);
+                let reply = self.check(reply, err)?;
+                property_type = reply.type_;
+                let value = slice::from_raw_parts(
+                    self.xcb.xcb_get_property_value(&*reply) as *const u8,
+                    self.xcb.xcb_get_property_value_length(&*reply) as usize,
+                );
+                data.extend_from_slice(value);
+                if reply.bytes_after == 0 {
+                    break;
+                }
+            }
+        }
+        Ok((property_type, data))
+    }
+
     pub fn change_property<T: XcbDataType>(
         &self,
         window: ffi::xcb_window_t,
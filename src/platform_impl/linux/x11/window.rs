@@ -2,13 +2,7 @@ use raw_window_handle::unix::XcbHandle;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering::Relaxed;
 use std::{
-    cmp, env,
-    ffi::CString,
-    mem::{replace, MaybeUninit},
-    os::raw::*,
-    path::Path,
-    ptr, slice,
-    sync::Arc,
+    cmp, env, ffi::CString, mem::MaybeUninit, os::raw::*, path::Path, ptr, slice, sync::Arc,
 };
 
 use libc;
@@ -18,15 +12,25 @@ use parking_lot::Mutex;
 use crate::{
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
     error::{ExternalError, NotSupportedError, OsError as RootOsError},
+    event::DndAction,
     monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
     platform_impl::{
         x11::MonitorHandle as X11MonitorHandle, MonitorHandle as PlatformMonitorHandle, OsError,
         PlatformSpecificWindowBuilderAttributes, VideoMode as PlatformVideoMode,
     },
-    window::{CursorIcon, Fullscreen, Icon, UserAttentionType, WindowAttributes},
+    window::{
+        CursorGrabMode, CursorIcon, CustomCursor, DamageRect, DragData, Fullscreen, Icon,
+        UserAttentionType, WindowAttributes,
+    },
 };
 
-use super::{ffi, util, EventLoopWindowTarget, WindowId, XConnection};
+use super::{
+    drag::DragRequest,
+    ffi, gesture_ffi,
+    ime::ImeRequest,
+    util::{self, XCustomCursor},
+    EventLoopWindowTarget, WindowId, XConnection,
+};
 use crate::platform_impl::x11::util::HintsError;
 use crate::platform_impl::x11::util::PropMode;
 use crate::platform_impl::x11::xdisplay::Screen;
@@ -55,6 +59,8 @@ pub struct SharedState {
     pub resize_increments: Option<Size>,
     pub base_size: Option<Size>,
     pub visibility: Visibility,
+    // The position at which the cursor is held while it is locked
+    pub cursor_lock: Option<(i16, i16)>,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -92,10 +98,17 @@ impl SharedState {
             max_inner_size: None,
             resize_increments: None,
             base_size: None,
+            cursor_lock: None,
         })
     }
 }
 
+/// The cursor that is shown while the cursor is visible.
+enum WindowCursor {
+    Icon(CursorIcon),
+    Custom(XCustomCursor),
+}
+
 unsafe impl Send for UnownedWindow {}
 unsafe impl Sync for UnownedWindow {}
 
@@ -103,12 +116,15 @@ pub struct UnownedWindow {
     pub xconn: Arc<XConnection>,    // never changes
     pub xwindow: ffi::xcb_window_t, // never changes
     pub screen: Arc<Screen>,
-    cursor: Mutex<CursorIcon>,
-    cursor_grabbed: Mutex<bool>,
+    cursor: Mutex<WindowCursor>,
+    cursor_grabbed: Mutex<CursorGrabMode>,
     cursor_visible: Mutex<bool>,
     pub shared_state: Mutex<SharedState>,
     redraw_sender: Sender<WindowId>,
     reset_dead_keys: Arc<AtomicUsize>,
+    ime_sender: Sender<ImeRequest>,
+    drag_sender: Sender<DragRequest>,
+    presenter: Mutex<util::Presenter>,
 }
 
 impl UnownedWindow {
@@ -269,12 +285,15 @@ impl UnownedWindow {
             xconn: Arc::clone(xconn),
             xwindow,
             screen: screen.clone(),
-            cursor: Default::default(),
-            cursor_grabbed: Mutex::new(false),
+            cursor: Mutex::new(WindowCursor::Icon(Default::default())),
+            cursor_grabbed: Mutex::new(CursorGrabMode::None),
             cursor_visible: Mutex::new(true),
             shared_state: SharedState::new(guessed_monitor, window_attrs.visible),
             redraw_sender: event_loop.redraw_sender.clone(),
             reset_dead_keys: event_loop.reset_dead_keys.clone(),
+            ime_sender: event_loop.ime_sender.clone(),
+            drag_sender: event_loop.drag_sender.clone(),
+            presenter: Mutex::new(util::Presenter::new(xconn, xwindow)),
         };
 
         // Title must be set before mapping. Some tiling window managers (i.e. i3) use the window
@@ -413,6 +432,16 @@ impl UnownedWindow {
                     | ffi::XCB_INPUT_XI_EVENT_MASK_TOUCH_BEGIN
                     | ffi::XCB_INPUT_XI_EVENT_MASK_TOUCH_UPDATE
                     | ffi::XCB_INPUT_XI_EVENT_MASK_TOUCH_END;
+                let mut mask = [mask, 0];
+                if xconn.xinput_version >= (2, 4) {
+                    // The bit of `XI_GestureSwipeEnd` is the first bit of the second word.
+                    mask[0] |= gesture_ffi::XCB_INPUT_XI_EVENT_MASK_GESTURE_PINCH_BEGIN
+                        | gesture_ffi::XCB_INPUT_XI_EVENT_MASK_GESTURE_PINCH_UPDATE
+                        | gesture_ffi::XCB_INPUT_XI_EVENT_MASK_GESTURE_PINCH_END
+                        | gesture_ffi::XCB_INPUT_XI_EVENT_MASK_GESTURE_SWIPE_BEGIN
+                        | gesture_ffi::XCB_INPUT_XI_EVENT_MASK_GESTURE_SWIPE_UPDATE;
+                    mask[1] |= 1 << (gesture_ffi::XCB_INPUT_GESTURE_SWIPE_END - 32);
+                }
                 mask
             };
             let pending = xconn.select_xinput_events(
@@ -607,6 +636,24 @@ impl UnownedWindow {
                 shared_state_lock.desktop_video_mode =
                     Some((monitor.id, self.xconn.get_crtc_mode(monitor.id).unwrap()));
             }
+            // Restore the desktop video mode of the previous monitor if exclusive fullscreen
+            // moves to another monitor
+            (
+                &Some(Fullscreen::Exclusive(_)),
+                &Some(Fullscreen::Exclusive(RootVideoMode {
+                    video_mode: PlatformVideoMode::X(ref video_mode),
+                })),
+            ) => {
+                let monitor = video_mode.monitor.as_ref().unwrap();
+                let (monitor_id, mode_id) = shared_state_lock.desktop_video_mode.unwrap();
+                if monitor_id != monitor.id {
+                    self.xconn
+                        .set_crtc_config(monitor_id, mode_id)
+                        .expect("failed to restore desktop video mode");
+                    shared_state_lock.desktop_video_mode =
+                        Some((monitor.id, self.xconn.get_crtc_mode(monitor.id).unwrap()));
+                }
+            }
             // Restore desktop video mode upon exiting exclusive fullscreen
             (&Some(Fullscreen::Exclusive(_)), &None)
             | (&Some(Fullscreen::Exclusive(_)), &Some(Fullscreen::Borderless(_))) => {
@@ -639,7 +686,7 @@ impl UnownedWindow {
                         inner: PlatformMonitorHandle::X(monitor),
                     })) => (None, monitor),
                     Fullscreen::Borderless(None) => (None, self.current_monitor()),
-                    #[cfg(feature = "wayland")]
+                    #[cfg(any(feature = "wayland", feature = "headless"))]
                     _ => unreachable!(),
                 };
 
@@ -1234,18 +1281,51 @@ impl UnownedWindow {
 
     #[inline]
     pub fn set_cursor_icon(&self, cursor: CursorIcon) {
-        let old_cursor = replace(&mut *self.cursor.lock(), cursor);
-        if cursor != old_cursor && *self.cursor_visible.lock() {
-            self.xconn.set_cursor_icon(self.xwindow as _, Some(cursor));
+        let mut cursor_lock = self.cursor.lock();
+        if let WindowCursor::Icon(old_cursor) = *cursor_lock {
+            if cursor == old_cursor {
+                return;
+            }
+        }
+        *cursor_lock = WindowCursor::Icon(cursor);
+        if *self.cursor_visible.lock() {
+            self.xconn
+                .set_cursor_icon(self.xwindow, Some(cursor), self.scale_factor());
+        }
+    }
+
+    /// Reloads the cursor icon after the cursor theme or the scale factor has changed.
+    pub(crate) fn refresh_cursor(&self, scale_factor: f64) {
+        if let WindowCursor::Icon(cursor) = *self.cursor.lock() {
+            if *self.cursor_visible.lock() {
+                self.xconn
+                    .set_cursor_icon(self.xwindow, Some(cursor), scale_factor);
+            }
         }
     }
 
     #[inline]
-    pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
+    pub fn set_custom_cursor(&self, cursor: &CustomCursor) -> Result<(), ExternalError> {
+        let cursor = self
+            .xconn
+            .create_custom_cursor(cursor)
+            .map_err(ExternalError::NotSupported)?;
+        let mut cursor_lock = self.cursor.lock();
+        if *self.cursor_visible.lock() {
+            self.xconn.update_cursor(self.xwindow, cursor.cursor);
+        }
+        // The previous custom cursor is freed after it has been replaced.
+        *cursor_lock = WindowCursor::Custom(cursor);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
         let mut grabbed_lock = self.cursor_grabbed.lock();
-        if grab == *grabbed_lock {
+        if mode == *grabbed_lock {
             return Ok(());
         }
+        self.shared_state.lock().cursor_lock = None;
         let ungrab = unsafe {
             // We ungrab before grabbing to prevent passive grabs from causing `AlreadyGrabbed`.
             // Therefore, this is common to both codepaths.
@@ -1254,8 +1334,13 @@ impl UnownedWindow {
                 .xcb_ungrab_pointer_checked(self.xconn.c, ffi::XCB_TIME_CURRENT_TIME)
                 .into()
         };
-        let result = if grab {
+        let result = if mode != CursorGrabMode::None {
             self.xconn.discard(ungrab);
+            // A locked cursor is hidden while the pointer is grabbed.
+            let cursor = match mode {
+                CursorGrabMode::Locked => self.xconn.get_cached_cursor(None, 1.0),
+                _ => 0,
+            };
             loop {
                 let result = unsafe {
                     let cookie = self.xconn.xcb.xcb_grab_pointer(
@@ -1278,7 +1363,7 @@ impl UnownedWindow {
                         ffi::XCB_GRAB_MODE_ASYNC as u8,
                         ffi::XCB_GRAB_MODE_ASYNC as u8,
                         self.xwindow,
-                        0,
+                        cursor,
                         ffi::XCB_TIME_CURRENT_TIME,
                     );
                     let mut err = ptr::null_mut();
@@ -1320,7 +1405,21 @@ impl UnownedWindow {
                 .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err.into()))))
         };
         if result.is_ok() {
-            *grabbed_lock = grab;
+            *grabbed_lock = mode;
+        }
+        if let (Ok(()), CursorGrabMode::Locked) = (&result, mode) {
+            match self
+                .xconn
+                .query_pointer(self.xwindow, util::VIRTUAL_CORE_POINTER)
+            {
+                Ok(pointer) => {
+                    self.shared_state.lock().cursor_lock = Some((
+                        util::fp1616_to_f64(pointer.win_x) as i16,
+                        util::fp1616_to_f64(pointer.win_y) as i16,
+                    ));
+                }
+                Err(e) => log::error!("Could not query the position of the locked cursor: {}", e),
+            }
         }
         result
     }
@@ -1331,14 +1430,18 @@ impl UnownedWindow {
         if visible == *visible_lock {
             return;
         }
-        let cursor = if visible {
-            Some(*self.cursor.lock())
-        } else {
-            None
-        };
         *visible_lock = visible;
         drop(visible_lock);
-        self.xconn.set_cursor_icon(self.xwindow as _, cursor);
+        match &*self.cursor.lock() {
+            WindowCursor::Custom(cursor) if visible => {
+                self.xconn.update_cursor(self.xwindow, cursor.cursor)
+            }
+            WindowCursor::Icon(cursor) if visible => {
+                self.xconn
+                    .set_cursor_icon(self.xwindow, Some(*cursor), self.scale_factor())
+            }
+            _ => self.xconn.set_cursor_icon(self.xwindow, None, 1.0),
+        }
     }
 
     #[inline]
@@ -1375,8 +1478,8 @@ impl UnownedWindow {
 
         let message = self.xconn.get_atom("_NET_WM_MOVERESIZE");
 
-        // we can't use `set_cursor_grab(false)` here because it doesn't run `XUngrabPointer`
-        // if the cursor isn't currently grabbed
+        // we can't use `set_cursor_grab(CursorGrabMode::None)` here because it doesn't run
+        // `XUngrabPointer` if the cursor isn't currently grabbed
         let mut grabbed_lock = self.cursor_grabbed.lock();
         let pending = unsafe {
             self.xconn
@@ -1387,7 +1490,8 @@ impl UnownedWindow {
         self.xconn
             .check_pending1(pending)
             .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err.into()))))?;
-        *grabbed_lock = false;
+        *grabbed_lock = CursorGrabMode::None;
+        self.shared_state.lock().cursor_lock = None;
 
         // we keep the lock until we are done
         let pending = self.xconn.send_client_msg(
@@ -1411,8 +1515,37 @@ impl UnownedWindow {
             .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err.into()))))
     }
 
+    pub fn start_drag(
+        &self,
+        data: DragData,
+        allowed_actions: &[DndAction],
+        icon: Option<CursorIcon>,
+    ) -> Result<(), ExternalError> {
+        self.drag_sender
+            .send(DragRequest {
+                window: self.xwindow,
+                data: data.offers,
+                actions: allowed_actions.to_vec(),
+                icon,
+                scale_factor: self.scale_factor(),
+            })
+            .map_err(|_| ExternalError::Os(os_error!(OsError::XMisc("The event loop is gone"))))
+    }
+
     #[inline]
-    pub fn set_ime_position(&self, _spot: Position) {}
+    pub fn set_ime_position(&self, spot: Position) {
+        let (x, y): (i32, i32) = spot.to_physical::<i32>(self.scale_factor()).into();
+        self.ime_sender
+            .send(ImeRequest::Position(self.xwindow, x as i16, y as i16))
+            .unwrap();
+    }
+
+    #[inline]
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        self.ime_sender
+            .send(ImeRequest::Allow(self.xwindow, allowed))
+            .unwrap();
+    }
 
     #[inline]
     pub fn reset_dead_keys(&self) {
@@ -1445,6 +1578,17 @@ impl UnownedWindow {
         self.redraw_sender.send(WindowId(self.xwindow)).unwrap();
     }
 
+    #[inline]
+    pub fn present_rgba(
+        &self,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        damage: &[DamageRect],
+    ) -> Result<(), ExternalError> {
+        self.presenter.lock().present(rgba, width, height, damage)
+    }
+
     #[inline]
     pub fn raw_window_handle(&self) -> XcbHandle {
         XcbHandle {
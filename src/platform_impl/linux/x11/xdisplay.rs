@@ -5,11 +5,11 @@ use parking_lot::Mutex;
 
 use crate::window::CursorIcon;
 
-use super::ffi;
+use super::{ffi, util};
 use crate::platform_impl::x11::xlib::Xlib;
 use crate::platform_impl::x11::MonitorHandle;
 use thiserror::Error;
-use xcb_dl::{Xcb, XcbRandr, XcbRender, XcbXfixes, XcbXinput, XcbXkb};
+use xcb_dl::{Xcb, XcbRandr, XcbRender, XcbShm, XcbXfixes, XcbXinput, XcbXkb};
 use xcb_dl_util::cursor::XcbCursorContext;
 use xcb_dl_util::error::{XcbError, XcbErrorParser};
 
@@ -35,6 +35,8 @@ pub struct XConnection {
 
     pub xinput: Box<XcbXinput>,
     pub xinput_extension: u8,
+    pub xinput_first_event: u8,
+    pub xinput_version: (u16, u16),
 
     pub render: Box<XcbRender>,
 
@@ -42,8 +44,11 @@ pub struct XConnection {
     pub randr_version: (u32, u32),
     pub randr_first_event: u8,
 
+    pub shm: Option<Box<XcbShm>>,
+
     pub cursors: XcbCursorContext,
-    pub cursor_cache: Mutex<HashMap<Option<CursorIcon>, ffi::xcb_cursor_t>>,
+    pub cursor_cache: Mutex<HashMap<(Option<CursorIcon>, Option<u32>), ffi::xcb_cursor_t>>,
+    pub cursor_theme: Mutex<Option<util::CursorTheme>>,
 
     pub monitors: Mutex<Option<Vec<MonitorHandle>>>,
 
@@ -148,7 +153,7 @@ impl XConnection {
                         });
                     }
                 };
-                let version = (res.$server_major, res.$server_major);
+                let version = (res.$server_major, res.$server_minor);
                 if version < ($major, $minor) {
                     log::warn!(
                         "winit uses the `{}` extension in version {:?} but the X server only \
@@ -170,12 +175,12 @@ impl XConnection {
             0,
             ffi::XCB_XFIXES_NAME_STR
         );
-        enable_extension!(
+        let xinput_version = enable_extension!(
             xinput,
             xcb_input_xi_query_version,
             xcb_input_xi_query_version_reply,
             2,
-            2,
+            4,
             ffi::XCB_INPUT_NAME_STR
         );
         enable_extension!(
@@ -205,6 +210,12 @@ impl XConnection {
             ffi::XCB_RANDR_NAME_STR
         );
 
+        // MIT-SHM is optional. Without it, frames are presented with `PutImage`.
+        let shm = XcbShm::load_loose().ok().map(Box::new).filter(|shm| {
+            let data = xcb.xcb_get_extension_data(c, shm.xcb_shm_id());
+            !data.is_null() && (*data).present != 0
+        });
+
         let cursors = XcbCursorContext::new(&xcb, &render, c);
 
         let fd = xcb.xcb_get_file_descriptor(c);
@@ -264,11 +275,15 @@ impl XConnection {
             xfixes_first_event: (*xfixes_data).first_event,
             xinput,
             xinput_extension: (*xinput_data).major_opcode,
+            xinput_first_event: (*xinput_data).first_event,
+            xinput_version,
             render,
             randr,
             randr_version,
             randr_first_event: (*randr_data).first_event,
+            shm,
             cursor_cache: Default::default(),
+            cursor_theme: Default::default(),
             monitors: Default::default(),
             xlib,
         })
@@ -75,6 +75,7 @@ unsafe fn maybe_dispatch_device_event(event: id) {
                         axis: 0,
                         value: delta_x,
                     },
+                    time: None,
                 }));
             }
 
@@ -85,6 +86,7 @@ unsafe fn maybe_dispatch_device_event(event: id) {
                         axis: 1,
                         value: delta_y,
                     },
+                    time: None,
                 }));
             }
 
@@ -94,6 +96,7 @@ unsafe fn maybe_dispatch_device_event(event: id) {
                     event: DeviceEvent::MouseMotion {
                         delta: (delta_x, delta_y),
                     },
+                    time: None,
                 }));
             }
 
@@ -108,6 +111,7 @@ unsafe fn maybe_dispatch_device_event(event: id) {
                     button: event.buttonNumber() as u32,
                     state: ElementState::Pressed,
                 },
+                time: None,
             }));
 
             AppState::queue_events(events);
@@ -121,6 +125,7 @@ unsafe fn maybe_dispatch_device_event(event: id) {
                     button: event.buttonNumber() as u32,
                     state: ElementState::Released,
                 },
+                time: None,
             }));
 
             AppState::queue_events(events);
@@ -297,6 +297,7 @@ pub unsafe fn modifier_event(
                 modifiers: event_mods(ns_event),
             },
             is_synthetic: false,
+            time: None,
         })
     } else {
         None
@@ -20,7 +20,7 @@ use crate::{
     dpi::LogicalPosition,
     event::{
         DeviceEvent, ElementState, Event, KeyboardInput, ModifiersState, MouseButton,
-        MouseScrollDelta, TouchPhase, VirtualKeyCode, WindowEvent,
+        MouseScrollDelta, ScrollSource, TouchPhase, VirtualKeyCode, WindowEvent,
     },
     platform_impl::platform::{
         app_state::AppState,
@@ -669,6 +669,7 @@ extern "C" fn key_down(this: &Object, _sel: Sel, event: id) {
                     modifiers: event_mods(event),
                 },
                 is_synthetic: false,
+                time: None,
             },
         };
 
@@ -724,6 +725,7 @@ extern "C" fn key_up(this: &Object, _sel: Sel, event: id) {
                     modifiers: event_mods(event),
                 },
                 is_synthetic: false,
+                time: None,
             },
         };
 
@@ -843,6 +845,7 @@ extern "C" fn cancel_operation(this: &Object, _sel: Sel, _sender: id) {
                     modifiers: event_mods(event),
                 },
                 is_synthetic: false,
+                time: None,
             },
         };
 
@@ -864,6 +867,7 @@ fn mouse_click(this: &Object, event: id, button: MouseButton, button_state: Elem
                 device_id: DEVICE_ID,
                 state: button_state,
                 button,
+                time: None,
                 modifiers: event_mods(event),
             },
         };
@@ -937,6 +941,7 @@ fn mouse_motion(this: &Object, event: id) {
             event: WindowEvent::CursorMoved {
                 device_id: DEVICE_ID,
                 position: logical_position.to_physical(state.get_scale_factor()),
+                time: None,
                 modifiers: event_mods(event),
             },
         };
@@ -1006,14 +1011,17 @@ extern "C" fn scroll_wheel(this: &Object, _sel: Sel, event: id) {
         let state_ptr: *mut c_void = *this.get_ivar("winitState");
         let state = &mut *(state_ptr as *mut ViewState);
 
-        let delta = {
+        let (delta, source) = {
             // macOS horizontal sign convention is the inverse of winit.
             let (x, y) = (event.scrollingDeltaX() * -1.0, event.scrollingDeltaY());
             if event.hasPreciseScrollingDeltas() == YES {
                 let delta = LogicalPosition::new(x, y).to_physical(state.get_scale_factor());
-                MouseScrollDelta::PixelDelta(delta)
+                (MouseScrollDelta::PixelDelta(delta), ScrollSource::Finger)
             } else {
-                MouseScrollDelta::LineDelta(x as f32, y as f32)
+                (
+                    MouseScrollDelta::LineDelta(x as f32, y as f32),
+                    ScrollSource::Wheel,
+                )
             }
         };
         let phase = match event.phase() {
@@ -1027,6 +1035,7 @@ extern "C" fn scroll_wheel(this: &Object, _sel: Sel, event: id) {
         let device_event = Event::DeviceEvent {
             device_id: DEVICE_ID,
             event: DeviceEvent::MouseWheel { delta },
+            time: None,
         };
 
         let state_ptr: *mut c_void = *this.get_ivar("winitState");
@@ -1040,6 +1049,8 @@ extern "C" fn scroll_wheel(this: &Object, _sel: Sel, event: id) {
                 device_id: DEVICE_ID,
                 delta,
                 phase,
+                source,
+                time: None,
                 modifiers: event_mods(event),
             },
         };
@@ -14,6 +14,7 @@ use crate::{
         LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Position, Size, Size::Logical,
     },
     error::{ExternalError, NotSupportedError, OsError as RootOsError},
+    event::DndAction,
     icon::Icon,
     monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
     platform::macos::WindowExtMacOS,
@@ -29,7 +30,8 @@ use crate::{
         OsError,
     },
     window::{
-        CursorIcon, Fullscreen, UserAttentionType, WindowAttributes, WindowId as RootWindowId,
+        CursorGrabMode, CursorIcon, CustomCursor, DamageRect, DragData, Fullscreen,
+        UserAttentionType, WindowAttributes, WindowId as RootWindowId,
     },
 };
 use cocoa::{
@@ -456,6 +458,17 @@ impl UnownedWindow {
         AppState::queue_redraw(RootWindowId(self.id()));
     }
 
+    #[inline]
+    pub fn present_rgba(
+        &self,
+        _rgba: &[u8],
+        _width: u32,
+        _height: u32,
+        _damage: &[DamageRect],
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
         let frame_rect = unsafe { NSWindow::frame(*self.ns_window) };
         let position = LogicalPosition::new(
@@ -566,9 +579,20 @@ impl UnownedWindow {
         }
     }
 
+    pub fn set_custom_cursor(&self, _cursor: &CustomCursor) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     #[inline]
-    pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
-        // TODO: Do this for real https://stackoverflow.com/a/40922095/5435443
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+        let grab = match mode {
+            CursorGrabMode::None => false,
+            CursorGrabMode::Locked => true,
+            // TODO: Do this for real https://stackoverflow.com/a/40922095/5435443
+            CursorGrabMode::Confined => {
+                return Err(ExternalError::NotSupported(NotSupportedError::new()))
+            }
+        };
         CGDisplay::associate_mouse_and_mouse_cursor_position(!grab)
             .map_err(|status| ExternalError::Os(os_error!(OsError::CGError(status))))
     }
@@ -622,6 +646,16 @@ impl UnownedWindow {
         Ok(())
     }
 
+    #[inline]
+    pub fn start_drag(
+        &self,
+        _data: DragData,
+        _allowed_actions: &[DndAction],
+        _icon: Option<CursorIcon>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     pub(crate) fn is_zoomed(&self) -> bool {
         // because `isZoomed` doesn't work if the window's borderless,
         // we make it resizable temporalily.
@@ -970,6 +1004,11 @@ impl UnownedWindow {
         }
     }
 
+    #[inline]
+    pub fn set_ime_allowed(&self, _allowed: bool) {
+        // Input methods are always allowed.
+    }
+
     #[inline]
     pub fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
         let ns_request_type = request_type.map(|ty| match ty {
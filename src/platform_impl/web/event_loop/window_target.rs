@@ -1,7 +1,8 @@
 use super::{super::monitor, backend, device, proxy::Proxy, runner, window};
 use crate::dpi::{PhysicalSize, Size};
 use crate::event::{
-    DeviceEvent, DeviceId, ElementState, Event, KeyboardInput, TouchPhase, WindowEvent,
+    DeviceEvent, DeviceId, ElementState, Event, KeyboardInput, ScrollSource, TouchPhase,
+    WindowEvent,
 };
 use crate::event_loop::ControlFlow;
 use crate::monitor::MonitorHandle as RootMH;
@@ -81,6 +82,7 @@ impl<T> WindowTarget<T> {
                         modifiers,
                     },
                     is_synthetic: false,
+                    time: None,
                 },
             });
         });
@@ -99,6 +101,7 @@ impl<T> WindowTarget<T> {
                         modifiers,
                     },
                     is_synthetic: false,
+                    time: None,
                 },
             });
         });
@@ -138,6 +141,7 @@ impl<T> WindowTarget<T> {
                 event: WindowEvent::CursorMoved {
                     device_id: DeviceId(device::Id(pointer_id)),
                     position,
+                    time: None,
                     modifiers,
                 },
             });
@@ -146,6 +150,7 @@ impl<T> WindowTarget<T> {
                 event: DeviceEvent::MouseMotion {
                     delta: (delta.x, delta.y),
                 },
+                time: None,
             });
         });
 
@@ -160,6 +165,7 @@ impl<T> WindowTarget<T> {
                     event: WindowEvent::CursorMoved {
                         device_id: DeviceId(device::Id(pointer_id)),
                         position,
+                        time: None,
                         modifiers,
                     },
                 })
@@ -169,6 +175,7 @@ impl<T> WindowTarget<T> {
                         device_id: DeviceId(device::Id(pointer_id)),
                         state: ElementState::Pressed,
                         button,
+                        time: None,
                         modifiers,
                     },
                 })),
@@ -183,6 +190,7 @@ impl<T> WindowTarget<T> {
                     device_id: DeviceId(device::Id(pointer_id)),
                     state: ElementState::Released,
                     button,
+                    time: None,
                     modifiers,
                 },
             });
@@ -196,6 +204,8 @@ impl<T> WindowTarget<T> {
                     device_id: DeviceId(device::Id(pointer_id)),
                     delta,
                     phase: TouchPhase::Moved,
+                    source: ScrollSource::Wheel,
+                    time: None,
                     modifiers,
                 },
             });
@@ -254,4 +264,13 @@ impl<T> WindowTarget<T> {
             inner: monitor::Handle,
         })
     }
+
+    pub fn add_timer(
+        &self,
+        _timer: crate::event_loop::Timer,
+    ) -> Result<crate::event_loop::TimerId, crate::error::NotSupportedError> {
+        Err(crate::error::NotSupportedError::new())
+    }
+
+    pub fn cancel_timer(&self, _id: crate::event_loop::TimerId) {}
 }
@@ -45,6 +45,10 @@ impl Mode {
         32
     }
 
+    pub fn refresh_rate_millihertz(&self) -> u32 {
+        self.refresh_rate() as u32 * 1000
+    }
+
     pub fn monitor(&self) -> MonitorHandle {
         MonitorHandle { inner: Handle }
     }
@@ -1,10 +1,11 @@
 use crate::dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
 use crate::error::{ExternalError, NotSupportedError, OsError as RootOE};
-use crate::event;
+use crate::event::{self, DndAction};
 use crate::icon::Icon;
 use crate::monitor::MonitorHandle as RootMH;
 use crate::window::{
-    CursorIcon, Fullscreen, UserAttentionType, WindowAttributes, WindowId as RootWI,
+    CursorGrabMode, CursorIcon, CustomCursor, DamageRect, DragData, Fullscreen, UserAttentionType,
+    WindowAttributes, WindowId as RootWI,
 };
 
 use raw_window_handle::web::WebHandle;
@@ -93,6 +94,17 @@ impl Window {
         (self.register_redraw_request)();
     }
 
+    #[inline]
+    pub fn present_rgba(
+        &self,
+        _rgba: &[u8],
+        _width: u32,
+        _height: u32,
+        _damage: &[DamageRect],
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
         Ok(self
             .canvas
@@ -201,13 +213,18 @@ impl Window {
         backend::set_canvas_style_property(self.canvas.borrow().raw(), "cursor", text);
     }
 
+    #[inline]
+    pub fn set_custom_cursor(&self, _cursor: &CustomCursor) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     #[inline]
     pub fn set_cursor_position(&self, _position: Position) -> Result<(), ExternalError> {
         Err(ExternalError::NotSupported(NotSupportedError::new()))
     }
 
     #[inline]
-    pub fn set_cursor_grab(&self, _grab: bool) -> Result<(), ExternalError> {
+    pub fn set_cursor_grab(&self, _mode: CursorGrabMode) -> Result<(), ExternalError> {
         Err(ExternalError::NotSupported(NotSupportedError::new()))
     }
 
@@ -227,6 +244,16 @@ impl Window {
         Err(ExternalError::NotSupported(NotSupportedError::new()))
     }
 
+    #[inline]
+    pub fn start_drag(
+        &self,
+        _data: DragData,
+        _allowed_actions: &[DndAction],
+        _icon: Option<CursorIcon>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     #[inline]
     pub fn set_minimized(&self, _minimized: bool) {
         // Intentionally a no-op, as canvases cannot be 'minimized'
@@ -281,6 +308,11 @@ impl Window {
         // Currently a no-op as it does not seem there is good support for this on web
     }
 
+    #[inline]
+    pub fn set_ime_allowed(&self, _allowed: bool) {
+        // Currently a no-op as it does not seem there is good support for this on web
+    }
+
     #[inline]
     pub fn request_user_attention(&self, _request_type: Option<UserAttentionType>) {
         // Currently an intentional no-op
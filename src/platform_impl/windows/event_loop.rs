@@ -34,7 +34,7 @@ use winapi::{
 
 use crate::{
     dpi::{PhysicalPosition, PhysicalSize},
-    event::{DeviceEvent, Event, Force, RawKeyEvent, Touch, TouchPhase, WindowEvent},
+    event::{DeviceEvent, Event, Force, RawKeyEvent, ScrollSource, Touch, TouchPhase, WindowEvent},
     event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW},
     keyboard::{KeyCode, ModifiersState},
     monitor::MonitorHandle as RootMonitorHandle,
@@ -275,6 +275,15 @@ impl<T> EventLoopWindowTarget<T> {
         let monitor = monitor::primary_monitor();
         Some(RootMonitorHandle { inner: monitor })
     }
+
+    pub fn add_timer(
+        &self,
+        _timer: crate::event_loop::Timer,
+    ) -> Result<crate::event_loop::TimerId, crate::error::NotSupportedError> {
+        Err(crate::error::NotSupportedError::new())
+    }
+
+    pub fn cancel_timer(&self, _id: crate::event_loop::TimerId) {}
 }
 
 fn main_thread_id() -> DWORD {
@@ -873,6 +882,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     device_id: DEVICE_ID,
                     event: event.event,
                     is_synthetic: event.is_synthetic,
+                    time: None,
                 },
             });
         }
@@ -883,7 +893,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
         .unwrap_or_else(|| result = ProcResult::Value(-1));
 
     let ime_callback = || {
-        use crate::event::WindowEvent::ReceivedImeText;
+        use crate::event::{Ime, WindowEvent::Ime as ImeEvent};
         let is_ime_related = is_msg_ime_related(msg);
         if !is_ime_related {
             return;
@@ -897,7 +907,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
         if let Some(str) = text {
             subclass_input.send_event(Event::WindowEvent {
                 window_id: RootWindowId(WindowId(window)),
-                event: ReceivedImeText(str),
+                event: ImeEvent(Ime::Commit(str)),
             });
         }
     };
@@ -1146,6 +1156,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     event: CursorMoved {
                         device_id: DEVICE_ID,
                         position,
+                        time: None,
                         modifiers,
                     },
                 });
@@ -1188,6 +1199,8 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     device_id: DEVICE_ID,
                     delta: LineDelta(0.0, value),
                     phase: TouchPhase::Moved,
+                    source: ScrollSource::Wheel,
+                    time: None,
                     modifiers,
                 },
             });
@@ -1210,6 +1223,8 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     device_id: DEVICE_ID,
                     delta: LineDelta(value, 0.0),
                     phase: TouchPhase::Moved,
+                    source: ScrollSource::Wheel,
+                    time: None,
                     modifiers,
                 },
             });
@@ -1236,6 +1251,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     device_id: DEVICE_ID,
                     state: Pressed,
                     button: Left,
+                    time: None,
                     modifiers,
                 },
             });
@@ -1257,6 +1273,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     device_id: DEVICE_ID,
                     state: Released,
                     button: Left,
+                    time: None,
                     modifiers,
                 },
             });
@@ -1278,6 +1295,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     device_id: DEVICE_ID,
                     state: Pressed,
                     button: Right,
+                    time: None,
                     modifiers,
                 },
             });
@@ -1299,6 +1317,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     device_id: DEVICE_ID,
                     state: Released,
                     button: Right,
+                    time: None,
                     modifiers,
                 },
             });
@@ -1320,6 +1339,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     device_id: DEVICE_ID,
                     state: Pressed,
                     button: Middle,
+                    time: None,
                     modifiers,
                 },
             });
@@ -1341,6 +1361,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     device_id: DEVICE_ID,
                     state: Released,
                     button: Middle,
+                    time: None,
                     modifiers,
                 },
             });
@@ -1363,6 +1384,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     device_id: DEVICE_ID,
                     state: Pressed,
                     button: Other(xbutton),
+                    time: None,
                     modifiers,
                 },
             });
@@ -1385,6 +1407,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     device_id: DEVICE_ID,
                     state: Released,
                     button: Other(xbutton),
+                    time: None,
                     modifiers,
                 },
             });
@@ -1443,6 +1466,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                             force: None, // WM_TOUCH doesn't support pressure information
                             id: input.dwID as u64,
                             device_id: DEVICE_ID,
+                            time: None,
                         }),
                     });
                 }
@@ -1584,6 +1608,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                             force,
                             id: pointer_info.pointerId as u64,
                             device_id: DEVICE_ID,
+                            time: None,
                         }),
                     });
                 }
@@ -2009,6 +2034,7 @@ unsafe extern "system" fn thread_event_target_callback<T: 'static>(
             subclass_input.send_event(Event::DeviceEvent {
                 device_id: wrap_device_id(lparam as _),
                 event,
+                time: None,
             });
 
             0
@@ -2112,6 +2138,7 @@ unsafe fn handle_raw_input<T: 'static>(
                 subclass_input.send_event(Event::DeviceEvent {
                     device_id,
                     event: Motion { axis: 0, value: x },
+                    time: None,
                 });
             }
 
@@ -2119,6 +2146,7 @@ unsafe fn handle_raw_input<T: 'static>(
                 subclass_input.send_event(Event::DeviceEvent {
                     device_id,
                     event: Motion { axis: 1, value: y },
+                    time: None,
                 });
             }
 
@@ -2126,6 +2154,7 @@ unsafe fn handle_raw_input<T: 'static>(
                 subclass_input.send_event(Event::DeviceEvent {
                     device_id,
                     event: MouseMotion { delta: (x, y) },
+                    time: None,
                 });
             }
         }
@@ -2138,6 +2167,7 @@ unsafe fn handle_raw_input<T: 'static>(
                 event: MouseWheel {
                     delta: LineDelta(0.0, delta),
                 },
+                time: None,
             });
         }
 
@@ -2152,6 +2182,7 @@ unsafe fn handle_raw_input<T: 'static>(
                 subclass_input.send_event(Event::DeviceEvent {
                     device_id,
                     event: Button { button, state },
+                    time: None,
                 });
             }
         }
@@ -2266,6 +2297,7 @@ unsafe fn handle_raw_input<T: 'static>(
                 physical_key: code,
                 state,
             }),
+            time: None,
         });
     }
 }
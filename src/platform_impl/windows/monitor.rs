@@ -74,6 +74,10 @@ impl VideoMode {
         self.refresh_rate
     }
 
+    pub fn refresh_rate_millihertz(&self) -> u32 {
+        self.refresh_rate as u32 * 1000
+    }
+
     pub fn monitor(&self) -> RootMonitorHandle {
         RootMonitorHandle {
             inner: self.monitor.clone(),
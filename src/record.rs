@@ -0,0 +1,866 @@
+#![cfg(all(
+    feature = "record",
+    any(
+        target_os = "windows",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )
+))]
+
+//! Recording events and replaying them into an event handler.
+//!
+//! This module is only available with the `record` feature on Windows and Unix. A [`Recorder`] writes the events
+//! that an application receives to a file, one JSON object per line. A [`Replayer`] reads such
+//! a file and feeds the events to an event handler in the same order, which makes bug reports
+//! reproducible:
+//!
+//! ```no_run
+//! use winit::{event_loop::EventLoop, record::Recorder, window::Window};
+//!
+//! let event_loop = EventLoop::new();
+//! let _window = Window::new(&event_loop).unwrap();
+//! let mut recorder = Recorder::create("events.jsonl").unwrap();
+//! event_loop.run(move |event, _, control_flow| {
+//!     // Record the event before handling it.
+//!     recorder.record(&event).unwrap();
+//!     // ...
+//! });
+//! ```
+//!
+//! Window and device ids cannot be carried over from one session to the next. The recorder
+//! replaces them with small integers in the order in which they first appear. During a replay,
+//! windows and devices have to be associated with these integers with
+//! [`Replayer::map_window`] and [`Replayer::map_device`].
+//!
+//! User events, timers, file descriptor readiness, and monitor events refer to state of the
+//! recording session and are not recorded.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+
+use instant::Instant;
+use serde::{de::Error as _, Deserializer};
+
+use crate::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{
+        AxisId, ClipboardEvent, DeviceEvent, DeviceId, DndAction, DndEvent, ElementState, Event,
+        Force, Ime, KeyEvent, MouseButton, MouseScrollDelta, Pen, PenPhase, PenState, PenTool,
+        ScrollSource, StartCause, Timestamp, Touch, TouchPhase, WindowEvent,
+    },
+    event_loop::{ControlFlow, EventLoopWindowTarget},
+    keyboard::{Key, KeyCode, KeyLocation, ModifiersState},
+    platform::modifier_supplement::KeyEventExtModifierSupplement,
+    platform_impl,
+    window::{Theme, WindowId},
+};
+
+/// An event of a recording together with the time at which it was received.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    /// The time since the recording started.
+    pub time: Duration,
+    pub event: RecordedEventKind,
+}
+
+/// A recorded [`Event`].
+///
+/// Windows and devices are identified by the integers that the [`Recorder`] assigned to them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RecordedEventKind {
+    NewEvents(RecordedStartCause),
+    WindowEvent {
+        window: u64,
+        event: RecordedWindowEvent,
+    },
+    DeviceEvent {
+        device: u64,
+        event: DeviceEvent,
+        #[serde(default)]
+        time: Option<Timestamp>,
+    },
+    Suspended,
+    Resumed,
+    MainEventsCleared,
+    RedrawRequested(u64),
+    RedrawEventsCleared,
+    Clipboard(ClipboardEvent),
+    LoopDestroyed,
+}
+
+/// A recorded [`StartCause`].
+///
+/// The instants of the cause are not recorded. They are replaced by the time of the replay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecordedStartCause {
+    ResumeTimeReached,
+    WaitCancelled,
+    Poll,
+    Init,
+}
+
+/// A recorded [`WindowEvent`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RecordedWindowEvent {
+    Resized(PhysicalSize<u32>),
+    Moved(PhysicalPosition<i32>),
+    CloseRequested,
+    Destroyed,
+    DroppedFile(PathBuf),
+    HoveredFile(PathBuf),
+    HoveredFileCancelled,
+    DragAndDrop(DndEvent),
+    DragFinished {
+        action: Option<DndAction>,
+    },
+    Ime(Ime),
+    Focused(bool),
+    KeyboardInput {
+        device: u64,
+        event: RecordedKeyEvent,
+        is_synthetic: bool,
+        #[serde(default)]
+        time: Option<Timestamp>,
+    },
+    ModifiersChanged(ModifiersState),
+    CursorMoved {
+        device: u64,
+        position: PhysicalPosition<f64>,
+        #[serde(default)]
+        time: Option<Timestamp>,
+        modifiers: ModifiersState,
+    },
+    CursorEntered {
+        device: u64,
+    },
+    CursorLeft {
+        device: u64,
+    },
+    MouseWheel {
+        device: u64,
+        delta: MouseScrollDelta,
+        phase: TouchPhase,
+        source: ScrollSource,
+        #[serde(default)]
+        time: Option<Timestamp>,
+        modifiers: ModifiersState,
+    },
+    MouseInput {
+        device: u64,
+        state: ElementState,
+        button: MouseButton,
+        #[serde(default)]
+        time: Option<Timestamp>,
+        modifiers: ModifiersState,
+    },
+    TouchpadPressure {
+        device: u64,
+        pressure: f32,
+        stage: i64,
+    },
+    AxisMotion {
+        device: u64,
+        axis: AxisId,
+        value: f64,
+    },
+    Touch {
+        device: u64,
+        phase: TouchPhase,
+        location: PhysicalPosition<f64>,
+        force: Option<Force>,
+        id: u64,
+        #[serde(default)]
+        time: Option<Timestamp>,
+    },
+    Pen {
+        device: u64,
+        phase: PenPhase,
+        tool: PenTool,
+        location: PhysicalPosition<f64>,
+        state: PenState,
+    },
+    PinchGesture {
+        device: u64,
+        delta: f64,
+        phase: TouchPhase,
+    },
+    RotationGesture {
+        device: u64,
+        delta: f32,
+        phase: TouchPhase,
+    },
+    SwipeGesture {
+        device: u64,
+        delta: PhysicalPosition<f64>,
+        fingers: u32,
+        phase: TouchPhase,
+    },
+    /// `new_inner_size` is the size that the platform suggested, i.e., the size before the
+    /// event handler had a chance to change it.
+    ScaleFactorChanged {
+        scale_factor: f64,
+        new_inner_size: PhysicalSize<u32>,
+    },
+    ThemeChanged(Theme),
+}
+
+/// A recorded [`KeyEvent`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedKeyEvent {
+    pub physical_key: KeyCode,
+    #[serde(deserialize_with = "deserialize_key")]
+    pub logical_key: Key<'static>,
+    pub text: Option<String>,
+    pub location: KeyLocation,
+    pub state: ElementState,
+    pub repeat: bool,
+    #[serde(deserialize_with = "deserialize_key")]
+    pub key_without_modifiers: Key<'static>,
+    pub text_with_all_modifiers: Option<String>,
+}
+
+impl RecordedKeyEvent {
+    fn new(event: &KeyEvent) -> Self {
+        RecordedKeyEvent {
+            physical_key: event.physical_key,
+            logical_key: event.logical_key,
+            text: event.text.map(str::to_string),
+            location: event.location,
+            state: event.state,
+            repeat: event.repeat,
+            key_without_modifiers: event.key_without_modifiers(),
+            text_with_all_modifiers: event.text_with_all_modifiers().map(str::to_string),
+        }
+    }
+
+    fn to_event(&self) -> KeyEvent {
+        let text_with_all_modifiers = self.text_with_all_modifiers.as_deref().map(intern);
+        KeyEvent {
+            physical_key: self.physical_key,
+            logical_key: self.logical_key,
+            text: self.text.as_deref().map(intern),
+            location: self.location,
+            state: self.state,
+            repeat: self.repeat,
+            #[cfg(target_os = "windows")]
+            platform_specific: platform_impl::KeyEventExtra {
+                text_with_all_modifers: text_with_all_modifiers,
+                key_without_modifiers: self.key_without_modifiers,
+            },
+            #[cfg(not(target_os = "windows"))]
+            platform_specific: platform_impl::KeyEventExtra {
+                key_without_modifiers: self.key_without_modifiers,
+                text_with_all_modifiers,
+            },
+        }
+    }
+}
+
+/// Deserializes a key without borrowing from the input.
+fn deserialize_key<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Key<'static>, D::Error> {
+    let value: serde_json::Value = serde::Deserialize::deserialize(deserializer)?;
+    if let Some(text) = value.get("Character").and_then(|text| text.as_str()) {
+        return Ok(Key::Character(intern(text)));
+    }
+    // All other variants are free of strings.
+    serde::Deserialize::deserialize(value).map_err(D::Error::custom)
+}
+
+/// Returns a `'static` copy of `s`. Every distinct string is leaked only once.
+fn intern(s: &str) -> &'static str {
+    lazy_static! {
+        static ref STRINGS: Mutex<HashSet<&'static str>> = Mutex::new(HashSet::new());
+    }
+    let mut strings = STRINGS.lock().unwrap();
+    if let Some(&s) = strings.get(s) {
+        return s;
+    }
+    let s: &'static str = Box::leak(s.to_string().into_boxed_str());
+    strings.insert(s);
+    s
+}
+
+/// Writes the events of a session to a file.
+pub struct Recorder<W: Write> {
+    writer: W,
+    start: Instant,
+    windows: HashMap<WindowId, u64>,
+    devices: HashMap<DeviceId, u64>,
+}
+
+impl Recorder<BufWriter<File>> {
+    /// Creates a recorder that writes to the file at `path`, truncating the file if it exists.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Recorder::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    /// Creates a recorder that writes to `writer`. The recording starts now.
+    pub fn new(writer: W) -> Self {
+        Recorder {
+            writer,
+            start: Instant::now(),
+            windows: HashMap::new(),
+            devices: HashMap::new(),
+        }
+    }
+
+    /// Records an event.
+    ///
+    /// This should be called before the event is handled so that the recording contains the
+    /// size that the platform suggested in `ScaleFactorChanged` events. Events that cannot be
+    /// recorded are ignored.
+    pub fn record<T>(&mut self, event: &Event<'_, T>) -> io::Result<()> {
+        let event = match self.convert(event) {
+            Some(event) => event,
+            None => return Ok(()),
+        };
+        let event = RecordedEvent {
+            time: self.start.elapsed(),
+            event,
+        };
+        serde_json::to_writer(&mut self.writer, &event)?;
+        self.writer.write_all(b"\n")?;
+        // Flush the end of the session so that nothing is lost when the process exits.
+        if let RecordedEventKind::LoopDestroyed = event.event {
+            self.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the events that have been recorded.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Returns the writer of the recording.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn window(&mut self, window_id: WindowId) -> u64 {
+        let next = self.windows.len() as u64;
+        *self.windows.entry(window_id).or_insert(next)
+    }
+
+    fn device(&mut self, device_id: DeviceId) -> u64 {
+        let next = self.devices.len() as u64;
+        *self.devices.entry(device_id).or_insert(next)
+    }
+
+    fn convert<T>(&mut self, event: &Event<'_, T>) -> Option<RecordedEventKind> {
+        let event = match event {
+            Event::NewEvents(cause) => RecordedEventKind::NewEvents(match cause {
+                StartCause::ResumeTimeReached { .. } => RecordedStartCause::ResumeTimeReached,
+                StartCause::WaitCancelled { .. } => RecordedStartCause::WaitCancelled,
+                StartCause::Poll => RecordedStartCause::Poll,
+                StartCause::Init => RecordedStartCause::Init,
+            }),
+            Event::WindowEvent { window_id, event } => RecordedEventKind::WindowEvent {
+                window: self.window(*window_id),
+                event: self.convert_window_event(event),
+            },
+            Event::DeviceEvent {
+                device_id,
+                event,
+                time,
+            } => RecordedEventKind::DeviceEvent {
+                device: self.device(*device_id),
+                event: event.clone(),
+                time: *time,
+            },
+            Event::Suspended => RecordedEventKind::Suspended,
+            Event::Resumed => RecordedEventKind::Resumed,
+            Event::MainEventsCleared => RecordedEventKind::MainEventsCleared,
+            Event::RedrawRequested(window_id) => {
+                RecordedEventKind::RedrawRequested(self.window(*window_id))
+            }
+            Event::RedrawEventsCleared => RecordedEventKind::RedrawEventsCleared,
+            Event::Clipboard(event) => RecordedEventKind::Clipboard(event.clone()),
+            Event::LoopDestroyed => RecordedEventKind::LoopDestroyed,
+            Event::UserEvent(_)
+            | Event::FdReady { .. }
+            | Event::Timer(_)
+            | Event::MonitorAdded(_)
+            | Event::MonitorRemoved(_)
+            | Event::MonitorChanged(_) => return None,
+        };
+        Some(event)
+    }
+
+    fn convert_window_event(&mut self, event: &WindowEvent<'_>) -> RecordedWindowEvent {
+        use RecordedWindowEvent as R;
+        match event {
+            WindowEvent::Resized(size) => R::Resized(*size),
+            WindowEvent::Moved(position) => R::Moved(*position),
+            WindowEvent::CloseRequested => R::CloseRequested,
+            WindowEvent::Destroyed => R::Destroyed,
+            WindowEvent::DroppedFile(path) => R::DroppedFile(path.clone()),
+            WindowEvent::HoveredFile(path) => R::HoveredFile(path.clone()),
+            WindowEvent::HoveredFileCancelled => R::HoveredFileCancelled,
+            WindowEvent::DragAndDrop(event) => R::DragAndDrop(event.clone()),
+            WindowEvent::DragFinished { action } => R::DragFinished { action: *action },
+            WindowEvent::Ime(ime) => R::Ime(ime.clone()),
+            WindowEvent::Focused(focused) => R::Focused(*focused),
+            WindowEvent::KeyboardInput {
+                device_id,
+                event,
+                is_synthetic,
+                time,
+            } => R::KeyboardInput {
+                device: self.device(*device_id),
+                event: RecordedKeyEvent::new(event),
+                is_synthetic: *is_synthetic,
+                time: *time,
+            },
+            WindowEvent::ModifiersChanged(modifiers) => R::ModifiersChanged(*modifiers),
+            #[allow(deprecated)]
+            WindowEvent::CursorMoved {
+                device_id,
+                position,
+                time,
+                modifiers,
+            } => R::CursorMoved {
+                device: self.device(*device_id),
+                position: *position,
+                time: *time,
+                modifiers: *modifiers,
+            },
+            WindowEvent::CursorEntered { device_id } => R::CursorEntered {
+                device: self.device(*device_id),
+            },
+            WindowEvent::CursorLeft { device_id } => R::CursorLeft {
+                device: self.device(*device_id),
+            },
+            #[allow(deprecated)]
+            WindowEvent::MouseWheel {
+                device_id,
+                delta,
+                phase,
+                source,
+                time,
+                modifiers,
+            } => R::MouseWheel {
+                device: self.device(*device_id),
+                delta: *delta,
+                phase: *phase,
+                source: *source,
+                time: *time,
+                modifiers: *modifiers,
+            },
+            #[allow(deprecated)]
+            WindowEvent::MouseInput {
+                device_id,
+                state,
+                button,
+                time,
+                modifiers,
+            } => R::MouseInput {
+                device: self.device(*device_id),
+                state: *state,
+                button: *button,
+                time: *time,
+                modifiers: *modifiers,
+            },
+            WindowEvent::TouchpadPressure {
+                device_id,
+                pressure,
+                stage,
+            } => R::TouchpadPressure {
+                device: self.device(*device_id),
+                pressure: *pressure,
+                stage: *stage,
+            },
+            WindowEvent::AxisMotion {
+                device_id,
+                axis,
+                value,
+            } => R::AxisMotion {
+                device: self.device(*device_id),
+                axis: *axis,
+                value: *value,
+            },
+            WindowEvent::Touch(touch) => R::Touch {
+                device: self.device(touch.device_id),
+                phase: touch.phase,
+                location: touch.location,
+                force: touch.force,
+                id: touch.id,
+                time: touch.time,
+            },
+            WindowEvent::Pen(pen) => R::Pen {
+                device: self.device(pen.device_id),
+                phase: pen.phase,
+                tool: pen.tool,
+                location: pen.location,
+                state: pen.state,
+            },
+            WindowEvent::PinchGesture {
+                device_id,
+                delta,
+                phase,
+            } => R::PinchGesture {
+                device: self.device(*device_id),
+                delta: *delta,
+                phase: *phase,
+            },
+            WindowEvent::RotationGesture {
+                device_id,
+                delta,
+                phase,
+            } => R::RotationGesture {
+                device: self.device(*device_id),
+                delta: *delta,
+                phase: *phase,
+            },
+            WindowEvent::SwipeGesture {
+                device_id,
+                delta,
+                fingers,
+                phase,
+            } => R::SwipeGesture {
+                device: self.device(*device_id),
+                delta: *delta,
+                fingers: *fingers,
+                phase: *phase,
+            },
+            WindowEvent::ScaleFactorChanged {
+                scale_factor,
+                new_inner_size,
+            } => R::ScaleFactorChanged {
+                scale_factor: *scale_factor,
+                new_inner_size: **new_inner_size,
+            },
+            WindowEvent::ThemeChanged(theme) => R::ThemeChanged(*theme),
+        }
+    }
+}
+
+/// Feeds the events of a recording to an event handler.
+///
+/// The replay is deterministic: events are dispatched in the recorded order whenever
+/// [`replay`](Self::replay) or [`replay_next`](Self::replay_next) is called, regardless of the
+/// recorded times.
+pub struct Replayer {
+    events: VecDeque<RecordedEvent>,
+    windows: HashMap<u64, WindowId>,
+    devices: HashMap<u64, DeviceId>,
+}
+
+impl Replayer {
+    /// Reads the recording in the file at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Replayer::new(BufReader::new(File::open(path)?))
+    }
+
+    /// Reads a recording from `reader`.
+    pub fn new<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut events = VecDeque::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            events.push_back(serde_json::from_str(&line)?);
+        }
+        Ok(Replayer {
+            events,
+            windows: HashMap::new(),
+            devices: HashMap::new(),
+        })
+    }
+
+    /// Returns the events that have not been replayed yet.
+    pub fn events(&self) -> impl Iterator<Item = &RecordedEvent> {
+        self.events.iter()
+    }
+
+    /// Dispatches the events of the recorded window `recorded` with the id of `window`.
+    ///
+    /// The events of windows that have not been mapped are dispatched with
+    /// [`WindowId::dummy`].
+    pub fn map_window(&mut self, recorded: u64, window: WindowId) {
+        self.windows.insert(recorded, window);
+    }
+
+    /// Dispatches the events of the recorded device `recorded` with the id of `device`.
+    ///
+    /// The events of devices that have not been mapped are dispatched with
+    /// [`DeviceId::dummy`].
+    pub fn map_device(&mut self, recorded: u64, device: DeviceId) {
+        self.devices.insert(recorded, device);
+    }
+
+    /// Dispatches all remaining events to `callback` until `control_flow` is set to
+    /// [`ControlFlow::Exit`].
+    pub fn replay<T, F>(
+        &mut self,
+        target: &EventLoopWindowTarget<T>,
+        control_flow: &mut ControlFlow,
+        mut callback: F,
+    ) where
+        F: FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        while *control_flow != ControlFlow::Exit
+            && self.replay_next(target, control_flow, &mut callback)
+        {}
+    }
+
+    /// Dispatches the next event to `callback`. Returns `false` if the recording has ended.
+    pub fn replay_next<T, F>(
+        &mut self,
+        target: &EventLoopWindowTarget<T>,
+        control_flow: &mut ControlFlow,
+        mut callback: F,
+    ) -> bool
+    where
+        F: FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        let event = match self.events.pop_front() {
+            Some(event) => event.event,
+            None => return false,
+        };
+        let event = match event {
+            RecordedEventKind::NewEvents(cause) => {
+                let now = Instant::now();
+                Event::NewEvents(match cause {
+                    RecordedStartCause::ResumeTimeReached => StartCause::ResumeTimeReached {
+                        start: now,
+                        requested_resume: now,
+                    },
+                    RecordedStartCause::WaitCancelled => StartCause::WaitCancelled {
+                        start: now,
+                        requested_resume: None,
+                    },
+                    RecordedStartCause::Poll => StartCause::Poll,
+                    RecordedStartCause::Init => StartCause::Init,
+                })
+            }
+            RecordedEventKind::WindowEvent {
+                window,
+                event:
+                    RecordedWindowEvent::ScaleFactorChanged {
+                        scale_factor,
+                        new_inner_size,
+                    },
+            } => {
+                // The handler may change the size, so it gets a reference to a copy.
+                let mut new_inner_size = new_inner_size;
+                let event = Event::WindowEvent {
+                    window_id: self.window(window),
+                    event: WindowEvent::ScaleFactorChanged {
+                        scale_factor,
+                        new_inner_size: &mut new_inner_size,
+                    },
+                };
+                callback(event, target, control_flow);
+                return true;
+            }
+            RecordedEventKind::WindowEvent { window, event } => Event::WindowEvent {
+                window_id: self.window(window),
+                event: self.convert_window_event(event),
+            },
+            RecordedEventKind::DeviceEvent {
+                device,
+                event,
+                time,
+            } => Event::DeviceEvent {
+                device_id: self.device(device),
+                event,
+                time,
+            },
+            RecordedEventKind::Suspended => Event::Suspended,
+            RecordedEventKind::Resumed => Event::Resumed,
+            RecordedEventKind::MainEventsCleared => Event::MainEventsCleared,
+            RecordedEventKind::RedrawRequested(window) => {
+                Event::RedrawRequested(self.window(window))
+            }
+            RecordedEventKind::RedrawEventsCleared => Event::RedrawEventsCleared,
+            RecordedEventKind::Clipboard(event) => Event::Clipboard(event),
+            RecordedEventKind::LoopDestroyed => Event::LoopDestroyed,
+        };
+        callback(event, target, control_flow);
+        true
+    }
+
+    fn window(&self, window: u64) -> WindowId {
+        match self.windows.get(&window) {
+            Some(&window_id) => window_id,
+            None => unsafe { WindowId::dummy() },
+        }
+    }
+
+    fn device(&self, device: u64) -> DeviceId {
+        match self.devices.get(&device) {
+            Some(&device_id) => device_id,
+            None => unsafe { DeviceId::dummy() },
+        }
+    }
+
+    /// Converts all recorded window events except `ScaleFactorChanged`.
+    fn convert_window_event(&self, event: RecordedWindowEvent) -> WindowEvent<'static> {
+        use RecordedWindowEvent as R;
+        match event {
+            R::Resized(size) => WindowEvent::Resized(size),
+            R::Moved(position) => WindowEvent::Moved(position),
+            R::CloseRequested => WindowEvent::CloseRequested,
+            R::Destroyed => WindowEvent::Destroyed,
+            R::DroppedFile(path) => WindowEvent::DroppedFile(path),
+            R::HoveredFile(path) => WindowEvent::HoveredFile(path),
+            R::HoveredFileCancelled => WindowEvent::HoveredFileCancelled,
+            R::DragAndDrop(event) => WindowEvent::DragAndDrop(event),
+            R::DragFinished { action } => WindowEvent::DragFinished { action },
+            R::Ime(ime) => WindowEvent::Ime(ime),
+            R::Focused(focused) => WindowEvent::Focused(focused),
+            R::KeyboardInput {
+                device,
+                event,
+                is_synthetic,
+                time,
+            } => WindowEvent::KeyboardInput {
+                device_id: self.device(device),
+                event: event.to_event(),
+                is_synthetic,
+                time,
+            },
+            R::ModifiersChanged(modifiers) => WindowEvent::ModifiersChanged(modifiers),
+            #[allow(deprecated)]
+            R::CursorMoved {
+                device,
+                position,
+                time,
+                modifiers,
+            } => WindowEvent::CursorMoved {
+                device_id: self.device(device),
+                position,
+                time,
+                modifiers,
+            },
+            R::CursorEntered { device } => WindowEvent::CursorEntered {
+                device_id: self.device(device),
+            },
+            R::CursorLeft { device } => WindowEvent::CursorLeft {
+                device_id: self.device(device),
+            },
+            #[allow(deprecated)]
+            R::MouseWheel {
+                device,
+                delta,
+                phase,
+                source,
+                time,
+                modifiers,
+            } => WindowEvent::MouseWheel {
+                device_id: self.device(device),
+                delta,
+                phase,
+                source,
+                time,
+                modifiers,
+            },
+            #[allow(deprecated)]
+            R::MouseInput {
+                device,
+                state,
+                button,
+                time,
+                modifiers,
+            } => WindowEvent::MouseInput {
+                device_id: self.device(device),
+                state,
+                button,
+                time,
+                modifiers,
+            },
+            R::TouchpadPressure {
+                device,
+                pressure,
+                stage,
+            } => WindowEvent::TouchpadPressure {
+                device_id: self.device(device),
+                pressure,
+                stage,
+            },
+            R::AxisMotion {
+                device,
+                axis,
+                value,
+            } => WindowEvent::AxisMotion {
+                device_id: self.device(device),
+                axis,
+                value,
+            },
+            R::Touch {
+                device,
+                phase,
+                location,
+                force,
+                id,
+                time,
+            } => WindowEvent::Touch(Touch {
+                device_id: self.device(device),
+                phase,
+                location,
+                force,
+                id,
+                time,
+            }),
+            R::Pen {
+                device,
+                phase,
+                tool,
+                location,
+                state,
+            } => WindowEvent::Pen(Pen {
+                device_id: self.device(device),
+                phase,
+                tool,
+                location,
+                state,
+            }),
+            R::PinchGesture {
+                device,
+                delta,
+                phase,
+            } => WindowEvent::PinchGesture {
+                device_id: self.device(device),
+                delta,
+                phase,
+            },
+            R::RotationGesture {
+                device,
+                delta,
+                phase,
+            } => WindowEvent::RotationGesture {
+                device_id: self.device(device),
+                delta,
+                phase,
+            },
+            R::SwipeGesture {
+                device,
+                delta,
+                fingers,
+                phase,
+            } => WindowEvent::SwipeGesture {
+                device_id: self.device(device),
+                delta,
+                fingers,
+                phase,
+            },
+            R::ScaleFactorChanged { .. } => {
+                unreachable!("`ScaleFactorChanged` borrows its size from the replayer")
+            }
+            R::ThemeChanged(theme) => WindowEvent::ThemeChanged(theme),
+        }
+    }
+}
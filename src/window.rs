@@ -1,14 +1,16 @@
 //! The `Window` struct and associated types.
-use std::fmt;
+use std::{fmt, sync::Arc, time::Duration};
 
 use crate::{
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
     error::{ExternalError, NotSupportedError, OsError},
+    event::DndAction,
     event_loop::EventLoopWindowTarget,
     monitor::{MonitorHandle, VideoMode},
     platform_impl,
 };
 
+use crate::icon::RgbaIcon;
 pub use crate::icon::{BadIcon, Icon};
 
 /// Represents a window.
@@ -444,6 +446,48 @@ impl Window {
         self.window.request_redraw()
     }
 
+    /// Copies a frame of 32bpp RGBA pixels into the window.
+    ///
+    /// The frame is `width` pixels wide and `height` pixels high and is placed at the top-left
+    /// corner of the window. It should have the size of [`Window::inner_size`]. `damage` lists
+    /// the rectangles of the frame that have changed since the previous frame. Rectangles are
+    /// clipped to the frame. If `damage` is empty, the whole frame is copied. The alpha channel
+    /// is ignored.
+    ///
+    /// This allows applications that render on the CPU to display their frames without a
+    /// graphics API.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rgba.len()` is not `4 * width * height`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Uses the MIT-SHM extension if the X server supports it and `PutImage`
+    ///   otherwise. Returns once the X server has copied the frame.
+    /// - **Wayland:** The frame is copied into a `wl_shm` buffer and attached to the surface of
+    ///   the window after all requests of the window have been processed. If the compositor
+    ///   still uses both buffers of the window, only the latest frame is attached once one of
+    ///   them is released. The size of the frame determines the size of the window unless it
+    ///   is fullscreen or maximized.
+    /// - **Windows / macOS / iOS / Android / Web:** Always returns an
+    ///   [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn present_rgba(
+        &self,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        damage: &[DamageRect],
+    ) -> Result<(), ExternalError> {
+        assert_eq!(
+            rgba.len() as u64,
+            4 * width as u64 * height as u64,
+            "The length of the frame does not match its size"
+        );
+        self.window.present_rgba(rgba, width, height, damage)
+    }
+
     /// Reset the dead key state of the keyboard.
     ///
     /// This is useful when a dead key is bound to trigger an action. Then
@@ -678,7 +722,13 @@ impl Window {
     ///
     ///   The dock and the menu bar are always disabled in fullscreen mode.
     /// - **iOS:** Can only be called on the main thread.
-    /// - **Wayland:** Does not support exclusive fullscreen mode and will no-op a request.
+    /// - **Wayland:** The video mode of an output cannot be changed. `Fullscreen::Exclusive`
+    ///   makes the window fullscreen on the output of the video mode. If the compositor supports
+    ///   `wp_viewporter`, the window reports the size of the video mode as its inner size and the
+    ///   compositor scales its contents to the size of the output. This takes effect once the
+    ///   compositor has configured the window and is announced with `WindowEvent::Resized`.
+    ///   Otherwise, the window falls back to borderless fullscreen on that output and
+    ///   `fullscreen` returns `Borderless`.
     /// - **Windows:** Screen saver is disabled in fullscreen mode.
     /// - **Android:** Unsupported.
     #[inline]
@@ -692,7 +742,9 @@ impl Window {
     ///
     /// - **iOS:** Can only be called on the main thread.
     /// - **Android:** Will always return `None`.
-    /// - **Wayland:** Can return `Borderless(None)` when there are no monitors.
+    /// - **Wayland:** Can return `Borderless(None)` when there are no monitors. Returns
+    ///   `Exclusive` only while the contents of the window are scaled from the size of the video
+    ///   mode, and `Borderless` if exclusive fullscreen has fallen back to borderless fullscreen.
     #[inline]
     pub fn fullscreen(&self) -> Option<Fullscreen> {
         self.window.fullscreen()
@@ -747,6 +799,24 @@ impl Window {
         self.window.set_ime_position(position.into())
     }
 
+    /// Sets whether the window accepts input from an input method (IME).
+    ///
+    /// While input methods are allowed, [`WindowEvent::Ime`] events are emitted and key presses
+    /// that are consumed by the input method do not generate `KeyboardInput` events.
+    ///
+    /// The default is `false`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported. Input methods are always
+    ///   allowed on Windows and macOS.
+    ///
+    /// [`WindowEvent::Ime`]: crate::event::WindowEvent::Ime
+    #[inline]
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        self.window.set_ime_allowed(allowed)
+    }
+
     /// Requests user attention to the window, this has no effect if the application
     /// is already focused. How requesting for user attention manifests is platform dependent,
     /// see `UserAttentionType` for details.
@@ -777,6 +847,20 @@ impl Window {
         self.window.set_cursor_icon(cursor);
     }
 
+    /// Changes the cursor of the window to a custom image.
+    ///
+    /// The cursor can be changed back with [`Window::set_cursor_icon`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Returns [`ExternalError::NotSupported`] if an image is wider or higher than
+    ///   65535 pixels.
+    /// - **Wayland / Windows / macOS / iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn set_custom_cursor(&self, cursor: &CustomCursor) -> Result<(), ExternalError> {
+        self.window.set_custom_cursor(cursor)
+    }
+
     /// Changes the position of the cursor in window coordinates.
     ///
     /// ## Platform-specific
@@ -789,16 +873,17 @@ impl Window {
 
     /// Grabs the cursor, preventing it from leaving the window.
     ///
-    /// There's no guarantee that the cursor will be hidden. You should
-    /// hide it by yourself if you want so.
+    /// With [`CursorGrabMode::Confined`], there's no guarantee that the cursor will be hidden.
+    /// You should hide it by yourself if you want so. [`CursorGrabMode::None`] releases the grab.
     ///
     /// ## Platform-specific
     ///
-    /// - **macOS:** This locks the cursor in a fixed location, which looks visually awkward.
+    /// - **macOS:** Only [`CursorGrabMode::Locked`] is supported.
+    /// - **Windows:** Only [`CursorGrabMode::Confined`] is supported.
     /// - **iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
     #[inline]
-    pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
-        self.window.set_cursor_grab(grab)
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+        self.window.set_cursor_grab(mode)
     }
 
     /// Modifies the cursor's visibility.
@@ -833,6 +918,31 @@ impl Window {
     pub fn drag_window(&self) -> Result<(), ExternalError> {
         self.window.drag_window()
     }
+
+    /// Starts a drag-and-drop operation that offers `data` to other windows.
+    ///
+    /// The drag follows the cursor until the left mouse button is released, so this should be
+    /// called while the button is pressed. `allowed_actions` are the actions that the target of
+    /// the drop may perform with the data, in the order of preference. `icon` is the cursor that
+    /// is shown while the drop would be accepted. If it is `None`, a cursor matching the action
+    /// is shown.
+    ///
+    /// Once the operation has ended, a
+    /// [`WindowEvent::DragFinished`](crate::event::WindowEvent::DragFinished) event is emitted.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland / Windows / macOS / iOS / Android / Web:** Always returns an
+    ///   [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn start_drag(
+        &self,
+        data: DragData,
+        allowed_actions: &[DndAction],
+        icon: Option<CursorIcon>,
+    ) -> Result<(), ExternalError> {
+        self.window.start_drag(data, allowed_actions, icon)
+    }
 }
 
 /// Monitor info functions.
@@ -957,6 +1067,107 @@ impl Default for CursorIcon {
     }
 }
 
+/// How the cursor is grabbed by [`Window::set_cursor_grab`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CursorGrabMode {
+    /// The cursor is not grabbed.
+    None,
+    /// The cursor can move freely within the window but cannot leave it.
+    Confined,
+    /// The cursor is hidden and stays in place.
+    ///
+    /// Movements of the pointer are only reported as [`DeviceEvent::MouseMotion`] deltas.
+    ///
+    /// [`DeviceEvent::MouseMotion`]: crate::event::DeviceEvent::MouseMotion
+    Locked,
+}
+
+impl Default for CursorGrabMode {
+    fn default() -> Self {
+        CursorGrabMode::None
+    }
+}
+
+/// A mouse cursor created from RGBA data.
+///
+/// Cursors with more than one frame are animated. Cloning a `CustomCursor` is cheap.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomCursor {
+    pub(crate) frames: Arc<[CursorFrame]>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CursorFrame {
+    pub(crate) image: RgbaIcon,
+    pub(crate) hotspot: PhysicalPosition<u32>,
+    pub(crate) duration: Duration,
+}
+
+impl CustomCursor {
+    /// Creates a `CustomCursor` from 32bpp RGBA data.
+    ///
+    /// The length of `rgba` must be divisible by 4, and `width * height` must equal
+    /// `rgba.len() / 4`. `hotspot` is the pixel of the image that is placed at the position of
+    /// the pointer and must lie within the image. Otherwise, this will return a `BadIcon` error.
+    pub fn from_rgba(
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        hotspot: PhysicalPosition<u32>,
+    ) -> Result<Self, BadIcon> {
+        let image = RgbaIcon::from_rgba(rgba, width, height)?;
+        if hotspot.x >= width || hotspot.y >= height {
+            return Err(BadIcon::HotspotOutOfBounds {
+                width,
+                height,
+                hotspot_x: hotspot.x,
+                hotspot_y: hotspot.y,
+            });
+        }
+        let frame = CursorFrame {
+            image,
+            hotspot,
+            duration: Duration::from_secs(0),
+        };
+        Ok(CustomCursor {
+            frames: Arc::new([frame]),
+        })
+    }
+
+    /// Creates an animated cursor that shows each of the cursors for the given duration.
+    ///
+    /// The animation starts again after the last frame. If one of the cursors is animated itself,
+    /// its frames keep their own durations. Returns `BadIcon::NoFrames` if `frames` is empty.
+    pub fn from_animation(frames: Vec<(CustomCursor, Duration)>) -> Result<Self, BadIcon> {
+        if frames.is_empty() {
+            return Err(BadIcon::NoFrames);
+        }
+        let mut res = vec![];
+        for (cursor, duration) in frames {
+            match &*cursor.frames {
+                [frame] => res.push(CursorFrame {
+                    duration,
+                    ..frame.clone()
+                }),
+                frames => res.extend(frames.iter().cloned()),
+            }
+        }
+        Ok(CustomCursor { frames: res.into() })
+    }
+}
+
+/// A rectangle of a frame that has changed since the previous frame.
+///
+/// See [`Window::present_rgba`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DamageRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
 /// Fullscreen modes.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Fullscreen {
@@ -967,6 +1178,7 @@ pub enum Fullscreen {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Theme {
     Light,
     Dark,
@@ -992,3 +1204,30 @@ impl Default for UserAttentionType {
         UserAttentionType::Informational
     }
 }
+
+/// The data offered by a drag-and-drop operation started with [`Window::start_drag`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DragData {
+    pub(crate) offers: Vec<(String, Vec<u8>)>,
+}
+
+impl DragData {
+    /// Creates a `DragData` that offers no data.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Offers `data` under the given MIME type.
+    ///
+    /// Targets of the drop usually pick the first MIME type that they understand, so the most
+    /// specific type should be added first.
+    pub fn with_data(mut self, mime_type: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        self.offers.push((mime_type.into(), data.into()));
+        self
+    }
+
+    /// Returns the offered MIME types together with their data.
+    pub fn offers(&self) -> &[(String, Vec<u8>)] {
+        &self.offers
+    }
+}
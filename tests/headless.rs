@@ -0,0 +1,236 @@
+#![cfg(all(feature = "headless", target_os = "linux"))]
+
+use std::time::Duration;
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{DndAction, DndEvent, ElementState, Event, MouseButton, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    keyboard::{KeyCode, ModifiersState},
+    platform::{
+        headless::{EventLoopExtHeadless, EventLoopWindowTargetExtHeadless, WindowExtHeadless},
+        pump_events::EventLoopExtPumpEvents,
+        unix::EventLoopWindowTargetExtUnix,
+    },
+    window::{DamageRect, Window, WindowBuilder, WindowId},
+};
+
+/// Runs one iteration of the event loop and returns the window events.
+fn pump(event_loop: &mut EventLoop<()>) -> Vec<(WindowId, WindowEvent<'static>)> {
+    let mut events = vec![];
+    event_loop.pump_events(Some(Duration::from_millis(0)), |event, _, control_flow| {
+        *control_flow = ControlFlow::Wait;
+        if let Some(Event::WindowEvent { window_id, event }) = event.to_static() {
+            events.push((window_id, event));
+        }
+    });
+    events
+}
+
+fn window(event_loop: &EventLoop<()>, x: i32, width: u32) -> Window {
+    WindowBuilder::new()
+        .with_position(PhysicalPosition::new(x, 0))
+        .with_inner_size(PhysicalSize::new(width, 100))
+        .build(event_loop)
+        .unwrap()
+}
+
+#[test]
+fn key_repeat() {
+    let mut event_loop = EventLoop::new_headless();
+    let window = window(&event_loop, 0, 100);
+    let seat = event_loop.headless_seat().unwrap();
+    let keyboard = seat.add_keyboard();
+    seat.focus(&window);
+    pump(&mut event_loop);
+
+    let first = keyboard.press(KeyCode::KeyA);
+    let second = keyboard.press(KeyCode::KeyA);
+    drop(second);
+    let keys: Vec<_> = pump(&mut event_loop)
+        .into_iter()
+        .filter_map(|(_, event)| match event {
+            WindowEvent::KeyboardInput { event, .. } => Some((event.state, event.repeat)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        keys,
+        [
+            (ElementState::Pressed, false),
+            (ElementState::Pressed, true),
+            (ElementState::Released, false),
+        ]
+    );
+    drop(first);
+}
+
+#[test]
+fn modifiers() {
+    let mut event_loop = EventLoop::new_headless();
+    let window = window(&event_loop, 0, 100);
+    let seat = event_loop.headless_seat().unwrap();
+    let keyboard = seat.add_keyboard();
+    seat.focus(&window);
+    assert!(pump(&mut event_loop).contains(&(
+        window.id(),
+        WindowEvent::ModifiersChanged(ModifiersState::empty())
+    )));
+
+    let shift = keyboard.press(KeyCode::ShiftLeft);
+    let a = keyboard.press(KeyCode::KeyA);
+    drop(a);
+    let events = pump(&mut event_loop);
+    assert!(events.contains(&(
+        window.id(),
+        WindowEvent::ModifiersChanged(ModifiersState::SHIFT)
+    )));
+    let text = events.iter().find_map(|(_, event)| match event {
+        WindowEvent::KeyboardInput { event, .. } if event.physical_key == KeyCode::KeyA => {
+            Some(event.text)
+        }
+        _ => None,
+    });
+    assert_eq!(text, Some(Some("A")));
+
+    drop(shift);
+    let events = pump(&mut event_loop);
+    assert!(events.contains(&(
+        window.id(),
+        WindowEvent::ModifiersChanged(ModifiersState::empty())
+    )));
+}
+
+#[test]
+fn pointer_grab() {
+    let mut event_loop = EventLoop::new_headless();
+    let left = window(&event_loop, 0, 100);
+    let right = window(&event_loop, 200, 100);
+    let seat = event_loop.headless_seat().unwrap();
+    let mouse = seat.add_mouse();
+    pump(&mut event_loop);
+
+    seat.set_cursor_position(50, 50);
+    let events = pump(&mut event_loop);
+    assert!(matches!(events[0], (id, WindowEvent::CursorEntered { .. }) if id == left.id()));
+
+    // The window in which a button is pressed receives the events until it is released.
+    let button = mouse.press(MouseButton::Left);
+    mouse.move_(200, 0);
+    let events = pump(&mut event_loop);
+    assert!(events.iter().all(|(id, _)| *id == left.id()));
+    assert!(matches!(
+        events[0].1,
+        WindowEvent::MouseInput {
+            state: ElementState::Pressed,
+            button: MouseButton::Left,
+            ..
+        }
+    ));
+    assert!(events.iter().any(|(_, event)| matches!(
+        event,
+        WindowEvent::CursorMoved { position, .. } if *position == PhysicalPosition::new(250.0, 50.0)
+    )));
+
+    // Releasing the button moves the cursor to the window under it.
+    drop(button);
+    let events = pump(&mut event_loop);
+    assert!(matches!(
+        events[0],
+        (id, WindowEvent::MouseInput { state: ElementState::Released, .. }) if id == left.id()
+    ));
+    assert!(matches!(events[1], (id, WindowEvent::CursorLeft { .. }) if id == left.id()));
+    assert!(matches!(events[2], (id, WindowEvent::CursorEntered { .. }) if id == right.id()));
+    assert!(matches!(
+        events[3],
+        (id, WindowEvent::CursorMoved { position, .. })
+            if id == right.id() && position == PhysicalPosition::new(50.0, 50.0)
+    ));
+}
+
+#[test]
+fn drag_and_drop() {
+    let mut event_loop = EventLoop::new_headless();
+    let window = window(&event_loop, 0, 100);
+    let seat = event_loop.headless_seat().unwrap();
+    pump(&mut event_loop);
+
+    let drag = seat.start_drag(&[("text/plain", b"winit")], DndAction::Copy);
+    drag.drag_to(10, 20);
+    assert_eq!(
+        pump(&mut event_loop),
+        [
+            (
+                window.id(),
+                WindowEvent::DragAndDrop(DndEvent::Enter {
+                    mime_types: vec!["text/plain".to_string()],
+                }),
+            ),
+            (
+                window.id(),
+                WindowEvent::DragAndDrop(DndEvent::Motion {
+                    position: PhysicalPosition::new(10.0, 20.0),
+                    action: DndAction::Copy,
+                }),
+            ),
+        ]
+    );
+
+    event_loop.set_dnd_action(Some(DndAction::Copy));
+    event_loop.request_dnd_data("text/plain");
+    assert_eq!(drag.do_drop(), Some(DndAction::Copy));
+    assert_eq!(
+        pump(&mut event_loop),
+        [
+            (
+                window.id(),
+                WindowEvent::DragAndDrop(DndEvent::Data {
+                    mime_type: "text/plain".to_string(),
+                    data: Some(b"winit".to_vec()),
+                }),
+            ),
+            (window.id(), WindowEvent::DragAndDrop(DndEvent::Drop)),
+        ]
+    );
+
+    // A drop that has not been accepted leaves the window.
+    let drag = seat.start_drag(&[("text/plain", b"winit")], DndAction::Move);
+    drag.drag_to(10, 20);
+    pump(&mut event_loop);
+    assert_eq!(drag.do_drop(), None);
+    assert_eq!(
+        pump(&mut event_loop),
+        [(window.id(), WindowEvent::DragAndDrop(DndEvent::Leave))]
+    );
+}
+
+#[test]
+fn present_rgba() {
+    let event_loop = EventLoop::<()>::new_headless();
+    let window = window(&event_loop, 0, 2);
+    let headless = window.headless_window().unwrap();
+    assert_eq!(headless.pixel(0, 0), None);
+
+    let full = DamageRect {
+        x: 0,
+        y: 0,
+        width: 2,
+        height: 1,
+    };
+    let frame = [255, 0, 0, 255, 0, 255, 0, 255];
+    window.present_rgba(&frame, 2, 1, &[full]).unwrap();
+    assert_eq!(headless.pixel(0, 0), Some((255, 0, 0)));
+    assert_eq!(headless.pixel(1, 0), Some((0, 255, 0)));
+    assert_eq!(headless.pixel(2, 0), None);
+
+    // Only the damaged pixels are updated.
+    let damage = DamageRect {
+        x: 1,
+        y: 0,
+        width: 1,
+        height: 1,
+    };
+    let frame = [0, 0, 255, 255, 0, 0, 255, 255];
+    window.present_rgba(&frame, 2, 1, &[damage]).unwrap();
+    assert_eq!(headless.pixel(0, 0), Some((255, 0, 0)));
+    assert_eq!(headless.pixel(1, 0), Some((0, 0, 255)));
+}
@@ -0,0 +1,46 @@
+#![cfg(all(feature = "headless", target_os = "linux"))]
+
+use std::time::Duration;
+use winit::{
+    event::{Event, StartCause},
+    event_loop::{ControlFlow, EventLoop},
+    platform::{
+        headless::EventLoopExtHeadless,
+        pump_events::{EventLoopExtPumpEvents, PumpStatus},
+    },
+};
+
+/// Runs `pump_events` with a zero timeout, setting `control_flow` to `new_control_flow`, and
+/// returns the status and the dispatched events.
+fn pump(
+    event_loop: &mut EventLoop<()>,
+    new_control_flow: ControlFlow,
+) -> (PumpStatus, Vec<Event<'static, ()>>) {
+    let mut events = vec![];
+    let status =
+        event_loop.pump_events(Some(Duration::from_millis(0)), |event, _, control_flow| {
+            *control_flow = new_control_flow;
+            events.extend(event.to_static());
+        });
+    (status, events)
+}
+
+#[test]
+fn pump_after_exit() {
+    let mut event_loop = EventLoop::new_headless();
+
+    let (status, events) = pump(&mut event_loop, ControlFlow::Wait);
+    assert_eq!(status, PumpStatus::Continue);
+    assert!(matches!(events[0], Event::NewEvents(StartCause::Init)));
+
+    let (status, events) = pump(&mut event_loop, ControlFlow::Exit);
+    assert_eq!(status, PumpStatus::Exit);
+    assert!(matches!(events.last(), Some(Event::LoopDestroyed)));
+
+    // The event loop stays exited instead of starting over.
+    for _ in 0..2 {
+        let (status, events) = pump(&mut event_loop, ControlFlow::Wait);
+        assert_eq!(status, PumpStatus::Exit);
+        assert!(events.is_empty());
+    }
+}
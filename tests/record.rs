@@ -0,0 +1,115 @@
+#![cfg(all(feature = "record", feature = "headless", target_os = "linux"))]
+
+use std::time::Duration;
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{DeviceEvent, DeviceId, Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    keyboard::KeyCode,
+    platform::{
+        headless::{EventLoopExtHeadless, EventLoopWindowTargetExtHeadless},
+        pump_events::EventLoopExtPumpEvents,
+    },
+    record::{Recorder, Replayer},
+    window::WindowBuilder,
+};
+
+/// Returns the device of an event that the recorder assigns a number to.
+fn device_id(event: &Event<'_, ()>) -> Option<DeviceId> {
+    match event {
+        Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { device_id, .. },
+            ..
+        } => Some(*device_id),
+        Event::DeviceEvent { device_id, .. } => Some(*device_id),
+        _ => None,
+    }
+}
+
+#[test]
+fn round_trip() {
+    let mut event_loop = EventLoop::new_headless();
+    let window = WindowBuilder::new()
+        .with_position(PhysicalPosition::new(0, 0))
+        .with_inner_size(PhysicalSize::new(100, 100))
+        .build(&event_loop)
+        .unwrap();
+    let seat = event_loop.headless_seat().unwrap();
+    let keyboard = seat.add_keyboard();
+    seat.focus(&window);
+    drop(keyboard.press(KeyCode::KeyA));
+
+    let mut recorder = Recorder::new(vec![]);
+    let mut recorded = vec![];
+    let mut devices = vec![];
+    event_loop.pump_events(Some(Duration::from_millis(0)), |event, _, control_flow| {
+        *control_flow = ControlFlow::Wait;
+        recorder.record(&event).unwrap();
+        if let Some(device_id) = device_id(&event) {
+            if !devices.contains(&device_id) {
+                devices.push(device_id);
+            }
+        }
+        match event.to_static() {
+            Some(event @ Event::WindowEvent { .. }) | Some(event @ Event::DeviceEvent { .. }) => {
+                recorded.push(event)
+            }
+            _ => {}
+        }
+    });
+    let mut new_inner_size = PhysicalSize::new(200, 200);
+    recorder
+        .record::<()>(&Event::WindowEvent {
+            window_id: window.id(),
+            event: WindowEvent::ScaleFactorChanged {
+                scale_factor: 2.0,
+                new_inner_size: &mut new_inner_size,
+            },
+        })
+        .unwrap();
+
+    assert!(recorded.iter().any(|event| matches!(
+        event,
+        Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { .. },
+            ..
+        }
+    )));
+    assert!(recorded.iter().any(|event| matches!(
+        event,
+        Event::DeviceEvent {
+            event: DeviceEvent::Key(..),
+            ..
+        }
+    )));
+
+    let recording = recorder.into_inner();
+    let mut replayer = Replayer::new(&recording[..]).unwrap();
+    replayer.map_window(0, window.id());
+    for (recorded, &device_id) in devices.iter().enumerate() {
+        replayer.map_device(recorded as u64, device_id);
+    }
+
+    let mut replayed = vec![];
+    let mut scale_factor = None;
+    let mut control_flow = ControlFlow::Wait;
+    replayer.replay(&event_loop, &mut control_flow, |event, _, _| match event {
+        Event::WindowEvent {
+            window_id,
+            event:
+                WindowEvent::ScaleFactorChanged {
+                    scale_factor: factor,
+                    new_inner_size,
+                },
+        } => {
+            assert_eq!(window_id, window.id());
+            scale_factor = Some((factor, *new_inner_size));
+        }
+        Event::WindowEvent { .. } | Event::DeviceEvent { .. } => {
+            replayed.push(event.to_static().unwrap())
+        }
+        _ => {}
+    });
+    assert_eq!(replayed, recorded);
+    assert_eq!(scale_factor, Some((2.0, PhysicalSize::new(200, 200))));
+}
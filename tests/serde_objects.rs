@@ -3,7 +3,7 @@
 use serde::{Deserialize, Serialize};
 use winit::{
     dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize},
-    event::{ElementState, MouseButton, MouseScrollDelta, TouchPhase},
+    event::{ClipboardKind, DndAction, ElementState, MouseButton, MouseScrollDelta, TouchPhase},
     keyboard::{Key, KeyCode, KeyLocation, ModifiersState},
     window::CursorIcon,
 };
@@ -26,6 +26,8 @@ fn events_serde() {
     needs_serde::<KeyCode>();
     needs_serde::<KeyLocation>();
     needs_serde::<ModifiersState>();
+    needs_serde::<ClipboardKind>();
+    needs_serde::<DndAction>();
 }
 
 #[test]
@@ -0,0 +1,116 @@
+#![cfg(all(feature = "headless", feature = "stream", target_os = "linux"))]
+
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::time::Duration;
+
+use futures_core::Stream;
+use futures_sink::Sink;
+use winit::{
+    event::Event,
+    event_loop::EventLoop,
+    platform::{
+        headless::EventLoopExtHeadless,
+        stream::{EventLoopExtStream, EventStream},
+    },
+};
+
+/// A waker that records whether it has been woken.
+#[derive(Default)]
+struct Flag {
+    woken: Mutex<bool>,
+    cond: Condvar,
+}
+
+impl Wake for Flag {
+    fn wake(self: Arc<Self>) {
+        *self.woken.lock().unwrap() = true;
+        self.cond.notify_all();
+    }
+}
+
+impl Flag {
+    /// Waits until the flag has been woken and resets it.
+    fn wait(&self, timeout: Duration) -> bool {
+        let woken = self.woken.lock().unwrap();
+        let (mut woken, _) = self
+            .cond
+            .wait_timeout_while(woken, timeout, |woken| !*woken)
+            .unwrap();
+        std::mem::replace(&mut *woken, false)
+    }
+}
+
+/// Polls the stream until it is pending or has ended and returns the yielded events.
+fn drain(stream: &mut EventStream<u32>, waker: &Waker) -> (Vec<Event<'static, u32>>, bool) {
+    let mut cx = Context::from_waker(waker);
+    let mut events = vec![];
+    loop {
+        match Pin::new(&mut *stream).poll_next(&mut cx) {
+            Poll::Ready(Some(event)) => events.push(event),
+            Poll::Ready(None) => return (events, true),
+            Poll::Pending => return (events, false),
+        }
+    }
+}
+
+#[test]
+fn sink_wakes_stream() {
+    let mut stream = EventLoop::<u32>::new_headless().into_stream();
+    let flag = Arc::new(Flag::default());
+    let waker = Waker::from(flag.clone());
+
+    let (events, ended) = drain(&mut stream, &waker);
+    assert!(!ended);
+    assert!(matches!(events.first(), Some(Event::NewEvents(_))));
+
+    let mut proxy = stream.create_proxy();
+    let mut cx = Context::from_waker(&waker);
+    assert!(matches!(
+        Pin::new(&mut proxy).poll_ready(&mut cx),
+        Poll::Ready(Ok(()))
+    ));
+    Pin::new(&mut proxy).start_send(1).unwrap();
+    assert!(flag.wait(Duration::from_secs(5)));
+
+    let (events, ended) = drain(&mut stream, &waker);
+    assert!(!ended);
+    assert!(events.iter().any(|e| matches!(e, Event::UserEvent(1))));
+}
+
+#[test]
+fn stream_ends_after_exit() {
+    let mut stream = EventLoop::<u32>::new_headless().into_stream();
+    let waker = Waker::from(Arc::new(Flag::default()));
+
+    let (_, ended) = drain(&mut stream, &waker);
+    assert!(!ended);
+
+    stream.exit();
+    let (events, ended) = drain(&mut stream, &waker);
+    assert!(ended);
+    assert!(matches!(events.last(), Some(Event::LoopDestroyed)));
+
+    // The stream stays ended.
+    let (events, ended) = drain(&mut stream, &waker);
+    assert!(ended);
+    assert!(events.is_empty());
+}
+
+#[test]
+fn waiter_joined_on_drop() {
+    let mut stream = EventLoop::<u32>::new_headless().into_stream();
+    let flag = Arc::new(Flag::default());
+    let waker = Waker::from(flag.clone());
+
+    // The background thread holds on to the waker while it waits.
+    let (_, ended) = drain(&mut stream, &waker);
+    assert!(!ended);
+    drop(waker);
+    assert!(Arc::strong_count(&flag) > 1);
+
+    // The waker is released once the thread has exited.
+    drop(stream);
+    assert_eq!(Arc::strong_count(&flag), 1);
+}